@@ -0,0 +1,186 @@
+//! Generate the table of supported instruction forms from the instruction implementations in
+//! `src/insn`, see `insn::FORMS`, and the dynamic dispatch of the forms, see `Asm::emit_insn`.
+
+use std::fs;
+use std::path::Path;
+
+/// Split `list` at the commas outside of parentheses.
+fn split_top(list: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let (mut depth, mut start) = (0, 0);
+    for (i, c) in list.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(&list[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&list[start..]);
+    parts.into_iter().filter(|p| !p.trim().is_empty()).collect()
+}
+
+/// Get the index of the parenthesis closing the already opened one in `list`.
+fn close(list: &str) -> usize {
+    let mut depth = 0;
+    for (i, c) in list.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return i,
+            ')' => depth -= 1,
+            _ => {}
+        }
+    }
+    panic!("Unbalanced parentheses in {:?}", list);
+}
+
+/// Parse the parameter list `params` of an instruction method without the `&mut self`, eg
+/// `, cond: Cond, op1: &mut Label`, into the operand types and the call arguments with the
+/// operands bound to `op1..opN`.
+///
+/// Tuple parameters are flattened into the operands, eg `(Reg64, Reg64)`, and passed as tuple.
+fn params(params: &str) -> (Vec<String>, String) {
+    let mut operands = Vec::new();
+    let mut args = Vec::new();
+    for param in split_top(params) {
+        // UNWRAP: Parameters have the form `pattern: Type`.
+        let (_, ty) = param.split_once(':').unwrap();
+        let ty = ty.trim();
+        let tys = match ty.strip_prefix('(') {
+            Some(tuple) => split_top(tuple.strip_suffix(')').unwrap_or(tuple)),
+            None => vec![ty],
+        };
+        let mut arg = Vec::new();
+        for ty in tys {
+            let ty = ty
+                .trim()
+                .trim_start_matches("&mut ")
+                .trim_start_matches('&');
+            operands.push(ty.to_string());
+            arg.push(match ty {
+                // The `&mut &mut Label` binding is coerced to `&mut Label`.
+                "Label" => format!("op{}", operands.len()),
+                _ => format!("*op{}", operands.len()),
+            });
+        }
+        args.push(if ty.starts_with('(') {
+            format!("({})", arg.join(", "))
+        } else {
+            arg.join(", ")
+        });
+    }
+    (operands, args.join(", "))
+}
+
+/// Instruction form implemented in `src/insn`.
+struct Form {
+    /// Instruction mnemonic, eg `mov`.
+    mnemonic: String,
+    /// Operand type names, eg `["Reg64", "Imm64"]`.
+    operands: Vec<String>,
+    /// Call of the instruction with the operands bound to `op1..opN`, see `Asm::emit_insn`.
+    call: String,
+}
+
+/// Collect all instruction forms implemented in `src`.
+fn forms(src: &str) -> Vec<Form> {
+    let mut forms = Vec::new();
+    // Trait of the current implementation, eg `Mov<Reg64, Imm64>`.
+    let mut imp = None;
+
+    for line in src.lines().map(str::trim) {
+        if let Some(line) = line.strip_prefix("impl ") {
+            // Inherent implementation `impl Asm {` or trait implementation, eg
+            // `impl Mov<Reg64, Imm64> for Asm {`.
+            imp = line.strip_suffix(" for Asm {").map(String::from);
+            continue;
+        }
+
+        // Instruction method, eg `fn mov(&mut self, op1: Reg64, op2: Imm64) {`.
+        let fun = match &imp {
+            Some(_) => line.strip_prefix("fn "),
+            None => line.strip_prefix("pub fn "),
+        };
+        let Some((name, rest)) = fun.and_then(|fun| fun.split_once("(&mut self")) else {
+            continue;
+        };
+        // Generic helpers, eg `lock_xadd<M, T>`, are no instruction forms.
+        if name.contains('<') {
+            continue;
+        }
+        let (operands, args) = params(&rest[..close(rest)]);
+        let call = match &imp {
+            Some(tr) => format!("<Asm as crate::insn::{}>::{}(self, {})", tr, name, args),
+            None => format!("self.{}({})", name, args),
+        };
+        forms.push(Form {
+            mnemonic: name.to_string(),
+            operands,
+            call,
+        });
+    }
+
+    forms
+}
+
+/// Generate the table of all forms, see `insn::FORMS`.
+fn gen_forms(all: &[Form]) -> String {
+    let mut out = String::from("&[\n");
+    for form in all {
+        let ops = form
+            .operands
+            .iter()
+            .map(|op| format!("{:?}", op))
+            .collect::<Vec<_>>()
+            .join(", ");
+        out += &format!(
+            "    Form {{ mnemonic: {:?}, operands: &[{}] }},\n",
+            form.mnemonic, ops
+        );
+    }
+    out += "]\n";
+    out
+}
+
+/// Generate the match of `(mnemonic, operands)` emitting all forms, see `Asm::emit_insn`.
+fn gen_emit(all: &[Form]) -> String {
+    let mut out = String::from("match (mnemonic, operands) {\n");
+    for form in all {
+        let pats = form
+            .operands
+            .iter()
+            .enumerate()
+            .map(|(i, op)| format!("Operand::{}(op{})", op, i + 1))
+            .collect::<Vec<_>>()
+            .join(", ");
+        out += &format!("    ({:?}, [{}]) => {},\n", form.mnemonic, pats, form.call);
+    }
+    out += "    (mnemonic, operands) => return Err(InvalidInsn::new(mnemonic, operands)),\n}\n";
+    out
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed=src/insn");
+
+    let mut files: Vec<_> = fs::read_dir("src/insn")
+        .expect("Failed to read src/insn")
+        .map(|entry| entry.expect("Failed to read src/insn entry").path())
+        .collect();
+    files.sort();
+
+    let mut all = Vec::new();
+    for file in files {
+        println!("cargo:rerun-if-changed={}", file.display());
+        let src = fs::read_to_string(&file).expect("Failed to read instruction source");
+        all.extend(forms(&src));
+    }
+    all.sort_by(|a, b| (&a.mnemonic, &a.operands).cmp(&(&b.mnemonic, &b.operands)));
+    all.dedup_by(|a, b| (&a.mnemonic, &a.operands) == (&b.mnemonic, &b.operands));
+
+    let out = std::env::var("OUT_DIR").expect("OUT_DIR not set");
+    fs::write(Path::new(&out).join("forms.rs"), gen_forms(&all)).expect("Failed to write forms.rs");
+    fs::write(Path::new(&out).join("emit.rs"), gen_emit(&all)).expect("Failed to write emit.rs");
+}
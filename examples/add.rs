@@ -6,9 +6,8 @@
 #[cfg(not(any(target_arch = "x86_64", target_os = "linux")))]
 compile_error!("Only supported on x86_64 with SystemV abi");
 
-use juicebox_asm::insn::*;
-use juicebox_asm::Runtime;
-use juicebox_asm::{Asm, Imm64, Reg64::*};
+use juicebox_asm::prelude::*;
+use juicebox_asm::Reg64::*;
 
 extern "C" fn add(a: u32, b: u32) -> u32 {
     a + b
@@ -23,7 +22,10 @@ fn main() {
     //   rax -> return value
 
     asm.mov(rsi, Imm64::from(42));
-    asm.mov(rax, Imm64::from(add as usize));
+    asm.mov(
+        rax,
+        Imm64::from(add as extern "C" fn(u32, u32) -> u32 as usize),
+    );
     asm.call(rax);
     asm.ret();
 
@@ -1,7 +1,9 @@
 //! Brainfuck VM.
 //!
 //! This example implements a simple [brainfuck][bf] interpreter
-//! [`BrainfuckInterp`] and a jit compiler [`BrainfuckJit`].
+//! [`BrainfuckInterp`] and two jit compilers. [`BrainfuckJit`] compiles the
+//! whole program into a single function, while [`BrainfuckThreaded`] compiles
+//! each loop body into its own function on first execution.
 //!
 //! Brainfuck is an esoteric programming languge existing of 8 commands.
 //! - `>` increment data pointer.
@@ -21,12 +23,13 @@
 //! [bf]: https://en.wikipedia.org/wiki/Brainfuck
 //! [hw]: https://en.wikipedia.org/wiki/Brainfuck#Hello_World!
 
+use std::cell::RefCell;
 use std::collections::HashMap;
 use std::io::Write;
+use std::time::Instant;
 
-use juicebox_asm::insn::*;
-use juicebox_asm::Runtime;
-use juicebox_asm::{Asm, Imm64, Imm8, Label, Mem8, Reg64, Reg8};
+use juicebox_asm::prelude::*;
+use juicebox_asm::Entry;
 
 // -- BRAINFUCK INTERPRETER ----------------------------------------------------
 
@@ -90,15 +93,11 @@ impl BrainfuckInterp {
 fn run_interp(prog: &str) {
     let mut vm = BrainfuckInterp::new(prog).unwrap();
 
-    loop {
-        let insn = match vm.imem.get(vm.pc) {
-            Some(insn) => insn,
-            None => break, // End of bf program.
-        };
-
+    // Run until the end of the bf program.
+    while let Some(insn) = vm.imem.get(vm.pc) {
         let putchar = |val: u8| {
             std::io::stdout()
-                .write(&[val])
+                .write_all(&[val])
                 .expect("Failed to write to stdout!");
         };
 
@@ -172,7 +171,7 @@ impl BrainfuckJit {
 
 extern "C" fn putchar(c: u8) {
     std::io::stdout()
-        .write(&[c])
+        .write_all(&[c])
         .expect("Failed to write to stdout!");
 }
 
@@ -200,7 +199,8 @@ fn run_jit(prog: &str) {
     asm.xor(dmem_idx, dmem_idx);
 
     // A stack of label pairs, used to link up forward and backward jumps for a
-    // given '[]' pair.
+    // given '[]' pair. The labels are owned by the assembler and the stack only
+    // holds their ids.
     let mut label_stack = Vec::new();
 
     // Label to jump to when a data pointer overflow is detected.
@@ -244,7 +244,7 @@ fn run_jit(prog: &str) {
                         // of the loop.
                         pc += cnt - 1;
                     }
-                    cnt @ _ => unimplemented!("cnt={cnt} oob, add with larger imm"),
+                    cnt => unimplemented!("cnt={cnt} oob, add with larger imm"),
                 }
             }
             '-' => {
@@ -265,7 +265,7 @@ fn run_jit(prog: &str) {
                         // of the loop.
                         pc += cnt - 1;
                     }
-                    cnt @ _ => unimplemented!("cnt={cnt} oob, sub with larger imm"),
+                    cnt => unimplemented!("cnt={cnt} oob, sub with larger imm"),
                 }
             }
             '.' => {
@@ -275,7 +275,10 @@ fn run_jit(prog: &str) {
                 // callee saved registers we don't need to save any registers
                 // before the call.
                 asm.mov(Reg8::dil, Mem8::indirect_base_index(dmem_base, dmem_idx));
-                asm.mov(Reg64::rax, Imm64::from(putchar as usize));
+                asm.mov(
+                    Reg64::rax,
+                    Imm64::from(putchar as extern "C" fn(u8) as usize),
+                );
                 asm.call(Reg64::rax);
             }
             ',' => {
@@ -283,9 +286,8 @@ fn run_jit(prog: &str) {
             }
             '[' => {
                 // Create new label pair.
-                label_stack.push((Label::new(), Label::new()));
-                // UNWRAP: We just pushed a new entry on the stack.
-                let label_pair = label_stack.last_mut().unwrap();
+                let label_pair = (asm.new_label(), asm.new_label());
+                label_stack.push(label_pair);
 
                 // Goto label_pair.0 if data memory at active cell is 0.
                 //   if vm.dmem[vm.dptr] == 0 goto label_pair.0
@@ -293,14 +295,14 @@ fn run_jit(prog: &str) {
                     Mem8::indirect_base_index(dmem_base, dmem_idx),
                     Imm8::from(0u8),
                 );
-                asm.jz(&mut label_pair.0);
+                asm.jz(label_pair.0);
 
                 // Bind label_pair.1 after the jump instruction, which will be
                 // the branch target for the matching ']'.
-                asm.bind(&mut label_pair.1);
+                asm.bind_label(label_pair.1);
             }
             ']' => {
-                let mut label_pair = label_stack
+                let label_pair = label_stack
                     .pop()
                     .expect("encountered un-balanced brackets, found ']' without matching '['");
 
@@ -310,11 +312,11 @@ fn run_jit(prog: &str) {
                     Mem8::indirect_base_index(dmem_base, dmem_idx),
                     Imm8::from(0u8),
                 );
-                asm.jnz(&mut label_pair.1);
+                asm.jnz(label_pair.1);
 
                 // Bind label_pair.0 after the jump instruction, which is the
                 // branch target for the matching '['.
-                asm.bind(&mut label_pair.0);
+                asm.bind_label(label_pair.0);
             }
             _ => unreachable!(),
         }
@@ -361,6 +363,242 @@ fn run_jit(prog: &str) {
     }
 }
 
+// -- BRAINFUCK THREADED JIT ---------------------------------------------------
+
+// The vm state is shared by all jitted functions and held in the same callee
+// saved registers as in the single function jit.
+const DMEM_BASE: Reg64 = Reg64::rbx;
+const DMEM_SIZE: Reg64 = Reg64::r12;
+const DMEM_IDX: Reg64 = Reg64::r13;
+
+const DMEM_LEN: usize = 256;
+
+/// Jit compiling each loop body into its own function on first execution.
+///
+/// A function calls the loops it contains through an [`Entry`], which initially
+/// points to a stub that compiles the loop, publishes the compiled function in
+/// the entry and runs it. From then on the callers call the compiled function
+/// directly. A loop function is only called when the loop is entered and at
+/// the end of its body it jumps back to its head instead of returning to the
+/// caller.
+///
+/// Every function returns 0 on success or the oob error code, which callers
+/// forward up to the top-level function.
+struct BrainfuckThreaded {
+    imem: Vec<char>,
+    /// Mapping from '[' to the matching ']'.
+    branches: HashMap<usize, usize>,
+    /// Entry of the loop function for each '['.
+    entries: HashMap<usize, Entry>,
+    rt: RefCell<Runtime>,
+}
+
+impl BrainfuckThreaded {
+    fn new(prog: &str) -> Result<Box<Self>, String> {
+        // Reuse the validation and branch target computation of the interpreter.
+        let BrainfuckInterp { imem, branches, .. } = BrainfuckInterp::new(prog)?;
+        let branches = branches
+            .into_iter()
+            .filter(|&(lhs, rhs)| lhs < rhs)
+            .collect();
+
+        let mut rt = Runtime::new();
+        // Record the jitted functions by name in the descriptor table.
+        rt.enable_descriptors();
+
+        // Box the jit, as its address is embedded in the lazy compilation stubs.
+        let mut jit = Box::new(BrainfuckThreaded {
+            imem,
+            branches,
+            entries: HashMap::new(),
+            rt: RefCell::new(rt),
+        });
+
+        let entries = jit
+            .branches
+            .keys()
+            .map(|&pc| (pc, Entry::new(jit.stub(pc))))
+            .collect();
+        jit.entries = entries;
+
+        Ok(jit)
+    }
+
+    /// Install the lazy compilation stub for the loop starting at `pc`.
+    fn stub(&self, pc: usize) -> *const u8 {
+        let mut asm = Asm::new();
+
+        // Keep the stack 16 byte aligned at the calls.
+        asm.push(Reg64::rbp);
+        asm.mov(Reg64::rdi, Imm64::from(self as *const Self as usize));
+        asm.mov(Reg64::rsi, Imm64::from(pc));
+        asm.mov(
+            Reg64::rax,
+            Imm64::from(compile_loop as extern "C" fn(&Self, usize) -> *const u8 as usize),
+        );
+        asm.call(Reg64::rax);
+        // Run the compiled loop once, its return value is forwarded to the caller.
+        asm.call(Reg64::rax);
+        asm.pop(Reg64::rbp);
+        asm.ret();
+
+        let mut rt = self.rt.borrow_mut();
+        unsafe { rt.add_function(&format!("bf_stub_{pc}"), pc, asm.into_code()) }
+    }
+
+    /// Compile the instructions in `start..end` into a function. For a loop
+    /// body the function runs the loop, else it is the top-level function
+    /// taking the data memory pointer as argument.
+    fn compile(&self, start: usize, end: usize, is_loop: bool) -> Vec<u8> {
+        let mut asm = Asm::new();
+
+        let mut head = Label::new();
+        let mut exit = Label::new();
+        let mut oob_ov = Label::new();
+        let mut oob_uv = Label::new();
+
+        let cell = || Mem8::indirect_base_index(DMEM_BASE, DMEM_IDX);
+
+        // On entry the stack is 8 byte off of the 16 byte alignment, one or
+        // three pushes keep it aligned at the calls.
+        if is_loop {
+            asm.push(Reg64::rbp);
+        } else {
+            asm.push(DMEM_BASE);
+            asm.push(DMEM_SIZE);
+            asm.push(DMEM_IDX);
+
+            asm.mov(DMEM_BASE, Reg64::rdi);
+            asm.mov(DMEM_SIZE, Imm64::from(DMEM_LEN));
+            asm.xor(DMEM_IDX, DMEM_IDX);
+        }
+
+        asm.bind(&mut head);
+
+        let mut pc = start;
+        while pc < end {
+            match self.imem[pc] {
+                '>' => {
+                    asm.inc(DMEM_IDX);
+                    asm.cmp(DMEM_IDX, DMEM_SIZE);
+                    asm.jz(&mut oob_ov);
+                }
+                '<' => {
+                    asm.test(DMEM_IDX, DMEM_IDX);
+                    asm.jz(&mut oob_uv);
+                    asm.dec(DMEM_IDX);
+                }
+                op @ ('+' | '-') => {
+                    // Fold consecutive '+' or '-' instructions into a single
+                    // add or sub instruction.
+                    let cnt = self.imem[pc..end].iter().take_while(|&&i| i == op).count();
+                    // CAST: The cells wrap around, hence truncating the count is intended.
+                    let imm = Imm8::from(cnt as u8);
+                    if op == '+' {
+                        asm.add(cell(), imm);
+                    } else {
+                        asm.sub(cell(), imm);
+                    }
+                    pc += cnt - 1;
+                }
+                '.' => {
+                    asm.mov(Reg8::dil, cell());
+                    asm.mov(
+                        Reg64::rax,
+                        Imm64::from(putchar as extern "C" fn(u8) as usize),
+                    );
+                    asm.call(Reg64::rax);
+                }
+                ',' => {
+                    unimplemented!("getchar");
+                }
+                '[' => {
+                    // Skip the loop if data memory at the active cell is 0,
+                    // such that a loop is only compiled once it is entered.
+                    let mut skip = Label::new();
+                    asm.cmp(cell(), Imm8::from(0u8));
+                    asm.jz(&mut skip);
+
+                    // Call the loop function and forward its error code if any.
+                    asm.call_entry(&self.entries[&pc]);
+                    asm.test(Reg64::rax, Reg64::rax);
+                    asm.jnz(&mut exit);
+                    asm.bind(&mut skip);
+
+                    // Continue after the matching ']'.
+                    pc = self.branches[&pc];
+                }
+                _ => unreachable!(),
+            }
+
+            pc += 1;
+        }
+
+        if is_loop {
+            // Jump back to the head while data memory at the active cell is
+            // not 0.
+            asm.cmp(cell(), Imm8::from(0u8));
+            asm.jnz(&mut head);
+        }
+
+        asm.xor(Reg64::rax, Reg64::rax);
+        asm.bind(&mut exit);
+        if is_loop {
+            asm.pop(Reg64::rbp);
+        } else {
+            asm.pop(DMEM_IDX);
+            asm.pop(DMEM_SIZE);
+            asm.pop(DMEM_BASE);
+        }
+        asm.ret();
+
+        asm.bind(&mut oob_ov);
+        asm.mov(Reg64::rax, Imm64::from(1));
+        asm.jmp(&mut exit);
+
+        asm.bind(&mut oob_uv);
+        asm.mov(Reg64::rax, Imm64::from(2));
+        asm.jmp(&mut exit);
+
+        asm.into_code()
+    }
+
+    /// Compile the top-level function and run it on the data memory `dmem`.
+    fn run(&self, dmem: &mut [u8; DMEM_LEN]) -> u64 {
+        let code = self.compile(0, self.imem.len(), false);
+        let bf_main = unsafe {
+            self.rt
+                .borrow_mut()
+                .add_function::<extern "C" fn(*mut u8) -> u64>("bf_main", 0, code)
+        };
+        bf_main(dmem.as_mut_ptr())
+    }
+}
+
+/// Compile the loop starting at `pc` and publish it in its entry, invoked by
+/// the lazy compilation stub.
+extern "C" fn compile_loop(jit: &BrainfuckThreaded, pc: usize) -> *const u8 {
+    let code = jit.compile(pc + 1, jit.branches[&pc], true);
+    let f = unsafe {
+        jit.rt
+            .borrow_mut()
+            .add_function::<*const u8>(&format!("bf_loop_{pc}"), pc, code)
+    };
+    jit.entries[&pc].publish(f);
+    f
+}
+
+fn run_threaded(prog: &str) {
+    let jit = BrainfuckThreaded::new(prog).unwrap();
+
+    match jit.run(&mut [0; DMEM_LEN]) {
+        0 => { /* success */ }
+        1 => panic!("oob: data pointer overflow"),
+        2 => panic!("oob: data pointer underflow"),
+        _ => unreachable!(),
+    }
+}
+
 // -- MAIN ---------------------------------------------------------------------
 
 fn main() {
@@ -370,6 +608,8 @@ fn main() {
     run_interp(inp);
     println!("hello-world (wikipedia.org) - jit");
     run_jit(inp);
+    println!("hello-world (wikipedia.org) - threaded jit");
+    run_threaded(inp);
 
     // https://programmingwiki.de/Brainfuck
     let inp = ">+++++++++[<++++++++>-]<.>+++++++[<++++>-]<+.+++++++..+++.[-]>++++++++[<++++>-] <.>+++++++++++[<++++++++>-]<-.--------.+++.------.--------.[-]>++++++++[<++++>- ]<+.[-]++++++++++.";
@@ -377,6 +617,18 @@ fn main() {
     run_interp(inp);
     println!("hello-world (programmingwiki.de) - jit");
     run_jit(inp);
+    println!("hello-world (programmingwiki.de) - threaded jit");
+    run_threaded(inp);
+
+    // Nested loops running ~16M iterations without output, to compare the
+    // performance of both jit strategies including compilation.
+    let inp = "-[>-[>-[-]<-]<-]";
+    let now = Instant::now();
+    run_jit(inp);
+    println!("nested-loops - jit: {:?}", now.elapsed());
+    let now = Instant::now();
+    run_threaded(inp);
+    println!("nested-loops - threaded jit: {:?}", now.elapsed());
 }
 
 #[cfg(test)]
@@ -408,4 +660,46 @@ mod test {
         let inp = ">><< >< <";
         run_jit(&inp);
     }
+
+    #[test]
+    #[should_panic]
+    fn threaded_data_ptr_overflow_in_loop() {
+        run_threaded("+[>+]");
+    }
+
+    #[test]
+    #[should_panic]
+    fn threaded_data_ptr_underflow() {
+        run_threaded(">><< >< <");
+    }
+
+    #[test]
+    fn threaded_data_ptr_no_underflow() {
+        run_threaded(">><< ><");
+    }
+
+    #[test]
+    fn threaded_loops() {
+        let jit = BrainfuckThreaded::new("++++[>+++[>+<-]<-]>[-]+").unwrap();
+        let mut dmem = [0; DMEM_LEN];
+        assert_eq!(jit.run(&mut dmem), 0);
+        assert_eq!(dmem[..3], [0, 1, 12]);
+
+        // The loop which is never entered is not compiled.
+        let rt = jit.rt.borrow();
+        let desc = rt.descriptors().unwrap();
+        let mut names: Vec<_> = desc.all().iter().map(|d| desc.name(d)).collect();
+        names.sort();
+        assert_eq!(
+            names,
+            [
+                "bf_loop_4",
+                "bf_loop_9",
+                "bf_main",
+                "bf_stub_19",
+                "bf_stub_4",
+                "bf_stub_9"
+            ]
+        );
+    }
 }
@@ -0,0 +1,236 @@
+//! Jitdump example.
+//!
+//! Jit compile a cold function filling a buffer with random numbers and a hot function mixing
+//! the buffer in a loop, and record both with their names and source line mappings in a perf
+//! [`jitdump`](juicebox_asm::jitdump) file. The line mappings point to the lines of this file
+//! emitting the instructions.
+//!
+//! Profile the example with `perf` as follows, the hot loop shows up as `mix` in the report and
+//! `perf annotate` interleaves its disassembly with the lines of this file.
+//!
+//! ```text
+//! cargo build --release --features jitdump --example jitdump
+//! perf record -k mono target/release/examples/jitdump [seconds]
+//! perf inject --jit -i perf.data -o perf.jit.data
+//! perf report -i perf.jit.data
+//! perf annotate -i perf.jit.data mix
+//! ```
+
+#[cfg(not(any(target_arch = "x86_64", target_os = "linux")))]
+compile_error!("Only supported on x86_64 with SystemV abi");
+
+use std::time::{Duration, Instant};
+
+use juicebox_asm::prelude::*;
+use juicebox_asm::{LineInfo, Reg64::*};
+
+/// Signature of the jitted `fn(buf, len, seed)` filling the buffer.
+type FillFn = extern "C" fn(*mut u64, u64, u64);
+/// Signature of the jitted `fn(buf, len, rounds) -> hash` mixing the buffer.
+type MixFn = extern "C" fn(*const u64, u64, u64) -> u64;
+
+/// Source file of the line mappings.
+const SOURCE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/", file!());
+
+/// Multiplier of the mix function.
+const MUL: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/// Line mappings of a function under construction.
+struct Lines(Vec<LineInfo>);
+
+impl Lines {
+    /// Map the code emitted next to the source line of the caller.
+    #[track_caller]
+    fn mark(&mut self, asm: &Asm) {
+        // UNWRAP: The code fits on the runtime code page.
+        let offset = u32::try_from(asm.len()).unwrap();
+        let line = std::panic::Location::caller().line();
+        self.0.push(LineInfo { offset, line });
+    }
+}
+
+/// Jit compile the fill function, a xorshift generator.
+fn compile_fill(rt: &mut Runtime) -> FillFn {
+    // SystemV abi:
+    //   rdi -> buf
+    //   rsi -> len
+    //   rdx -> seed
+    let (buf, len, x) = (rdi, rsi, rdx);
+    let tmp = rax;
+
+    let mut asm = Asm::new();
+    let mut lines = Lines(Vec::new());
+    let mut lp = Label::new();
+    let mut end = Label::new();
+
+    lines.mark(&asm);
+    asm.test(len, len);
+    asm.jz(&mut end);
+    asm.bind(&mut lp);
+    // x ^= x << 13
+    lines.mark(&asm);
+    asm.mov(tmp, x);
+    asm.shl(tmp, Imm8::from(13u8));
+    asm.xor(x, tmp);
+    // x ^= x >> 7
+    lines.mark(&asm);
+    asm.mov(tmp, x);
+    asm.shr(tmp, Imm8::from(7u8));
+    asm.xor(x, tmp);
+    // x ^= x << 17
+    lines.mark(&asm);
+    asm.mov(tmp, x);
+    asm.shl(tmp, Imm8::from(17u8));
+    asm.xor(x, tmp);
+    // *buf++ = x
+    lines.mark(&asm);
+    asm.mov(Mem64::indirect(buf), x);
+    asm.lea(buf, Mem64::indirect_disp(buf, 8));
+    lines.mark(&asm);
+    asm.dec(len);
+    asm.jnz(&mut lp);
+    asm.bind(&mut end);
+    lines.mark(&asm);
+    asm.ret();
+
+    unsafe { rt.add_function_with_lines::<FillFn>("fill", 0, asm.into_code(), SOURCE, &lines.0) }
+}
+
+/// Jit compile the mix function, which mixes the buffer `rounds` times into a hash.
+fn compile_mix(rt: &mut Runtime) -> MixFn {
+    // SystemV abi:
+    //   rdi -> buf
+    //   rsi -> len
+    //   rdx -> rounds
+    //   rax -> return value
+    let (buf, len, rounds, acc) = (rdi, rsi, rdx, rax);
+    let (mul, p, i, tmp) = (r8, r9, rcx, r10);
+
+    let mut asm = Asm::new();
+    let mut lines = Lines(Vec::new());
+    let mut outer = Label::new();
+    let mut inner = Label::new();
+
+    lines.mark(&asm);
+    asm.xor(acc, acc);
+    asm.mov(mul, Imm64::from(MUL));
+    // for _ in 0..rounds
+    asm.bind(&mut outer);
+    lines.mark(&asm);
+    asm.mov(p, buf);
+    asm.mov(i, len);
+    // for v in buf
+    asm.bind(&mut inner);
+    lines.mark(&asm);
+    asm.mov(tmp, Mem64::indirect(p));
+    asm.xor(acc, tmp);
+    lines.mark(&asm);
+    asm.imul((acc, mul));
+    lines.mark(&asm);
+    asm.rol(acc, Imm8::from(31u8));
+    lines.mark(&asm);
+    asm.lea(p, Mem64::indirect_disp(p, 8));
+    asm.dec(i);
+    asm.jnz(&mut inner);
+    lines.mark(&asm);
+    asm.dec(rounds);
+    asm.jnz(&mut outer);
+    lines.mark(&asm);
+    asm.ret();
+
+    unsafe { rt.add_function_with_lines::<MixFn>("mix", 0, asm.into_code(), SOURCE, &lines.0) }
+}
+
+/// Fill a buffer of `len` words starting from the non-zero `seed` and mix it `rounds` times with
+/// the jitted functions.
+fn run(fill: FillFn, mix: MixFn, len: usize, rounds: u64, seed: u64) -> u64 {
+    // The jitted loops run at least once.
+    assert!(len > 0 && rounds > 0);
+    let mut buf = vec![0u64; len];
+    fill(buf.as_mut_ptr(), len as u64, seed);
+    mix(buf.as_ptr(), len as u64, rounds)
+}
+
+fn main() {
+    let secs = match std::env::args().nth(1).map(|a| a.parse()) {
+        Some(Ok(secs)) => secs,
+        None => 2,
+        Some(Err(_)) => {
+            println!("Usage: jitdump [seconds]");
+            std::process::exit(1);
+        }
+    };
+
+    let mut rt = Runtime::with_jitdump();
+    let fill = compile_fill(&mut rt);
+    let mix = compile_mix(&mut rt);
+    // UNWRAP: The runtime was created with a jitdump file.
+    println!("jitdump: {}", rt.jitdump().unwrap().path().display());
+
+    // Keep the hot loop busy for the requested time.
+    let start = Instant::now();
+    let mut iters = 0u64;
+    let mut hash = 0u64;
+    while start.elapsed() < Duration::from_secs(secs) {
+        // Seed with the iteration, such that the hashes differ.
+        hash = hash.wrapping_add(run(fill, mix, 1024, 1000, iters + 1));
+        iters += 1;
+    }
+    println!("iterations: {}, hash: {:#x}", iters, hash);
+
+    // Dropping the runtime closes the jitdump file.
+    drop(rt);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Reference implementation of the fill and mix functions.
+    fn run_rs(len: usize, rounds: u64, seed: u64) -> u64 {
+        let mut x = seed;
+        let buf: Vec<u64> = (0..len)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                x
+            })
+            .collect();
+
+        let mut acc = 0u64;
+        for _ in 0..rounds {
+            for v in &buf {
+                acc = (acc ^ v).wrapping_mul(MUL).rotate_left(31);
+            }
+        }
+        acc
+    }
+
+    #[test]
+    fn test_run() {
+        let mut rt = Runtime::with_jitdump();
+        let fill = compile_fill(&mut rt);
+        let mix = compile_mix(&mut rt);
+        for (len, rounds, seed) in [(1, 1, 1), (3, 2, 42), (100, 7, 0x1234_5678)] {
+            assert_eq!(run(fill, mix, len, rounds, seed), run_rs(len, rounds, seed));
+        }
+
+        // The dump names both functions and is closed on drop.
+        // UNWRAP: The runtime was created with a jitdump file.
+        let path = rt.jitdump().unwrap().path().to_path_buf();
+        drop(rt);
+        let dump = std::fs::read(&path).unwrap();
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+        let has = |s: &[u8]| dump.windows(s.len()).any(|w| w == s);
+        assert!(has(b"fill\0"));
+        assert!(has(b"mix\0"));
+        assert!(has(SOURCE.as_bytes()));
+        // The close record is the last record, the id followed by the record size.
+        assert_eq!(
+            dump[dump.len() - 16..dump.len() - 8],
+            [3, 0, 0, 0, 16, 0, 0, 0]
+        );
+    }
+}
@@ -0,0 +1,198 @@
+//! Block layout example.
+//!
+//! Jit compile a loop with a rarely taken branch with the [`BlockAsm`] twice, once with the
+//! static layout and once with the layout guided by edge weights collected by profiling the
+//! reference implementation, and benchmark both.
+//!
+//! The static layout prefers the fallthrough successor of each branch, which places the cold
+//! block inside of the hot loop. With the edge weights the hot path becomes a straight line and
+//! only the loop back edge is a taken branch.
+//!
+//! ```text
+//! cargo run --release --example layout
+//! ```
+
+#[cfg(not(any(target_arch = "x86_64", target_os = "linux")))]
+compile_error!("Only supported on x86_64 with SystemV abi");
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use juicebox_asm::prelude::*;
+use juicebox_asm::Reg64::*;
+use juicebox_asm::{BlockAsm, Terminator};
+
+/// Signature of the jitted function `fn(data, len) -> sum`.
+type SumFn = extern "C" fn(*const u64, u64) -> u64;
+
+/// Number of calls per benchmark.
+const ITERS: usize = 2_000;
+
+/// Number of elements summed per call.
+const LEN: usize = 4096;
+
+/// Reference implementation, sums the even values and three times the odd values.
+fn sum_rs(data: &[u64]) -> u64 {
+    data.iter().fold(0u64, |sum, &v| {
+        if v & 1 == 0 {
+            sum.wrapping_add(v)
+        } else {
+            sum.wrapping_add(v.wrapping_mul(3))
+        }
+    })
+}
+
+/// Edge counts of the loop, collected by profiling the reference implementation.
+#[derive(Default)]
+struct Profile {
+    /// Number of even values, the `head -> even` edge.
+    even: u64,
+    /// Number of odd values, the `head -> odd` edge.
+    odd: u64,
+}
+
+impl Profile {
+    fn collect(data: &[u64]) -> Profile {
+        let mut profile = Profile::default();
+        for v in data {
+            if v & 1 == 0 {
+                profile.even += 1;
+            } else {
+                profile.odd += 1;
+            }
+        }
+        profile
+    }
+}
+
+/// Jit compile [`sum_rs`], guided by the edge weights of the `profile` if given.
+fn compile(rt: &mut Runtime, profile: Option<&Profile>) -> SumFn {
+    let mut basm = BlockAsm::new();
+    let entry = basm.create_block();
+    let head = basm.create_block();
+    let odd = basm.create_block();
+    let even = basm.create_block();
+    let latch = basm.create_block();
+    let exit = basm.create_block();
+
+    // SystemV abi:
+    //   rdi -> data
+    //   rsi -> len
+    //   rax -> return value
+
+    // entry: sum = 0; if (len == 0) goto exit else goto head;
+    let asm = basm.block(entry);
+    asm.xor(rax, rax);
+    asm.test(rsi, rsi);
+    basm.terminate(entry, Terminator::Jz(exit, head));
+
+    // head: v = *data; if (v & 1 == 0) goto even else goto odd;
+    let asm = basm.block(head);
+    asm.mov(rcx, Mem64::indirect(rdi));
+    asm.test(rcx, Imm32::from(1));
+    basm.terminate(head, Terminator::Jz(even, odd));
+
+    // odd: sum += 3 * v; goto latch;
+    let asm = basm.block(odd);
+    asm.add(rax, rcx);
+    asm.add(rax, rcx);
+    asm.add(rax, rcx);
+    basm.terminate(odd, Terminator::Jmp(latch));
+
+    // even: sum += v; goto latch;
+    basm.block(even).add(rax, rcx);
+    basm.terminate(even, Terminator::Jmp(latch));
+
+    // latch: data++; if (--len != 0) goto head else goto exit;
+    let asm = basm.block(latch);
+    asm.add(rdi, Imm8::from(8u8));
+    asm.dec(rsi);
+    basm.terminate(latch, Terminator::Jnz(head, exit));
+
+    // exit: return sum;
+    basm.terminate(exit, Terminator::Ret);
+
+    if let Some(profile) = profile {
+        basm.set_edge_weight(head, even, profile.even);
+        basm.set_edge_weight(head, odd, profile.odd);
+        basm.set_edge_weight(even, latch, profile.even);
+        basm.set_edge_weight(odd, latch, profile.odd);
+        basm.set_edge_weight(latch, head, profile.even + profile.odd);
+    }
+
+    unsafe { rt.add_code::<SumFn>(basm.finalize().into_code()) }
+}
+
+/// Generate `len` values, one in 64 values is odd.
+fn data(len: usize) -> Vec<u64> {
+    let mut x: u64 = 0x2545_f491_4f6c_dd1d;
+    (0..len)
+        .map(|_| {
+            // xorshift64
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            if x.is_multiple_of(64) {
+                x | 1
+            } else {
+                x & !1
+            }
+        })
+        .collect()
+}
+
+/// Run `f` for [`ITERS`] iterations and get the average time per element in nanoseconds.
+fn bench(mut f: impl FnMut()) -> f64 {
+    let start = Instant::now();
+    for _ in 0..ITERS {
+        f();
+    }
+    start.elapsed().as_nanos() as f64 / (ITERS * LEN) as f64
+}
+
+fn main() {
+    let data = data(LEN);
+    let profile = Profile::collect(&data);
+    println!("profile: {} even, {} odd values", profile.even, profile.odd);
+
+    let mut rt = Runtime::new();
+    let stat = compile(&mut rt, None);
+    let prof = compile(&mut rt, Some(&profile));
+
+    let expected = sum_rs(&data);
+    assert_eq!(stat(data.as_ptr(), LEN as u64), expected);
+    assert_eq!(prof(data.as_ptr(), LEN as u64), expected);
+
+    let stat_ns = bench(|| {
+        black_box(stat(black_box(data.as_ptr()), LEN as u64));
+    });
+    let prof_ns = bench(|| {
+        black_box(prof(black_box(data.as_ptr()), LEN as u64));
+    });
+
+    println!("{:>10} {:>12}", "layout", "[ns/elem]");
+    println!("{:>10} {:>12.3}", "static", stat_ns);
+    println!("{:>10} {:>12.3}", "profiled", prof_ns);
+    println!("speedup: {:.2}x", stat_ns / prof_ns);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_sum() {
+        let data = data(1000);
+        let profile = Profile::collect(&data);
+        assert!(profile.odd > 0 && profile.even > profile.odd);
+
+        let mut rt = Runtime::new();
+        let stat = compile(&mut rt, None);
+        let prof = compile(&mut rt, Some(&profile));
+        for len in [0, 1, 2, 1000] {
+            let expected = sum_rs(&data[..len]);
+            assert_eq!(stat(data.as_ptr(), len as u64), expected);
+            assert_eq!(prof(data.as_ptr(), len as u64), expected);
+        }
+    }
+}
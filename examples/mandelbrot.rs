@@ -0,0 +1,160 @@
+//! Mandelbrot example.
+//!
+//! Jit compile the escape time kernel of the [Mandelbrot
+//! set](https://en.wikipedia.org/wiki/Mandelbrot_set) in fixed-point arithmetic and render the set
+//! as ASCII art.
+//!
+//! ```text
+//! cargo run --example mandelbrot [max_iter]
+//! ```
+
+#[cfg(not(any(target_arch = "x86_64", target_os = "linux")))]
+compile_error!("Only supported on x86_64 with SystemV abi");
+
+use juicebox_asm::prelude::*;
+use juicebox_asm::Reg64::*;
+
+/// Number of fractional bits of the fixed-point numbers.
+const FRAC: u8 = 24;
+
+/// Signature of the jitted kernel `fn(cx, cy, max_iter) -> iter`.
+type KernelFn = extern "C" fn(i64, i64, u64) -> u64;
+
+/// Convert `v` to a fixed-point number.
+fn fixed(v: f64) -> i64 {
+    (v * f64::from(1 << FRAC)) as i64
+}
+
+/// Jit compile the kernel, which iterates `z = z^2 + c` until `|z| > 2` or `max_iter` is reached
+/// and returns the number of iterations.
+fn compile(rt: &mut Runtime) -> KernelFn {
+    // SystemV abi:
+    //   rdi -> cx
+    //   rsi -> cy
+    //   rdx -> max_iter
+    //   rax -> return value
+    let (cx, cy, max, iter) = (rdi, rsi, rdx, rax);
+    let (x, y, xx, yy, tmp) = (r8, r9, rcx, r10, r11);
+
+    let mut asm = Asm::new();
+    let mut lp = Label::new();
+    let mut end = Label::new();
+
+    asm.xor(iter, iter);
+    asm.xor(x, x);
+    asm.xor(y, y);
+
+    asm.bind(&mut lp);
+    // if iter >= max_iter goto end
+    asm.mov(tmp, iter);
+    asm.sub(tmp, max);
+    asm.jcc(Cond::Ae, &mut end);
+
+    // xx = x * x, yy = y * y
+    asm.mov(xx, x);
+    asm.imul((xx, xx));
+    asm.sar(xx, Imm8::from(FRAC));
+    asm.mov(yy, y);
+    asm.imul((yy, yy));
+    asm.sar(yy, Imm8::from(FRAC));
+
+    // if xx + yy > 4 goto end
+    asm.mov(tmp, Imm64::from(fixed(4.0)));
+    asm.sub(tmp, xx);
+    asm.sub(tmp, yy);
+    asm.jcc(Cond::L, &mut end);
+
+    // y = 2 * x * y + cy
+    asm.imul((y, x));
+    asm.sar(y, Imm8::from(FRAC - 1));
+    asm.add(y, cy);
+    // x = xx - yy + cx
+    asm.sub(xx, yy);
+    asm.add(xx, cx);
+    asm.mov(x, xx);
+
+    asm.inc(iter);
+    asm.jmp(&mut lp);
+
+    asm.bind(&mut end);
+    asm.ret();
+
+    unsafe { rt.add_code::<KernelFn>(asm.into_code()) }
+}
+
+fn main() {
+    let max_iter = match std::env::args().nth(1).map(|a| a.parse()) {
+        Some(Ok(max_iter)) => max_iter,
+        None => 64,
+        Some(Err(_)) => {
+            println!("Usage: mandelbrot [max_iter]");
+            std::process::exit(1);
+        }
+    };
+
+    let mut rt = Runtime::new();
+    let kernel = compile(&mut rt);
+
+    const CHARS: &[u8] = b" .:-=+*#%@";
+    let (w, h) = (78, 32);
+    for row in 0..h {
+        let line: String = (0..w)
+            .map(|col| {
+                let cx = -2.2 + 3.2 * f64::from(col) / f64::from(w);
+                let cy = -1.2 + 2.4 * f64::from(row) / f64::from(h);
+                let iter = kernel(fixed(cx), fixed(cy), max_iter);
+                if iter == max_iter {
+                    '@'
+                } else {
+                    // CAST: iter < max_iter, hence the index is in the range of CHARS.
+                    char::from(CHARS[(iter as usize * (CHARS.len() - 1)) / max_iter as usize])
+                }
+            })
+            .collect();
+        println!("{}", line);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Reference implementation of the kernel in floating-point arithmetic.
+    fn kernel_rs(cx: f64, cy: f64, max_iter: u64) -> u64 {
+        let (mut x, mut y) = (0f64, 0f64);
+        for iter in 0..max_iter {
+            if x * x + y * y > 4.0 {
+                return iter;
+            }
+            (x, y) = (x * x - y * y + cx, 2.0 * x * y + cy);
+        }
+        max_iter
+    }
+
+    #[test]
+    fn test_kernel() {
+        let mut rt = Runtime::new();
+        let kernel = compile(&mut rt);
+
+        // Points clearly inside or outside the set, away from the boundary where the fixed-point
+        // rounding can change the escape time.
+        for (cx, cy) in [
+            (0.0, 0.0),
+            (-1.0, 0.0),
+            (-0.1, 0.1),
+            (1.0, 1.0),
+            (-2.5, 0.0),
+            (0.5, 0.5),
+        ] {
+            let max_iter = 100;
+            assert_eq!(
+                kernel(fixed(cx), fixed(cy), max_iter),
+                kernel_rs(cx, cy, max_iter),
+                "c = {} + {}i",
+                cx,
+                cy
+            );
+        }
+        assert_eq!(kernel(0, 0, 0), 0);
+    }
+}
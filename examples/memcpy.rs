@@ -0,0 +1,96 @@
+//! Memcpy example.
+//!
+//! Jit compile memcpy functions specialized for a copy size known at runtime and benchmark them
+//! against [`std::ptr::copy`]. Small sizes are copied with unrolled moves, larger sizes with
+//! `rep movsb`, see [`Asm::emit_memcpy`].
+//!
+//! ```text
+//! cargo run --release --example memcpy [size..]
+//! ```
+
+#[cfg(not(any(target_arch = "x86_64", target_os = "linux")))]
+compile_error!("Only supported on x86_64 with SystemV abi");
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use juicebox_asm::prelude::*;
+use juicebox_asm::Reg64::*;
+
+/// Signature of the jitted copy function `fn(dst, src)`.
+type CopyFn = extern "C" fn(*mut u8, *const u8);
+
+/// Number of copies per benchmark.
+const ITERS: usize = 1_000_000;
+
+/// Jit compile a copy of `len` bytes.
+fn compile(rt: &mut Runtime, len: usize) -> CopyFn {
+    // SystemV abi:
+    //   rdi -> dst
+    //   rsi -> src
+    let mut asm = Asm::new();
+    asm.emit_memcpy(rdi, rsi, len);
+    asm.ret();
+    unsafe { rt.add_code::<CopyFn>(asm.into_code()) }
+}
+
+/// Run `f` for [`ITERS`] iterations and get the average time per iteration in nanoseconds.
+fn bench(mut f: impl FnMut()) -> f64 {
+    let start = Instant::now();
+    for _ in 0..ITERS {
+        f();
+    }
+    start.elapsed().as_nanos() as f64 / ITERS as f64
+}
+
+fn main() {
+    let sizes: Vec<usize> = match std::env::args()
+        .skip(1)
+        .map(|a| a.parse())
+        .collect::<Result<Vec<_>, _>>()
+    {
+        Ok(sizes) if !sizes.is_empty() => sizes,
+        Ok(_) => vec![8, 15, 32, 64, 100, 256, 4096],
+        Err(_) => {
+            println!("Usage: memcpy [size..]");
+            std::process::exit(1);
+        }
+    };
+
+    let mut rt = Runtime::new();
+    println!("{:>8} {:>10} {:>10}", "size", "jit [ns]", "std [ns]");
+
+    for len in sizes {
+        let copy = compile(&mut rt, len);
+
+        let src: Vec<u8> = (0..len).map(|i| i as u8).collect();
+        let mut dst = vec![0u8; len];
+        copy(dst.as_mut_ptr(), src.as_ptr());
+        assert_eq!(dst, src, "Jitted copy of {} bytes is wrong", len);
+
+        let jit = bench(|| copy(black_box(dst.as_mut_ptr()), black_box(src.as_ptr())));
+        let std = bench(|| unsafe {
+            std::ptr::copy(black_box(src.as_ptr()), black_box(dst.as_mut_ptr()), len)
+        });
+        println!("{:>8} {:>10.2} {:>10.2}", len, jit, std);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_copy() {
+        let mut rt = Runtime::new();
+        for len in [0, 1, 7, 8, 9, 31, 64, 65, 1000] {
+            let copy = compile(&mut rt, len);
+            let src: Vec<u8> = (0..len + 1).map(|i| (i as u8).wrapping_add(1)).collect();
+            let mut dst = vec![0u8; len + 1];
+            copy(dst.as_mut_ptr(), src.as_ptr());
+            assert_eq!(dst[..len], src[..len]);
+            // The byte after the copy is untouched.
+            assert_eq!(dst[len], 0);
+        }
+    }
+}
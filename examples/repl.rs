@@ -0,0 +1,270 @@
+//! REPL example.
+//!
+//! Interactive assembler, which reads one instruction per line from _stdin_, assembles it with
+//! [`Asm::emit_insn`], executes it on a persistent register state and prints the encoding and
+//! the modified registers.
+//!
+//! ```text
+//! > mov rax, 0x1234
+//! 48 c7 c0 34 12 00 00
+//! rax = 0x1234
+//! > add rax, qword [rsp]
+//! ...
+//! ```
+//!
+//! Instructions use the mnemonics and operand orders of the [`juicebox_asm`] instruction traits,
+//! see the [`FORMS`](juicebox_asm::insn::FORMS) table, eg `setcc e, al`. Operands are registers,
+//! immediates, condition codes and memory operands with a size, eg `qword [rdi + rsi*8 + 8]`.
+//! Instructions modifying `rsp` or jumping are not supported, the stack pointer is not part of
+//! the register state.
+
+#[cfg(not(any(target_arch = "x86_64", target_os = "linux")))]
+compile_error!("Only supported on x86_64 with SystemV abi");
+
+use std::io::{BufRead, Write};
+
+use juicebox_asm::prelude::*;
+use juicebox_asm::{Operand, Reg64::*};
+
+/// Parse a register operand, eg `eax`.
+fn parse_reg(tok: &str) -> Option<Operand<'static>> {
+    if let Some(r) = Reg64::iter().find(|r| r.to_string() == tok) {
+        return Some(r.into());
+    }
+    if let Some(r) = Reg32::iter().find(|r| r.to_string() == tok) {
+        return Some(r.into());
+    }
+    if let Some(r) = Reg16::iter().find(|r| r.to_string() == tok) {
+        return Some(r.into());
+    }
+    Reg8::iter()
+        .find(|r| r.to_string() == tok)
+        .map(Operand::from)
+}
+
+/// Parse a decimal or hexadecimal integer, eg `-8` or `0xff`.
+fn parse_int(tok: &str) -> Option<i64> {
+    let (neg, tok) = match tok.strip_prefix('-') {
+        Some(tok) => (true, tok),
+        None => (false, tok),
+    };
+    let val = match tok.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16).ok()? as i64,
+        None => tok.parse().ok()?,
+    };
+    Some(if neg { val.wrapping_neg() } else { val })
+}
+
+/// Parse an immediate operand into all immediate types it fits into, from smallest to largest.
+fn parse_imm(tok: &str) -> Option<Vec<Operand<'static>>> {
+    let val = parse_int(tok)?;
+    let mut imms = Vec::new();
+    if let Ok(v) = i8::try_from(val) {
+        imms.push(Imm8::from(v).into());
+    } else if let Ok(v) = u8::try_from(val) {
+        imms.push(Imm8::from(v).into());
+    }
+    if let Ok(v) = i16::try_from(val) {
+        imms.push(Imm16::from(v).into());
+    } else if let Ok(v) = u16::try_from(val) {
+        imms.push(Imm16::from(v).into());
+    }
+    if let Ok(v) = i32::try_from(val) {
+        imms.push(Imm32::from(v).into());
+    } else if let Ok(v) = u32::try_from(val) {
+        imms.push(Imm32::from(v).into());
+    }
+    imms.push(Imm64::from(val).into());
+    Some(imms)
+}
+
+/// Parse a memory operand, eg `qword [rdi + rsi*8 - 8]`.
+fn parse_mem(tok: &str) -> Option<Operand<'static>> {
+    let (size, addr) = tok.split_once('[')?;
+    let addr = addr.strip_suffix(']')?.replace(' ', "").replace('-', "+-");
+
+    let (mut base, mut index, mut scale, mut disp) = (None, None, 1, 0);
+    for part in addr.split('+').filter(|p| !p.is_empty()) {
+        let (reg, s) = match part.split_once('*') {
+            Some((reg, s)) => (reg, Some(parse_int(s)?)),
+            None => (part, None),
+        };
+        match Reg64::iter().find(|r| r.to_string() == reg) {
+            Some(r) if base.is_none() && s.is_none() => base = Some(r),
+            Some(r) if index.is_none() => {
+                index = Some(r);
+                scale = u8::try_from(s.unwrap_or(1)).ok()?;
+            }
+            Some(_) => return None,
+            None => disp += i32::try_from(parse_int(part)?).ok()?,
+        }
+    }
+
+    macro_rules! mem {
+        ($mem:ident) => {
+            match (base?, index) {
+                (base, None) => $mem::indirect_disp(base, disp),
+                (base, Some(index)) => {
+                    $mem::indirect_base_index_scale_disp(base, index, scale, disp)
+                }
+            }
+            .into()
+        };
+    }
+
+    Some(match size.trim() {
+        "byte" => mem!(Mem8),
+        "word" => mem!(Mem16),
+        "dword" => mem!(Mem32),
+        "qword" => mem!(Mem64),
+        _ => return None,
+    })
+}
+
+/// Parse a condition code operand, eg `ne`.
+fn parse_cond(tok: &str) -> Option<Operand<'static>> {
+    Cond::ALL
+        .into_iter()
+        .find(|c| format!("{:?}", c).to_lowercase() == tok)
+        .map(Operand::from)
+}
+
+/// Parse an operand into all candidate operands, immediates are ambiguous in their size.
+fn parse_operand(tok: &str) -> Option<Vec<Operand<'static>>> {
+    let tok = tok.trim();
+    parse_reg(tok)
+        .or_else(|| parse_mem(tok))
+        .or_else(|| parse_cond(tok))
+        .map(|op| vec![op])
+        .or_else(|| parse_imm(tok))
+}
+
+/// Assemble the instruction `line`, trying all candidate operand combinations.
+fn assemble(line: &str) -> Result<Vec<u8>, String> {
+    let (mnemonic, ops) = line.split_once(' ').unwrap_or((line, ""));
+    let mut cands = Vec::new();
+    for tok in ops.split(',').filter(|tok| !tok.trim().is_empty()) {
+        cands.push(parse_operand(tok).ok_or_else(|| format!("Invalid operand '{}'", tok.trim()))?);
+    }
+
+    // Iterate over all combinations of the candidates, preferring the smallest immediates.
+    let mut sel = vec![0; cands.len()];
+    loop {
+        let mut ops: Vec<_> = cands.iter().zip(&sel).map(|(c, i)| copy(&c[*i])).collect();
+        let mut asm = Asm::new();
+        match asm.emit_insn(mnemonic, &mut ops) {
+            Ok(()) => return Ok(asm.into_code()),
+            Err(err) => {
+                let Some(pos) = (0..sel.len()).rev().find(|&i| sel[i] + 1 < cands[i].len()) else {
+                    return Err(err.to_string());
+                };
+                sel[pos] += 1;
+                sel[pos + 1..].fill(0);
+            }
+        }
+    }
+}
+
+/// Copy an operand, the parsed operands never contain labels.
+fn copy(op: &Operand<'static>) -> Operand<'static> {
+    match op {
+        Operand::Reg64(r) => (*r).into(),
+        Operand::Reg32(r) => (*r).into(),
+        Operand::Reg16(r) => (*r).into(),
+        Operand::Reg8(r) => (*r).into(),
+        Operand::Mem64(m) => (*m).into(),
+        Operand::Mem32(m) => (*m).into(),
+        Operand::Mem16(m) => (*m).into(),
+        Operand::Mem8(m) => (*m).into(),
+        Operand::Imm64(i) => (*i).into(),
+        Operand::Imm32(i) => (*i).into(),
+        Operand::Imm16(i) => (*i).into(),
+        Operand::Imm8(i) => (*i).into(),
+        Operand::Cond(c) => (*c).into(),
+        Operand::LabelId(l) => (*l).into(),
+        Operand::Label(_) => unreachable!("Labels are not parsed"),
+    }
+}
+
+/// Wrap the instruction `code` into a `fn(regs: &mut [u64; 16])`, which loads the register
+/// state from `regs`, executes the instruction and stores the register state back.
+fn wrap(code: &[u8]) -> Vec<u8> {
+    // Registers of the state, rsp is excluded and the regs pointer in rdi is loaded last.
+    let regs: Vec<_> = Reg64::iter().filter(|r| !matches!(r, rsp | rdi)).collect();
+
+    let mut asm = Asm::new();
+    // Save the callee-saved registers and the regs pointer, which keeps the stack aligned.
+    for r in [rbx, rbp, r12, r13, r14, r15, rdi] {
+        asm.push(r);
+    }
+    for &r in &regs {
+        let disp = i32::from(r.index()) * 8;
+        asm.mov(r, Mem64::indirect_disp(rdi, disp));
+    }
+    asm.mov(rdi, Mem64::indirect_disp(rdi, 7 * 8));
+
+    asm.emit_blob(code, &mut []);
+
+    // Use rax to hold the regs pointer, stored last from the stack.
+    asm.push(rax);
+    asm.mov(rax, Mem64::indirect_disp(rsp, 8));
+    for r in regs.into_iter().chain([rdi]).filter(|r| *r != rax) {
+        let disp = i32::from(r.index()) * 8;
+        asm.mov(Mem64::indirect_disp(rax, disp), r);
+    }
+    asm.pop(rcx);
+    asm.mov(Mem64::indirect(rax), rcx);
+
+    for r in [rdi, r15, r14, r13, r12, rbp, rbx] {
+        asm.pop(r);
+    }
+    asm.ret();
+    asm.into_code()
+}
+
+fn main() {
+    let mut regs = [0u64; 16];
+    let stdin = std::io::stdin();
+
+    loop {
+        print!("> ");
+        std::io::stdout().flush().expect("Failed to flush stdout");
+
+        let mut line = String::new();
+        if stdin
+            .lock()
+            .read_line(&mut line)
+            .expect("Failed to read stdin")
+            == 0
+        {
+            break;
+        }
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        // The instructions assert on invalid operands, eg an rsp index.
+        let code = match std::panic::catch_unwind(|| assemble(line)) {
+            Ok(Ok(code)) => code,
+            Ok(Err(err)) => {
+                println!("{}", err);
+                continue;
+            }
+            Err(_) => continue,
+        };
+        let hex: Vec<_> = code.iter().map(|b| format!("{:02x}", b)).collect();
+        println!("{}", hex.join(" "));
+
+        let mut rt = Runtime::new();
+        let run = unsafe { rt.add_code::<extern "C" fn(&mut [u64; 16])>(wrap(&code)) };
+        let prev = regs;
+        run(&mut regs);
+
+        for r in
+            Reg64::iter().filter(|r| regs[usize::from(r.index())] != prev[usize::from(r.index())])
+        {
+            println!("{} = {:#x}", r, regs[usize::from(r.index())]);
+        }
+    }
+}
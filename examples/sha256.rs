@@ -0,0 +1,226 @@
+//! SHA-256 example.
+//!
+//! Jit compile the [SHA-256](https://en.wikipedia.org/wiki/SHA-2) block compression function and
+//! hash the arguments with it. The compression function demonstrates the rotates and byte swaps.
+//!
+//! ```text
+//! cargo run --example sha256 [message..]
+//! ```
+
+#[cfg(not(any(target_arch = "x86_64", target_os = "linux")))]
+compile_error!("Only supported on x86_64 with SystemV abi");
+
+use juicebox_asm::prelude::*;
+use juicebox_asm::{Reg32::*, Reg64::*};
+
+/// SHA-256 round constants.
+const K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// SHA-256 initial hash value.
+const H0: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// Signature of the jitted compression function `fn(state, block, schedule, k)`, the message
+/// schedule is a scratch buffer and `k` the table of the round constants.
+type CompressFn = extern "C" fn(&mut [u32; 8], &[u8; 64], &mut [u32; 64], &[u32; 64]);
+
+/// Emit `dst = dst ^ ror(src, r0) ^ ror(src, r1) ^ op(src)`, which clobbers `tmp`, where the
+/// last term is a rotate or shift by `r2` depending on `shift`.
+fn emit_sigma(asm: &mut Asm, dst: Reg32, src: Reg32, tmp: Reg32, r: [u8; 3], shift: bool) {
+    asm.mov(dst, src);
+    asm.ror(dst, Imm8::from(r[0]));
+    asm.mov(tmp, src);
+    asm.ror(tmp, Imm8::from(r[1]));
+    asm.xor(dst, tmp);
+    asm.mov(tmp, src);
+    if shift {
+        asm.shr(tmp, Imm8::from(r[2]));
+    } else {
+        asm.ror(tmp, Imm8::from(r[2]));
+    }
+    asm.xor(dst, tmp);
+}
+
+/// Jit compile the compression function.
+fn compile(rt: &mut Runtime) -> CompressFn {
+    // SystemV abi:
+    //   rdi -> state
+    //   rsi -> block
+    //   rdx -> schedule
+    //   rcx -> k
+    let (state, block, w, k) = (rdi, rsi, rdx, rcx);
+    let at = |base: Reg64, i: isize| Mem32::indirect_disp(base, (i * 4) as i32);
+
+    let mut asm = Asm::new();
+    let callee_saved = [rbx, r12, r13, r14, r15];
+    for r in callee_saved {
+        asm.push(r);
+    }
+
+    // Message schedule, the block holds big-endian words.
+    for i in 0..16 {
+        asm.mov(eax, at(block, i));
+        asm.bswap(eax);
+        asm.mov(at(w, i), eax);
+    }
+
+    // The block is consumed, rsi is the loop counter from here on.
+    let cnt = rsi;
+    let mut lp = Label::new();
+    // p = &w[i], for i in 16..64
+    let p = r10;
+    asm.lea(p, Mem64::indirect_disp(w, 16 * 4));
+    asm.mov(cnt, Imm64::from(48));
+    asm.bind(&mut lp);
+    // w[i] = s1(w[i - 2]) + w[i - 7] + s0(w[i - 15]) + w[i - 16]
+    asm.mov(r8d, at(p, -15));
+    emit_sigma(&mut asm, eax, r8d, ebx, [7, 18, 3], true);
+    asm.mov(r8d, at(p, -2));
+    emit_sigma(&mut asm, r9d, r8d, ebx, [17, 19, 10], true);
+    asm.add(eax, r9d);
+    asm.mov(ebx, at(p, -7));
+    asm.add(eax, ebx);
+    asm.mov(ebx, at(p, -16));
+    asm.add(eax, ebx);
+    asm.mov(at(p, 0), eax);
+    asm.lea(p, Mem64::indirect_disp(p, 4));
+    asm.dec(cnt);
+    asm.jnz(&mut lp);
+
+    // The working variables a..h are held in registers. Instead of moving them after each round
+    // the register assignment is rotated, which repeats after eight rounds, hence the loop body
+    // consists of eight rounds.
+    let mut v = [r8d, r9d, r10d, r11d, r12d, r13d, r14d, r15d];
+    for (i, r) in v.iter().enumerate() {
+        asm.mov(*r, at(state, i as isize));
+    }
+
+    let mut lp = Label::new();
+    asm.mov(cnt, Imm64::from(8));
+    asm.bind(&mut lp);
+    for i in 0..8 {
+        let [a, b, c, d, e, f, g, h] = v;
+
+        // h += S1(e) + ch(e, f, g) + k[i] + w[i]
+        emit_sigma(&mut asm, eax, e, ebx, [6, 11, 25], false);
+        asm.add(h, eax);
+        // ch(e, f, g) = ((f ^ g) & e) ^ g
+        asm.mov(eax, f);
+        asm.xor(eax, g);
+        asm.and(eax, e);
+        asm.xor(eax, g);
+        asm.add(h, eax);
+        asm.mov(eax, at(k, i));
+        asm.add(h, eax);
+        asm.mov(eax, at(w, i));
+        asm.add(h, eax);
+        // d += t1
+        asm.add(d, h);
+
+        // h += S0(a) + maj(a, b, c)
+        emit_sigma(&mut asm, eax, a, ebx, [2, 13, 22], false);
+        asm.add(h, eax);
+        // maj(a, b, c) = ((a | b) & c) | (a & b)
+        asm.mov(eax, a);
+        asm.or(eax, b);
+        asm.and(eax, c);
+        asm.mov(ebx, a);
+        asm.and(ebx, b);
+        asm.or(eax, ebx);
+        asm.add(h, eax);
+
+        v.rotate_right(1);
+    }
+    asm.lea(w, Mem64::indirect_disp(w, 8 * 4));
+    asm.lea(k, Mem64::indirect_disp(k, 8 * 4));
+    asm.dec(cnt);
+    asm.jnz(&mut lp);
+
+    for (i, r) in v.iter().enumerate() {
+        asm.mov(eax, at(state, i as isize));
+        asm.add(eax, *r);
+        asm.mov(at(state, i as isize), eax);
+    }
+
+    for r in callee_saved.into_iter().rev() {
+        asm.pop(r);
+    }
+    asm.ret();
+
+    unsafe { rt.add_code::<CompressFn>(asm.into_code()) }
+}
+
+/// Compute the SHA-256 digest of `msg` with the jitted `compress` function.
+fn sha256(compress: CompressFn, msg: &[u8]) -> [u8; 32] {
+    // Pad with a one bit, zeros and the message length in bits.
+    let mut data = msg.to_vec();
+    data.push(0x80);
+    while data.len() % 64 != 56 {
+        data.push(0);
+    }
+    data.extend_from_slice(&(msg.len() as u64 * 8).to_be_bytes());
+
+    let mut state = H0;
+    let mut w = [0u32; 64];
+    for block in data.chunks_exact(64) {
+        // UNWRAP: The chunks are exactly 64 bytes.
+        compress(&mut state, block.try_into().unwrap(), &mut w, &K);
+    }
+
+    let mut digest = [0u8; 32];
+    for (out, word) in digest.chunks_exact_mut(4).zip(state) {
+        out.copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
+
+/// Format the `digest` as hex string.
+fn hex(digest: &[u8]) -> String {
+    digest.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn main() {
+    let mut rt = Runtime::new();
+    let compress = compile(&mut rt);
+
+    for msg in std::env::args().skip(1) {
+        println!("{}  {:?}", hex(&sha256(compress, msg.as_bytes())), msg);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_sha256() {
+        let mut rt = Runtime::new();
+        let compress = compile(&mut rt);
+
+        assert_eq!(
+            hex(&sha256(compress, b"")),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            hex(&sha256(compress, b"abc")),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            hex(&sha256(
+                compress,
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"
+            )),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+    }
+}
@@ -6,46 +6,53 @@
 //! Fibonacci sequence.
 //!
 //! The `TinyVm` implements a simple _just-in-time (JIT)_ compiler to demonstrate the
-//! [`juicebox_asm`] crate. Additionally, it implements a reference _interpreter_.
+//! [`juicebox_asm`] crate. Additionally, it implements a reference _interpreter_. The
+//! [`differential`] harness runs random guest programs with both and cross-checks the results,
+//! eg to validate new instruction lowerings of the JIT.
+//!
+//! With [`TinyVm::enable_debugger`] the JIT emits a patchable breakpoint site before each guest
+//! instruction. Breakpoints are set by hot-patching the sites with a jump to an exit stub, and
+//! [`TinyVm::step`] executes a single guest instruction.
+//!
+//! [`TinyVm::snapshot`] serializes the guest state together with the code of the JIT cache, which
+//! [`TinyVm::restore`] installs again, eg in another process, without re-translating.
 //!
 //! ```
-//! fn main() {
-//!   let mut prog = Vec::new();
-//!   prog.push(TinyInsn::LoadImm(TinyReg::A, 100));
-//!   prog.push(TinyInsn::Add(TinyReg::B, TinyReg::A));
-//!   prog.push(TinyInsn::Addi(TinyReg::C, 100));
-//!   prog.push(TinyInsn::Halt);
+//! let mut prog = Vec::new();
+//! prog.push(TinyInsn::LoadImm(TinyReg::A, 100));
+//! prog.push(TinyInsn::Add(TinyReg::B, TinyReg::A));
+//! prog.push(TinyInsn::Addi(TinyReg::C, 100));
+//! prog.push(TinyInsn::Halt);
 //!
-//!   let mut vm = TinyVm::new(prog);
-//!   vm.interp();
+//! let mut vm = TinyVm::new(prog);
+//! vm.interp();
 //!
-//!   assert_eq!(100, vm.read_reg(TinyReg::A));
-//!   assert_eq!(100, vm.read_reg(TinyReg::B));
-//!   assert_eq!(100, vm.read_reg(TinyReg::C));
-//!   assert_eq!(4, vm.icnt);
-//!   assert_eq!(4, vm.pc);
+//! assert_eq!(100, vm.read_reg(TinyReg::A));
+//! assert_eq!(100, vm.read_reg(TinyReg::B));
+//! assert_eq!(100, vm.read_reg(TinyReg::C));
+//! assert_eq!(4, vm.icnt);
+//! assert_eq!(4, vm.pc);
 //!
-//!   vm.pc = 0;
-//!   vm.jit();
+//! vm.pc = 0;
+//! vm.jit();
 //!
-//!   assert_eq!(100, vm.read_reg(TinyReg::A));
-//!   assert_eq!(200, vm.read_reg(TinyReg::B));
-//!   assert_eq!(200, vm.read_reg(TinyReg::C));
-//!   assert_eq!(8, vm.icnt);
-//!   assert_eq!(4, vm.pc);
-//! }
+//! assert_eq!(100, vm.read_reg(TinyReg::A));
+//! assert_eq!(200, vm.read_reg(TinyReg::B));
+//! assert_eq!(200, vm.read_reg(TinyReg::C));
+//! assert_eq!(8, vm.icnt);
+//! assert_eq!(4, vm.pc);
 //! ```
 
-use juicebox_asm::insn::*;
-use juicebox_asm::Runtime;
-use juicebox_asm::{Asm, Imm16, Imm64, Mem16, Reg16, Reg64};
+use std::collections::{HashMap, HashSet};
+
+use juicebox_asm::prelude::*;
 
 /// A guest physical address.
 pub struct PhysAddr(pub u16);
 
-impl Into<usize> for PhysAddr {
-    fn into(self) -> usize {
-        self.0 as usize
+impl From<PhysAddr> for usize {
+    fn from(paddr: PhysAddr) -> usize {
+        paddr.0 as usize
     }
 }
 
@@ -62,6 +69,12 @@ impl TinyReg {
     fn idx(&self) -> usize {
         *self as usize
     }
+
+    fn from_idx(idx: u8) -> Option<TinyReg> {
+        [TinyReg::A, TinyReg::B, TinyReg::C]
+            .get(usize::from(idx))
+            .copied()
+    }
 }
 
 /// The instructions for the [`TinyVm`].
@@ -85,10 +98,99 @@ pub enum TinyInsn {
     BranchZero(TinyReg, usize),
 }
 
+impl TinyInsn {
+    /// Size in bytes of an encoded instruction, see [`TinyInsn::encode`].
+    const ENCODED_LEN: usize = 10;
+
+    /// Encode the instruction as opcode, register and operand bytes.
+    fn encode(&self) -> [u8; Self::ENCODED_LEN] {
+        let (opc, reg, op) = match *self {
+            TinyInsn::Halt => (0, 0, 0),
+            TinyInsn::LoadImm(a, imm) => (1, a.idx(), u64::from(imm)),
+            TinyInsn::Load(a, addr) => (2, a.idx(), u64::from(addr)),
+            TinyInsn::Store(a, addr) => (3, a.idx(), u64::from(addr)),
+            TinyInsn::Add(a, b) => (4, a.idx(), b.idx() as u64),
+            TinyInsn::Addi(a, imm) => (5, a.idx(), u64::from(imm as u16)),
+            TinyInsn::Branch(disp) => (6, 0, disp as u64),
+            TinyInsn::BranchZero(a, disp) => (7, a.idx(), disp as u64),
+        };
+
+        let mut bytes = [0; Self::ENCODED_LEN];
+        bytes[0] = opc;
+        bytes[1] = reg as u8;
+        bytes[2..].copy_from_slice(&op.to_le_bytes());
+        bytes
+    }
+
+    /// Decode an instruction encoded with [`TinyInsn::encode`].
+    fn decode(bytes: &[u8; Self::ENCODED_LEN]) -> Option<TinyInsn> {
+        let reg = TinyReg::from_idx(bytes[1]);
+        let op = u64::from_le_bytes(bytes[2..].try_into().unwrap());
+        let op16 = u16::try_from(op).ok();
+        let disp = usize::try_from(op).ok();
+
+        let insn = match bytes[0] {
+            0 => TinyInsn::Halt,
+            1 => TinyInsn::LoadImm(reg?, op16?),
+            2 => TinyInsn::Load(reg?, op16?),
+            3 => TinyInsn::Store(reg?, op16?),
+            4 => TinyInsn::Add(reg?, TinyReg::from_idx(u8::try_from(op).ok()?)?),
+            5 => TinyInsn::Addi(reg?, op16? as i16),
+            6 => TinyInsn::Branch(disp?),
+            7 => TinyInsn::BranchZero(reg?, disp?),
+            _ => return None,
+        };
+        Some(insn)
+    }
+}
+
+/// Magic bytes at the start of a [`TinyVm::snapshot`].
+const SNAPSHOT_MAGIC: &[u8; 4] = b"TVM1";
+
+/// Reader over the bytes of a snapshot, see [`TinyVm::restore`].
+struct SnapshotReader<'a>(&'a [u8]);
+
+impl<'a> SnapshotReader<'a> {
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], String> {
+        if self.0.len() < len {
+            return Err(String::from("snapshot truncated"));
+        }
+        let (bytes, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(bytes)
+    }
+
+    fn u64(&mut self) -> Result<u64, String> {
+        // UNWRAP: Exactly 8 bytes are read.
+        Ok(u64::from_le_bytes(self.bytes(8)?.try_into().unwrap()))
+    }
+
+    fn usize(&mut self) -> Result<usize, String> {
+        usize::try_from(self.u64()?).map_err(|e| e.to_string())
+    }
+}
+
 /// Value returned from a [`JitFn`].
 #[repr(C)]
 struct JitRet(u64, u64);
 
+/// Flag set in `JitRet.1` when a [`JitFn`] exits at a breakpoint.
+const JIT_BREAK: u64 = 1 << 63;
+
+/// Size in bytes of a breakpoint site, large enough to be patched with a `jmp rel32`.
+const SITE_LEN: usize = 5;
+
+/// Reason the JIT stopped executing guest code.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum JitStop {
+    /// Executed a [`TinyInsn::Halt`] instruction.
+    Halt,
+    /// Hit a breakpoint, the instruction at the pc is not executed yet.
+    Breakpoint,
+    /// Executed a single instruction, see [`TinyVm::step`].
+    Step,
+}
+
 /// Function signature defining the simple JIT ABI used in this example.
 /// A `JitFn` represents the entry point to a jit compiled _basic block_ of the guest software.
 ///
@@ -102,6 +204,9 @@ struct JitRet(u64, u64);
 ///      JitRet(N, R): N!=0
 ///                    End of basic block, executed N instructions,
 ///                    must re-enter at `pc = R`.
+///      JitRet(N, R | JIT_BREAK):
+///                    Hit a breakpoint, executed N instructions,
+///                    must re-enter at `pc = R`.
 /// ```
 type JitFn = extern "C" fn(*mut u16, *mut u8) -> JitRet;
 
@@ -127,6 +232,20 @@ pub struct TinyVm {
     jit_cache: Vec<Option<JitFn>>,
     /// JIT runtime maintaining the host pages containing the jitted guest code.
     rt: Runtime,
+    /// Guest PC and code of the jitted _basic blocks_ in the `jit_cache`, kept to snapshot the
+    /// cache. The code is position independent, hence it can be installed at any address.
+    jit_code: Vec<(usize, Vec<u8>)>,
+
+    // -- Debugger state.
+    /// Emit breakpoint sites when translating guest _basic blocks_.
+    debug: bool,
+    /// Guest PCs with a breakpoint.
+    breakpoints: HashSet<usize>,
+    /// Breakpoint sites in the jitted host code together with their exit stub, by guest PC. A
+    /// guest PC may be covered by multiple translated _basic blocks_.
+    sites: HashMap<usize, Vec<(*const u8, *const u8)>>,
+    /// Mapping of guest PCs to jitted host code executing a single guest instruction.
+    step_cache: Vec<Option<JitFn>>,
 }
 
 impl TinyVm {
@@ -142,10 +261,16 @@ impl TinyVm {
             pc: 0,
             icnt: 0,
             // -- JIT state.
+            step_cache: jit_cache.clone(),
             jit_cache,
             rt: Runtime::new(),
             // Confifigure the runtime to generates perf meta data.
             //rt: Runtime::with_profile(),
+            jit_code: Vec::new(),
+            // -- Debugger state.
+            debug: false,
+            breakpoints: HashSet::new(),
+            sites: HashMap::new(),
         }
     }
 
@@ -189,6 +314,99 @@ impl TinyVm {
         );
     }
 
+    /// Serialize the guest state together with the code of the JIT cache.
+    ///
+    /// All numbers are stored in little endian:
+    ///
+    /// ```text
+    /// magic "TVM1"
+    /// pc, icnt          : u64
+    /// regs              : [u16; 3]
+    /// imem length       : u64, followed by the encoded instructions
+    /// dmem              : [u8; 0x1_0000 + 1]
+    /// jit cache length  : u64, followed by per basic block:
+    ///     guest pc, code length : u64, followed by the code
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the debugger is enabled, as the jitted code is patched with breakpoints.
+    pub fn snapshot(&self) -> Vec<u8> {
+        assert!(!self.debug, "Snapshot with enabled debugger not supported");
+
+        let mut buf = Vec::new();
+        buf.extend_from_slice(SNAPSHOT_MAGIC);
+        buf.extend_from_slice(&(self.pc as u64).to_le_bytes());
+        buf.extend_from_slice(&(self.icnt as u64).to_le_bytes());
+        for reg in self.regs {
+            buf.extend_from_slice(&reg.to_le_bytes());
+        }
+        buf.extend_from_slice(&(self.imem.len() as u64).to_le_bytes());
+        for insn in &self.imem {
+            buf.extend_from_slice(&insn.encode());
+        }
+        buf.extend_from_slice(&self.dmem);
+        buf.extend_from_slice(&(self.jit_code.len() as u64).to_le_bytes());
+        for (pc, code) in &self.jit_code {
+            buf.extend_from_slice(&(*pc as u64).to_le_bytes());
+            buf.extend_from_slice(&(code.len() as u64).to_le_bytes());
+            buf.extend_from_slice(code);
+        }
+        buf
+    }
+
+    /// Create a new [`TinyVm`] from a [`TinyVm::snapshot`] and install the jitted code of the
+    /// snapshot into the JIT cache.
+    ///
+    /// The jitted code is trusted, hence the snapshot must come from a trusted source.
+    pub fn restore(snapshot: &[u8]) -> Result<Self, String> {
+        let mut rd = SnapshotReader(snapshot);
+
+        if rd.bytes(SNAPSHOT_MAGIC.len())? != SNAPSHOT_MAGIC {
+            return Err(String::from("invalid snapshot magic"));
+        }
+        let pc = rd.usize()?;
+        let icnt = rd.usize()?;
+        let mut regs = [0; 3];
+        for reg in &mut regs {
+            // UNWRAP: Exactly 2 bytes are read.
+            *reg = u16::from_le_bytes(rd.bytes(2)?.try_into().unwrap());
+        }
+        let imem = (0..rd.usize()?)
+            .map(|_| {
+                // UNWRAP: Exactly ENCODED_LEN bytes are read.
+                let bytes = rd.bytes(TinyInsn::ENCODED_LEN)?.try_into().unwrap();
+                TinyInsn::decode(bytes).ok_or_else(|| String::from("invalid instruction"))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        let mut vm = TinyVm::new(imem);
+        vm.pc = pc;
+        vm.icnt = icnt;
+        vm.regs = regs;
+        let dmem = rd.bytes(vm.dmem.len())?;
+        vm.dmem.copy_from_slice(dmem);
+
+        for _ in 0..rd.usize()? {
+            let pc = rd.usize()?;
+            let len = rd.usize()?;
+            let code = rd.bytes(len)?;
+            if pc >= vm.imem.len() || code.is_empty() {
+                return Err(format!("invalid jit cache entry at pc={}", pc));
+            }
+
+            let bb_fn = unsafe { vm.rt.add_code::<JitFn>(code) };
+            vm.jit_cache[pc] = Some(bb_fn);
+            vm.jit_code.push((pc, code.to_vec()));
+        }
+
+        if rd.0.is_empty() {
+            Ok(vm)
+        } else {
+            Err(String::from("trailing bytes in snapshot"))
+        }
+    }
+
     /// Run in interpreter mode until the next [`TinyInsn::Halt`] instruction is hit.
     pub fn interp(&mut self) {
         'outer: loop {
@@ -233,44 +451,125 @@ impl TinyVm {
         }
     }
 
-    /// Run in JIT mode until the next [`TinyInsn::Halt`] instruction is hit. Translate guest
-    /// _basic blocks_ on demand.
-    pub fn jit(&mut self) {
-        'outer: loop {
+    /// Run in JIT mode until the next [`TinyInsn::Halt`] instruction or breakpoint is hit.
+    /// Translate guest _basic blocks_ on demand.
+    pub fn jit(&mut self) -> JitStop {
+        loop {
             let bb_fn = if let Some(bb_fn) = self.jit_cache[self.pc] {
                 bb_fn
             } else {
-                let bb_fn = self.translate_next_bb();
+                let bb_fn = self.translate_next_bb(false);
                 self.jit_cache[self.pc] = Some(bb_fn);
                 //println!("[0x{:02x}] translated bb at {:p}", self.pc, bb_fn);
                 bb_fn
             };
 
-            match bb_fn(self.regs.as_mut_ptr(), self.dmem.as_mut_ptr()) {
-                // HALT instruction hit.
-                JitRet(0, insn) => {
-                    self.pc += insn as usize;
-                    self.icnt += insn as usize;
-                    break 'outer;
-                }
-                // End of basic block, re-enter.
-                JitRet(insn, reenter_pc) => {
-                    self.pc = reenter_pc as usize;
-                    self.icnt += insn as usize;
-                }
+            if let Some(stop) = self.run_bb(bb_fn) {
+                return stop;
+            }
+        }
+    }
+
+    /// Execute the jitted _basic block_ `bb_fn` and update the pc and instruction counter.
+    /// Returns the reason to stop, or `None` when execution continues at the next bb.
+    fn run_bb(&mut self, bb_fn: JitFn) -> Option<JitStop> {
+        match bb_fn(self.regs.as_mut_ptr(), self.dmem.as_mut_ptr()) {
+            // Breakpoint hit.
+            JitRet(insn, break_pc) if break_pc & JIT_BREAK != 0 => {
+                self.pc = (break_pc & !JIT_BREAK) as usize;
+                self.icnt += insn as usize;
+                Some(JitStop::Breakpoint)
             }
+            // HALT instruction hit.
+            JitRet(0, insn) => {
+                self.pc += insn as usize;
+                self.icnt += insn as usize;
+                Some(JitStop::Halt)
+            }
+            // End of basic block, re-enter.
+            JitRet(insn, reenter_pc) => {
+                self.pc = reenter_pc as usize;
+                self.icnt += insn as usize;
+                None
+            }
+        }
+    }
+
+    /// Enable the guest debugger. Guest _basic blocks_ translated from now on contain a breakpoint
+    /// site before each guest instruction, hence previous translations are discarded.
+    pub fn enable_debugger(&mut self) {
+        self.debug = true;
+        self.jit_cache.fill(None);
+        self.jit_code.clear();
+    }
+
+    /// Set a breakpoint at the guest `pc`, the JIT stops before executing the instruction.
+    ///
+    /// The breakpoint sites of already translated _basic blocks_ are hot-patched with a jump to
+    /// their exit stub.
+    pub fn set_breakpoint(&mut self, pc: usize) {
+        assert!(self.debug, "Debugger not enabled");
+        self.breakpoints.insert(pc);
+        for &(site, stub) in self.sites.get(&pc).into_iter().flatten() {
+            unsafe { self.rt.redirect(site, stub) };
+        }
+    }
+
+    /// Clear the breakpoint at the guest `pc`.
+    pub fn clear_breakpoint(&mut self, pc: usize) {
+        self.breakpoints.remove(&pc);
+        for &(site, _) in self.sites.get(&pc).into_iter().flatten() {
+            // A jump to the next instruction restores the fall-through of the site.
+            unsafe { self.rt.redirect(site, site.add(SITE_LEN)) };
+        }
+    }
+
+    /// Execute the single guest instruction at the current pc in JIT mode, ignoring a breakpoint
+    /// at the pc.
+    pub fn step(&mut self) -> JitStop {
+        let bb_fn = if let Some(bb_fn) = self.step_cache[self.pc] {
+            bb_fn
+        } else {
+            let bb_fn = self.translate_next_bb(true);
+            self.step_cache[self.pc] = Some(bb_fn);
+            bb_fn
+        };
+
+        self.run_bb(bb_fn).unwrap_or(JitStop::Step)
+    }
+
+    /// Continue in JIT mode after a breakpoint, until the next [`TinyInsn::Halt`] instruction or
+    /// breakpoint is hit.
+    pub fn cont(&mut self) -> JitStop {
+        match self.step() {
+            JitStop::Halt => JitStop::Halt,
+            _ => self.jit(),
         }
     }
 
-    #[cfg(all(any(target_arch = "x86_64", target_os = "linux")))]
-    /// Translate the bb at the current pc and return a JitFn pointer to it.
-    fn translate_next_bb(&mut self) -> JitFn {
+    #[cfg(any(target_arch = "x86_64", target_os = "linux"))]
+    /// Translate the bb at the current pc and return a JitFn pointer to it. In `step` mode the
+    /// bb ends after the first instruction.
+    fn translate_next_bb(&mut self, step: bool) -> JitFn {
         let mut bb = Asm::new();
         let mut pc = self.pc;
 
+        // Guest PC and executed instructions of the breakpoint sites.
+        let mut breaks = Vec::new();
+
         'outer: loop {
             let insn = self.imem[pc];
 
+            if self.debug && !step {
+                // Patchable breakpoint site, recorded in the trap table with the guest PC as code.
+                breaks.push((pc, (pc - self.pc) as u64));
+                bb.trapping(pc as u32, |bb| {
+                    for _ in 0..SITE_LEN {
+                        bb.nop();
+                    }
+                });
+            }
+
             pc = pc.wrapping_add(1);
 
             // JIT abi: JitFn -> JitRet
@@ -307,15 +606,16 @@ impl TinyVm {
                     bb.mov(reg_op(a), Imm16::from(imm));
                 }
                 TinyInsn::Load(a, addr) => {
-                    bb.mov(Reg16::ax, mem_op(addr));
+                    // Widening load, avoids a partial register write to ax.
+                    bb.movzx(Reg32::eax, mem_op(addr));
                     bb.mov(reg_op(a), Reg16::ax);
                 }
                 TinyInsn::Store(a, addr) => {
-                    bb.mov(Reg16::ax, reg_op(a));
+                    bb.movzx(Reg32::eax, reg_op(a));
                     bb.mov(mem_op(addr), Reg16::ax);
                 }
                 TinyInsn::Add(a, b) => {
-                    bb.mov(Reg16::ax, reg_op(b));
+                    bb.movzx(Reg32::eax, reg_op(b));
                     bb.add(reg_op(a), Reg16::ax);
                 }
                 TinyInsn::Addi(a, imm) => {
@@ -341,9 +641,46 @@ impl TinyVm {
                     break 'outer;
                 }
             }
+
+            if step {
+                bb.mov(Reg64::rax, Imm64::from(bb_icnt()));
+                bb.mov(Reg64::rdx, Imm64::from(reenter_pc(pc)));
+                bb.ret();
+                break 'outer;
+            }
+        }
+
+        // Exit stubs of the breakpoint sites, in the order of the sites.
+        let stubs: Vec<_> = breaks
+            .into_iter()
+            .map(|(pc, icnt)| {
+                let off = bb.len();
+                bb.mov(Reg64::rax, Imm64::from(icnt));
+                bb.mov(Reg64::rdx, Imm64::from(pc as u64 | JIT_BREAK));
+                bb.ret();
+                off
+            })
+            .collect();
+        let traps = bb.traps().clone();
+
+        let code = bb.into_code();
+        let bb_fn = unsafe { self.rt.add_code::<JitFn>(&code) };
+        if !step {
+            self.jit_code.push((self.pc, code));
         }
 
-        unsafe { self.rt.add_code::<JitFn>(bb.into_code()) }
+        // Register the sites and patch the ones with a breakpoint.
+        let base = bb_fn as *const u8;
+        for (site, stub) in traps.sites().iter().zip(stubs) {
+            let pc = site.code as usize;
+            let (site, stub) = unsafe { (base.add(site.off), base.add(stub)) };
+            self.sites.entry(pc).or_default().push((site, stub));
+            if self.breakpoints.contains(&pc) {
+                unsafe { self.rt.redirect(site, stub) };
+            }
+        }
+
+        bb_fn
     }
 }
 
@@ -359,12 +696,14 @@ impl Fixup {
     }
 
     /// Bind the `Fixup` to the current location of `prog` and resolve the `Fixup`.
-    pub fn bind(self, prog: &mut Vec<TinyInsn>) {
+    pub fn bind(self, prog: &mut [TinyInsn]) {
         let plen = prog.len();
-        let insn = prog.get_mut(self.pc).expect(&format!(
-            "Trying to apply Fixup, but Fixup is out of range pc={} prog.len={}",
-            self.pc, plen
-        ));
+        let insn = prog.get_mut(self.pc).unwrap_or_else(|| {
+            panic!(
+                "Trying to apply Fixup, but Fixup is out of range pc={} prog.len={}",
+                self.pc, plen
+            )
+        });
 
         match insn {
             TinyInsn::Branch(disp) | TinyInsn::BranchZero(_, disp) => {
@@ -377,6 +716,9 @@ impl Fixup {
     }
 }
 
+/// Guest PC of the loop head in the program generated by [`make_tinyvm_fib`].
+pub const FIB_LOOP_PC: usize = 6;
+
 /// Generate a guest program to compute the fiibonacci sequence for `n`.
 pub fn make_tinyvm_fib(start_n: u16) -> Vec<TinyInsn> {
     // Reference implementation:
@@ -418,6 +760,7 @@ pub fn make_tinyvm_fib(start_n: u16) -> Vec<TinyInsn> {
 
     // Create loop_start label.
     let loop_start = prog.len();
+    debug_assert_eq!(loop_start, FIB_LOOP_PC);
 
     // Create fixup to capture PC that need to be patched later.
     let end_fixup = Fixup::new(prog.len());
@@ -497,22 +840,169 @@ pub fn make_tinyvm_jit_perf() -> Vec<TinyInsn> {
     prog
 }
 
+/// A minimal xorshift pseudo random number generator, to generate reproducible guest programs.
+pub struct Rng(u64);
+
+impl Rng {
+    /// Create a new `Rng` from the `seed`.
+    pub fn new(seed: u64) -> Self {
+        // The xorshift state must not be zero.
+        Rng(seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1)
+    }
+
+    /// Get the next random number.
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Get the next random number in the range `[0:n)`.
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// Get a random guest register.
+    pub fn reg(&mut self) -> TinyReg {
+        [TinyReg::A, TinyReg::B, TinyReg::C][self.below(3)]
+    }
+}
+
+/// Generate a random guest program with `len` instructions followed by a halt.
+///
+/// Branches only jump forward, hence every program terminates. Memory accesses use a small set of
+/// addresses including the unaligned access to `0xffff`, such that loads observe prior stores.
+pub fn make_tinyvm_random(rng: &mut Rng, len: usize) -> Vec<TinyInsn> {
+    const ADDRS: [u16; 4] = [0x0000, 0x0001, 0x1234, 0xffff];
+
+    let mut prog = Vec::with_capacity(len + 1);
+    for pc in 0..len {
+        // Branch targets in the range `(pc:len]`, where `len` is the final halt.
+        let target = pc + 1 + rng.below(len - pc);
+        let insn = match rng.below(8) {
+            0 => TinyInsn::LoadImm(rng.reg(), rng.next_u64() as u16),
+            1 => TinyInsn::Load(rng.reg(), ADDRS[rng.below(ADDRS.len())]),
+            2 => TinyInsn::Store(rng.reg(), ADDRS[rng.below(ADDRS.len())]),
+            3 => TinyInsn::Add(rng.reg(), rng.reg()),
+            4 => TinyInsn::Addi(rng.reg(), rng.next_u64() as i16),
+            5 => TinyInsn::Branch(target),
+            6 => TinyInsn::BranchZero(rng.reg(), target),
+            _ if rng.below(4) == 0 => TinyInsn::Halt,
+            _ => TinyInsn::Addi(rng.reg(), -1),
+        };
+        prog.push(insn);
+    }
+    prog.push(TinyInsn::Halt);
+    prog
+}
+
+/// Run the random guest program generated from `seed` with the interpreter and the jit, and
+/// cross-check the final VM states. Returns the program on a mismatch.
+pub fn differential(seed: u64, len: usize) -> Result<(), Vec<TinyInsn>> {
+    let prog = make_tinyvm_random(&mut Rng::new(seed), len);
+
+    let mut interp = TinyVm::new(prog.clone());
+    interp.interp();
+    let mut jit = TinyVm::new(prog.clone());
+    jit.jit();
+
+    let same = interp.regs == jit.regs
+        && interp.pc == jit.pc
+        && interp.icnt == jit.icnt
+        && interp.dmem[..] == jit.dmem[..];
+    if same {
+        Ok(())
+    } else {
+        interp.dump();
+        jit.dump();
+        Err(prog)
+    }
+}
+
 fn main() {
-    let use_jit = match std::env::args().nth(1) {
-        Some(a) if a == "-h" || a == "--help" => {
-            println!("Usage: tiny_vm [mode]");
-            println!("");
+    let mode = std::env::args().nth(1);
+    match mode.as_deref() {
+        Some("-h" | "--help") => {
+            println!("Usage: tiny_vm [mode] [file]");
+            println!();
             println!("Options:");
-            println!("    mode    if mode is 'jit' then run in jit mode, else in interpreter mode");
+            println!("    mode    if mode is 'jit' then run in jit mode, if mode is 'diff' then");
+            println!("            cross-check the jit against the interpreter on random programs,");
+            println!("            if mode is 'debug' then run in jit mode with a breakpoint,");
+            println!("            if mode is 'save' then run in jit mode and save a snapshot to");
+            println!("            file, if mode is 'load' then restore the snapshot from file");
+            println!("            and re-run the program from the jit cache,");
+            println!("            else run in interpreter mode");
+            println!("    file    snapshot file for the 'save' and 'load' modes");
             std::process::exit(0);
         }
-        Some(a) if a == "jit" => true,
-        _ => false,
-    };
+        Some("diff") => {
+            println!("Run differential mode..");
+            for seed in 0..10_000 {
+                if let Err(prog) = differential(seed, 64) {
+                    println!("Mismatch for seed {}:", seed);
+                    for (pc, insn) in prog.iter().enumerate() {
+                        println!("  [0x{:02x}] {:?}", pc, insn);
+                    }
+                    std::process::exit(1);
+                }
+            }
+            println!("No mismatch found");
+            return;
+        }
+        Some("debug") => {
+            println!("Run in jit mode with a breakpoint at the fib loop head..");
+            let mut vm = TinyVm::new(make_tinyvm_fib(5));
+            vm.enable_debugger();
+            vm.set_breakpoint(FIB_LOOP_PC);
+
+            let mut stop = vm.jit();
+            while stop == JitStop::Breakpoint {
+                println!(
+                    "Breakpoint [0x{:02x}] {:?}: n={}",
+                    vm.pc,
+                    vm.imem[vm.pc],
+                    vm.read_reg(TinyReg::C)
+                );
+                stop = vm.cont();
+            }
+            vm.dump();
+            return;
+        }
+        Some("save") => {
+            let file = std::env::args().nth(2).expect("Missing snapshot file");
+            println!("Run in jit mode and save snapshot to {}..", file);
+            let mut vm = TinyVm::new(make_tinyvm_fib(42));
+            vm.jit();
+            vm.dump();
+            std::fs::write(&file, vm.snapshot()).expect("Failed to write snapshot");
+            return;
+        }
+        Some("load") => {
+            let file = std::env::args().nth(2).expect("Missing snapshot file");
+            println!("Restore snapshot from {}..", file);
+            let snapshot = std::fs::read(&file).expect("Failed to read snapshot");
+            let mut vm = TinyVm::restore(&snapshot).unwrap_or_else(|e| {
+                println!("Invalid snapshot: {}", e);
+                std::process::exit(1);
+            });
+            vm.dump();
+
+            let cached = vm.jit_code.len();
+            println!("Re-run in jit mode from {} cached bbs..", cached);
+            vm.pc = 0;
+            vm.jit();
+            vm.dump();
+            println!("Translated {} new bbs", vm.jit_code.len() - cached);
+            return;
+        }
+        _ => {}
+    }
 
     let mut vm = TinyVm::new(make_tinyvm_fib(42));
 
-    if use_jit {
+    if mode.as_deref() == Some("jit") {
         println!("Run in jit mode..");
         vm.jit();
     } else {
@@ -676,6 +1166,26 @@ mod test {
         assert_eq!(6, vm.pc);
     }
 
+    #[test]
+    fn test_differential() {
+        for seed in 0..500 {
+            assert!(differential(seed, 32).is_ok(), "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn test_random_terminates() {
+        let mut rng = Rng::new(0);
+        let prog = make_tinyvm_random(&mut rng, 128);
+        assert_eq!(prog.len(), 129);
+        assert_eq!(prog.last(), Some(&TinyInsn::Halt));
+        for (pc, insn) in prog.iter().enumerate() {
+            if let TinyInsn::Branch(disp) | TinyInsn::BranchZero(_, disp) = insn {
+                assert!(*disp > pc && *disp < prog.len());
+            }
+        }
+    }
+
     #[test]
     fn test_mixed() {
         let mut prog = Vec::new();
@@ -702,4 +1212,103 @@ mod test {
         assert_eq!(8, vm.icnt);
         assert_eq!(4, vm.pc);
     }
+
+    #[test]
+    fn test_debug_breakpoint() {
+        let mut vm = TinyVm::new(make_tinyvm_fib(5));
+        vm.enable_debugger();
+        vm.set_breakpoint(FIB_LOOP_PC);
+
+        // The loop head is hit once per iteration and once at the loop exit.
+        let mut n = Vec::new();
+        let mut stop = vm.jit();
+        while stop == JitStop::Breakpoint {
+            assert_eq!(FIB_LOOP_PC, vm.pc);
+            n.push(vm.read_reg(TinyReg::C));
+            stop = vm.cont();
+        }
+        assert_eq!(JitStop::Halt, stop);
+        assert_eq!(n, [5, 4, 3, 2, 1, 0]);
+        assert_eq!(5, vm.read_reg(TinyReg::A));
+
+        let mut interp = TinyVm::new(make_tinyvm_fib(5));
+        interp.interp();
+        assert_eq!(interp.icnt, vm.icnt);
+        assert_eq!(interp.pc, vm.pc);
+    }
+
+    #[test]
+    fn test_debug_patch_translated() {
+        let mut vm = TinyVm::new(make_tinyvm_fib(5));
+        vm.enable_debugger();
+        vm.set_breakpoint(FIB_LOOP_PC);
+        assert_eq!(JitStop::Breakpoint, vm.jit());
+        assert_eq!(JitStop::Breakpoint, vm.cont());
+
+        // Set a breakpoint in the middle of the already translated loop body.
+        vm.set_breakpoint(FIB_LOOP_PC + 4);
+        vm.clear_breakpoint(FIB_LOOP_PC);
+        assert_eq!(JitStop::Breakpoint, vm.cont());
+        assert_eq!(FIB_LOOP_PC + 4, vm.pc);
+        assert_eq!(4, vm.read_reg(TinyReg::C));
+
+        vm.clear_breakpoint(FIB_LOOP_PC + 4);
+        assert_eq!(JitStop::Halt, vm.cont());
+        assert_eq!(5, vm.read_reg(TinyReg::A));
+    }
+
+    #[test]
+    fn test_debug_step() {
+        let mut interp = TinyVm::new(make_tinyvm_jit_test());
+        interp.interp();
+
+        let mut vm = TinyVm::new(make_tinyvm_jit_test());
+        let mut steps = 0;
+        while vm.step() == JitStop::Step {
+            steps += 1;
+        }
+        assert_eq!(interp.icnt, steps + 1);
+        assert_eq!(interp.icnt, vm.icnt);
+        assert_eq!(interp.pc, vm.pc);
+        assert_eq!(interp.regs, vm.regs);
+    }
+
+    #[test]
+    fn test_snapshot_restore() {
+        let mut vm = TinyVm::new(make_tinyvm_fib(42));
+        vm.write_mem(PhysAddr(0x8000), 0xf00d);
+        vm.jit();
+        let snapshot = vm.snapshot();
+
+        let mut restored = TinyVm::restore(&snapshot).unwrap();
+        assert_eq!(vm.imem, restored.imem);
+        assert_eq!(vm.regs, restored.regs);
+        assert_eq!(vm.pc, restored.pc);
+        assert_eq!(vm.icnt, restored.icnt);
+        assert_eq!(vm.dmem[..], restored.dmem[..]);
+        assert_eq!(snapshot, restored.snapshot());
+
+        // Re-run from the restored jit cache without translating any bb.
+        let cached = restored.jit_code.len();
+        restored.pc = 0;
+        restored.jit();
+        assert_eq!(cached, restored.jit_code.len());
+        assert_eq!(vm.read_reg(TinyReg::A), restored.read_reg(TinyReg::A));
+        assert_eq!(2 * vm.icnt, restored.icnt);
+    }
+
+    #[test]
+    fn test_snapshot_insn_encoding() {
+        for insn in make_tinyvm_random(&mut Rng::new(0), 256) {
+            assert_eq!(Some(insn), TinyInsn::decode(&insn.encode()));
+        }
+    }
+
+    #[test]
+    fn test_snapshot_invalid() {
+        let snapshot = TinyVm::new(make_tinyvm_fib(1)).snapshot();
+        assert!(TinyVm::restore(&snapshot).is_ok());
+        assert!(TinyVm::restore(&snapshot[..snapshot.len() - 1]).is_err());
+        assert!(TinyVm::restore(b"TVM0").is_err());
+    }
 }
@@ -0,0 +1,167 @@
+//! Fuzz the encoder by interpreting the input as a sequence of instructions with operands.
+//!
+//! Only valid operand combinations are generated, hence any panic is a bug. The crate has no
+//! built-in decoder, hence the output is not decoded. Instead each instruction is emitted with the
+//! trait API and with the type erased [`Asm::emit_insn`], which dispatches on the supported
+//! instruction forms, and both encodings must be identical.
+
+#![no_main]
+
+use juicebox_asm::insn::*;
+use juicebox_asm::{Asm, Imm16, Imm64, Imm8, Label, Mem16, Mem64, Mem8, Operand, Reg32, Reg64};
+use libfuzzer_sys::fuzz_target;
+
+/// Simple reader handing out the fuzz input byte by byte, returns zeros once exhausted.
+struct Input<'a>(&'a [u8]);
+
+impl Input<'_> {
+    fn u8(&mut self) -> u8 {
+        match self.0.split_first() {
+            Some((&b, rest)) => {
+                self.0 = rest;
+                b
+            }
+            None => 0,
+        }
+    }
+
+    fn u16(&mut self) -> u16 {
+        u16::from_le_bytes([self.u8(), self.u8()])
+    }
+
+    fn u64(&mut self) -> u64 {
+        u64::from(self.u16()) | (u64::from(self.u16()) << 16) | (u64::from(self.u16()) << 32)
+    }
+
+    fn reg64(&mut self) -> Reg64 {
+        use Reg64::*;
+        [
+            rax, rcx, rdx, rbx, rsp, rbp, rsi, rdi, r8, r9, r10, r11, r12, r13, r14, r15,
+        ][usize::from(self.u8() & 0xf)]
+    }
+
+    fn reg32(&mut self) -> Reg32 {
+        use Reg32::*;
+        [
+            eax, ecx, edx, ebx, esp, ebp, esi, edi, r8d, r9d, r10d, r11d, r12d, r13d, r14d, r15d,
+        ][usize::from(self.u8() & 0xf)]
+    }
+
+    /// Get the memory operand parts (mode, base, index, scale, disp), the index is never `rsp`,
+    /// which is not encodable as index register.
+    fn mem(&mut self) -> (u8, Reg64, Reg64, u8, i32) {
+        let mode = self.u8() % 4;
+        let base = self.reg64();
+        let index = match self.reg64() {
+            Reg64::rsp => Reg64::rbx,
+            index => index,
+        };
+        let scale = 1 << (self.u8() & 3);
+        (mode, base, index, scale, self.u16() as i16 as i32)
+    }
+}
+
+macro_rules! mem {
+    ($ty:ident, $input:expr) => {{
+        match $input.mem() {
+            (0, base, _, _, _) => $ty::indirect(base),
+            (1, base, _, _, disp) => $ty::indirect_disp(base, disp),
+            (2, base, index, _, _) => $ty::indirect_base_index(base, index),
+            (_, base, index, scale, disp) => {
+                $ty::indirect_base_index_scale_disp(base, index, scale, disp)
+            }
+        }
+    }};
+}
+
+/// Emit the instruction `insn` with the operands with the trait API into `asm` and with the type
+/// erased [`Asm::emit_insn`] into `erased`.
+macro_rules! insn {
+    ($asm:expr, $erased:expr, $insn:ident) => {{
+        $asm.$insn();
+        $erased.emit_insn(stringify!($insn), &mut []).unwrap();
+    }};
+    ($asm:expr, $erased:expr, $insn:ident, $op1:expr) => {{
+        let op1 = $op1;
+        $asm.$insn(op1);
+        $erased
+            .emit_insn(stringify!($insn), &mut [op1.into()])
+            .unwrap();
+    }};
+    ($asm:expr, $erased:expr, $insn:ident, $op1:expr, $op2:expr) => {{
+        let (op1, op2) = ($op1, $op2);
+        $asm.$insn(op1, op2);
+        $erased
+            .emit_insn(stringify!($insn), &mut [op1.into(), op2.into()])
+            .unwrap();
+    }};
+}
+
+fuzz_target!(|data: &[u8]| {
+    let mut input = Input(data);
+    let mut asm = Asm::new();
+    let mut erased = Asm::new();
+    let mut labels: Vec<(Label, Label)> = (0..4).map(|_| (Label::new(), Label::new())).collect();
+    let mut bound = [false; 4];
+
+    while !input.0.is_empty() {
+        match input.u8() % 20 {
+            0 => insn!(asm, erased, mov, input.reg64(), input.reg64()),
+            1 => insn!(asm, erased, mov, input.reg32(), input.reg32()),
+            2 => insn!(asm, erased, mov, input.reg64(), Imm64::from(input.u64())),
+            3 => insn!(asm, erased, mov, mem!(Mem64, input), input.reg64()),
+            4 => insn!(asm, erased, mov, input.reg64(), mem!(Mem64, input)),
+            5 => insn!(
+                asm,
+                erased,
+                mov,
+                mem!(Mem16, input),
+                Imm16::from(input.u16())
+            ),
+            6 => insn!(asm, erased, add, input.reg64(), input.reg64()),
+            7 => insn!(asm, erased, add, mem!(Mem8, input), Imm8::from(input.u8())),
+            8 => insn!(asm, erased, sub, input.reg64(), input.reg64()),
+            9 => insn!(asm, erased, cmp, mem!(Mem8, input), Imm8::from(input.u8())),
+            10 => insn!(asm, erased, test, input.reg32(), input.reg32()),
+            11 => insn!(asm, erased, inc, mem!(Mem64, input)),
+            12 => insn!(asm, erased, dec, input.reg64()),
+            13 => insn!(asm, erased, push, input.reg64()),
+            14 => insn!(asm, erased, pop, input.reg64()),
+            15 => insn!(asm, erased, call, input.reg64()),
+            16 => {
+                let (label, erased_label) = &mut labels[usize::from(input.u8() & 3)];
+                asm.jmp(label);
+                erased
+                    .emit_insn("jmp", &mut [Operand::Label(erased_label)])
+                    .unwrap();
+            }
+            17 => {
+                let (label, erased_label) = &mut labels[usize::from(input.u8() & 3)];
+                asm.jz(label);
+                erased
+                    .emit_insn("jz", &mut [Operand::Label(erased_label)])
+                    .unwrap();
+            }
+            18 => {
+                // A label can only be bound once.
+                let idx = usize::from(input.u8() & 3);
+                if !bound[idx] {
+                    asm.bind(&mut labels[idx].0);
+                    erased.bind(&mut labels[idx].1);
+                    bound[idx] = true;
+                }
+            }
+            _ => insn!(asm, erased, ret),
+        }
+    }
+
+    // Bind all remaining labels to resolve pending relocations.
+    for ((label, erased_label), _) in labels.iter_mut().zip(bound).filter(|(_, bound)| !bound) {
+        asm.bind(label);
+        erased.bind(erased_label);
+    }
+
+    // Without a decoder in the crate, the oracle of the encoding is the type erased emission,
+    // which dispatches on the supported instruction forms independently of the trait API.
+    assert_eq!(asm.into_code(), erased.into_code());
+});
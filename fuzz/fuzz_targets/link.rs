@@ -0,0 +1,122 @@
+//! Fuzz linking blobs of raw code with arbitrary relocation tables against labels and named
+//! entry points, and installing the result in the runtime, the code is never executed.
+//!
+//! Relocations and symbols violating the preconditions of `emit_blob` and `install_entries` are
+//! skipped, hence any panic is a bug.
+
+#![no_main]
+
+use juicebox_asm::{Asm, Label, Reloc, Runtime};
+use libfuzzer_sys::fuzz_target;
+
+/// Number of labels relocations refer to.
+const LABELS: usize = 4;
+
+/// Simple reader handing out the fuzz input byte by byte, returns zeros once exhausted.
+struct Input<'a>(&'a [u8]);
+
+impl Input<'_> {
+    fn u8(&mut self) -> u8 {
+        match self.0.split_first() {
+            Some((&b, rest)) => {
+                self.0 = rest;
+                b
+            }
+            None => 0,
+        }
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    let mut input = Input(data);
+    let mut asm = Asm::new();
+    let mut labels: Vec<Label> = (0..LABELS).map(|_| Label::new()).collect();
+    // Symbol table of the labels, the code offset each label is bound to.
+    let mut symbols: [Option<usize>; LABELS] = [None; LABELS];
+    // Relocation table, the code offset of each rel32 and the label it refers to.
+    let mut relocs: Vec<(usize, usize)> = Vec::new();
+    // Entry point table, the code offset of each entry point.
+    let mut entries: Vec<(String, usize)> = Vec::new();
+
+    // Locate the installed code by an entry point at its start.
+    asm.entry("start");
+
+    // Leave room for the trailing ret on the code page.
+    while !input.0.is_empty() && asm.len() < 4000 {
+        match input.u8() % 4 {
+            0 => {
+                let len = usize::from(input.u8() % 32);
+                let bytes: Vec<u8> = (0..len).map(|_| input.u8()).collect();
+
+                // Only in bound and non-overlapping relocations are valid. A blob refers to
+                // each label at most once, as the relocation borrows the label.
+                let mut table: Vec<(usize, usize)> = Vec::new();
+                for _ in 0..input.u8() % 4 {
+                    let off = usize::from(input.u8());
+                    let label = usize::from(input.u8()) % LABELS;
+                    let overlaps = table.iter().any(|&(o, _)| off < o + 4 && o < off + 4);
+                    let dup = table.iter().any(|&(_, l)| l == label);
+                    if off + 4 <= len && !overlaps && !dup {
+                        table.push((off, label));
+                    }
+                }
+
+                let base = asm.len();
+                let mut blob_relocs: Vec<Reloc> = labels
+                    .iter_mut()
+                    .enumerate()
+                    .filter_map(|(idx, label)| {
+                        let &(off, _) = table.iter().find(|&&(_, l)| l == idx)?;
+                        Some(Reloc::rel32(off, label))
+                    })
+                    .collect();
+                asm.emit_blob(&bytes, &mut blob_relocs);
+                relocs.extend(table.iter().map(|&(off, label)| (base + off, label)));
+            }
+            1 => {
+                // A label can only be bound once.
+                let idx = usize::from(input.u8()) % LABELS;
+                if symbols[idx].is_none() {
+                    symbols[idx] = Some(asm.len());
+                    asm.bind(&mut labels[idx]);
+                }
+            }
+            2 => {
+                let name = format!("entry{}", entries.len());
+                entries.push((name.clone(), asm.len()));
+                asm.entry(&name);
+            }
+            _ => asm.nop(),
+        }
+    }
+
+    // Bind all remaining labels to resolve pending relocations.
+    for (label, sym) in labels.iter_mut().zip(symbols.iter_mut()) {
+        if sym.is_none() {
+            *sym = Some(asm.len());
+            asm.bind(label);
+        }
+    }
+    // Entry points must not be at the end of the code.
+    asm.ret();
+    let len = asm.len();
+
+    let mut rt = Runtime::new();
+    let eps = rt.install_entries(asm);
+    // UNWRAP: Declared above.
+    let base = eps.resolve("start").unwrap();
+    let code = unsafe { std::slice::from_raw_parts(base, len) };
+
+    // Each relocation must be linked against the offset its label is bound to.
+    for (off, label) in relocs {
+        // UNWRAP: All labels are bound.
+        let target = symbols[label].unwrap();
+        let rel = i32::from_le_bytes(code[off..off + 4].try_into().unwrap());
+        assert_eq!(off as i64 + 4 + i64::from(rel), target as i64);
+    }
+
+    // Each entry point must resolve to its offset in the installed code.
+    for (name, off) in entries {
+        assert_eq!(eps.resolve(&name), Some(base.wrapping_add(off)));
+    }
+});
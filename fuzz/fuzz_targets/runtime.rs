@@ -0,0 +1,30 @@
+//! Fuzz adding code blocks of arbitrary sizes to the runtime, the code is never executed.
+//!
+//! Code blocks violating the preconditions of `add_code` are skipped, hence any panic is a bug.
+
+#![no_main]
+
+use juicebox_asm::Runtime;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let mut rt = Runtime::new();
+    let mut used = 0;
+    let mut data = data;
+
+    while let Some((&len, rest)) = data.split_first() {
+        let len = usize::from(len).min(rest.len());
+        let (code, rest) = rest.split_at(len);
+        data = rest;
+
+        if code.is_empty() || used + code.len() > 4096 {
+            continue;
+        }
+
+        let ptr = unsafe { rt.add_code::<*const u8>(code) };
+        used += code.len();
+
+        // The installed bytes must match the added code.
+        assert_eq!(unsafe { std::slice::from_raw_parts(ptr, code.len()) }, code);
+    }
+});
@@ -0,0 +1,55 @@
+/* C API of the juicebox-asm crate, requires building the shared library with
+ *   cargo rustc --release --features ffi --crate-type cdylib
+ *
+ * Registers are passed by their raw x64 register code:
+ *   0 -> rax, 1 -> rcx, 2 -> rdx, 3 -> rbx, 4 -> rsp,  5 -> rbp,  6 -> rsi,  7 -> rdi,
+ *   8 -> r8,  9 -> r9, 10 -> r10, 11 -> r11, 12 -> r12, 13 -> r13, 14 -> r14, 15 -> r15
+ *
+ * Functions returning bool return false if a register code is invalid.
+ */
+
+#ifndef JUICEBOX_ASM_H
+#define JUICEBOX_ASM_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+typedef struct Asm Asm;
+typedef struct Code Code;
+typedef struct Runtime Runtime;
+
+/* -- Asm. */
+
+Asm* asm_new(void);
+void asm_free(Asm* asm);
+
+bool asm_mov_rr(Asm* asm, uint8_t op1, uint8_t op2);
+bool asm_mov_ri(Asm* asm, uint8_t op1, uint64_t imm);
+bool asm_add_rr(Asm* asm, uint8_t op1, uint8_t op2);
+bool asm_sub_rr(Asm* asm, uint8_t op1, uint8_t op2);
+bool asm_push_r(Asm* asm, uint8_t op1);
+bool asm_pop_r(Asm* asm, uint8_t op1);
+bool asm_call_r(Asm* asm, uint8_t op1);
+void asm_nop(Asm* asm);
+void asm_ret(Asm* asm);
+
+/* Consumes the Asm handle. */
+Code* asm_finalize(Asm* asm);
+
+/* -- Code. */
+
+size_t code_len(const Code* code);
+const uint8_t* code_data(const Code* code);
+void code_free(Code* code);
+
+/* -- Runtime. */
+
+Runtime* rt_new(void);
+void rt_free(Runtime* rt);
+
+/* Does not consume the Code handle. */
+const void* rt_add_code(Runtime* rt, const Code* code);
+void rt_disasm(const Runtime* rt);
+
+#endif
@@ -0,0 +1,497 @@
+//! Python bindings for juicebox-asm, built with [pyo3](https://pyo3.rs) as the extension module
+//! `juicebox_asm`, for teaching and quick experiments.
+//!
+//! Instructions are emitted by calling a method named after the mnemonic on an `Asm`, which is
+//! forwarded to [`Asm::emit_insn`](juicebox::Asm::emit_insn). The instruction form is selected from
+//! the Python operands, see [`select`].
+//!
+//! ```python
+//! from juicebox_asm import Asm, Runtime, mem64, rax, rdi, rsi
+//!
+//! asm = Asm()
+//! asm.mov(rax, mem64(rdi, disp=8))
+//! asm.add(rax, rsi)
+//! asm.ret()
+//!
+//! add = Runtime().add_code(asm)
+//! ```
+
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use juicebox::insn::FORMS;
+use juicebox::{
+    Imm16, Imm32, Imm64, Imm8, LabelId, Mem16, Mem32, Mem64, Mem8, Operand, Reg16, Reg32, Reg64,
+    Reg8,
+};
+use pyo3::exceptions::{PyAttributeError, PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyTuple};
+
+macro_rules! impl_op {
+    ($($ty:ident),+ $(,)?) => {
+        /// Register, memory or immediate operand.
+        #[derive(Clone, Copy)]
+        enum Op {
+            $( $ty($ty), )+
+        }
+
+        impl Op {
+            /// Get the operand type name as used in the [`FORMS`] table, eg `Reg64`.
+            fn kind(&self) -> &'static str {
+                match self {
+                    $( Op::$ty(_) => stringify!($ty), )+
+                }
+            }
+
+            /// Get the type erased operand.
+            fn operand(&self) -> Operand<'static> {
+                match *self {
+                    $( Op::$ty(op) => op.into(), )+
+                }
+            }
+        }
+
+        impl fmt::Display for Op {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                match self {
+                    $( Op::$ty(op) => fmt::Display::fmt(op, f), )+
+                }
+            }
+        }
+    };
+}
+
+impl_op!(Reg64, Reg32, Reg16, Reg8, Mem64, Mem32, Mem16, Mem8, Imm64, Imm32, Imm16, Imm8);
+
+/// Operand exposed to Python, the registers are module attributes, eg `rax`, memory operands and
+/// immediates are created with `mem64(..)` and `imm64(..)` and friends.
+#[pyclass(frozen, name = "Operand")]
+#[derive(Clone, Copy)]
+struct PyOperand(Op);
+
+#[pymethods]
+impl PyOperand {
+    fn __repr__(&self) -> String {
+        self.0.to_string()
+    }
+}
+
+/// Get the 64 bit register of the operand `op`, used as address register.
+fn reg64(op: PyOperand) -> PyResult<Reg64> {
+    match op.0 {
+        Op::Reg64(reg) => Ok(reg),
+        op => Err(PyTypeError::new_err(format!(
+            "Expected a 64 bit register, got {}",
+            op
+        ))),
+    }
+}
+
+macro_rules! mem_fn {
+    ($name:ident, $ty:ident) => {
+        #[doc = concat!("Create a [`", stringify!($ty), "`] operand `[base + index * scale + disp]`.")]
+        #[pyfunction]
+        #[pyo3(signature = (base, index = None, scale = 1, disp = 0))]
+        fn $name(
+            base: PyOperand,
+            index: Option<PyOperand>,
+            scale: u8,
+            disp: i32,
+        ) -> PyResult<PyOperand> {
+            let base = reg64(base)?;
+            let mem = match index {
+                None if scale != 1 => {
+                    return Err(PyValueError::new_err("Scale requires an index register"))
+                }
+                None if disp == 0 => $ty::indirect(base),
+                None => $ty::indirect_disp(base, disp),
+                Some(_) if !matches!(scale, 1 | 2 | 4 | 8) => {
+                    return Err(PyValueError::new_err("Scale must be 1, 2, 4 or 8"))
+                }
+                Some(index) if scale == 1 && disp == 0 => {
+                    $ty::indirect_base_index(base, reg64(index)?)
+                }
+                Some(index) => {
+                    $ty::indirect_base_index_scale_disp(base, reg64(index)?, scale, disp)
+                }
+            };
+            Ok(PyOperand(Op::$ty(mem)))
+        }
+    };
+}
+
+mem_fn!(mem64, Mem64);
+mem_fn!(mem32, Mem32);
+mem_fn!(mem16, Mem16);
+mem_fn!(mem8, Mem8);
+
+macro_rules! imm_fn {
+    ($name:ident, $ty:ident, $int:ty, $uint:ty) => {
+        #[doc = concat!("Create an explicit [`", stringify!($ty), "`] operand from a signed or unsigned integer.")]
+        #[pyfunction]
+        fn $name(imm: i128) -> PyResult<PyOperand> {
+            let imm = <$int>::try_from(imm)
+                .map(|imm| imm as $uint)
+                .or_else(|_| <$uint>::try_from(imm))
+                .map_err(|_| {
+                    PyValueError::new_err(format!(
+                        "Immediate {} out of range for {}",
+                        imm,
+                        stringify!($ty)
+                    ))
+                })?;
+            Ok(PyOperand(Op::$ty($ty::from(imm))))
+        }
+    };
+}
+
+imm_fn!(imm64, Imm64, i64, u64);
+imm_fn!(imm32, Imm32, i32, u32);
+imm_fn!(imm16, Imm16, i16, u16);
+imm_fn!(imm8, Imm8, i8, u8);
+
+/// Label created with `Asm.label()`, it must only be used with the assembler which created it.
+#[pyclass(frozen)]
+#[derive(Clone, Copy)]
+struct Label {
+    asm: usize,
+    id: LabelId,
+}
+
+/// Instruction operand passed from Python.
+enum Arg {
+    Op(Op),
+    Label(LabelId),
+    Int(i128),
+}
+
+impl Arg {
+    /// Get the operand type name for error messages.
+    fn kind(&self) -> &'static str {
+        match self {
+            Arg::Op(op) => op.kind(),
+            Arg::Label(_) => "Label",
+            Arg::Int(_) => "int",
+        }
+    }
+
+    /// Get the operand for the operand type name `kind`, if the argument can be passed as `kind`.
+    /// `width` is the width in bits of the operation.
+    fn operand(&self, kind: &str, width: u32) -> Option<Operand<'static>> {
+        match *self {
+            Arg::Op(op) => (op.kind() == kind).then(|| op.operand()),
+            Arg::Label(id) => (kind == "LabelId").then(|| id.into()),
+            Arg::Int(imm) => {
+                let bits = bits(kind, &["Imm"])?;
+                // Immediates narrower than the operation are sign extended.
+                let signed = imm >= -(1i128 << (bits - 1)) && imm < 1i128 << (bits - 1);
+                let unsigned = bits >= width && imm >= 0 && imm < 1i128 << bits;
+                (signed || unsigned).then(|| match bits {
+                    8 => Imm8::from(imm as u8).into(),
+                    16 => Imm16::from(imm as u16).into(),
+                    32 => Imm32::from(imm as u32).into(),
+                    _ => Imm64::from(imm as u64).into(),
+                })
+            }
+        }
+    }
+}
+
+/// Get the width in bits of the operand type name `kind` starting with one of the `prefixes`, eg
+/// `64` for `Reg64`.
+fn bits(kind: &str, prefixes: &[&str]) -> Option<u32> {
+    prefixes
+        .iter()
+        .find_map(|prefix| kind.strip_prefix(prefix))
+        .and_then(|bits| bits.parse().ok())
+}
+
+/// Select the operands of the supported instruction form of `mnemonic` matching the `args`.
+///
+/// Python integers are passed as the smallest immediate which represents the value, considering
+/// that immediates narrower than the operation are sign extended, eg `mov(rax, 0xffffffff)` uses
+/// an `Imm64`.
+fn select(mnemonic: &str, args: &[Arg]) -> PyResult<Vec<Operand<'static>>> {
+    FORMS
+        .iter()
+        .filter(|form| form.mnemonic == mnemonic && form.operands.len() == args.len())
+        .filter_map(|form| {
+            let width = form
+                .operands
+                .iter()
+                .filter_map(|kind| bits(kind, &["Reg", "Mem"]))
+                .max()
+                .unwrap_or(64);
+            let ops = args
+                .iter()
+                .zip(form.operands)
+                .map(|(arg, kind)| arg.operand(kind, width))
+                .collect::<Option<Vec<_>>>()?;
+            let imm: u32 = form
+                .operands
+                .iter()
+                .filter_map(|kind| bits(kind, &["Imm"]))
+                .sum();
+            Some((imm, ops))
+        })
+        .min_by_key(|(imm, _)| *imm)
+        .map(|(_, ops)| ops)
+        .ok_or_else(|| {
+            let kinds: Vec<_> = args.iter().map(Arg::kind).collect();
+            PyValueError::new_err(format!(
+                "Unsupported instruction form: {} {}",
+                mnemonic,
+                kinds.join(", ")
+            ))
+        })
+}
+
+/// Unique identifier of each [`Asm`], to check that labels are used with their assembler.
+static ASM_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Assembler, instructions are emitted by calling methods named after the mnemonic, eg
+/// `asm.mov(rax, rdi)`.
+#[pyclass(unsendable)]
+struct Asm {
+    id: usize,
+    asm: Option<juicebox::Asm>,
+    bound: HashSet<LabelId>,
+}
+
+impl Asm {
+    /// Get the assembler, or an error if the code was already consumed.
+    fn asm(&mut self) -> PyResult<&mut juicebox::Asm> {
+        self.asm
+            .as_mut()
+            .ok_or_else(|| PyValueError::new_err("Asm already consumed"))
+    }
+
+    /// Take the assembler, or an error if the code was already consumed.
+    fn take(&mut self) -> PyResult<juicebox::Asm> {
+        self.asm
+            .take()
+            .ok_or_else(|| PyValueError::new_err("Asm already consumed"))
+    }
+
+    /// Check that the `label` was created by this assembler.
+    fn check(&self, label: &Label) -> PyResult<LabelId> {
+        if label.asm != self.id {
+            return Err(PyValueError::new_err("Label belongs to a different Asm"));
+        }
+        Ok(label.id)
+    }
+
+    /// Convert the Python operand `op`.
+    fn arg(&self, op: &Bound<'_, PyAny>) -> PyResult<Arg> {
+        if let Ok(op) = op.downcast::<PyOperand>() {
+            return Ok(Arg::Op(op.get().0));
+        }
+        if let Ok(label) = op.downcast::<Label>() {
+            return Ok(Arg::Label(self.check(label.get())?));
+        }
+        if let Ok(imm) = op.extract::<i128>() {
+            return Ok(Arg::Int(imm));
+        }
+        Err(PyTypeError::new_err(format!("Unsupported operand {}", op)))
+    }
+}
+
+#[pymethods]
+impl Asm {
+    #[new]
+    fn new() -> Asm {
+        Asm {
+            id: ASM_ID.fetch_add(1, Ordering::Relaxed),
+            asm: Some(juicebox::Asm::new()),
+            bound: HashSet::new(),
+        }
+    }
+
+    /// Create a new unbound label.
+    fn label(&mut self) -> PyResult<Label> {
+        let id = self.asm()?.new_label();
+        Ok(Label { asm: self.id, id })
+    }
+
+    /// Bind the `label` to the current location.
+    fn bind(&mut self, label: Label) -> PyResult<()> {
+        let id = self.check(&label)?;
+        if self.bound.contains(&id) {
+            return Err(PyValueError::new_err("Label already bound"));
+        }
+        self.asm()?.bind_label(id);
+        self.bound.insert(id);
+        Ok(())
+    }
+
+    /// Emit the instruction `mnemonic` with the `operands`, eg `asm.emit("mov", rax, rdi)`.
+    #[pyo3(signature = (mnemonic, *operands))]
+    fn emit(&mut self, mnemonic: &str, operands: &Bound<'_, PyTuple>) -> PyResult<()> {
+        let args = operands
+            .iter()
+            .map(|op| self.arg(&op))
+            .collect::<PyResult<Vec<_>>>()?;
+        let mut ops = select(mnemonic, &args)?;
+        self.asm()?
+            .emit_insn(mnemonic, &mut ops)
+            .map_err(|err| PyValueError::new_err(err.to_string()))
+    }
+
+    /// Get the instruction `mnemonic`, which emits the instruction when called, such that
+    /// `asm.mov(rax, rdi)` is the same as `asm.emit("mov", rax, rdi)`.
+    fn __getattr__(slf: Bound<'_, Self>, mnemonic: String) -> PyResult<Insn> {
+        if !FORMS.iter().any(|form| form.mnemonic == mnemonic) {
+            return Err(PyAttributeError::new_err(format!(
+                "Unknown instruction {}",
+                mnemonic
+            )));
+        }
+        Ok(Insn {
+            asm: slf.unbind(),
+            mnemonic,
+        })
+    }
+
+    /// Get the number of bytes emitted so far.
+    fn __len__(&mut self) -> PyResult<usize> {
+        Ok(self.asm()?.len())
+    }
+
+    /// Get the listing of the code emitted so far, `None` if `objdump` is not available.
+    fn listing(&mut self) -> PyResult<Option<String>> {
+        Ok(self.asm()?.export_lst())
+    }
+
+    /// Consume the assembler and get the emitted code.
+    #[pyo3(name = "into_code")]
+    fn take_code<'py>(&mut self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        Ok(PyBytes::new_bound(py, &self.take()?.into_code()))
+    }
+}
+
+/// Instruction of an [`Asm`], calling it emits the instruction.
+#[pyclass(unsendable)]
+struct Insn {
+    asm: Py<Asm>,
+    mnemonic: String,
+}
+
+#[pymethods]
+impl Insn {
+    #[pyo3(signature = (*operands))]
+    fn __call__(&self, py: Python<'_>, operands: &Bound<'_, PyTuple>) -> PyResult<()> {
+        self.asm.borrow_mut(py).emit(&self.mnemonic, operands)
+    }
+}
+
+/// Runtime holding the jitted code.
+#[pyclass(unsendable)]
+struct Runtime(juicebox::Runtime);
+
+#[pymethods]
+impl Runtime {
+    #[new]
+    fn new() -> Runtime {
+        Runtime(juicebox::Runtime::new())
+    }
+
+    /// Add the code of `asm` to the runtime and get a function calling it, consumes the `asm`.
+    ///
+    /// The code must follow the SystemV abi, taking up to six integer arguments and returning an
+    /// integer in `rax`.
+    fn add_code(slf: Bound<'_, Self>, asm: &Bound<'_, Asm>) -> PyResult<Function> {
+        let code = asm.borrow_mut().take()?.into_code();
+        // SAFETY: The function is only called through the pointer, which stays valid as long as
+        // the runtime, which is kept alive by the function.
+        let ptr = unsafe { slf.borrow_mut().0.add_code::<*const u8>(code) };
+        Ok(Function {
+            _rt: slf.unbind(),
+            ptr,
+        })
+    }
+
+    /// Disassemble the code added to the runtime and print it to stdout.
+    fn disasm(&self) {
+        self.0.disasm();
+    }
+}
+
+/// Function calling jitted code added to a [`Runtime`].
+#[pyclass(unsendable)]
+struct Function {
+    _rt: Py<Runtime>,
+    ptr: *const u8,
+}
+
+#[pymethods]
+impl Function {
+    /// Call the function with up to six integer arguments and get the integer returned in `rax`.
+    #[pyo3(signature = (*args))]
+    fn __call__(&self, args: &Bound<'_, PyTuple>) -> PyResult<u64> {
+        use std::mem::transmute;
+
+        let args: Vec<u64> = args.extract()?;
+        let f = self.ptr;
+        // SAFETY: Running the jitted code is the purpose of the bindings, the code is required to
+        // follow the SystemV abi, see `Runtime.add_code`.
+        let ret = unsafe {
+            match *args.as_slice() {
+                [] => transmute::<_, extern "C" fn() -> u64>(f)(),
+                [a] => transmute::<_, extern "C" fn(u64) -> u64>(f)(a),
+                [a, b] => transmute::<_, extern "C" fn(u64, u64) -> u64>(f)(a, b),
+                [a, b, c] => transmute::<_, extern "C" fn(u64, u64, u64) -> u64>(f)(a, b, c),
+                [a, b, c, d] => {
+                    transmute::<_, extern "C" fn(u64, u64, u64, u64) -> u64>(f)(a, b, c, d)
+                }
+                [a, b, c, d, e] => {
+                    transmute::<_, extern "C" fn(u64, u64, u64, u64, u64) -> u64>(f)(a, b, c, d, e)
+                }
+                [a, b, c, d, e, g] => transmute::<
+                    _,
+                    extern "C" fn(u64, u64, u64, u64, u64, u64) -> u64,
+                >(f)(a, b, c, d, e, g),
+                _ => {
+                    return Err(PyValueError::new_err(
+                        "At most six integer arguments are supported",
+                    ))
+                }
+            }
+        };
+        Ok(ret)
+    }
+}
+
+#[pymodule]
+fn juicebox_asm(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Asm>()?;
+    m.add_class::<Insn>()?;
+    m.add_class::<Label>()?;
+    m.add_class::<PyOperand>()?;
+    m.add_class::<Runtime>()?;
+    m.add_class::<Function>()?;
+
+    for reg in Reg64::iter() {
+        m.add(reg.to_string().as_str(), PyOperand(Op::Reg64(reg)))?;
+    }
+    for reg in Reg32::iter() {
+        m.add(reg.to_string().as_str(), PyOperand(Op::Reg32(reg)))?;
+    }
+    for reg in Reg16::iter() {
+        m.add(reg.to_string().as_str(), PyOperand(Op::Reg16(reg)))?;
+    }
+    for reg in Reg8::iter() {
+        m.add(reg.to_string().as_str(), PyOperand(Op::Reg8(reg)))?;
+    }
+
+    m.add_function(wrap_pyfunction!(mem64, m)?)?;
+    m.add_function(wrap_pyfunction!(mem32, m)?)?;
+    m.add_function(wrap_pyfunction!(mem16, m)?)?;
+    m.add_function(wrap_pyfunction!(mem8, m)?)?;
+    m.add_function(wrap_pyfunction!(imm64, m)?)?;
+    m.add_function(wrap_pyfunction!(imm32, m)?)?;
+    m.add_function(wrap_pyfunction!(imm16, m)?)?;
+    m.add_function(wrap_pyfunction!(imm8, m)?)?;
+    Ok(())
+}
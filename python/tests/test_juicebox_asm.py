@@ -0,0 +1,113 @@
+"""Tests for the Python bindings, run in a virtualenv with
+
+    maturin develop
+    python3 -m unittest discover tests
+"""
+
+import unittest
+
+from juicebox_asm import (Asm, Runtime, ah, al, eax, imm32, mem64, r9b, rax,
+                          rcx, rdi, rsi)
+
+
+class TestAsm(unittest.TestCase):
+    def test_encode(self):
+        asm = Asm()
+        asm.mov(rax, rdi)
+        asm.ret()
+        self.assertEqual(len(asm), 4)
+        self.assertEqual(asm.into_code(), bytes([0x48, 0x89, 0xf8, 0xc3]))
+
+    def test_imm(self):
+        asm = Asm()
+        asm.add(rax, 1)
+        asm.mov(rax, 0xffffffff)
+        asm.mov(rax, -1)
+        asm.mov(eax, 0xffffffff)
+        asm.mov(al, 0xff)
+        asm.emit("add", rax, imm32(1))
+        self.assertEqual(asm.into_code(), bytes([
+            0x48, 0x83, 0xc0, 0x01,
+            0x48, 0xb8, 0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x00,
+            0x48, 0xc7, 0xc0, 0xff, 0xff, 0xff, 0xff,
+            0xb8, 0xff, 0xff, 0xff, 0xff,
+            0xb0, 0xff,
+            0x48, 0x81, 0xc0, 0x01, 0x00, 0x00, 0x00,
+        ]))
+
+    def test_mem(self):
+        asm = Asm()
+        asm.mov(rax, mem64(rdi, disp=8))
+        asm.mov(mem64(rdi, rcx, 8), rax)
+        self.assertEqual(asm.into_code(), bytes([
+            0x48, 0x8b, 0x87, 0x08, 0x00, 0x00, 0x00,
+            0x48, 0x89, 0x84, 0xcf, 0x00, 0x00, 0x00, 0x00,
+        ]))
+        self.assertEqual(repr(mem64(rdi, disp=8)), "qword ptr [rdi+0x8]")
+
+    def test_invalid(self):
+        asm = Asm()
+        with self.assertRaises(ValueError):
+            asm.mov(1, rax)
+        with self.assertRaises(ValueError):
+            asm.mov(al, 0x100)
+        with self.assertRaises(TypeError):
+            asm.mov(rax, "rdi")
+        with self.assertRaises(TypeError):
+            mem64(eax)
+        with self.assertRaises(AttributeError):
+            asm.foo(rax)
+        with self.assertRaises(BaseException):
+            # High byte registers can not be encoded with a REX prefix, which panics.
+            asm.mov(ah, r9b)
+
+    def test_consumed(self):
+        asm = Asm()
+        asm.ret()
+        asm.into_code()
+        with self.assertRaises(ValueError):
+            asm.ret()
+
+    def test_labels(self):
+        asm = Asm()
+        label = asm.label()
+        asm.bind(label)
+        with self.assertRaises(ValueError):
+            asm.bind(label)
+        with self.assertRaises(ValueError):
+            Asm().jmp(label)
+
+
+class TestRuntime(unittest.TestCase):
+    def test_add(self):
+        asm = Asm()
+        asm.mov(rax, rdi)
+        asm.add(rax, rsi)
+        asm.ret()
+
+        add = Runtime().add_code(asm)
+        self.assertEqual(add(1, 2), 3)
+        with self.assertRaises(ValueError):
+            add(*range(7))
+
+    def test_loop(self):
+        # fn(n: u64) -> u64 { let mut r = 0; while n != 0 { r += n; n -= 1; } r }
+        asm = Asm()
+        head, end = asm.label(), asm.label()
+        asm.xor(eax, eax)
+        asm.bind(head)
+        asm.test(rdi, rdi)
+        asm.jz(end)
+        asm.add(rax, rdi)
+        asm.dec(rdi)
+        asm.jmp(head)
+        asm.bind(end)
+        asm.ret()
+
+        total = Runtime().add_code(asm)
+        self.assertEqual(total(0), 0)
+        self.assertEqual(total(100), 5050)
+
+
+if __name__ == "__main__":
+    unittest.main()
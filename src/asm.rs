@@ -1,8 +1,17 @@
 //! The `x64` jit assembler.
 
+use std::mem::ManuallyDrop;
+
+use crate::cpu::CpuFeatures;
 use crate::imm::Imm;
+use crate::label::{LabelId, RelocKind, Relocs};
 use crate::mem::{AddrMode, Mem, Mem16, Mem32, Mem64, Mem8};
+use crate::redzone::Redzone;
 use crate::reg::{Reg, Reg16, Reg32, Reg64, Reg8};
+use crate::shadow::ShadowRef;
+use crate::stats::Stats;
+use crate::trap::TrapTable;
+use crate::watch::{Watch, WatchAccess};
 use crate::Label;
 
 /// Encode the `REX` byte.
@@ -14,6 +23,16 @@ const fn rex(w: bool, r: u8, x: u8, b: u8) -> u8 {
     0b0100_0000 | ((w & 1) << 3) | (r << 2) | (x << 1) | b
 }
 
+/// Check that no high byte register is encoded with the `REX` byte `rex`, which would turn it into
+/// one of `{SPL, BPL, SIL, DIL}`, and get the `REX` byte.
+fn check_high8(rex: Option<u8>, high8: bool) -> Option<u8> {
+    assert!(
+        rex.is_none() || !high8,
+        "High byte register can not be encoded with a REX prefix"
+    );
+    rex
+}
+
 /// Encode the `ModR/M` byte.
 const fn modrm(mod_: u8, reg: u8, rm: u8) -> u8 {
     ((mod_ & 0b11) << 6) | ((reg & 0b111) << 3) | (rm & 0b111)
@@ -24,22 +43,368 @@ const fn sib(scale: u8, index: u8, base: u8) -> u8 {
     ((scale & 0b11) << 6) | ((index & 0b111) << 3) | (base & 0b111)
 }
 
+/// Mnemonics of the instructions which accept the `lock` prefix, see [`Asm::lock`].
+const LOCKABLE: [&str; 19] = [
+    "adc",
+    "add",
+    "and",
+    "btc",
+    "btr",
+    "bts",
+    "cmpxchg",
+    "cmpxchg8b",
+    "cmpxchg16b",
+    "dec",
+    "inc",
+    "neg",
+    "not",
+    "or",
+    "sbb",
+    "sub",
+    "xadd",
+    "xchg",
+    "xor",
+];
+
+/// Instructions emitted inside [`Asm::lock`], to validate the `lock` prefix.
+struct LockCheck {
+    /// Nesting depth of the locked instruction.
+    depth: usize,
+    /// Mnemonics of the instructions emitted at the locked depth.
+    mnemonics: Vec<&'static str>,
+    /// Operand forms of all encoded instructions.
+    categories: Vec<&'static str>,
+}
+
+/// Error returned by [`Asm::try_into_code`] if the emitted code exceeds the maximum size set with
+/// [`Asm::set_max_len`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CodeTooLarge {
+    /// Size of the emitted code in bytes.
+    pub len: usize,
+    /// Maximum size in bytes.
+    pub max: usize,
+}
+
+impl std::fmt::Display for CodeTooLarge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Code size of {} bytes exceeds the maximum of {} bytes",
+            self.len, self.max
+        )
+    }
+}
+
+impl std::error::Error for CodeTooLarge {}
+
+/// Growth strategy of the code buffer of an [Asm], see [`Asm::with_growth`].
+///
+/// ```rust
+/// use juicebox_asm::{Asm, Growth};
+///
+/// // Start small and grow by 50%, but never allocate more than 4 KiB.
+/// let mut asm = Asm::with_growth(Growth::new(16, 1.5).with_cap(4096));
+/// for _ in 0..17 {
+///     asm.nop();
+/// }
+/// assert_eq!(asm.capacity(), 24);
+///
+/// asm.shrink_to_fit();
+/// assert_eq!(asm.capacity(), 17);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Growth {
+    initial: usize,
+    factor: f64,
+    cap: Option<usize>,
+}
+
+impl Growth {
+    /// Create a growth strategy with an `initial` capacity in bytes, which is multiplied by
+    /// `factor` whenever the buffer is full.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `factor` is not larger than `1.0`.
+    pub fn new(initial: usize, factor: f64) -> Growth {
+        assert!(factor > 1.0, "Growth factor must be larger than 1.0");
+        Growth {
+            initial,
+            factor,
+            cap: None,
+        }
+    }
+
+    /// Limit the capacity of the buffer to `cap` bytes, emitting more bytes panics.
+    ///
+    /// Unlike [`Asm::set_max_len`], which is checked when getting the code, this bounds the
+    /// memory allocated for the buffer.
+    pub fn with_cap(mut self, cap: usize) -> Growth {
+        self.cap = Some(cap);
+        self
+    }
+
+    /// Get the new capacity for a buffer with capacity `cap`, which must hold at least `need`
+    /// bytes.
+    fn grow(&self, cap: usize, need: usize) -> usize {
+        if let Some(max) = self.cap {
+            assert!(
+                need <= max,
+                "Code buffer capacity of {} bytes exceeded",
+                max
+            );
+        }
+        // CAST: Saturating float to int conversion, buffer sizes are far below the f64 precision.
+        let grown = (cap as f64 * self.factor).ceil() as usize;
+        let new = grown.max(need);
+        self.cap.map_or(new, |max| new.min(max))
+    }
+}
+
+impl Default for Growth {
+    /// Default growth strategy with an initial capacity of 1 KiB, doubled when full and no cap.
+    fn default() -> Growth {
+        Growth::new(1024, 2.0)
+    }
+}
+
+/// Call of a host function, whose `rel32` displacement is patched once the final address of the
+/// code is known, see [`Asm::call_host`].
+#[derive(Clone, Copy, Debug)]
+struct HostCall {
+    /// Code offset of the first byte of the displacement.
+    off: usize,
+    /// Absolute address of the host function.
+    target: usize,
+}
+
+/// Callback invoked with the code and its final address, see [`Asm::on_install`].
+type InstallFn = Box<dyn FnOnce(&mut [u8], usize)>;
+
+/// Position dependent fixups of the code, applied once the final address of the code is known,
+/// see [`Runtime::install`](crate::Runtime::install).
+#[derive(Default)]
+pub(crate) struct Fixups {
+    host_calls: Vec<HostCall>,
+    callbacks: Vec<InstallFn>,
+}
+
+impl Fixups {
+    /// Check if the code is position independent, ie there are no fixups.
+    fn is_empty(&self) -> bool {
+        self.host_calls.is_empty() && self.callbacks.is_empty()
+    }
+
+    /// Apply the fixups to the `code`, which is placed at address `base`. Host calls are linked
+    /// first, followed by the callbacks in the order they were registered.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a host function is out of range of a `rel32` call from `base`.
+    pub(crate) fn apply(self, code: &mut [u8], base: usize) {
+        for call in self.host_calls {
+            let next = base + call.off + 4 /* account for the disp32 */;
+            let disp = i32::try_from(call.target as i64 - next as i64)
+                .expect("Host function out of range for rel32 call");
+            code[call.off..call.off + 4].copy_from_slice(&disp.to_ne_bytes());
+        }
+
+        for f in self.callbacks {
+            f(code, base);
+        }
+    }
+}
+
 /// `x64` jit assembler.
 pub struct Asm {
     buf: Vec<u8>,
+    /// Growth strategy of the code buffer, see [`Asm::with_growth`].
+    growth: Growth,
+    /// Pending relocations of labels not yet bound.
+    relocs: Relocs,
+    /// Labels owned by the assembler, see [`Asm::new_label`]. Their bound check is done when
+    /// getting the code, instead of when dropping them.
+    labels: Vec<ManuallyDrop<Label>>,
+    /// Position dependent fixups, applied when installing the code, see [`Asm::call_host`] and
+    /// [`Asm::on_install`].
+    fixups: Fixups,
+    /// Named entry points as code offsets, see [`Asm::entry`].
+    entries: Vec<(String, usize)>,
+    shadow: Option<ShadowRef>,
+    stats: Option<Box<Stats>>,
+    traps: TrapTable,
+    features: CpuFeatures,
+    /// Nesting depth of instructions currently being emitted, see [`Asm::insn`].
+    depth: usize,
+    /// Pending validation of a locked instruction, see [`Asm::lock`].
+    lock: Option<LockCheck>,
+    /// Maximum size of the emitted code, see [`Asm::set_max_len`].
+    max_len: Option<usize>,
+    /// Watch instrumentation of memory accesses, see [`Asm::set_watch`].
+    watch: Option<Watch>,
+    /// Memory access of the instruction currently being emitted, if watched.
+    watch_access: Option<WatchAccess>,
+    /// Bounds checks of the memory helpers, see [`Asm::set_redzone`].
+    redzone: Option<Redzone>,
+    #[cfg(feature = "telemetry")]
+    timers: crate::telemetry::Timers,
 }
 
 impl Asm {
     /// Create a new `x64` jit assembler.
     pub fn new() -> Asm {
-        // Some random default capacity.
-        let buf = Vec::with_capacity(1024);
-        Asm { buf }
+        Asm::with_growth(Growth::default())
+    }
+
+    /// Create a new `x64` jit assembler, which grows the code buffer according to `growth`.
+    pub fn with_growth(growth: Growth) -> Asm {
+        let initial = growth
+            .cap
+            .map_or(growth.initial, |cap| growth.initial.min(cap));
+        Asm {
+            buf: Vec::with_capacity(initial),
+            growth,
+            relocs: Relocs::default(),
+            labels: Vec::new(),
+            fixups: Fixups::default(),
+            entries: Vec::new(),
+            shadow: None,
+            stats: None,
+            traps: TrapTable::default(),
+            features: CpuFeatures::host(),
+            depth: 0,
+            lock: None,
+            max_len: None,
+            watch: None,
+            watch_access: None,
+            redzone: None,
+            #[cfg(feature = "telemetry")]
+            timers: Default::default(),
+        }
     }
 
     /// Consume the assembler and get the emitted code.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the code exceeds the maximum size set with [`Asm::set_max_len`], if a label
+    /// created with [`Asm::new_label`] is not bound or if the code is position dependent, see
+    /// [`Asm::call_host`] and [`Asm::on_install`], which requires
+    /// [`Runtime::install`](crate::Runtime::install).
     pub fn into_code(self) -> Vec<u8> {
-        self.buf
+        self.try_into_code().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Consume the assembler and get the emitted code, or an error if the code exceeds the
+    /// maximum size set with [`Asm::set_max_len`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if a label created with [`Asm::new_label`] is not bound or if the code is position
+    /// dependent.
+    pub fn try_into_code(self) -> Result<Vec<u8>, CodeTooLarge> {
+        let (code, fixups) = self.try_finalize()?;
+        assert!(
+            fixups.is_empty(),
+            "Position dependent code requires installing the code with Runtime::install"
+        );
+        Ok(code)
+    }
+
+    /// Consume the assembler and get the emitted code together with the fixups, which must be
+    /// applied once the final address of the code is known.
+    pub(crate) fn try_finalize(self) -> Result<(Vec<u8>, Fixups), CodeTooLarge> {
+        assert!(
+            self.labels.iter().all(|l| l.location().is_some()),
+            "Label not bound"
+        );
+
+        if let Some(max) = self.max_len {
+            if self.buf.len() > max {
+                return Err(CodeTooLarge {
+                    len: self.buf.len(),
+                    max,
+                });
+            }
+        }
+
+        #[cfg(feature = "telemetry")]
+        {
+            use crate::telemetry::{report, Event, Phase};
+            for (phase, time) in [
+                (Phase::Encode, self.timers.encode),
+                (Phase::Link, self.timers.link),
+            ] {
+                report(Event {
+                    phase,
+                    time,
+                    code_size: self.buf.len(),
+                    peak_mem: self.buf.capacity(),
+                });
+            }
+        }
+        Ok((self.buf, self.fixups))
+    }
+
+    /// Consume the assembler and get the emitted code without any spare capacity, eg to keep many
+    /// code buffers alive.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`Asm::into_code`].
+    pub fn into_boxed_slice(self) -> Box<[u8]> {
+        self.into_code().into_boxed_slice()
+    }
+
+    /// Shrink the capacity of the code buffer to the number of bytes emitted so far.
+    pub fn shrink_to_fit(&mut self) {
+        self.buf.shrink_to_fit();
+    }
+
+    /// Get the capacity in bytes of the code buffer.
+    pub fn capacity(&self) -> usize {
+        self.buf.capacity()
+    }
+
+    /// Set the maximum size in bytes of the code, eg the space left in the
+    /// [`Runtime`](crate::Runtime), see [`Runtime::available`](crate::Runtime::available).
+    ///
+    /// Code generators query [`Asm::remaining`] while emitting, to continue in a new unit before
+    /// the limit is exceeded.
+    ///
+    /// ```rust
+    /// use juicebox_asm::Asm;
+    ///
+    /// let mut asm = Asm::new();
+    /// asm.set_max_len(2);
+    /// asm.nop();
+    /// assert_eq!(asm.remaining(), Some(1));
+    /// asm.nop();
+    /// asm.ret();
+    /// assert_eq!(asm.remaining(), Some(0));
+    ///
+    /// let err = asm.try_into_code().unwrap_err();
+    /// assert_eq!((err.len, err.max), (3, 2));
+    /// ```
+    pub fn set_max_len(&mut self, max: usize) {
+        self.max_len = Some(max);
+    }
+
+    /// Get the number of bytes which can still be emitted before exceeding the maximum size set
+    /// with [`Asm::set_max_len`], `None` if no maximum is set.
+    pub fn remaining(&self) -> Option<usize> {
+        self.max_len.map(|max| max.saturating_sub(self.buf.len()))
+    }
+
+    /// Get the number of bytes emitted so far.
+    pub fn len(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Check if no bytes have been emitted so far.
+    pub fn is_empty(&self) -> bool {
+        self.buf.is_empty()
     }
 
     /// Disassemble the code currently added to the runtime, using
@@ -55,13 +420,274 @@ impl Asm {
         crate::disasm::disasm(&self.buf);
     }
 
+    /// Disassemble the emitted code in the given [`Syntax`](crate::Syntax) and print it to
+    /// _stdout_. Intel syntax uses `ndisasm`, AT&T syntax uses `objdump`. If the disassembler is
+    /// not available on the system this prints a warning and becomes a nop.
+    ///
+    /// # Panics
+    ///
+    /// Panics if anything goes wrong with spawning, writing to or reading from the disassembler
+    /// child process.
+    pub fn disasm_with(&self, syntax: crate::Syntax) {
+        crate::disasm::disasm_with(&self.buf, syntax);
+    }
+
+    /// Export the emitted code as textual assembly in intel syntax, defining the global function
+    /// `symbol` in the `.text` section.
+    ///
+    /// ```rust
+    /// use juicebox_asm::{Asm, Label, Reg64::*};
+    /// use juicebox_asm::insn::{Dec, Jnz};
+    ///
+    /// let mut asm = Asm::new();
+    /// let mut lp = Label::new();
+    /// asm.bind(&mut lp);
+    /// asm.dec(rdi);
+    /// asm.jnz(&mut lp);
+    /// asm.ret();
+    ///
+    /// std::fs::write(std::env::temp_dir().join("count.s"), asm.export_s("count")).unwrap();
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if anything goes wrong with spawning `objdump`.
+    pub fn export_s(&self, symbol: &str) -> String {
+        crate::export::export_s(&self.buf, symbol)
+    }
+
+    /// Export the code emitted so far as a listing with one instruction per line, prefixed with its
+    /// offset and its encoding. Branch targets inside the code are replaced with local labels
+    /// `.L<offset>`. Returns `None` if
+    /// [`objdump`](https://sourceware.org/binutils/docs/binutils/objdump.html) is not available on
+    /// the system.
+    ///
+    /// ```rust
+    /// use juicebox_asm::{Asm, Reg64::*};
+    /// use juicebox_asm::insn::Mov;
+    ///
+    /// let mut asm = Asm::new();
+    /// asm.mov(rax, rdi);
+    /// asm.ret();
+    ///
+    /// if let Some(lst) = asm.export_lst() {
+    ///     assert_eq!(lst.lines().next(), Some("0000: 48 89 f8                       mov rax,rdi"));
+    /// }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if anything goes wrong with spawning `objdump`.
+    pub fn export_lst(&self) -> Option<String> {
+        crate::export::export_lst(&self.buf)
+    }
+
+    /// Get the shadow stack enabled for instrumentation, if any.
+    pub(crate) fn shadow(&self) -> Option<ShadowRef> {
+        self.shadow
+    }
+
+    /// Set the shadow stack used for instrumentation.
+    pub(crate) fn set_shadow(&mut self, shadow: Option<ShadowRef>) {
+        self.shadow = shadow;
+    }
+
+    /// Get the named entry points declared so far for updating.
+    pub(crate) fn entries_mut(&mut self) -> &mut Vec<(String, usize)> {
+        &mut self.entries
+    }
+
+    /// Get the watch used for instrumentation for updating.
+    pub(crate) fn watch_mut(&mut self) -> &mut Option<Watch> {
+        &mut self.watch
+    }
+
+    /// Get the redzone used for the bounds checks for updating.
+    pub(crate) fn redzone_mut(&mut self) -> &mut Option<Redzone> {
+        &mut self.redzone
+    }
+
+    /// Get the [`CpuFeatures`](crate::CpuFeatures) the emitted code may use, defaults to the
+    /// features of the host CPU.
+    pub fn cpu_features(&self) -> CpuFeatures {
+        self.features
+    }
+
+    /// Set the [`CpuFeatures`](crate::CpuFeatures) the emitted code may use for all instructions
+    /// emitted from now on, eg to emit code for another machine.
+    pub fn set_cpu_features(&mut self, features: CpuFeatures) {
+        self.features = features;
+    }
+
+    /// Enable the deterministic build mode, guaranteeing byte-identical code for identical
+    /// instruction sequences, eg to use hashes of the code as cache keys across machines.
+    ///
+    /// Selects the baseline [`CpuFeatures`](crate::CpuFeatures), such that helpers selecting
+    /// instruction sequences do not depend on the host CPU. Features set explicitly afterwards
+    /// with [`Asm::set_cpu_features`] are part of the input. The same holds for addresses embedded
+    /// as immediates, eg by instrumentation hooks.
+    ///
+    /// ```rust
+    /// use juicebox_asm::{Asm, Label, Mem64, Reg64::*};
+    /// use juicebox_asm::insn::{Jmp, Jz};
+    ///
+    /// let emit = || {
+    ///     let mut asm = Asm::new();
+    ///     asm.set_deterministic();
+    ///     let mut end = Label::new();
+    ///     asm.jz(&mut end);
+    ///     asm.load_be64(rax, Mem64::indirect(rdi));
+    ///     asm.jmp(&mut end);
+    ///     asm.bind(&mut end);
+    ///     asm.ret();
+    ///     asm.into_code()
+    /// };
+    ///
+    /// assert_eq!(emit(), emit());
+    /// ```
+    pub fn set_deterministic(&mut self) {
+        self.features = CpuFeatures::baseline();
+    }
+
+    /// Enable collecting encode-time [`Stats`](crate::Stats) for all instructions emitted from
+    /// now on.
+    pub fn enable_stats(&mut self) {
+        self.stats.get_or_insert_with(Default::default);
+    }
+
+    /// Get the collected [`Stats`](crate::Stats), `None` if not enabled.
+    pub fn stats(&self) -> Option<&Stats> {
+        self.stats.as_deref()
+    }
+
+    /// Get the collected stats for updating, `None` if not enabled.
+    pub(crate) fn stats_mut(&mut self) -> Option<&mut Stats> {
+        self.stats.as_deref_mut()
+    }
+
+    /// Get the [`TrapTable`](crate::TrapTable) of the instructions emitted with
+    /// [`Asm::trapping`].
+    pub fn traps(&self) -> &TrapTable {
+        &self.traps
+    }
+
+    /// Get the trap table for updating.
+    pub(crate) fn traps_mut(&mut self) -> &mut TrapTable {
+        &mut self.traps
+    }
+
+    /// Emit an instruction by invoking `f` and account it to `mnemonic`.
+    ///
+    /// Instructions emitted while `f` runs are accounted to `mnemonic` as well.
+    pub(crate) fn insn(&mut self, mnemonic: &'static str, f: impl FnOnce(&mut Asm)) {
+        #[cfg(feature = "telemetry")]
+        let now = std::time::Instant::now();
+
+        self.depth += 1;
+        let start = self.buf.len();
+        f(self);
+        if let Some(access) = self.watch_access.take() {
+            // The instrumentation is emitted after the instruction and moved in front of it,
+            // including the prefix of a locked instruction.
+            let locked = self
+                .lock
+                .as_ref()
+                .is_some_and(|l| l.depth + 1 == self.depth);
+            let lock = self.lock.take();
+            let end = self.buf.len();
+            self.watch_insn(mnemonic, access);
+            let len = self.buf.len() - end;
+            self.buf[start - usize::from(locked)..].rotate_right(len);
+            self.lock = lock;
+        }
+        self.depth -= 1;
+
+        if let Some(lock) = &mut self.lock {
+            if lock.depth == self.depth {
+                lock.mnemonics.push(mnemonic);
+            }
+        }
+
+        if self.depth == 0 {
+            #[cfg(feature = "telemetry")]
+            {
+                self.timers.encode += now.elapsed();
+            }
+            let bytes = self.buf.len() - start;
+            if let Some(stats) = self.stats_mut() {
+                stats.add_insn(mnemonic, bytes);
+            }
+        }
+    }
+
+    /// Emit the instruction of `f` with the
+    /// [`lock`](https://www.felixcloutier.com/x86/lock) prefix, making a read-modify-write of its
+    /// memory operand atomic.
+    ///
+    /// ```rust
+    /// use juicebox_asm::insn::Add;
+    /// use juicebox_asm::{Asm, Mem64, Reg64::*};
+    ///
+    /// let mut asm = Asm::new();
+    /// asm.lock(|asm| asm.add(Mem64::indirect(rdi), rax));
+    /// assert_eq!(asm.into_code(), [0xf0, 0x48, 0x01, 0x07]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `f` does not emit exactly one instruction, or if the instruction does not accept
+    /// the `lock` prefix or has no memory destination operand.
+    pub fn lock(&mut self, f: impl FnOnce(&mut Asm)) {
+        assert!(self.lock.is_none(), "Lock prefix can not be nested");
+        self.lock = Some(LockCheck {
+            depth: self.depth + 1,
+            mnemonics: Vec::new(),
+            categories: Vec::new(),
+        });
+
+        self.insn("lock", |asm| {
+            asm.emit(&[0xf0]);
+            f(asm);
+        });
+
+        // UNWRAP: Lock check was set above.
+        let lock = self.lock.take().unwrap();
+        match (&lock.mnemonics[..], &lock.categories[..]) {
+            ([m], [c]) if LOCKABLE.contains(m) && c.starts_with("mem") => {}
+            _ => panic!("Instruction can not be locked: {:?}", lock.mnemonics),
+        }
+    }
+
+    /// Record the operand form `category` of an encoded instruction for the [`Asm::lock`]
+    /// validation.
+    pub(crate) fn lock_category(&mut self, category: &'static str) {
+        if let Some(lock) = &mut self.lock {
+            lock.categories.push(category);
+        }
+    }
+
+    /// Make room for `len` additional bytes in the code buffer according to the growth strategy.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity cap of the growth strategy is exceeded.
+    fn reserve(&mut self, len: usize) {
+        let need = self.buf.len() + len;
+        if need > self.buf.capacity() {
+            let cap = self.growth.grow(self.buf.capacity(), need);
+            self.buf.reserve_exact(cap - self.buf.len());
+        }
+    }
+
     /// Emit a slice of bytes.
     pub(crate) fn emit(&mut self, bytes: &[u8]) {
+        self.reserve(bytes.len());
         self.buf.extend_from_slice(bytes);
     }
 
     /// Emit a slice of optional bytes.
     fn emit_optional(&mut self, bytes: &[Option<u8>]) {
+        self.reserve(bytes.iter().flatten().count());
         for byte in bytes.iter().filter_map(|&b| b) {
             self.buf.push(byte);
         }
@@ -82,28 +708,178 @@ impl Asm {
 
     /// Bind the [Label] to the current location.
     pub fn bind(&mut self, label: &mut Label) {
+        #[cfg(feature = "telemetry")]
+        let now = std::time::Instant::now();
+
         // Bind the label to the current offset.
         label.bind(self.buf.len());
 
         // Resolve any pending relocations for the label.
         self.resolve(label);
+
+        #[cfg(feature = "telemetry")]
+        {
+            self.timers.link += now.elapsed();
+        }
+    }
+
+    /// Create a new unbound label owned by the assembler and get its identifier.
+    ///
+    /// Owned labels are used like a [Label], but the identifier can be copied and stored freely
+    /// while the assembler is borrowed mutably, eg in maps keyed by guest addresses.
+    ///
+    /// ```rust
+    /// use std::collections::HashMap;
+    /// use juicebox_asm::{Asm, Cond};
+    /// use juicebox_asm::insn::Jz;
+    ///
+    /// let mut asm = Asm::new();
+    /// let mut labels = HashMap::new();
+    ///
+    /// // Branches of guest pc 0 and 1 to the guest pc 2.
+    /// for (pc, target) in [(0, 2), (1, 2)] {
+    ///     let l = *labels.entry(target).or_insert_with(|| asm.new_label());
+    ///     if pc == 0 {
+    ///         asm.jz(l);
+    ///     } else {
+    ///         asm.with_label(l, |asm, l| asm.jcc(Cond::S, l));
+    ///     }
+    /// }
+    /// asm.bind_label(labels[&2]);
+    /// asm.ret();
+    ///
+    /// assert_eq!(asm.into_code(), [0x0f, 0x84, 0x06, 0, 0, 0, 0x0f, 0x88, 0, 0, 0, 0, 0xc3]);
+    /// ```
+    pub fn new_label(&mut self) -> LabelId {
+        self.labels.push(ManuallyDrop::new(Label::new()));
+        LabelId::new(self.labels.len() - 1)
+    }
+
+    /// Bind the label `id` to the current location.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the label is already bound.
+    pub fn bind_label(&mut self, id: LabelId) {
+        self.with_label(id, |asm, l| asm.bind(l));
+    }
+
+    /// Invoke `f` with the label `id`, eg to pass it to an instruction only taking a [Label].
+    pub fn with_label<R>(&mut self, id: LabelId, f: impl FnOnce(&mut Asm, &mut Label) -> R) -> R {
+        // The placeholder is not dropped, hence it does not need to be bound.
+        let mut label =
+            std::mem::replace(&mut self.labels[id.idx()], ManuallyDrop::new(Label::new()));
+        let ret = f(self, &mut label);
+        self.labels[id.idx()] = label;
+        ret
+    }
+
+    /// Emit a `call rel32` of the host function at address `target`, eg a function of the
+    /// embedding program.
+    ///
+    /// The displacement depends on the final address of the code, hence it is patched when
+    /// installing the code with [`Runtime::install`](crate::Runtime::install). Compared to an
+    /// indirect call through a register, this needs no scratch register and is only 5 bytes.
+    ///
+    /// ```rust
+    /// use juicebox_asm::{Asm, Runtime};
+    ///
+    /// let mut rt = Runtime::new();
+    /// let one = unsafe { rt.add_code::<extern "C" fn() -> u32>([0xb8, 1, 0, 0, 0, 0xc3]) };
+    ///
+    /// let mut asm = Asm::new();
+    /// asm.call_host(one as usize);
+    /// asm.ret();
+    ///
+    /// let f = unsafe { rt.install::<extern "C" fn() -> u32>(asm) };
+    /// assert_eq!(f(), 1);
+    /// ```
+    pub fn call_host(&mut self, target: usize) {
+        self.insn("call", |asm| {
+            let start = asm.buf.len();
+            asm.emit(&[0xe8]);
+            asm.fixups.host_calls.push(HostCall {
+                off: asm.buf.len(),
+                target,
+            });
+            // Zeroed displacement, which serves as placeholder until the code is installed.
+            asm.emit(&[0u8; 4]);
+            asm.insn_category("host", start);
+        });
+    }
+
+    /// Register the callback `f`, which is invoked with the code and its final address when
+    /// installing the code with [`Runtime::install`](crate::Runtime::install), before the code is
+    /// copied onto the code page.
+    ///
+    /// This allows to patch absolute addresses, which can not be expressed by a [Label], eg
+    /// pointers in self-referential data emitted along the code.
+    ///
+    /// ```rust
+    /// use juicebox_asm::{Asm, Imm64, Mem64, Reg64::rax, Runtime};
+    /// use juicebox_asm::insn::Mov;
+    ///
+    /// let mut asm = Asm::new();
+    /// // rax = *data, the absolute address of data is patched at install time.
+    /// asm.mov(rax, Imm64::from(0u64));
+    /// let imm = asm.len() - 8;
+    /// asm.mov(rax, Mem64::indirect(rax));
+    /// asm.ret();
+    ///
+    /// let data = asm.len();
+    /// asm.emit_blob(&42u64.to_ne_bytes(), &mut []);
+    ///
+    /// asm.on_install(move |code, base| {
+    ///     let addr = (base + data) as u64;
+    ///     code[imm..imm + 8].copy_from_slice(&addr.to_ne_bytes());
+    /// });
+    ///
+    /// let mut rt = Runtime::new();
+    /// let f = unsafe { rt.install::<extern "C" fn() -> u64>(asm) };
+    /// assert_eq!(f(), 42);
+    /// ```
+    pub fn on_install(&mut self, f: impl FnOnce(&mut [u8], usize) + 'static) {
+        self.fixups.callbacks.push(Box::new(f));
+    }
+
+    /// Record a relocation of `kind` at the code offset `off` referring to `label`, which is
+    /// patched by [`Asm::resolve`].
+    pub(crate) fn record_reloc(&mut self, label: &mut Label, off: usize, kind: RelocKind) {
+        self.relocs.push(label, off, kind);
     }
 
     /// If the [Label] is bound, patch any pending relocation.
-    fn resolve(&mut self, label: &mut Label) {
+    ///
+    /// # Panics
+    ///
+    /// Panics if the label location is out of range for a `rel8` relocation.
+    pub(crate) fn resolve(&mut self, label: &mut Label) {
         if let Some(loc) = label.location() {
-            // For now we only support disp32 as label location.
             let loc = i32::try_from(loc).expect("Label location did not fit into i32.");
 
             // Resolve any pending relocations for the label.
-            for off in label.offsets_mut().drain() {
+            while let Some((off, kind)) = self.relocs.pop(label) {
                 // Displacement is relative to the next instruction following the jump.
-                // We record the offset to patch at the first byte of the disp32 therefore we need
-                // to account for that in the disp computation.
-                let disp32 = loc - i32::try_from(off).expect("Label offset did not fit into i32") - 4 /* account for the disp32 */;
+                // We record the offset to patch at the first byte of the displacement therefore we
+                // need to account for that in the disp computation.
+                let disp = loc - i32::try_from(off).expect("Label offset did not fit into i32");
 
-                // Patch the relocation with the disp32.
-                self.emit_at(off, &disp32.to_ne_bytes());
+                // Patch the relocation with the displacement.
+                match kind {
+                    RelocKind::Rel8 => {
+                        let disp8 = i8::try_from(disp - 1 /* account for the disp8 */)
+                            .expect("Label out of range for rel8 jump");
+                        self.emit_at(off, &disp8.to_ne_bytes());
+                    }
+                    RelocKind::Rel32 => {
+                        let disp32 = disp - 4 /* account for the disp32 */;
+                        self.emit_at(off, &disp32.to_ne_bytes());
+                    }
+                    RelocKind::Table(base) => {
+                        let base = i32::try_from(base).expect("Table base did not fit into i32");
+                        self.emit_at(off, &(loc - base).to_ne_bytes());
+                    }
+                }
             }
         }
     }
@@ -112,6 +888,31 @@ impl Asm {
 
     /// Encode an register-register instruction.
     pub(crate) fn encode_rr<T: Reg>(&mut self, opc: &[u8], op1: T, op2: T)
+    where
+        Self: EncodeRR<T>,
+    {
+        let start = self.buf.len();
+        self.encode_rr_raw(opc, op1, op2);
+        self.insn_category("reg, reg", start);
+    }
+
+    /// Encode a register-register-immediate instruction.
+    pub(crate) fn encode_rri<T: Reg, U: Imm>(&mut self, opc: &[u8], op1: T, op2: T, op3: U)
+    where
+        Self: EncodeRR<T>,
+    {
+        // RMI operand encoding.
+        //   op1 -> modrm.reg
+        //   op2 -> modrm.rm
+        //   op3 -> imm
+        let start = self.buf.len();
+        self.encode_rr_raw(opc, op2, op1);
+        self.emit(op3.bytes());
+        self.insn_category("reg, reg, imm", start);
+    }
+
+    /// Encode an register-register instruction, without accounting it to the stats.
+    fn encode_rr_raw<T: Reg>(&mut self, opc: &[u8], op1: T, op2: T)
     where
         Self: EncodeRR<T>,
     {
@@ -138,6 +939,7 @@ impl Asm {
     where
         Self: EncodeR<T>,
     {
+        let start = self.buf.len();
         let opc = opc + (op1.idx() & 0b111);
         let prefix = <Self as EncodeR<T>>::legacy_prefix();
         let rex = <Self as EncodeR<T>>::rex(op1);
@@ -145,13 +947,33 @@ impl Asm {
         self.emit_optional(&[prefix, rex]);
         self.emit(&[opc]);
         self.emit(op2.bytes());
+        self.insn_category("reg, imm", start);
+    }
+
+    /// Encode a register instruction with the register encoded in the last opcode byte.
+    pub(crate) fn encode_o<T: Reg>(&mut self, opc: &[u8], op1: T)
+    where
+        Self: EncodeR<T>,
+    {
+        let start = self.buf.len();
+        // UNWRAP: Opcodes are never empty.
+        let (last, opc) = opc.split_last().unwrap();
+        let opc_reg = last + (op1.idx() & 0b111);
+        let prefix = <Self as EncodeR<T>>::legacy_prefix();
+        let rex = <Self as EncodeR<T>>::rex(op1);
+
+        self.emit_optional(&[prefix, rex]);
+        self.emit(opc);
+        self.emit(&[opc_reg]);
+        self.insn_category("reg", start);
     }
 
     /// Encode a register instruction.
-    pub(crate) fn encode_r<T: Reg>(&mut self, opc: u8, opc_ext: u8, op1: T)
+    pub(crate) fn encode_r<T: Reg>(&mut self, opc: &[u8], opc_ext: u8, op1: T)
     where
         Self: EncodeR<T>,
     {
+        let start = self.buf.len();
         // M operand encoding.
         //   op1           -> modrm.rm
         //   opc extension -> modrm.reg
@@ -164,54 +986,78 @@ impl Asm {
         let prefix = <Self as EncodeR<T>>::legacy_prefix();
         let rex = <Self as EncodeR<T>>::rex(op1);
 
+        self.emit_optional(&[prefix, rex]);
+        self.emit(opc);
+        self.emit(&[modrm]);
+        self.insn_category("reg", start);
+    }
+
+    /// Encode a register-immediate instruction.
+    pub(crate) fn encode_ri<T: Reg, U: Imm>(&mut self, opc: u8, opc_ext: u8, op1: T, op2: U)
+    where
+        Self: EncodeR<T>,
+    {
+        let start = self.buf.len();
+        // MI operand encoding.
+        //   op1           -> modrm.rm
+        //   opc extension -> modrm.reg
+        //   op2           -> imm
+        let modrm = modrm(
+            0b11,      /* mod */
+            opc_ext,   /* reg */
+            op1.idx(), /* rm */
+        );
+
+        let prefix = <Self as EncodeR<T>>::legacy_prefix();
+        let rex = <Self as EncodeR<T>>::rex(op1);
+
         self.emit_optional(&[prefix, rex]);
         self.emit(&[opc, modrm]);
+        self.emit(op2.bytes());
+        self.insn_category("reg, imm", start);
+    }
+
+    /// Encode a register instruction with the implicit `cl` count operand, eg shifts.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `op2` is not `cl`.
+    pub(crate) fn encode_r_cl<T: Reg>(&mut self, opc: u8, opc_ext: u8, op1: T, op2: Reg8)
+    where
+        Self: EncodeR<T>,
+    {
+        assert_eq!(op2, Reg8::cl, "Count register must be cl");
+        self.encode_r(&[opc], opc_ext, op1);
     }
 
     /// Encode a memory operand instruction.
-    pub(crate) fn encode_m<T: Mem>(&mut self, opc: u8, opc_ext: u8, op1: T)
+    pub(crate) fn encode_m<T: Mem>(&mut self, opc: &[u8], opc_ext: u8, op1: T)
     where
         Self: EncodeM<T>,
     {
+        let start = self.buf.len();
         // M operand encoding.
         //   op1 -> modrm.rm
-        let (mode, rm) = match op1.mode() {
-            AddrMode::Indirect => {
-                assert!(!op1.base().need_sib() && !op1.base().is_pc_rel());
-                (0b00, op1.base().idx())
-            }
-            AddrMode::IndirectDisp => {
-                assert!(!op1.base().need_sib());
-                (0b10, op1.base().idx())
-            }
-            AddrMode::IndirectBaseIndex => {
-                assert!(!op1.base().is_pc_rel());
-                // Using rsp as index register is interpreted as just base w/o offset.
-                //   https://wiki.osdev.org/X86-64_Instruction_Encoding#32.2F64-bit_addressing_2
-                // Disallow this case, as guard for the user.
-                assert!(!matches!(op1.index(), Reg64::rsp));
-                (0b00, 0b100)
-            }
-        };
-
-        let modrm = modrm(
-            mode,    /* mode */
-            opc_ext, /* reg */
-            rm,      /* rm */
-        );
-
         let prefix = <Self as EncodeM<T>>::legacy_prefix();
         let rex = <Self as EncodeM<T>>::rex(&op1);
 
         self.emit_optional(&[prefix, rex]);
-        self.emit(&[opc, modrm]);
-        match op1.mode() {
-            AddrMode::Indirect => {}
-            AddrMode::IndirectDisp => self.emit(&op1.disp().to_ne_bytes()),
-            AddrMode::IndirectBaseIndex => {
-                self.emit(&[sib(0, op1.index().idx(), op1.base().idx())])
-            }
-        }
+        self.emit(opc);
+        self.emit_mem(opc_ext, &op1);
+        self.insn_category("mem", start);
+    }
+
+    /// Encode a memory operand instruction with the implicit `cl` count operand, eg shifts.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `op2` is not `cl`.
+    pub(crate) fn encode_m_cl<T: Mem>(&mut self, opc: u8, opc_ext: u8, op1: T, op2: Reg8)
+    where
+        Self: EncodeM<T>,
+    {
+        assert_eq!(op2, Reg8::cl, "Count register must be cl");
+        self.encode_m(&[opc], opc_ext, op1);
     }
 
     /// Encode a memory-immediate instruction.
@@ -219,121 +1065,239 @@ impl Asm {
     where
         Self: EncodeM<M>,
     {
+        let start = self.buf.len();
         // MI operand encoding.
         //   op1 -> modrm.rm
         //   op2 -> imm
-        let (mode, rm) = match op1.mode() {
-            AddrMode::Indirect => {
-                assert!(!op1.base().need_sib() && !op1.base().is_pc_rel());
-                (0b00, op1.base().idx())
-            }
-            AddrMode::IndirectDisp => {
-                assert!(!op1.base().need_sib());
-                (0b10, op1.base().idx())
-            }
-            AddrMode::IndirectBaseIndex => {
-                assert!(!op1.base().is_pc_rel());
-                // Using rsp as index register is interpreted as just base w/o offset.
-                //   https://wiki.osdev.org/X86-64_Instruction_Encoding#32.2F64-bit_addressing_2
-                // Disallow this case, as guard for the user.
-                assert!(!matches!(op1.index(), Reg64::rsp));
-                (0b00, 0b100)
-            }
-        };
-
-        let modrm = modrm(
-            mode,    /* mode */
-            opc_ext, /* reg */
-            rm,      /* rm */
-        );
-
         let prefix = <Self as EncodeM<M>>::legacy_prefix();
         let rex = <Self as EncodeM<M>>::rex(&op1);
 
         self.emit_optional(&[prefix, rex]);
-        self.emit(&[opc, modrm]);
-        match op1.mode() {
-            AddrMode::Indirect => {}
-            AddrMode::IndirectDisp => self.emit(&op1.disp().to_ne_bytes()),
-            AddrMode::IndirectBaseIndex => {
-                self.emit(&[sib(0, op1.index().idx(), op1.base().idx())])
-            }
-        }
+        self.emit(&[opc]);
+        self.emit_mem(opc_ext, &op1);
         self.emit(op2.bytes());
+        self.insn_category("mem, imm", start);
     }
 
     /// Encode a memory-register instruction.
-    pub(crate) fn encode_mr<M: Mem, T: Reg>(&mut self, opc: u8, op1: M, op2: T)
+    pub(crate) fn encode_mr<M: Mem, T: Reg>(&mut self, opc: &[u8], op1: M, op2: T)
+    where
+        Self: EncodeMR<M>,
+    {
+        let start = self.buf.len();
+        self.encode_mr_raw(opc, op1, op2);
+        self.insn_category("mem, reg", start);
+    }
+
+    /// Encode a memory-register instruction, without accounting it to the stats.
+    fn encode_mr_raw<M: Mem, T: Reg>(&mut self, opc: &[u8], op1: M, op2: T)
     where
         Self: EncodeMR<M>,
     {
         // MR operand encoding.
         //   op1 -> modrm.rm
         //   op2 -> modrm.reg
-        let (mode, rm) = match op1.mode() {
-            AddrMode::Indirect => {
-                assert!(!op1.base().need_sib() && !op1.base().is_pc_rel());
-                (0b00, op1.base().idx())
+        let reg = op2.idx();
+        let prefix = <Self as EncodeMR<M>>::legacy_prefix();
+        let rex = <Self as EncodeMR<M>>::rex(&op1, op2);
+
+        self.emit_optional(&[prefix, rex]);
+        self.emit(opc);
+        self.emit_mem(reg, &op1);
+    }
+
+    /// Encode a register-memory instruction.
+    pub(crate) fn encode_rm<T: Reg, M: Mem>(&mut self, opc: &[u8], op1: T, op2: M)
+    where
+        Self: EncodeMR<M>,
+    {
+        // RM operand encoding.
+        //   op1 -> modrm.reg
+        //   op2 -> modrm.rm
+        let start = self.buf.len();
+        self.encode_mr_raw(opc, op2, op1);
+        self.insn_category("reg, mem", start);
+    }
+
+    /// Encode a register-memory-immediate instruction.
+    pub(crate) fn encode_rmi<T: Reg, M: Mem, U: Imm>(&mut self, opc: &[u8], op1: T, op2: M, op3: U)
+    where
+        Self: EncodeMR<M>,
+    {
+        // RMI operand encoding.
+        //   op1 -> modrm.reg
+        //   op2 -> modrm.rm
+        //   op3 -> imm
+        let start = self.buf.len();
+        self.encode_mr_raw(opc, op2, op1);
+        self.emit(op3.bytes());
+        self.insn_category("reg, mem, imm", start);
+    }
+
+    /// Emit the `ModR/M` byte and the optional `SIB` byte and displacement for the memory
+    /// operand `op`, with `reg` encoded in `modrm.reg`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `op` can not be encoded in its addressing mode.
+    fn emit_mem<M: Mem>(&mut self, reg: u8, op: &M) {
+        if self.watch.is_some() {
+            self.watch_access = Some(WatchAccess::new(op));
+        }
+
+        // In the rm field of the ModR/M byte, rsp/r12 select a SIB byte and rbp/r13 with mod 0b00
+        // select rip-relative addressing, which need special forms when used as base register.
+        //   https://wiki.osdev.org/X86-64_Instruction_Encoding#32.2F64-bit_addressing_2
+        let base = op.base();
+        match op.mode() {
+            AddrMode::Indirect if base.is_pc_rel() => {
+                // Encode with a zero disp8.
+                self.emit(&[modrm(0b01, reg, base.idx()), 0]);
             }
-            AddrMode::IndirectDisp => {
-                assert!(!op1.base().need_sib());
-                (0b10, op1.base().idx())
+            AddrMode::Indirect | AddrMode::IndirectDisp => {
+                let disp = matches!(op.mode(), AddrMode::IndirectDisp);
+                let mode = if disp { 0b10 } else { 0b00 };
+                self.emit(&[modrm(mode, reg, base.idx())]);
+                if base.need_sib() {
+                    // SIB byte without index.
+                    self.emit(&[sib(0, 0b100, base.idx())]);
+                }
+                if disp {
+                    self.emit(&op.disp().to_ne_bytes());
+                }
             }
             AddrMode::IndirectBaseIndex => {
-                assert!(!op1.base().is_pc_rel());
                 // Using rsp as index register is interpreted as just base w/o offset.
-                //   https://wiki.osdev.org/X86-64_Instruction_Encoding#32.2F64-bit_addressing_2
                 // Disallow this case, as guard for the user.
-                assert!(!matches!(op1.index(), Reg64::rsp));
-                (0b00, 0b100)
+                assert!(!matches!(op.index(), Reg64::rsp));
+                let sib = sib(0, op.index().idx(), base.idx());
+                if base.is_pc_rel() {
+                    // With mod 0b00 the SIB byte encodes no base, encode with a zero disp8.
+                    self.emit(&[modrm(0b01, reg, 0b100), sib, 0]);
+                } else {
+                    self.emit(&[modrm(0b00, reg, 0b100), sib]);
+                }
             }
-        };
+            AddrMode::IndirectBaseIndexScaleDisp => {
+                // Any base can be encoded with a disp32, see above for the index.
+                assert!(!matches!(op.index(), Reg64::rsp));
+                let scale = op.scale().trailing_zeros() as u8;
+                self.emit(&[
+                    modrm(0b10, reg, 0b100),
+                    sib(scale, op.index().idx(), base.idx()),
+                ]);
+                self.emit(&op.disp().to_ne_bytes());
+            }
+        }
+    }
 
+    /// Encode a register-register instruction with differently sized operands, eg `movzx`. The
+    /// operand size is defined by the destination `op1`.
+    pub(crate) fn encode_rr_ext<T: Reg, U: Reg>(&mut self, opc: &[u8], op1: T, op2: U)
+    where
+        Self: EncodeR<T>,
+    {
+        let start = self.buf.len();
+        // RM operand encoding.
+        //   op1 -> modrm.reg
+        //   op2 -> modrm.rm
         let modrm = modrm(
-            mode,      /* mode */
-            op2.idx(), /* reg */
-            rm,        /* rm */
+            0b11,      /* mod */
+            op1.idx(), /* reg */
+            op2.idx(), /* rm */
         );
 
-        let prefix = <Self as EncodeMR<M>>::legacy_prefix();
-        let rex = <Self as EncodeMR<M>>::rex(&op1, op2);
+        let prefix = <Self as EncodeR<T>>::legacy_prefix();
+        let rex =
+            (op1.need_rex() || op2.need_rex()).then(|| rex(op1.rexw(), op1.idx(), 0, op2.idx()));
+        let rex = check_high8(rex, op1.is_high8() || op2.is_high8());
 
         self.emit_optional(&[prefix, rex]);
-        self.emit(&[opc, modrm]);
-        match op1.mode() {
-            AddrMode::Indirect => {}
-            AddrMode::IndirectDisp => self.emit(&op1.disp().to_ne_bytes()),
-            AddrMode::IndirectBaseIndex => {
-                self.emit(&[sib(0, op1.index().idx(), op1.base().idx())])
-            }
-        }
+        self.emit(opc);
+        self.emit(&[modrm]);
+        self.insn_category("reg, reg", start);
     }
 
-    /// Encode a register-memory instruction.
-    pub(crate) fn encode_rm<T: Reg, M: Mem>(&mut self, opc: u8, op1: T, op2: M)
+    /// Encode a register-memory instruction with differently sized operands, eg `movzx`. The
+    /// operand size is defined by the destination `op1`.
+    pub(crate) fn encode_rm_ext<T: Reg, M: Mem>(&mut self, opc: &[u8], op1: T, op2: M)
     where
-        Self: EncodeMR<M>,
+        Self: EncodeR<T>,
     {
+        let start = self.buf.len();
         // RM operand encoding.
         //   op1 -> modrm.reg
         //   op2 -> modrm.rm
-        self.encode_mr(opc, op2, op1);
+        let reg = op1.idx();
+        let prefix = <Self as EncodeR<T>>::legacy_prefix();
+        let rex = (op1.need_rex() || op2.base().is_ext() || op2.index().is_ext())
+            .then(|| rex(op1.rexw(), reg, op2.index().idx(), op2.base().idx()));
+        let rex = check_high8(rex, op1.is_high8());
+
+        self.emit_optional(&[prefix, rex]);
+        self.emit(opc);
+        self.emit_mem(reg, &op2);
+        self.insn_category("reg, mem", start);
+    }
+
+    /// Encode an instruction without operands.
+    pub(crate) fn encode_zo(&mut self, opc: &[u8]) {
+        let start = self.buf.len();
+        self.emit(opc);
+        self.insn_category("none", start);
+    }
+
+    /// Encode an instruction with two immediate operands, eg `enter`.
+    pub(crate) fn encode_ii<T: Imm, U: Imm>(&mut self, opc: u8, op1: T, op2: U) {
+        let start = self.buf.len();
+        self.emit(&[opc]);
+        self.emit(op1.bytes());
+        self.emit(op2.bytes());
+        self.insn_category("imm, imm", start);
     }
 
-    /// Encode a jump to label instruction.
+    /// Encode a jump to label instruction with a `rel32` displacement.
     pub(crate) fn encode_jmp_label(&mut self, opc: &[u8], op1: &mut Label) {
+        self.encode_jmp_label_kind(opc, op1, RelocKind::Rel32);
+    }
+
+    /// Encode a jump to label instruction with a `rel8` displacement.
+    pub(crate) fn encode_jmp_label8(&mut self, opc: &[u8], op1: &mut Label) {
+        self.encode_jmp_label_kind(opc, op1, RelocKind::Rel8);
+    }
+
+    /// Encode a jump to label instruction, using the short `opc8` with a `rel8` displacement if
+    /// the label is already bound and in range, else the `opc32` with a `rel32` displacement.
+    pub(crate) fn encode_jmp_label_relaxed(&mut self, opc8: &[u8], opc32: &[u8], op1: &mut Label) {
+        if let Some(loc) = op1.location() {
+            // CAST: Code buffer offsets always fit into isize.
+            let next = (self.buf.len() + opc8.len() + 1) as isize;
+            if i8::try_from(loc as isize - next).is_ok() {
+                return self.encode_jmp_label8(opc8, op1);
+            }
+        }
+        self.encode_jmp_label(opc32, op1);
+    }
+
+    fn encode_jmp_label_kind(&mut self, opc: &[u8], op1: &mut Label, kind: RelocKind) {
+        let start = self.buf.len();
+
         // Emit the opcode.
         self.emit(opc);
 
-        // Record relocation offset starting at the first byte of the disp32.
-        op1.record_offset(self.buf.len());
+        // Record relocation offset starting at the first byte of the displacement.
+        self.record_reloc(op1, self.buf.len(), kind);
 
-        // Emit a zeroed disp32, which serves as placeholder for the relocation.
-        // We currently only support disp32 jump targets.
-        self.emit(&[0u8; 4]);
+        // Emit a zeroed displacement, which serves as placeholder for the relocation.
+        match kind {
+            RelocKind::Rel8 => self.emit(&[0u8; 1]),
+            RelocKind::Rel32 | RelocKind::Table(_) => self.emit(&[0u8; 4]),
+        }
 
         // Resolve any pending relocations for the label.
         self.resolve(op1);
+
+        self.insn_category("label", start);
     }
 }
 
@@ -346,11 +1310,12 @@ pub(crate) trait EncodeRR<T: Reg> {
     }
 
     fn rex(op1: T, op2: T) -> Option<u8> {
-        if op1.need_rex() || op2.need_rex() {
+        let rex = if op1.need_rex() || op2.need_rex() {
             Some(rex(op1.rexw(), op2.idx(), 0, op1.idx()))
         } else {
             None
-        }
+        };
+        check_high8(rex, op1.is_high8() || op2.is_high8())
     }
 }
 
@@ -394,7 +1359,7 @@ pub(crate) trait EncodeMR<M: Mem> {
     }
 
     fn rex<T: Reg>(op1: &M, op2: T) -> Option<u8> {
-        if M::is_64() || op2.is_ext() || op1.base().is_ext() || op1.index().is_ext() {
+        let rex = if M::is_64() || op2.need_rex() || op1.base().is_ext() || op1.index().is_ext() {
             Some(rex(
                 M::is_64(),
                 op2.idx(),
@@ -403,7 +1368,8 @@ pub(crate) trait EncodeMR<M: Mem> {
             ))
         } else {
             None
-        }
+        };
+        check_high8(rex, op2.is_high8())
     }
 }
 
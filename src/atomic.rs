@@ -0,0 +1,114 @@
+//! Atomic loads and stores following the memory orderings of
+//! [`std::sync::atomic::Ordering`].
+//!
+//! On `x64` aligned loads and stores are atomic and all orderings except a sequentially
+//! consistent store map to a plain `mov`. A sequentially consistent store additionally requires
+//! a `mfence`, as otherwise a later load may be reordered before the store.
+
+use std::sync::atomic::Ordering;
+
+use crate::insn::Mov;
+use crate::Asm;
+
+impl Asm {
+    /// Emit an atomic load `dst = [mem]` with the memory ordering `ord`. The memory operand must
+    /// be naturally aligned.
+    ///
+    /// ```rust
+    /// use juicebox_asm::{Asm, Mem64, Reg64::*};
+    /// use std::sync::atomic::Ordering;
+    ///
+    /// let mut asm = Asm::new();
+    /// asm.atomic_load(rax, Mem64::indirect(rdi), Ordering::Acquire);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ord` is [`Release`](Ordering::Release) or [`AcqRel`](Ordering::AcqRel), like
+    /// [`AtomicU64::load`](std::sync::atomic::AtomicU64::load).
+    pub fn atomic_load<T, M>(&mut self, dst: T, mem: M, ord: Ordering)
+    where
+        Self: Mov<T, M>,
+    {
+        assert!(
+            !matches!(ord, Ordering::Release | Ordering::AcqRel),
+            "Invalid ordering {:?} for an atomic load",
+            ord
+        );
+        self.mov(dst, mem);
+    }
+
+    /// Emit an atomic store `[mem] = src` with the memory ordering `ord`. The memory operand must
+    /// be naturally aligned.
+    ///
+    /// ```rust
+    /// use juicebox_asm::{Asm, Mem64, Reg64::*};
+    /// use std::sync::atomic::Ordering;
+    ///
+    /// let mut asm = Asm::new();
+    /// asm.atomic_store(Mem64::indirect(rdi), rax, Ordering::SeqCst);
+    /// assert_eq!(asm.len(), 3 /* mov */ + 3 /* mfence */);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ord` is [`Acquire`](Ordering::Acquire) or [`AcqRel`](Ordering::AcqRel), like
+    /// [`AtomicU64::store`](std::sync::atomic::AtomicU64::store).
+    pub fn atomic_store<M, T>(&mut self, mem: M, src: T, ord: Ordering)
+    where
+        Self: Mov<M, T>,
+    {
+        assert!(
+            !matches!(ord, Ordering::Acquire | Ordering::AcqRel),
+            "Invalid ordering {:?} for an atomic store",
+            ord
+        );
+        self.mov(mem, src);
+        if ord == Ordering::SeqCst {
+            self.mfence();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Imm16, Mem16, Mem32, Reg32, Reg64::*};
+
+    #[test]
+    fn test_orderings() {
+        let enc = |ord| {
+            let mut asm = Asm::new();
+            asm.atomic_load(Reg32::eax, Mem32::indirect(rdi), ord);
+            asm.into_code()
+        };
+        for ord in [Ordering::Relaxed, Ordering::Acquire, Ordering::SeqCst] {
+            assert_eq!(enc(ord), [0x8b, 0x07]);
+        }
+
+        let enc = |ord| {
+            let mut asm = Asm::new();
+            asm.atomic_store(Mem16::indirect(rdi), Imm16::from(1u16), ord);
+            asm.into_code()
+        };
+        for ord in [Ordering::Relaxed, Ordering::Release] {
+            assert_eq!(enc(ord), [0x66, 0xc7, 0x07, 0x01, 0x00]);
+        }
+        assert_eq!(
+            enc(Ordering::SeqCst),
+            [0x66, 0xc7, 0x07, 0x01, 0x00, 0x0f, 0xae, 0xf0]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid ordering Release for an atomic load")]
+    fn test_release_load() {
+        Asm::new().atomic_load(rax, crate::Mem64::indirect(rdi), Ordering::Release);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid ordering Acquire for an atomic store")]
+    fn test_acquire_store() {
+        Asm::new().atomic_store(crate::Mem64::indirect(rdi), rax, Ordering::Acquire);
+    }
+}
@@ -0,0 +1,116 @@
+//! Import of externally produced machine code into an [Asm] buffer.
+
+use crate::label::RelocKind;
+use crate::{Asm, Label};
+
+/// A relocation in a blob of raw bytes, which is patched with the location of a [Label] by the
+/// assembler, see [`Asm::emit_blob`].
+pub struct Reloc<'a> {
+    /// Offset of the first byte of the relocation in the blob.
+    off: usize,
+    /// Label the relocation refers to.
+    label: &'a mut Label,
+}
+
+impl<'a> Reloc<'a> {
+    /// Create a `rel32` relocation at offset `off` in the blob, the 4 bytes at `off` are patched
+    /// with the displacement to `label` relative to the end of the relocation, as used by
+    /// `jmp`/`call`/`jcc` instructions with a `rel32` operand.
+    pub fn rel32(off: usize, label: &'a mut Label) -> Reloc<'a> {
+        Reloc { off, label }
+    }
+}
+
+impl Asm {
+    /// Emit a blob of raw machine code `bytes`, eg produced by another tool, and register its
+    /// `relocs` with the assembler. Relocations are resolved once the referred [Label] is bound,
+    /// or immediately if it is already bound. The placeholder bytes of a relocation are
+    /// overwritten.
+    ///
+    /// ```rust
+    /// use juicebox_asm::{Asm, Label, Reloc};
+    ///
+    /// let mut asm = Asm::new();
+    /// let mut lbl = Label::new();
+    ///
+    /// // jmp rel32
+    /// asm.emit_blob(&[0xe9, 0, 0, 0, 0], &mut [Reloc::rel32(1, &mut lbl)]);
+    /// asm.nop();
+    /// asm.bind(&mut lbl);
+    ///
+    /// assert_eq!(asm.into_code(), [0xe9, 0x01, 0x00, 0x00, 0x00, 0x90]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if a relocation indexes out of bound of the `bytes`.
+    pub fn emit_blob(&mut self, bytes: &[u8], relocs: &mut [Reloc]) {
+        for r in relocs.iter() {
+            assert!(
+                r.off.checked_add(4).is_some_and(|end| end <= bytes.len()),
+                "Relocation out of bound of blob"
+            );
+        }
+
+        let base = self.len();
+        self.emit(bytes);
+
+        for r in relocs.iter_mut() {
+            // Record relocation offset starting at the first byte of the rel32.
+            self.record_reloc(r.label, base + r.off, RelocKind::Rel32);
+            self.resolve(r.label);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_blob_backward() {
+        let mut asm = Asm::new();
+        let mut lbl = Label::new();
+        asm.bind(&mut lbl);
+        asm.nop();
+        // call rel32 ; jmp rel32
+        asm.emit_blob(
+            &[0xe8, 0xaa, 0xaa, 0xaa, 0xaa, 0xe9, 0xbb, 0xbb, 0xbb, 0xbb],
+            &mut [Reloc::rel32(1, &mut lbl)],
+        );
+        assert_eq!(
+            asm.into_code(),
+            [0x90, 0xe8, 0xfa, 0xff, 0xff, 0xff, 0xe9, 0xbb, 0xbb, 0xbb, 0xbb]
+        );
+    }
+
+    #[test]
+    fn test_blob_exec() {
+        use crate::insn::Mov;
+        use crate::{Imm64, Reg64, Runtime};
+
+        let mut asm = Asm::new();
+        let mut end = Label::new();
+        asm.mov(Reg64::rax, Imm64::from(1));
+        // jmp rel32 ; mov eax, 2
+        asm.emit_blob(
+            &[0xe9, 0, 0, 0, 0, 0xb8, 0x02, 0x00, 0x00, 0x00],
+            &mut [Reloc::rel32(1, &mut end)],
+        );
+        asm.bind(&mut end);
+        asm.ret();
+
+        let mut rt = Runtime::new();
+        let f = unsafe { rt.add_code::<extern "C" fn() -> u64>(asm.into_code()) };
+        assert_eq!(f(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Relocation out of bound of blob")]
+    fn test_blob_oob() {
+        let mut asm = Asm::new();
+        let mut lbl = Label::new();
+        asm.bind(&mut lbl);
+        asm.emit_blob(&[0xe9, 0, 0, 0], &mut [Reloc::rel32(1, &mut lbl)]);
+    }
+}
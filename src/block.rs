@@ -0,0 +1,410 @@
+//! Definition of a basic block level assembler, which lays out blocks and manages fallthroughs
+//! before the final encoding.
+
+use std::collections::HashMap;
+
+use crate::insn::{Jmp, Jnz, Jz};
+use crate::{Asm, Label};
+
+/// Identifier of a block in a [BlockAsm].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BlockId(usize);
+
+/// Terminator of a block, describing the control flow to the successor blocks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Terminator {
+    /// Unconditional jump to the block.
+    Jmp(BlockId),
+    /// Jump to the first block if zero (`ZF = 1`), else to the second block.
+    Jz(BlockId, BlockId),
+    /// Jump to the first block if not zero (`ZF = 0`), else to the second block.
+    Jnz(BlockId, BlockId),
+    /// Return from the function.
+    Ret,
+}
+
+impl Terminator {
+    /// Get the successor blocks, the second one is the preferred fallthrough block.
+    fn successors(&self) -> (Option<BlockId>, Option<BlockId>) {
+        match *self {
+            Terminator::Jmp(t) => (None, Some(t)),
+            Terminator::Jz(t, f) | Terminator::Jnz(t, f) => (Some(t), Some(f)),
+            Terminator::Ret => (None, None),
+        }
+    }
+}
+
+/// A basic block, consisting of the block body and its terminator.
+struct Block {
+    body: Asm,
+    term: Option<Terminator>,
+    hotness: u32,
+}
+
+/// A basic block level assembler.
+///
+/// Code is emitted into blocks, which are terminated with branches to other blocks. During
+/// [`BlockAsm::finalize`] the blocks are laid out such that fallthroughs are straightened and
+/// colder blocks are moved to the end, jumps to the block laid out next are omitted. The layout
+/// can be guided by block hotness hints and edge weights, eg collected by profiling.
+///
+/// The first created block is the entry block and is always laid out first.
+///
+/// ```rust
+/// use juicebox_asm::{BlockAsm, Reg64, Terminator};
+/// use juicebox_asm::insn::{Dec, Test};
+///
+/// let mut basm = BlockAsm::new();
+/// let entry = basm.create_block();
+/// let exit = basm.create_block();
+/// let body = basm.create_block();
+///
+/// // entry: if (rdi == 0) goto exit else goto body;
+/// basm.block(entry).test(Reg64::rdi, Reg64::rdi);
+/// basm.terminate(entry, Terminator::Jz(exit, body));
+///
+/// // body: --rdi; goto exit;
+/// basm.block(body).dec(Reg64::rdi);
+/// basm.terminate(body, Terminator::Jmp(exit));
+///
+/// // exit: return;
+/// basm.terminate(exit, Terminator::Ret);
+///
+/// // Laid out as entry, body, exit, hence all jumps except the jz are omitted.
+/// let code = basm.finalize().into_code();
+/// assert_eq!(code.len(), 3 /* test */ + 6 /* jz */ + 3 /* dec */ + 1 /* ret */);
+/// ```
+pub struct BlockAsm {
+    blocks: Vec<Block>,
+    weights: HashMap<(BlockId, BlockId), u64>,
+}
+
+impl BlockAsm {
+    /// Create a new basic block level assembler without any blocks.
+    pub fn new() -> BlockAsm {
+        BlockAsm {
+            blocks: Vec::new(),
+            weights: HashMap::new(),
+        }
+    }
+
+    /// Create a new empty block, the first created block is the entry block.
+    pub fn create_block(&mut self) -> BlockId {
+        self.blocks.push(Block {
+            body: Asm::new(),
+            term: None,
+            hotness: 0,
+        });
+        BlockId(self.blocks.len() - 1)
+    }
+
+    /// Get the assembler to emit code into the body of block `id`.
+    ///
+    /// Labels used in the body must be bound in the same body, control flow between blocks must
+    /// be expressed via the block [Terminator].
+    ///
+    /// # Panics
+    ///
+    /// Panics if block `id` is already terminated.
+    pub fn block(&mut self, id: BlockId) -> &mut Asm {
+        let block = &mut self.blocks[id.0];
+        assert!(block.term.is_none(), "Block already terminated");
+        &mut block.body
+    }
+
+    /// Terminate the block `id` with `term`, a block can only be terminated once.
+    ///
+    /// # Panics
+    ///
+    /// Panics if block `id` is already terminated.
+    pub fn terminate(&mut self, id: BlockId, term: Terminator) {
+        let block = &mut self.blocks[id.0];
+        assert!(block.term.is_none(), "Block already terminated");
+        block.term = Some(term);
+    }
+
+    /// Set the hotness hint of block `id`, hotter blocks are laid out first.
+    pub fn set_hotness(&mut self, id: BlockId, hotness: u32) {
+        self.blocks[id.0].hotness = hotness;
+    }
+
+    /// Set the weight of the edge from block `from` to block `to`, eg the number of times the
+    /// edge was taken during profiling. The layout avoids taken branches along heavier edges.
+    ///
+    /// Setting a weight for an edge which does not exist in the control flow graph has no effect.
+    pub fn set_edge_weight(&mut self, from: BlockId, to: BlockId, weight: u64) {
+        self.weights.insert((from, to), weight);
+    }
+
+    /// Compute the layout of the blocks.
+    ///
+    /// The layout is computed bottom up in the spirit of _Pettis and Hansen_. Every block starts
+    /// as its own chain. The control flow edges are visited from the heaviest to the lightest
+    /// edge, where edges without weight are ranked by the hotness of the target block and the
+    /// fallthrough successor is preferred over the taken one. An edge `a -> b` merges the chains
+    /// if `a` ends one chain and `b` starts another chain, such that `b` becomes the fallthrough
+    /// of `a`.
+    ///
+    /// The chain containing the entry block is laid out first, followed by the remaining chains
+    /// ordered by their hottest block.
+    fn layout(&self) -> Vec<BlockId> {
+        let weight = |from: BlockId, to: BlockId| {
+            let w = self.weights.get(&(from, to)).copied().unwrap_or(0);
+            (w, self.blocks[to.0].hotness)
+        };
+
+        // Collect all edges, fallthrough edges first for each block.
+        let mut edges = Vec::new();
+        for (from, block) in self.blocks.iter().enumerate() {
+            let (taken, fallthrough) = block
+                .term
+                .as_ref()
+                .map_or((None, None), Terminator::successors);
+            for to in [fallthrough, taken].into_iter().flatten() {
+                edges.push((BlockId(from), to));
+            }
+        }
+        // Stable sort keeps the fallthrough preference and creation order on ties.
+        edges.sort_by_key(|&(from, to)| std::cmp::Reverse(weight(from, to)));
+
+        // Initially each block forms its own chain.
+        let mut chains: Vec<Vec<BlockId>> =
+            (0..self.blocks.len()).map(|b| vec![BlockId(b)]).collect();
+        let mut chain_of: Vec<usize> = (0..self.blocks.len()).collect();
+
+        for (from, to) in edges {
+            let (cf, ct) = (chain_of[from.0], chain_of[to.0]);
+            // The entry block must always start a chain.
+            if cf == ct || to.0 == 0 || chains[cf].last() != Some(&from) || chains[ct][0] != to {
+                continue;
+            }
+
+            let tail = std::mem::take(&mut chains[ct]);
+            for b in &tail {
+                chain_of[b.0] = cf;
+            }
+            chains[cf].extend(tail);
+        }
+
+        // Entry chain first, followed by the remaining chains ordered by hotness, ties are broken
+        // by the creation order of the chain heads.
+        let mut rest: Vec<_> = chains.into_iter().filter(|c| !c.is_empty()).collect();
+        let entry = rest.iter().position(|c| c[0].0 == 0);
+        let mut order = entry.map_or_else(Vec::new, |e| rest.remove(e));
+
+        rest.sort_by_key(|c| {
+            let hotness = c.iter().map(|b| self.blocks[b.0].hotness).max();
+            (std::cmp::Reverse(hotness), c[0].0)
+        });
+        order.extend(rest.into_iter().flatten());
+
+        order
+    }
+
+    /// Lay out the blocks and encode them into an [Asm].
+    ///
+    /// # Panics
+    ///
+    /// Panics if any block is not terminated.
+    pub fn finalize(self) -> Asm {
+        // Validate before creating any labels, as unbound labels panic when dropped.
+        assert!(
+            self.blocks.iter().all(|b| b.term.is_some()),
+            "Block not terminated"
+        );
+
+        let order = self.layout();
+
+        let mut labels: Vec<Label> = self.blocks.iter().map(|_| Label::new()).collect();
+        let mut asm = Asm::new();
+
+        let mut blocks: Vec<Option<Block>> = self.blocks.into_iter().map(Some).collect();
+
+        for (pos, &id) in order.iter().enumerate() {
+            let next = order.get(pos + 1).copied();
+
+            // UNWRAP: Each block is laid out exactly once.
+            let block = blocks[id.0].take().unwrap();
+            // UNWRAP: All blocks are terminated, validated above.
+            let term = block.term.unwrap();
+
+            asm.bind(&mut labels[id.0]);
+            if let Some(stats) = block.body.stats() {
+                asm.enable_stats();
+                // UNWRAP: Stats enabled above.
+                asm.stats_mut().unwrap().merge(stats);
+            }
+            let base = asm.len();
+            asm.traps_mut().merge(block.body.traps(), base);
+            asm.emit(&block.body.into_code());
+
+            match term {
+                Terminator::Jmp(t) => {
+                    if Some(t) != next {
+                        asm.jmp(&mut labels[t.0]);
+                    }
+                }
+                Terminator::Jz(t, f) => {
+                    if Some(t) == next {
+                        asm.jnz(&mut labels[f.0]);
+                    } else {
+                        asm.jz(&mut labels[t.0]);
+                        if Some(f) != next {
+                            asm.jmp(&mut labels[f.0]);
+                        }
+                    }
+                }
+                Terminator::Jnz(t, f) => {
+                    if Some(t) == next {
+                        asm.jz(&mut labels[f.0]);
+                    } else {
+                        asm.jnz(&mut labels[t.0]);
+                        if Some(f) != next {
+                            asm.jmp(&mut labels[f.0]);
+                        }
+                    }
+                }
+                Terminator::Ret => asm.ret(),
+            }
+        }
+
+        asm
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_fallthrough() {
+        let mut basm = BlockAsm::new();
+        let b0 = basm.create_block();
+        let b1 = basm.create_block();
+        let b2 = basm.create_block();
+
+        // Created out of order, layout must follow the jumps b0 -> b2 -> b1.
+        basm.block(b0).nop();
+        basm.terminate(b0, Terminator::Jmp(b2));
+        basm.terminate(b1, Terminator::Ret);
+        basm.terminate(b2, Terminator::Jmp(b1));
+
+        assert_eq!(basm.layout(), [b0, b2, b1]);
+        assert_eq!(basm.finalize().into_code(), [0x90, 0xc3]);
+    }
+
+    #[test]
+    fn test_invert_cond() {
+        let mut basm = BlockAsm::new();
+        let b0 = basm.create_block();
+        let b1 = basm.create_block();
+        let b2 = basm.create_block();
+
+        // Taken target b1 is hotter and therefore laid out as fallthrough, the condition must be
+        // inverted.
+        basm.set_hotness(b1, 10);
+        basm.terminate(b0, Terminator::Jz(b1, b2));
+        basm.terminate(b1, Terminator::Ret);
+        basm.terminate(b2, Terminator::Ret);
+
+        assert_eq!(basm.layout(), [b0, b1, b2]);
+        #[rustfmt::skip]
+        assert_eq!(
+            basm.finalize().into_code(),
+            [
+                0x0f, 0x85, 0x01, 0x00, 0x00, 0x00, // jnz b2
+                0xc3,                               // b1: ret
+                0xc3,                               // b2: ret
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cond_no_fallthrough() {
+        let mut basm = BlockAsm::new();
+        let b0 = basm.create_block();
+        let b1 = basm.create_block();
+        let b2 = basm.create_block();
+
+        // Both successors are placed before b2, b2 needs a conditional and an unconditional jump.
+        basm.terminate(b0, Terminator::Jmp(b1));
+        basm.terminate(b1, Terminator::Jmp(b2));
+        basm.terminate(b2, Terminator::Jnz(b0, b1));
+
+        assert_eq!(basm.layout(), [b0, b1, b2]);
+        #[rustfmt::skip]
+        assert_eq!(
+            basm.finalize().into_code(),
+            [
+                0x75, 0xfe, // b2: jnz b0
+                0xeb, 0xfc, //     jmp b1
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hotness_order() {
+        let mut basm = BlockAsm::new();
+        let b0 = basm.create_block();
+        let b1 = basm.create_block();
+        let b2 = basm.create_block();
+
+        // Unreachable blocks are ordered by hotness.
+        basm.set_hotness(b2, 1);
+        basm.terminate(b0, Terminator::Ret);
+        basm.terminate(b1, Terminator::Ret);
+        basm.terminate(b2, Terminator::Ret);
+
+        assert_eq!(basm.layout(), [b0, b2, b1]);
+    }
+
+    #[test]
+    fn test_edge_weights() {
+        let mut basm = BlockAsm::new();
+        let entry = basm.create_block();
+        let head = basm.create_block();
+        let body = basm.create_block();
+        let exit = basm.create_block();
+
+        // entry -> head
+        // head  -> body | exit
+        // body  -> head
+        basm.terminate(entry, Terminator::Jmp(head));
+        basm.terminate(head, Terminator::Jz(exit, body));
+        basm.terminate(body, Terminator::Jmp(head));
+        basm.terminate(exit, Terminator::Ret);
+
+        // Without weights the fallthrough successor is preferred.
+        assert_eq!(basm.layout(), [entry, head, body, exit]);
+
+        // With a heavier exit edge, the exit is laid out as fallthrough of the loop head.
+        basm.set_edge_weight(head, body, 1);
+        basm.set_edge_weight(head, exit, 100);
+        assert_eq!(basm.layout(), [entry, head, exit, body]);
+    }
+
+    #[test]
+    fn test_traps() {
+        let mut basm = BlockAsm::new();
+        let b0 = basm.create_block();
+        let b1 = basm.create_block();
+
+        // Laid out as b0, b1, the trap site of b1 is placed after the nop of b0.
+        basm.block(b0).nop();
+        basm.block(b1).trapping(7, |asm| asm.nop());
+        basm.terminate(b0, Terminator::Jmp(b1));
+        basm.terminate(b1, Terminator::Ret);
+
+        let asm = basm.finalize();
+        assert_eq!(asm.traps().lookup(0), None);
+        assert_eq!(asm.traps().lookup(1), Some(7));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_not_terminated() {
+        let mut basm = BlockAsm::new();
+        basm.create_block();
+        basm.finalize();
+    }
+}
@@ -0,0 +1,174 @@
+//! Stack canaries for jitted frames, to detect stack corruption originating in generated code.
+//!
+//! [`Asm::canary_prologue`] pushes the canary value at the function entry, and
+//! [`Asm::canary_epilogue`] checks it before returning. A mismatch invokes the configured
+//! [`CanaryFail`] behavior.
+
+use std::ffi::c_void;
+
+use crate::insn::{Call, Cmp, Mov, Pop, Push};
+use crate::{Asm, Cond, Imm64, Label, Len, Reg64};
+
+/// Hook invoked on a canary mismatch with the user `data` and the `found` canary value, see
+/// [`CanaryFail::Call`].
+pub type CanaryHook = extern "C" fn(data: *mut c_void, found: u64);
+
+/// Behavior on a canary mismatch.
+#[derive(Clone, Copy, Debug)]
+pub enum CanaryFail {
+    /// Trap with an `ud2` instruction.
+    Trap,
+    /// Invoke the hook with the user data, the hook must not return. If it returns anyway, the
+    /// code traps with an `ud2` instruction.
+    Call(CanaryHook, *mut c_void),
+}
+
+/// Stack canary configuration, see [`Asm::canary_prologue`].
+#[derive(Clone, Copy, Debug)]
+pub struct Canary {
+    value: u64,
+    fail: CanaryFail,
+}
+
+impl Canary {
+    /// Create a canary with a fixed `value`.
+    pub fn new(value: u64, fail: CanaryFail) -> Canary {
+        Canary { value, fail }
+    }
+
+    /// Create a canary with a random value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the random value can not be obtained from the system.
+    pub fn random(fail: CanaryFail) -> Canary {
+        let mut value = 0u64;
+        let ret = unsafe { libc::getrandom((&mut value as *mut u64).cast(), 8, 0) };
+        assert_eq!(ret, 8, "Failed to get random canary");
+        Canary { value, fail }
+    }
+
+    /// Get the canary value.
+    pub fn value(&self) -> u64 {
+        self.value
+    }
+}
+
+impl Asm {
+    /// Emit the canary store, which must be emitted at the function entry.
+    ///
+    /// Pushes the canary value, which keeps the stack 16 byte aligned for the frame of the
+    /// function, like a `push rbp`. Clobbers `r11`.
+    pub fn canary_prologue(&mut self, canary: &Canary) {
+        self.redzone_check(Reg64::rsp, -8, Len::Const(8));
+        self.mov(Reg64::r11, Imm64::from(canary.value));
+        self.push(Reg64::r11);
+    }
+
+    /// Emit the canary check, which must be emitted right before the `ret` of the function with
+    /// the stack pointer restored to the value after [`Asm::canary_prologue`].
+    ///
+    /// Pops the canary value and invokes the [`CanaryFail`] behavior on a mismatch. Clobbers `r10`,
+    /// `r11` and the flags, the return value registers `rax` and `rdx` are preserved.
+    pub fn canary_epilogue(&mut self, canary: &Canary) {
+        use Reg64::*;
+
+        let mut ok = Label::new();
+
+        self.redzone_check(rsp, 0, Len::Const(8));
+        self.pop(r11);
+        self.mov(r10, Imm64::from(canary.value));
+        self.cmp(r11, r10);
+        self.jcc_short(Cond::E, &mut ok);
+
+        if let CanaryFail::Call(hook, data) = canary.fail {
+            // Pushing the found value realigns the stack to 16 byte for the call.
+            self.push(r11);
+            self.mov(rsi, r11);
+            self.mov(rdi, Imm64::from(data as usize));
+            self.mov(rax, Imm64::from(hook as usize));
+            self.call(rax);
+        }
+        self.ud2();
+
+        self.bind(&mut ok);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const VALUE: u64 = 0x1234_5678_9abc_def0;
+
+    /// Compile a `fn(v: u64) -> u64` returning `v`, which overwrites the canary with `v` if
+    /// `corrupt` is set.
+    fn compile(canary: &Canary, corrupt: bool) -> Vec<u8> {
+        use Reg64::*;
+
+        let mut asm = Asm::new();
+        asm.canary_prologue(canary);
+        if corrupt {
+            asm.pop(r10);
+            asm.push(rdi);
+        }
+        asm.mov(rax, rdi);
+        asm.canary_epilogue(canary);
+        asm.ret();
+        asm.into_code()
+    }
+
+    /// Run the `code` in a forked child with the argument `v` and get the wait status.
+    fn run_child(code: &[u8], v: u64) -> libc::c_int {
+        let mut rt = crate::Runtime::new();
+        let f = unsafe { rt.add_code::<extern "C" fn(u64) -> u64>(code) };
+
+        match unsafe { libc::fork() } {
+            0 => {
+                let ret = f(v);
+                unsafe { libc::_exit(if ret == v { 0 } else { 1 }) };
+            }
+            pid => {
+                assert!(pid > 0, "Failed to fork");
+                let mut status = 0;
+                assert_eq!(unsafe { libc::waitpid(pid, &mut status, 0) }, pid);
+                status
+            }
+        }
+    }
+
+    extern "C" fn hook(data: *mut c_void, found: u64) {
+        let code = if data as u64 == VALUE && found == 42 {
+            42
+        } else {
+            1
+        };
+        unsafe { libc::_exit(code) };
+    }
+
+    #[test]
+    fn test_ok() {
+        let canary = Canary::random(CanaryFail::Trap);
+        let mut rt = crate::Runtime::new();
+        let f = unsafe { rt.add_code::<extern "C" fn(u64) -> u64>(compile(&canary, false)) };
+        assert_eq!(f(7), 7);
+    }
+
+    #[test]
+    fn test_trap() {
+        let canary = Canary::new(VALUE, CanaryFail::Trap);
+        let status = run_child(&compile(&canary, false), 42);
+        assert!(libc::WIFEXITED(status) && libc::WEXITSTATUS(status) == 0);
+
+        let status = run_child(&compile(&canary, true), 42);
+        assert!(libc::WIFSIGNALED(status) && libc::WTERMSIG(status) == libc::SIGILL);
+    }
+
+    #[test]
+    fn test_call() {
+        let canary = Canary::new(VALUE, CanaryFail::Call(hook, VALUE as *mut c_void));
+        let status = run_child(&compile(&canary, true), 42);
+        assert!(libc::WIFEXITED(status));
+        assert_eq!(libc::WEXITSTATUS(status), 42);
+    }
+}
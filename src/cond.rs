@@ -0,0 +1,97 @@
+//! Definition of the condition codes used by conditional instructions, eg `jcc`.
+
+/// Condition code of a conditional instruction, evaluated on the status flags.
+///
+/// The conditions `B`, `Be`, `A` and `Ae` are meant for unsigned comparisons, the conditions `L`,
+/// `Le`, `G` and `Ge` for signed comparisons.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Cond {
+    /// Overflow (`OF = 1`).
+    O,
+    /// Not overflow (`OF = 0`).
+    No,
+    /// Below (`CF = 1`).
+    B,
+    /// Above or equal (`CF = 0`).
+    Ae,
+    /// Equal / zero (`ZF = 1`).
+    E,
+    /// Not equal / not zero (`ZF = 0`).
+    Ne,
+    /// Below or equal (`CF = 1 or ZF = 1`).
+    Be,
+    /// Above (`CF = 0 and ZF = 0`).
+    A,
+    /// Sign (`SF = 1`).
+    S,
+    /// Not sign (`SF = 0`).
+    Ns,
+    /// Parity (`PF = 1`).
+    P,
+    /// Not parity (`PF = 0`).
+    Np,
+    /// Less (`SF != OF`).
+    L,
+    /// Greater or equal (`SF = OF`).
+    Ge,
+    /// Less or equal (`ZF = 1 or SF != OF`).
+    Le,
+    /// Greater (`ZF = 0 and SF = OF`).
+    G,
+}
+
+impl Cond {
+    /// All condition codes, ordered by their encoding.
+    pub const ALL: [Cond; 16] = [
+        Cond::O,
+        Cond::No,
+        Cond::B,
+        Cond::Ae,
+        Cond::E,
+        Cond::Ne,
+        Cond::Be,
+        Cond::A,
+        Cond::S,
+        Cond::Ns,
+        Cond::P,
+        Cond::Np,
+        Cond::L,
+        Cond::Ge,
+        Cond::Le,
+        Cond::G,
+    ];
+
+    /// Get the negated condition, eg `Ne` for `E`.
+    pub const fn negate(self) -> Cond {
+        Cond::ALL[(self.code() ^ 1) as usize]
+    }
+
+    /// Get the 4 bit condition code, which is added to the base opcode of the conditional
+    /// instructions.
+    pub(crate) const fn code(self) -> u8 {
+        self as u8
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_code() {
+        for (code, cond) in Cond::ALL.into_iter().enumerate() {
+            assert_eq!(usize::from(cond.code()), code);
+        }
+    }
+
+    #[test]
+    fn test_negate() {
+        assert_eq!(Cond::E.negate(), Cond::Ne);
+        assert_eq!(Cond::Ne.negate(), Cond::E);
+        assert_eq!(Cond::L.negate(), Cond::Ge);
+        assert_eq!(Cond::A.negate(), Cond::Be);
+        for cond in Cond::ALL {
+            assert_eq!(cond.negate().negate(), cond);
+        }
+    }
+}
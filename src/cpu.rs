@@ -0,0 +1,63 @@
+//! Optional CPU features, used by the assembler to select instruction sequences.
+
+/// Set of optional CPU features the emitted code may use, see [`Asm::set_cpu_features`].
+///
+/// [`Asm::set_cpu_features`]: crate::Asm::set_cpu_features
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CpuFeatures {
+    /// [`movbe`](https://www.felixcloutier.com/x86/movbe) instruction.
+    pub movbe: bool,
+    /// AVX2 vector instructions.
+    pub avx2: bool,
+    /// [`clflushopt`](https://www.felixcloutier.com/x86/clflushopt) instruction.
+    pub clflushopt: bool,
+    /// [`clwb`](https://www.felixcloutier.com/x86/clwb) instruction.
+    pub clwb: bool,
+    /// [`lzcnt`](https://www.felixcloutier.com/x86/lzcnt) instruction.
+    pub lzcnt: bool,
+    /// BMI1 instructions, eg [`tzcnt`](https://www.felixcloutier.com/x86/tzcnt).
+    pub bmi1: bool,
+    /// [`popcnt`](https://www.felixcloutier.com/x86/popcnt) instruction.
+    pub popcnt: bool,
+}
+
+impl CpuFeatures {
+    /// Features available on every `x64` CPU, no optional feature is used.
+    pub fn baseline() -> CpuFeatures {
+        CpuFeatures::default()
+    }
+
+    /// Features available on the host CPU.
+    pub fn host() -> CpuFeatures {
+        // The cache-line flush features are not known to `is_x86_feature_detected`, read them from
+        // the structured extended feature flags of cpuid leaf 7.
+        use std::arch::x86_64::{__cpuid, __cpuid_count};
+        let ebx = if __cpuid(0).eax >= 7 {
+            __cpuid_count(7, 0).ebx
+        } else {
+            0
+        };
+
+        CpuFeatures {
+            movbe: std::arch::is_x86_feature_detected!("movbe"),
+            avx2: std::arch::is_x86_feature_detected!("avx2"),
+            clflushopt: ebx & (1 << 23) != 0,
+            clwb: ebx & (1 << 24) != 0,
+            lzcnt: std::arch::is_x86_feature_detected!("lzcnt"),
+            bmi1: std::arch::is_x86_feature_detected!("bmi1"),
+            popcnt: std::arch::is_x86_feature_detected!("popcnt"),
+        }
+    }
+
+    /// Check if all features of `other` are also available in `self`.
+    pub fn contains(&self, other: &CpuFeatures) -> bool {
+        (self.movbe || !other.movbe)
+            && (self.avx2 || !other.avx2)
+            && (self.clflushopt || !other.clflushopt)
+            && (self.clwb || !other.clwb)
+            && (self.lzcnt || !other.lzcnt)
+            && (self.bmi1 || !other.bmi1)
+            && (self.popcnt || !other.popcnt)
+    }
+}
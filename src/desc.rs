@@ -0,0 +1,272 @@
+//! Read-only table of [FunctionDescriptor] records for the code added to a
+//! [`Runtime`](crate::Runtime), see [`Runtime::enable_descriptors`](crate::Runtime::enable_descriptors).
+//!
+//! The table lives on its own `mmap`ed page which is kept read-only, except while a descriptor
+//! is appended. Lookups take no locks and do not allocate, which allows to resolve instruction
+//! pointers from signal handlers and samplers.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Descriptor of a function added to the runtime.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FunctionDescriptor {
+    /// Address of the first instruction of the function.
+    pub entry: usize,
+    /// Size in bytes of the function.
+    pub size: usize,
+    /// Offset of the function name on the descriptor page.
+    pub name_off: u32,
+    /// Length in bytes of the function name.
+    pub name_len: u32,
+    /// User data attached to the function.
+    pub data: usize,
+}
+
+impl FunctionDescriptor {
+    /// Check if `ip` points into the function.
+    pub fn contains(&self, ip: usize) -> bool {
+        (self.entry..self.entry + self.size).contains(&ip)
+    }
+}
+
+/// Size of the descriptor page.
+const PAGE_SIZE: usize = 4096;
+
+/// Header at the start of the descriptor page, followed by the descriptors. The names are
+/// allocated from the end of the page.
+#[repr(C)]
+struct Header {
+    /// Number of published descriptors.
+    count: AtomicUsize,
+    /// Start offset of the lowest allocated name.
+    names: usize,
+}
+
+/// Owner of the descriptor page.
+pub(crate) struct DescPage {
+    buf: *mut u8,
+}
+
+impl DescPage {
+    /// Create a new empty descriptor page.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `mmap` call fails.
+    pub(crate) fn new() -> DescPage {
+        let buf = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                PAGE_SIZE,
+                libc::PROT_READ,
+                libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+                0, /* fd */
+                0, /* off */
+            ) as *mut u8
+        };
+        assert_ne!(
+            buf.cast(),
+            libc::MAP_FAILED,
+            "Failed to mmap runtime descriptor page"
+        );
+
+        let mut page = DescPage { buf };
+        page.write(|hdr, _| hdr.names = PAGE_SIZE);
+        page
+    }
+
+    /// Get a view of the descriptors on the page.
+    pub(crate) fn descriptors(&self) -> Descriptors<'_> {
+        Descriptors {
+            buf: self.buf,
+            _rt: std::marker::PhantomData,
+        }
+    }
+
+    /// Append a descriptor for the function at `entry`.
+    ///
+    /// Descriptors must be appended in increasing order of `entry`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the descriptor page is full.
+    pub(crate) fn append(&mut self, entry: usize, size: usize, name: &str, data: usize) {
+        // Check capacity before making the page writable.
+        let hdr = unsafe { &*self.buf.cast::<Header>() };
+        assert!(
+            desc_off(hdr.count.load(Ordering::Relaxed) + 1) + name.len() <= hdr.names,
+            "Runtime descriptor page full"
+        );
+
+        self.write(|hdr, buf| {
+            let count = hdr.count.load(Ordering::Relaxed);
+
+            // Copy the name to the end of the page.
+            let name_off = hdr.names - name.len();
+            unsafe { std::ptr::copy_nonoverlapping(name.as_ptr(), buf.add(name_off), name.len()) };
+            hdr.names = name_off;
+
+            // UNWRAP: Offsets and lengths on the page fit into an u32.
+            let desc = FunctionDescriptor {
+                entry,
+                size,
+                name_off: u32::try_from(name_off).unwrap(),
+                name_len: u32::try_from(name.len()).unwrap(),
+                data,
+            };
+            unsafe {
+                buf.add(desc_off(count))
+                    .cast::<FunctionDescriptor>()
+                    .write(desc)
+            };
+
+            // Publish the descriptor to concurrent readers.
+            hdr.count.store(count + 1, Ordering::Release);
+        });
+    }
+
+    /// Make the page writable while running `f`.
+    fn write(&mut self, f: impl FnOnce(&mut Header, *mut u8)) {
+        unsafe {
+            let ret = libc::mprotect(
+                self.buf.cast(),
+                PAGE_SIZE,
+                libc::PROT_READ | libc::PROT_WRITE,
+            );
+            assert_eq!(ret, 0, "Failed to RW mprotect runtime descriptor page");
+
+            f(&mut *self.buf.cast::<Header>(), self.buf);
+
+            let ret = libc::mprotect(self.buf.cast(), PAGE_SIZE, libc::PROT_READ);
+            assert_eq!(ret, 0, "Failed to R mprotect runtime descriptor page");
+        }
+    }
+}
+
+impl Drop for DescPage {
+    fn drop(&mut self) {
+        unsafe {
+            let ret = libc::munmap(self.buf.cast(), PAGE_SIZE);
+            assert_eq!(ret, 0, "Failed to munmap runtime descriptor page");
+        }
+    }
+}
+
+/// Offset of the descriptor `idx` on the page.
+const fn desc_off(idx: usize) -> usize {
+    let hdr = std::mem::size_of::<Header>();
+    hdr + idx * std::mem::size_of::<FunctionDescriptor>()
+}
+
+/// Lock-free view of the descriptor table of a [`Runtime`](crate::Runtime).
+///
+/// The view is cheap to copy and can be turned into a raw pointer with [`Descriptors::as_ptr`]
+/// to be stashed away for signal handlers, which can not borrow the runtime.
+#[derive(Clone, Copy)]
+pub struct Descriptors<'a> {
+    buf: *const u8,
+    _rt: std::marker::PhantomData<&'a crate::Runtime>,
+}
+
+// SAFETY: The view only reads the descriptor page, which is synchronized by the atomic count.
+unsafe impl Send for Descriptors<'_> {}
+unsafe impl Sync for Descriptors<'_> {}
+
+impl<'a> Descriptors<'a> {
+    /// Get the raw pointer of the descriptor page.
+    pub fn as_ptr(&self) -> *const u8 {
+        self.buf
+    }
+
+    /// Create a view from a raw pointer obtained by [`Descriptors::as_ptr`].
+    ///
+    /// # Safety
+    ///
+    /// The runtime owning the descriptor page must outlive the view.
+    pub unsafe fn from_ptr(ptr: *const u8) -> Descriptors<'a> {
+        Descriptors {
+            buf: ptr,
+            _rt: std::marker::PhantomData,
+        }
+    }
+
+    /// Get all published descriptors, ordered by increasing entry address.
+    pub fn all(&self) -> &'a [FunctionDescriptor] {
+        let hdr = unsafe { &*self.buf.cast::<Header>() };
+        let count = hdr.count.load(Ordering::Acquire);
+        unsafe {
+            std::slice::from_raw_parts(
+                self.buf.add(desc_off(0)).cast::<FunctionDescriptor>(),
+                count,
+            )
+        }
+    }
+
+    /// Lookup the descriptor of the function containing the instruction pointer `ip`.
+    pub fn lookup(&self, ip: usize) -> Option<&'a FunctionDescriptor> {
+        let descs = self.all();
+        let idx = descs.partition_point(|d| d.entry <= ip);
+        descs[..idx].last().filter(|d| d.contains(ip))
+    }
+
+    /// Get the name of the function described by `desc`, which must be obtained from this view.
+    pub fn name(&self, desc: &FunctionDescriptor) -> &'a str {
+        let off = desc.name_off as usize;
+        let len = desc.name_len as usize;
+        assert!(off + len <= PAGE_SIZE, "Descriptor name out of bound");
+        // UNWRAP: Names are copied from a str.
+        std::str::from_utf8(unsafe { std::slice::from_raw_parts(self.buf.add(off), len) }).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Runtime;
+
+    #[test]
+    fn test_lookup() {
+        let mut rt = Runtime::new();
+        assert!(rt.descriptors().is_none());
+        rt.enable_descriptors();
+
+        let code = [
+            0x90, /* nop */
+            0x90, /* nop */
+            0xc3, /* ret */
+        ];
+        let f = unsafe { rt.add_function::<*const u8>("foo", 42, code) } as usize;
+        let g = unsafe { rt.add_code::<*const u8>(code) } as usize;
+        let h = unsafe { rt.add_function::<*const u8>("bar", 7, code) } as usize;
+
+        let descs = rt.descriptors().unwrap();
+        assert_eq!(descs.all().len(), 3);
+
+        let d = descs.lookup(f + 1).unwrap();
+        assert_eq!((d.entry, d.size, d.data), (f, 3, 42));
+        assert_eq!(descs.name(d), "foo");
+
+        let d = descs.lookup(g).unwrap();
+        assert_eq!((d.entry, descs.name(d)), (g, ""));
+
+        let d = descs.lookup(h + 2).unwrap();
+        assert_eq!((d.entry, d.data, descs.name(d)), (h, 7, "bar"));
+
+        assert!(descs.lookup(f - 1).is_none());
+        assert!(descs.lookup(h + 3).is_none());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_page_full() {
+        let mut rt = Runtime::new();
+        rt.enable_descriptors();
+
+        let name = "x".repeat(1000);
+        for _ in 0..5 {
+            unsafe {
+                rt.add_function::<*const u8>(&name, 0, [0xc3 /* ret */])
+            };
+        }
+    }
+}
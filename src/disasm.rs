@@ -1,5 +1,8 @@
 use std::io::{ErrorKind, Write};
 use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::Syntax;
 
 /// Disassemble the code currently added to the runtime, using
 /// [`ndisasm`](https://nasm.us/index.php) and print it to _stdout_. If
@@ -11,8 +14,29 @@ use std::process::{Command, Stdio};
 /// Panics if anything goes wrong with spawning, writing to or reading from
 /// the `ndisasm` child process.
 pub(crate) fn disasm<T: AsRef<[u8]>>(code: T) {
-    let code = code.as_ref();
+    disasm_with(code, Syntax::Intel);
+}
 
+/// Disassemble the code in the given [Syntax] and print it to _stdout_.
+///
+/// [`Syntax::Intel`] uses [`ndisasm`](https://nasm.us/index.php), [`Syntax::Att`] uses
+/// [`objdump`](https://sourceware.org/binutils/docs/binutils/objdump.html) as `ndisasm` only
+/// supports intel syntax. If the tool is not available on the system this prints a warning and
+/// becomes a nop.
+///
+/// # Panics
+///
+/// Panics if anything goes wrong with spawning, writing to or reading from
+/// the disassembler child process.
+pub(crate) fn disasm_with<T: AsRef<[u8]>>(code: T, syntax: Syntax) {
+    match syntax {
+        Syntax::Intel => ndisasm(code.as_ref()),
+        Syntax::Att => objdump(code.as_ref()),
+    }
+}
+
+/// Disassemble with `ndisasm`, which expects input on stdin.
+fn ndisasm(code: &[u8]) {
     // Create ndisasm process, which expects input on stdin.
     let mut child = match Command::new("ndisasm")
         .args(["-b64", "-"])
@@ -49,3 +73,51 @@ pub(crate) fn disasm<T: AsRef<[u8]>>(code: T) {
         )
     );
 }
+
+/// Disassemble with `objdump`, which expects input in a file.
+fn objdump(code: &[u8]) {
+    match objdump_output(code, "att") {
+        Some(out) => println!("{}", out),
+        None => println!("disasm: skipping, objdump not found"),
+    }
+}
+
+/// Disassemble `code` with `objdump` using the disassembler `style` (`att` or `intel`) and get
+/// the textual output, `None` if `objdump` is not available on the system.
+///
+/// # Panics
+///
+/// Panics if anything goes wrong with writing the code file or spawning the `objdump` process.
+pub(crate) fn objdump_output(code: &[u8], style: &str) -> Option<String> {
+    // Unique file name per disassemble call.
+    static CNT: AtomicUsize = AtomicUsize::new(0);
+    let path = std::env::temp_dir().join(format!(
+        "juicebox-disasm-{}-{}.bin",
+        std::process::id(),
+        CNT.fetch_add(1, Ordering::Relaxed)
+    ));
+    std::fs::write(&path, code).expect("failed to write code file");
+
+    let out = Command::new("objdump")
+        .args([
+            "-D",
+            "-b",
+            "binary",
+            "-m",
+            "i386:x86-64",
+            "-M",
+            style,
+            "--no-show-raw-insn",
+        ])
+        .arg(&path)
+        .output();
+    let _ = std::fs::remove_file(&path);
+
+    match out {
+        Ok(out) => Some(String::from_utf8_lossy(&out.stdout).into_owned()),
+        Err(err) if err.kind() == ErrorKind::NotFound => None,
+        Err(err) => {
+            panic!("{:?}", err);
+        }
+    }
+}
@@ -0,0 +1,222 @@
+//! Function multi-versioning by CPU feature, see [`Runtime::add_multiversion`].
+//!
+//! All versions of a function are added to the runtime behind a dispatch function, which jumps
+//! indirectly through an [Entry]. Initially the entry points to a resolver stub. On the first call
+//! the stub selects the best version for the host CPU, publishes it to the entry and continues
+//! into the selected version. All subsequent calls jump directly to the selected version, similar
+//! to an IFUNC resolved by the dynamic linker.
+
+use crate::insn::{Call, Jmp, Mov, Pop, Push};
+use crate::{Asm, CpuFeatures, Entry, Imm64, Mem64, Reg64, Runtime};
+
+/// Resolver state of a multi-versioned function, owned by the [Runtime].
+pub(crate) struct Resolver {
+    /// Entry the dispatch function jumps through.
+    entry: Entry,
+    /// Versions with their required features, ordered by preference.
+    versions: Vec<(CpuFeatures, usize)>,
+    /// Features to select the version for.
+    host: CpuFeatures,
+}
+
+impl Resolver {
+    /// Get the address of the first version supported by the host.
+    fn select(&self) -> Option<usize> {
+        self.versions
+            .iter()
+            .find(|(req, _)| self.host.contains(req))
+            .map(|(_, addr)| *addr)
+    }
+}
+
+/// Resolve the version on the first call, invoked by the resolver stub.
+extern "C" fn resolve(res: *const Resolver) -> *const u8 {
+    // SAFETY: The resolver is owned by the runtime and outlives the emitted code.
+    let res = unsafe { &*res };
+    // UNWRAP: A supported version is checked when adding the function.
+    let ptr = res.select().unwrap() as *const u8;
+    // Concurrent first calls publish the same version.
+    res.entry.publish(ptr);
+    ptr
+}
+
+/// Emit the resolver stub, which calls [resolve] and continues into the selected version.
+///
+/// The stub preserves the integer argument registers and `rax`, like
+/// [`Asm::hot_counter`](crate::Asm::hot_counter).
+fn emit_stub(asm: &mut Asm, res: *const Resolver) {
+    use Reg64::*;
+
+    // Seven registers are pushed which keeps the stack 16 byte aligned at the call.
+    let regs = [rax, rdi, rsi, rdx, rcx, r8, r9];
+    for r in regs {
+        asm.push(r);
+    }
+    asm.mov(rdi, Imm64::from(res as usize));
+    let resolve: extern "C" fn(*const Resolver) -> *const u8 = resolve;
+    asm.mov(rax, Imm64::from(resolve as usize));
+    asm.call(rax);
+    asm.mov(r11, rax);
+    for r in regs.into_iter().rev() {
+        asm.pop(r);
+    }
+    asm.jmp(r11);
+}
+
+/// Emit the dispatch function, which jumps through the `entry`. Only clobbers the scratch
+/// register `r11`, which is not used for argument passing.
+fn emit_dispatch(asm: &mut Asm, entry: &Entry) {
+    use Reg64::r11;
+
+    // An aligned 8 byte load is atomic on x64.
+    asm.mov(r11, Imm64::from(entry.slot() as usize));
+    asm.jmp(Mem64::indirect(r11));
+}
+
+impl Runtime {
+    /// Add multiple `versions` of a function, each with the [`CpuFeatures`] it requires, and get
+    /// a function pointer of type `F` to a dispatch function.
+    ///
+    /// The versions are ordered by preference, on the first call the first version supported by
+    /// the host CPU is selected and all calls are forwarded to it from then on.
+    ///
+    /// ```rust
+    /// use juicebox_asm::insn::Mov;
+    /// use juicebox_asm::{Asm, CpuFeatures, Imm64, Reg64, Runtime};
+    ///
+    /// let version = |v: u64| {
+    ///     let mut asm = Asm::new();
+    ///     asm.mov(Reg64::rax, Imm64::from(v));
+    ///     asm.ret();
+    ///     asm.into_code()
+    /// };
+    ///
+    /// let mut rt = Runtime::new();
+    /// let host = CpuFeatures::host();
+    /// let f = unsafe {
+    ///     rt.add_multiversion::<extern "C" fn() -> u64>(&[
+    ///         (host, &version(2)),
+    ///         (CpuFeatures::baseline(), &version(1)),
+    ///     ])
+    /// };
+    /// assert_eq!(f(), 2);
+    /// ```
+    ///
+    /// The resolver stub preserves the integer argument registers, vector registers are not
+    /// preserved by the resolver invoked on the first call.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no version is supported by the host CPU or under the same conditions as
+    /// [`Runtime::add_code`].
+    ///
+    /// # Safety
+    ///
+    /// All versions must fulfill the ABI of the specified function `F` and the returned function
+    /// pointer is only valid until the [`Runtime`] is dropped.
+    pub unsafe fn add_multiversion<F>(&mut self, versions: &[(CpuFeatures, &[u8])]) -> F {
+        let mut res = Box::new(Resolver {
+            entry: Entry::new(std::ptr::null()),
+            versions: Vec::new(),
+            host: CpuFeatures::host(),
+        });
+        for (req, code) in versions {
+            let addr = unsafe { self.add_code::<*const u8>(code) };
+            res.versions.push((*req, addr as usize));
+        }
+        assert!(
+            res.select().is_some(),
+            "No version supported by the host CPU"
+        );
+
+        let mut asm = Asm::new();
+        emit_stub(&mut asm, &*res);
+        let stub = unsafe { self.add_code::<*const u8>(asm.into_code()) };
+        res.entry = Entry::new(stub);
+
+        let mut asm = Asm::new();
+        emit_dispatch(&mut asm, &res.entry);
+        let dispatch = unsafe { self.add_code::<F>(asm.into_code()) };
+
+        // The resolver is boxed, its address embedded in the stub stays valid.
+        self.add_resolver(res);
+        dispatch
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::insn::Add;
+    use Reg64::*;
+
+    /// Get a version returning `v` plus all integer arguments.
+    fn version(v: u64) -> Vec<u8> {
+        let mut asm = Asm::new();
+        asm.mov(rax, Imm64::from(v));
+        for r in [rdi, rsi, rdx, rcx, r8, r9] {
+            asm.add(rax, r);
+        }
+        asm.ret();
+        asm.into_code()
+    }
+
+    type Fn6 = extern "C" fn(u64, u64, u64, u64, u64, u64) -> u64;
+
+    #[test]
+    fn test_select() {
+        let mut rt = Runtime::new();
+        let host = CpuFeatures::host();
+        let base = CpuFeatures::baseline();
+        let all = CpuFeatures {
+            movbe: true,
+            avx2: true,
+            clflushopt: true,
+            clwb: true,
+            lzcnt: true,
+            bmi1: true,
+            popcnt: true,
+        };
+
+        let f =
+            unsafe { rt.add_multiversion::<Fn6>(&[(host, &version(200)), (base, &version(100))]) };
+        assert_eq!(f(1, 2, 3, 4, 5, 6), 221);
+        // Second call goes directly to the selected version.
+        assert_eq!(f(1, 2, 3, 4, 5, 6), 221);
+
+        let f =
+            unsafe { rt.add_multiversion::<Fn6>(&[(base, &version(100)), (host, &version(200))]) };
+        assert_eq!(f(0, 0, 0, 0, 0, 1), 101);
+
+        let f =
+            unsafe { rt.add_multiversion::<Fn6>(&[(all, &version(200)), (base, &version(100))]) };
+        let expect = if host.contains(&all) { 200 } else { 100 };
+        assert_eq!(f(0, 0, 0, 0, 0, 0), expect);
+    }
+
+    #[test]
+    fn test_publish() {
+        let mut rt = Runtime::new();
+        let f = unsafe { rt.add_multiversion::<Fn6>(&[(CpuFeatures::baseline(), &version(7))]) };
+        let res = rt.resolvers().last().unwrap();
+        assert_eq!(res.entry.generation(), 0);
+
+        let threads: Vec<_> = (0..4)
+            .map(|i| std::thread::spawn(move || f(i, 0, 0, 0, 0, 0)))
+            .collect();
+        for (i, t) in threads.into_iter().enumerate() {
+            assert_eq!(t.join().unwrap(), 7 + i as u64);
+        }
+
+        let res = rt.resolvers().last().unwrap();
+        assert!(res.entry.generation() >= 1);
+        assert_eq!(res.entry.load().0 as usize, res.versions[0].1);
+    }
+
+    #[test]
+    #[should_panic(expected = "No version supported by the host CPU")]
+    fn test_no_version() {
+        let mut rt = Runtime::new();
+        unsafe { rt.add_multiversion::<Fn6>(&[]) };
+    }
+}
@@ -0,0 +1,253 @@
+//! Big-endian loads and stores, eg for emulating big-endian guests.
+//!
+//! The helpers use `movbe` if enabled in the [`CpuFeatures`](crate::CpuFeatures) of the
+//! assembler, else a `mov` combined with a byte swap. The byte swap of the 16 bit helpers is a
+//! `rol` by 8 bits, which clobbers the `CF` and `OF` flags, all other sequences preserve the
+//! flags.
+
+use crate::insn::{Bswap, Mov, Movbe, Rol};
+use crate::mem::{AddrMode, Mem};
+use crate::{Asm, Imm8, Mem16, Mem32, Mem64, Reg16, Reg32, Reg64};
+
+/// Check that the register with the index `src` does not address the memory operand `mem`, as the
+/// stores without `movbe` swap the bytes of the source register in place.
+fn assert_no_addr<M: Mem>(mem: &M, src: u8) {
+    let index = matches!(
+        mem.mode(),
+        AddrMode::IndirectBaseIndex | AddrMode::IndirectBaseIndexScaleDisp
+    ) && mem.index().index() == src;
+    assert!(
+        mem.base().index() != src && !index,
+        "Source register must not address the memory operand"
+    );
+}
+
+impl Asm {
+    /// Emit a big-endian 16 bit load `dst = [mem]`. Without `movbe` the `CF` and `OF` flags are
+    /// clobbered.
+    pub fn load_be16(&mut self, dst: Reg16, mem: Mem16) {
+        if self.cpu_features().movbe {
+            self.movbe(dst, mem);
+        } else {
+            self.mov(dst, mem);
+            self.rol(dst, Imm8::from(8u8));
+        }
+    }
+
+    /// Emit a big-endian 32 bit load `dst = [mem]`.
+    pub fn load_be32(&mut self, dst: Reg32, mem: Mem32) {
+        if self.cpu_features().movbe {
+            self.movbe(dst, mem);
+        } else {
+            self.mov(dst, mem);
+            self.bswap(dst);
+        }
+    }
+
+    /// Emit a big-endian 64 bit load `dst = [mem]`.
+    pub fn load_be64(&mut self, dst: Reg64, mem: Mem64) {
+        if self.cpu_features().movbe {
+            self.movbe(dst, mem);
+        } else {
+            self.mov(dst, mem);
+            self.bswap(dst);
+        }
+    }
+
+    /// Emit a big-endian 16 bit store `[mem] = src`, `src` is preserved. Without `movbe` the `CF`
+    /// and `OF` flags are clobbered.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `src` is the base or index register of `mem`.
+    pub fn store_be16(&mut self, mem: Mem16, src: Reg16) {
+        assert_no_addr(&mem, src.index());
+        if self.cpu_features().movbe {
+            self.movbe(mem, src);
+        } else {
+            self.rol(src, Imm8::from(8u8));
+            self.mov(mem, src);
+            self.rol(src, Imm8::from(8u8));
+        }
+    }
+
+    /// Emit a big-endian 32 bit store `[mem] = src`, `src` is preserved.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `src` is the base or index register of `mem`.
+    pub fn store_be32(&mut self, mem: Mem32, src: Reg32) {
+        assert_no_addr(&mem, src.index());
+        if self.cpu_features().movbe {
+            self.movbe(mem, src);
+        } else {
+            self.bswap(src);
+            self.mov(mem, src);
+            self.bswap(src);
+        }
+    }
+
+    /// Emit a big-endian 64 bit store `[mem] = src`, `src` is preserved.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `src` is the base or index register of `mem`.
+    pub fn store_be64(&mut self, mem: Mem64, src: Reg64) {
+        assert_no_addr(&mem, src.index());
+        if self.cpu_features().movbe {
+            self.movbe(mem, src);
+        } else {
+            self.bswap(src);
+            self.mov(mem, src);
+            self.bswap(src);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::insn::{Add, Setcc};
+    use crate::{Cond, CpuFeatures, Imm64, Reg8, Runtime};
+    use Reg64::*;
+
+    /// Feature sets to test with, movbe only if available on the host.
+    fn features() -> Vec<CpuFeatures> {
+        let mut all = vec![CpuFeatures::baseline()];
+        if CpuFeatures::host().movbe {
+            all.push(CpuFeatures::host());
+        }
+        all
+    }
+
+    #[test]
+    fn test_deterministic() {
+        // Emit on assemblers created on hosts with and without movbe.
+        let emit = |movbe: bool| {
+            let mut features = CpuFeatures::baseline();
+            features.movbe = movbe;
+
+            let mut asm = Asm::new();
+            asm.set_cpu_features(features);
+            asm.set_deterministic();
+            asm.load_be64(rax, Mem64::indirect(rdi));
+            asm.store_be32(Mem32::indirect(rdi), Reg32::eax);
+            asm.ret();
+            asm.into_code()
+        };
+
+        let code = emit(false);
+        assert_eq!(code, emit(true));
+        // Baseline sequence without movbe.
+        assert_eq!(code[..6], [0x48, 0x8b, 0x07, 0x48, 0x0f, 0xc8]);
+    }
+
+    #[test]
+    fn test_encoding() {
+        let mut asm = Asm::new();
+        asm.set_cpu_features(CpuFeatures::baseline());
+        asm.load_be64(rax, Mem64::indirect(rdi));
+        asm.store_be32(Mem32::indirect(rdi), Reg32::r9d);
+        assert_eq!(
+            asm.into_code(),
+            [
+                0x48, 0x8b, 0x07, // mov rax, [rdi]
+                0x48, 0x0f, 0xc8, // bswap rax
+                0x41, 0x0f, 0xc9, // bswap r9d
+                0x44, 0x89, 0x0f, // mov [rdi], r9d
+                0x41, 0x0f, 0xc9, // bswap r9d
+            ]
+        );
+
+        let mut asm = Asm::new();
+        let mut movbe = CpuFeatures::baseline();
+        movbe.movbe = true;
+        asm.set_cpu_features(movbe);
+        asm.load_be16(Reg16::cx, Mem16::indirect(rsi));
+        asm.store_be64(Mem64::indirect(rdi), r10);
+        assert_eq!(
+            asm.into_code(),
+            [
+                0x66, 0x0f, 0x38, 0xf0, 0x0e, // movbe cx, [rsi]
+                0x4c, 0x0f, 0x38, 0xf1, 0x17, // movbe [rdi], r10
+            ]
+        );
+    }
+
+    #[test]
+    fn test_load_store() {
+        let mut rt = Runtime::new();
+        for f in features() {
+            // fn(src: *const u8, dst: *mut u8) -> u64, loads all widths from src and stores them
+            // to dst, returns the 64 bit load.
+            let mut asm = Asm::new();
+            asm.set_cpu_features(f);
+            asm.load_be16(Reg16::ax, Mem16::indirect(rdi));
+            asm.store_be16(Mem16::indirect(rsi), Reg16::ax);
+            asm.load_be32(Reg32::eax, Mem32::indirect_disp(rdi, 2));
+            asm.store_be32(Mem32::indirect_disp(rsi, 2), Reg32::eax);
+            asm.load_be64(rax, Mem64::indirect_disp(rdi, 6));
+            asm.store_be64(Mem64::indirect_disp(rsi, 6), rax);
+            asm.ret();
+            let copy =
+                unsafe { rt.add_code::<extern "C" fn(*const u8, *mut u8) -> u64>(asm.into_code()) };
+
+            let src: [u8; 14] = std::array::from_fn(|i| i as u8 + 1);
+            let mut dst = [0u8; 14];
+            let val = copy(src.as_ptr(), dst.as_mut_ptr());
+            assert_eq!(val, u64::from_be_bytes([7, 8, 9, 10, 11, 12, 13, 14]));
+            assert_eq!(dst, src, "{:?}", f);
+        }
+    }
+
+    #[test]
+    fn test_flags() {
+        let mut rt = Runtime::new();
+        let mut buf = [0u8; 8];
+
+        // Get the carry flag after the sequence emitted by `emit` with the carry flag set before,
+        // the value 0x100 is in rax.
+        let mut carry = |f: CpuFeatures, emit: fn(&mut Asm)| {
+            let mut asm = Asm::new();
+            asm.set_cpu_features(f);
+            asm.mov(rax, Imm64::from(u64::MAX));
+            asm.add(rax, Imm8::from(1u8));
+            asm.mov(rax, Imm64::from(0x100u64));
+            emit(&mut asm);
+            asm.setcc(Cond::B, Reg8::al);
+            asm.ret();
+            let f = unsafe { rt.add_code::<extern "C" fn(*mut u8) -> u8>(asm.into_code()) };
+            f(buf.as_mut_ptr()) == 1
+        };
+
+        for f in features() {
+            assert!(carry(f, |asm| asm.load_be32(Reg32::eax, Mem32::indirect(rdi))));
+            assert!(carry(f, |asm| asm.load_be64(rax, Mem64::indirect(rdi))));
+            assert!(carry(f, |asm| asm.store_be32(Mem32::indirect(rdi), Reg32::eax)));
+            assert!(carry(f, |asm| asm.store_be64(Mem64::indirect(rdi), rax)));
+
+            // The rol of the 16 bit byte swap sets the carry flag to the low bit of the result.
+            let movbe = f.movbe;
+            assert_eq!(
+                carry(f, |asm| asm.store_be16(Mem16::indirect(rdi), Reg16::ax)),
+                movbe
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic = "Source register must not address the memory operand"]
+    fn test_store_base() {
+        let mut asm = Asm::new();
+        asm.set_cpu_features(CpuFeatures::baseline());
+        asm.store_be64(Mem64::indirect(rdi), rdi);
+    }
+
+    #[test]
+    #[should_panic = "Source register must not address the memory operand"]
+    fn test_store_index() {
+        let mut asm = Asm::new();
+        asm.set_cpu_features(CpuFeatures::baseline());
+        asm.store_be16(Mem16::indirect_base_index(rsi, rdi), Reg16::di);
+    }
+}
@@ -0,0 +1,135 @@
+//! Functions with multiple named entry points, eg a fast path and a slow path entry, emitted and
+//! installed as a single block of code.
+
+use std::collections::HashMap;
+
+use crate::Asm;
+
+/// Entry points of a block of code installed with
+/// [`Runtime::install_entries`](crate::Runtime::install_entries), declared with [`Asm::entry`].
+///
+/// ```rust
+/// use juicebox_asm::{Asm, Imm64, Reg64, Runtime};
+/// use juicebox_asm::insn::Mov;
+///
+/// let mut asm = Asm::new();
+/// asm.entry("slow");
+/// asm.mov(Reg64::rdi, Imm64::from(1));
+/// asm.entry("fast");
+/// asm.mov(Reg64::rax, Reg64::rdi);
+/// asm.ret();
+///
+/// let mut rt = Runtime::new();
+/// let entries = rt.install_entries(asm);
+///
+/// let fast = unsafe { entries.get::<extern "C" fn(u64) -> u64>("fast") }.unwrap();
+/// let slow = unsafe { entries.get::<extern "C" fn(u64) -> u64>("slow") }.unwrap();
+/// assert_eq!(fast(2), 2);
+/// assert_eq!(slow(2), 1);
+/// ```
+#[derive(Debug)]
+pub struct EntryPoints {
+    /// Entry points by name, as address to keep the map `Send + Sync`.
+    entries: HashMap<String, usize>,
+}
+
+impl EntryPoints {
+    /// Create the entry points of code installed at address `base` from the code offsets of the
+    /// `entries`.
+    pub(crate) fn new(base: *const u8, entries: Vec<(String, usize)>) -> EntryPoints {
+        EntryPoints {
+            entries: entries
+                .into_iter()
+                .map(|(name, off)| (name, base as usize + off))
+                .collect(),
+        }
+    }
+
+    /// Get the address of the entry point `name`, `None` if not declared.
+    pub fn resolve(&self, name: &str) -> Option<*const u8> {
+        self.entries.get(name).map(|&addr| addr as *const u8)
+    }
+
+    /// Get the entry point `name` as function pointer of type `F`, `None` if not declared.
+    ///
+    /// # Safety
+    ///
+    /// The code at the entry point must fulfill the ABI of `F` and the returned function pointer
+    /// is only valid until the [`Runtime`](crate::Runtime) is dropped.
+    pub unsafe fn get<F>(&self, name: &str) -> Option<F> {
+        self.resolve(name)
+            .map(|ptr| unsafe { std::mem::transmute_copy(&ptr) })
+    }
+
+    /// Get the names of the entry points, in arbitrary order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(String::as_str)
+    }
+}
+
+impl Asm {
+    /// Declare the entry point `name` at the current location, which is retrieved after
+    /// installing the code with [`Runtime::install_entries`](crate::Runtime::install_entries).
+    ///
+    /// Entry points are only recorded, they do not emit any code. Hence the code falling through
+    /// into an entry point continues with the code following it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the entry point `name` is already declared.
+    pub fn entry(&mut self, name: &str) {
+        let off = self.len();
+        let entries = self.entries_mut();
+        assert!(
+            entries.iter().all(|(n, _)| n != name),
+            "Entry point {} already declared",
+            name
+        );
+        entries.push((name.to_string(), off));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::insn::{Jmp, Mov};
+    use crate::{Imm64, Label, Reg64::*, Runtime};
+
+    #[test]
+    fn test_entries() {
+        let mut asm = Asm::new();
+        let mut ret = Label::new();
+
+        asm.entry("one");
+        asm.mov(rax, Imm64::from(1u64));
+        asm.jmp(&mut ret);
+        asm.entry("two");
+        asm.mov(rax, Imm64::from(2u64));
+        asm.bind(&mut ret);
+        asm.ret();
+
+        let mut rt = Runtime::new();
+        rt.randomize_placement();
+        unsafe { rt.add_code::<extern "C" fn()>([0xc3]) };
+        let entries = rt.install_entries(asm);
+
+        let mut names: Vec<_> = entries.names().collect();
+        names.sort();
+        assert_eq!(names, ["one", "two"]);
+
+        let one = unsafe { entries.get::<extern "C" fn() -> u64>("one") }.unwrap();
+        let two = unsafe { entries.get::<extern "C" fn() -> u64>("two") }.unwrap();
+        assert_eq!(one(), 1);
+        assert_eq!(two(), 2);
+        assert!(unsafe { entries.get::<extern "C" fn() -> u64>("three") }.is_none());
+    }
+
+    #[test]
+    #[should_panic = "Entry point one already declared"]
+    fn test_entry_twice() {
+        let mut asm = Asm::new();
+        asm.entry("one");
+        asm.nop();
+        asm.entry("one");
+    }
+}
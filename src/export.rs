@@ -0,0 +1,207 @@
+//! Export of emitted code as textual assembly.
+//!
+//! The exported `.s` file uses GNU `as` intel syntax and can be assembled and linked by a standard
+//! toolchain, or diffed against compiler output. The exported listing additionally shows the offset
+//! and the encoding of each instruction, eg for snapshot tests.
+//!
+//! Instructions are obtained by disassembling the emitted code with
+//! [`objdump`](https://sourceware.org/binutils/docs/binutils/objdump.html). Branch targets inside
+//! the code are replaced with local labels. Instructions which can not be represented as text are
+//! exported as raw `.byte` directives, if `objdump` is not available this is done for the whole
+//! code.
+
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+/// A disassembled instruction.
+struct Insn<'a> {
+    off: usize,
+    mnemonic: &'a str,
+    operands: &'a str,
+}
+
+/// Parse the instructions from the `objdump` output, lines look like `   a:\tmov    rax,rdi`.
+fn parse(out: &str) -> Vec<Insn<'_>> {
+    out.lines()
+        .filter_map(|line| {
+            let (off, text) = line.split_once(":\t")?;
+            let off = usize::from_str_radix(off.trim(), 16).ok()?;
+            let (mnemonic, operands) = text.split_once(' ').unwrap_or((text, ""));
+            Some(Insn {
+                off,
+                mnemonic,
+                operands: operands.trim(),
+            })
+        })
+        .collect()
+}
+
+/// Check if the `mnemonic` names a relative branch instruction.
+fn is_branch(mnemonic: &str) -> bool {
+    mnemonic.starts_with('j') || mnemonic.starts_with("loop") || mnemonic == "call"
+}
+
+/// Get the branch target of `insn`, if it is a relative branch to a hex offset.
+fn branch_target(insn: &Insn) -> Option<usize> {
+    if !is_branch(insn.mnemonic) {
+        return None;
+    }
+    usize::from_str_radix(insn.operands.strip_prefix("0x")?, 16).ok()
+}
+
+/// Get the branch targets of `insns` which are at an instruction boundary, only those can be
+/// replaced with a label.
+fn branch_targets(insns: &[Insn]) -> BTreeSet<usize> {
+    let bounds: BTreeSet<_> = insns.iter().map(|insn| insn.off).collect();
+    insns
+        .iter()
+        .filter_map(branch_target)
+        .filter(|tgt| bounds.contains(tgt))
+        .collect()
+}
+
+/// Emit `bytes` as `.byte` directives.
+fn emit_bytes(s: &mut String, bytes: &[u8]) {
+    for chunk in bytes.chunks(16) {
+        let bytes: Vec<_> = chunk.iter().map(|b| format!("0x{:02x}", b)).collect();
+        // UNWRAP: Writing to a String can not fail.
+        writeln!(s, "\t.byte\t{}", bytes.join(", ")).unwrap();
+    }
+}
+
+/// Export `code` as textual assembly defining the function `symbol`.
+pub(crate) fn export_s(code: &[u8], symbol: &str) -> String {
+    let mut s = String::new();
+    // UNWRAP: Writing to a String can not fail.
+    write!(
+        s,
+        "\t.intel_syntax noprefix\n\t.text\n\t.globl\t{0}\n\t.type\t{0}, @function\n{0}:\n",
+        symbol
+    )
+    .unwrap();
+
+    let out = crate::disasm::objdump_output(code, "intel");
+    let insns = out.as_deref().map(parse).unwrap_or_default();
+
+    if insns.is_empty() {
+        emit_bytes(&mut s, code);
+    } else {
+        let targets = branch_targets(&insns);
+        let label = |off: usize| format!(".L{}_{:x}", symbol, off);
+
+        for (idx, insn) in insns.iter().enumerate() {
+            let end = insns.get(idx + 1).map_or(code.len(), |next| next.off);
+
+            if targets.contains(&insn.off) {
+                // UNWRAP: Writing to a String can not fail.
+                writeln!(s, "{}:", label(insn.off)).unwrap();
+            }
+
+            // UNWRAP: Writing to a String can not fail.
+            match branch_target(insn) {
+                Some(tgt) if targets.contains(&tgt) => {
+                    writeln!(s, "\t{}\t{}", insn.mnemonic, label(tgt)).unwrap()
+                }
+                // Branches leaving the code and undecodable bytes are kept as raw bytes.
+                Some(_) => emit_bytes(&mut s, &code[insn.off..end]),
+                None if insn.mnemonic.contains("(bad)") => emit_bytes(&mut s, &code[insn.off..end]),
+                None if insn.operands.is_empty() => writeln!(s, "\t{}", insn.mnemonic).unwrap(),
+                None => writeln!(s, "\t{}\t{}", insn.mnemonic, insn.operands).unwrap(),
+            }
+        }
+    }
+
+    // UNWRAP: Writing to a String can not fail.
+    writeln!(s, "\t.size\t{0}, .-{0}", symbol).unwrap();
+    s
+}
+
+/// Export `code` as a listing with one instruction per line, prefixed with its offset and its
+/// encoding. Returns `None` if `objdump` is not available on the system.
+pub(crate) fn export_lst(code: &[u8]) -> Option<String> {
+    let out = crate::disasm::objdump_output(code, "intel")?;
+    let insns = parse(&out);
+    let targets = branch_targets(&insns);
+    let label = |off: usize| format!(".L{:x}", off);
+
+    let mut s = String::new();
+    for (idx, insn) in insns.iter().enumerate() {
+        let end = insns.get(idx + 1).map_or(code.len(), |next| next.off);
+
+        if targets.contains(&insn.off) {
+            // UNWRAP: Writing to a String can not fail.
+            writeln!(s, "{}:", label(insn.off)).unwrap();
+        }
+
+        let bytes: Vec<_> = code[insn.off..end]
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        let text = match branch_target(insn) {
+            Some(tgt) if targets.contains(&tgt) => format!("{} {}", insn.mnemonic, label(tgt)),
+            _ if insn.operands.is_empty() => insn.mnemonic.to_string(),
+            _ => format!("{} {}", insn.mnemonic, insn.operands),
+        };
+        // UNWRAP: Writing to a String can not fail.
+        writeln!(s, "{:04x}: {:<30} {}", insn.off, bytes.join(" "), text).unwrap();
+    }
+    Some(s)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_export_bytes() {
+        // Without disassembly everything is exported as raw bytes.
+        let mut s = String::new();
+        emit_bytes(&mut s, &[0x90; 17]);
+        assert_eq!(s.lines().count(), 2);
+        assert!(s.starts_with("\t.byte\t0x90, 0x90,"));
+        assert_eq!(s.lines().nth(1), Some("\t.byte\t0x90"));
+    }
+
+    #[test]
+    fn test_export_labels() {
+        // mov rax, rdi ; jmp +2 ; nop ; nop ; ret ; call +0x100
+        let code = [
+            0x48, 0x89, 0xf8, 0xe9, 0x02, 0x00, 0x00, 0x00, 0x90, 0x90, 0xc3, 0xe8, 0x00, 0x01,
+            0x00, 0x00,
+        ];
+        let s = export_s(&code, "foo");
+        assert!(s.starts_with("\t.intel_syntax noprefix\n\t.text\n\t.globl\tfoo\n"));
+        assert!(s.ends_with("\t.size\tfoo, .-foo\n"));
+
+        if crate::disasm::objdump_output(&code, "intel").is_none() {
+            println!("export: skipping, objdump not found");
+            return;
+        }
+        assert!(s.contains("\tmov\trax,rdi\n"));
+        assert!(s.contains("\tjmp\t.Lfoo_a\n"));
+        assert!(s.contains(".Lfoo_a:\n\tret\n"));
+        // Call target outside of the code.
+        assert!(s.contains("\t.byte\t0xe8, 0x00, 0x01, 0x00, 0x00\n"));
+    }
+
+    #[test]
+    fn test_export_lst() {
+        // mov rax, rdi ; jmp +2 ; nop ; nop ; ret
+        let code = [
+            0x48, 0x89, 0xf8, 0xe9, 0x02, 0x00, 0x00, 0x00, 0x90, 0x90, 0xc3,
+        ];
+        let Some(s) = export_lst(&code) else {
+            println!("export: skipping, objdump not found");
+            return;
+        };
+        assert_eq!(
+            s,
+            "0000: 48 89 f8                       mov rax,rdi\n\
+             0003: e9 02 00 00 00                 jmp .La\n\
+             0008: 90                             nop\n\
+             0009: 90                             nop\n\
+             .La:\n\
+             000a: c3                             ret\n"
+        );
+    }
+}
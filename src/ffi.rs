@@ -0,0 +1,321 @@
+//! C API for driving the assembler and runtime from non-Rust code.
+//!
+//! The API is handle based, the [`Asm`], the emitted code and the [`Runtime`] are handed out as
+//! opaque pointers, which must be released with the matching `*_free` function. Registers are
+//! passed by their raw x64 register code, eg `0 -> rax`, `1 -> rcx`, .., `15 -> r15`.
+//!
+//! The corresponding C declarations are provided in `include/juicebox_asm.h`. The crate is only
+//! built as `rlib` by default, the shared library is built with
+//! `cargo rustc --release --features ffi --crate-type cdylib`.
+//!
+//! ```c
+//! Asm* asm = asm_new();
+//! asm_mov_rr(asm, 0 /* rax */, 7 /* rdi */);
+//! asm_add_rr(asm, 0 /* rax */, 6 /* rsi */);
+//! asm_ret(asm);
+//!
+//! Code* code = asm_finalize(asm);
+//! Runtime* rt = rt_new();
+//! uint64_t (*add)(uint64_t, uint64_t) = rt_add_code(rt, code);
+//! code_free(code);
+//!
+//! add(1, 2);
+//! rt_free(rt);
+//! ```
+
+use std::ffi::c_void;
+
+use crate::insn::{Add, Call, Mov, Pop, Push, Sub};
+use crate::{Asm, Imm64, Reg64, Runtime};
+
+/// Opaque handle to the code emitted by an [`Asm`].
+pub struct Code(Vec<u8>);
+
+/// Get the [`Reg64`] for the raw x64 register code `idx`.
+fn reg64(idx: u8) -> Option<Reg64> {
+    use Reg64::*;
+    const REGS: [Reg64; 16] = [
+        rax, rcx, rdx, rbx, rsp, rbp, rsi, rdi, r8, r9, r10, r11, r12, r13, r14, r15,
+    ];
+    REGS.get(usize::from(idx)).copied()
+}
+
+/// Emit an instruction with a single [`Reg64`] operand, returns `false` if `op1` is not a valid
+/// register code.
+unsafe fn emit_r(asm: *mut Asm, op1: u8, f: impl FnOnce(&mut Asm, Reg64)) -> bool {
+    let asm = unsafe { asm.as_mut() }.expect("Asm handle must not be null");
+    match reg64(op1) {
+        Some(op1) => {
+            f(asm, op1);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Emit an instruction with two [`Reg64`] operands, returns `false` if `op1` or `op2` is not a
+/// valid register code.
+unsafe fn emit_rr(asm: *mut Asm, op1: u8, op2: u8, f: impl FnOnce(&mut Asm, Reg64, Reg64)) -> bool {
+    let asm = unsafe { asm.as_mut() }.expect("Asm handle must not be null");
+    match (reg64(op1), reg64(op2)) {
+        (Some(op1), Some(op2)) => {
+            f(asm, op1, op2);
+            true
+        }
+        _ => false,
+    }
+}
+
+// -- Asm.
+
+/// Create a new [`Asm`] handle.
+#[no_mangle]
+pub extern "C" fn asm_new() -> *mut Asm {
+    Box::into_raw(Box::new(Asm::new()))
+}
+
+/// Release an [`Asm`] handle, passing `null` is a nop.
+///
+/// # Safety
+///
+/// `asm` must be `null` or a handle returned by [`asm_new`] which was not yet released.
+#[no_mangle]
+pub unsafe extern "C" fn asm_free(asm: *mut Asm) {
+    if !asm.is_null() {
+        drop(unsafe { Box::from_raw(asm) });
+    }
+}
+
+/// Emit `mov op1, op2` with 64 bit registers.
+///
+/// # Safety
+///
+/// `asm` must be a valid [`Asm`] handle.
+#[no_mangle]
+pub unsafe extern "C" fn asm_mov_rr(asm: *mut Asm, op1: u8, op2: u8) -> bool {
+    unsafe { emit_rr(asm, op1, op2, |asm, op1, op2| asm.mov(op1, op2)) }
+}
+
+/// Emit `mov op1, imm` with a 64 bit register and a 64 bit immediate.
+///
+/// # Safety
+///
+/// `asm` must be a valid [`Asm`] handle.
+#[no_mangle]
+pub unsafe extern "C" fn asm_mov_ri(asm: *mut Asm, op1: u8, imm: u64) -> bool {
+    unsafe { emit_r(asm, op1, |asm, op1| asm.mov(op1, Imm64::from(imm))) }
+}
+
+/// Emit `add op1, op2` with 64 bit registers.
+///
+/// # Safety
+///
+/// `asm` must be a valid [`Asm`] handle.
+#[no_mangle]
+pub unsafe extern "C" fn asm_add_rr(asm: *mut Asm, op1: u8, op2: u8) -> bool {
+    unsafe { emit_rr(asm, op1, op2, |asm, op1, op2| asm.add(op1, op2)) }
+}
+
+/// Emit `sub op1, op2` with 64 bit registers.
+///
+/// # Safety
+///
+/// `asm` must be a valid [`Asm`] handle.
+#[no_mangle]
+pub unsafe extern "C" fn asm_sub_rr(asm: *mut Asm, op1: u8, op2: u8) -> bool {
+    unsafe { emit_rr(asm, op1, op2, |asm, op1, op2| asm.sub(op1, op2)) }
+}
+
+/// Emit `push op1` with a 64 bit register.
+///
+/// # Safety
+///
+/// `asm` must be a valid [`Asm`] handle.
+#[no_mangle]
+pub unsafe extern "C" fn asm_push_r(asm: *mut Asm, op1: u8) -> bool {
+    unsafe { emit_r(asm, op1, |asm, op1| asm.push(op1)) }
+}
+
+/// Emit `pop op1` with a 64 bit register.
+///
+/// # Safety
+///
+/// `asm` must be a valid [`Asm`] handle.
+#[no_mangle]
+pub unsafe extern "C" fn asm_pop_r(asm: *mut Asm, op1: u8) -> bool {
+    unsafe { emit_r(asm, op1, |asm, op1| asm.pop(op1)) }
+}
+
+/// Emit `call op1` with a 64 bit register.
+///
+/// # Safety
+///
+/// `asm` must be a valid [`Asm`] handle.
+#[no_mangle]
+pub unsafe extern "C" fn asm_call_r(asm: *mut Asm, op1: u8) -> bool {
+    unsafe { emit_r(asm, op1, |asm, op1| asm.call(op1)) }
+}
+
+/// Emit `nop`.
+///
+/// # Safety
+///
+/// `asm` must be a valid [`Asm`] handle.
+#[no_mangle]
+pub unsafe extern "C" fn asm_nop(asm: *mut Asm) {
+    unsafe { asm.as_mut() }
+        .expect("Asm handle must not be null")
+        .nop();
+}
+
+/// Emit `ret`.
+///
+/// # Safety
+///
+/// `asm` must be a valid [`Asm`] handle.
+#[no_mangle]
+pub unsafe extern "C" fn asm_ret(asm: *mut Asm) {
+    unsafe { asm.as_mut() }
+        .expect("Asm handle must not be null")
+        .ret();
+}
+
+/// Consume the [`Asm`] handle and get a handle to the emitted [`Code`].
+///
+/// # Safety
+///
+/// `asm` must be a valid [`Asm`] handle, which is released by this call.
+#[no_mangle]
+pub unsafe extern "C" fn asm_finalize(asm: *mut Asm) -> *mut Code {
+    assert!(!asm.is_null(), "Asm handle must not be null");
+    let asm = unsafe { Box::from_raw(asm) };
+    Box::into_raw(Box::new(Code(asm.into_code())))
+}
+
+// -- Code.
+
+/// Get the length in bytes of the emitted code.
+///
+/// # Safety
+///
+/// `code` must be a valid [`Code`] handle.
+#[no_mangle]
+pub unsafe extern "C" fn code_len(code: *const Code) -> usize {
+    unsafe { code.as_ref() }
+        .expect("Code handle must not be null")
+        .0
+        .len()
+}
+
+/// Get a pointer to the emitted code, valid until the handle is released.
+///
+/// # Safety
+///
+/// `code` must be a valid [`Code`] handle.
+#[no_mangle]
+pub unsafe extern "C" fn code_data(code: *const Code) -> *const u8 {
+    unsafe { code.as_ref() }
+        .expect("Code handle must not be null")
+        .0
+        .as_ptr()
+}
+
+/// Release a [`Code`] handle, passing `null` is a nop.
+///
+/// # Safety
+///
+/// `code` must be `null` or a handle returned by [`asm_finalize`] which was not yet released.
+#[no_mangle]
+pub unsafe extern "C" fn code_free(code: *mut Code) {
+    if !code.is_null() {
+        drop(unsafe { Box::from_raw(code) });
+    }
+}
+
+// -- Runtime.
+
+/// Create a new [`Runtime`] handle.
+#[no_mangle]
+pub extern "C" fn rt_new() -> *mut Runtime {
+    Box::into_raw(Box::new(Runtime::new()))
+}
+
+/// Release a [`Runtime`] handle, passing `null` is a nop. This invalidates all the function
+/// pointer returned by [`rt_add_code`].
+///
+/// # Safety
+///
+/// `rt` must be `null` or a handle returned by [`rt_new`] which was not yet released.
+#[no_mangle]
+pub unsafe extern "C" fn rt_free(rt: *mut Runtime) {
+    if !rt.is_null() {
+        drop(unsafe { Box::from_raw(rt) });
+    }
+}
+
+/// Add the `code` to the runtime and get a pointer to the start of the added code. The [`Code`]
+/// handle is not released by this call.
+///
+/// # Panics
+///
+/// Panics under the same conditions as [`Runtime::add_code`], which aborts the process as
+/// unwinding out of an `extern "C"` function is not possible.
+///
+/// # Safety
+///
+/// `rt` must be a valid [`Runtime`] handle and `code` must be a valid [`Code`] handle.
+#[no_mangle]
+pub unsafe extern "C" fn rt_add_code(rt: *mut Runtime, code: *const Code) -> *const c_void {
+    let rt = unsafe { rt.as_mut() }.expect("Runtime handle must not be null");
+    let code = unsafe { code.as_ref() }.expect("Code handle must not be null");
+    unsafe { rt.add_code::<*const c_void>(&code.0) }
+}
+
+/// Disassemble the code currently added to the runtime, see [`Runtime::disasm`].
+///
+/// # Safety
+///
+/// `rt` must be a valid [`Runtime`] handle.
+#[no_mangle]
+pub unsafe extern "C" fn rt_disasm(rt: *const Runtime) {
+    unsafe { rt.as_ref() }
+        .expect("Runtime handle must not be null")
+        .disasm();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_add() {
+        unsafe {
+            let asm = asm_new();
+            assert!(asm_mov_rr(asm, 0 /* rax */, 7 /* rdi */));
+            assert!(asm_add_rr(asm, 0 /* rax */, 6 /* rsi */));
+            asm_ret(asm);
+
+            let code = asm_finalize(asm);
+            assert_eq!(code_len(code), 7);
+
+            let rt = rt_new();
+            let add: extern "C" fn(u64, u64) -> u64 = std::mem::transmute(rt_add_code(rt, code));
+            code_free(code);
+
+            assert_eq!(add(1, 2), 3);
+            rt_free(rt);
+        }
+    }
+
+    #[test]
+    fn test_invalid_reg() {
+        unsafe {
+            let asm = asm_new();
+            assert!(!asm_mov_rr(asm, 16, 0));
+            assert!(!asm_push_r(asm, 0xff));
+            let code = asm_finalize(asm);
+            assert_eq!(code_len(code), 0);
+            code_free(code);
+        }
+    }
+}
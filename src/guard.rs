@@ -0,0 +1,381 @@
+//! Guard-paged guest memory regions, which allow to emit guest memory accesses without explicit
+//! bounds checks.
+//!
+//! A [GuardRegion] reserves [`GuardRegion::RESERVE`] bytes of address space of which only the
+//! first [`GuardRegion::size`] bytes are accessible. Jitted code accesses guest memory as
+//! `[base + index + disp]`, where `base` holds the region base, `index` holds a zero-extended
+//! 32 bit guest address and `disp` is a non-negative 32 bit displacement. Such an access can not
+//! leave the reservation, hence out of bound accesses hit the inaccessible guard pages.
+//!
+//! The resulting `SIGSEGV` is turned into a trap with [`GuardRegion::set_trap`]: execution
+//! continues at a user provided address in the jitted code, eg a stub returning an error code,
+//! with `rdi` holding the faulting address and `rsi` the address of the faulting instruction. The
+//! latter can be looked up in the [`TrapTable`](crate::TrapTable) of the assembler to get the trap
+//! code of the instruction.
+//!
+//! Only faults of instructions in the jitted code accessing the region are turned into traps, see
+//! [`GuardRegion::set_code`]. All other faults, including host accesses into the reservation, are
+//! forwarded to the previously installed `SIGSEGV` handler.
+//!
+//! ```rust
+//! use juicebox_asm::insn::Mov;
+//! use juicebox_asm::{Asm, Imm64, Mem64, Reg32, Reg64::*, Runtime};
+//!
+//! // fn load(base: *const u8, addr: u32) -> u64, returns u64::MAX on out of bound accesses.
+//! let mut asm = Asm::new();
+//! asm.mov(Reg32::esi, Reg32::esi); // Zero-extend the guest address.
+//! asm.mov(rax, Mem64::indirect_base_index(rdi, rsi));
+//! asm.ret();
+//! // Trap stub.
+//! let trap_off = asm.len();
+//! asm.mov(rax, Imm64::from(u64::MAX));
+//! asm.ret();
+//!
+//! let mut rt = Runtime::new();
+//! let load = unsafe { rt.add_code::<extern "C" fn(*const u8, u32) -> u64>(asm.into_code()) };
+//!
+//! let region = rt.add_guard_region(4096);
+//! region.set_trap(unsafe { (load as *const u8).add(trap_off) });
+//!
+//! assert_eq!(load(region.base(), 0), 0);
+//! assert_eq!(load(region.base(), 4096), u64::MAX);
+//! ```
+
+use std::ops::Range;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::OnceLock;
+
+/// Maximum number of guard regions with a trap, see [`GuardRegion::set_trap`].
+const MAX_REGIONS: usize = 64;
+
+/// Trap registration of a guard region.
+struct Slot {
+    /// Start address of the reservation, zero if the slot is free.
+    start: AtomicUsize,
+    /// Address execution continues at after a fault in the reservation, zero if not yet set.
+    resume: AtomicUsize,
+    /// Start address of the jitted code whose faults are turned into traps.
+    code_start: AtomicUsize,
+    /// End address of the jitted code whose faults are turned into traps.
+    code_end: AtomicUsize,
+}
+
+static SLOTS: [Slot; MAX_REGIONS] = [const {
+    Slot {
+        start: AtomicUsize::new(0),
+        resume: AtomicUsize::new(0),
+        code_start: AtomicUsize::new(0),
+        code_end: AtomicUsize::new(0),
+    }
+}; MAX_REGIONS];
+
+/// `SIGSEGV` handler installed before the first trap was registered.
+static OLD: OnceLock<libc::sigaction> = OnceLock::new();
+
+/// `SIGSEGV` handler, must be async-signal-safe.
+extern "C" fn on_sigsegv(_sig: libc::c_int, info: *mut libc::siginfo_t, ctx: *mut libc::c_void) {
+    let addr = unsafe { (*info).si_addr() } as usize;
+    let uctx = unsafe { &mut *ctx.cast::<libc::ucontext_t>() };
+    let rip = uctx.uc_mcontext.gregs[libc::REG_RIP as usize] as usize;
+
+    for slot in &SLOTS {
+        let start = slot.start.load(Ordering::Acquire);
+        if start == 0 || !(start..start + GuardRegion::RESERVE).contains(&addr) {
+            continue;
+        }
+        let resume = slot.resume.load(Ordering::Acquire);
+        if resume == 0 {
+            continue;
+        }
+        // Only faults of the jitted code are resumed at the trap, host accesses into the
+        // reservation are genuine faults.
+        let code = slot.code_start.load(Ordering::Acquire)..slot.code_end.load(Ordering::Acquire);
+        if !code.contains(&rip) {
+            continue;
+        }
+
+        uctx.uc_mcontext.gregs[libc::REG_RIP as usize] = resume as i64;
+        uctx.uc_mcontext.gregs[libc::REG_RDI as usize] = addr as i64;
+        uctx.uc_mcontext.gregs[libc::REG_RSI as usize] = rip as i64;
+        return;
+    }
+
+    // Not a guard page fault, forward it to the previous handler.
+    if let Some(old) = OLD.get() {
+        unsafe { forward(old, info, ctx) };
+    }
+}
+
+/// Forward the `SIGSEGV` described by `info` and `ctx` to the handler `old`, which stays
+/// installed for later faults.
+///
+/// # Safety
+///
+/// Must only be called from the `SIGSEGV` handler with its arguments.
+unsafe fn forward(old: &libc::sigaction, info: *mut libc::siginfo_t, ctx: *mut libc::c_void) {
+    if old.sa_flags & libc::SA_SIGINFO != 0 {
+        let handler: extern "C" fn(libc::c_int, *mut libc::siginfo_t, *mut libc::c_void) =
+            unsafe { std::mem::transmute(old.sa_sigaction) };
+        handler(libc::SIGSEGV, info, ctx);
+    } else if old.sa_sigaction == libc::SIG_DFL || old.sa_sigaction == libc::SIG_IGN {
+        // A fault can not be ignored, restore the default action which terminates the process
+        // when the faulting instruction is re-executed.
+        let mut dfl: libc::sigaction = unsafe { std::mem::zeroed() };
+        dfl.sa_sigaction = libc::SIG_DFL;
+        unsafe { libc::sigaction(libc::SIGSEGV, &dfl, std::ptr::null_mut()) };
+    } else {
+        let handler: extern "C" fn(libc::c_int) = unsafe { std::mem::transmute(old.sa_sigaction) };
+        handler(libc::SIGSEGV);
+    }
+}
+
+/// Install the `SIGSEGV` handler, once.
+///
+/// # Panics
+///
+/// Panics if installing the signal handler fails.
+fn install_handler() {
+    OLD.get_or_init(|| unsafe {
+        let mut old: libc::sigaction = std::mem::zeroed();
+        let mut sa: libc::sigaction = std::mem::zeroed();
+        sa.sa_sigaction = on_sigsegv as *const () as libc::sighandler_t;
+        sa.sa_flags = libc::SA_SIGINFO | libc::SA_NODEFER;
+        libc::sigemptyset(&mut sa.sa_mask);
+        let ret = libc::sigaction(libc::SIGSEGV, &sa, &mut old);
+        assert_eq!(ret, 0, "Failed to install SIGSEGV handler");
+        old
+    });
+}
+
+/// A guard-paged guest memory region, see the [module](self) documentation.
+pub struct GuardRegion {
+    base: *mut u8,
+    size: usize,
+    /// Jitted code whose faults are turned into traps, see [`GuardRegion::set_code`].
+    code: Range<usize>,
+    /// Index into the trap registrations, if a trap is set.
+    slot: Option<usize>,
+}
+
+impl GuardRegion {
+    /// Size in bytes of the address space reserved per region. Covers a zero-extended 32 bit
+    /// index plus a non-negative 32 bit displacement.
+    pub const RESERVE: usize = 8 << 30;
+
+    /// Maximum accessible size in bytes of a region, the 32 bit guest address space.
+    pub const MAX_SIZE: usize = 4 << 30;
+
+    /// Reserve a new region with the first `size` bytes accessible, rounded up to the page size.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` exceeds [`GuardRegion::MAX_SIZE`] or the `mmap` call fails.
+    pub fn new(size: usize) -> GuardRegion {
+        let base = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                GuardRegion::RESERVE,
+                libc::PROT_NONE,
+                libc::MAP_PRIVATE | libc::MAP_ANONYMOUS | libc::MAP_NORESERVE,
+                0, /* fd */
+                0, /* off */
+            ) as *mut u8
+        };
+        assert_ne!(base.cast(), libc::MAP_FAILED, "Failed to mmap guard region");
+
+        let mut region = GuardRegion {
+            base,
+            size: 0,
+            code: 0..0,
+            slot: None,
+        };
+        region.grow(size);
+        region
+    }
+
+    /// Get the base address of the region.
+    pub fn base(&self) -> *mut u8 {
+        self.base
+    }
+
+    /// Get the accessible size in bytes of the region.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Grow the accessible part of the region to `size` bytes, rounded up to the page size. The
+    /// region base does not change.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is smaller than the current size, exceeds [`GuardRegion::MAX_SIZE`] or the
+    /// `mprotect` call fails.
+    pub fn grow(&mut self, size: usize) {
+        let size = size.next_multiple_of(4096);
+        assert!(size >= self.size, "Guard region can not shrink");
+        assert!(size <= GuardRegion::MAX_SIZE, "Guard region too large");
+
+        if size > self.size {
+            let ret = unsafe {
+                libc::mprotect(self.base.cast(), size, libc::PROT_READ | libc::PROT_WRITE)
+            };
+            assert_eq!(ret, 0, "Failed to mprotect guard region");
+            self.size = size;
+        }
+    }
+
+    /// Set the `len` bytes of jitted code at `code` accessing the region. Only faults of
+    /// instructions in this code are turned into traps by [`GuardRegion::set_trap`].
+    ///
+    /// Regions added with [`Runtime::add_guard_region`](crate::Runtime::add_guard_region) are set
+    /// up with the code of the runtime.
+    pub fn set_code(&mut self, code: *const u8, len: usize) {
+        self.code = code as usize..code as usize + len;
+        if let Some(idx) = self.slot {
+            SLOTS[idx]
+                .code_start
+                .store(self.code.start, Ordering::Release);
+            SLOTS[idx].code_end.store(self.code.end, Ordering::Release);
+        }
+    }
+
+    /// Turn faults of the jitted code in the region into traps, continuing execution at `resume`
+    /// with `rdi` holding the faulting address and `rsi` the address of the faulting instruction.
+    /// Setting the trap again replaces the resume address.
+    ///
+    /// The trap is handled by a process wide `SIGSEGV` handler. Faults outside of any region, and
+    /// faults of instructions outside of the code set with [`GuardRegion::set_code`], are
+    /// forwarded to the previously installed handler, which stays installed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no code is set, more than `64` regions have a trap set or installing the signal
+    /// handler fails.
+    pub fn set_trap(&mut self, resume: *const u8) {
+        assert!(!self.code.is_empty(), "Guard region code not set");
+        install_handler();
+
+        let base = self.base as usize;
+        let idx = *self.slot.get_or_insert_with(|| {
+            SLOTS
+                .iter()
+                .position(|s| {
+                    s.start
+                        .compare_exchange(0, base, Ordering::AcqRel, Ordering::Acquire)
+                        .is_ok()
+                })
+                .expect("Too many guard regions with traps")
+        });
+
+        SLOTS[idx]
+            .code_start
+            .store(self.code.start, Ordering::Release);
+        SLOTS[idx].code_end.store(self.code.end, Ordering::Release);
+        SLOTS[idx].resume.store(resume as usize, Ordering::Release);
+    }
+}
+
+impl Drop for GuardRegion {
+    fn drop(&mut self) {
+        if let Some(idx) = self.slot {
+            SLOTS[idx].resume.store(0, Ordering::Release);
+            SLOTS[idx].code_start.store(0, Ordering::Release);
+            SLOTS[idx].code_end.store(0, Ordering::Release);
+            SLOTS[idx].start.store(0, Ordering::Release);
+        }
+        unsafe { libc::munmap(self.base.cast(), GuardRegion::RESERVE) };
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_grow() {
+        let mut region = GuardRegion::new(1);
+        assert_eq!(region.size(), 4096);
+        unsafe { region.base().add(4095).write(1) };
+
+        region.grow(8192);
+        assert_eq!(region.size(), 8192);
+        unsafe { region.base().add(8191).write(1) };
+        assert_eq!(unsafe { region.base().add(4095).read() }, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Guard region can not shrink")]
+    fn test_shrink() {
+        GuardRegion::new(8192).grow(4096);
+    }
+
+    #[test]
+    fn test_trap() {
+        use crate::insn::Mov;
+        use crate::{Asm, Mem8, Reg64::*, Reg8, Runtime};
+
+        // fn(base: *mut u8, addr: u64) -> u64 { base[addr] = 1; 0 }, returns the fault address
+        // on traps.
+        let mut asm = Asm::new();
+        asm.load_const(rax, 1);
+        asm.mov(Mem8::indirect_base_index(rdi, rsi), Reg8::al);
+        asm.load_const(rax, 0);
+        asm.ret();
+        let trap_off = asm.len();
+        asm.mov(rax, rdi);
+        asm.ret();
+
+        let mut rt = Runtime::new();
+        let store = unsafe { rt.add_code::<extern "C" fn(*mut u8, u64) -> u64>(asm.into_code()) };
+        let trap = unsafe { (store as *const u8).add(trap_off) };
+
+        let mut a = GuardRegion::new(4096);
+        let mut b = GuardRegion::new(4096);
+        a.set_code(store as *const u8, trap_off);
+        b.set_code(store as *const u8, trap_off);
+        a.set_trap(trap);
+        b.set_trap(trap);
+
+        for r in [&a, &b] {
+            assert_eq!(store(r.base(), 4095), 0);
+            assert_eq!(unsafe { r.base().add(4095).read() }, 1);
+            let fault = r.base() as u64 + 0xffff_ffff;
+            assert_eq!(store(r.base(), 0xffff_ffff), fault);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Guard region code not set")]
+    fn test_trap_no_code() {
+        GuardRegion::new(4096).set_trap(std::ptr::null());
+    }
+
+    #[test]
+    fn test_trap_site() {
+        use crate::insn::Mov;
+        use crate::{Asm, Mem64, Reg64::*, Runtime};
+
+        const OOB: u32 = 42;
+
+        // fn(base: *const u8, addr: u64) -> u64 { base[addr] }, returns the faulting instruction
+        // address on traps.
+        let mut asm = Asm::new();
+        asm.trapping(OOB, |asm| {
+            asm.mov(rax, Mem64::indirect_base_index(rdi, rsi))
+        });
+        asm.ret();
+        let trap_off = asm.len();
+        asm.mov(rax, rsi);
+        asm.ret();
+
+        let traps = asm.traps().clone();
+        let mut rt = Runtime::new();
+        let load = unsafe { rt.add_code::<extern "C" fn(*const u8, u64) -> u64>(asm.into_code()) };
+
+        let region = rt.add_guard_region(4096);
+        region.set_trap(unsafe { (load as *const u8).add(trap_off) });
+
+        let rip = load(region.base(), 4096);
+        assert_eq!(traps.lookup_rip(load as usize, rip as usize), Some(OOB));
+    }
+}
@@ -1,6 +1,10 @@
 //! Definition of different immediate types which are used as input operands for various
 //! instructions.
 
+use std::fmt;
+
+use crate::Att;
+
 /// Trait to interact with immediate operands.
 pub(crate) trait Imm {
     /// Get immediate operand as slice of bytes.
@@ -10,8 +14,38 @@ pub(crate) trait Imm {
 macro_rules! impl_imm {
     (#[$doc:meta] $name:ident, $size:expr, from: { $( $from:ty ),* $(,)? }) => {
         #[$doc]
+        #[derive(Clone, Copy, PartialEq, Eq, Hash)]
         pub struct $name([u8; $size]);
 
+        impl $name {
+            /// Get the immediate value zero extended to 64 bit.
+            fn value(&self) -> u64 {
+                let mut buf = [0u8; 8];
+                buf[..$size].copy_from_slice(&self.0);
+                u64::from_ne_bytes(buf)
+            }
+        }
+
+        impl fmt::Display for $name {
+            /// Format the immediate as hex number, eg `0x2a`.
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{:#x}", self.value())
+            }
+        }
+
+        impl fmt::Display for Att<$name> {
+            /// Format the immediate as hex number in AT&T syntax, eg `$0x2a`.
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "${}", self.0)
+            }
+        }
+
+        impl fmt::Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}({:#x})", stringify!($name), self.value())
+            }
+        }
+
         impl Imm for $name {
             /// Get immediate operand as slice of bytes.
             fn bytes(&self) -> &[u8] {
@@ -54,6 +88,18 @@ mod test {
     use super::*;
     use std::mem::size_of;
 
+    #[test]
+    fn test_display() {
+        assert_eq!(Imm8::from(0x2au8).to_string(), "0x2a");
+        assert_eq!(Imm8::from(-1i8).to_string(), "0xff");
+        assert_eq!(
+            Imm64::from(0x1122_3344_5566_7788u64).to_string(),
+            "0x1122334455667788"
+        );
+        assert_eq!(format!("{:?}", Imm32::from(16u8)), "Imm32(0x10)");
+        assert_eq!(Att(Imm16::from(0xaabbu16)).to_string(), "$0xaabb");
+    }
+
     #[test]
     fn test_usize_isize() {
         // Imm64 should not implementd from usize/isize if this fails.
@@ -0,0 +1,233 @@
+//! Definition of helper fragments, which are expanded inline at their call sites or called
+//! out-of-line, without the need for an IR.
+
+use std::rc::Rc;
+
+use crate::insn::Call;
+use crate::{Asm, Label};
+
+/// Identifier of a helper in an [Inliner].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct HelperId(usize);
+
+/// Body of a helper, emitted with a fresh exit label for each expansion.
+type Body<'a> = Rc<dyn Fn(&mut Inliner<'a>, &mut Asm, &mut Label) + 'a>;
+
+/// A registered helper.
+struct Helper<'a> {
+    body: Body<'a>,
+    inline: bool,
+    /// Entry of the out-of-line copy, created on the first call which is not inlined.
+    entry: Option<Label>,
+}
+
+/// Registry of helper fragments, which are either expanded inline at their call sites or called
+/// as out-of-line functions.
+///
+/// A helper body is emitted by a closure, which receives the [Inliner] to call further helpers
+/// and an exit label. The body returns by falling through at its end or by jumping to the exit
+/// label, it must not emit a `ret` itself. Each expansion of the body uses fresh labels, hence
+/// labels local to the body are simply created in the closure.
+///
+/// Calls of helpers marked inlinable are expanded in place. Calls of other helpers and recursive
+/// calls of a helper currently being expanded, which can not be inlined, emit a `call` to the
+/// out-of-line copy of the helper. The out-of-line copies are emitted by [`Inliner::finalize`].
+///
+/// As the return address is only pushed for out-of-line calls, helper bodies must not depend on
+/// the stack layout of the caller.
+///
+/// ```rust
+/// use juicebox_asm::{Asm, Inliner, Reg64::*, Runtime};
+/// use juicebox_asm::insn::{Add, Mov};
+///
+/// let mut inl = Inliner::new();
+/// // rax += rdi
+/// let acc = inl.helper(true, |_, asm, _| asm.add(rax, rdi));
+///
+/// let mut asm = Asm::new();
+/// asm.mov(rax, rdi);
+/// inl.call(&mut asm, acc);
+/// inl.call(&mut asm, acc);
+/// asm.ret();
+/// inl.finalize(&mut asm);
+///
+/// let mut rt = Runtime::new();
+/// let f = unsafe { rt.add_code::<extern "C" fn(u64) -> u64>(asm.into_code()) };
+/// assert_eq!(f(2), 6);
+/// ```
+pub struct Inliner<'a> {
+    helpers: Vec<Helper<'a>>,
+    /// Helpers currently being expanded, the innermost last.
+    stack: Vec<HelperId>,
+}
+
+impl<'a> Inliner<'a> {
+    /// Create a new inliner without any helpers.
+    pub fn new() -> Inliner<'a> {
+        Inliner {
+            helpers: Vec::new(),
+            stack: Vec::new(),
+        }
+    }
+
+    /// Register a helper emitted by `body`, which is expanded inline at its call sites if `inline`
+    /// is set.
+    pub fn helper(
+        &mut self,
+        inline: bool,
+        body: impl Fn(&mut Inliner<'a>, &mut Asm, &mut Label) + 'a,
+    ) -> HelperId {
+        self.helpers.push(Helper {
+            body: Rc::new(body),
+            inline,
+            entry: None,
+        });
+        HelperId(self.helpers.len() - 1)
+    }
+
+    /// Check if helper `id` is currently being expanded, ie a call is recursive.
+    fn is_active(&self, id: HelperId) -> bool {
+        self.stack.contains(&id)
+    }
+
+    /// Emit a call of helper `id` into `asm`, either by expanding the helper inline or by calling
+    /// its out-of-line copy.
+    pub fn call(&mut self, asm: &mut Asm, id: HelperId) {
+        let active = self.is_active(id);
+        let helper = &mut self.helpers[id.0];
+        if helper.inline && !active {
+            let body = Rc::clone(&helper.body);
+            let mut exit = Label::new();
+            self.stack.push(id);
+            body(self, asm, &mut exit);
+            self.stack.pop();
+            asm.bind(&mut exit);
+        } else {
+            asm.call(helper.entry.get_or_insert_with(Label::new));
+        }
+    }
+
+    /// Emit the out-of-line copies of all helpers called and not inlined into `asm`, at its
+    /// current position, eg after the final `ret` of the function.
+    pub fn finalize(mut self, asm: &mut Asm) {
+        // Out-of-line copies may call further helpers out-of-line, emit until all are bound.
+        while let Some(idx) = self
+            .helpers
+            .iter()
+            .position(|h| h.entry.as_ref().is_some_and(|l| l.location().is_none()))
+        {
+            let helper = &mut self.helpers[idx];
+            // UNWRAP: Found a helper with an entry above.
+            asm.bind(helper.entry.as_mut().unwrap());
+            let body = Rc::clone(&helper.body);
+
+            // Recursive calls in the body call the out-of-line copy.
+            let mut exit = Label::new();
+            self.stack.push(HelperId(idx));
+            body(&mut self, asm, &mut exit);
+            self.stack.pop();
+            asm.bind(&mut exit);
+            asm.ret();
+        }
+    }
+}
+
+impl Default for Inliner<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::insn::{Dec, Inc, Jz, Mov, Test};
+    use crate::{Imm64, Reg64::*, Runtime};
+
+    #[test]
+    fn test_inline() {
+        let mut inl = Inliner::new();
+        let id = inl.helper(true, |_, asm, _| asm.inc(rax));
+
+        let mut asm = Asm::new();
+        inl.call(&mut asm, id);
+        inl.call(&mut asm, id);
+        asm.ret();
+        inl.finalize(&mut asm);
+
+        // inc rax; inc rax; ret
+        assert_eq!(asm.into_code(), [0x48, 0xff, 0xc0, 0x48, 0xff, 0xc0, 0xc3]);
+    }
+
+    #[test]
+    fn test_out_of_line() {
+        let mut inl = Inliner::new();
+        let id = inl.helper(false, |_, asm, _| asm.inc(rax));
+
+        let mut asm = Asm::new();
+        inl.call(&mut asm, id);
+        inl.call(&mut asm, id);
+        asm.ret();
+        inl.finalize(&mut asm);
+
+        #[rustfmt::skip]
+        assert_eq!(
+            asm.into_code(),
+            [
+                0xe8, 0x06, 0x00, 0x00, 0x00, // call helper
+                0xe8, 0x01, 0x00, 0x00, 0x00, // call helper
+                0xc3,                         // ret
+                0x48, 0xff, 0xc0,             // helper: inc rax
+                0xc3,                         // ret
+            ]
+        );
+    }
+
+    #[test]
+    fn test_nested() {
+        let mut inl = Inliner::new();
+        let leaf = inl.helper(false, |_, asm, _| asm.inc(rax));
+        let outer = inl.helper(true, move |inl, asm, _| {
+            inl.call(asm, leaf);
+            inl.call(asm, leaf);
+        });
+
+        let mut asm = Asm::new();
+        asm.mov(rax, rdi);
+        inl.call(&mut asm, outer);
+        asm.ret();
+        inl.finalize(&mut asm);
+
+        let mut rt = Runtime::new();
+        let f = unsafe { rt.add_code::<extern "C" fn(u64) -> u64>(asm.into_code()) };
+        assert_eq!(f(40), 42);
+    }
+
+    #[test]
+    fn test_recursive() {
+        let mut inl = Inliner::new();
+        // Count rdi down to zero, incrementing rax in each step, with an early exit.
+        let count = inl.helper(true, |inl, asm, exit| {
+            asm.test(rdi, rdi);
+            asm.jz(exit);
+            asm.dec(rdi);
+            asm.inc(rax);
+            // HelperId(0) is the helper itself.
+            inl.call(asm, HelperId(0));
+        });
+
+        let mut asm = Asm::new();
+        asm.mov(rax, Imm64::from(0u64));
+        inl.call(&mut asm, count);
+        asm.ret();
+
+        // The recursive call refers to the out-of-line copy.
+        assert!(inl.helpers[count.0].entry.is_some());
+        inl.finalize(&mut asm);
+
+        let mut rt = Runtime::new();
+        let f = unsafe { rt.add_code::<extern "C" fn(u64) -> u64>(asm.into_code()) };
+        assert_eq!(f(0), 0);
+        assert_eq!(f(5), 5);
+    }
+}
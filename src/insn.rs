@@ -1,36 +1,152 @@
 //! Trait definitions of various instructions.
 
+mod adc;
 mod add;
+mod and;
+mod bswap;
 mod call;
+mod cbw;
+mod cdq;
+mod cdqe;
+mod clflush;
+mod clflushopt;
+mod clwb;
+mod cmov;
 mod cmovnz;
 mod cmovz;
 mod cmp;
+mod cmps;
+mod cqo;
+mod cwd;
+mod cwde;
 mod dec;
+mod div;
+mod enter;
+mod idiv;
+mod imul;
 mod inc;
+mod jcc;
 mod jmp;
 mod jnz;
 mod jz;
+mod lea;
+mod leave;
+mod lfence;
+mod lzcnt;
+mod mfence;
 mod mov;
+mod movbe;
+mod movs;
+mod movsx;
+mod movsxd;
+mod movzx;
+mod mul;
+mod neg;
 mod nop;
+mod not;
+mod or;
 mod pop;
+mod popcnt;
+mod popfq;
 mod push;
+mod pushfq;
+mod rdtsc;
+mod rdtscp;
 mod ret;
+mod rol;
+mod ror;
+mod sar;
+mod sbb;
+mod scas;
+mod setcc;
+mod sfence;
+mod shl;
+mod shr;
+mod stos;
 mod sub;
 mod test;
+mod tzcnt;
+mod ud2;
+mod xadd;
 mod xor;
 
+use crate::Cond;
+
+/// Trait for [`adc`](https://www.felixcloutier.com/x86/adc) instruction kinds.
+pub trait Adc<T, U> {
+    /// Emit an add with carry instruction.
+    ///
+    /// Computes `op1 + op2 + CF` and stores the result in `op1`. An `Imm8` operand is
+    /// sign-extended for 16, 32 and 64 bit destinations, as is an `Imm32` operand for 64 bit
+    /// destinations.
+    fn adc(&mut self, op1: T, op2: U);
+}
+
 /// Trait for [`add`](https://www.felixcloutier.com/x86/add) instruction kinds.
 pub trait Add<T, U> {
     /// Emit an add instruction.
     fn add(&mut self, op1: T, op2: U);
 }
 
+/// Trait for [`and`](https://www.felixcloutier.com/x86/and) instruction kinds.
+pub trait And<T, U> {
+    /// Emit a logical and instruction.
+    ///
+    /// Computes the bit-wise logical AND of `op1` and `op2` and stores the result in `op1`. An
+    /// `Imm8` operand is sign-extended for 16, 32 and 64 bit destinations, as is an `Imm32`
+    /// operand for 64 bit destinations.
+    fn and(&mut self, op1: T, op2: U);
+}
+
+/// Trait for [`bswap`](https://www.felixcloutier.com/x86/bswap) instruction kinds.
+pub trait Bswap<T> {
+    /// Emit a byte swap instruction, reversing the byte order of `op1`.
+    fn bswap(&mut self, op1: T);
+}
+
 /// Trait for [`call`](https://www.felixcloutier.com/x86/call) instruction kinds.
 pub trait Call<T> {
     /// Emit a call instruction.
     fn call(&mut self, op1: T);
 }
 
+/// Trait for [`clflush`](https://www.felixcloutier.com/x86/clflush) instruction kinds.
+pub trait Clflush<T> {
+    /// Emit a flush of the cache line containing the address `op1`.
+    fn clflush(&mut self, op1: T);
+}
+
+/// Trait for [`clflushopt`](https://www.felixcloutier.com/x86/clflushopt) instruction kinds.
+pub trait Clflushopt<T> {
+    /// Emit an optimized flush of the cache line containing the address `op1`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `clflushopt` is not enabled in the [`CpuFeatures`](crate::CpuFeatures) of the
+    /// assembler, see [`Asm::set_cpu_features`](crate::Asm::set_cpu_features).
+    fn clflushopt(&mut self, op1: T);
+}
+
+/// Trait for [`clwb`](https://www.felixcloutier.com/x86/clwb) instruction kinds.
+pub trait Clwb<T> {
+    /// Emit a write back of the cache line containing the address `op1`, which may retain the
+    /// line in the cache.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `clwb` is not enabled in the [`CpuFeatures`](crate::CpuFeatures) of the
+    /// assembler, see [`Asm::set_cpu_features`](crate::Asm::set_cpu_features).
+    fn clwb(&mut self, op1: T);
+}
+
+/// Trait for [`cmovcc`](https://www.felixcloutier.com/x86/cmovcc) instruction kinds.
+pub trait Cmov<T, U> {
+    /// Emit a conditional move instruction.
+    ///
+    /// Move is only commited if `cond` holds.
+    fn cmov(&mut self, cond: Cond, op1: T, op2: U);
+}
+
 /// Trait for [`cmovnz`](https://www.felixcloutier.com/x86/cmovcc) instruction kinds.
 pub trait Cmovnz<T, U> {
     /// Emit a (conditional) move if not zero instruction.
@@ -51,7 +167,7 @@ pub trait Cmovz<T, U> {
 pub trait Cmp<T, U> {
     /// Emit a compare instruction.
     ///
-    /// Computes `op2 - op1` and sets the status flags in the same way as the `sub` instruction,
+    /// Computes `op1 - op2` and sets the status flags in the same way as the `sub` instruction,
     /// the result is discarded.
     fn cmp(&mut self, op1: T, op2: U);
 }
@@ -62,6 +178,34 @@ pub trait Dec<T> {
     fn dec(&mut self, op1: T);
 }
 
+/// Trait for [`div`](https://www.felixcloutier.com/x86/div) instruction kinds.
+pub trait Div<T> {
+    /// Emit an unsigned divide of the accumulator `dx:ax` of the operand width (`ax` for 8 bit
+    /// operands) by `op1`, storing the quotient in `ax` and the remainder in `dx`.
+    fn div(&mut self, op1: T);
+}
+
+/// Trait for [`idiv`](https://www.felixcloutier.com/x86/idiv) instruction kinds.
+pub trait Idiv<T> {
+    /// Emit a signed divide of the accumulator `dx:ax` of the operand width (`ax` for 8 bit
+    /// operands) by `op1`, storing the quotient in `ax` and the remainder in `dx`.
+    fn idiv(&mut self, op1: T);
+}
+
+/// Trait for [`imul`](https://www.felixcloutier.com/x86/imul) instruction kinds.
+///
+/// The two and three operand forms take their operands as tuple, eg `asm.imul((rax, rcx))`.
+pub trait Imul<T> {
+    /// Emit a signed multiply instruction.
+    ///
+    /// - `imul(op1)`: multiply the accumulator by `op1`, the double width result is stored in
+    ///   `rdx:rax` (`ax` for 8 bit operands).
+    /// - `imul((op1, op2))`: `op1 = op1 * op2`, truncated to the operand width.
+    /// - `imul((op1, op2, op3))`: `op1 = op2 * op3`, truncated to the operand width. An `Imm8`
+    ///   operand is sign-extended, as is an `Imm32` operand for 64 bit destinations.
+    fn imul(&mut self, op1: T);
+}
+
 /// Trait for [`inc`](https://www.felixcloutier.com/x86/inc) instruction kinds.
 pub trait Inc<T> {
     /// Emit a increment instruction.
@@ -86,24 +230,192 @@ pub trait Jz<T> {
     fn jz(&mut self, op1: T);
 }
 
+/// Trait for [`lea`](https://www.felixcloutier.com/x86/lea) instruction kinds.
+pub trait Lea<T, U> {
+    /// Emit a load effective address instruction, computing the address of the memory operand `op2`
+    /// into `op1` without accessing memory.
+    fn lea(&mut self, op1: T, op2: U);
+}
+
+/// Trait for [`lzcnt`](https://www.felixcloutier.com/x86/lzcnt) instruction kinds.
+pub trait Lzcnt<T, U> {
+    /// Emit a count leading zero bits instruction.
+    ///
+    /// Counts the leading zero bits of `op2` into `op1`, the operand size if `op2` is zero.
+    ///
+    /// On CPUs without `lzcnt` support the encoding executes as
+    /// [`bsr`](https://www.felixcloutier.com/x86/bsr), which yields the index of the most
+    /// significant set bit instead and leaves `op1` undefined if `op2` is zero.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `lzcnt` is not enabled in the [`CpuFeatures`](crate::CpuFeatures) of the
+    /// assembler.
+    fn lzcnt(&mut self, op1: T, op2: U);
+}
+
 /// Trait for [`mov`](https://www.felixcloutier.com/x86/mov) instruction kinds.
 pub trait Mov<T, U> {
     /// Emit an move instruction.
     fn mov(&mut self, op1: T, op2: U);
 }
 
+/// Trait for [`movbe`](https://www.felixcloutier.com/x86/movbe) instruction kinds.
+pub trait Movbe<T, U> {
+    /// Emit a move with byte swap instruction, a big-endian load or store.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `movbe` is not enabled in the [`CpuFeatures`](crate::CpuFeatures) of the
+    /// assembler, see [`Asm::set_cpu_features`](crate::Asm::set_cpu_features).
+    fn movbe(&mut self, op1: T, op2: U);
+}
+
+/// Trait for [`movsx`](https://www.felixcloutier.com/x86/movsx:movsxd) instruction kinds.
+pub trait Movsx<T, U> {
+    /// Emit a move with sign-extension of `op2` into the wider `op1`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `op2` is one of the high byte registers `ah`, `ch`, `dh` or `bh` and the
+    /// instruction requires a `REX` prefix, eg for a 64 bit or an extended `op1`.
+    fn movsx(&mut self, op1: T, op2: U);
+}
+
+/// Trait for [`movsxd`](https://www.felixcloutier.com/x86/movsx:movsxd) instruction kinds.
+pub trait Movsxd<T, U> {
+    /// Emit a move with sign-extension of the 32 bit `op2` into the 64 bit `op1`.
+    fn movsxd(&mut self, op1: T, op2: U);
+}
+
+/// Trait for [`movzx`](https://www.felixcloutier.com/x86/movzx) instruction kinds.
+pub trait Movzx<T, U> {
+    /// Emit a move with zero-extension of `op2` into the wider `op1`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `op2` is one of the high byte registers `ah`, `ch`, `dh` or `bh` and the
+    /// instruction requires a `REX` prefix, eg for a 64 bit or an extended `op1`.
+    fn movzx(&mut self, op1: T, op2: U);
+}
+
+/// Trait for [`mul`](https://www.felixcloutier.com/x86/mul) instruction kinds.
+pub trait Mul<T> {
+    /// Emit an unsigned multiply of the accumulator with `op1`, the double width result is stored
+    /// in `dx:ax` of the operand width (`ax` for 8 bit operands).
+    fn mul(&mut self, op1: T);
+}
+
+/// Trait for [`neg`](https://www.felixcloutier.com/x86/neg) instruction kinds.
+pub trait Neg<T> {
+    /// Emit a two's complement negation instruction.
+    fn neg(&mut self, op1: T);
+}
+
+/// Trait for [`not`](https://www.felixcloutier.com/x86/not) instruction kinds.
+pub trait Not<T> {
+    /// Emit a one's complement negation instruction, inverting each bit of `op1`.
+    fn not(&mut self, op1: T);
+}
+
+/// Trait for [`or`](https://www.felixcloutier.com/x86/or) instruction kinds.
+pub trait Or<T, U> {
+    /// Emit a logical inclusive or instruction.
+    ///
+    /// Computes the bit-wise logical OR of `op1` and `op2` and stores the result in `op1`. An
+    /// `Imm8` operand is sign-extended for 16, 32 and 64 bit destinations, as is an `Imm32`
+    /// operand for 64 bit destinations.
+    fn or(&mut self, op1: T, op2: U);
+}
+
 /// Trait for [`pop`](https://www.felixcloutier.com/x86/pop) instruction kinds.
 pub trait Pop<T> {
     /// Emit a pop instruction.
     fn pop(&mut self, op1: T);
 }
 
+/// Trait for [`popcnt`](https://www.felixcloutier.com/x86/popcnt) instruction kinds.
+pub trait Popcnt<T, U> {
+    /// Emit a population count instruction.
+    ///
+    /// Counts the set bits of `op2` into `op1`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `popcnt` is not enabled in the [`CpuFeatures`](crate::CpuFeatures) of the
+    /// assembler.
+    fn popcnt(&mut self, op1: T, op2: U);
+}
+
 /// Trait for [`push`](https://www.felixcloutier.com/x86/push) instruction kinds.
 pub trait Push<T> {
     /// Emit a push instruction.
     fn push(&mut self, op1: T);
 }
 
+/// Trait for [`rol`](https://www.felixcloutier.com/x86/rcl:rcr:rol:ror) instruction kinds.
+pub trait Rol<T, U> {
+    /// Emit a rotate left instruction.
+    ///
+    /// Rotates `op1` left by the count in `op2`, which is either an immediate or the `cl`
+    /// register.
+    fn rol(&mut self, op1: T, op2: U);
+}
+
+/// Trait for [`ror`](https://www.felixcloutier.com/x86/rcl:rcr:rol:ror) instruction kinds.
+pub trait Ror<T, U> {
+    /// Emit a rotate right instruction.
+    ///
+    /// Rotates `op1` right by the count in `op2`, which is either an immediate or the `cl`
+    /// register.
+    fn ror(&mut self, op1: T, op2: U);
+}
+
+/// Trait for [`sar`](https://www.felixcloutier.com/x86/sal:sar:shl:shr) instruction kinds.
+pub trait Sar<T, U> {
+    /// Emit an arithmetic shift right instruction.
+    ///
+    /// Shifts `op1` right by the count in `op2` and fills the vacated bits with the sign bit.
+    /// The count is either an immediate or the `cl` register.
+    fn sar(&mut self, op1: T, op2: U);
+}
+
+/// Trait for [`sbb`](https://www.felixcloutier.com/x86/sbb) instruction kinds.
+pub trait Sbb<T, U> {
+    /// Emit an integer subtraction with borrow instruction.
+    ///
+    /// Computes `op1 - (op2 + CF)` and stores the result in `op1`. An `Imm8` operand is
+    /// sign-extended for 16, 32 and 64 bit destinations, as is an `Imm32` operand for 64 bit
+    /// destinations.
+    fn sbb(&mut self, op1: T, op2: U);
+}
+
+/// Trait for [`setcc`](https://www.felixcloutier.com/x86/setcc) instruction kinds.
+pub trait Setcc<T> {
+    /// Emit a set byte on condition instruction.
+    ///
+    /// Sets `op1` to `1` if `cond` holds, else to `0`.
+    fn setcc(&mut self, cond: Cond, op1: T);
+}
+
+/// Trait for [`shl`](https://www.felixcloutier.com/x86/sal:sar:shl:shr) instruction kinds.
+pub trait Shl<T, U> {
+    /// Emit a logical shift left instruction.
+    ///
+    /// Shifts `op1` left by the count in `op2`, which is either an immediate or the `cl`
+    /// register.
+    fn shl(&mut self, op1: T, op2: U);
+}
+
+/// Trait for [`shr`](https://www.felixcloutier.com/x86/sal:sar:shl:shr) instruction kinds.
+pub trait Shr<T, U> {
+    /// Emit a logical shift right instruction.
+    ///
+    /// Shifts `op1` right by the count in `op2` and fills the vacated bits with zero. The
+    /// count is either an immediate or the `cl` register.
+    fn shr(&mut self, op1: T, op2: U);
+}
+
 /// Trait for [`sub`](https://www.felixcloutier.com/x86/sub) instruction kinds.
 pub trait Sub<T, U> {
     /// Emit an sub instruction.
@@ -119,8 +431,94 @@ pub trait Test<T, U> {
     fn test(&mut self, op1: T, op2: U);
 }
 
+/// Trait for [`tzcnt`](https://www.felixcloutier.com/x86/tzcnt) instruction kinds.
+pub trait Tzcnt<T, U> {
+    /// Emit a count trailing zero bits instruction.
+    ///
+    /// Counts the trailing zero bits of `op2` into `op1`, the operand size if `op2` is zero.
+    ///
+    /// On CPUs without `tzcnt` support (BMI1) the encoding executes as
+    /// [`bsf`](https://www.felixcloutier.com/x86/bsf), which yields the same count for a non-zero
+    /// `op2` but leaves `op1` undefined if `op2` is zero.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bmi1` is not enabled in the [`CpuFeatures`](crate::CpuFeatures) of the
+    /// assembler.
+    fn tzcnt(&mut self, op1: T, op2: U);
+}
+
+/// Trait for [`xadd`](https://www.felixcloutier.com/x86/xadd) instruction kinds.
+pub trait Xadd<T, U> {
+    /// Emit an exchange and add instruction.
+    ///
+    /// Exchanges `op1` and `op2`, then stores the sum of both in `op1`. Use
+    /// [`Asm::lock_xadd`](crate::Asm::lock_xadd) for an atomic fetch-add.
+    fn xadd(&mut self, op1: T, op2: U);
+}
+
 /// Trait for [`xor`](https://www.felixcloutier.com/x86/xor) instruction kinds.
 pub trait Xor<T, U> {
     /// Emit a xor instruction.
     fn xor(&mut self, op1: T, op2: U);
 }
+
+/// An instruction form supported by the [`Asm`](crate::Asm), consisting of the mnemonic and the
+/// operand types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Form {
+    /// Instruction mnemonic, eg `mov`.
+    pub mnemonic: &'static str,
+    /// Operand type names, eg `["Reg64", "Imm64"]`.
+    pub operands: &'static [&'static str],
+}
+
+/// Table of all supported instruction forms, sorted by mnemonic.
+///
+/// The table is generated at build time from the instruction implementations, which allows code
+/// generators to check at runtime whether a lowering is available.
+///
+/// ```
+/// use juicebox_asm::insn::FORMS;
+///
+/// for form in FORMS.iter().filter(|f| f.mnemonic == "mov") {
+///     println!("mov {}", form.operands.join(", "));
+/// }
+/// ```
+pub const FORMS: &[Form] = include!(concat!(env!("OUT_DIR"), "/forms.rs"));
+
+/// Check if the instruction form given by `mnemonic` and `operands` is supported.
+///
+/// ```
+/// use juicebox_asm::insn::is_supported;
+///
+/// assert!(is_supported("mov", &["Reg64", "Imm64"]));
+/// assert!(is_supported("jmp", &["Label"]));
+/// assert!(is_supported("ret", &[]));
+/// assert!(is_supported("imul", &["Reg64", "Reg64", "Imm8"]));
+/// assert!(!is_supported("mov", &["Imm64", "Reg64"]));
+/// ```
+pub fn is_supported(mnemonic: &str, operands: &[&str]) -> bool {
+    FORMS
+        .iter()
+        .any(|f| f.mnemonic == mnemonic && f.operands == operands)
+}
+
+/// Re-export of all instruction traits, to import them with a single glob, see also the crate
+/// [`prelude`](crate::prelude).
+///
+/// ```
+/// use juicebox_asm::insn::prelude::*;
+/// use juicebox_asm::{Asm, Reg64::*};
+///
+/// let mut asm = Asm::new();
+/// asm.xor(rax, rdi);
+/// asm.bswap(rax);
+/// ```
+pub mod prelude {
+    pub use super::{
+        Adc, Add, And, Bswap, Call, Clflush, Clflushopt, Clwb, Cmov, Cmovnz, Cmovz, Cmp, Dec, Div,
+        Idiv, Imul, Inc, Jmp, Jnz, Jz, Lea, Lzcnt, Mov, Movbe, Movsx, Movsxd, Movzx, Mul, Neg, Not,
+        Or, Pop, Popcnt, Push, Rol, Ror, Sar, Sbb, Setcc, Shl, Shr, Sub, Test, Tzcnt, Xadd, Xor,
+    };
+}
@@ -0,0 +1,168 @@
+use super::Adc;
+use crate::{Asm, Imm16, Imm32, Imm8, Mem16, Mem32, Mem64, Mem8, Reg16, Reg32, Reg64, Reg8};
+
+// -- ADC : reg reg
+
+impl Adc<Reg64, Reg64> for Asm {
+    fn adc(&mut self, op1: Reg64, op2: Reg64) {
+        self.insn("adc", |asm| asm.encode_rr(&[0x11], op1, op2));
+    }
+}
+
+impl Adc<Reg32, Reg32> for Asm {
+    fn adc(&mut self, op1: Reg32, op2: Reg32) {
+        self.insn("adc", |asm| asm.encode_rr(&[0x11], op1, op2));
+    }
+}
+
+impl Adc<Reg16, Reg16> for Asm {
+    fn adc(&mut self, op1: Reg16, op2: Reg16) {
+        self.insn("adc", |asm| asm.encode_rr(&[0x11], op1, op2));
+    }
+}
+
+impl Adc<Reg8, Reg8> for Asm {
+    fn adc(&mut self, op1: Reg8, op2: Reg8) {
+        self.insn("adc", |asm| asm.encode_rr(&[0x10], op1, op2));
+    }
+}
+
+// -- ADC : reg imm
+
+impl Adc<Reg64, Imm32> for Asm {
+    fn adc(&mut self, op1: Reg64, op2: Imm32) {
+        self.insn("adc", |asm| asm.encode_ri(0x81, 2, op1, op2));
+    }
+}
+
+impl Adc<Reg32, Imm32> for Asm {
+    fn adc(&mut self, op1: Reg32, op2: Imm32) {
+        self.insn("adc", |asm| asm.encode_ri(0x81, 2, op1, op2));
+    }
+}
+
+impl Adc<Reg16, Imm16> for Asm {
+    fn adc(&mut self, op1: Reg16, op2: Imm16) {
+        self.insn("adc", |asm| asm.encode_ri(0x81, 2, op1, op2));
+    }
+}
+
+impl Adc<Reg8, Imm8> for Asm {
+    fn adc(&mut self, op1: Reg8, op2: Imm8) {
+        self.insn("adc", |asm| asm.encode_ri(0x80, 2, op1, op2));
+    }
+}
+
+impl Adc<Reg64, Imm8> for Asm {
+    fn adc(&mut self, op1: Reg64, op2: Imm8) {
+        self.insn("adc", |asm| asm.encode_ri(0x83, 2, op1, op2));
+    }
+}
+
+impl Adc<Reg32, Imm8> for Asm {
+    fn adc(&mut self, op1: Reg32, op2: Imm8) {
+        self.insn("adc", |asm| asm.encode_ri(0x83, 2, op1, op2));
+    }
+}
+
+impl Adc<Reg16, Imm8> for Asm {
+    fn adc(&mut self, op1: Reg16, op2: Imm8) {
+        self.insn("adc", |asm| asm.encode_ri(0x83, 2, op1, op2));
+    }
+}
+
+// -- ADC : mem imm
+
+impl Adc<Mem64, Imm32> for Asm {
+    fn adc(&mut self, op1: Mem64, op2: Imm32) {
+        self.insn("adc", |asm| asm.encode_mi(0x81, 2, op1, op2));
+    }
+}
+
+impl Adc<Mem32, Imm32> for Asm {
+    fn adc(&mut self, op1: Mem32, op2: Imm32) {
+        self.insn("adc", |asm| asm.encode_mi(0x81, 2, op1, op2));
+    }
+}
+
+impl Adc<Mem16, Imm16> for Asm {
+    fn adc(&mut self, op1: Mem16, op2: Imm16) {
+        self.insn("adc", |asm| asm.encode_mi(0x81, 2, op1, op2));
+    }
+}
+
+impl Adc<Mem8, Imm8> for Asm {
+    fn adc(&mut self, op1: Mem8, op2: Imm8) {
+        self.insn("adc", |asm| asm.encode_mi(0x80, 2, op1, op2));
+    }
+}
+
+impl Adc<Mem64, Imm8> for Asm {
+    fn adc(&mut self, op1: Mem64, op2: Imm8) {
+        self.insn("adc", |asm| asm.encode_mi(0x83, 2, op1, op2));
+    }
+}
+
+impl Adc<Mem32, Imm8> for Asm {
+    fn adc(&mut self, op1: Mem32, op2: Imm8) {
+        self.insn("adc", |asm| asm.encode_mi(0x83, 2, op1, op2));
+    }
+}
+
+impl Adc<Mem16, Imm8> for Asm {
+    fn adc(&mut self, op1: Mem16, op2: Imm8) {
+        self.insn("adc", |asm| asm.encode_mi(0x83, 2, op1, op2));
+    }
+}
+
+// -- ADC : reg mem
+
+impl Adc<Reg64, Mem64> for Asm {
+    fn adc(&mut self, op1: Reg64, op2: Mem64) {
+        self.insn("adc", |asm| asm.encode_rm(&[0x13], op1, op2));
+    }
+}
+
+impl Adc<Reg32, Mem32> for Asm {
+    fn adc(&mut self, op1: Reg32, op2: Mem32) {
+        self.insn("adc", |asm| asm.encode_rm(&[0x13], op1, op2));
+    }
+}
+
+impl Adc<Reg16, Mem16> for Asm {
+    fn adc(&mut self, op1: Reg16, op2: Mem16) {
+        self.insn("adc", |asm| asm.encode_rm(&[0x13], op1, op2));
+    }
+}
+
+impl Adc<Reg8, Mem8> for Asm {
+    fn adc(&mut self, op1: Reg8, op2: Mem8) {
+        self.insn("adc", |asm| asm.encode_rm(&[0x12], op1, op2));
+    }
+}
+
+// -- ADC : mem reg
+
+impl Adc<Mem64, Reg64> for Asm {
+    fn adc(&mut self, op1: Mem64, op2: Reg64) {
+        self.insn("adc", |asm| asm.encode_mr(&[0x11], op1, op2));
+    }
+}
+
+impl Adc<Mem32, Reg32> for Asm {
+    fn adc(&mut self, op1: Mem32, op2: Reg32) {
+        self.insn("adc", |asm| asm.encode_mr(&[0x11], op1, op2));
+    }
+}
+
+impl Adc<Mem16, Reg16> for Asm {
+    fn adc(&mut self, op1: Mem16, op2: Reg16) {
+        self.insn("adc", |asm| asm.encode_mr(&[0x11], op1, op2));
+    }
+}
+
+impl Adc<Mem8, Reg8> for Asm {
+    fn adc(&mut self, op1: Mem8, op2: Reg8) {
+        self.insn("adc", |asm| asm.encode_mr(&[0x10], op1, op2));
+    }
+}
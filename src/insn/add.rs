@@ -1,62 +1,168 @@
 use super::Add;
-use crate::{Asm, Imm16, Imm8, Mem16, Mem32, Mem64, Mem8, Reg16, Reg32, Reg64};
+use crate::{Asm, Imm16, Imm32, Imm8, Mem16, Mem32, Mem64, Mem8, Reg16, Reg32, Reg64, Reg8};
+
+// -- ADD : reg reg
+
+impl Add<Reg64, Reg64> for Asm {
+    fn add(&mut self, op1: Reg64, op2: Reg64) {
+        self.insn("add", |asm| asm.encode_rr(&[0x01], op1, op2));
+    }
+}
 
 impl Add<Reg32, Reg32> for Asm {
     fn add(&mut self, op1: Reg32, op2: Reg32) {
-        self.encode_rr(&[0x01], op1, op2);
+        self.insn("add", |asm| asm.encode_rr(&[0x01], op1, op2));
     }
 }
 
-impl Add<Reg64, Reg64> for Asm {
-    fn add(&mut self, op1: Reg64, op2: Reg64) {
-        self.encode_rr(&[0x01], op1, op2);
+impl Add<Reg16, Reg16> for Asm {
+    fn add(&mut self, op1: Reg16, op2: Reg16) {
+        self.insn("add", |asm| asm.encode_rr(&[0x01], op1, op2));
     }
 }
 
-impl Add<Mem16, Reg16> for Asm {
-    fn add(&mut self, op1: Mem16, op2: Reg16) {
-        self.encode_mr(0x01, op1, op2);
+impl Add<Reg8, Reg8> for Asm {
+    fn add(&mut self, op1: Reg8, op2: Reg8) {
+        self.insn("add", |asm| asm.encode_rr(&[0x00], op1, op2));
     }
 }
 
-impl Add<Mem64, Reg64> for Asm {
-    fn add(&mut self, op1: Mem64, op2: Reg64) {
-        self.encode_mr(0x01, op1, op2);
+// -- ADD : reg imm
+
+impl Add<Reg64, Imm8> for Asm {
+    fn add(&mut self, op1: Reg64, op2: Imm8) {
+        self.insn("add", |asm| asm.encode_ri(0x83, 0, op1, op2));
+    }
+}
+
+impl Add<Reg32, Imm8> for Asm {
+    fn add(&mut self, op1: Reg32, op2: Imm8) {
+        self.insn("add", |asm| asm.encode_ri(0x83, 0, op1, op2));
     }
 }
 
+impl Add<Reg16, Imm8> for Asm {
+    fn add(&mut self, op1: Reg16, op2: Imm8) {
+        self.insn("add", |asm| asm.encode_ri(0x83, 0, op1, op2));
+    }
+}
+
+impl Add<Reg8, Imm8> for Asm {
+    fn add(&mut self, op1: Reg8, op2: Imm8) {
+        self.insn("add", |asm| asm.encode_ri(0x80, 0, op1, op2));
+    }
+}
+
+impl Add<Reg64, Imm32> for Asm {
+    fn add(&mut self, op1: Reg64, op2: Imm32) {
+        self.insn("add", |asm| asm.encode_ri(0x81, 0, op1, op2));
+    }
+}
+
+impl Add<Reg32, Imm32> for Asm {
+    fn add(&mut self, op1: Reg32, op2: Imm32) {
+        self.insn("add", |asm| asm.encode_ri(0x81, 0, op1, op2));
+    }
+}
+
+impl Add<Reg16, Imm16> for Asm {
+    fn add(&mut self, op1: Reg16, op2: Imm16) {
+        self.insn("add", |asm| asm.encode_ri(0x81, 0, op1, op2));
+    }
+}
+
+// -- ADD : reg mem
+
 impl Add<Reg64, Mem64> for Asm {
     fn add(&mut self, op1: Reg64, op2: Mem64) {
-        self.encode_rm(0x03, op1, op2);
+        self.insn("add", |asm| asm.encode_rm(&[0x03], op1, op2));
     }
 }
 
-impl Add<Mem8, Imm8> for Asm {
-    fn add(&mut self, op1: Mem8, op2: Imm8) {
-        self.encode_mi(0x80, 0, op1, op2);
+impl Add<Reg32, Mem32> for Asm {
+    fn add(&mut self, op1: Reg32, op2: Mem32) {
+        self.insn("add", |asm| asm.encode_rm(&[0x03], op1, op2));
     }
 }
 
-impl Add<Mem16, Imm8> for Asm {
-    fn add(&mut self, op1: Mem16, op2: Imm8) {
-        self.encode_mi(0x83, 0, op1, op2);
+impl Add<Reg16, Mem16> for Asm {
+    fn add(&mut self, op1: Reg16, op2: Mem16) {
+        self.insn("add", |asm| asm.encode_rm(&[0x03], op1, op2));
     }
 }
 
-impl Add<Mem32, Imm8> for Asm {
-    fn add(&mut self, op1: Mem32, op2: Imm8) {
-        self.encode_mi(0x83, 0, op1, op2);
+impl Add<Reg8, Mem8> for Asm {
+    fn add(&mut self, op1: Reg8, op2: Mem8) {
+        self.insn("add", |asm| asm.encode_rm(&[0x02], op1, op2));
     }
 }
 
+// -- ADD : mem reg
+
+impl Add<Mem64, Reg64> for Asm {
+    fn add(&mut self, op1: Mem64, op2: Reg64) {
+        self.insn("add", |asm| asm.encode_mr(&[0x01], op1, op2));
+    }
+}
+
+impl Add<Mem32, Reg32> for Asm {
+    fn add(&mut self, op1: Mem32, op2: Reg32) {
+        self.insn("add", |asm| asm.encode_mr(&[0x01], op1, op2));
+    }
+}
+
+impl Add<Mem16, Reg16> for Asm {
+    fn add(&mut self, op1: Mem16, op2: Reg16) {
+        self.insn("add", |asm| asm.encode_mr(&[0x01], op1, op2));
+    }
+}
+
+impl Add<Mem8, Reg8> for Asm {
+    fn add(&mut self, op1: Mem8, op2: Reg8) {
+        self.insn("add", |asm| asm.encode_mr(&[0x00], op1, op2));
+    }
+}
+
+// -- ADD : mem imm
+
 impl Add<Mem64, Imm8> for Asm {
     fn add(&mut self, op1: Mem64, op2: Imm8) {
-        self.encode_mi(0x83, 0, op1, op2);
+        self.insn("add", |asm| asm.encode_mi(0x83, 0, op1, op2));
+    }
+}
+
+impl Add<Mem32, Imm8> for Asm {
+    fn add(&mut self, op1: Mem32, op2: Imm8) {
+        self.insn("add", |asm| asm.encode_mi(0x83, 0, op1, op2));
+    }
+}
+
+impl Add<Mem16, Imm8> for Asm {
+    fn add(&mut self, op1: Mem16, op2: Imm8) {
+        self.insn("add", |asm| asm.encode_mi(0x83, 0, op1, op2));
+    }
+}
+
+impl Add<Mem8, Imm8> for Asm {
+    fn add(&mut self, op1: Mem8, op2: Imm8) {
+        self.insn("add", |asm| asm.encode_mi(0x80, 0, op1, op2));
+    }
+}
+
+impl Add<Mem64, Imm32> for Asm {
+    fn add(&mut self, op1: Mem64, op2: Imm32) {
+        self.insn("add", |asm| asm.encode_mi(0x81, 0, op1, op2));
+    }
+}
+
+impl Add<Mem32, Imm32> for Asm {
+    fn add(&mut self, op1: Mem32, op2: Imm32) {
+        self.insn("add", |asm| asm.encode_mi(0x81, 0, op1, op2));
     }
 }
 
 impl Add<Mem16, Imm16> for Asm {
     fn add(&mut self, op1: Mem16, op2: Imm16) {
-        self.encode_mi(0x81, 0, op1, op2);
+        self.insn("add", |asm| asm.encode_mi(0x81, 0, op1, op2));
     }
 }
@@ -0,0 +1,168 @@
+use super::And;
+use crate::{Asm, Imm16, Imm32, Imm8, Mem16, Mem32, Mem64, Mem8, Reg16, Reg32, Reg64, Reg8};
+
+// -- AND : reg reg
+
+impl And<Reg64, Reg64> for Asm {
+    fn and(&mut self, op1: Reg64, op2: Reg64) {
+        self.insn("and", |asm| asm.encode_rr(&[0x21], op1, op2));
+    }
+}
+
+impl And<Reg32, Reg32> for Asm {
+    fn and(&mut self, op1: Reg32, op2: Reg32) {
+        self.insn("and", |asm| asm.encode_rr(&[0x21], op1, op2));
+    }
+}
+
+impl And<Reg16, Reg16> for Asm {
+    fn and(&mut self, op1: Reg16, op2: Reg16) {
+        self.insn("and", |asm| asm.encode_rr(&[0x21], op1, op2));
+    }
+}
+
+impl And<Reg8, Reg8> for Asm {
+    fn and(&mut self, op1: Reg8, op2: Reg8) {
+        self.insn("and", |asm| asm.encode_rr(&[0x20], op1, op2));
+    }
+}
+
+// -- AND : reg imm
+
+impl And<Reg64, Imm32> for Asm {
+    fn and(&mut self, op1: Reg64, op2: Imm32) {
+        self.insn("and", |asm| asm.encode_ri(0x81, 4, op1, op2));
+    }
+}
+
+impl And<Reg32, Imm32> for Asm {
+    fn and(&mut self, op1: Reg32, op2: Imm32) {
+        self.insn("and", |asm| asm.encode_ri(0x81, 4, op1, op2));
+    }
+}
+
+impl And<Reg16, Imm16> for Asm {
+    fn and(&mut self, op1: Reg16, op2: Imm16) {
+        self.insn("and", |asm| asm.encode_ri(0x81, 4, op1, op2));
+    }
+}
+
+impl And<Reg8, Imm8> for Asm {
+    fn and(&mut self, op1: Reg8, op2: Imm8) {
+        self.insn("and", |asm| asm.encode_ri(0x80, 4, op1, op2));
+    }
+}
+
+impl And<Reg64, Imm8> for Asm {
+    fn and(&mut self, op1: Reg64, op2: Imm8) {
+        self.insn("and", |asm| asm.encode_ri(0x83, 4, op1, op2));
+    }
+}
+
+impl And<Reg32, Imm8> for Asm {
+    fn and(&mut self, op1: Reg32, op2: Imm8) {
+        self.insn("and", |asm| asm.encode_ri(0x83, 4, op1, op2));
+    }
+}
+
+impl And<Reg16, Imm8> for Asm {
+    fn and(&mut self, op1: Reg16, op2: Imm8) {
+        self.insn("and", |asm| asm.encode_ri(0x83, 4, op1, op2));
+    }
+}
+
+// -- AND : mem imm
+
+impl And<Mem64, Imm32> for Asm {
+    fn and(&mut self, op1: Mem64, op2: Imm32) {
+        self.insn("and", |asm| asm.encode_mi(0x81, 4, op1, op2));
+    }
+}
+
+impl And<Mem32, Imm32> for Asm {
+    fn and(&mut self, op1: Mem32, op2: Imm32) {
+        self.insn("and", |asm| asm.encode_mi(0x81, 4, op1, op2));
+    }
+}
+
+impl And<Mem16, Imm16> for Asm {
+    fn and(&mut self, op1: Mem16, op2: Imm16) {
+        self.insn("and", |asm| asm.encode_mi(0x81, 4, op1, op2));
+    }
+}
+
+impl And<Mem8, Imm8> for Asm {
+    fn and(&mut self, op1: Mem8, op2: Imm8) {
+        self.insn("and", |asm| asm.encode_mi(0x80, 4, op1, op2));
+    }
+}
+
+impl And<Mem64, Imm8> for Asm {
+    fn and(&mut self, op1: Mem64, op2: Imm8) {
+        self.insn("and", |asm| asm.encode_mi(0x83, 4, op1, op2));
+    }
+}
+
+impl And<Mem32, Imm8> for Asm {
+    fn and(&mut self, op1: Mem32, op2: Imm8) {
+        self.insn("and", |asm| asm.encode_mi(0x83, 4, op1, op2));
+    }
+}
+
+impl And<Mem16, Imm8> for Asm {
+    fn and(&mut self, op1: Mem16, op2: Imm8) {
+        self.insn("and", |asm| asm.encode_mi(0x83, 4, op1, op2));
+    }
+}
+
+// -- AND : reg mem
+
+impl And<Reg64, Mem64> for Asm {
+    fn and(&mut self, op1: Reg64, op2: Mem64) {
+        self.insn("and", |asm| asm.encode_rm(&[0x23], op1, op2));
+    }
+}
+
+impl And<Reg32, Mem32> for Asm {
+    fn and(&mut self, op1: Reg32, op2: Mem32) {
+        self.insn("and", |asm| asm.encode_rm(&[0x23], op1, op2));
+    }
+}
+
+impl And<Reg16, Mem16> for Asm {
+    fn and(&mut self, op1: Reg16, op2: Mem16) {
+        self.insn("and", |asm| asm.encode_rm(&[0x23], op1, op2));
+    }
+}
+
+impl And<Reg8, Mem8> for Asm {
+    fn and(&mut self, op1: Reg8, op2: Mem8) {
+        self.insn("and", |asm| asm.encode_rm(&[0x22], op1, op2));
+    }
+}
+
+// -- AND : mem reg
+
+impl And<Mem64, Reg64> for Asm {
+    fn and(&mut self, op1: Mem64, op2: Reg64) {
+        self.insn("and", |asm| asm.encode_mr(&[0x21], op1, op2));
+    }
+}
+
+impl And<Mem32, Reg32> for Asm {
+    fn and(&mut self, op1: Mem32, op2: Reg32) {
+        self.insn("and", |asm| asm.encode_mr(&[0x21], op1, op2));
+    }
+}
+
+impl And<Mem16, Reg16> for Asm {
+    fn and(&mut self, op1: Mem16, op2: Reg16) {
+        self.insn("and", |asm| asm.encode_mr(&[0x21], op1, op2));
+    }
+}
+
+impl And<Mem8, Reg8> for Asm {
+    fn and(&mut self, op1: Mem8, op2: Reg8) {
+        self.insn("and", |asm| asm.encode_mr(&[0x20], op1, op2));
+    }
+}
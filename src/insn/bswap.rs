@@ -0,0 +1,14 @@
+use super::Bswap;
+use crate::{Asm, Reg32, Reg64};
+
+impl Bswap<Reg64> for Asm {
+    fn bswap(&mut self, op1: Reg64) {
+        self.insn("bswap", |asm| asm.encode_o(&[0x0f, 0xc8], op1));
+    }
+}
+
+impl Bswap<Reg32> for Asm {
+    fn bswap(&mut self, op1: Reg32) {
+        self.insn("bswap", |asm| asm.encode_o(&[0x0f, 0xc8], op1));
+    }
+}
@@ -1,8 +1,26 @@
 use super::Call;
-use crate::{Asm, Reg64};
+use crate::{Asm, Label, LabelId, Mem64, Reg64};
 
 impl Call<Reg64> for Asm {
     fn call(&mut self, op1: Reg64) {
-        self.encode_r(0xff, 0x2, op1);
+        self.insn("call", |asm| asm.encode_r(&[0xff], 0x2, op1));
+    }
+}
+
+impl Call<Mem64> for Asm {
+    fn call(&mut self, op1: Mem64) {
+        self.insn("call", |asm| asm.encode_m(&[0xff], 0x2, op1));
+    }
+}
+
+impl Call<&mut Label> for Asm {
+    fn call(&mut self, op1: &mut Label) {
+        self.insn("call", |asm| asm.encode_jmp_label(&[0xe8], op1));
+    }
+}
+
+impl Call<LabelId> for Asm {
+    fn call(&mut self, op1: LabelId) {
+        self.with_label(op1, |asm, l| asm.call(l));
     }
 }
@@ -0,0 +1,8 @@
+use crate::Asm;
+
+impl Asm {
+    /// Emit a [`cbw`](https://www.felixcloutier.com/x86/cbw:cwde:cdqe) instruction, sign-extending `al` into `ax`.
+    pub fn cbw(&mut self) {
+        self.insn("cbw", |asm| asm.encode_zo(&[0x66, 0x98]));
+    }
+}
@@ -0,0 +1,8 @@
+use crate::Asm;
+
+impl Asm {
+    /// Emit a [`cdq`](https://www.felixcloutier.com/x86/cwd:cdq:cqo) instruction, sign-extending `eax` into `edx:eax`.
+    pub fn cdq(&mut self) {
+        self.insn("cdq", |asm| asm.encode_zo(&[0x99]));
+    }
+}
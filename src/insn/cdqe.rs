@@ -0,0 +1,8 @@
+use crate::Asm;
+
+impl Asm {
+    /// Emit a [`cdqe`](https://www.felixcloutier.com/x86/cbw:cwde:cdqe) instruction, sign-extending `eax` into `rax`.
+    pub fn cdqe(&mut self) {
+        self.insn("cdqe", |asm| asm.encode_zo(&[0x48, 0x98]));
+    }
+}
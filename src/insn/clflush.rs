@@ -0,0 +1,8 @@
+use super::Clflush;
+use crate::{Asm, Mem8};
+
+impl Clflush<Mem8> for Asm {
+    fn clflush(&mut self, op1: Mem8) {
+        self.insn("clflush", |asm| asm.encode_m(&[0x0f, 0xae], 7, op1));
+    }
+}
@@ -0,0 +1,17 @@
+use super::Clflushopt;
+use crate::{Asm, Mem8};
+
+// The mandatory 66 prefix is emitted before the REX prefix.
+
+impl Clflushopt<Mem8> for Asm {
+    fn clflushopt(&mut self, op1: Mem8) {
+        assert!(
+            self.cpu_features().clflushopt,
+            "Instruction clflushopt not enabled in the CPU features"
+        );
+        self.insn("clflushopt", |asm| {
+            asm.emit(&[0x66]);
+            asm.encode_m(&[0x0f, 0xae], 7, op1);
+        });
+    }
+}
@@ -0,0 +1,17 @@
+use super::Clwb;
+use crate::{Asm, Mem8};
+
+// The mandatory 66 prefix is emitted before the REX prefix.
+
+impl Clwb<Mem8> for Asm {
+    fn clwb(&mut self, op1: Mem8) {
+        assert!(
+            self.cpu_features().clwb,
+            "Instruction clwb not enabled in the CPU features"
+        );
+        self.insn("clwb", |asm| {
+            asm.emit(&[0x66]);
+            asm.encode_m(&[0x0f, 0xae], 6, op1);
+        });
+    }
+}
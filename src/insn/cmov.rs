@@ -0,0 +1,55 @@
+use super::Cmov;
+use crate::{Asm, Cond, Mem16, Mem32, Mem64, Reg16, Reg32, Reg64};
+
+const MNEMONICS: [&str; 16] = [
+    "cmovo", "cmovno", "cmovb", "cmovae", "cmove", "cmovne", "cmovbe", "cmova", "cmovs", "cmovns",
+    "cmovp", "cmovnp", "cmovl", "cmovge", "cmovle", "cmovg",
+];
+
+impl Cmov<Reg64, Reg64> for Asm {
+    fn cmov(&mut self, cond: Cond, op1: Reg64, op2: Reg64) {
+        self.insn(MNEMONICS[usize::from(cond.code())], |asm| {
+            asm.encode_rr(&[0x0f, 0x40 + cond.code()], op2, op1)
+        });
+    }
+}
+
+impl Cmov<Reg32, Reg32> for Asm {
+    fn cmov(&mut self, cond: Cond, op1: Reg32, op2: Reg32) {
+        self.insn(MNEMONICS[usize::from(cond.code())], |asm| {
+            asm.encode_rr(&[0x0f, 0x40 + cond.code()], op2, op1)
+        });
+    }
+}
+
+impl Cmov<Reg16, Reg16> for Asm {
+    fn cmov(&mut self, cond: Cond, op1: Reg16, op2: Reg16) {
+        self.insn(MNEMONICS[usize::from(cond.code())], |asm| {
+            asm.encode_rr(&[0x0f, 0x40 + cond.code()], op2, op1)
+        });
+    }
+}
+
+impl Cmov<Reg64, Mem64> for Asm {
+    fn cmov(&mut self, cond: Cond, op1: Reg64, op2: Mem64) {
+        self.insn(MNEMONICS[usize::from(cond.code())], |asm| {
+            asm.encode_rm(&[0x0f, 0x40 + cond.code()], op1, op2)
+        });
+    }
+}
+
+impl Cmov<Reg32, Mem32> for Asm {
+    fn cmov(&mut self, cond: Cond, op1: Reg32, op2: Mem32) {
+        self.insn(MNEMONICS[usize::from(cond.code())], |asm| {
+            asm.encode_rm(&[0x0f, 0x40 + cond.code()], op1, op2)
+        });
+    }
+}
+
+impl Cmov<Reg16, Mem16> for Asm {
+    fn cmov(&mut self, cond: Cond, op1: Reg16, op2: Mem16) {
+        self.insn(MNEMONICS[usize::from(cond.code())], |asm| {
+            asm.encode_rm(&[0x0f, 0x40 + cond.code()], op1, op2)
+        });
+    }
+}
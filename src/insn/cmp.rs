@@ -1,20 +1,168 @@
 use super::Cmp;
-use crate::{Asm, Imm16, Imm8, Mem16, Mem8, Reg64};
+use crate::{Asm, Imm16, Imm32, Imm8, Mem16, Mem32, Mem64, Mem8, Reg16, Reg32, Reg64, Reg8};
+
+// -- CMP : reg reg
+
+impl Cmp<Reg64, Reg64> for Asm {
+    fn cmp(&mut self, op1: Reg64, op2: Reg64) {
+        self.insn("cmp", |asm| asm.encode_rr(&[0x39], op1, op2));
+    }
+}
+
+impl Cmp<Reg32, Reg32> for Asm {
+    fn cmp(&mut self, op1: Reg32, op2: Reg32) {
+        self.insn("cmp", |asm| asm.encode_rr(&[0x39], op1, op2));
+    }
+}
+
+impl Cmp<Reg16, Reg16> for Asm {
+    fn cmp(&mut self, op1: Reg16, op2: Reg16) {
+        self.insn("cmp", |asm| asm.encode_rr(&[0x39], op1, op2));
+    }
+}
+
+impl Cmp<Reg8, Reg8> for Asm {
+    fn cmp(&mut self, op1: Reg8, op2: Reg8) {
+        self.insn("cmp", |asm| asm.encode_rr(&[0x38], op1, op2));
+    }
+}
+
+// -- CMP : reg imm
+
+impl Cmp<Reg64, Imm8> for Asm {
+    fn cmp(&mut self, op1: Reg64, op2: Imm8) {
+        self.insn("cmp", |asm| asm.encode_ri(0x83, 0x7, op1, op2));
+    }
+}
+
+impl Cmp<Reg32, Imm8> for Asm {
+    fn cmp(&mut self, op1: Reg32, op2: Imm8) {
+        self.insn("cmp", |asm| asm.encode_ri(0x83, 0x7, op1, op2));
+    }
+}
+
+impl Cmp<Reg16, Imm8> for Asm {
+    fn cmp(&mut self, op1: Reg16, op2: Imm8) {
+        self.insn("cmp", |asm| asm.encode_ri(0x83, 0x7, op1, op2));
+    }
+}
+
+impl Cmp<Reg8, Imm8> for Asm {
+    fn cmp(&mut self, op1: Reg8, op2: Imm8) {
+        self.insn("cmp", |asm| asm.encode_ri(0x80, 0x7, op1, op2));
+    }
+}
+
+impl Cmp<Reg64, Imm32> for Asm {
+    fn cmp(&mut self, op1: Reg64, op2: Imm32) {
+        self.insn("cmp", |asm| asm.encode_ri(0x81, 0x7, op1, op2));
+    }
+}
+
+impl Cmp<Reg32, Imm32> for Asm {
+    fn cmp(&mut self, op1: Reg32, op2: Imm32) {
+        self.insn("cmp", |asm| asm.encode_ri(0x81, 0x7, op1, op2));
+    }
+}
+
+impl Cmp<Reg16, Imm16> for Asm {
+    fn cmp(&mut self, op1: Reg16, op2: Imm16) {
+        self.insn("cmp", |asm| asm.encode_ri(0x81, 0x7, op1, op2));
+    }
+}
+
+// -- CMP : reg mem
+
+impl Cmp<Reg64, Mem64> for Asm {
+    fn cmp(&mut self, op1: Reg64, op2: Mem64) {
+        self.insn("cmp", |asm| asm.encode_rm(&[0x3b], op1, op2));
+    }
+}
+
+impl Cmp<Reg32, Mem32> for Asm {
+    fn cmp(&mut self, op1: Reg32, op2: Mem32) {
+        self.insn("cmp", |asm| asm.encode_rm(&[0x3b], op1, op2));
+    }
+}
+
+impl Cmp<Reg16, Mem16> for Asm {
+    fn cmp(&mut self, op1: Reg16, op2: Mem16) {
+        self.insn("cmp", |asm| asm.encode_rm(&[0x3b], op1, op2));
+    }
+}
+
+impl Cmp<Reg8, Mem8> for Asm {
+    fn cmp(&mut self, op1: Reg8, op2: Mem8) {
+        self.insn("cmp", |asm| asm.encode_rm(&[0x3a], op1, op2));
+    }
+}
+
+// -- CMP : mem reg
+
+impl Cmp<Mem64, Reg64> for Asm {
+    fn cmp(&mut self, op1: Mem64, op2: Reg64) {
+        self.insn("cmp", |asm| asm.encode_mr(&[0x39], op1, op2));
+    }
+}
+
+impl Cmp<Mem32, Reg32> for Asm {
+    fn cmp(&mut self, op1: Mem32, op2: Reg32) {
+        self.insn("cmp", |asm| asm.encode_mr(&[0x39], op1, op2));
+    }
+}
+
+impl Cmp<Mem16, Reg16> for Asm {
+    fn cmp(&mut self, op1: Mem16, op2: Reg16) {
+        self.insn("cmp", |asm| asm.encode_mr(&[0x39], op1, op2));
+    }
+}
+
+impl Cmp<Mem8, Reg8> for Asm {
+    fn cmp(&mut self, op1: Mem8, op2: Reg8) {
+        self.insn("cmp", |asm| asm.encode_mr(&[0x38], op1, op2));
+    }
+}
+
+// -- CMP : mem imm
+
+impl Cmp<Mem64, Imm8> for Asm {
+    fn cmp(&mut self, op1: Mem64, op2: Imm8) {
+        self.insn("cmp", |asm| asm.encode_mi(0x83, 0x7, op1, op2));
+    }
+}
+
+impl Cmp<Mem32, Imm8> for Asm {
+    fn cmp(&mut self, op1: Mem32, op2: Imm8) {
+        self.insn("cmp", |asm| asm.encode_mi(0x83, 0x7, op1, op2));
+    }
+}
+
+impl Cmp<Mem16, Imm8> for Asm {
+    fn cmp(&mut self, op1: Mem16, op2: Imm8) {
+        self.insn("cmp", |asm| asm.encode_mi(0x83, 0x7, op1, op2));
+    }
+}
 
 impl Cmp<Mem8, Imm8> for Asm {
     fn cmp(&mut self, op1: Mem8, op2: Imm8) {
-        self.encode_mi(0x80, 0x7, op1, op2);
+        self.insn("cmp", |asm| asm.encode_mi(0x80, 0x7, op1, op2));
     }
 }
 
-impl Cmp<Mem16, Imm16> for Asm {
-    fn cmp(&mut self, op1: Mem16, op2: Imm16) {
-        self.encode_mi(0x81, 0x7, op1, op2);
+impl Cmp<Mem64, Imm32> for Asm {
+    fn cmp(&mut self, op1: Mem64, op2: Imm32) {
+        self.insn("cmp", |asm| asm.encode_mi(0x81, 0x7, op1, op2));
     }
 }
 
-impl Cmp<Reg64, Reg64> for Asm {
-    fn cmp(&mut self, op1: Reg64, op2: Reg64) {
-        self.encode_rr(&[0x3b], op1, op2);
+impl Cmp<Mem32, Imm32> for Asm {
+    fn cmp(&mut self, op1: Mem32, op2: Imm32) {
+        self.insn("cmp", |asm| asm.encode_mi(0x81, 0x7, op1, op2));
+    }
+}
+
+impl Cmp<Mem16, Imm16> for Asm {
+    fn cmp(&mut self, op1: Mem16, op2: Imm16) {
+        self.insn("cmp", |asm| asm.encode_mi(0x81, 0x7, op1, op2));
     }
 }
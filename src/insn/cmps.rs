@@ -0,0 +1,18 @@
+use crate::Asm;
+
+impl Asm {
+    /// Emit a [`cmpsb`](https://www.felixcloutier.com/x86/cmps:cmpsb:cmpsw:cmpsd:cmpsq)
+    /// instruction, comparing the bytes at `[rsi]` and `[rdi]` and advancing both.
+    pub fn cmpsb(&mut self) {
+        self.insn("cmpsb", |asm| asm.encode_zo(&[0xa6]));
+    }
+
+    /// Emit a [`repe`](https://www.felixcloutier.com/x86/rep:repe:repz:repne:repnz)
+    /// [`cmpsb`](https://www.felixcloutier.com/x86/cmps:cmpsb:cmpsw:cmpsd:cmpsq) instruction,
+    /// comparing at most `rcx` bytes at `[rsi]` and `[rdi]` until they differ.
+    ///
+    /// After a mismatch both pointers point past the differing bytes and `ZF` is cleared.
+    pub fn repe_cmpsb(&mut self) {
+        self.insn("repe cmpsb", |asm| asm.encode_zo(&[0xf3, 0xa6]));
+    }
+}
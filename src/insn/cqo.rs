@@ -0,0 +1,8 @@
+use crate::Asm;
+
+impl Asm {
+    /// Emit a [`cqo`](https://www.felixcloutier.com/x86/cwd:cdq:cqo) instruction, sign-extending `rax` into `rdx:rax`.
+    pub fn cqo(&mut self) {
+        self.insn("cqo", |asm| asm.encode_zo(&[0x48, 0x99]));
+    }
+}
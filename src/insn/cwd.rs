@@ -0,0 +1,8 @@
+use crate::Asm;
+
+impl Asm {
+    /// Emit a [`cwd`](https://www.felixcloutier.com/x86/cwd:cdq:cqo) instruction, sign-extending `ax` into `dx:ax`.
+    pub fn cwd(&mut self) {
+        self.insn("cwd", |asm| asm.encode_zo(&[0x66, 0x99]));
+    }
+}
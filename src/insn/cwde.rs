@@ -0,0 +1,8 @@
+use crate::Asm;
+
+impl Asm {
+    /// Emit a [`cwde`](https://www.felixcloutier.com/x86/cbw:cwde:cdqe) instruction, sign-extending `ax` into `eax`.
+    pub fn cwde(&mut self) {
+        self.insn("cwde", |asm| asm.encode_zo(&[0x98]));
+    }
+}
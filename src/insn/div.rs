@@ -0,0 +1,54 @@
+use super::Div;
+use crate::{Asm, Mem16, Mem32, Mem64, Mem8, Reg16, Reg32, Reg64, Reg8};
+
+// -- DIV : reg
+
+impl Div<Reg64> for Asm {
+    fn div(&mut self, op1: Reg64) {
+        self.insn("div", |asm| asm.encode_r(&[0xf7], 6, op1));
+    }
+}
+
+impl Div<Reg32> for Asm {
+    fn div(&mut self, op1: Reg32) {
+        self.insn("div", |asm| asm.encode_r(&[0xf7], 6, op1));
+    }
+}
+
+impl Div<Reg16> for Asm {
+    fn div(&mut self, op1: Reg16) {
+        self.insn("div", |asm| asm.encode_r(&[0xf7], 6, op1));
+    }
+}
+
+impl Div<Reg8> for Asm {
+    fn div(&mut self, op1: Reg8) {
+        self.insn("div", |asm| asm.encode_r(&[0xf6], 6, op1));
+    }
+}
+
+// -- DIV : mem
+
+impl Div<Mem64> for Asm {
+    fn div(&mut self, op1: Mem64) {
+        self.insn("div", |asm| asm.encode_m(&[0xf7], 6, op1));
+    }
+}
+
+impl Div<Mem32> for Asm {
+    fn div(&mut self, op1: Mem32) {
+        self.insn("div", |asm| asm.encode_m(&[0xf7], 6, op1));
+    }
+}
+
+impl Div<Mem16> for Asm {
+    fn div(&mut self, op1: Mem16) {
+        self.insn("div", |asm| asm.encode_m(&[0xf7], 6, op1));
+    }
+}
+
+impl Div<Mem8> for Asm {
+    fn div(&mut self, op1: Mem8) {
+        self.insn("div", |asm| asm.encode_m(&[0xf6], 6, op1));
+    }
+}
@@ -0,0 +1,9 @@
+use crate::{Asm, Imm16, Imm8};
+
+impl Asm {
+    /// Emit an [`enter`](https://www.felixcloutier.com/x86/enter) instruction, creating a stack
+    /// frame with `op1` bytes of local storage and the lexical nesting level `op2`.
+    pub fn enter(&mut self, op1: Imm16, op2: Imm8) {
+        self.insn("enter", |asm| asm.encode_ii(0xc8, op1, op2));
+    }
+}
@@ -0,0 +1,54 @@
+use super::Idiv;
+use crate::{Asm, Mem16, Mem32, Mem64, Mem8, Reg16, Reg32, Reg64, Reg8};
+
+// -- IDIV : reg
+
+impl Idiv<Reg64> for Asm {
+    fn idiv(&mut self, op1: Reg64) {
+        self.insn("idiv", |asm| asm.encode_r(&[0xf7], 7, op1));
+    }
+}
+
+impl Idiv<Reg32> for Asm {
+    fn idiv(&mut self, op1: Reg32) {
+        self.insn("idiv", |asm| asm.encode_r(&[0xf7], 7, op1));
+    }
+}
+
+impl Idiv<Reg16> for Asm {
+    fn idiv(&mut self, op1: Reg16) {
+        self.insn("idiv", |asm| asm.encode_r(&[0xf7], 7, op1));
+    }
+}
+
+impl Idiv<Reg8> for Asm {
+    fn idiv(&mut self, op1: Reg8) {
+        self.insn("idiv", |asm| asm.encode_r(&[0xf6], 7, op1));
+    }
+}
+
+// -- IDIV : mem
+
+impl Idiv<Mem64> for Asm {
+    fn idiv(&mut self, op1: Mem64) {
+        self.insn("idiv", |asm| asm.encode_m(&[0xf7], 7, op1));
+    }
+}
+
+impl Idiv<Mem32> for Asm {
+    fn idiv(&mut self, op1: Mem32) {
+        self.insn("idiv", |asm| asm.encode_m(&[0xf7], 7, op1));
+    }
+}
+
+impl Idiv<Mem16> for Asm {
+    fn idiv(&mut self, op1: Mem16) {
+        self.insn("idiv", |asm| asm.encode_m(&[0xf7], 7, op1));
+    }
+}
+
+impl Idiv<Mem8> for Asm {
+    fn idiv(&mut self, op1: Mem8) {
+        self.insn("idiv", |asm| asm.encode_m(&[0xf6], 7, op1));
+    }
+}
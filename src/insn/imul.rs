@@ -0,0 +1,170 @@
+use super::Imul;
+use crate::{Asm, Imm16, Imm32, Imm8, Mem16, Mem32, Mem64, Mem8, Reg16, Reg32, Reg64, Reg8};
+
+// -- IMUL : reg
+
+impl Imul<Reg64> for Asm {
+    fn imul(&mut self, op1: Reg64) {
+        self.insn("imul", |asm| asm.encode_r(&[0xf7], 5, op1));
+    }
+}
+
+impl Imul<Reg32> for Asm {
+    fn imul(&mut self, op1: Reg32) {
+        self.insn("imul", |asm| asm.encode_r(&[0xf7], 5, op1));
+    }
+}
+
+impl Imul<Reg16> for Asm {
+    fn imul(&mut self, op1: Reg16) {
+        self.insn("imul", |asm| asm.encode_r(&[0xf7], 5, op1));
+    }
+}
+
+impl Imul<Reg8> for Asm {
+    fn imul(&mut self, op1: Reg8) {
+        self.insn("imul", |asm| asm.encode_r(&[0xf6], 5, op1));
+    }
+}
+
+// -- IMUL : mem
+
+impl Imul<Mem64> for Asm {
+    fn imul(&mut self, op1: Mem64) {
+        self.insn("imul", |asm| asm.encode_m(&[0xf7], 5, op1));
+    }
+}
+
+impl Imul<Mem32> for Asm {
+    fn imul(&mut self, op1: Mem32) {
+        self.insn("imul", |asm| asm.encode_m(&[0xf7], 5, op1));
+    }
+}
+
+impl Imul<Mem16> for Asm {
+    fn imul(&mut self, op1: Mem16) {
+        self.insn("imul", |asm| asm.encode_m(&[0xf7], 5, op1));
+    }
+}
+
+impl Imul<Mem8> for Asm {
+    fn imul(&mut self, op1: Mem8) {
+        self.insn("imul", |asm| asm.encode_m(&[0xf6], 5, op1));
+    }
+}
+
+// -- IMUL : reg reg
+
+impl Imul<(Reg64, Reg64)> for Asm {
+    fn imul(&mut self, (op1, op2): (Reg64, Reg64)) {
+        self.insn("imul", |asm| asm.encode_rr(&[0x0f, 0xaf], op2, op1));
+    }
+}
+
+impl Imul<(Reg32, Reg32)> for Asm {
+    fn imul(&mut self, (op1, op2): (Reg32, Reg32)) {
+        self.insn("imul", |asm| asm.encode_rr(&[0x0f, 0xaf], op2, op1));
+    }
+}
+
+impl Imul<(Reg16, Reg16)> for Asm {
+    fn imul(&mut self, (op1, op2): (Reg16, Reg16)) {
+        self.insn("imul", |asm| asm.encode_rr(&[0x0f, 0xaf], op2, op1));
+    }
+}
+
+// -- IMUL : reg mem
+
+impl Imul<(Reg64, Mem64)> for Asm {
+    fn imul(&mut self, (op1, op2): (Reg64, Mem64)) {
+        self.insn("imul", |asm| asm.encode_rm(&[0x0f, 0xaf], op1, op2));
+    }
+}
+
+impl Imul<(Reg32, Mem32)> for Asm {
+    fn imul(&mut self, (op1, op2): (Reg32, Mem32)) {
+        self.insn("imul", |asm| asm.encode_rm(&[0x0f, 0xaf], op1, op2));
+    }
+}
+
+impl Imul<(Reg16, Mem16)> for Asm {
+    fn imul(&mut self, (op1, op2): (Reg16, Mem16)) {
+        self.insn("imul", |asm| asm.encode_rm(&[0x0f, 0xaf], op1, op2));
+    }
+}
+
+// -- IMUL : reg reg imm
+
+impl Imul<(Reg64, Reg64, Imm32)> for Asm {
+    fn imul(&mut self, (op1, op2, op3): (Reg64, Reg64, Imm32)) {
+        self.insn("imul", |asm| asm.encode_rri(&[0x69], op1, op2, op3));
+    }
+}
+
+impl Imul<(Reg32, Reg32, Imm32)> for Asm {
+    fn imul(&mut self, (op1, op2, op3): (Reg32, Reg32, Imm32)) {
+        self.insn("imul", |asm| asm.encode_rri(&[0x69], op1, op2, op3));
+    }
+}
+
+impl Imul<(Reg16, Reg16, Imm16)> for Asm {
+    fn imul(&mut self, (op1, op2, op3): (Reg16, Reg16, Imm16)) {
+        self.insn("imul", |asm| asm.encode_rri(&[0x69], op1, op2, op3));
+    }
+}
+
+impl Imul<(Reg64, Reg64, Imm8)> for Asm {
+    fn imul(&mut self, (op1, op2, op3): (Reg64, Reg64, Imm8)) {
+        self.insn("imul", |asm| asm.encode_rri(&[0x6b], op1, op2, op3));
+    }
+}
+
+impl Imul<(Reg32, Reg32, Imm8)> for Asm {
+    fn imul(&mut self, (op1, op2, op3): (Reg32, Reg32, Imm8)) {
+        self.insn("imul", |asm| asm.encode_rri(&[0x6b], op1, op2, op3));
+    }
+}
+
+impl Imul<(Reg16, Reg16, Imm8)> for Asm {
+    fn imul(&mut self, (op1, op2, op3): (Reg16, Reg16, Imm8)) {
+        self.insn("imul", |asm| asm.encode_rri(&[0x6b], op1, op2, op3));
+    }
+}
+
+// -- IMUL : reg mem imm
+
+impl Imul<(Reg64, Mem64, Imm32)> for Asm {
+    fn imul(&mut self, (op1, op2, op3): (Reg64, Mem64, Imm32)) {
+        self.insn("imul", |asm| asm.encode_rmi(&[0x69], op1, op2, op3));
+    }
+}
+
+impl Imul<(Reg32, Mem32, Imm32)> for Asm {
+    fn imul(&mut self, (op1, op2, op3): (Reg32, Mem32, Imm32)) {
+        self.insn("imul", |asm| asm.encode_rmi(&[0x69], op1, op2, op3));
+    }
+}
+
+impl Imul<(Reg16, Mem16, Imm16)> for Asm {
+    fn imul(&mut self, (op1, op2, op3): (Reg16, Mem16, Imm16)) {
+        self.insn("imul", |asm| asm.encode_rmi(&[0x69], op1, op2, op3));
+    }
+}
+
+impl Imul<(Reg64, Mem64, Imm8)> for Asm {
+    fn imul(&mut self, (op1, op2, op3): (Reg64, Mem64, Imm8)) {
+        self.insn("imul", |asm| asm.encode_rmi(&[0x6b], op1, op2, op3));
+    }
+}
+
+impl Imul<(Reg32, Mem32, Imm8)> for Asm {
+    fn imul(&mut self, (op1, op2, op3): (Reg32, Mem32, Imm8)) {
+        self.insn("imul", |asm| asm.encode_rmi(&[0x6b], op1, op2, op3));
+    }
+}
+
+impl Imul<(Reg16, Mem16, Imm8)> for Asm {
+    fn imul(&mut self, (op1, op2, op3): (Reg16, Mem16, Imm8)) {
+        self.insn("imul", |asm| asm.encode_rmi(&[0x6b], op1, op2, op3));
+    }
+}
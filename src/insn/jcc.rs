@@ -0,0 +1,31 @@
+use crate::{Asm, Cond, Label};
+
+const MNEMONICS: [&str; 16] = [
+    "jo", "jno", "jb", "jae", "je", "jne", "jbe", "ja", "js", "jns", "jp", "jnp", "jl", "jge",
+    "jle", "jg",
+];
+
+impl Asm {
+    /// Emit a [`jcc`](https://www.felixcloutier.com/x86/jcc) instruction, jumping to `op1` if
+    /// `cond` holds.
+    pub fn jcc(&mut self, cond: Cond, op1: &mut Label) {
+        self.insn(MNEMONICS[usize::from(cond.code())], |asm| {
+            asm.encode_jmp_label_relaxed(&[0x70 + cond.code()], &[0x0f, 0x80 + cond.code()], op1)
+        });
+    }
+
+    /// Emit a [`jcc`](https://www.felixcloutier.com/x86/jcc) instruction with a `rel8`
+    /// displacement, jumping to `op1` if `cond` holds.
+    ///
+    /// Unlike [`Asm::jcc`], which only emits the short form for bound labels in range, this also
+    /// emits the short form for forward jumps.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the label is bound out of range of the `rel8` displacement.
+    pub fn jcc_short(&mut self, cond: Cond, op1: &mut Label) {
+        self.insn(MNEMONICS[usize::from(cond.code())], |asm| {
+            asm.encode_jmp_label8(&[0x70 + cond.code()], op1)
+        });
+    }
+}
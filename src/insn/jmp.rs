@@ -1,8 +1,43 @@
 use super::Jmp;
-use crate::{Asm, Label};
+use crate::{Asm, Label, LabelId, Mem64, Reg64};
 
 impl Jmp<&mut Label> for Asm {
     fn jmp(&mut self, op1: &mut Label) {
-        self.encode_jmp_label(&[0xe9], op1);
+        self.insn("jmp", |asm| {
+            asm.encode_jmp_label_relaxed(&[0xeb], &[0xe9], op1)
+        });
+    }
+}
+
+impl Jmp<LabelId> for Asm {
+    fn jmp(&mut self, op1: LabelId) {
+        self.with_label(op1, |asm, l| asm.jmp(l));
+    }
+}
+
+impl Jmp<Reg64> for Asm {
+    fn jmp(&mut self, op1: Reg64) {
+        self.insn("jmp", |asm| asm.encode_r(&[0xff], 0x4, op1));
+    }
+}
+
+impl Jmp<Mem64> for Asm {
+    fn jmp(&mut self, op1: Mem64) {
+        self.insn("jmp", |asm| asm.encode_m(&[0xff], 0x4, op1));
+    }
+}
+
+impl Asm {
+    /// Emit a [`jmp`](https://www.felixcloutier.com/x86/jmp) instruction with a `rel8`
+    /// displacement to `op1`.
+    ///
+    /// Unlike [`Jmp::jmp`], which only emits the short form for bound labels in range, this also
+    /// emits the short form for forward jumps.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the label is bound out of range of the `rel8` displacement.
+    pub fn jmp_short(&mut self, op1: &mut Label) {
+        self.insn("jmp", |asm| asm.encode_jmp_label8(&[0xeb], op1));
     }
 }
@@ -1,8 +1,16 @@
 use super::Jnz;
-use crate::{Asm, Label};
+use crate::{Asm, Label, LabelId};
 
 impl Jnz<&mut Label> for Asm {
     fn jnz(&mut self, op1: &mut Label) {
-        self.encode_jmp_label(&[0x0f, 0x85], op1);
+        self.insn("jnz", |asm| {
+            asm.encode_jmp_label_relaxed(&[0x75], &[0x0f, 0x85], op1)
+        });
+    }
+}
+
+impl Jnz<LabelId> for Asm {
+    fn jnz(&mut self, op1: LabelId) {
+        self.with_label(op1, |asm, l| asm.jnz(l));
     }
 }
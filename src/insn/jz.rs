@@ -1,8 +1,16 @@
 use super::Jz;
-use crate::{Asm, Label};
+use crate::{Asm, Label, LabelId};
 
 impl Jz<&mut Label> for Asm {
     fn jz(&mut self, op1: &mut Label) {
-        self.encode_jmp_label(&[0x0f, 0x84], op1);
+        self.insn("jz", |asm| {
+            asm.encode_jmp_label_relaxed(&[0x74], &[0x0f, 0x84], op1)
+        });
+    }
+}
+
+impl Jz<LabelId> for Asm {
+    fn jz(&mut self, op1: LabelId) {
+        self.with_label(op1, |asm, l| asm.jz(l));
     }
 }
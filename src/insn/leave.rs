@@ -0,0 +1,9 @@
+use crate::Asm;
+
+impl Asm {
+    /// Emit a [`leave`](https://www.felixcloutier.com/x86/leave) instruction, tearing down the
+    /// stack frame by restoring `rsp` from `rbp` and popping `rbp`.
+    pub fn leave(&mut self) {
+        self.insn("leave", |asm| asm.encode_zo(&[0xc9]));
+    }
+}
@@ -0,0 +1,9 @@
+use crate::Asm;
+
+impl Asm {
+    /// Emit a [`lfence`](https://www.felixcloutier.com/x86/lfence) instruction, serializing all
+    /// prior loads.
+    pub fn lfence(&mut self) {
+        self.insn("lfence", |asm| asm.encode_zo(&[0x0f, 0xae, 0xe8]));
+    }
+}
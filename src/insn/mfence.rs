@@ -0,0 +1,9 @@
+use crate::Asm;
+
+impl Asm {
+    /// Emit a [`mfence`](https://www.felixcloutier.com/x86/mfence) instruction, serializing all
+    /// prior loads and stores.
+    pub fn mfence(&mut self) {
+        self.insn("mfence", |asm| asm.encode_zo(&[0x0f, 0xae, 0xf0]));
+    }
+}
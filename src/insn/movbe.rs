@@ -0,0 +1,54 @@
+use super::Movbe;
+use crate::{Asm, Mem16, Mem32, Mem64, Reg16, Reg32, Reg64};
+
+impl Asm {
+    /// Check that `movbe` may be emitted for the configured CPU features.
+    fn check_movbe(&self) {
+        assert!(
+            self.cpu_features().movbe,
+            "Instruction movbe not enabled in the CPU features"
+        );
+    }
+}
+
+impl Movbe<Reg64, Mem64> for Asm {
+    fn movbe(&mut self, op1: Reg64, op2: Mem64) {
+        self.check_movbe();
+        self.insn("movbe", |asm| asm.encode_rm(&[0x0f, 0x38, 0xf0], op1, op2));
+    }
+}
+
+impl Movbe<Reg32, Mem32> for Asm {
+    fn movbe(&mut self, op1: Reg32, op2: Mem32) {
+        self.check_movbe();
+        self.insn("movbe", |asm| asm.encode_rm(&[0x0f, 0x38, 0xf0], op1, op2));
+    }
+}
+
+impl Movbe<Reg16, Mem16> for Asm {
+    fn movbe(&mut self, op1: Reg16, op2: Mem16) {
+        self.check_movbe();
+        self.insn("movbe", |asm| asm.encode_rm(&[0x0f, 0x38, 0xf0], op1, op2));
+    }
+}
+
+impl Movbe<Mem64, Reg64> for Asm {
+    fn movbe(&mut self, op1: Mem64, op2: Reg64) {
+        self.check_movbe();
+        self.insn("movbe", |asm| asm.encode_mr(&[0x0f, 0x38, 0xf1], op1, op2));
+    }
+}
+
+impl Movbe<Mem32, Reg32> for Asm {
+    fn movbe(&mut self, op1: Mem32, op2: Reg32) {
+        self.check_movbe();
+        self.insn("movbe", |asm| asm.encode_mr(&[0x0f, 0x38, 0xf1], op1, op2));
+    }
+}
+
+impl Movbe<Mem16, Reg16> for Asm {
+    fn movbe(&mut self, op1: Mem16, op2: Reg16) {
+        self.check_movbe();
+        self.insn("movbe", |asm| asm.encode_mr(&[0x0f, 0x38, 0xf1], op1, op2));
+    }
+}
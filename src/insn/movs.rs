@@ -0,0 +1,17 @@
+use crate::Asm;
+
+impl Asm {
+    /// Emit a [`rep`](https://www.felixcloutier.com/x86/rep:repe:repz:repne:repnz)
+    /// [`movsb`](https://www.felixcloutier.com/x86/movs:movsb:movsw:movsd:movsq) instruction,
+    /// copying `rcx` bytes from `[rsi]` to `[rdi]`.
+    pub fn rep_movsb(&mut self) {
+        self.insn("rep movsb", |asm| asm.encode_zo(&[0xf3, 0xa4]));
+    }
+
+    /// Emit a [`rep`](https://www.felixcloutier.com/x86/rep:repe:repz:repne:repnz)
+    /// [`movsq`](https://www.felixcloutier.com/x86/movs:movsb:movsw:movsd:movsq) instruction,
+    /// copying `rcx` quadwords from `[rsi]` to `[rdi]`.
+    pub fn rep_movsq(&mut self) {
+        self.insn("rep movsq", |asm| asm.encode_zo(&[0xf3, 0x48, 0xa5]));
+    }
+}
@@ -0,0 +1,62 @@
+use super::Movsx;
+use crate::{Asm, Mem16, Mem8, Reg16, Reg32, Reg64, Reg8};
+
+impl Movsx<Reg64, Reg8> for Asm {
+    fn movsx(&mut self, op1: Reg64, op2: Reg8) {
+        self.insn("movsx", |asm| asm.encode_rr_ext(&[0x0f, 0xbe], op1, op2));
+    }
+}
+
+impl Movsx<Reg64, Reg16> for Asm {
+    fn movsx(&mut self, op1: Reg64, op2: Reg16) {
+        self.insn("movsx", |asm| asm.encode_rr_ext(&[0x0f, 0xbf], op1, op2));
+    }
+}
+
+impl Movsx<Reg64, Mem8> for Asm {
+    fn movsx(&mut self, op1: Reg64, op2: Mem8) {
+        self.insn("movsx", |asm| asm.encode_rm_ext(&[0x0f, 0xbe], op1, op2));
+    }
+}
+
+impl Movsx<Reg64, Mem16> for Asm {
+    fn movsx(&mut self, op1: Reg64, op2: Mem16) {
+        self.insn("movsx", |asm| asm.encode_rm_ext(&[0x0f, 0xbf], op1, op2));
+    }
+}
+
+impl Movsx<Reg32, Reg8> for Asm {
+    fn movsx(&mut self, op1: Reg32, op2: Reg8) {
+        self.insn("movsx", |asm| asm.encode_rr_ext(&[0x0f, 0xbe], op1, op2));
+    }
+}
+
+impl Movsx<Reg32, Reg16> for Asm {
+    fn movsx(&mut self, op1: Reg32, op2: Reg16) {
+        self.insn("movsx", |asm| asm.encode_rr_ext(&[0x0f, 0xbf], op1, op2));
+    }
+}
+
+impl Movsx<Reg32, Mem8> for Asm {
+    fn movsx(&mut self, op1: Reg32, op2: Mem8) {
+        self.insn("movsx", |asm| asm.encode_rm_ext(&[0x0f, 0xbe], op1, op2));
+    }
+}
+
+impl Movsx<Reg32, Mem16> for Asm {
+    fn movsx(&mut self, op1: Reg32, op2: Mem16) {
+        self.insn("movsx", |asm| asm.encode_rm_ext(&[0x0f, 0xbf], op1, op2));
+    }
+}
+
+impl Movsx<Reg16, Reg8> for Asm {
+    fn movsx(&mut self, op1: Reg16, op2: Reg8) {
+        self.insn("movsx", |asm| asm.encode_rr_ext(&[0x0f, 0xbe], op1, op2));
+    }
+}
+
+impl Movsx<Reg16, Mem8> for Asm {
+    fn movsx(&mut self, op1: Reg16, op2: Mem8) {
+        self.insn("movsx", |asm| asm.encode_rm_ext(&[0x0f, 0xbe], op1, op2));
+    }
+}
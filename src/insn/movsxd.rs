@@ -0,0 +1,14 @@
+use super::Movsxd;
+use crate::{Asm, Mem32, Reg32, Reg64};
+
+impl Movsxd<Reg64, Reg32> for Asm {
+    fn movsxd(&mut self, op1: Reg64, op2: Reg32) {
+        self.insn("movsxd", |asm| asm.encode_rr_ext(&[0x63], op1, op2));
+    }
+}
+
+impl Movsxd<Reg64, Mem32> for Asm {
+    fn movsxd(&mut self, op1: Reg64, op2: Mem32) {
+        self.insn("movsxd", |asm| asm.encode_rm_ext(&[0x63], op1, op2));
+    }
+}
@@ -0,0 +1,62 @@
+use super::Movzx;
+use crate::{Asm, Mem16, Mem8, Reg16, Reg32, Reg64, Reg8};
+
+impl Movzx<Reg64, Reg8> for Asm {
+    fn movzx(&mut self, op1: Reg64, op2: Reg8) {
+        self.insn("movzx", |asm| asm.encode_rr_ext(&[0x0f, 0xb6], op1, op2));
+    }
+}
+
+impl Movzx<Reg64, Reg16> for Asm {
+    fn movzx(&mut self, op1: Reg64, op2: Reg16) {
+        self.insn("movzx", |asm| asm.encode_rr_ext(&[0x0f, 0xb7], op1, op2));
+    }
+}
+
+impl Movzx<Reg64, Mem8> for Asm {
+    fn movzx(&mut self, op1: Reg64, op2: Mem8) {
+        self.insn("movzx", |asm| asm.encode_rm_ext(&[0x0f, 0xb6], op1, op2));
+    }
+}
+
+impl Movzx<Reg64, Mem16> for Asm {
+    fn movzx(&mut self, op1: Reg64, op2: Mem16) {
+        self.insn("movzx", |asm| asm.encode_rm_ext(&[0x0f, 0xb7], op1, op2));
+    }
+}
+
+impl Movzx<Reg32, Reg8> for Asm {
+    fn movzx(&mut self, op1: Reg32, op2: Reg8) {
+        self.insn("movzx", |asm| asm.encode_rr_ext(&[0x0f, 0xb6], op1, op2));
+    }
+}
+
+impl Movzx<Reg32, Reg16> for Asm {
+    fn movzx(&mut self, op1: Reg32, op2: Reg16) {
+        self.insn("movzx", |asm| asm.encode_rr_ext(&[0x0f, 0xb7], op1, op2));
+    }
+}
+
+impl Movzx<Reg32, Mem8> for Asm {
+    fn movzx(&mut self, op1: Reg32, op2: Mem8) {
+        self.insn("movzx", |asm| asm.encode_rm_ext(&[0x0f, 0xb6], op1, op2));
+    }
+}
+
+impl Movzx<Reg32, Mem16> for Asm {
+    fn movzx(&mut self, op1: Reg32, op2: Mem16) {
+        self.insn("movzx", |asm| asm.encode_rm_ext(&[0x0f, 0xb7], op1, op2));
+    }
+}
+
+impl Movzx<Reg16, Reg8> for Asm {
+    fn movzx(&mut self, op1: Reg16, op2: Reg8) {
+        self.insn("movzx", |asm| asm.encode_rr_ext(&[0x0f, 0xb6], op1, op2));
+    }
+}
+
+impl Movzx<Reg16, Mem8> for Asm {
+    fn movzx(&mut self, op1: Reg16, op2: Mem8) {
+        self.insn("movzx", |asm| asm.encode_rm_ext(&[0x0f, 0xb6], op1, op2));
+    }
+}
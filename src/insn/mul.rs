@@ -0,0 +1,54 @@
+use super::Mul;
+use crate::{Asm, Mem16, Mem32, Mem64, Mem8, Reg16, Reg32, Reg64, Reg8};
+
+// -- MUL : reg
+
+impl Mul<Reg64> for Asm {
+    fn mul(&mut self, op1: Reg64) {
+        self.insn("mul", |asm| asm.encode_r(&[0xf7], 4, op1));
+    }
+}
+
+impl Mul<Reg32> for Asm {
+    fn mul(&mut self, op1: Reg32) {
+        self.insn("mul", |asm| asm.encode_r(&[0xf7], 4, op1));
+    }
+}
+
+impl Mul<Reg16> for Asm {
+    fn mul(&mut self, op1: Reg16) {
+        self.insn("mul", |asm| asm.encode_r(&[0xf7], 4, op1));
+    }
+}
+
+impl Mul<Reg8> for Asm {
+    fn mul(&mut self, op1: Reg8) {
+        self.insn("mul", |asm| asm.encode_r(&[0xf6], 4, op1));
+    }
+}
+
+// -- MUL : mem
+
+impl Mul<Mem64> for Asm {
+    fn mul(&mut self, op1: Mem64) {
+        self.insn("mul", |asm| asm.encode_m(&[0xf7], 4, op1));
+    }
+}
+
+impl Mul<Mem32> for Asm {
+    fn mul(&mut self, op1: Mem32) {
+        self.insn("mul", |asm| asm.encode_m(&[0xf7], 4, op1));
+    }
+}
+
+impl Mul<Mem16> for Asm {
+    fn mul(&mut self, op1: Mem16) {
+        self.insn("mul", |asm| asm.encode_m(&[0xf7], 4, op1));
+    }
+}
+
+impl Mul<Mem8> for Asm {
+    fn mul(&mut self, op1: Mem8) {
+        self.insn("mul", |asm| asm.encode_m(&[0xf6], 4, op1));
+    }
+}
@@ -0,0 +1,54 @@
+use super::Neg;
+use crate::{Asm, Mem16, Mem32, Mem64, Mem8, Reg16, Reg32, Reg64, Reg8};
+
+// -- NEG : reg
+
+impl Neg<Reg64> for Asm {
+    fn neg(&mut self, op1: Reg64) {
+        self.insn("neg", |asm| asm.encode_r(&[0xf7], 3, op1));
+    }
+}
+
+impl Neg<Reg32> for Asm {
+    fn neg(&mut self, op1: Reg32) {
+        self.insn("neg", |asm| asm.encode_r(&[0xf7], 3, op1));
+    }
+}
+
+impl Neg<Reg16> for Asm {
+    fn neg(&mut self, op1: Reg16) {
+        self.insn("neg", |asm| asm.encode_r(&[0xf7], 3, op1));
+    }
+}
+
+impl Neg<Reg8> for Asm {
+    fn neg(&mut self, op1: Reg8) {
+        self.insn("neg", |asm| asm.encode_r(&[0xf6], 3, op1));
+    }
+}
+
+// -- NEG : mem
+
+impl Neg<Mem64> for Asm {
+    fn neg(&mut self, op1: Mem64) {
+        self.insn("neg", |asm| asm.encode_m(&[0xf7], 3, op1));
+    }
+}
+
+impl Neg<Mem32> for Asm {
+    fn neg(&mut self, op1: Mem32) {
+        self.insn("neg", |asm| asm.encode_m(&[0xf7], 3, op1));
+    }
+}
+
+impl Neg<Mem16> for Asm {
+    fn neg(&mut self, op1: Mem16) {
+        self.insn("neg", |asm| asm.encode_m(&[0xf7], 3, op1));
+    }
+}
+
+impl Neg<Mem8> for Asm {
+    fn neg(&mut self, op1: Mem8) {
+        self.insn("neg", |asm| asm.encode_m(&[0xf6], 3, op1));
+    }
+}
@@ -3,6 +3,6 @@ use crate::Asm;
 impl Asm {
     /// Emit a [`nop`](https://www.felixcloutier.com/x86/nop) instruction.
     pub fn nop(&mut self) {
-        self.emit(&[0x90]);
+        self.insn("nop", |asm| asm.encode_zo(&[0x90]));
     }
 }
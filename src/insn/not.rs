@@ -0,0 +1,54 @@
+use super::Not;
+use crate::{Asm, Mem16, Mem32, Mem64, Mem8, Reg16, Reg32, Reg64, Reg8};
+
+// -- NOT : reg
+
+impl Not<Reg64> for Asm {
+    fn not(&mut self, op1: Reg64) {
+        self.insn("not", |asm| asm.encode_r(&[0xf7], 2, op1));
+    }
+}
+
+impl Not<Reg32> for Asm {
+    fn not(&mut self, op1: Reg32) {
+        self.insn("not", |asm| asm.encode_r(&[0xf7], 2, op1));
+    }
+}
+
+impl Not<Reg16> for Asm {
+    fn not(&mut self, op1: Reg16) {
+        self.insn("not", |asm| asm.encode_r(&[0xf7], 2, op1));
+    }
+}
+
+impl Not<Reg8> for Asm {
+    fn not(&mut self, op1: Reg8) {
+        self.insn("not", |asm| asm.encode_r(&[0xf6], 2, op1));
+    }
+}
+
+// -- NOT : mem
+
+impl Not<Mem64> for Asm {
+    fn not(&mut self, op1: Mem64) {
+        self.insn("not", |asm| asm.encode_m(&[0xf7], 2, op1));
+    }
+}
+
+impl Not<Mem32> for Asm {
+    fn not(&mut self, op1: Mem32) {
+        self.insn("not", |asm| asm.encode_m(&[0xf7], 2, op1));
+    }
+}
+
+impl Not<Mem16> for Asm {
+    fn not(&mut self, op1: Mem16) {
+        self.insn("not", |asm| asm.encode_m(&[0xf7], 2, op1));
+    }
+}
+
+impl Not<Mem8> for Asm {
+    fn not(&mut self, op1: Mem8) {
+        self.insn("not", |asm| asm.encode_m(&[0xf6], 2, op1));
+    }
+}
@@ -0,0 +1,168 @@
+use super::Or;
+use crate::{Asm, Imm16, Imm32, Imm8, Mem16, Mem32, Mem64, Mem8, Reg16, Reg32, Reg64, Reg8};
+
+// -- OR : reg reg
+
+impl Or<Reg64, Reg64> for Asm {
+    fn or(&mut self, op1: Reg64, op2: Reg64) {
+        self.insn("or", |asm| asm.encode_rr(&[0x09], op1, op2));
+    }
+}
+
+impl Or<Reg32, Reg32> for Asm {
+    fn or(&mut self, op1: Reg32, op2: Reg32) {
+        self.insn("or", |asm| asm.encode_rr(&[0x09], op1, op2));
+    }
+}
+
+impl Or<Reg16, Reg16> for Asm {
+    fn or(&mut self, op1: Reg16, op2: Reg16) {
+        self.insn("or", |asm| asm.encode_rr(&[0x09], op1, op2));
+    }
+}
+
+impl Or<Reg8, Reg8> for Asm {
+    fn or(&mut self, op1: Reg8, op2: Reg8) {
+        self.insn("or", |asm| asm.encode_rr(&[0x08], op1, op2));
+    }
+}
+
+// -- OR : reg imm
+
+impl Or<Reg64, Imm32> for Asm {
+    fn or(&mut self, op1: Reg64, op2: Imm32) {
+        self.insn("or", |asm| asm.encode_ri(0x81, 1, op1, op2));
+    }
+}
+
+impl Or<Reg32, Imm32> for Asm {
+    fn or(&mut self, op1: Reg32, op2: Imm32) {
+        self.insn("or", |asm| asm.encode_ri(0x81, 1, op1, op2));
+    }
+}
+
+impl Or<Reg16, Imm16> for Asm {
+    fn or(&mut self, op1: Reg16, op2: Imm16) {
+        self.insn("or", |asm| asm.encode_ri(0x81, 1, op1, op2));
+    }
+}
+
+impl Or<Reg8, Imm8> for Asm {
+    fn or(&mut self, op1: Reg8, op2: Imm8) {
+        self.insn("or", |asm| asm.encode_ri(0x80, 1, op1, op2));
+    }
+}
+
+impl Or<Reg64, Imm8> for Asm {
+    fn or(&mut self, op1: Reg64, op2: Imm8) {
+        self.insn("or", |asm| asm.encode_ri(0x83, 1, op1, op2));
+    }
+}
+
+impl Or<Reg32, Imm8> for Asm {
+    fn or(&mut self, op1: Reg32, op2: Imm8) {
+        self.insn("or", |asm| asm.encode_ri(0x83, 1, op1, op2));
+    }
+}
+
+impl Or<Reg16, Imm8> for Asm {
+    fn or(&mut self, op1: Reg16, op2: Imm8) {
+        self.insn("or", |asm| asm.encode_ri(0x83, 1, op1, op2));
+    }
+}
+
+// -- OR : mem imm
+
+impl Or<Mem64, Imm32> for Asm {
+    fn or(&mut self, op1: Mem64, op2: Imm32) {
+        self.insn("or", |asm| asm.encode_mi(0x81, 1, op1, op2));
+    }
+}
+
+impl Or<Mem32, Imm32> for Asm {
+    fn or(&mut self, op1: Mem32, op2: Imm32) {
+        self.insn("or", |asm| asm.encode_mi(0x81, 1, op1, op2));
+    }
+}
+
+impl Or<Mem16, Imm16> for Asm {
+    fn or(&mut self, op1: Mem16, op2: Imm16) {
+        self.insn("or", |asm| asm.encode_mi(0x81, 1, op1, op2));
+    }
+}
+
+impl Or<Mem8, Imm8> for Asm {
+    fn or(&mut self, op1: Mem8, op2: Imm8) {
+        self.insn("or", |asm| asm.encode_mi(0x80, 1, op1, op2));
+    }
+}
+
+impl Or<Mem64, Imm8> for Asm {
+    fn or(&mut self, op1: Mem64, op2: Imm8) {
+        self.insn("or", |asm| asm.encode_mi(0x83, 1, op1, op2));
+    }
+}
+
+impl Or<Mem32, Imm8> for Asm {
+    fn or(&mut self, op1: Mem32, op2: Imm8) {
+        self.insn("or", |asm| asm.encode_mi(0x83, 1, op1, op2));
+    }
+}
+
+impl Or<Mem16, Imm8> for Asm {
+    fn or(&mut self, op1: Mem16, op2: Imm8) {
+        self.insn("or", |asm| asm.encode_mi(0x83, 1, op1, op2));
+    }
+}
+
+// -- OR : reg mem
+
+impl Or<Reg64, Mem64> for Asm {
+    fn or(&mut self, op1: Reg64, op2: Mem64) {
+        self.insn("or", |asm| asm.encode_rm(&[0x0b], op1, op2));
+    }
+}
+
+impl Or<Reg32, Mem32> for Asm {
+    fn or(&mut self, op1: Reg32, op2: Mem32) {
+        self.insn("or", |asm| asm.encode_rm(&[0x0b], op1, op2));
+    }
+}
+
+impl Or<Reg16, Mem16> for Asm {
+    fn or(&mut self, op1: Reg16, op2: Mem16) {
+        self.insn("or", |asm| asm.encode_rm(&[0x0b], op1, op2));
+    }
+}
+
+impl Or<Reg8, Mem8> for Asm {
+    fn or(&mut self, op1: Reg8, op2: Mem8) {
+        self.insn("or", |asm| asm.encode_rm(&[0x0a], op1, op2));
+    }
+}
+
+// -- OR : mem reg
+
+impl Or<Mem64, Reg64> for Asm {
+    fn or(&mut self, op1: Mem64, op2: Reg64) {
+        self.insn("or", |asm| asm.encode_mr(&[0x09], op1, op2));
+    }
+}
+
+impl Or<Mem32, Reg32> for Asm {
+    fn or(&mut self, op1: Mem32, op2: Reg32) {
+        self.insn("or", |asm| asm.encode_mr(&[0x09], op1, op2));
+    }
+}
+
+impl Or<Mem16, Reg16> for Asm {
+    fn or(&mut self, op1: Mem16, op2: Reg16) {
+        self.insn("or", |asm| asm.encode_mr(&[0x09], op1, op2));
+    }
+}
+
+impl Or<Mem8, Reg8> for Asm {
+    fn or(&mut self, op1: Mem8, op2: Reg8) {
+        self.insn("or", |asm| asm.encode_mr(&[0x08], op1, op2));
+    }
+}
@@ -0,0 +1,74 @@
+use super::Popcnt;
+use crate::{Asm, Mem16, Mem32, Mem64, Reg16, Reg32, Reg64};
+
+impl Asm {
+    /// Check that `popcnt` may be emitted for the configured CPU features.
+    fn check_popcnt(&self) {
+        assert!(
+            self.cpu_features().popcnt,
+            "Instruction popcnt not enabled in the CPU features"
+        );
+    }
+}
+
+// The mandatory F3 prefix is emitted before the operand size prefix and the REX prefix.
+
+impl Popcnt<Reg64, Reg64> for Asm {
+    fn popcnt(&mut self, op1: Reg64, op2: Reg64) {
+        self.check_popcnt();
+        self.insn("popcnt", |asm| {
+            asm.emit(&[0xf3]);
+            asm.encode_rr(&[0x0f, 0xb8], op2, op1);
+        });
+    }
+}
+
+impl Popcnt<Reg32, Reg32> for Asm {
+    fn popcnt(&mut self, op1: Reg32, op2: Reg32) {
+        self.check_popcnt();
+        self.insn("popcnt", |asm| {
+            asm.emit(&[0xf3]);
+            asm.encode_rr(&[0x0f, 0xb8], op2, op1);
+        });
+    }
+}
+
+impl Popcnt<Reg16, Reg16> for Asm {
+    fn popcnt(&mut self, op1: Reg16, op2: Reg16) {
+        self.check_popcnt();
+        self.insn("popcnt", |asm| {
+            asm.emit(&[0xf3]);
+            asm.encode_rr(&[0x0f, 0xb8], op2, op1);
+        });
+    }
+}
+
+impl Popcnt<Reg64, Mem64> for Asm {
+    fn popcnt(&mut self, op1: Reg64, op2: Mem64) {
+        self.check_popcnt();
+        self.insn("popcnt", |asm| {
+            asm.emit(&[0xf3]);
+            asm.encode_rm(&[0x0f, 0xb8], op1, op2);
+        });
+    }
+}
+
+impl Popcnt<Reg32, Mem32> for Asm {
+    fn popcnt(&mut self, op1: Reg32, op2: Mem32) {
+        self.check_popcnt();
+        self.insn("popcnt", |asm| {
+            asm.emit(&[0xf3]);
+            asm.encode_rm(&[0x0f, 0xb8], op1, op2);
+        });
+    }
+}
+
+impl Popcnt<Reg16, Mem16> for Asm {
+    fn popcnt(&mut self, op1: Reg16, op2: Mem16) {
+        self.check_popcnt();
+        self.insn("popcnt", |asm| {
+            asm.emit(&[0xf3]);
+            asm.encode_rm(&[0x0f, 0xb8], op1, op2);
+        });
+    }
+}
@@ -0,0 +1,9 @@
+use crate::Asm;
+
+impl Asm {
+    /// Emit a [`popfq`](https://www.felixcloutier.com/x86/popf:popfd:popfq) instruction, popping
+    /// the `rflags` register from the stack.
+    pub fn popfq(&mut self) {
+        self.insn("popfq", |asm| asm.encode_zo(&[0x9d]));
+    }
+}
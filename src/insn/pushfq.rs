@@ -0,0 +1,9 @@
+use crate::Asm;
+
+impl Asm {
+    /// Emit a [`pushfq`](https://www.felixcloutier.com/x86/pushf:pushfd:pushfq) instruction,
+    /// pushing the `rflags` register onto the stack.
+    pub fn pushfq(&mut self) {
+        self.insn("pushfq", |asm| asm.encode_zo(&[0x9c]));
+    }
+}
@@ -0,0 +1,9 @@
+use crate::Asm;
+
+impl Asm {
+    /// Emit a [`rdtsc`](https://www.felixcloutier.com/x86/rdtsc) instruction, reading the time
+    /// stamp counter into `edx:eax`.
+    pub fn rdtsc(&mut self) {
+        self.insn("rdtsc", |asm| asm.encode_zo(&[0x0f, 0x31]));
+    }
+}
@@ -0,0 +1,10 @@
+use crate::Asm;
+
+impl Asm {
+    /// Emit a [`rdtscp`](https://www.felixcloutier.com/x86/rdtscp) instruction, reading the time
+    /// stamp counter into `edx:eax` and the processor id into `ecx`, after all prior instructions
+    /// executed.
+    pub fn rdtscp(&mut self) {
+        self.insn("rdtscp", |asm| asm.encode_zo(&[0x0f, 0x01, 0xf9]));
+    }
+}
@@ -2,7 +2,15 @@ use crate::Asm;
 
 impl Asm {
     /// Emit a [`ret`](https://www.felixcloutier.com/x86/ret) instruction.
+    ///
+    /// If a [`ShadowStack`](crate::ShadowStack) is enabled, the return address is checked
+    /// against the shadow stack before returning.
     pub fn ret(&mut self) {
-        self.emit(&[0xc3]);
+        self.insn("ret", |asm| {
+            if let Some(ss) = asm.shadow() {
+                asm.shadow_ret(ss);
+            }
+            asm.encode_zo(&[0xc3]);
+        });
     }
 }
@@ -0,0 +1,106 @@
+use super::Rol;
+use crate::{Asm, Imm8, Mem16, Mem32, Mem64, Mem8, Reg16, Reg32, Reg64, Reg8};
+
+// -- ROL : reg imm
+
+impl Rol<Reg64, Imm8> for Asm {
+    fn rol(&mut self, op1: Reg64, op2: Imm8) {
+        self.insn("rol", |asm| asm.encode_ri(0xc1, 0, op1, op2));
+    }
+}
+
+impl Rol<Reg32, Imm8> for Asm {
+    fn rol(&mut self, op1: Reg32, op2: Imm8) {
+        self.insn("rol", |asm| asm.encode_ri(0xc1, 0, op1, op2));
+    }
+}
+
+impl Rol<Reg16, Imm8> for Asm {
+    fn rol(&mut self, op1: Reg16, op2: Imm8) {
+        self.insn("rol", |asm| asm.encode_ri(0xc1, 0, op1, op2));
+    }
+}
+
+impl Rol<Reg8, Imm8> for Asm {
+    fn rol(&mut self, op1: Reg8, op2: Imm8) {
+        self.insn("rol", |asm| asm.encode_ri(0xc0, 0, op1, op2));
+    }
+}
+
+// -- ROL : mem imm
+
+impl Rol<Mem64, Imm8> for Asm {
+    fn rol(&mut self, op1: Mem64, op2: Imm8) {
+        self.insn("rol", |asm| asm.encode_mi(0xc1, 0, op1, op2));
+    }
+}
+
+impl Rol<Mem32, Imm8> for Asm {
+    fn rol(&mut self, op1: Mem32, op2: Imm8) {
+        self.insn("rol", |asm| asm.encode_mi(0xc1, 0, op1, op2));
+    }
+}
+
+impl Rol<Mem16, Imm8> for Asm {
+    fn rol(&mut self, op1: Mem16, op2: Imm8) {
+        self.insn("rol", |asm| asm.encode_mi(0xc1, 0, op1, op2));
+    }
+}
+
+impl Rol<Mem8, Imm8> for Asm {
+    fn rol(&mut self, op1: Mem8, op2: Imm8) {
+        self.insn("rol", |asm| asm.encode_mi(0xc0, 0, op1, op2));
+    }
+}
+
+// -- ROL : reg cl
+
+impl Rol<Reg64, Reg8> for Asm {
+    fn rol(&mut self, op1: Reg64, op2: Reg8) {
+        self.insn("rol", |asm| asm.encode_r_cl(0xd3, 0, op1, op2));
+    }
+}
+
+impl Rol<Reg32, Reg8> for Asm {
+    fn rol(&mut self, op1: Reg32, op2: Reg8) {
+        self.insn("rol", |asm| asm.encode_r_cl(0xd3, 0, op1, op2));
+    }
+}
+
+impl Rol<Reg16, Reg8> for Asm {
+    fn rol(&mut self, op1: Reg16, op2: Reg8) {
+        self.insn("rol", |asm| asm.encode_r_cl(0xd3, 0, op1, op2));
+    }
+}
+
+impl Rol<Reg8, Reg8> for Asm {
+    fn rol(&mut self, op1: Reg8, op2: Reg8) {
+        self.insn("rol", |asm| asm.encode_r_cl(0xd2, 0, op1, op2));
+    }
+}
+
+// -- ROL : mem cl
+
+impl Rol<Mem64, Reg8> for Asm {
+    fn rol(&mut self, op1: Mem64, op2: Reg8) {
+        self.insn("rol", |asm| asm.encode_m_cl(0xd3, 0, op1, op2));
+    }
+}
+
+impl Rol<Mem32, Reg8> for Asm {
+    fn rol(&mut self, op1: Mem32, op2: Reg8) {
+        self.insn("rol", |asm| asm.encode_m_cl(0xd3, 0, op1, op2));
+    }
+}
+
+impl Rol<Mem16, Reg8> for Asm {
+    fn rol(&mut self, op1: Mem16, op2: Reg8) {
+        self.insn("rol", |asm| asm.encode_m_cl(0xd3, 0, op1, op2));
+    }
+}
+
+impl Rol<Mem8, Reg8> for Asm {
+    fn rol(&mut self, op1: Mem8, op2: Reg8) {
+        self.insn("rol", |asm| asm.encode_m_cl(0xd2, 0, op1, op2));
+    }
+}
@@ -0,0 +1,106 @@
+use super::Ror;
+use crate::{Asm, Imm8, Mem16, Mem32, Mem64, Mem8, Reg16, Reg32, Reg64, Reg8};
+
+// -- ROR : reg imm
+
+impl Ror<Reg64, Imm8> for Asm {
+    fn ror(&mut self, op1: Reg64, op2: Imm8) {
+        self.insn("ror", |asm| asm.encode_ri(0xc1, 1, op1, op2));
+    }
+}
+
+impl Ror<Reg32, Imm8> for Asm {
+    fn ror(&mut self, op1: Reg32, op2: Imm8) {
+        self.insn("ror", |asm| asm.encode_ri(0xc1, 1, op1, op2));
+    }
+}
+
+impl Ror<Reg16, Imm8> for Asm {
+    fn ror(&mut self, op1: Reg16, op2: Imm8) {
+        self.insn("ror", |asm| asm.encode_ri(0xc1, 1, op1, op2));
+    }
+}
+
+impl Ror<Reg8, Imm8> for Asm {
+    fn ror(&mut self, op1: Reg8, op2: Imm8) {
+        self.insn("ror", |asm| asm.encode_ri(0xc0, 1, op1, op2));
+    }
+}
+
+// -- ROR : mem imm
+
+impl Ror<Mem64, Imm8> for Asm {
+    fn ror(&mut self, op1: Mem64, op2: Imm8) {
+        self.insn("ror", |asm| asm.encode_mi(0xc1, 1, op1, op2));
+    }
+}
+
+impl Ror<Mem32, Imm8> for Asm {
+    fn ror(&mut self, op1: Mem32, op2: Imm8) {
+        self.insn("ror", |asm| asm.encode_mi(0xc1, 1, op1, op2));
+    }
+}
+
+impl Ror<Mem16, Imm8> for Asm {
+    fn ror(&mut self, op1: Mem16, op2: Imm8) {
+        self.insn("ror", |asm| asm.encode_mi(0xc1, 1, op1, op2));
+    }
+}
+
+impl Ror<Mem8, Imm8> for Asm {
+    fn ror(&mut self, op1: Mem8, op2: Imm8) {
+        self.insn("ror", |asm| asm.encode_mi(0xc0, 1, op1, op2));
+    }
+}
+
+// -- ROR : reg cl
+
+impl Ror<Reg64, Reg8> for Asm {
+    fn ror(&mut self, op1: Reg64, op2: Reg8) {
+        self.insn("ror", |asm| asm.encode_r_cl(0xd3, 1, op1, op2));
+    }
+}
+
+impl Ror<Reg32, Reg8> for Asm {
+    fn ror(&mut self, op1: Reg32, op2: Reg8) {
+        self.insn("ror", |asm| asm.encode_r_cl(0xd3, 1, op1, op2));
+    }
+}
+
+impl Ror<Reg16, Reg8> for Asm {
+    fn ror(&mut self, op1: Reg16, op2: Reg8) {
+        self.insn("ror", |asm| asm.encode_r_cl(0xd3, 1, op1, op2));
+    }
+}
+
+impl Ror<Reg8, Reg8> for Asm {
+    fn ror(&mut self, op1: Reg8, op2: Reg8) {
+        self.insn("ror", |asm| asm.encode_r_cl(0xd2, 1, op1, op2));
+    }
+}
+
+// -- ROR : mem cl
+
+impl Ror<Mem64, Reg8> for Asm {
+    fn ror(&mut self, op1: Mem64, op2: Reg8) {
+        self.insn("ror", |asm| asm.encode_m_cl(0xd3, 1, op1, op2));
+    }
+}
+
+impl Ror<Mem32, Reg8> for Asm {
+    fn ror(&mut self, op1: Mem32, op2: Reg8) {
+        self.insn("ror", |asm| asm.encode_m_cl(0xd3, 1, op1, op2));
+    }
+}
+
+impl Ror<Mem16, Reg8> for Asm {
+    fn ror(&mut self, op1: Mem16, op2: Reg8) {
+        self.insn("ror", |asm| asm.encode_m_cl(0xd3, 1, op1, op2));
+    }
+}
+
+impl Ror<Mem8, Reg8> for Asm {
+    fn ror(&mut self, op1: Mem8, op2: Reg8) {
+        self.insn("ror", |asm| asm.encode_m_cl(0xd2, 1, op1, op2));
+    }
+}
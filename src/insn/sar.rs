@@ -0,0 +1,106 @@
+use super::Sar;
+use crate::{Asm, Imm8, Mem16, Mem32, Mem64, Mem8, Reg16, Reg32, Reg64, Reg8};
+
+// -- SAR : reg imm
+
+impl Sar<Reg64, Imm8> for Asm {
+    fn sar(&mut self, op1: Reg64, op2: Imm8) {
+        self.insn("sar", |asm| asm.encode_ri(0xc1, 7, op1, op2));
+    }
+}
+
+impl Sar<Reg32, Imm8> for Asm {
+    fn sar(&mut self, op1: Reg32, op2: Imm8) {
+        self.insn("sar", |asm| asm.encode_ri(0xc1, 7, op1, op2));
+    }
+}
+
+impl Sar<Reg16, Imm8> for Asm {
+    fn sar(&mut self, op1: Reg16, op2: Imm8) {
+        self.insn("sar", |asm| asm.encode_ri(0xc1, 7, op1, op2));
+    }
+}
+
+impl Sar<Reg8, Imm8> for Asm {
+    fn sar(&mut self, op1: Reg8, op2: Imm8) {
+        self.insn("sar", |asm| asm.encode_ri(0xc0, 7, op1, op2));
+    }
+}
+
+// -- SAR : mem imm
+
+impl Sar<Mem64, Imm8> for Asm {
+    fn sar(&mut self, op1: Mem64, op2: Imm8) {
+        self.insn("sar", |asm| asm.encode_mi(0xc1, 7, op1, op2));
+    }
+}
+
+impl Sar<Mem32, Imm8> for Asm {
+    fn sar(&mut self, op1: Mem32, op2: Imm8) {
+        self.insn("sar", |asm| asm.encode_mi(0xc1, 7, op1, op2));
+    }
+}
+
+impl Sar<Mem16, Imm8> for Asm {
+    fn sar(&mut self, op1: Mem16, op2: Imm8) {
+        self.insn("sar", |asm| asm.encode_mi(0xc1, 7, op1, op2));
+    }
+}
+
+impl Sar<Mem8, Imm8> for Asm {
+    fn sar(&mut self, op1: Mem8, op2: Imm8) {
+        self.insn("sar", |asm| asm.encode_mi(0xc0, 7, op1, op2));
+    }
+}
+
+// -- SAR : reg cl
+
+impl Sar<Reg64, Reg8> for Asm {
+    fn sar(&mut self, op1: Reg64, op2: Reg8) {
+        self.insn("sar", |asm| asm.encode_r_cl(0xd3, 7, op1, op2));
+    }
+}
+
+impl Sar<Reg32, Reg8> for Asm {
+    fn sar(&mut self, op1: Reg32, op2: Reg8) {
+        self.insn("sar", |asm| asm.encode_r_cl(0xd3, 7, op1, op2));
+    }
+}
+
+impl Sar<Reg16, Reg8> for Asm {
+    fn sar(&mut self, op1: Reg16, op2: Reg8) {
+        self.insn("sar", |asm| asm.encode_r_cl(0xd3, 7, op1, op2));
+    }
+}
+
+impl Sar<Reg8, Reg8> for Asm {
+    fn sar(&mut self, op1: Reg8, op2: Reg8) {
+        self.insn("sar", |asm| asm.encode_r_cl(0xd2, 7, op1, op2));
+    }
+}
+
+// -- SAR : mem cl
+
+impl Sar<Mem64, Reg8> for Asm {
+    fn sar(&mut self, op1: Mem64, op2: Reg8) {
+        self.insn("sar", |asm| asm.encode_m_cl(0xd3, 7, op1, op2));
+    }
+}
+
+impl Sar<Mem32, Reg8> for Asm {
+    fn sar(&mut self, op1: Mem32, op2: Reg8) {
+        self.insn("sar", |asm| asm.encode_m_cl(0xd3, 7, op1, op2));
+    }
+}
+
+impl Sar<Mem16, Reg8> for Asm {
+    fn sar(&mut self, op1: Mem16, op2: Reg8) {
+        self.insn("sar", |asm| asm.encode_m_cl(0xd3, 7, op1, op2));
+    }
+}
+
+impl Sar<Mem8, Reg8> for Asm {
+    fn sar(&mut self, op1: Mem8, op2: Reg8) {
+        self.insn("sar", |asm| asm.encode_m_cl(0xd2, 7, op1, op2));
+    }
+}
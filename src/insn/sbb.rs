@@ -0,0 +1,168 @@
+use super::Sbb;
+use crate::{Asm, Imm16, Imm32, Imm8, Mem16, Mem32, Mem64, Mem8, Reg16, Reg32, Reg64, Reg8};
+
+// -- SBB : reg reg
+
+impl Sbb<Reg64, Reg64> for Asm {
+    fn sbb(&mut self, op1: Reg64, op2: Reg64) {
+        self.insn("sbb", |asm| asm.encode_rr(&[0x19], op1, op2));
+    }
+}
+
+impl Sbb<Reg32, Reg32> for Asm {
+    fn sbb(&mut self, op1: Reg32, op2: Reg32) {
+        self.insn("sbb", |asm| asm.encode_rr(&[0x19], op1, op2));
+    }
+}
+
+impl Sbb<Reg16, Reg16> for Asm {
+    fn sbb(&mut self, op1: Reg16, op2: Reg16) {
+        self.insn("sbb", |asm| asm.encode_rr(&[0x19], op1, op2));
+    }
+}
+
+impl Sbb<Reg8, Reg8> for Asm {
+    fn sbb(&mut self, op1: Reg8, op2: Reg8) {
+        self.insn("sbb", |asm| asm.encode_rr(&[0x18], op1, op2));
+    }
+}
+
+// -- SBB : reg imm
+
+impl Sbb<Reg64, Imm32> for Asm {
+    fn sbb(&mut self, op1: Reg64, op2: Imm32) {
+        self.insn("sbb", |asm| asm.encode_ri(0x81, 3, op1, op2));
+    }
+}
+
+impl Sbb<Reg32, Imm32> for Asm {
+    fn sbb(&mut self, op1: Reg32, op2: Imm32) {
+        self.insn("sbb", |asm| asm.encode_ri(0x81, 3, op1, op2));
+    }
+}
+
+impl Sbb<Reg16, Imm16> for Asm {
+    fn sbb(&mut self, op1: Reg16, op2: Imm16) {
+        self.insn("sbb", |asm| asm.encode_ri(0x81, 3, op1, op2));
+    }
+}
+
+impl Sbb<Reg8, Imm8> for Asm {
+    fn sbb(&mut self, op1: Reg8, op2: Imm8) {
+        self.insn("sbb", |asm| asm.encode_ri(0x80, 3, op1, op2));
+    }
+}
+
+impl Sbb<Reg64, Imm8> for Asm {
+    fn sbb(&mut self, op1: Reg64, op2: Imm8) {
+        self.insn("sbb", |asm| asm.encode_ri(0x83, 3, op1, op2));
+    }
+}
+
+impl Sbb<Reg32, Imm8> for Asm {
+    fn sbb(&mut self, op1: Reg32, op2: Imm8) {
+        self.insn("sbb", |asm| asm.encode_ri(0x83, 3, op1, op2));
+    }
+}
+
+impl Sbb<Reg16, Imm8> for Asm {
+    fn sbb(&mut self, op1: Reg16, op2: Imm8) {
+        self.insn("sbb", |asm| asm.encode_ri(0x83, 3, op1, op2));
+    }
+}
+
+// -- SBB : mem imm
+
+impl Sbb<Mem64, Imm32> for Asm {
+    fn sbb(&mut self, op1: Mem64, op2: Imm32) {
+        self.insn("sbb", |asm| asm.encode_mi(0x81, 3, op1, op2));
+    }
+}
+
+impl Sbb<Mem32, Imm32> for Asm {
+    fn sbb(&mut self, op1: Mem32, op2: Imm32) {
+        self.insn("sbb", |asm| asm.encode_mi(0x81, 3, op1, op2));
+    }
+}
+
+impl Sbb<Mem16, Imm16> for Asm {
+    fn sbb(&mut self, op1: Mem16, op2: Imm16) {
+        self.insn("sbb", |asm| asm.encode_mi(0x81, 3, op1, op2));
+    }
+}
+
+impl Sbb<Mem8, Imm8> for Asm {
+    fn sbb(&mut self, op1: Mem8, op2: Imm8) {
+        self.insn("sbb", |asm| asm.encode_mi(0x80, 3, op1, op2));
+    }
+}
+
+impl Sbb<Mem64, Imm8> for Asm {
+    fn sbb(&mut self, op1: Mem64, op2: Imm8) {
+        self.insn("sbb", |asm| asm.encode_mi(0x83, 3, op1, op2));
+    }
+}
+
+impl Sbb<Mem32, Imm8> for Asm {
+    fn sbb(&mut self, op1: Mem32, op2: Imm8) {
+        self.insn("sbb", |asm| asm.encode_mi(0x83, 3, op1, op2));
+    }
+}
+
+impl Sbb<Mem16, Imm8> for Asm {
+    fn sbb(&mut self, op1: Mem16, op2: Imm8) {
+        self.insn("sbb", |asm| asm.encode_mi(0x83, 3, op1, op2));
+    }
+}
+
+// -- SBB : reg mem
+
+impl Sbb<Reg64, Mem64> for Asm {
+    fn sbb(&mut self, op1: Reg64, op2: Mem64) {
+        self.insn("sbb", |asm| asm.encode_rm(&[0x1b], op1, op2));
+    }
+}
+
+impl Sbb<Reg32, Mem32> for Asm {
+    fn sbb(&mut self, op1: Reg32, op2: Mem32) {
+        self.insn("sbb", |asm| asm.encode_rm(&[0x1b], op1, op2));
+    }
+}
+
+impl Sbb<Reg16, Mem16> for Asm {
+    fn sbb(&mut self, op1: Reg16, op2: Mem16) {
+        self.insn("sbb", |asm| asm.encode_rm(&[0x1b], op1, op2));
+    }
+}
+
+impl Sbb<Reg8, Mem8> for Asm {
+    fn sbb(&mut self, op1: Reg8, op2: Mem8) {
+        self.insn("sbb", |asm| asm.encode_rm(&[0x1a], op1, op2));
+    }
+}
+
+// -- SBB : mem reg
+
+impl Sbb<Mem64, Reg64> for Asm {
+    fn sbb(&mut self, op1: Mem64, op2: Reg64) {
+        self.insn("sbb", |asm| asm.encode_mr(&[0x19], op1, op2));
+    }
+}
+
+impl Sbb<Mem32, Reg32> for Asm {
+    fn sbb(&mut self, op1: Mem32, op2: Reg32) {
+        self.insn("sbb", |asm| asm.encode_mr(&[0x19], op1, op2));
+    }
+}
+
+impl Sbb<Mem16, Reg16> for Asm {
+    fn sbb(&mut self, op1: Mem16, op2: Reg16) {
+        self.insn("sbb", |asm| asm.encode_mr(&[0x19], op1, op2));
+    }
+}
+
+impl Sbb<Mem8, Reg8> for Asm {
+    fn sbb(&mut self, op1: Mem8, op2: Reg8) {
+        self.insn("sbb", |asm| asm.encode_mr(&[0x18], op1, op2));
+    }
+}
@@ -0,0 +1,12 @@
+use crate::Asm;
+
+impl Asm {
+    /// Emit a [`repne`](https://www.felixcloutier.com/x86/rep:repe:repz:repne:repnz)
+    /// [`scasb`](https://www.felixcloutier.com/x86/scas:scasb:scasw:scasd) instruction, scanning
+    /// at most `rcx` bytes at `[rdi]` for `al`.
+    ///
+    /// After a match `rdi` points past the matching byte and `ZF` is set.
+    pub fn repne_scasb(&mut self) {
+        self.insn("repne scasb", |asm| asm.encode_zo(&[0xf2, 0xae]));
+    }
+}
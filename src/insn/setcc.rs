@@ -0,0 +1,23 @@
+use super::Setcc;
+use crate::{Asm, Cond, Mem8, Reg8};
+
+const MNEMONICS: [&str; 16] = [
+    "seto", "setno", "setb", "setae", "sete", "setne", "setbe", "seta", "sets", "setns", "setp",
+    "setnp", "setl", "setge", "setle", "setg",
+];
+
+impl Setcc<Reg8> for Asm {
+    fn setcc(&mut self, cond: Cond, op1: Reg8) {
+        self.insn(MNEMONICS[usize::from(cond.code())], |asm| {
+            asm.encode_r(&[0x0f, 0x90 + cond.code()], 0, op1)
+        });
+    }
+}
+
+impl Setcc<Mem8> for Asm {
+    fn setcc(&mut self, cond: Cond, op1: Mem8) {
+        self.insn(MNEMONICS[usize::from(cond.code())], |asm| {
+            asm.encode_m(&[0x0f, 0x90 + cond.code()], 0, op1)
+        });
+    }
+}
@@ -0,0 +1,9 @@
+use crate::Asm;
+
+impl Asm {
+    /// Emit a [`sfence`](https://www.felixcloutier.com/x86/sfence) instruction, serializing all
+    /// prior stores.
+    pub fn sfence(&mut self) {
+        self.insn("sfence", |asm| asm.encode_zo(&[0x0f, 0xae, 0xf8]));
+    }
+}
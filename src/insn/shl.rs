@@ -0,0 +1,106 @@
+use super::Shl;
+use crate::{Asm, Imm8, Mem16, Mem32, Mem64, Mem8, Reg16, Reg32, Reg64, Reg8};
+
+// -- SHL : reg imm
+
+impl Shl<Reg64, Imm8> for Asm {
+    fn shl(&mut self, op1: Reg64, op2: Imm8) {
+        self.insn("shl", |asm| asm.encode_ri(0xc1, 4, op1, op2));
+    }
+}
+
+impl Shl<Reg32, Imm8> for Asm {
+    fn shl(&mut self, op1: Reg32, op2: Imm8) {
+        self.insn("shl", |asm| asm.encode_ri(0xc1, 4, op1, op2));
+    }
+}
+
+impl Shl<Reg16, Imm8> for Asm {
+    fn shl(&mut self, op1: Reg16, op2: Imm8) {
+        self.insn("shl", |asm| asm.encode_ri(0xc1, 4, op1, op2));
+    }
+}
+
+impl Shl<Reg8, Imm8> for Asm {
+    fn shl(&mut self, op1: Reg8, op2: Imm8) {
+        self.insn("shl", |asm| asm.encode_ri(0xc0, 4, op1, op2));
+    }
+}
+
+// -- SHL : mem imm
+
+impl Shl<Mem64, Imm8> for Asm {
+    fn shl(&mut self, op1: Mem64, op2: Imm8) {
+        self.insn("shl", |asm| asm.encode_mi(0xc1, 4, op1, op2));
+    }
+}
+
+impl Shl<Mem32, Imm8> for Asm {
+    fn shl(&mut self, op1: Mem32, op2: Imm8) {
+        self.insn("shl", |asm| asm.encode_mi(0xc1, 4, op1, op2));
+    }
+}
+
+impl Shl<Mem16, Imm8> for Asm {
+    fn shl(&mut self, op1: Mem16, op2: Imm8) {
+        self.insn("shl", |asm| asm.encode_mi(0xc1, 4, op1, op2));
+    }
+}
+
+impl Shl<Mem8, Imm8> for Asm {
+    fn shl(&mut self, op1: Mem8, op2: Imm8) {
+        self.insn("shl", |asm| asm.encode_mi(0xc0, 4, op1, op2));
+    }
+}
+
+// -- SHL : reg cl
+
+impl Shl<Reg64, Reg8> for Asm {
+    fn shl(&mut self, op1: Reg64, op2: Reg8) {
+        self.insn("shl", |asm| asm.encode_r_cl(0xd3, 4, op1, op2));
+    }
+}
+
+impl Shl<Reg32, Reg8> for Asm {
+    fn shl(&mut self, op1: Reg32, op2: Reg8) {
+        self.insn("shl", |asm| asm.encode_r_cl(0xd3, 4, op1, op2));
+    }
+}
+
+impl Shl<Reg16, Reg8> for Asm {
+    fn shl(&mut self, op1: Reg16, op2: Reg8) {
+        self.insn("shl", |asm| asm.encode_r_cl(0xd3, 4, op1, op2));
+    }
+}
+
+impl Shl<Reg8, Reg8> for Asm {
+    fn shl(&mut self, op1: Reg8, op2: Reg8) {
+        self.insn("shl", |asm| asm.encode_r_cl(0xd2, 4, op1, op2));
+    }
+}
+
+// -- SHL : mem cl
+
+impl Shl<Mem64, Reg8> for Asm {
+    fn shl(&mut self, op1: Mem64, op2: Reg8) {
+        self.insn("shl", |asm| asm.encode_m_cl(0xd3, 4, op1, op2));
+    }
+}
+
+impl Shl<Mem32, Reg8> for Asm {
+    fn shl(&mut self, op1: Mem32, op2: Reg8) {
+        self.insn("shl", |asm| asm.encode_m_cl(0xd3, 4, op1, op2));
+    }
+}
+
+impl Shl<Mem16, Reg8> for Asm {
+    fn shl(&mut self, op1: Mem16, op2: Reg8) {
+        self.insn("shl", |asm| asm.encode_m_cl(0xd3, 4, op1, op2));
+    }
+}
+
+impl Shl<Mem8, Reg8> for Asm {
+    fn shl(&mut self, op1: Mem8, op2: Reg8) {
+        self.insn("shl", |asm| asm.encode_m_cl(0xd2, 4, op1, op2));
+    }
+}
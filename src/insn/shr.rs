@@ -0,0 +1,106 @@
+use super::Shr;
+use crate::{Asm, Imm8, Mem16, Mem32, Mem64, Mem8, Reg16, Reg32, Reg64, Reg8};
+
+// -- SHR : reg imm
+
+impl Shr<Reg64, Imm8> for Asm {
+    fn shr(&mut self, op1: Reg64, op2: Imm8) {
+        self.insn("shr", |asm| asm.encode_ri(0xc1, 5, op1, op2));
+    }
+}
+
+impl Shr<Reg32, Imm8> for Asm {
+    fn shr(&mut self, op1: Reg32, op2: Imm8) {
+        self.insn("shr", |asm| asm.encode_ri(0xc1, 5, op1, op2));
+    }
+}
+
+impl Shr<Reg16, Imm8> for Asm {
+    fn shr(&mut self, op1: Reg16, op2: Imm8) {
+        self.insn("shr", |asm| asm.encode_ri(0xc1, 5, op1, op2));
+    }
+}
+
+impl Shr<Reg8, Imm8> for Asm {
+    fn shr(&mut self, op1: Reg8, op2: Imm8) {
+        self.insn("shr", |asm| asm.encode_ri(0xc0, 5, op1, op2));
+    }
+}
+
+// -- SHR : mem imm
+
+impl Shr<Mem64, Imm8> for Asm {
+    fn shr(&mut self, op1: Mem64, op2: Imm8) {
+        self.insn("shr", |asm| asm.encode_mi(0xc1, 5, op1, op2));
+    }
+}
+
+impl Shr<Mem32, Imm8> for Asm {
+    fn shr(&mut self, op1: Mem32, op2: Imm8) {
+        self.insn("shr", |asm| asm.encode_mi(0xc1, 5, op1, op2));
+    }
+}
+
+impl Shr<Mem16, Imm8> for Asm {
+    fn shr(&mut self, op1: Mem16, op2: Imm8) {
+        self.insn("shr", |asm| asm.encode_mi(0xc1, 5, op1, op2));
+    }
+}
+
+impl Shr<Mem8, Imm8> for Asm {
+    fn shr(&mut self, op1: Mem8, op2: Imm8) {
+        self.insn("shr", |asm| asm.encode_mi(0xc0, 5, op1, op2));
+    }
+}
+
+// -- SHR : reg cl
+
+impl Shr<Reg64, Reg8> for Asm {
+    fn shr(&mut self, op1: Reg64, op2: Reg8) {
+        self.insn("shr", |asm| asm.encode_r_cl(0xd3, 5, op1, op2));
+    }
+}
+
+impl Shr<Reg32, Reg8> for Asm {
+    fn shr(&mut self, op1: Reg32, op2: Reg8) {
+        self.insn("shr", |asm| asm.encode_r_cl(0xd3, 5, op1, op2));
+    }
+}
+
+impl Shr<Reg16, Reg8> for Asm {
+    fn shr(&mut self, op1: Reg16, op2: Reg8) {
+        self.insn("shr", |asm| asm.encode_r_cl(0xd3, 5, op1, op2));
+    }
+}
+
+impl Shr<Reg8, Reg8> for Asm {
+    fn shr(&mut self, op1: Reg8, op2: Reg8) {
+        self.insn("shr", |asm| asm.encode_r_cl(0xd2, 5, op1, op2));
+    }
+}
+
+// -- SHR : mem cl
+
+impl Shr<Mem64, Reg8> for Asm {
+    fn shr(&mut self, op1: Mem64, op2: Reg8) {
+        self.insn("shr", |asm| asm.encode_m_cl(0xd3, 5, op1, op2));
+    }
+}
+
+impl Shr<Mem32, Reg8> for Asm {
+    fn shr(&mut self, op1: Mem32, op2: Reg8) {
+        self.insn("shr", |asm| asm.encode_m_cl(0xd3, 5, op1, op2));
+    }
+}
+
+impl Shr<Mem16, Reg8> for Asm {
+    fn shr(&mut self, op1: Mem16, op2: Reg8) {
+        self.insn("shr", |asm| asm.encode_m_cl(0xd3, 5, op1, op2));
+    }
+}
+
+impl Shr<Mem8, Reg8> for Asm {
+    fn shr(&mut self, op1: Mem8, op2: Reg8) {
+        self.insn("shr", |asm| asm.encode_m_cl(0xd2, 5, op1, op2));
+    }
+}
@@ -0,0 +1,17 @@
+use crate::Asm;
+
+impl Asm {
+    /// Emit a [`rep`](https://www.felixcloutier.com/x86/rep:repe:repz:repne:repnz)
+    /// [`stosb`](https://www.felixcloutier.com/x86/stos:stosb:stosw:stosd:stosq) instruction,
+    /// storing `al` to `rcx` bytes at `[rdi]`.
+    pub fn rep_stosb(&mut self) {
+        self.insn("rep stosb", |asm| asm.encode_zo(&[0xf3, 0xaa]));
+    }
+
+    /// Emit a [`rep`](https://www.felixcloutier.com/x86/rep:repe:repz:repne:repnz)
+    /// [`stosq`](https://www.felixcloutier.com/x86/stos:stosb:stosw:stosd:stosq) instruction,
+    /// storing `rax` to `rcx` quadwords at `[rdi]`.
+    pub fn rep_stosq(&mut self) {
+        self.insn("rep stosq", |asm| asm.encode_zo(&[0xf3, 0x48, 0xab]));
+    }
+}
@@ -1,14 +1,168 @@
 use super::Sub;
-use crate::{Asm, Imm8, Mem8, Reg64};
+use crate::{Asm, Imm16, Imm32, Imm8, Mem16, Mem32, Mem64, Mem8, Reg16, Reg32, Reg64, Reg8};
+
+// -- SUB : reg reg
 
 impl Sub<Reg64, Reg64> for Asm {
     fn sub(&mut self, op1: Reg64, op2: Reg64) {
-        self.encode_rr(&[0x29], op1, op2);
+        self.insn("sub", |asm| asm.encode_rr(&[0x29], op1, op2));
+    }
+}
+
+impl Sub<Reg32, Reg32> for Asm {
+    fn sub(&mut self, op1: Reg32, op2: Reg32) {
+        self.insn("sub", |asm| asm.encode_rr(&[0x29], op1, op2));
+    }
+}
+
+impl Sub<Reg16, Reg16> for Asm {
+    fn sub(&mut self, op1: Reg16, op2: Reg16) {
+        self.insn("sub", |asm| asm.encode_rr(&[0x29], op1, op2));
+    }
+}
+
+impl Sub<Reg8, Reg8> for Asm {
+    fn sub(&mut self, op1: Reg8, op2: Reg8) {
+        self.insn("sub", |asm| asm.encode_rr(&[0x28], op1, op2));
+    }
+}
+
+// -- SUB : reg imm
+
+impl Sub<Reg64, Imm8> for Asm {
+    fn sub(&mut self, op1: Reg64, op2: Imm8) {
+        self.insn("sub", |asm| asm.encode_ri(0x83, 5, op1, op2));
+    }
+}
+
+impl Sub<Reg32, Imm8> for Asm {
+    fn sub(&mut self, op1: Reg32, op2: Imm8) {
+        self.insn("sub", |asm| asm.encode_ri(0x83, 5, op1, op2));
+    }
+}
+
+impl Sub<Reg16, Imm8> for Asm {
+    fn sub(&mut self, op1: Reg16, op2: Imm8) {
+        self.insn("sub", |asm| asm.encode_ri(0x83, 5, op1, op2));
+    }
+}
+
+impl Sub<Reg8, Imm8> for Asm {
+    fn sub(&mut self, op1: Reg8, op2: Imm8) {
+        self.insn("sub", |asm| asm.encode_ri(0x80, 5, op1, op2));
+    }
+}
+
+impl Sub<Reg64, Imm32> for Asm {
+    fn sub(&mut self, op1: Reg64, op2: Imm32) {
+        self.insn("sub", |asm| asm.encode_ri(0x81, 5, op1, op2));
+    }
+}
+
+impl Sub<Reg32, Imm32> for Asm {
+    fn sub(&mut self, op1: Reg32, op2: Imm32) {
+        self.insn("sub", |asm| asm.encode_ri(0x81, 5, op1, op2));
+    }
+}
+
+impl Sub<Reg16, Imm16> for Asm {
+    fn sub(&mut self, op1: Reg16, op2: Imm16) {
+        self.insn("sub", |asm| asm.encode_ri(0x81, 5, op1, op2));
+    }
+}
+
+// -- SUB : reg mem
+
+impl Sub<Reg64, Mem64> for Asm {
+    fn sub(&mut self, op1: Reg64, op2: Mem64) {
+        self.insn("sub", |asm| asm.encode_rm(&[0x2b], op1, op2));
+    }
+}
+
+impl Sub<Reg32, Mem32> for Asm {
+    fn sub(&mut self, op1: Reg32, op2: Mem32) {
+        self.insn("sub", |asm| asm.encode_rm(&[0x2b], op1, op2));
+    }
+}
+
+impl Sub<Reg16, Mem16> for Asm {
+    fn sub(&mut self, op1: Reg16, op2: Mem16) {
+        self.insn("sub", |asm| asm.encode_rm(&[0x2b], op1, op2));
+    }
+}
+
+impl Sub<Reg8, Mem8> for Asm {
+    fn sub(&mut self, op1: Reg8, op2: Mem8) {
+        self.insn("sub", |asm| asm.encode_rm(&[0x2a], op1, op2));
+    }
+}
+
+// -- SUB : mem reg
+
+impl Sub<Mem64, Reg64> for Asm {
+    fn sub(&mut self, op1: Mem64, op2: Reg64) {
+        self.insn("sub", |asm| asm.encode_mr(&[0x29], op1, op2));
+    }
+}
+
+impl Sub<Mem32, Reg32> for Asm {
+    fn sub(&mut self, op1: Mem32, op2: Reg32) {
+        self.insn("sub", |asm| asm.encode_mr(&[0x29], op1, op2));
+    }
+}
+
+impl Sub<Mem16, Reg16> for Asm {
+    fn sub(&mut self, op1: Mem16, op2: Reg16) {
+        self.insn("sub", |asm| asm.encode_mr(&[0x29], op1, op2));
+    }
+}
+
+impl Sub<Mem8, Reg8> for Asm {
+    fn sub(&mut self, op1: Mem8, op2: Reg8) {
+        self.insn("sub", |asm| asm.encode_mr(&[0x28], op1, op2));
+    }
+}
+
+// -- SUB : mem imm
+
+impl Sub<Mem64, Imm8> for Asm {
+    fn sub(&mut self, op1: Mem64, op2: Imm8) {
+        self.insn("sub", |asm| asm.encode_mi(0x83, 5, op1, op2));
+    }
+}
+
+impl Sub<Mem32, Imm8> for Asm {
+    fn sub(&mut self, op1: Mem32, op2: Imm8) {
+        self.insn("sub", |asm| asm.encode_mi(0x83, 5, op1, op2));
+    }
+}
+
+impl Sub<Mem16, Imm8> for Asm {
+    fn sub(&mut self, op1: Mem16, op2: Imm8) {
+        self.insn("sub", |asm| asm.encode_mi(0x83, 5, op1, op2));
     }
 }
 
 impl Sub<Mem8, Imm8> for Asm {
     fn sub(&mut self, op1: Mem8, op2: Imm8) {
-        self.encode_mi(0x80, 5, op1, op2);
+        self.insn("sub", |asm| asm.encode_mi(0x80, 5, op1, op2));
+    }
+}
+
+impl Sub<Mem64, Imm32> for Asm {
+    fn sub(&mut self, op1: Mem64, op2: Imm32) {
+        self.insn("sub", |asm| asm.encode_mi(0x81, 5, op1, op2));
+    }
+}
+
+impl Sub<Mem32, Imm32> for Asm {
+    fn sub(&mut self, op1: Mem32, op2: Imm32) {
+        self.insn("sub", |asm| asm.encode_mi(0x81, 5, op1, op2));
+    }
+}
+
+impl Sub<Mem16, Imm16> for Asm {
+    fn sub(&mut self, op1: Mem16, op2: Imm16) {
+        self.insn("sub", |asm| asm.encode_mi(0x81, 5, op1, op2));
     }
 }
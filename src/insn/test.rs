@@ -1,20 +1,106 @@
 use super::Test;
-use crate::{Asm, Imm16, Mem16, Reg32, Reg64};
+use crate::{Asm, Imm16, Imm32, Imm8, Mem16, Mem32, Mem64, Mem8, Reg16, Reg32, Reg64, Reg8};
+
+// -- TEST : reg reg
 
 impl Test<Reg64, Reg64> for Asm {
     fn test(&mut self, op1: Reg64, op2: Reg64) {
-        self.encode_rr(&[0x85], op1, op2);
+        self.insn("test", |asm| asm.encode_rr(&[0x85], op1, op2));
     }
 }
 
 impl Test<Reg32, Reg32> for Asm {
     fn test(&mut self, op1: Reg32, op2: Reg32) {
-        self.encode_rr(&[0x85], op1, op2);
+        self.insn("test", |asm| asm.encode_rr(&[0x85], op1, op2));
+    }
+}
+
+impl Test<Reg16, Reg16> for Asm {
+    fn test(&mut self, op1: Reg16, op2: Reg16) {
+        self.insn("test", |asm| asm.encode_rr(&[0x85], op1, op2));
+    }
+}
+
+impl Test<Reg8, Reg8> for Asm {
+    fn test(&mut self, op1: Reg8, op2: Reg8) {
+        self.insn("test", |asm| asm.encode_rr(&[0x84], op1, op2));
+    }
+}
+
+// -- TEST : reg imm
+
+impl Test<Reg64, Imm32> for Asm {
+    fn test(&mut self, op1: Reg64, op2: Imm32) {
+        self.insn("test", |asm| asm.encode_ri(0xf7, 0, op1, op2));
+    }
+}
+
+impl Test<Reg32, Imm32> for Asm {
+    fn test(&mut self, op1: Reg32, op2: Imm32) {
+        self.insn("test", |asm| asm.encode_ri(0xf7, 0, op1, op2));
+    }
+}
+
+impl Test<Reg16, Imm16> for Asm {
+    fn test(&mut self, op1: Reg16, op2: Imm16) {
+        self.insn("test", |asm| asm.encode_ri(0xf7, 0, op1, op2));
+    }
+}
+
+impl Test<Reg8, Imm8> for Asm {
+    fn test(&mut self, op1: Reg8, op2: Imm8) {
+        self.insn("test", |asm| asm.encode_ri(0xf6, 0, op1, op2));
+    }
+}
+
+// -- TEST : mem imm
+
+impl Test<Mem64, Imm32> for Asm {
+    fn test(&mut self, op1: Mem64, op2: Imm32) {
+        self.insn("test", |asm| asm.encode_mi(0xf7, 0, op1, op2));
+    }
+}
+
+impl Test<Mem32, Imm32> for Asm {
+    fn test(&mut self, op1: Mem32, op2: Imm32) {
+        self.insn("test", |asm| asm.encode_mi(0xf7, 0, op1, op2));
     }
 }
 
 impl Test<Mem16, Imm16> for Asm {
     fn test(&mut self, op1: Mem16, op2: Imm16) {
-        self.encode_mi(0xf7, 0, op1, op2);
+        self.insn("test", |asm| asm.encode_mi(0xf7, 0, op1, op2));
+    }
+}
+
+impl Test<Mem8, Imm8> for Asm {
+    fn test(&mut self, op1: Mem8, op2: Imm8) {
+        self.insn("test", |asm| asm.encode_mi(0xf6, 0, op1, op2));
+    }
+}
+
+// -- TEST : mem reg
+
+impl Test<Mem64, Reg64> for Asm {
+    fn test(&mut self, op1: Mem64, op2: Reg64) {
+        self.insn("test", |asm| asm.encode_mr(&[0x85], op1, op2));
+    }
+}
+
+impl Test<Mem32, Reg32> for Asm {
+    fn test(&mut self, op1: Mem32, op2: Reg32) {
+        self.insn("test", |asm| asm.encode_mr(&[0x85], op1, op2));
+    }
+}
+
+impl Test<Mem16, Reg16> for Asm {
+    fn test(&mut self, op1: Mem16, op2: Reg16) {
+        self.insn("test", |asm| asm.encode_mr(&[0x85], op1, op2));
+    }
+}
+
+impl Test<Mem8, Reg8> for Asm {
+    fn test(&mut self, op1: Mem8, op2: Reg8) {
+        self.insn("test", |asm| asm.encode_mr(&[0x84], op1, op2));
     }
 }
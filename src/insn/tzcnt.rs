@@ -0,0 +1,74 @@
+use super::Tzcnt;
+use crate::{Asm, Mem16, Mem32, Mem64, Reg16, Reg32, Reg64};
+
+impl Asm {
+    /// Check that `tzcnt` may be emitted for the configured CPU features.
+    fn check_tzcnt(&self) {
+        assert!(
+            self.cpu_features().bmi1,
+            "Instruction tzcnt not enabled in the CPU features"
+        );
+    }
+}
+
+// The mandatory F3 prefix is emitted before the operand size prefix and the REX prefix.
+
+impl Tzcnt<Reg64, Reg64> for Asm {
+    fn tzcnt(&mut self, op1: Reg64, op2: Reg64) {
+        self.check_tzcnt();
+        self.insn("tzcnt", |asm| {
+            asm.emit(&[0xf3]);
+            asm.encode_rr(&[0x0f, 0xbc], op2, op1);
+        });
+    }
+}
+
+impl Tzcnt<Reg32, Reg32> for Asm {
+    fn tzcnt(&mut self, op1: Reg32, op2: Reg32) {
+        self.check_tzcnt();
+        self.insn("tzcnt", |asm| {
+            asm.emit(&[0xf3]);
+            asm.encode_rr(&[0x0f, 0xbc], op2, op1);
+        });
+    }
+}
+
+impl Tzcnt<Reg16, Reg16> for Asm {
+    fn tzcnt(&mut self, op1: Reg16, op2: Reg16) {
+        self.check_tzcnt();
+        self.insn("tzcnt", |asm| {
+            asm.emit(&[0xf3]);
+            asm.encode_rr(&[0x0f, 0xbc], op2, op1);
+        });
+    }
+}
+
+impl Tzcnt<Reg64, Mem64> for Asm {
+    fn tzcnt(&mut self, op1: Reg64, op2: Mem64) {
+        self.check_tzcnt();
+        self.insn("tzcnt", |asm| {
+            asm.emit(&[0xf3]);
+            asm.encode_rm(&[0x0f, 0xbc], op1, op2);
+        });
+    }
+}
+
+impl Tzcnt<Reg32, Mem32> for Asm {
+    fn tzcnt(&mut self, op1: Reg32, op2: Mem32) {
+        self.check_tzcnt();
+        self.insn("tzcnt", |asm| {
+            asm.emit(&[0xf3]);
+            asm.encode_rm(&[0x0f, 0xbc], op1, op2);
+        });
+    }
+}
+
+impl Tzcnt<Reg16, Mem16> for Asm {
+    fn tzcnt(&mut self, op1: Reg16, op2: Mem16) {
+        self.check_tzcnt();
+        self.insn("tzcnt", |asm| {
+            asm.emit(&[0xf3]);
+            asm.encode_rm(&[0x0f, 0xbc], op1, op2);
+        });
+    }
+}
@@ -0,0 +1,9 @@
+use crate::Asm;
+
+impl Asm {
+    /// Emit an [`ud2`](https://www.felixcloutier.com/x86/ud) instruction, raising an invalid
+    /// opcode exception.
+    pub fn ud2(&mut self) {
+        self.insn("ud2", |asm| asm.encode_zo(&[0x0f, 0x0b]));
+    }
+}
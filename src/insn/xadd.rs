@@ -0,0 +1,40 @@
+use super::Xadd;
+use crate::{Asm, Mem16, Mem32, Mem64, Mem8, Reg16, Reg32, Reg64, Reg8};
+
+// -- XADD : mem reg
+
+impl Xadd<Mem64, Reg64> for Asm {
+    fn xadd(&mut self, op1: Mem64, op2: Reg64) {
+        self.insn("xadd", |asm| asm.encode_mr(&[0x0f, 0xc1], op1, op2));
+    }
+}
+
+impl Xadd<Mem32, Reg32> for Asm {
+    fn xadd(&mut self, op1: Mem32, op2: Reg32) {
+        self.insn("xadd", |asm| asm.encode_mr(&[0x0f, 0xc1], op1, op2));
+    }
+}
+
+impl Xadd<Mem16, Reg16> for Asm {
+    fn xadd(&mut self, op1: Mem16, op2: Reg16) {
+        self.insn("xadd", |asm| asm.encode_mr(&[0x0f, 0xc1], op1, op2));
+    }
+}
+
+impl Xadd<Mem8, Reg8> for Asm {
+    fn xadd(&mut self, op1: Mem8, op2: Reg8) {
+        self.insn("xadd", |asm| asm.encode_mr(&[0x0f, 0xc0], op1, op2));
+    }
+}
+
+impl Asm {
+    /// Emit a [`xadd`](https://www.felixcloutier.com/x86/xadd) instruction with the `lock`
+    /// prefix, atomically adding `op2` to `op1` and returning the previous value of `op1` in
+    /// `op2`, see [`Asm::lock`].
+    pub fn lock_xadd<M, T>(&mut self, op1: M, op2: T)
+    where
+        Self: Xadd<M, T>,
+    {
+        self.lock(|asm| asm.xadd(op1, op2));
+    }
+}
@@ -1,8 +1,14 @@
 use super::Xor;
-use crate::{Asm, Reg64};
+use crate::{Asm, Reg32, Reg64};
 
 impl Xor<Reg64, Reg64> for Asm {
     fn xor(&mut self, op1: Reg64, op2: Reg64) {
-        self.encode_rr(&[0x31], op1, op2);
+        self.insn("xor", |asm| asm.encode_rr(&[0x31], op1, op2));
+    }
+}
+
+impl Xor<Reg32, Reg32> for Asm {
+    fn xor(&mut self, op1: Reg32, op2: Reg32) {
+        self.insn("xor", |asm| asm.encode_rr(&[0x31], op1, op2));
     }
 }
@@ -0,0 +1,248 @@
+//! Helpers for 128 bit integer arithmetic on values held in register pairs.
+
+use crate::insn::{Adc, Add, Imul, Mov, Mul, Sbb, Sub, Xor};
+use crate::{Asm, Reg64};
+
+/// A 128 bit value held in a pair of 64 bit registers.
+///
+/// The SysV ABI passes `__int128` arguments in two consecutive integer argument registers, low
+/// half first, and returns them in `rax:rdx`, see [`RegPair::sysv_arg`] and
+/// [`RegPair::SYSV_RET`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RegPair {
+    /// Register holding the low 64 bits.
+    pub lo: Reg64,
+    /// Register holding the high 64 bits.
+    pub hi: Reg64,
+}
+
+impl RegPair {
+    /// Register pair of a returned `__int128` in the SysV ABI, `lo = rax` and `hi = rdx`.
+    pub const SYSV_RET: RegPair = RegPair::new(Reg64::rax, Reg64::rdx);
+
+    /// Create a register pair from the `lo` and `hi` registers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `lo` and `hi` are the same register.
+    pub const fn new(lo: Reg64, hi: Reg64) -> RegPair {
+        assert!(lo as u8 != hi as u8, "Register pair needs two registers");
+        RegPair { lo, hi }
+    }
+
+    /// Get the register pair of an `__int128` argument in the SysV ABI, starting at the integer
+    /// argument register `slot`. Each preceding integer or pointer argument occupies one slot,
+    /// each preceding `__int128` argument two slots.
+    ///
+    /// ```rust
+    /// use juicebox_asm::{RegPair, Reg64::*};
+    ///
+    /// // fn(a: u128, b: u64, c: u128)
+    /// assert_eq!(RegPair::sysv_arg(0), RegPair::new(rdi, rsi));
+    /// assert_eq!(RegPair::sysv_arg(3), RegPair::new(rcx, r8));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the argument does not fit into the argument registers, in which case it is
+    /// passed on the stack.
+    pub fn sysv_arg(slot: usize) -> RegPair {
+        use Reg64::*;
+        const ARGS: [Reg64; 6] = [rdi, rsi, rdx, rcx, r8, r9];
+        assert!(slot + 1 < ARGS.len(), "Argument passed on the stack");
+        RegPair::new(ARGS[slot], ARGS[slot + 1])
+    }
+
+    /// Check if the pair uses the register `r`.
+    fn uses(&self, r: Reg64) -> bool {
+        self.lo == r || self.hi == r
+    }
+}
+
+impl Asm {
+    /// Emit `dst = src` for 128 bit values, correctly handling overlapping register pairs.
+    pub fn mov128(&mut self, dst: RegPair, src: RegPair) {
+        if dst.lo == src.hi && dst.hi == src.lo {
+            // Swap the halves.
+            self.xor(dst.lo, dst.hi);
+            self.xor(dst.hi, dst.lo);
+            self.xor(dst.lo, dst.hi);
+        } else if dst.lo == src.hi {
+            // Moving the low half first would overwrite the high half of the source.
+            self.mov_if_ne(dst.hi, src.hi);
+            self.mov_if_ne(dst.lo, src.lo);
+        } else {
+            self.mov_if_ne(dst.lo, src.lo);
+            self.mov_if_ne(dst.hi, src.hi);
+        }
+    }
+
+    /// Emit `dst = dst + src` for 128 bit values with wrapping semantics.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `src.hi` is `dst.lo`.
+    pub fn add128(&mut self, dst: RegPair, src: RegPair) {
+        assert_ne!(src.hi, dst.lo, "Source high half overwritten before use");
+        self.add(dst.lo, src.lo);
+        self.adc(dst.hi, src.hi);
+    }
+
+    /// Emit `dst = dst - src` for 128 bit values with wrapping semantics.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `src.hi` is `dst.lo`.
+    pub fn sub128(&mut self, dst: RegPair, src: RegPair) {
+        assert_ne!(src.hi, dst.lo, "Source high half overwritten before use");
+        self.sub(dst.lo, src.lo);
+        self.sbb(dst.hi, src.hi);
+    }
+
+    /// Emit `rdx:rax = rdx:rax * src` for 128 bit values with wrapping semantics, `tmp` is
+    /// clobbered.
+    ///
+    /// The destination is fixed to [`RegPair::SYSV_RET`] by the widening `mul`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `src` uses `rax`, `rdx` or `tmp`, or if `tmp` is `rax` or `rdx`.
+    pub fn mul128(&mut self, src: RegPair, tmp: Reg64) {
+        use Reg64::{rax, rdx};
+        let dst = RegPair::SYSV_RET;
+        assert!(
+            !dst.uses(tmp) && !src.uses(tmp) && !src.uses(rax) && !src.uses(rdx),
+            "Registers of mul128 must not overlap"
+        );
+
+        // lo(a * b) = lo(a.lo * b.lo)
+        // hi(a * b) = hi(a.lo * b.lo) + a.hi * b.lo + a.lo * b.hi
+        self.mov(tmp, dst.hi);
+        self.imul((tmp, src.lo));
+        self.mov(rdx, src.hi);
+        self.imul((rdx, rax));
+        self.add(tmp, rdx);
+        self.mul(src.lo);
+        self.add(rdx, tmp);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Runtime;
+    use Reg64::*;
+
+    const VALUES: [u128; 6] = [
+        0,
+        1,
+        u64::MAX as u128,
+        1 << 64,
+        0x0123_4567_89ab_cdef_fedc_ba98_7654_3210,
+        u128::MAX,
+    ];
+
+    /// Compile `emit` into a `fn(u128, u128) -> u128`, with the arguments in `rdi:rsi` and
+    /// `rdx:rcx` and the result in `rax:rdx`.
+    fn compile(rt: &mut Runtime, emit: impl FnOnce(&mut Asm)) -> extern "C" fn(u128, u128) -> u128 {
+        let mut asm = Asm::new();
+        emit(&mut asm);
+        asm.ret();
+        unsafe { rt.add_code(asm.into_code()) }
+    }
+
+    #[test]
+    fn test_sysv() {
+        let mut rt = Runtime::new();
+        let a = RegPair::sysv_arg(0);
+        let b = RegPair::sysv_arg(2);
+
+        let add = compile(&mut rt, |asm| {
+            asm.add128(a, b);
+            asm.mov128(RegPair::SYSV_RET, a);
+        });
+        let sub = compile(&mut rt, |asm| {
+            asm.sub128(a, b);
+            asm.mov128(RegPair::SYSV_RET, a);
+        });
+        let mul = compile(&mut rt, |asm| {
+            let b2 = RegPair::new(r8, r9);
+            asm.mov128(b2, b);
+            asm.mov128(RegPair::SYSV_RET, a);
+            asm.mul128(b2, r10);
+        });
+
+        for x in VALUES {
+            for y in VALUES {
+                assert_eq!(add(x, y), x.wrapping_add(y), "{:#x} + {:#x}", x, y);
+                assert_eq!(sub(x, y), x.wrapping_sub(y), "{:#x} - {:#x}", x, y);
+                assert_eq!(mul(x, y), x.wrapping_mul(y), "{:#x} * {:#x}", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn test_mov128() {
+        let mut rt = Runtime::new();
+        // Overlapping moves, the argument is in rdi:rsi.
+        for (via, len) in [
+            (RegPair::new(rsi, rax), 6),
+            (RegPair::new(rsi, rdi), 9),
+            (RegPair::new(rdi, rsi), 0),
+        ] {
+            let f = compile(&mut rt, |asm| {
+                let start = asm.len();
+                asm.mov128(via, RegPair::sysv_arg(0));
+                assert_eq!(asm.len() - start, len);
+                asm.mov128(RegPair::SYSV_RET, via);
+            });
+            for x in VALUES {
+                assert_eq!(f(x, 0), x);
+            }
+        }
+    }
+
+    #[test]
+    fn test_alias() {
+        let mut rt = Runtime::new();
+        let dst = RegPair::new(r8, r9);
+        // The low half of the source is the high half of the destination, which is only written
+        // after it was read.
+        let src = RegPair::new(r9, r10);
+
+        let f = |asm: &mut Asm, emit: fn(&mut Asm, RegPair, RegPair)| {
+            asm.mov128(dst, RegPair::sysv_arg(0));
+            asm.mov(r10, rcx);
+            emit(asm, dst, src);
+            asm.mov128(RegPair::SYSV_RET, dst);
+        };
+        let add = compile(&mut rt, |asm| f(asm, Asm::add128));
+        let sub = compile(&mut rt, |asm| f(asm, Asm::sub128));
+
+        for x in VALUES {
+            for y in VALUES {
+                let y = (y >> 64 << 64) | (x >> 64);
+                assert_eq!(add(x, y), x.wrapping_add(y), "{:#x} + {:#x}", x, y);
+                assert_eq!(sub(x, y), x.wrapping_sub(y), "{:#x} - {:#x}", x, y);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Source high half overwritten before use")]
+    fn test_add128_alias() {
+        Asm::new().add128(RegPair::new(r8, r9), RegPair::new(r10, r8));
+    }
+
+    #[test]
+    #[should_panic(expected = "Source high half overwritten before use")]
+    fn test_sub128_alias() {
+        Asm::new().sub128(RegPair::new(r8, r9), RegPair::new(r10, r8));
+    }
+
+    #[test]
+    #[should_panic(expected = "Registers of mul128 must not overlap")]
+    fn test_mul128_overlap() {
+        Asm::new().mul128(RegPair::new(rcx, rdx), r8);
+    }
+}
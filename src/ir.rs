@@ -0,0 +1,422 @@
+//! A tiny IR with a lowering pass to [Asm], for front-ends which want to target something
+//! slightly higher-level than raw instructions.
+//!
+//! A [Function] consists of blocks of instructions operating on 64 bit [Value]s. Each block ends
+//! with a terminator, either a jump, a conditional branch or a return. Values are not in SSA
+//! form, but each instruction defines a new value.
+//!
+//! The lowering assigns each value a stack slot in the function frame, instructions load their
+//! operands into scratch registers and store the result back into the slot of the defined value.
+//! This keeps the lowering trivially correct across calls and control flow, at the cost of
+//! performance. The blocks are laid out with a [BlockAsm].
+//!
+//! ```rust
+//! use juicebox_asm::ir::{BinOp, Cond, Function};
+//! use juicebox_asm::Runtime;
+//!
+//! // fn sum(n: u64) -> u64 { let mut s = 0; while n != 0 { s += n; n -= 1; } s }
+//! let mut f = Function::new(1);
+//! let n = f.arg(0);
+//! let (zero, one) = (f.iconst(0), f.iconst(1));
+//! let s = f.binop(BinOp::Add, zero, zero);
+//!
+//! let head = f.create_block();
+//! let body = f.create_block();
+//! let exit = f.create_block();
+//! f.jump(head);
+//!
+//! f.switch_to(head);
+//! f.branch(Cond::NotZero, n, body, exit);
+//!
+//! f.switch_to(body);
+//! f.assign(s, BinOp::Add, s, n);
+//! f.assign(n, BinOp::Sub, n, one);
+//! f.jump(head);
+//!
+//! f.switch_to(exit);
+//! f.ret(Some(s));
+//!
+//! let mut rt = Runtime::new();
+//! let sum = unsafe { rt.add_code::<extern "C" fn(u64) -> u64>(f.lower().into_code()) };
+//! assert_eq!(sum(10), 55);
+//! ```
+
+use crate::insn::{Add, And, Call, Imul, Mov, Or, Pop, Push, Sub, Test, Xor};
+use crate::{Asm, BlockAsm, BlockId, Imm64, Mem64, Reg64, Terminator};
+
+/// A 64 bit value defined by an instruction or a function argument.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Value(usize);
+
+/// Identifier of a block in a [Function].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Block(usize);
+
+/// Binary operations on two values.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BinOp {
+    /// Wrapping addition.
+    Add,
+    /// Wrapping subtraction.
+    Sub,
+    /// Wrapping multiplication.
+    Mul,
+    /// Bitwise and.
+    And,
+    /// Bitwise or.
+    Or,
+    /// Bitwise xor.
+    Xor,
+}
+
+/// Condition of a conditional branch on a value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Cond {
+    /// Branch if the value is zero.
+    Zero,
+    /// Branch if the value is not zero.
+    NotZero,
+}
+
+/// An instruction, the first [Value] is the value defined by the instruction.
+enum Inst {
+    Const(Value, u64),
+    Load(Value, Value, i32),
+    Store(Value, i32, Value),
+    Bin(BinOp, Value, Value, Value),
+    Call(Value, usize, Vec<Value>),
+}
+
+/// Terminator of a block.
+enum Term {
+    Jump(Block),
+    Branch(Cond, Value, Block, Block),
+    Ret(Option<Value>),
+}
+
+/// A function in the IR, see the [module](self) documentation.
+pub struct Function {
+    blocks: Vec<(Vec<Inst>, Option<Term>)>,
+    nargs: usize,
+    nvalues: usize,
+    cur: Block,
+}
+
+/// Registers used to pass the integer arguments according to the SystemV abi.
+const ARGS: [Reg64; 6] = [
+    Reg64::rdi,
+    Reg64::rsi,
+    Reg64::rdx,
+    Reg64::rcx,
+    Reg64::r8,
+    Reg64::r9,
+];
+
+impl Function {
+    /// Create a function with `nargs` integer arguments. The function has an entry block, which
+    /// is the current block instructions are appended to.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `nargs` exceeds the 6 integer argument registers of the SystemV abi.
+    pub fn new(nargs: usize) -> Function {
+        assert!(nargs <= ARGS.len(), "Too many arguments");
+        Function {
+            blocks: vec![(Vec::new(), None)],
+            nargs,
+            nvalues: nargs,
+            cur: Block(0),
+        }
+    }
+
+    /// Create a new empty block.
+    pub fn create_block(&mut self) -> Block {
+        self.blocks.push((Vec::new(), None));
+        Block(self.blocks.len() - 1)
+    }
+
+    /// Set the block instructions are appended to.
+    pub fn switch_to(&mut self, block: Block) {
+        self.cur = block;
+    }
+
+    /// Get the value of the argument `idx`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `idx` is not a valid argument index.
+    pub fn arg(&self, idx: usize) -> Value {
+        assert!(idx < self.nargs, "Argument index out of bound");
+        Value(idx)
+    }
+
+    /// Define a value holding the constant `imm`.
+    pub fn iconst(&mut self, imm: u64) -> Value {
+        let dst = self.value();
+        self.push(Inst::Const(dst, imm));
+        dst
+    }
+
+    /// Define a value loaded from the address `addr + off`.
+    pub fn load(&mut self, addr: Value, off: i32) -> Value {
+        let dst = self.value();
+        self.push(Inst::Load(dst, addr, off));
+        dst
+    }
+
+    /// Store `val` to the address `addr + off`.
+    pub fn store(&mut self, addr: Value, off: i32, val: Value) {
+        self.push(Inst::Store(addr, off, val));
+    }
+
+    /// Define a value holding the result of `lhs op rhs`.
+    pub fn binop(&mut self, op: BinOp, lhs: Value, rhs: Value) -> Value {
+        let dst = self.value();
+        self.assign(dst, op, lhs, rhs);
+        dst
+    }
+
+    /// Assign the result of `lhs op rhs` to the existing value `dst`, eg to update loop
+    /// variables.
+    pub fn assign(&mut self, dst: Value, op: BinOp, lhs: Value, rhs: Value) {
+        self.push(Inst::Bin(op, dst, lhs, rhs));
+    }
+
+    /// Define a value holding the result of calling the function at address `func` with `args`
+    /// according to the SystemV abi.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of `args` exceeds the 6 integer argument registers.
+    pub fn call(&mut self, func: usize, args: &[Value]) -> Value {
+        assert!(args.len() <= ARGS.len(), "Too many arguments");
+        let dst = self.value();
+        self.push(Inst::Call(dst, func, args.to_vec()));
+        dst
+    }
+
+    /// Terminate the current block with a jump to `target`.
+    pub fn jump(&mut self, target: Block) {
+        self.terminate(Term::Jump(target));
+    }
+
+    /// Terminate the current block with a branch to `then` if `cond` holds for `val`, else to
+    /// `els`.
+    pub fn branch(&mut self, cond: Cond, val: Value, then: Block, els: Block) {
+        self.terminate(Term::Branch(cond, val, then, els));
+    }
+
+    /// Terminate the current block with a return of `val`, if any.
+    pub fn ret(&mut self, val: Option<Value>) {
+        self.terminate(Term::Ret(val));
+    }
+
+    /// Lower the function into an [Asm].
+    ///
+    /// # Panics
+    ///
+    /// Panics if any block is not terminated.
+    pub fn lower(self) -> Asm {
+        use Reg64::*;
+
+        let mut basm = BlockAsm::new();
+        let ids: Vec<BlockId> = self.blocks.iter().map(|_| basm.create_block()).collect();
+
+        // Prologue, setup the frame and spill the arguments to their slots. The frame size is
+        // kept 16 byte aligned, as required by the SystemV abi at calls.
+        let frame = (self.nvalues * 8).next_multiple_of(16);
+        let asm = basm.block(ids[0]);
+        asm.push(rbp);
+        asm.mov(rbp, rsp);
+        asm.mov(rax, Imm64::from(frame));
+        asm.sub(rsp, rax);
+        for (idx, reg) in ARGS.into_iter().enumerate().take(self.nargs) {
+            asm.mov(slot(Value(idx)), reg);
+        }
+
+        for ((insts, term), id) in self.blocks.into_iter().zip(ids.iter().copied()) {
+            let asm = basm.block(id);
+            for inst in insts {
+                lower_inst(asm, inst);
+            }
+
+            let term = term.expect("Block not terminated");
+            let term = match term {
+                Term::Jump(t) => Terminator::Jmp(ids[t.0]),
+                Term::Branch(cond, val, t, e) => {
+                    asm.mov(rax, slot(val));
+                    asm.test(rax, rax);
+                    match cond {
+                        Cond::Zero => Terminator::Jz(ids[t.0], ids[e.0]),
+                        Cond::NotZero => Terminator::Jnz(ids[t.0], ids[e.0]),
+                    }
+                }
+                Term::Ret(val) => {
+                    if let Some(val) = val {
+                        asm.mov(rax, slot(val));
+                    }
+                    asm.mov(rsp, rbp);
+                    asm.pop(rbp);
+                    Terminator::Ret
+                }
+            };
+            basm.terminate(id, term);
+        }
+
+        basm.finalize()
+    }
+
+    /// Allocate a new value.
+    fn value(&mut self) -> Value {
+        self.nvalues += 1;
+        Value(self.nvalues - 1)
+    }
+
+    /// Append `inst` to the current block.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the current block is already terminated.
+    fn push(&mut self, inst: Inst) {
+        let (insts, term) = &mut self.blocks[self.cur.0];
+        assert!(term.is_none(), "Block already terminated");
+        insts.push(inst);
+    }
+
+    /// Terminate the current block with `term`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the current block is already terminated.
+    fn terminate(&mut self, t: Term) {
+        let (_, term) = &mut self.blocks[self.cur.0];
+        assert!(term.is_none(), "Block already terminated");
+        *term = Some(t);
+    }
+}
+
+/// Get the stack slot of `val` in the function frame.
+fn slot(val: Value) -> Mem64 {
+    let off = i32::try_from((val.0 + 1) * 8).expect("Frame too large");
+    Mem64::indirect_disp(Reg64::rbp, -off)
+}
+
+/// Lower a single instruction, using `rax` and `rcx` as scratch registers.
+fn lower_inst(asm: &mut Asm, inst: Inst) {
+    use Reg64::*;
+
+    match inst {
+        Inst::Const(dst, imm) => {
+            asm.mov(rax, Imm64::from(imm));
+            asm.mov(slot(dst), rax);
+        }
+        Inst::Load(dst, addr, off) => {
+            asm.mov(rax, slot(addr));
+            asm.mov(rax, Mem64::indirect_disp(rax, off));
+            asm.mov(slot(dst), rax);
+        }
+        Inst::Store(addr, off, val) => {
+            asm.mov(rax, slot(addr));
+            asm.mov(rcx, slot(val));
+            asm.mov(Mem64::indirect_disp(rax, off), rcx);
+        }
+        Inst::Bin(op, dst, lhs, rhs) => {
+            asm.mov(rax, slot(lhs));
+            asm.mov(rcx, slot(rhs));
+            match op {
+                BinOp::Add => asm.add(rax, rcx),
+                BinOp::Sub => asm.sub(rax, rcx),
+                BinOp::Mul => asm.imul((rax, rcx)),
+                BinOp::And => asm.and(rax, rcx),
+                BinOp::Or => asm.or(rax, rcx),
+                BinOp::Xor => asm.xor(rax, rcx),
+            }
+            asm.mov(slot(dst), rax);
+        }
+        Inst::Call(dst, func, args) => {
+            for (&arg, reg) in args.iter().zip(ARGS) {
+                asm.mov(reg, slot(arg));
+            }
+            asm.mov(rax, Imm64::from(func));
+            asm.call(rax);
+            asm.mov(slot(dst), rax);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Runtime;
+
+    #[test]
+    fn test_binops() {
+        let ops = [
+            (BinOp::Add, 0xf0 + 0x0f),
+            (BinOp::Sub, 0xf0 - 0x0f),
+            (BinOp::Mul, 0xf0 * 0x0f),
+            (BinOp::And, 0xf0 & 0x0f),
+            (BinOp::Or, 0xf0 | 0x0f),
+            (BinOp::Xor, 0xf0 ^ 0x0f),
+        ];
+
+        let mut rt = Runtime::new();
+        for (op, res) in ops {
+            let mut f = Function::new(2);
+            let v = f.binop(op, f.arg(0), f.arg(1));
+            f.ret(Some(v));
+
+            let f = unsafe { rt.add_code::<extern "C" fn(u64, u64) -> u64>(f.lower().into_code()) };
+            assert_eq!(f(0xf0, 0x0f), res, "{:?}", op);
+        }
+    }
+
+    #[test]
+    fn test_load_store_call() {
+        extern "C" fn triple(v: u64) -> u64 {
+            3 * v
+        }
+
+        // fn(p: *mut u64) { p[1] = triple(p[0]) }
+        let mut f = Function::new(1);
+        let p = f.arg(0);
+        let v = f.load(p, 0);
+        let v = f.call(triple as extern "C" fn(u64) -> u64 as usize, &[v]);
+        f.store(p, 8, v);
+        f.ret(None);
+
+        let mut rt = Runtime::new();
+        let f = unsafe { rt.add_code::<extern "C" fn(*mut u64)>(f.lower().into_code()) };
+        let mut mem = [14u64, 0];
+        f(mem.as_mut_ptr());
+        assert_eq!(mem, [14, 42]);
+    }
+
+    #[test]
+    fn test_branch_zero() {
+        // fn(v: u64) -> u64 { if v == 0 { 1 } else { 2 } }
+        let mut f = Function::new(1);
+        let a = f.create_block();
+        let b = f.create_block();
+        f.branch(Cond::Zero, f.arg(0), a, b);
+        f.switch_to(a);
+        let one = f.iconst(1);
+        f.ret(Some(one));
+        f.switch_to(b);
+        let two = f.iconst(2);
+        f.ret(Some(two));
+
+        let mut rt = Runtime::new();
+        let f = unsafe { rt.add_code::<extern "C" fn(u64) -> u64>(f.lower().into_code()) };
+        assert_eq!(f(0), 1);
+        assert_eq!(f(7), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Block already terminated")]
+    fn test_terminated() {
+        let mut f = Function::new(0);
+        f.ret(None);
+        f.iconst(0);
+    }
+}
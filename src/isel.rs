@@ -0,0 +1,908 @@
+//! Instruction selection helpers, emitting efficient instruction sequences for common patterns.
+//!
+//! The helpers are meant as building blocks for front-ends, which otherwise hard-code generic
+//! sequences, eg an `imul` for each multiplication by a constant.
+
+use crate::insn::{Add, Cmov, Imul, Lea, Mov, Movzx, Mul, Pop, Push, Setcc, Shl, Shr, Sub, Xor};
+use crate::{
+    Asm, Cond, Imm32, Imm64, Imm8, Label, Mem16, Mem32, Mem64, Mem8, Reg16, Reg32, Reg64, Reg8,
+};
+
+/// Compute the magic number for the unsigned division by `d` with multiplication, see
+/// [`Asm::udiv_const`].
+///
+/// Returns `(magic, shift, add)`, without the `add` indicator the quotient is
+/// `mulhi(n, magic) >> shift`. With the `add` indicator the magic number requires 65 bits and the
+/// quotient is computed as `t = mulhi(n, magic); ((n - t) >> 1) + t) >> shift`.
+///
+/// See _Division by Invariant Integers using Multiplication_ by Granlund and Montgomery.
+fn udiv_magic(d: u64) -> (u64, u32, bool) {
+    assert!(d > 1 && !d.is_power_of_two());
+    // ceil(log2(d))
+    let l = 64 - (d - 1).leading_zeros();
+
+    // Find the smallest shift with a magic number fitting into 64 bits, which yields the exact
+    // quotient for all dividends.
+    for s in 0..l.min(64) {
+        let p = 1u128 << (64 + s);
+        let m = p.div_ceil(u128::from(d));
+        if m < 1 << 64 && m * u128::from(d) - p <= 1 << s {
+            // CAST: m fits into 64 bits, checked above.
+            return (m as u64, s, false);
+        }
+    }
+
+    // Fall back to the 65 bit magic number, where the implicit top bit is handled with the add.
+    let m = ((1u128 << 64) * ((1u128 << l) - u128::from(d))) / u128::from(d) + 1;
+    // CAST: m < 2^64 as 2^l - d < d.
+    (m as u64, l - 1, true)
+}
+
+/// Length of a memory region, either known at jit time or held in a register at runtime, see
+/// [`Asm::emit_memcpy`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Len {
+    /// Length known at jit time.
+    Const(usize),
+    /// Length held in the register at runtime.
+    Reg(Reg64),
+}
+
+impl From<usize> for Len {
+    fn from(len: usize) -> Len {
+        Len::Const(len)
+    }
+}
+
+impl From<Reg64> for Len {
+    fn from(len: Reg64) -> Len {
+        Len::Reg(len)
+    }
+}
+
+/// Operand of [`Asm::select`], either a register or a constant.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Sel {
+    /// Value held in the register.
+    Reg(Reg64),
+    /// Constant known at jit time.
+    Const(u64),
+}
+
+impl From<Reg64> for Sel {
+    fn from(op: Reg64) -> Sel {
+        Sel::Reg(op)
+    }
+}
+
+impl From<u64> for Sel {
+    fn from(op: u64) -> Sel {
+        Sel::Const(op)
+    }
+}
+
+/// Lengths up to which [`Asm::emit_memcpy`] and [`Asm::emit_memset`] emit unrolled moves, longer
+/// regions use `rep movsb` / `rep stosb`.
+pub const UNROLL_THRESHOLD: usize = 64;
+
+impl Asm {
+    /// Emit the shortest sequence to materialize the constant `imm` in `dst`.
+    ///
+    /// Zero is materialized with `xor` of the 32 bit sub-register, which clobbers the flags.
+    /// Constants fitting into 32 bit use a zero-extending 32 bit `mov`, constants fitting into a
+    /// sign-extended 32 bit immediate use the sign-extending `mov`, everything else uses a
+    /// `mov` with a 64 bit immediate.
+    ///
+    /// ```rust
+    /// use juicebox_asm::{Asm, Reg64::*};
+    ///
+    /// let mut asm = Asm::new();
+    /// asm.load_const(rax, 0);
+    /// asm.load_const(rcx, 0x1000);
+    /// asm.load_const(rdx, -1i64 as u64);
+    /// assert_eq!(asm.len(), 2 /* xor */ + 5 /* mov r32 */ + 7 /* mov r64 sext */);
+    /// ```
+    pub fn load_const(&mut self, dst: Reg64, imm: u64) {
+        if imm == 0 {
+            self.xor(dst.to32(), dst.to32());
+        } else {
+            self.mov_const(dst, imm);
+        }
+    }
+
+    /// Emit the shortest `mov` to materialize the constant `imm` in `dst`, preserving the flags.
+    fn mov_const(&mut self, dst: Reg64, imm: u64) {
+        if let Ok(imm) = u32::try_from(imm) {
+            self.mov(dst.to32(), Imm32::from(imm));
+        } else if let Ok(imm) = i32::try_from(imm as i64) {
+            self.mov(dst, Imm32::from(imm));
+        } else {
+            self.mov(dst, Imm64::from(imm));
+        }
+    }
+
+    /// Emit `dst = src * c` with wrapping semantics.
+    ///
+    /// Multiplications by `0`, powers of two and `{3, 5, 9} * 2^n` are emitted as `xor`, `shl`
+    /// and `lea`/`shl` sequences, other constants use `imul`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `src` is `rsp` and `c` is one of `{3, 5, 9} * 2^n`, as `rsp` can not be encoded as
+    /// index register of the `lea`, or if `c` does not fit into a sign-extended 32 bit immediate
+    /// and `dst` is equal to `src`.
+    pub fn mul_const(&mut self, dst: Reg64, src: Reg64, c: u64) {
+        // Zero yields no odd factor and is handled separately.
+        let odd = c.checked_shr(c.trailing_zeros()).unwrap_or(0);
+        // CAST: trailing_zeros <= 64.
+        let shift = c.trailing_zeros() as u8;
+
+        if c == 0 {
+            self.xor(dst, dst);
+        } else if odd == 1 {
+            self.mov_if_ne(dst, src);
+            if shift > 0 {
+                self.shl(dst, Imm8::from(shift));
+            }
+        } else if matches!(odd, 3 | 5 | 9) {
+            // CAST: odd - 1 is one of 2, 4, 8.
+            let m = Mem64::indirect_base_index_scale_disp(src, src, (odd - 1) as u8, 0);
+            self.lea(dst, m);
+            if shift > 0 {
+                self.shl(dst, Imm8::from(shift));
+            }
+        } else if let Ok(imm) = i8::try_from(c as i64) {
+            self.imul((dst, src, Imm8::from(imm)));
+        } else if let Ok(imm) = i32::try_from(c as i64) {
+            self.imul((dst, src, Imm32::from(imm)));
+        } else {
+            assert_ne!(
+                dst, src,
+                "Destination must not be the source for 64 bit constants"
+            );
+            self.mov(dst, Imm64::from(c));
+            self.imul((dst, src));
+        }
+    }
+
+    /// Emit the unsigned division `dst = src / d`, using a multiplication with a magic number for
+    /// constants which are not a power of two. Clobbers `rax` and `rdx`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `d` is zero or if `src` is `rax` or `rdx`.
+    pub fn udiv_const(&mut self, dst: Reg64, src: Reg64, d: u64) {
+        use Reg64::{rax, rdx};
+
+        assert_ne!(d, 0, "Division by zero");
+        assert!(!matches!(src, rax | rdx), "Source must not be rax or rdx");
+
+        if d.is_power_of_two() {
+            self.mov_if_ne(dst, src);
+            if d > 1 {
+                // CAST: trailing_zeros < 64.
+                self.shr(dst, Imm8::from(d.trailing_zeros() as u8));
+            }
+            return;
+        }
+
+        let (magic, shift, add) = udiv_magic(d);
+        // CAST: shift < 64.
+        let shift = shift as u8;
+
+        // rdx = mulhi(src, magic)
+        self.mov(rax, Imm64::from(magic));
+        self.mul(src);
+
+        if add {
+            self.mov(rax, src);
+            self.sub(rax, rdx);
+            self.shr(rax, Imm8::from(1u8));
+            self.add(rax, rdx);
+            if shift > 0 {
+                self.shr(rax, Imm8::from(shift));
+            }
+            self.mov_if_ne(dst, rax);
+        } else {
+            if shift > 0 {
+                self.shr(rdx, Imm8::from(shift));
+            }
+            self.mov_if_ne(dst, rdx);
+        }
+    }
+
+    /// Emit an unrolled store of `len` bytes of `val` to the address in `dst`. Clobbers `rax`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `dst` is `rax`, or if `len` exceeds `i32::MAX`.
+    pub fn memset_small(&mut self, dst: Reg64, val: u8, len: usize) {
+        use Reg64::rax;
+
+        assert_ne!(dst, rax, "Destination must not be rax");
+        if len == 0 {
+            return;
+        }
+
+        self.redzone_check(dst, 0, Len::Const(len));
+        self.mov(rax, Imm64::from(u64::from(val) * 0x0101_0101_0101_0101));
+        for (off, size) in chunks(len) {
+            match size {
+                8 => self.mov(Mem64::indirect_disp(dst, off), rax),
+                4 => self.mov(Mem32::indirect_disp(dst, off), Reg32::eax),
+                2 => self.mov(Mem16::indirect_disp(dst, off), Reg16::ax),
+                _ => self.mov(Mem8::indirect_disp(dst, off), Reg8::al),
+            }
+        }
+    }
+
+    /// Emit an unrolled copy of `len` bytes from the address in `src` to the address in `dst`.
+    /// The regions must not overlap. Clobbers `rax`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `dst` or `src` is `rax`, or if `len` exceeds `i32::MAX`.
+    pub fn memcpy_small(&mut self, dst: Reg64, src: Reg64, len: usize) {
+        use Reg64::rax;
+
+        assert!(
+            dst != rax && src != rax,
+            "Destination and source must not be rax"
+        );
+        self.redzone_check(dst, 0, Len::Const(len));
+        self.redzone_check(src, 0, Len::Const(len));
+        for (off, size) in chunks(len) {
+            match size {
+                8 => {
+                    self.mov(rax, Mem64::indirect_disp(src, off));
+                    self.mov(Mem64::indirect_disp(dst, off), rax);
+                }
+                4 => {
+                    self.mov(Reg32::eax, Mem32::indirect_disp(src, off));
+                    self.mov(Mem32::indirect_disp(dst, off), Reg32::eax);
+                }
+                2 => {
+                    self.mov(Reg16::ax, Mem16::indirect_disp(src, off));
+                    self.mov(Mem16::indirect_disp(dst, off), Reg16::ax);
+                }
+                _ => {
+                    self.mov(Reg8::al, Mem8::indirect_disp(src, off));
+                    self.mov(Mem8::indirect_disp(dst, off), Reg8::al);
+                }
+            }
+        }
+    }
+
+    /// Emit a copy of `len` bytes from the address in `src` to the address in `dst`. The regions
+    /// must not overlap.
+    ///
+    /// Constant lengths up to [`UNROLL_THRESHOLD`] are copied with unrolled moves, see
+    /// [`Asm::memcpy_small`], which clobbers `rax`. Longer or runtime lengths use `rep movsb`,
+    /// which clobbers `rcx`, `rsi` and `rdi`.
+    ///
+    /// ```rust
+    /// use juicebox_asm::{Asm, Reg64::*};
+    ///
+    /// let mut asm = Asm::new();
+    /// asm.emit_memcpy(rdi, rsi, 16);
+    /// asm.emit_memcpy(rdi, rsi, rdx);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the unrolled moves are used and `dst` or `src` is `rax`.
+    pub fn emit_memcpy(&mut self, dst: Reg64, src: Reg64, len: impl Into<Len>) {
+        use Reg64::{rdi, rsi};
+
+        let len = match len.into() {
+            Len::Const(len) if len <= UNROLL_THRESHOLD => return self.memcpy_small(dst, src, len),
+            len => len,
+        };
+
+        self.redzone_check(dst, 0, len);
+        self.redzone_check(src, 0, len);
+
+        // Move the addresses into place through the stack, which handles any assignment of the
+        // operand registers.
+        self.push(dst);
+        self.push(src);
+        self.load_len(len);
+        self.pop(rsi);
+        self.pop(rdi);
+        self.rep_movsb();
+    }
+
+    /// Emit a store of `len` bytes of `val` to the address in `dst`.
+    ///
+    /// Constant lengths up to [`UNROLL_THRESHOLD`] are stored with unrolled moves, see
+    /// [`Asm::memset_small`], which clobbers `rax`. Longer or runtime lengths use `rep stosb`,
+    /// which clobbers `rax`, `rcx` and `rdi`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the unrolled moves are used and `dst` is `rax`.
+    pub fn emit_memset(&mut self, dst: Reg64, val: u8, len: impl Into<Len>) {
+        use Reg64::{rax, rdi};
+
+        let len = match len.into() {
+            Len::Const(len) if len <= UNROLL_THRESHOLD => return self.memset_small(dst, val, len),
+            len => len,
+        };
+
+        self.redzone_check(dst, 0, len);
+        self.push(dst);
+        self.load_len(len);
+        self.pop(rdi);
+        self.load_const(rax, u64::from(val));
+        self.rep_stosb();
+    }
+
+    /// Emit `dst = dst + src` with unsigned overflow check, like [`u64::checked_add`]. `ovf` is
+    /// set to `1` on overflow, else to `0`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ovf` is equal to `dst`.
+    pub fn checked_uadd(&mut self, dst: Reg64, src: Reg64, ovf: Reg64) {
+        self.add(dst, src);
+        self.set_flag(Cond::B, dst, ovf);
+    }
+
+    /// Emit `dst = dst + src` with signed overflow check, like [`i64::checked_add`]. `ovf` is set
+    /// to `1` on overflow, else to `0`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ovf` is equal to `dst`.
+    pub fn checked_sadd(&mut self, dst: Reg64, src: Reg64, ovf: Reg64) {
+        self.add(dst, src);
+        self.set_flag(Cond::O, dst, ovf);
+    }
+
+    /// Emit `dst = dst - src` with unsigned overflow check, like [`u64::checked_sub`]. `ovf` is
+    /// set to `1` on overflow, else to `0`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ovf` is equal to `dst`.
+    pub fn checked_usub(&mut self, dst: Reg64, src: Reg64, ovf: Reg64) {
+        self.sub(dst, src);
+        self.set_flag(Cond::B, dst, ovf);
+    }
+
+    /// Emit `dst = dst - src` with signed overflow check, like [`i64::checked_sub`]. `ovf` is set
+    /// to `1` on overflow, else to `0`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ovf` is equal to `dst`.
+    pub fn checked_ssub(&mut self, dst: Reg64, src: Reg64, ovf: Reg64) {
+        self.sub(dst, src);
+        self.set_flag(Cond::O, dst, ovf);
+    }
+
+    /// Emit `rax = rax * src` with unsigned overflow check, like [`u64::checked_mul`]. `ovf` is
+    /// set to `1` on overflow, else to `0`.
+    ///
+    /// Uses the widening `mul`, which clobbers `rdx`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ovf` is `rax`.
+    pub fn checked_umul(&mut self, src: Reg64, ovf: Reg64) {
+        self.mul(src);
+        self.set_flag(Cond::O, Reg64::rax, ovf);
+    }
+
+    /// Emit `dst = dst * src` with signed overflow check, like [`i64::checked_mul`]. `ovf` is set
+    /// to `1` on overflow, else to `0`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ovf` is equal to `dst`.
+    pub fn checked_smul(&mut self, dst: Reg64, src: Reg64, ovf: Reg64) {
+        self.imul((dst, src));
+        self.set_flag(Cond::O, dst, ovf);
+    }
+
+    /// Emit `dst = dst + src` saturating at the numeric bounds, like [`u64::saturating_add`].
+    pub fn saturating_uadd(&mut self, dst: Reg64, src: Reg64) {
+        self.add(dst, src);
+        self.select(Cond::B, dst, u64::MAX, dst);
+    }
+
+    /// Emit `dst = dst + src` saturating at the numeric bounds, like [`i64::saturating_add`].
+    pub fn saturating_sadd(&mut self, dst: Reg64, src: Reg64) {
+        self.add(dst, src);
+        self.saturate_signed(dst);
+    }
+
+    /// Emit `dst = dst - src` saturating at the numeric bounds, like [`u64::saturating_sub`].
+    pub fn saturating_usub(&mut self, dst: Reg64, src: Reg64) {
+        self.sub(dst, src);
+        self.select(Cond::B, dst, 0, dst);
+    }
+
+    /// Emit `dst = dst - src` saturating at the numeric bounds, like [`i64::saturating_sub`].
+    pub fn saturating_ssub(&mut self, dst: Reg64, src: Reg64) {
+        self.sub(dst, src);
+        self.saturate_signed(dst);
+    }
+
+    /// Emit `ovf = cond as u64` for the result in `dst`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ovf` is equal to `dst`.
+    fn set_flag(&mut self, cond: Cond, dst: Reg64, ovf: Reg64) {
+        assert_ne!(
+            dst, ovf,
+            "Overflow register must differ from the result register"
+        );
+        self.setcc(cond, ovf.to8lo());
+        self.movzx(ovf.to32(), ovf.to8lo());
+    }
+
+    /// Saturate the wrapped result of a signed add or sub in `dst` on overflow. On overflow the
+    /// sign of the wrapped result is the inverse of the sign of the exact result.
+    fn saturate_signed(&mut self, dst: Reg64) {
+        let mut end = Label::new();
+        self.jcc_short(Cond::No, &mut end);
+        // CAST: Reinterpret the bounds as u64 constants.
+        self.select(Cond::S, dst, i64::MAX as u64, i64::MIN as u64);
+        self.bind(&mut end);
+    }
+
+    /// Emit `dst = if cond { if_true } else { if_false }`, evaluating `cond` on the current flags,
+    /// eg set by a preceding `cmp`. The flags are preserved.
+    ///
+    /// A register operand is selected with `cmov`, without register operand or if the constant
+    /// operand must be moved into `dst` while `dst` is the other operand, a short branch over the
+    /// `mov` of the constant is emitted instead.
+    ///
+    /// ```rust
+    /// use juicebox_asm::insn::Test;
+    /// use juicebox_asm::{Asm, Cond, Reg64::*};
+    ///
+    /// // rax = if rdi == 0 { rsi } else { 42 }
+    /// let mut asm = Asm::new();
+    /// asm.test(rdi, rdi);
+    /// asm.select(Cond::E, rax, rsi, 42);
+    /// assert_eq!(asm.len(), 3 /* test */ + 5 /* mov */ + 4 /* cmov */);
+    /// ```
+    pub fn select(
+        &mut self,
+        cond: Cond,
+        dst: Reg64,
+        if_true: impl Into<Sel>,
+        if_false: impl Into<Sel>,
+    ) {
+        match (if_true.into(), if_false.into()) {
+            (t, f) if t == f => self.mov_sel(dst, t),
+            // One operand already lives in dst.
+            (Sel::Reg(t), Sel::Reg(f)) if t == dst => self.cmov(cond.negate(), dst, f),
+            (Sel::Reg(t), Sel::Reg(f)) if f == dst => self.cmov(cond, dst, t),
+            (Sel::Reg(t), Sel::Const(f)) if t == dst => self.branch_const(cond, dst, f),
+            (Sel::Const(t), Sel::Reg(f)) if f == dst => self.branch_const(cond.negate(), dst, t),
+            // Neither operand lives in dst.
+            (Sel::Reg(t), f) => {
+                self.mov_sel(dst, f);
+                self.cmov(cond, dst, t);
+            }
+            (Sel::Const(t), Sel::Reg(f)) => {
+                self.mov_const(dst, t);
+                self.cmov(cond.negate(), dst, f);
+            }
+            (Sel::Const(t), Sel::Const(f)) => {
+                self.mov_const(dst, f);
+                self.branch_const(cond.negate(), dst, t);
+            }
+        }
+    }
+
+    /// Emit `dst = op`, preserving the flags.
+    fn mov_sel(&mut self, dst: Reg64, op: Sel) {
+        match op {
+            Sel::Reg(op) => self.mov_if_ne(dst, op),
+            Sel::Const(op) => self.mov_const(dst, op),
+        }
+    }
+
+    /// Emit `if !skip { dst = imm }`, preserving the flags.
+    fn branch_const(&mut self, skip: Cond, dst: Reg64, imm: u64) {
+        let mut end = Label::new();
+        self.jcc_short(skip, &mut end);
+        self.mov_const(dst, imm);
+        self.bind(&mut end);
+    }
+
+    /// Load the length `len` into `rcx`.
+    fn load_len(&mut self, len: Len) {
+        match len {
+            // CAST: usize fits into u64 on x64.
+            Len::Const(len) => self.load_const(Reg64::rcx, len as u64),
+            Len::Reg(len) => self.mov_if_ne(Reg64::rcx, len),
+        }
+    }
+
+    /// Emit `mov dst, src` if the registers differ.
+    pub(crate) fn mov_if_ne(&mut self, dst: Reg64, src: Reg64) {
+        if dst != src {
+            self.mov(dst, src);
+        }
+    }
+}
+
+/// Split `len` bytes into chunks of `8, 4, 2, 1` bytes, returns `(offset, size)` pairs.
+fn chunks(len: usize) -> impl Iterator<Item = (i32, usize)> {
+    let full = len / 8 * 8;
+    let tail = [4, 2, 1]
+        .into_iter()
+        .filter(move |size| (len - full) & size != 0);
+    let mut off = 0;
+    (0..full / 8).map(|_| 8).chain(tail).map(move |size| {
+        let o = i32::try_from(off).expect("Length exceeds i32");
+        off += size;
+        (o, size)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Runtime;
+
+    /// Compile `emit` into a `fn(u64) -> u64` with the argument in `rdi` and the result in `rax`.
+    fn compile(rt: &mut Runtime, emit: impl FnOnce(&mut Asm)) -> extern "C" fn(u64) -> u64 {
+        let mut asm = Asm::new();
+        emit(&mut asm);
+        asm.ret();
+        unsafe { rt.add_code(asm.into_code()) }
+    }
+
+    const VALUES: [u64; 10] = [
+        0,
+        1,
+        2,
+        7,
+        1000,
+        0x1234_5678,
+        0x7fff_ffff_ffff_ffff,
+        0x8000_0000_0000_0000,
+        0xdead_beef_cafe_babe,
+        u64::MAX,
+    ];
+
+    #[test]
+    fn test_load_const() {
+        use Reg64::*;
+
+        let enc = |dst, imm| {
+            let mut asm = Asm::new();
+            asm.load_const(dst, imm);
+            asm.into_code()
+        };
+        assert_eq!(enc(rax, 0), [0x31, 0xc0]);
+        assert_eq!(enc(r9, 0), [0x45, 0x31, 0xc9]);
+        assert_eq!(enc(rcx, 0xffff_ffff), [0xb9, 0xff, 0xff, 0xff, 0xff]);
+        assert_eq!(enc(r12, 1), [0x41, 0xbc, 0x01, 0x00, 0x00, 0x00]);
+        assert_eq!(
+            enc(rdx, -2i64 as u64),
+            [0x48, 0xc7, 0xc2, 0xfe, 0xff, 0xff, 0xff]
+        );
+        assert_eq!(enc(rax, 1 << 32).len(), 10);
+
+        let mut rt = Runtime::new();
+        for imm in [
+            0,
+            1,
+            0xffff_ffff,
+            -1i64 as u64,
+            0x8000_0000,
+            1 << 32,
+            u64::MAX - 0x8000_0000,
+        ] {
+            let f = compile(&mut rt, |asm| {
+                asm.mov(rax, Imm64::from(0x5555_5555_5555_5555u64));
+                asm.load_const(rax, imm);
+            });
+            assert_eq!(f(0), imm, "{:#x}", imm);
+        }
+    }
+
+    #[test]
+    fn test_emit_memcpy_memset() {
+        use Reg64::*;
+
+        let mut rt = Runtime::new();
+        for len in [0, 5, UNROLL_THRESHOLD, UNROLL_THRESHOLD + 1, 200] {
+            // fn(a: *mut u8, b: *mut u8, len: usize) { memset(b, 0xcd, LEN); memcpy(a, b, len) }
+            let mut asm = Asm::new();
+            asm.mov(r8, rdi);
+            asm.mov(r9, rsi);
+            asm.emit_memset(r9, 0xcd, len);
+            asm.emit_memcpy(r8, r9, rdx);
+            asm.ret();
+            let set_cpy =
+                unsafe { rt.add_code::<extern "C" fn(*mut u8, *mut u8, usize)>(asm.into_code()) };
+
+            // fn(a: *mut u8, b: *mut u8) { memcpy(b, a, LEN) }
+            let mut asm = Asm::new();
+            asm.emit_memcpy(rsi, rdi, len);
+            asm.ret();
+            let cpy = unsafe { rt.add_code::<extern "C" fn(*mut u8, *mut u8)>(asm.into_code()) };
+
+            let mut a = [0u8; 256];
+            let mut b = [0u8; 256];
+            let mut c = [0u8; 256];
+            set_cpy(a.as_mut_ptr(), b.as_mut_ptr(), len);
+            cpy(a.as_mut_ptr(), c.as_mut_ptr());
+            for i in 0..a.len() {
+                let exp = if i < len { 0xcd } else { 0 };
+                assert_eq!((a[i], b[i], c[i]), (exp, exp, exp), "len {} idx {}", len, i);
+            }
+        }
+    }
+
+    #[test]
+    fn test_mem_rsp_r12_base() {
+        use Reg64::*;
+
+        // Copy a 16 byte stack slot through r12, rsp and r12 need a SIB byte as base.
+        let mut rt = Runtime::new();
+        let f = compile(&mut rt, |asm| {
+            asm.push(r12);
+            asm.mov(r12, rdi);
+            asm.push(rdi);
+            asm.push(rdi);
+            asm.memset_small(rsp, 0x11, 16);
+            asm.memcpy_small(r12, rsp, 16);
+            asm.pop(rax);
+            asm.pop(rax);
+            asm.pop(r12);
+        });
+
+        let mut buf = [0u8; 16];
+        f(buf.as_mut_ptr() as u64);
+        assert_eq!(buf, [0x11; 16]);
+    }
+
+    #[test]
+    fn test_mul_const() {
+        use Reg64::*;
+
+        let mut rt = Runtime::new();
+        for c in [
+            0,
+            1,
+            2,
+            3,
+            6,
+            9,
+            40,
+            0x80,
+            7,
+            100,
+            -3i64 as u64,
+            0x1_0000_0001,
+        ] {
+            let f = compile(&mut rt, |asm| asm.mul_const(rax, rdi, c));
+            let g = compile(&mut rt, |asm| {
+                asm.mov(rax, rdi);
+                asm.mul_const(rax, rax, c as i32 as u64);
+            });
+            for v in VALUES {
+                assert_eq!(f(v), v.wrapping_mul(c), "{:#x} * {:#x}", v, c);
+                assert_eq!(g(v), v.wrapping_mul(c as i32 as u64));
+            }
+        }
+    }
+
+    #[test]
+    fn test_udiv_const() {
+        use Reg64::*;
+
+        let mut rt = Runtime::new();
+        for d in [
+            1,
+            2,
+            3,
+            5,
+            6,
+            7,
+            10,
+            641,
+            1 << 40,
+            0x8000_0000_0000_0001,
+            u64::MAX - 1,
+        ] {
+            let f = compile(&mut rt, |asm| asm.udiv_const(rax, rdi, d));
+            let g = compile(&mut rt, |asm| {
+                asm.udiv_const(rdx, rdi, d);
+                asm.mov(rax, rdx);
+            });
+            for v in VALUES {
+                assert_eq!(f(v), v / d, "{:#x} / {:#x}", v, d);
+                assert_eq!(g(v), v / d);
+            }
+        }
+    }
+
+    #[test]
+    fn test_udiv_magic() {
+        // Classic examples, 3 fits without add, 7 requires the 65 bit magic number.
+        assert_eq!(udiv_magic(3), (0xaaaa_aaaa_aaaa_aaab, 1, false));
+        assert_eq!(udiv_magic(7), (0x2492_4924_9249_2493, 2, true));
+    }
+
+    #[test]
+    fn test_checked() {
+        use crate::insn::Test;
+        use Reg64::*;
+
+        type Op = fn(&mut Asm, Reg64, Reg64, Reg64);
+        type Ref = fn(u64, u64) -> Option<u64>;
+        let ops: [(Op, Ref); 5] = [
+            (Asm::checked_uadd, u64::checked_add),
+            (Asm::checked_usub, u64::checked_sub),
+            (Asm::checked_sadd, |a, b| {
+                (a as i64).checked_add(b as i64).map(|r| r as u64)
+            }),
+            (Asm::checked_ssub, |a, b| {
+                (a as i64).checked_sub(b as i64).map(|r| r as u64)
+            }),
+            (Asm::checked_smul, |a, b| {
+                (a as i64).checked_mul(b as i64).map(|r| r as u64)
+            }),
+        ];
+
+        let mut rt = Runtime::new();
+        for (idx, (emit, check)) in ops.into_iter().enumerate() {
+            for b in VALUES {
+                // rax = (rdi op b), returns u64::MAX on overflow.
+                let f = compile(&mut rt, |asm| {
+                    asm.mov(rax, rdi);
+                    asm.load_const(rcx, b);
+                    emit(asm, rax, rcx, rdx);
+                    asm.test(rdx, rdx);
+                    asm.select(Cond::Ne, rax, u64::MAX, rax);
+                });
+                for a in VALUES {
+                    // Results equal to u64::MAX are indistinguishable from overflows.
+                    let exp = check(a, b).unwrap_or(u64::MAX);
+                    assert_eq!(f(a), exp, "op {} a={:#x} b={:#x}", idx, a, b);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_checked_umul() {
+        use Reg64::*;
+
+        let mut rt = Runtime::new();
+        for b in VALUES {
+            // Returns the overflow flag.
+            let f = compile(&mut rt, |asm| {
+                asm.mov(rax, rdi);
+                asm.load_const(rcx, b);
+                asm.checked_umul(rcx, rsi);
+                asm.mov(rax, rsi);
+            });
+            for a in VALUES {
+                let exp = u64::from(a.checked_mul(b).is_none());
+                assert_eq!(f(a), exp, "a={:#x} b={:#x}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_saturating() {
+        use Reg64::*;
+
+        type Op = fn(&mut Asm, Reg64, Reg64);
+        type Ref = fn(u64, u64) -> u64;
+        let ops: [(Op, Ref); 4] = [
+            (Asm::saturating_uadd, u64::saturating_add),
+            (Asm::saturating_usub, u64::saturating_sub),
+            (Asm::saturating_sadd, |a, b| {
+                (a as i64).saturating_add(b as i64) as u64
+            }),
+            (Asm::saturating_ssub, |a, b| {
+                (a as i64).saturating_sub(b as i64) as u64
+            }),
+        ];
+
+        let mut rt = Runtime::new();
+        for (idx, (emit, exp)) in ops.into_iter().enumerate() {
+            for b in VALUES {
+                let f = compile(&mut rt, |asm| {
+                    asm.mov(rax, rdi);
+                    asm.load_const(rcx, b);
+                    emit(asm, rax, rcx);
+                });
+                for a in VALUES {
+                    assert_eq!(f(a), exp(a, b), "op {} a={:#x} b={:#x}", idx, a, b);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_select() {
+        use crate::insn::Test;
+        use Reg64::*;
+
+        // Operand choices with the value they hold at runtime.
+        let ops = [
+            (Sel::Reg(rax), 11),
+            (Sel::Reg(rcx), 22),
+            (Sel::Reg(rdx), 33),
+            (Sel::Const(44), 44),
+            (Sel::Const(-1i64 as u64), u64::MAX),
+        ];
+
+        let mut rt = Runtime::new();
+        for (t, tv) in ops {
+            for (f, fv) in ops {
+                // rax = if rdi == 0 { t } else { f }
+                let sel = compile(&mut rt, |asm| {
+                    asm.load_const(rax, 11);
+                    asm.load_const(rcx, 22);
+                    asm.load_const(rdx, 33);
+                    asm.test(rdi, rdi);
+                    asm.select(Cond::E, rax, t, f);
+                });
+                assert_eq!(sel(0), tv, "{:?} {:?}", t, f);
+                assert_eq!(sel(1), fv, "{:?} {:?}", t, f);
+            }
+        }
+    }
+
+    #[test]
+    fn test_select_cmov() {
+        use Reg64::*;
+
+        let enc = |dst, t: Sel, f: Sel| {
+            let mut asm = Asm::new();
+            asm.select(Cond::L, dst, t, f);
+            asm.into_code()
+        };
+        // cmovl rax, rcx
+        assert_eq!(enc(rax, rcx.into(), rax.into()), [0x48, 0x0f, 0x4c, 0xc1]);
+        // cmovge rax, rcx
+        assert_eq!(enc(rax, rax.into(), rcx.into()), [0x48, 0x0f, 0x4d, 0xc1]);
+        // mov rax, rdx ; cmovl rax, rcx
+        assert_eq!(
+            enc(rax, rcx.into(), rdx.into()),
+            [0x48, 0x89, 0xd0, 0x48, 0x0f, 0x4c, 0xc1]
+        );
+        // jl +5 ; mov eax, 1
+        assert_eq!(
+            enc(rax, rax.into(), 1.into()),
+            [0x7c, 0x05, 0xb8, 0x01, 0x00, 0x00, 0x00]
+        );
+    }
+
+    #[test]
+    fn test_memset_memcpy() {
+        use Reg64::*;
+
+        let mut rt = Runtime::new();
+        for len in [0, 1, 3, 8, 15, 29] {
+            let mut asm = Asm::new();
+            asm.memset_small(rdi, 0xab, len);
+            asm.memcpy_small(rsi, rdi, len);
+            asm.ret();
+            let f = unsafe { rt.add_code::<extern "C" fn(*mut u8, *mut u8)>(asm.into_code()) };
+
+            let mut a = [0u8; 32];
+            let mut b = [0u8; 32];
+            f(a.as_mut_ptr(), b.as_mut_ptr());
+            for (i, (&a, &b)) in a.iter().zip(&b).enumerate() {
+                let exp = if i < len { 0xab } else { 0 };
+                assert_eq!((a, b), (exp, exp), "len {} idx {}", len, i);
+            }
+        }
+    }
+}
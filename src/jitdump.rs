@@ -0,0 +1,305 @@
+//! Support for the perf [jitdump][jitdump] format.
+//!
+//! Other than the static perf map, see [`Runtime::with_profile`](crate::Runtime::with_profile),
+//! the jitdump file records a copy of the code of each loaded function together with its name and
+//! source line mappings. `perf inject --jit` turns the dump into one ELF object per function, such
+//! that `perf report` and `perf annotate` can symbolize and disassemble samples in jitted code.
+//!
+//! The dump file `jit-<pid>.dump` is `mmap`ed executable once, which `perf record` captures as
+//! marker telling `perf inject` where to find the dump. The records are timestamped with
+//! `CLOCK_MONOTONIC`, hence the profile must be recorded with `perf record -k mono`.
+//!
+//! ```text
+//! perf record -k mono <jit-program>
+//! perf inject --jit -i perf.data -o perf.jit.data
+//! perf report -i perf.jit.data
+//! ```
+//!
+//! [jitdump]: https://elixir.bootlin.com/linux/v6.6.6/source/tools/perf/Documentation/jitdump-specification.txt
+
+use std::fs;
+use std::io::Write;
+use std::os::fd::AsRawFd;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::LineInfo;
+
+/// Magic number of the file header, `JiTD`.
+const MAGIC: u32 = 0x4a69_5444;
+/// Version of the jitdump format.
+const VERSION: u32 = 1;
+/// `EM_X86_64` elf machine.
+const EM_X86_64: u32 = 62;
+
+/// Size of the file header.
+const HEADER_SIZE: u32 = 40;
+
+/// `JIT_CODE_LOAD` record.
+const JIT_CODE_LOAD: u32 = 0;
+/// `JIT_CODE_DEBUG_INFO` record.
+const JIT_CODE_DEBUG_INFO: u32 = 2;
+/// `JIT_CODE_CLOSE` record.
+const JIT_CODE_CLOSE: u32 = 3;
+
+/// Get the `CLOCK_MONOTONIC` timestamp in nanoseconds.
+fn timestamp() -> u64 {
+    let mut ts = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    let ret = unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut ts) };
+    assert_eq!(ret, 0, "Failed to get monotonic clock");
+    // CAST: The monotonic clock is never negative.
+    ts.tv_sec as u64 * 1_000_000_000 + ts.tv_nsec as u64
+}
+
+/// Serialize a record with the record `id` and the `body` into `buf`.
+fn record(buf: &mut Vec<u8>, id: u32, body: &[u8]) {
+    // UNWRAP: Records of the code on the runtime pages fit into an u32.
+    let size = u32::try_from(16 + body.len()).unwrap();
+    buf.extend_from_slice(&id.to_ne_bytes());
+    buf.extend_from_slice(&size.to_ne_bytes());
+    buf.extend_from_slice(&timestamp().to_ne_bytes());
+    buf.extend_from_slice(body);
+}
+
+/// Append the string `s` with a terminating nul byte to `buf`, interior nul bytes are dropped.
+fn push_str(buf: &mut Vec<u8>, s: &str) {
+    buf.extend(s.bytes().filter(|b| *b != 0));
+    buf.push(0);
+}
+
+/// Writer of a perf jitdump file, see the [module](self) documentation.
+///
+/// Dropping the [`JitDump`] writes the close record, the file is kept for `perf inject`.
+pub struct JitDump {
+    file: fs::File,
+    path: PathBuf,
+    /// Executable mapping of the file header, the marker for `perf record`.
+    marker: *mut libc::c_void,
+    /// Index of the next loaded function.
+    index: u64,
+}
+
+impl JitDump {
+    /// Create a jitdump file in a new directory in the temporary directory.
+    ///
+    /// Each [`JitDump`] gets its own directory, as the file name is fixed to `jit-<pid>.dump` and
+    /// `perf inject` places the generated ELF objects next to the dump.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the directory or the file can not be created or the file can not be `mmap`ed.
+    pub fn new() -> JitDump {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+        let pid = std::process::id();
+        let dir = std::env::temp_dir().join(format!(
+            "juicebox-jit-{}-{}",
+            pid,
+            NEXT_ID.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir_all(&dir)
+            .unwrap_or_else(|_| panic!("Failed to create jitdump directory {}", dir.display()));
+
+        let path = dir.join(format!("jit-{}.dump", pid));
+        let mut file = fs::OpenOptions::new()
+            .truncate(true)
+            .create(true)
+            .read(true)
+            .write(true)
+            .open(&path)
+            .unwrap_or_else(|_| panic!("Failed to open jitdump file {}", path.display()));
+
+        let mut header = Vec::with_capacity(HEADER_SIZE as usize);
+        // Fields magic, version, total_size, elf_mach, pad1 and pid.
+        for v in [MAGIC, VERSION, HEADER_SIZE, EM_X86_64, 0, pid] {
+            header.extend_from_slice(&v.to_ne_bytes());
+        }
+        header.extend_from_slice(&timestamp().to_ne_bytes());
+        header.extend_from_slice(&0u64.to_ne_bytes() /* flags */);
+        file.write_all(&header)
+            .expect("Failed to write jitdump header");
+
+        // The mapping must be executable to be recorded by perf.
+        let len = HEADER_SIZE as usize;
+        let marker = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                len,
+                libc::PROT_READ | libc::PROT_EXEC,
+                libc::MAP_PRIVATE,
+                file.as_raw_fd(),
+                0, /* off */
+            )
+        };
+        assert_ne!(marker, libc::MAP_FAILED, "Failed to mmap jitdump file");
+
+        JitDump {
+            file,
+            path,
+            marker,
+            index: 0,
+        }
+    }
+
+    /// Get the path of the jitdump file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Record the function `name` with the `code` loaded at `addr`. Optionally a `source` file
+    /// with `lines` mapping code offsets to source lines can be provided.
+    ///
+    /// # Panics
+    ///
+    /// Panics if writing the records fails.
+    pub fn code_load(
+        &mut self,
+        name: &str,
+        addr: *const u8,
+        code: &[u8],
+        source: Option<&str>,
+        lines: &[LineInfo],
+    ) {
+        let addr = addr as u64;
+        let mut buf = Vec::new();
+
+        // Debug info must precede the code load record it belongs to.
+        if let Some(source) = source.filter(|_| !lines.is_empty()) {
+            let mut body = Vec::new();
+            body.extend_from_slice(&addr.to_ne_bytes());
+            body.extend_from_slice(&(lines.len() as u64).to_ne_bytes());
+            for l in lines {
+                body.extend_from_slice(&(addr + u64::from(l.offset)).to_ne_bytes());
+                body.extend_from_slice(&l.line.to_ne_bytes());
+                body.extend_from_slice(&0u32.to_ne_bytes() /* discrim */);
+                push_str(&mut body, source);
+            }
+            record(&mut buf, JIT_CODE_DEBUG_INFO, &body);
+        }
+
+        let mut body = Vec::new();
+        body.extend_from_slice(&std::process::id().to_ne_bytes());
+        // CAST: Thread ids are positive.
+        body.extend_from_slice(&(unsafe { libc::gettid() } as u32).to_ne_bytes());
+        body.extend_from_slice(&addr.to_ne_bytes() /* vma */);
+        body.extend_from_slice(&addr.to_ne_bytes());
+        body.extend_from_slice(&(code.len() as u64).to_ne_bytes());
+        body.extend_from_slice(&self.index.to_ne_bytes());
+        push_str(&mut body, name);
+        body.extend_from_slice(code);
+        record(&mut buf, JIT_CODE_LOAD, &body);
+        self.index += 1;
+
+        self.file
+            .write_all(&buf)
+            .expect("Failed to write jitdump record");
+    }
+}
+
+impl Drop for JitDump {
+    /// Writes the close record and unmaps the marker.
+    fn drop(&mut self) {
+        let mut buf = Vec::new();
+        record(&mut buf, JIT_CODE_CLOSE, &[]);
+        // Errors are ignored, perf also accepts dumps without close record.
+        let _ = self.file.write_all(&buf);
+
+        unsafe {
+            let ret = libc::munmap(self.marker, HEADER_SIZE as usize);
+            assert_eq!(ret, 0, "Failed to munmap jitdump marker");
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn u32_at(buf: &[u8], off: usize) -> u32 {
+        u32::from_ne_bytes(buf[off..off + 4].try_into().unwrap())
+    }
+
+    fn u64_at(buf: &[u8], off: usize) -> u64 {
+        u64::from_ne_bytes(buf[off..off + 8].try_into().unwrap())
+    }
+
+    #[test]
+    fn test_records() {
+        let code = [0x90, 0x90, 0xc3];
+        let lines = [
+            LineInfo {
+                offset: 0,
+                line: 10,
+            },
+            LineInfo {
+                offset: 2,
+                line: 11,
+            },
+        ];
+
+        let mut dump = JitDump::new();
+        let path = dump.path().to_path_buf();
+        assert!(path.ends_with(format!("jit-{}.dump", std::process::id())));
+        dump.code_load("foo", code.as_ptr(), &code, Some("foo.rs"), &lines);
+        dump.code_load("bar", code.as_ptr(), &code[2..], None, &[]);
+        drop(dump);
+
+        let buf = fs::read(&path).unwrap();
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+        // Header.
+        assert_eq!(u32_at(&buf, 0), MAGIC);
+        assert_eq!(u32_at(&buf, 8), HEADER_SIZE);
+        assert_eq!(u32_at(&buf, 12), EM_X86_64);
+        assert_eq!(u32_at(&buf, 20), std::process::id());
+
+        // Collect the records as (id, body).
+        let mut recs = Vec::new();
+        let mut off = HEADER_SIZE as usize;
+        while off < buf.len() {
+            let size = u32_at(&buf, off + 4) as usize;
+            recs.push((u32_at(&buf, off), &buf[off + 16..off + size]));
+            off += size;
+        }
+        assert_eq!(off, buf.len());
+        let ids: Vec<_> = recs.iter().map(|(id, _)| *id).collect();
+        assert_eq!(
+            ids,
+            [
+                JIT_CODE_DEBUG_INFO,
+                JIT_CODE_LOAD,
+                JIT_CODE_LOAD,
+                JIT_CODE_CLOSE
+            ]
+        );
+
+        let addr = code.as_ptr() as u64;
+
+        // Debug info of foo.
+        let dbg = recs[0].1;
+        assert_eq!(u64_at(dbg, 0), addr);
+        assert_eq!(u64_at(dbg, 8), 2);
+        assert_eq!(u64_at(dbg, 16), addr);
+        assert_eq!(u32_at(dbg, 24), 10);
+        assert_eq!(&dbg[32..39], b"foo.rs\0");
+        assert_eq!(u64_at(dbg, 39), addr + 2);
+        assert_eq!(u32_at(dbg, 47), 11);
+        assert_eq!(&dbg[55..], b"foo.rs\0");
+
+        // Code load of foo and bar.
+        let load = recs[1].1;
+        assert_eq!(u32_at(load, 0), std::process::id());
+        assert_eq!(u64_at(load, 16), addr);
+        assert_eq!(u64_at(load, 24), 3);
+        assert_eq!(u64_at(load, 32), 0);
+        assert_eq!(&load[40..], b"foo\0\x90\x90\xc3");
+
+        let load = recs[2].1;
+        assert_eq!(u64_at(load, 24), 1);
+        assert_eq!(u64_at(load, 32), 1);
+        assert_eq!(&load[40..], b"bar\0\xc3");
+    }
+}
@@ -0,0 +1,186 @@
+//! Jump tables for switch-style dispatch on a runtime index.
+//!
+//! [`Asm::jump_table`] emits a table with an entry per target [Label] and [`Asm::jmp_table`]
+//! jumps to the target selected by an index. Each entry holds the offset of the target relative
+//! to the start of the table, hence the emitted code is position independent.
+//!
+//! ```rust
+//! use juicebox_asm::insn::{Mov, Sub};
+//! use juicebox_asm::{Asm, Cond, Imm64, Label, Reg64::*, Runtime};
+//!
+//! // fn(idx: u64) -> u64 { match idx { 0 => 10, 1 => 20, 2 => 30, _ => 0 } }
+//! let mut table = Label::new();
+//! let mut cases = [Label::new(), Label::new(), Label::new()];
+//! let mut default = Label::new();
+//!
+//! let mut asm = Asm::new();
+//! // Sub sets the flags like cmp.
+//! asm.mov(rax, rdi);
+//! asm.mov(rcx, Imm64::from(3));
+//! asm.sub(rax, rcx);
+//! asm.jcc(Cond::Ae, &mut default);
+//! asm.jmp_table(&mut table, rdi, rsi);
+//! for (case, val) in cases.iter_mut().zip([10, 20, 30]) {
+//!     asm.bind(case);
+//!     asm.mov(rax, Imm64::from(val));
+//!     asm.ret();
+//! }
+//! asm.bind(&mut default);
+//! asm.mov(rax, Imm64::from(0));
+//! asm.ret();
+//! asm.jump_table(&mut table, &mut cases);
+//!
+//! let mut rt = Runtime::new();
+//! let f = unsafe { rt.add_code::<extern "C" fn(u64) -> u64>(asm.into_code()) };
+//! assert_eq!([f(0), f(1), f(2), f(3)], [10, 20, 30, 0]);
+//! ```
+
+use crate::insn::{Add, Jmp, Movsxd};
+use crate::label::RelocKind;
+use crate::reg::Reg;
+use crate::{Asm, Label, Mem32, Reg64, Reloc};
+
+impl Asm {
+    /// Emit a jump table with an entry for each of the `targets` and bind `table` to its start,
+    /// eg after the last instruction of the function. The entry of a target is resolved once the
+    /// target is bound, or immediately if it is already bound.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `table` is already bound.
+    pub fn jump_table<'a>(
+        &mut self,
+        table: &mut Label,
+        targets: impl IntoIterator<Item = &'a mut Label>,
+    ) {
+        let base = self.len();
+        self.bind(table);
+        for target in targets {
+            self.record_reloc(target, self.len(), RelocKind::Table(base));
+            self.emit(&[0u8; 4]);
+            self.resolve(target);
+        }
+    }
+
+    /// Emit an indirect jump to the target of entry `idx` of the jump `table`, see
+    /// [`Asm::jump_table`]. Clobbers `idx` and `tmp`.
+    ///
+    /// The index must be zero extended in `idx` and is not range checked, it must be valid for
+    /// the table.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `idx` and `tmp` are the same register.
+    pub fn jmp_table(&mut self, table: &mut Label, idx: Reg64, tmp: Reg64) {
+        assert_ne!(idx, tmp, "Jump table registers must differ");
+
+        // lea tmp, [rip + table]
+        self.insn("lea", |asm| {
+            let r = tmp.idx();
+            let rex = 0x48 | ((r >> 3) << 2);
+            let modrm = ((r & 0b111) << 3) | 0b101;
+            asm.emit_blob(
+                &[rex, 0x8d, modrm, 0, 0, 0, 0],
+                &mut [Reloc::rel32(3, table)],
+            );
+        });
+        self.movsxd(idx, Mem32::indirect_base_index_scale_disp(tmp, idx, 4, 0));
+        self.add(tmp, idx);
+        self.jmp(tmp);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::insn::{Mov, Sub};
+    use crate::{Cond, Imm64, Runtime};
+    use Reg64::*;
+
+    #[test]
+    fn test_table() {
+        let mut table = Label::new();
+        let mut a = Label::new();
+        let mut b = Label::new();
+        let mut c = Label::new();
+
+        // Targets sharing code bind their labels to the same location.
+        let mut asm = Asm::new();
+        asm.bind(&mut a);
+        asm.bind(&mut c);
+        asm.nop();
+        asm.jump_table(&mut table, [&mut a, &mut b, &mut c]);
+        asm.bind(&mut b);
+        asm.nop();
+        assert_eq!(
+            asm.into_code(),
+            [
+                0x90, // a
+                0xff, 0xff, 0xff, 0xff, // a
+                0x0c, 0x00, 0x00, 0x00, // b
+                0xff, 0xff, 0xff, 0xff, // a
+                0x90, // b
+            ]
+        );
+    }
+
+    #[test]
+    fn test_jmp_table() {
+        let mut table = Label::new();
+        let mut asm = Asm::new();
+        asm.jmp_table(&mut table, rdi, r10);
+        asm.jump_table(&mut table, []);
+        assert_eq!(
+            asm.into_code(),
+            [
+                0x4c, 0x8d, 0x15, 0x0e, 0x00, 0x00, 0x00, // lea r10, [rip+0xe]
+                0x49, 0x63, 0xbc, 0xba, 0x00, 0x00, 0x00, 0x00, // movsxd rdi, [r10+rdi*4+0x0]
+                0x49, 0x01, 0xfa, // add r10, rdi
+                0x49, 0xff, 0xe2, // jmp r10
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Jump table registers must differ")]
+    fn test_same_regs() {
+        let mut table = Label::new();
+        let mut asm = Asm::new();
+        asm.jump_table(&mut table, []);
+        asm.jmp_table(&mut table, rax, rax);
+    }
+
+    #[test]
+    fn test_switch() {
+        // fn(idx: u64) -> u64, with the targets bound before and after the table.
+        let mut table = Label::new();
+        let mut cases: Vec<_> = (0..4).map(|_| Label::new()).collect();
+        let mut default = Label::new();
+
+        let mut asm = Asm::new();
+        asm.mov(rax, rdi);
+        asm.mov(rcx, Imm64::from(4));
+        asm.sub(rax, rcx);
+        asm.jcc(Cond::Ae, &mut default);
+        asm.jmp_table(&mut table, rdi, rsi);
+        for (i, case) in cases.iter_mut().enumerate().take(2) {
+            asm.bind(case);
+            asm.mov(rax, Imm64::from(i * 100));
+            asm.ret();
+        }
+        asm.jump_table(&mut table, &mut cases);
+        for (i, case) in cases.iter_mut().enumerate().skip(2) {
+            asm.bind(case);
+            asm.mov(rax, Imm64::from(i * 100));
+            asm.ret();
+        }
+        asm.bind(&mut default);
+        asm.mov(rax, Imm64::from(u64::MAX));
+        asm.ret();
+
+        let mut rt = Runtime::new();
+        let f = unsafe { rt.add_code::<extern "C" fn(u64) -> u64>(asm.into_code()) };
+        assert_eq!([f(0), f(1), f(2), f(3)], [0, 100, 200, 300]);
+        assert_eq!(f(4), u64::MAX);
+    }
+}
@@ -1,8 +1,6 @@
 //! Definition of the lable type which can be used as jump target and can be bound to a location in
 //! the emitted code.
 
-use std::collections::HashSet;
-
 /// A label which is used as target for jump instructions.
 ///
 /// ```rust
@@ -22,12 +20,47 @@ use std::collections::HashSet;
 ///
 /// Panics if the label is dropped while not yet bound, or having unresolved relocations.
 /// This is mainly a safety-guard to detect wrong usage.
+///
+/// A label does not allocate, the relocations referring to a label which is not yet bound are
+/// stored in the [`Asm`](crate::Asm) emitting them. Hence a label must be bound by the same
+/// assembler which emitted the references to it.
 pub struct Label {
     /// Location of the label. Will be set after the label is bound, else None.
     location: Option<usize>,
 
-    /// Offsets that must be patched with the label location.
-    offsets: HashSet<usize>,
+    /// Head of the list of pending relocations in the [Relocs] of the assembler, if any.
+    pending: Option<u32>,
+}
+
+/// Identifier of a label owned by an [`Asm`](crate::Asm), see
+/// [`Asm::new_label`](crate::Asm::new_label).
+///
+/// Unlike a [Label], the identifier can be copied, eg to store it in maps keyed by guest
+/// addresses. It must only be used with the assembler which created it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct LabelId(usize);
+
+impl LabelId {
+    /// Create the identifier of the label at index `idx` of the assembler.
+    pub(crate) fn new(idx: usize) -> LabelId {
+        LabelId(idx)
+    }
+
+    /// Get the index of the label in the assembler.
+    pub(crate) fn idx(self) -> usize {
+        self.0
+    }
+}
+
+/// Kind of a relocation which refers to a [Label].
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RelocKind {
+    /// 8 bit displacement relative to the end of the displacement.
+    Rel8,
+    /// 32 bit displacement relative to the end of the displacement.
+    Rel32,
+    /// 32 bit offset relative to the code offset `base`, eg the start of a jump table.
+    Table(usize),
 }
 
 impl Label {
@@ -35,7 +68,7 @@ impl Label {
     pub fn new() -> Label {
         Label {
             location: None,
-            offsets: HashSet::new(),
+            pending: None,
         }
     }
 
@@ -51,22 +84,11 @@ impl Label {
         self.location = Some(loc);
     }
 
-    /// Record an offset that must be patched with the label location.
-    pub(crate) fn record_offset(&mut self, off: usize) {
-        self.offsets.insert(off);
-    }
-
     /// Get the location of the lable if already bound, `None` else.
     pub(crate) fn location(&self) -> Option<usize> {
         self.location
     }
 
-    /// Get the offsets which refer to the label. These are used to patch the jump instructions to
-    /// the label location.
-    pub(crate) fn offsets_mut(&mut self) -> &mut HashSet<usize> {
-        &mut self.offsets
-    }
-
     /// Check whether the label is bound to a location.
     const fn is_bound(&self) -> bool {
         self.location.is_some()
@@ -78,6 +100,94 @@ impl Drop for Label {
         // Ensure the label was bound when it is dropped.
         assert!(self.is_bound());
         // Ensure all offsets have been patched when the label is dropped.
-        assert!(self.offsets.is_empty());
+        assert!(self.pending.is_none());
+    }
+}
+
+/// A pending relocation, linked to the next relocation referring to the same label.
+#[derive(Clone, Copy)]
+struct Reloc {
+    off: usize,
+    kind: RelocKind,
+    next: Option<u32>,
+}
+
+/// Pending relocations of all labels of an assembler.
+///
+/// The relocations are stored as singly linked list per label in a single vector, where the
+/// [Label] only holds the list head. Entries of resolved relocations are reused, such that
+/// creating and resolving many labels does not allocate.
+#[derive(Default)]
+pub(crate) struct Relocs {
+    entries: Vec<Reloc>,
+    /// Head of the list of free entries.
+    free: Option<u32>,
+}
+
+impl Relocs {
+    /// Record a relocation of `kind` at the code offset `off` referring to `label`.
+    pub(crate) fn push(&mut self, label: &mut Label, off: usize, kind: RelocKind) {
+        let reloc = Reloc {
+            off,
+            kind,
+            next: label.pending,
+        };
+        let idx = match self.free {
+            Some(idx) => {
+                let slot = &mut self.entries[idx as usize];
+                self.free = slot.next;
+                *slot = reloc;
+                idx
+            }
+            None => {
+                let idx = u32::try_from(self.entries.len()).expect("Too many relocations");
+                self.entries.push(reloc);
+                idx
+            }
+        };
+        label.pending = Some(idx);
+    }
+
+    /// Remove the most recently recorded pending relocation of `label` and get its code offset
+    /// and kind, `None` if there is no pending relocation.
+    pub(crate) fn pop(&mut self, label: &mut Label) -> Option<(usize, RelocKind)> {
+        let idx = label.pending?;
+        let slot = &mut self.entries[idx as usize];
+        let Reloc { off, kind, next } = *slot;
+        slot.next = self.free;
+        self.free = Some(idx);
+        label.pending = next;
+        Some((off, kind))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_relocs_reuse() {
+        let mut relocs = Relocs::default();
+        let mut l1 = Label::new();
+        let mut l2 = Label::new();
+
+        relocs.push(&mut l1, 1, RelocKind::Rel8);
+        relocs.push(&mut l1, 2, RelocKind::Rel32);
+        relocs.push(&mut l2, 3, RelocKind::Rel32);
+        assert!(matches!(relocs.pop(&mut l1), Some((2, RelocKind::Rel32))));
+        assert!(matches!(relocs.pop(&mut l1), Some((1, RelocKind::Rel8))));
+        assert!(relocs.pop(&mut l1).is_none());
+
+        // Entries of resolved relocations are reused.
+        relocs.push(&mut l1, 4, RelocKind::Table(0));
+        assert_eq!(relocs.entries.len(), 3);
+        assert!(matches!(
+            relocs.pop(&mut l1),
+            Some((4, RelocKind::Table(0)))
+        ));
+        assert!(matches!(relocs.pop(&mut l2), Some((3, RelocKind::Rel32))));
+
+        l1.bind(0);
+        l2.bind(0);
     }
 }
@@ -2,9 +2,7 @@
 //!
 //! The following is an fibonacci example implementation.
 //! ```rust
-//! use juicebox_asm::{Asm, Reg64, Imm64, Label};
-//! use juicebox_asm::insn::*;
-//! use juicebox_asm::Runtime;
+//! use juicebox_asm::prelude::*;
 //!
 //! const fn fib_rs(n: u64) -> u64 {
 //!     match n {
@@ -74,18 +72,96 @@
 //! ```
 
 mod asm;
+mod atomic;
+mod blob;
+mod block;
+mod canary;
+mod cond;
+mod cpu;
+mod desc;
 mod disasm;
+mod dispatch;
+mod endian;
+mod entries;
+mod export;
 mod imm;
+mod inline;
+mod int128;
+mod isel;
+mod jump_table;
 mod label;
 mod mem;
+mod operand;
+mod publish;
+mod redzone;
 mod reg;
 mod rt;
+mod shadow;
+mod shared;
+mod stats;
+mod sync;
+mod syntax;
+mod template;
+mod threaded;
+mod tier;
+mod trap;
+mod watch;
 
 pub mod insn;
+pub mod prelude;
 
-pub use asm::Asm;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+
+#[cfg(feature = "guard")]
+pub mod guard;
+
+#[cfg(feature = "ir")]
+pub mod ir;
+
+#[cfg(feature = "jitdump")]
+pub mod jitdump;
+
+#[cfg(feature = "sampler")]
+pub mod sampler;
+
+#[cfg(feature = "telemetry")]
+pub mod telemetry;
+
+#[cfg(feature = "valgrind")]
+pub mod valgrind;
+
+#[cfg(feature = "vtune")]
+pub mod vtune;
+
+pub use asm::{Asm, CodeTooLarge, Growth};
+pub use blob::Reloc;
+pub use block::{BlockAsm, BlockId, Terminator};
+pub use canary::{Canary, CanaryFail, CanaryHook};
+pub use cond::Cond;
+pub use cpu::CpuFeatures;
+pub use desc::{Descriptors, FunctionDescriptor};
+pub use entries::EntryPoints;
 pub use imm::{Imm16, Imm32, Imm64, Imm8};
-pub use label::Label;
+pub use inline::{HelperId, Inliner};
+pub use int128::RegPair;
+pub use isel::{Len, Sel, UNROLL_THRESHOLD};
+pub use label::{Label, LabelId};
 pub use mem::{Mem16, Mem32, Mem64, Mem8};
-pub use reg::{Reg16, Reg32, Reg64, Reg8};
+pub use operand::{InvalidInsn, Operand};
+pub use publish::Entry;
+pub use redzone::{Redzone, RedzoneHook};
+pub use reg::{Abi, Reg16, Reg32, Reg64, Reg8};
+#[cfg(any(feature = "vtune", feature = "jitdump"))]
+pub use rt::LineInfo;
 pub use rt::Runtime;
+pub use shadow::ShadowStack;
+pub use shared::SharedRuntime;
+pub use stats::{Count, Stats};
+pub use sync::CodeSync;
+pub use syntax::{Att, Syntax};
+pub use template::{Hole, Template};
+pub use threaded::DispatchTable;
+pub use tier::HotHook;
+pub use trap::{TrapSite, TrapTable};
+pub use watch::{Watch, WatchHook};
@@ -1,9 +1,11 @@
 //! Definition of different addressing modes and memory operande used as input
 //! and ouput operands in various instructions.
 
-use crate::Reg64;
+use std::fmt;
 
-#[derive(Clone, Copy)]
+use crate::{Att, Reg64};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub(crate) enum AddrMode {
     /// An indirect memory operand, eg `mov [rax], rcx`.
     Indirect,
@@ -11,6 +13,9 @@ pub(crate) enum AddrMode {
     IndirectDisp,
     /// An indirect memory operand in the form base + index, eg `mov [rax + rcx], rdx`.
     IndirectBaseIndex,
+    /// An indirect memory operand in the form base + index * scale + displacement, eg
+    /// `lea rax, [rbx + rcx * 4 + 0x8]`.
+    IndirectBaseIndexScaleDisp,
 }
 
 /// Trait to interact with memory operands.
@@ -24,21 +29,29 @@ pub(crate) trait Mem {
     /// Get the index register of the memory operand.
     fn index(&self) -> Reg64;
 
+    /// Get the scale of the index register of the memory operand.
+    fn scale(&self) -> u8;
+
     /// Get the displacement of the memory operand.
     fn disp(&self) -> i32;
 
     /// Check if memory operand is 64 bit.
     fn is_64() -> bool;
+
+    /// Get the size in bytes of the memory accessed by the operand.
+    fn size() -> usize;
 }
 
 macro_rules! impl_mem {
-    ($(#[$doc:meta] $name:ident)+) => {
+    ($(#[$doc:meta] $name:ident, $ptr:literal, $size:literal)+) => {
         $(
         #[$doc]
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
         pub struct $name {
             mode: AddrMode,
             base: Reg64,
             index: Reg64,
+            scale: u8,
             disp: i32,
         }
 
@@ -55,6 +68,10 @@ macro_rules! impl_mem {
                 self.index
             }
 
+            fn scale(&self) -> u8 {
+                self.scale
+            }
+
             fn disp(&self) -> i32 {
                 self.disp
             }
@@ -63,6 +80,60 @@ macro_rules! impl_mem {
                 use std::any::TypeId;
                 TypeId::of::<Self>() == TypeId::of::<Mem64>()
             }
+
+            fn size() -> usize {
+                $size
+            }
+        }
+
+        impl fmt::Display for $name {
+            /// Format the memory operand in Intel syntax, eg `qword ptr [rax+0x10]`.
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{} ptr [{}", $ptr, self.base)?;
+                match self.mode {
+                    AddrMode::Indirect => {}
+                    AddrMode::IndirectDisp if self.disp < 0 => {
+                        write!(f, "-{:#x}", self.disp.unsigned_abs())?
+                    }
+                    AddrMode::IndirectDisp => write!(f, "+{:#x}", self.disp)?,
+                    AddrMode::IndirectBaseIndex => write!(f, "+{}", self.index)?,
+                    AddrMode::IndirectBaseIndexScaleDisp => {
+                        write!(f, "+{}*{}", self.index, self.scale)?;
+                        if self.disp < 0 {
+                            write!(f, "-{:#x}", self.disp.unsigned_abs())?
+                        } else {
+                            write!(f, "+{:#x}", self.disp)?
+                        }
+                    }
+                }
+                f.write_str("]")
+            }
+        }
+
+        impl fmt::Display for Att<$name> {
+            /// Format the memory operand in AT&T syntax, eg `0x10(%rax)`. The operand size is
+            /// expressed by the instruction suffix in AT&T syntax and therefore not printed.
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                let m = &self.0;
+                match m.mode {
+                    AddrMode::Indirect => write!(f, "({})", Att(m.base)),
+                    AddrMode::IndirectDisp if m.disp < 0 => {
+                        write!(f, "-{:#x}({})", m.disp.unsigned_abs(), Att(m.base))
+                    }
+                    AddrMode::IndirectDisp => write!(f, "{:#x}({})", m.disp, Att(m.base)),
+                    AddrMode::IndirectBaseIndex => {
+                        write!(f, "({},{})", Att(m.base), Att(m.index))
+                    }
+                    AddrMode::IndirectBaseIndexScaleDisp => {
+                        if m.disp < 0 {
+                            write!(f, "-{:#x}", m.disp.unsigned_abs())?
+                        } else {
+                            write!(f, "{:#x}", m.disp)?
+                        }
+                        write!(f, "({},{},{})", Att(m.base), Att(m.index), m.scale)
+                    }
+                }
+            }
         }
 
         impl $name {
@@ -73,6 +144,7 @@ macro_rules! impl_mem {
                     mode: AddrMode::Indirect,
                     base,
                     index: Reg64::rax, /* zero index */
+                    scale: 1,
                     disp: 0,
                 }
             }
@@ -85,6 +157,7 @@ macro_rules! impl_mem {
                     mode: AddrMode::IndirectDisp,
                     base,
                     index: Reg64::rax, /* zero index */
+                    scale: 1,
                     disp,
                 }
             }
@@ -96,9 +169,36 @@ macro_rules! impl_mem {
                     mode: AddrMode::IndirectBaseIndex,
                     base,
                     index,
+                    scale: 1,
                     disp: 0,
                 }
             }
+
+            /// Create a memory operand with `base + index * scale + displacement` addressing
+            /// mode.
+            /// For example `lea rax, [rbx + rcx * 4 + 0x8]`.
+            ///
+            /// # Panics
+            ///
+            /// Panics if `scale` is not one of `1, 2, 4, 8`.
+            pub fn indirect_base_index_scale_disp(
+                base: Reg64,
+                index: Reg64,
+                scale: u8,
+                disp: i32,
+            ) -> Self {
+                assert!(
+                    matches!(scale, 1 | 2 | 4 | 8),
+                    "Scale must be 1, 2, 4 or 8"
+                );
+                Self {
+                    mode: AddrMode::IndirectBaseIndexScaleDisp,
+                    base,
+                    index,
+                    scale,
+                    disp,
+                }
+            }
         }
         )+
     }
@@ -106,11 +206,102 @@ macro_rules! impl_mem {
 
 impl_mem!(
     /// A memory operand with `byte` size (8 bit).
-    Mem8
+    Mem8, "byte", 1
     /// A memory operand with `word` size (16 bit).
-    Mem16
+    Mem16, "word", 2
     /// A memory operand with `dword` size (32 bit).
-    Mem32
+    Mem32, "dword", 4
     /// A memory operand with `qword` size (64 bit).
-    Mem64
+    Mem64, "qword", 8
 );
+
+macro_rules! impl_mem_from {
+    ($($from:ident => $($to:ident),+;)+) => {
+        $($(
+        impl From<$from> for $to {
+            /// Convert the memory operand to another size, addressing the same memory.
+            fn from(m: $from) -> $to {
+                $to {
+                    mode: m.mode,
+                    base: m.base,
+                    index: m.index,
+                    scale: m.scale,
+                    disp: m.disp,
+                }
+            }
+        }
+        )+)+
+    }
+}
+
+impl_mem_from!(
+    Mem8 => Mem16, Mem32, Mem64;
+    Mem16 => Mem8, Mem32, Mem64;
+    Mem32 => Mem8, Mem16, Mem64;
+    Mem64 => Mem8, Mem16, Mem32;
+);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Reg64::*;
+
+    #[test]
+    fn test_from() {
+        let m = Mem64::indirect_base_index_scale_disp(rbx, rcx, 4, 8);
+        assert_eq!(Mem32::from(m).to_string(), "dword ptr [rbx+rcx*4+0x8]");
+        assert_eq!(Mem8::from(Mem16::indirect(rax)), Mem8::indirect(rax));
+        assert_eq!(Mem64::from(Mem32::from(m)), m);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Mem8::indirect(rax).to_string(), "byte ptr [rax]");
+        assert_eq!(
+            Mem16::indirect_disp(r12, 0x10).to_string(),
+            "word ptr [r12+0x10]"
+        );
+        assert_eq!(
+            Mem32::indirect_disp(rbp, -8).to_string(),
+            "dword ptr [rbp-0x8]"
+        );
+        assert_eq!(
+            Mem64::indirect_disp(rsi, i32::MIN).to_string(),
+            "qword ptr [rsi-0x80000000]"
+        );
+        assert_eq!(
+            Mem64::indirect_base_index(rdi, r9).to_string(),
+            "qword ptr [rdi+r9]"
+        );
+        assert_eq!(
+            Mem64::indirect_base_index_scale_disp(rbx, rcx, 4, 8).to_string(),
+            "qword ptr [rbx+rcx*4+0x8]"
+        );
+        assert_eq!(
+            Mem32::indirect_base_index_scale_disp(r13, r12, 8, -0x10).to_string(),
+            "dword ptr [r13+r12*8-0x10]"
+        );
+    }
+
+    #[test]
+    fn test_display_att() {
+        assert_eq!(Att(Mem8::indirect(rax)).to_string(), "(%rax)");
+        assert_eq!(
+            Att(Mem16::indirect_disp(r12, 0x10)).to_string(),
+            "0x10(%r12)"
+        );
+        assert_eq!(Att(Mem32::indirect_disp(rbp, -8)).to_string(), "-0x8(%rbp)");
+        assert_eq!(
+            Att(Mem64::indirect_base_index(rdi, r9)).to_string(),
+            "(%rdi,%r9)"
+        );
+        assert_eq!(
+            Att(Mem64::indirect_base_index_scale_disp(rbx, rcx, 4, 8)).to_string(),
+            "0x8(%rbx,%rcx,4)"
+        );
+        assert_eq!(
+            Att(Mem32::indirect_base_index_scale_disp(r13, r12, 8, -0x10)).to_string(),
+            "-0x10(%r13,%r12,8)"
+        );
+    }
+}
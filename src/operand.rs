@@ -0,0 +1,215 @@
+//! Type erased instruction operands, to emit instructions selected at runtime, eg read from a data
+//! file, see [`Asm::emit_insn`].
+
+use crate::{
+    Asm, Cond, Imm16, Imm32, Imm64, Imm8, Label, LabelId, Mem16, Mem32, Mem64, Mem8, Reg16, Reg32,
+    Reg64, Reg8,
+};
+
+macro_rules! impl_operand {
+    ($($ty:ident),+ $(,)?) => {
+        /// Type erased instruction operand, see [`Asm::emit_insn`].
+        pub enum Operand<'a> {
+            $(
+                #[doc = concat!("[`", stringify!($ty), "`] operand.")]
+                $ty($ty),
+            )+
+            /// [`Label`] operand.
+            Label(&'a mut Label),
+        }
+
+        impl Operand<'_> {
+            /// Get the operand type name as used in the [`FORMS`](crate::insn::FORMS) table, eg
+            /// `Reg64`.
+            pub fn kind(&self) -> &'static str {
+                match self {
+                    $( Operand::$ty(_) => stringify!($ty), )+
+                    Operand::Label(_) => "Label",
+                }
+            }
+        }
+
+        $(
+            impl From<$ty> for Operand<'_> {
+                fn from(op: $ty) -> Self {
+                    Operand::$ty(op)
+                }
+            }
+        )+
+    };
+}
+
+impl_operand!(
+    Reg64, Reg32, Reg16, Reg8, Mem64, Mem32, Mem16, Mem8, Imm64, Imm32, Imm16, Imm8, Cond, LabelId,
+);
+
+impl<'a> From<&'a mut Label> for Operand<'a> {
+    fn from(op: &'a mut Label) -> Self {
+        Operand::Label(op)
+    }
+}
+
+/// Error of an instruction form which is not supported, see [`Asm::emit_insn`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidInsn {
+    /// Instruction mnemonic, eg `mov`.
+    pub mnemonic: String,
+    /// Operand type names, eg `["Imm64", "Reg64"]`.
+    pub operands: Vec<&'static str>,
+}
+
+impl InvalidInsn {
+    fn new(mnemonic: &str, operands: &[Operand]) -> InvalidInsn {
+        InvalidInsn {
+            mnemonic: mnemonic.to_string(),
+            operands: operands.iter().map(Operand::kind).collect(),
+        }
+    }
+}
+
+impl std::fmt::Display for InvalidInsn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Unsupported instruction form: {} {}",
+            self.mnemonic,
+            self.operands.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for InvalidInsn {}
+
+impl Asm {
+    /// Emit the instruction `mnemonic` with the type erased `operands`, which is checked at
+    /// runtime against the supported instruction forms, see [`FORMS`](crate::insn::FORMS).
+    ///
+    /// The operands are mutable as [`Label`] operands are bound or record their use.
+    ///
+    /// ```rust
+    /// use juicebox_asm::{Asm, Imm64, Label, Operand, Reg64::*};
+    ///
+    /// let mut asm = Asm::new();
+    /// let mut lbl = Label::new();
+    /// asm.emit_insn("mov", &mut [rax.into(), Imm64::from(1).into()]).unwrap();
+    /// asm.emit_insn("jmp", &mut [Operand::Label(&mut lbl)]).unwrap();
+    /// asm.bind(&mut lbl);
+    /// asm.emit_insn("ret", &mut []).unwrap();
+    ///
+    /// let err = asm.emit_insn("mov", &mut [Imm64::from(1).into(), rax.into()]);
+    /// assert_eq!(err.unwrap_err().to_string(), "Unsupported instruction form: mov Imm64, Reg64");
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as the instruction emitted for the form.
+    pub fn emit_insn(
+        &mut self,
+        mnemonic: &str,
+        operands: &mut [Operand],
+    ) -> Result<(), InvalidInsn> {
+        include!(concat!(env!("OUT_DIR"), "/emit.rs"));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::insn::{Mov, FORMS};
+    use crate::CpuFeatures;
+    use Reg64::*;
+
+    #[test]
+    fn test_emit() {
+        let mut asm = Asm::new();
+        let mut lbl = Label::new();
+        asm.emit_insn("mov", &mut [rax.into(), Mem64::indirect(rdi).into()])
+            .unwrap();
+        asm.emit_insn("jcc", &mut [Cond::E.into(), (&mut lbl).into()])
+            .unwrap();
+        asm.emit_insn(
+            "imul",
+            &mut [rax.into(), rcx.into(), Imm8::from(2i8).into()],
+        )
+        .unwrap();
+        asm.bind(&mut lbl);
+        asm.emit_insn("ret", &mut []).unwrap();
+
+        let mut exp = Asm::new();
+        let mut lbl = Label::new();
+        exp.mov(rax, Mem64::indirect(rdi));
+        exp.jcc(Cond::E, &mut lbl);
+        crate::insn::Imul::imul(&mut exp, (rax, rcx, Imm8::from(2i8)));
+        exp.bind(&mut lbl);
+        exp.ret();
+
+        assert_eq!(asm.into_code(), exp.into_code());
+    }
+
+    #[test]
+    fn test_invalid() {
+        let mut asm = Asm::new();
+        let err = asm
+            .emit_insn("mov", &mut [Imm64::from(1).into(), rax.into()])
+            .unwrap_err();
+        assert_eq!(err.mnemonic, "mov");
+        assert_eq!(err.operands, ["Imm64", "Reg64"]);
+
+        let err = asm.emit_insn("foo", &mut []).unwrap_err();
+        assert_eq!(err.to_string(), "Unsupported instruction form: foo ");
+        let err = asm.emit_insn("ret", &mut [rax.into()]).unwrap_err();
+        assert_eq!(err.to_string(), "Unsupported instruction form: ret Reg64");
+        assert!(asm.into_code().is_empty());
+    }
+
+    /// Check that all forms of the [FORMS] table can be emitted.
+    #[test]
+    fn test_all_forms() {
+        let mut features = CpuFeatures::baseline();
+        features.movbe = true;
+        features.clflushopt = true;
+        features.clwb = true;
+        features.lzcnt = true;
+        features.bmi1 = true;
+        features.popcnt = true;
+
+        for form in FORMS {
+            let mut asm = Asm::new();
+            asm.set_cpu_features(features);
+            let mut lbl = Label::new();
+            let lbl_id = asm.new_label();
+            let mut ops: Vec<Operand> = Vec::new();
+            let mut lbl_op = Some(&mut lbl);
+            for op in form.operands {
+                ops.push(match *op {
+                    "Reg64" => rcx.into(),
+                    "Reg32" => Reg32::ecx.into(),
+                    "Reg16" => Reg16::cx.into(),
+                    "Reg8" => Reg8::cl.into(),
+                    "Mem64" => Mem64::indirect(rdi).into(),
+                    "Mem32" => Mem32::indirect(rdi).into(),
+                    "Mem16" => Mem16::indirect(rdi).into(),
+                    "Mem8" => Mem8::indirect(rdi).into(),
+                    "Imm64" => Imm64::from(1).into(),
+                    "Imm32" => Imm32::from(1).into(),
+                    "Imm16" => Imm16::from(1u16).into(),
+                    "Imm8" => Imm8::from(1u8).into(),
+                    "Cond" => Cond::E.into(),
+                    "Label" => Operand::Label(lbl_op.take().expect("Single label operand")),
+                    "LabelId" => lbl_id.into(),
+                    op => panic!("Unexpected operand type {}", op),
+                });
+            }
+            let kinds: Vec<_> = ops.iter().map(Operand::kind).collect();
+            assert_eq!(kinds, form.operands);
+
+            asm.emit_insn(form.mnemonic, &mut ops)
+                .unwrap_or_else(|e| panic!("{}", e));
+            drop(ops);
+            asm.bind(&mut lbl);
+            asm.bind_label(lbl_id);
+            assert!(!asm.into_code().is_empty(), "{:?}", form);
+        }
+    }
+}
@@ -0,0 +1,43 @@
+//! Re-export of the assembler, the runtime, all operand types and all instruction traits, to
+//! import everything needed to assemble and run code with a single glob.
+//!
+//! ```rust
+//! use juicebox_asm::prelude::*;
+//!
+//! let mut asm = Asm::new();
+//! asm.mov(Reg64::rax, Mem64::indirect(Reg64::rdi));
+//! asm.inc(Reg64::rax);
+//! asm.ret();
+//!
+//! let mut rt = Runtime::new();
+//! let inc = unsafe { rt.add_code::<extern "C" fn(&u64) -> u64>(asm.into_code()) };
+//! assert_eq!(inc(&41), 42);
+//! ```
+
+pub use crate::insn::prelude::*;
+pub use crate::{
+    Asm, Cond, Imm16, Imm32, Imm64, Imm8, Label, Mem16, Mem32, Mem64, Mem8, Reg16, Reg32, Reg64,
+    Reg8, Runtime,
+};
+
+#[cfg(test)]
+mod test {
+    /// Check that all instruction traits are re-exported by the instruction prelude.
+    #[test]
+    fn test_prelude() {
+        let src = include_str!("insn.rs");
+        let (traits, prelude) = src.split_once("pub mod prelude").unwrap();
+        let prelude = prelude.split_once("};").unwrap().0;
+        for line in traits.lines() {
+            if let Some(name) = line.strip_prefix("pub trait ") {
+                let name = name.split(['<', ' ']).next().unwrap();
+                assert!(
+                    prelude.contains(&format!(" {},", name))
+                        || prelude.contains(&format!("{}}}", name)),
+                    "Trait {} missing in the prelude",
+                    name
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,218 @@
+//! Atomic publication of function entry points, to swap a recompiled function without stopping
+//! the threads executing it.
+//!
+//! An [Entry] holds the current entry point of a function in an atomic slot. Jitted callers call
+//! indirectly through the slot with [`Asm::call_entry`], and Rust callers obtain the entry point
+//! with [`Entry::get`]. [`Entry::publish`] swaps the entry point atomically; callers either see
+//! the old or the new entry point, never a torn one.
+//!
+//! Executions which already entered the old code continue to run it. This is safe as the
+//! [`Runtime`](crate::Runtime) never reuses code memory, the old code stays valid as long as the
+//! runtime is alive.
+//!
+//! Each publication increments the generation of the entry. The pair of entry point and
+//! generation is read consistently with a seqlock, see [`Entry::load`].
+
+use std::sync::atomic::{AtomicPtr, AtomicU64, Ordering};
+
+use crate::insn::{Call, Mov};
+use crate::{Asm, Imm64, Mem64, Reg64};
+
+/// Atomic slot holding the entry point, which is embedded as absolute address in jitted callers.
+struct Slot {
+    ptr: AtomicPtr<u8>,
+    /// Sequence counter, odd while a publication is in progress.
+    seq: AtomicU64,
+}
+
+/// Atomically swappable entry point of a function.
+///
+/// ```rust
+/// use juicebox_asm::{Asm, Entry, Imm64, Reg64, Runtime};
+/// use juicebox_asm::insn::Mov;
+///
+/// let mut rt = Runtime::new();
+/// let mut asm = Asm::new();
+/// asm.mov(Reg64::rax, Imm64::from(1));
+/// asm.ret();
+/// let v1 = unsafe { rt.add_code::<*const u8>(asm.into_code()) };
+///
+/// let entry = Entry::new(v1);
+/// assert_eq!(unsafe { entry.get::<extern "C" fn() -> u64>() }(), 1);
+///
+/// let mut asm = Asm::new();
+/// asm.mov(Reg64::rax, Imm64::from(2));
+/// asm.ret();
+/// let v2 = unsafe { rt.add_code::<*const u8>(asm.into_code()) };
+///
+/// assert_eq!(entry.publish(v2), 1);
+/// assert_eq!(unsafe { entry.get::<extern "C" fn() -> u64>() }(), 2);
+/// ```
+pub struct Entry {
+    /// Boxed to keep the slot address stable, as it is embedded into jitted callers.
+    slot: Box<Slot>,
+}
+
+impl Entry {
+    /// Create a new entry with the initial entry point `ptr` and generation `0`.
+    pub fn new(ptr: *const u8) -> Entry {
+        Entry {
+            slot: Box::new(Slot {
+                ptr: AtomicPtr::new(ptr.cast_mut()),
+                seq: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    /// Atomically publish the new entry point `ptr` and get the new generation.
+    ///
+    /// Concurrent publications are serialized.
+    pub fn publish(&self, ptr: *const u8) -> u64 {
+        let slot = &self.slot;
+
+        // Acquire the write side by making the sequence odd.
+        let mut seq = slot.seq.load(Ordering::Relaxed);
+        loop {
+            if seq % 2 == 1 {
+                std::hint::spin_loop();
+                seq = slot.seq.load(Ordering::Relaxed);
+                continue;
+            }
+            match slot
+                .seq
+                .compare_exchange_weak(seq, seq + 1, Ordering::Acquire, Ordering::Relaxed)
+            {
+                Ok(_) => break,
+                Err(cur) => seq = cur,
+            }
+        }
+
+        slot.ptr.store(ptr.cast_mut(), Ordering::Release);
+        slot.seq.store(seq + 2, Ordering::Release);
+        (seq + 2) / 2
+    }
+
+    /// Get the current entry point together with its generation, both read consistently.
+    pub fn load(&self) -> (*const u8, u64) {
+        let slot = &self.slot;
+        loop {
+            let seq1 = slot.seq.load(Ordering::Acquire);
+            if seq1 % 2 == 1 {
+                std::hint::spin_loop();
+                continue;
+            }
+            let ptr = slot.ptr.load(Ordering::Acquire);
+            let seq2 = slot.seq.load(Ordering::Acquire);
+            if seq1 == seq2 {
+                return (ptr, seq1 / 2);
+            }
+        }
+    }
+
+    /// Get the current generation, incremented by each [`Entry::publish`].
+    pub fn generation(&self) -> u64 {
+        self.load().1
+    }
+
+    /// Get the address of the atomic slot holding the entry point, to embed it into jitted code.
+    pub(crate) fn slot(&self) -> *const AtomicPtr<u8> {
+        &self.slot.ptr
+    }
+
+    /// Get the current entry point reinterpreted as `F`.
+    ///
+    /// # Safety
+    ///
+    /// The caller must guarantee that the published code can be interpreted as `F`.
+    pub unsafe fn get<F>(&self) -> F {
+        let ptr = self.slot.ptr.load(Ordering::Acquire);
+        unsafe { std::mem::transmute_copy(&ptr) }
+    }
+}
+
+impl Asm {
+    /// Emit an indirect call through the [Entry], always calling the currently published entry
+    /// point.
+    ///
+    /// Clobbers `rax`. The address of the entry slot is embedded in the emitted code, hence the
+    /// `entry` must outlive the emitted code.
+    pub fn call_entry(&mut self, entry: &Entry) {
+        let slot = entry.slot();
+
+        // An aligned 8 byte load is atomic on x64.
+        self.mov(Reg64::rax, Imm64::from(slot as usize));
+        self.mov(Reg64::rax, Mem64::indirect(Reg64::rax));
+        self.call(Reg64::rax);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::insn::{Pop, Push};
+    use crate::Runtime;
+
+    fn add_const(rt: &mut Runtime, v: u64) -> *const u8 {
+        let mut asm = Asm::new();
+        asm.mov(Reg64::rax, Imm64::from(v));
+        asm.ret();
+        unsafe { rt.add_code::<*const u8>(asm.into_code()) }
+    }
+
+    #[test]
+    fn test_publish() {
+        let mut rt = Runtime::new();
+        let f1 = add_const(&mut rt, 1);
+        let f2 = add_const(&mut rt, 2);
+
+        let entry = Entry::new(f1);
+        assert_eq!(entry.load(), (f1, 0));
+        assert_eq!(entry.publish(f2), 1);
+        assert_eq!(entry.load(), (f2, 1));
+        assert_eq!(entry.publish(f1), 2);
+        assert_eq!(entry.generation(), 2);
+    }
+
+    #[test]
+    fn test_call_entry() {
+        let mut rt = Runtime::new();
+        let f1 = add_const(&mut rt, 1);
+        let f2 = add_const(&mut rt, 2);
+        let entry = Entry::new(f1);
+
+        // Keep the stack 16 byte aligned at the call.
+        let mut asm = Asm::new();
+        asm.push(Reg64::rbx);
+        asm.call_entry(&entry);
+        asm.pop(Reg64::rbx);
+        asm.ret();
+        let caller = unsafe { rt.add_code::<extern "C" fn() -> u64>(asm.into_code()) };
+
+        assert_eq!(caller(), 1);
+        entry.publish(f2);
+        assert_eq!(caller(), 2);
+    }
+
+    #[test]
+    fn test_concurrent() {
+        // Publish fake entry points which encode their generation, readers must never observe an
+        // inconsistent pair.
+        let entry = Entry::new(std::ptr::without_provenance(0));
+
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    for _ in 0..10_000 {
+                        let (ptr, gen) = entry.load();
+                        assert_eq!(ptr.addr() as u64, gen);
+                    }
+                });
+            }
+            s.spawn(|| {
+                for gen in 1..=10_000 {
+                    assert_eq!(entry.publish(std::ptr::without_provenance(gen)), gen as u64);
+                }
+            });
+        });
+    }
+}
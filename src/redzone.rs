@@ -0,0 +1,457 @@
+//! Redzone bounds checks of the memory accesses emitted by the helpers of the assembler.
+//!
+//! When a [Redzone] is enabled on the [Asm] with [`Asm::set_redzone`], the memory helpers
+//! [`Asm::emit_memcpy`], [`Asm::emit_memset`], [`Asm::memcpy_small`] and [`Asm::memset_small`]
+//! as well as the canary frame of [`Asm::canary_prologue`] and [`Asm::canary_epilogue`] check the
+//! accessed memory against the valid ranges registered by the user before accessing it, similar
+//! to the redzones of an address sanitizer. An access which is not fully contained in one of the
+//! valid ranges invokes the [RedzoneHook] with the address and length of the access.
+//!
+//! The check preserves all general purpose registers and the flags, and does not touch the red
+//! zone below the stack pointer. Accesses of zero length are always valid. The checks are meant
+//! as debug mode, instructions emitted directly are not checked, see [`Watch`](crate::Watch) for
+//! instrumenting those.
+//!
+//! ```rust
+//! use std::ffi::c_void;
+//! use juicebox_asm::{Asm, Redzone, Reg64::*, Runtime};
+//!
+//! extern "C" fn hook(_data: *mut c_void, addr: usize, len: usize) {
+//!     eprintln!("Invalid access of {} bytes at {:#x}", len, addr);
+//!     std::process::abort();
+//! }
+//!
+//! let src = [1u8; 32];
+//! let mut dst = [0u8; 32];
+//!
+//! let mut redzone = Redzone::new(hook, std::ptr::null_mut());
+//! redzone.add_range(src.as_ptr() as usize..src.as_ptr() as usize + src.len());
+//! redzone.add_range(dst.as_ptr() as usize..dst.as_ptr() as usize + dst.len());
+//!
+//! let mut asm = Asm::new();
+//! asm.set_redzone(Some(redzone));
+//! asm.emit_memcpy(rdi, rsi, rdx);
+//! asm.ret();
+//!
+//! let mut rt = Runtime::new();
+//! let f = unsafe { rt.add_code::<extern "C" fn(*mut u8, *const u8, usize)>(asm.into_code()) };
+//! f(dst.as_mut_ptr(), src.as_ptr(), 32);
+//!
+//! assert_eq!(dst, src);
+//! ```
+
+use std::ffi::c_void;
+use std::ops::Range;
+
+use crate::insn::{And, Call, Cmp, Lea, Mov, Pop, Push, Sub, Test};
+use crate::{Asm, Cond, Imm64, Imm8, Label, Len, Mem64, Reg64};
+
+/// Hook invoked with the user `data` for an access of `len` bytes at `addr`, which is not
+/// contained in any valid range, see [Redzone]. The hook must not return, if it returns anyway
+/// the code traps with an `ud2` instruction.
+pub type RedzoneHook = extern "C" fn(data: *mut c_void, addr: usize, len: usize);
+
+/// Size of the red zone below the stack pointer, which must not be clobbered.
+const RED_ZONE: i32 = 128;
+
+/// Registers saved around the range check, `rax` holds the accessed address and `rcx` the
+/// accessed length.
+const SAVED: [Reg64; 4] = [Reg64::rax, Reg64::rcx, Reg64::rdx, Reg64::rsi];
+
+/// Redzone configuration for checking the memory accesses of the helpers, see
+/// [`Asm::set_redzone`].
+///
+/// The valid ranges are embedded into the checks, ranges added after the code was emitted have
+/// no effect on it.
+#[derive(Clone, Debug)]
+pub struct Redzone {
+    ranges: Vec<Range<usize>>,
+    hook: RedzoneHook,
+    data: *mut c_void,
+}
+
+impl Redzone {
+    /// Create a redzone configuration without any valid ranges, invoking `hook` with the user
+    /// `data` on a violation.
+    pub fn new(hook: RedzoneHook, data: *mut c_void) -> Redzone {
+        Redzone {
+            ranges: Vec::new(),
+            hook,
+            data,
+        }
+    }
+
+    /// Add the valid range `range` of byte addresses.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is empty.
+    pub fn add_range(&mut self, range: Range<usize>) {
+        assert!(range.start < range.end, "Redzone range must not be empty");
+        self.ranges.push(range);
+    }
+
+    /// Get the valid ranges.
+    pub fn ranges(&self) -> &[Range<usize>] {
+        &self.ranges
+    }
+}
+
+impl Asm {
+    /// Enable the redzone checks of the memory helpers with `redzone` or disable them with
+    /// `None`. From now on each memory access emitted by a helper invokes the hook of the
+    /// redzone, if the access is not contained in a valid range.
+    pub fn set_redzone(&mut self, redzone: Option<Redzone>) {
+        *self.redzone_mut() = redzone;
+    }
+
+    /// Emit the check of the access of `len` bytes at the address `base + disp`, if redzone
+    /// checks are enabled. The registers hold the values right before the access.
+    pub(crate) fn redzone_check(&mut self, base: Reg64, disp: i32, len: Len) {
+        if len == Len::Const(0) {
+            return;
+        }
+        // Taking the redzone and the watch disables the instrumentation of the check.
+        let Some(redzone) = self.redzone_mut().take() else {
+            return;
+        };
+        let watch = self.watch_mut().take();
+        self.emit_redzone(&redzone, base, disp, len);
+        *self.watch_mut() = watch;
+        *self.redzone_mut() = Some(redzone);
+    }
+
+    /// Emit the check of the access against the valid ranges of `redzone`.
+    fn emit_redzone(&mut self, redzone: &Redzone, base: Reg64, disp: i32, len: Len) {
+        use Reg64::*;
+
+        let mut ok = Label::new();
+
+        self.lea(rsp, Mem64::indirect_disp(rsp, -RED_ZONE));
+        self.pushfq();
+        for r in SAVED {
+            self.push(r);
+        }
+
+        // CAST: The number of saved registers is small.
+        let pushed = RED_ZONE + 8 * (1 + SAVED.len() as i32);
+        self.redzone_load(rax, base, disp, pushed);
+        match len {
+            // CAST: usize fits into u64 on x64.
+            Len::Const(len) => self.mov(rcx, Imm64::from(len as u64)),
+            Len::Reg(len) => {
+                self.redzone_load(rcx, len, 0, pushed);
+                self.test(rcx, rcx);
+                self.jcc(Cond::E, &mut ok);
+            }
+        }
+
+        // The access [addr, addr + len) is contained in the range [start, end) iff
+        //   len <= end - start && addr - start <= (end - start) - len
+        // when computed with unsigned wrap around arithmetic.
+        for r in &redzone.ranges {
+            let span = r.end - r.start;
+            if matches!(len, Len::Const(len) if len > span) {
+                continue;
+            }
+            let mut next = Label::new();
+            self.mov(rsi, rax);
+            self.mov(rdx, Imm64::from(r.start));
+            self.sub(rsi, rdx);
+            match len {
+                Len::Const(len) => self.mov(rdx, Imm64::from(span - len)),
+                Len::Reg(_) => {
+                    self.mov(rdx, Imm64::from(span));
+                    self.sub(rdx, rcx);
+                    self.jcc(Cond::B, &mut next);
+                }
+            }
+            self.cmp(rsi, rdx);
+            self.jcc(Cond::Be, &mut ok);
+            self.bind(&mut next);
+        }
+
+        // The hook does not return, hence nothing is restored. Align the stack to 16 byte for
+        // the call.
+        self.and(rsp, Imm8::from(-16i8));
+        self.mov(rsi, rax);
+        self.mov(rdx, rcx);
+        self.mov(rdi, Imm64::from(redzone.data as usize));
+        self.mov(rax, Imm64::from(redzone.hook as usize));
+        self.call(rax);
+        self.ud2();
+
+        self.bind(&mut ok);
+        for r in SAVED.into_iter().rev() {
+            self.pop(r);
+        }
+        self.popfq();
+        self.lea(rsp, Mem64::indirect_disp(rsp, RED_ZONE));
+    }
+
+    /// Load the value `src + disp` into `dst`, where `src` holds the value before the check and
+    /// `pushed` bytes were pushed to the stack by the check since.
+    fn redzone_load(&mut self, dst: Reg64, src: Reg64, disp: i32, pushed: i32) {
+        use Reg64::rsp;
+
+        if src == rsp {
+            let disp = disp.checked_add(pushed).expect("Displacement overflow");
+            self.lea(dst, Mem64::indirect_disp(rsp, disp));
+            return;
+        }
+        let src = match SAVED.iter().position(|&r| r == src) {
+            Some(slot) => {
+                // CAST: The number of saved registers is small.
+                let slot = 8 * (SAVED.len() - 1 - slot) as i32;
+                self.mov(dst, Mem64::indirect_disp(rsp, slot));
+                dst
+            }
+            None => src,
+        };
+        if disp == 0 {
+            self.mov_if_ne(dst, src);
+        } else {
+            self.lea(dst, Mem64::indirect_disp(src, disp));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Canary, CanaryFail, Runtime};
+
+    /// Expected violation, an `addr` of `0` matches any address.
+    #[derive(Clone, Copy)]
+    struct Expect {
+        addr: usize,
+        len: usize,
+    }
+
+    extern "C" fn hook(data: *mut c_void, addr: usize, len: usize) {
+        let exp = unsafe { *data.cast::<Expect>() };
+        let code = if (exp.addr == 0 || exp.addr == addr) && exp.len == len {
+            42
+        } else {
+            1
+        };
+        unsafe { libc::_exit(code) };
+    }
+
+    fn redzone(exp: &mut Expect, ranges: &[(*const u8, usize)]) -> Redzone {
+        let mut redzone = Redzone::new(hook, (exp as *mut Expect).cast());
+        for &(start, len) in ranges {
+            redzone.add_range(start as usize..start as usize + len);
+        }
+        redzone
+    }
+
+    /// Run `f` in a forked child and get its exit code, `0` if `f` returns.
+    fn run_child(f: impl FnOnce()) -> libc::c_int {
+        match unsafe { libc::fork() } {
+            0 => {
+                f();
+                unsafe { libc::_exit(0) };
+            }
+            pid => {
+                assert!(pid > 0, "Failed to fork");
+                let mut status = 0;
+                assert_eq!(unsafe { libc::waitpid(pid, &mut status, 0) }, pid);
+                assert!(libc::WIFEXITED(status), "Child did not exit");
+                libc::WEXITSTATUS(status)
+            }
+        }
+    }
+
+    /// Compile `fn(dst, src, n)` copying with [`Asm::emit_memcpy`] and the length `len`, `n` is
+    /// passed in `rdx`.
+    fn memcpy(redzone: Redzone, len: Len) -> Vec<u8> {
+        let mut asm = Asm::new();
+        asm.set_redzone(Some(redzone));
+        asm.emit_memcpy(Reg64::rdi, Reg64::rsi, len);
+        asm.ret();
+        asm.into_code()
+    }
+
+    /// Compile `fn(dst, n)` storing `0xaa` with [`Asm::emit_memset`] and the length `len`, `n` is
+    /// passed in `rsi`.
+    fn memset(redzone: Redzone, len: Len) -> Vec<u8> {
+        let mut asm = Asm::new();
+        asm.set_redzone(Some(redzone));
+        asm.emit_memset(Reg64::rdi, 0xaa, len);
+        asm.ret();
+        asm.into_code()
+    }
+
+    type MemcpyFn = extern "C" fn(*mut u8, *const u8, usize);
+    type MemsetFn = extern "C" fn(*mut u8, usize);
+
+    #[test]
+    fn test_memcpy() {
+        let src: Vec<u8> = (0..=255).collect();
+        let mut dst = [0u8; 256];
+        let (s, d) = (src.as_ptr(), dst.as_mut_ptr());
+
+        let mut rt = Runtime::new();
+        for (len, n) in [
+            (Len::Const(0), 0),
+            (Len::Const(16), 16),
+            (Len::Const(256), 256),
+            (Len::Reg(Reg64::rdx), 0),
+            (Len::Reg(Reg64::rdx), 256),
+        ] {
+            // Valid copy into the tail of the destination.
+            let mut exp = Expect { addr: 0, len: 0 };
+            let code = memcpy(redzone(&mut exp, &[(s, 256), (d, 256)]), len);
+            let f = unsafe { rt.add_code::<MemcpyFn>(code) };
+            let status = run_child(|| {
+                f(unsafe { d.add(256 - n) }, s, n);
+                if dst[256 - n..] != src[..n] {
+                    unsafe { libc::_exit(2) };
+                }
+            });
+            assert_eq!(status, 0, "len {:?}", len);
+        }
+
+        for (len, n) in [
+            (Len::Const(16), 16),
+            (Len::Const(256), 256),
+            (Len::Reg(Reg64::rdx), 256),
+        ] {
+            // The destination overflows by one byte.
+            let mut exp = Expect {
+                addr: d as usize + 257 - n,
+                len: n,
+            };
+            let code = memcpy(redzone(&mut exp, &[(s, 256), (d, 256)]), len);
+            let f = unsafe { rt.add_code::<MemcpyFn>(code) };
+            let status = run_child(|| f(unsafe { d.add(257 - n) }, s, n));
+            assert_eq!(status, 42, "len {:?}", len);
+
+            // The source starts in front of the valid range.
+            let mut exp = Expect {
+                addr: s as usize - 1,
+                len: n,
+            };
+            let code = memcpy(redzone(&mut exp, &[(s, 256), (d, 256)]), len);
+            let f = unsafe { rt.add_code::<MemcpyFn>(code) };
+            let status = run_child(|| f(d, unsafe { s.sub(1) }, n));
+            assert_eq!(status, 42, "len {:?}", len);
+        }
+    }
+
+    #[test]
+    fn test_memset() {
+        let mut buf = [0u8; 256];
+        let b = buf.as_mut_ptr();
+
+        let mut rt = Runtime::new();
+        for (len, n) in [
+            (Len::Const(7), 7),
+            (Len::Const(200), 200),
+            (Len::Reg(Reg64::rsi), 0),
+            (Len::Reg(Reg64::rsi), 200),
+        ] {
+            let mut exp = Expect {
+                addr: b as usize + 250,
+                len: n,
+            };
+            let code = memset(redzone(&mut exp, &[(b, 256)]), len);
+            let f = unsafe { rt.add_code::<MemsetFn>(code) };
+
+            let status = run_child(|| {
+                f(unsafe { b.add(56) }, n);
+                if buf[56..56 + n].iter().any(|&v| v != 0xaa) {
+                    unsafe { libc::_exit(2) };
+                }
+            });
+            assert_eq!(status, 0, "len {:?}", len);
+
+            let status = run_child(|| f(unsafe { b.add(250) }, n));
+            assert_eq!(status, if n == 0 { 0 } else { 42 }, "len {:?}", len);
+        }
+    }
+
+    #[test]
+    fn test_no_range() {
+        let mut buf = [0u8; 8];
+        let b = buf.as_mut_ptr();
+
+        // Without any valid range each access is a violation. The address and length are held
+        // in registers saved by the check.
+        let mut exp = Expect {
+            addr: b as usize,
+            len: 8,
+        };
+        let mut asm = Asm::new();
+        asm.set_redzone(Some(redzone(&mut exp, &[])));
+        asm.mov(Reg64::rcx, Reg64::rdi);
+        asm.mov(Reg64::rax, Imm64::from(8u64));
+        asm.emit_memset(Reg64::rcx, 0, Reg64::rax);
+        asm.ret();
+
+        let mut rt = Runtime::new();
+        let f = unsafe { rt.add_code::<extern "C" fn(*mut u8)>(asm.into_code()) };
+        assert_eq!(run_child(|| f(b)), 42);
+    }
+
+    #[test]
+    fn test_canary() {
+        let canary = Canary::random(CanaryFail::Trap);
+        let compile = |redzone: Redzone| {
+            let mut asm = Asm::new();
+            asm.set_redzone(Some(redzone));
+            asm.canary_prologue(&canary);
+            asm.mov(Reg64::rax, Reg64::rdi);
+            asm.canary_epilogue(&canary);
+            asm.ret();
+            asm.into_code()
+        };
+
+        let mut rt = Runtime::new();
+        let local = 0u8;
+        let sp = &local as *const u8;
+
+        // The stack of the frame is valid.
+        let mut exp = Expect { addr: 0, len: 0 };
+        let code = compile(redzone(&mut exp, &[(unsafe { sp.sub(0x10000) }, 0x10000)]));
+        let f = unsafe { rt.add_code::<extern "C" fn(u64) -> u64>(code) };
+        assert_eq!(run_child(|| assert_eq!(f(42), 42)), 0);
+
+        // The stack of the frame is not valid.
+        let mut exp = Expect { addr: 0, len: 8 };
+        let code = compile(redzone(&mut exp, &[(sp, 1)]));
+        let f = unsafe { rt.add_code::<extern "C" fn(u64) -> u64>(code) };
+        assert_eq!(run_child(|| assert_eq!(f(42), 42)), 42);
+    }
+
+    #[test]
+    fn test_ranges() {
+        let mut exp = Expect { addr: 0, len: 0 };
+        let redzone = redzone(
+            &mut exp,
+            &[(0x1000 as *const u8, 0x1000), (0x3000 as *const u8, 1)],
+        );
+        assert_eq!(redzone.ranges(), [0x1000..0x2000, 0x3000..0x3001]);
+    }
+
+    #[test]
+    fn test_disable() {
+        let mut exp = Expect { addr: 0, len: 0 };
+        let mut asm = Asm::new();
+        asm.set_redzone(Some(redzone(&mut exp, &[])));
+        asm.set_redzone(None);
+        asm.memcpy_small(Reg64::rdi, Reg64::rsi, 1);
+
+        let mut plain = Asm::new();
+        plain.memcpy_small(Reg64::rdi, Reg64::rsi, 1);
+        assert_eq!(asm.into_code(), plain.into_code());
+    }
+
+    #[test]
+    #[should_panic = "Redzone range must not be empty"]
+    fn test_empty_range() {
+        let mut exp = Expect { addr: 0, len: 0 };
+        redzone(&mut exp, &[(std::ptr::null(), 0)]);
+    }
+}
@@ -1,5 +1,9 @@
 //! Definition of registers which are used as input operands for various instructions.
 
+use std::fmt;
+
+use crate::Att;
+
 /// Trait to interact with register operands.
 pub(crate) trait Reg {
     /// Get the raw x64 register code.
@@ -18,6 +22,12 @@ pub(crate) trait Reg {
         self.is_ext() || self.rexw()
     }
 
+    /// Check if the register is one of the high byte registers `{AH, CH, DH, BH}`, which can not
+    /// be encoded in an instruction with a `REX` byte.
+    fn is_high8(&self) -> bool {
+        false
+    }
+
     /// Check if the register requires a `SIB` byte if used as addressing operand.
     ///
     /// See [64 bit
@@ -41,17 +51,44 @@ macro_rules! enum_reg {
     (#[$doc:meta]  $name:ident, { $($reg:ident),+ $(,)? }) => {
         #[$doc]
         #[allow(non_camel_case_types)]
-        #[derive(Copy, Clone)]
+        #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
         #[repr(u8)]
         pub enum $name {
             $( $reg, )+
         }
 
-        #[cfg(test)]
+        impl fmt::Display for $name {
+            /// Format the register name in Intel syntax, eg `rax`.
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                let name = match self {
+                    $( $name::$reg => stringify!($reg), )+
+                };
+                // The low byte of the extended registers is named `rNb` in Intel syntax.
+                match name.strip_suffix('l') {
+                    Some(ext) if ext.starts_with("r") => write!(f, "{}b", ext),
+                    _ => f.write_str(name),
+                }
+            }
+        }
+
+        impl fmt::Display for Att<$name> {
+            /// Format the register name in AT&T syntax, eg `%rax`.
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "%{}", self.0)
+            }
+        }
+
         impl $name {
-            fn iter() -> impl Iterator<Item = &'static $name> {
+            /// Iterate over all registers in the order of their declaration.
+            pub fn iter() -> impl Iterator<Item = $name> {
                 use $name::*;
-                [$( $reg, )+].iter()
+                [$( $reg, )+].into_iter()
+            }
+
+            /// Get the x64 register encoding index in the range `[0:15]`, which is shared by all
+            /// sub-registers of a register, eg `0` for `al`, `ax`, `eax` and `rax`.
+            pub fn index(&self) -> u8 {
+                self.idx()
             }
         }
     };
@@ -89,6 +126,134 @@ enum_reg!(
     Reg8,         { al,  cl,  dl,  bl,  spl, bpl, sil, dil, r8l, r9l, r10l, r11l, r12l, r13l, r14l, r15l,
                           ah,  ch,  dh,  bh });
 
+macro_rules! impl_from_index {
+    ($name:ident) => {
+        impl $name {
+            /// Get the register with the encoding `index`, see [`index`](Self::index). This maps
+            /// between the register widths, eg `Reg32::from_index(Reg64::rax.index())` is `eax`.
+            ///
+            /// For [`Reg8`] the low byte register is returned, eg `spl` for the index `4`.
+            ///
+            /// # Panics
+            ///
+            /// Panics if `index` is not in the range `[0:15]`.
+            pub fn from_index(index: u8) -> $name {
+                assert!(index < 16, "Register index out of range");
+                // UNWRAP: The first 16 registers are declared in the order of their encoding.
+                $name::iter().nth(usize::from(index)).unwrap()
+            }
+        }
+    };
+}
+
+impl_from_index!(Reg64);
+impl_from_index!(Reg32);
+impl_from_index!(Reg16);
+impl_from_index!(Reg8);
+
+/// Calling conventions, to query the callee-saved registers, see [`Reg64::is_callee_saved`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Abi {
+    /// [System V AMD64 ABI](https://gitlab.com/x86-psABIs/x86-64-ABI) used on Linux.
+    SysV,
+    /// [Microsoft x64 calling
+    /// convention](https://learn.microsoft.com/en-us/cpp/build/x64-calling-convention) used on
+    /// Windows.
+    Win64,
+}
+
+impl Reg64 {
+    /// Check if the register must be preserved by a called function in the `abi`.
+    ///
+    /// ```rust
+    /// use juicebox_asm::{Abi, Reg64};
+    ///
+    /// let saved: Vec<_> = Reg64::iter().filter(|r| r.is_callee_saved(Abi::SysV)).collect();
+    /// assert_eq!(saved.len(), 7);
+    /// assert!(Reg64::rsi.is_callee_saved(Abi::Win64));
+    /// ```
+    pub fn is_callee_saved(&self, abi: Abi) -> bool {
+        use Reg64::*;
+        match abi {
+            Abi::SysV => matches!(self, rbx | rsp | rbp | r12 | r13 | r14 | r15),
+            Abi::Win64 => matches!(self, rbx | rsp | rbp | rsi | rdi | r12 | r13 | r14 | r15),
+        }
+    }
+
+    /// Check if the register may be clobbered by a called function in the `abi`, hence must be
+    /// saved by the caller if it is live across a call.
+    pub fn is_caller_saved(&self, abi: Abi) -> bool {
+        !self.is_callee_saved(abi)
+    }
+}
+
+macro_rules! impl_convert {
+    ($name:ident = $ex:ident, { $($to:ident: $reg:ident = $to_ex:ident),+ $(,)? }) => {
+        impl $name {
+            $(
+                #[doc = concat!("Get the aliasing [`", stringify!($reg), "`] register, eg `",
+                                stringify!($to_ex), "` for `", stringify!($ex), "`.")]
+                pub fn $to(&self) -> $reg {
+                    $reg::from_index(self.idx())
+                }
+            )+
+
+            #[doc = concat!("Get the low 8 bit sub-register, eg `al` for `", stringify!($ex), "`.")]
+            ///
+            /// The registers `spl`, `bpl`, `sil` and `dil` require a `REX` prefix, which can not be
+            /// combined with the high 8 bit registers, see [`to8hi`](Self::to8hi).
+            pub fn to8lo(&self) -> Reg8 {
+                Reg8::from_index(self.idx())
+            }
+
+            #[doc = concat!("Get the high 8 bit sub-register, eg `ah` for `", stringify!($ex), "`.")]
+            ///
+            /// Only `rax`, `rcx`, `rdx` and `rbx` and their sub-registers have one. The high 8 bit
+            /// registers can only be encoded without a `REX` prefix, see [`to8lo`](Self::to8lo).
+            pub fn to8hi(&self) -> Option<Reg8> {
+                use Reg8::*;
+                [ah, ch, dh, bh].get(usize::from(self.idx())).copied()
+            }
+        }
+    };
+}
+
+impl_convert!(Reg64 = rax, { to32: Reg32 = eax, to16: Reg16 = ax });
+impl_convert!(Reg32 = eax, { to64: Reg64 = rax, to16: Reg16 = ax });
+impl_convert!(Reg16 = ax, { to64: Reg64 = rax, to32: Reg32 = eax });
+
+impl Reg8 {
+    /// Check if the register is one of the high 8 bit registers `ah`, `ch`, `dh` and `bh`.
+    pub fn is_high(&self) -> bool {
+        matches!(self, Reg8::ah | Reg8::ch | Reg8::dh | Reg8::bh)
+    }
+
+    /// Get the aliasing register index of the full register, which differs from
+    /// [`index`](Self::index) for the high 8 bit registers, eg `0` for `ah`.
+    fn alias(&self) -> u8 {
+        if self.is_high() {
+            self.idx() - 4
+        } else {
+            self.idx()
+        }
+    }
+
+    /// Get the aliasing [`Reg64`] register, eg `rax` for `al` and `ah`.
+    pub fn to64(&self) -> Reg64 {
+        Reg64::from_index(self.alias())
+    }
+
+    /// Get the aliasing [`Reg32`] register, eg `eax` for `al` and `ah`.
+    pub fn to32(&self) -> Reg32 {
+        Reg32::from_index(self.alias())
+    }
+
+    /// Get the aliasing [`Reg16`] register, eg `ax` for `al` and `ah`.
+    pub fn to16(&self) -> Reg16 {
+        Reg16::from_index(self.alias())
+    }
+}
+
 impl Reg for Reg8 {
     /// Get the raw x64 register code.
     fn idx(&self) -> u8 {
@@ -118,12 +283,99 @@ impl Reg for Reg8 {
     fn need_rex(&self) -> bool {
         self.idx() > 7 || matches!(self, Reg8::spl | Reg8::bpl | Reg8::sil | Reg8::dil)
     }
+
+    /// Check if the register is one of the high byte registers `{AH, CH, DH, BH}`.
+    ///
+    /// With a `REX` byte their register codes encode `{SPL, BPL, SIL, DIL}` instead.
+    fn is_high8(&self) -> bool {
+        matches!(self, Reg8::ah | Reg8::ch | Reg8::dh | Reg8::bh)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_display() {
+        assert_eq!(Reg64::rax.to_string(), "rax");
+        assert_eq!(Reg64::r15.to_string(), "r15");
+        assert_eq!(Reg32::r8d.to_string(), "r8d");
+        assert_eq!(Reg16::r9w.to_string(), "r9w");
+        assert_eq!(Reg8::al.to_string(), "al");
+        assert_eq!(Reg8::dil.to_string(), "dil");
+        assert_eq!(Reg8::r12l.to_string(), "r12b");
+        assert_eq!(Reg8::ah.to_string(), "ah");
+        assert_eq!(format!("{:?}", Reg8::r12l), "r12l");
+        assert_eq!(Att(Reg64::rax).to_string(), "%rax");
+        assert_eq!(Att(Reg8::r12l).to_string(), "%r12b");
+    }
+
+    #[test]
+    fn test_index() {
+        for i in 0..16 {
+            let r = Reg64::from_index(i);
+            assert_eq!(r.index(), i);
+            assert_eq!(Reg32::from_index(i).index(), i);
+            assert_eq!(Reg16::from_index(i).index(), i);
+            assert_eq!(Reg8::from_index(i).index(), i);
+        }
+        assert_eq!(Reg32::from_index(Reg64::r9.index()), Reg32::r9d);
+        assert_eq!(Reg16::from_index(Reg32::esi.index()), Reg16::si);
+        assert_eq!(Reg8::from_index(Reg16::sp.index()), Reg8::spl);
+        assert_eq!(Reg8::from_index(Reg8::ah.index()), Reg8::spl);
+        assert_eq!(Reg8::iter().count(), 20);
+    }
+
+    #[test]
+    fn test_convert() {
+        for r in Reg64::iter() {
+            assert_eq!(r.to32().to64(), r);
+            assert_eq!(r.to16().to64(), r);
+            assert_eq!(r.to32().to16(), r.to16());
+            assert_eq!(r.to16().to32(), r.to32());
+            assert_eq!(r.to8lo().to64(), r);
+            assert_eq!(r.to32().to8lo(), r.to8lo());
+            if let Some(hi) = r.to8hi() {
+                assert!(hi.is_high());
+                assert_eq!(hi.to64(), r);
+                assert_eq!(hi.to16(), r.to16());
+                assert_eq!(r.to16().to8hi(), Some(hi));
+            }
+        }
+
+        assert_eq!(Reg64::rax.to32(), Reg32::eax);
+        assert_eq!(Reg64::r10.to16(), Reg16::r10w);
+        assert_eq!(Reg64::rdi.to8lo(), Reg8::dil);
+        assert_eq!(Reg64::rbx.to8hi(), Some(Reg8::bh));
+        assert_eq!(Reg64::rsp.to8hi(), None);
+        assert_eq!(Reg32::r8d.to8hi(), None);
+        assert_eq!(Reg8::ch.to32(), Reg32::ecx);
+        assert_eq!(Reg8::spl.to64(), Reg64::rsp);
+        assert!(!Reg8::spl.is_high());
+    }
+
+    #[test]
+    #[should_panic(expected = "Register index out of range")]
+    fn test_index_range() {
+        Reg8::from_index(16);
+    }
+
+    #[test]
+    fn test_abi() {
+        use Reg64::*;
+
+        let saved = |abi| -> Vec<_> { Reg64::iter().filter(|r| r.is_callee_saved(abi)).collect() };
+        assert_eq!(saved(Abi::SysV), [rbx, rsp, rbp, r12, r13, r14, r15]);
+        assert_eq!(
+            saved(Abi::Win64),
+            [rbx, rsp, rbp, rsi, rdi, r12, r13, r14, r15]
+        );
+        for r in Reg64::iter() {
+            assert_ne!(r.is_callee_saved(Abi::SysV), r.is_caller_saved(Abi::SysV));
+        }
+    }
+
     #[test]
     fn test_reg8() {
         use Reg8::*;
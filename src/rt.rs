@@ -3,6 +3,11 @@
 //! This runtime supports adding code to executable pages and turn the added code into user
 //! specified function pointer.
 
+use crate::desc::{DescPage, Descriptors};
+use crate::dispatch::Resolver;
+use crate::sync::CodeSync;
+use crate::{Asm, EntryPoints};
+
 #[cfg(not(target_os = "linux"))]
 compile_error!("This runtime is only supported on linux");
 
@@ -51,14 +56,84 @@ mod perf {
     }
 }
 
+/// Mapping of a code offset to a source line, announced to the profilers, see
+/// [`Runtime::add_function_with_lines`].
+///
+/// The layout matches the `LineNumberInfo` of the VTune JIT profiling api.
+#[cfg(any(feature = "vtune", feature = "jitdump"))]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LineInfo {
+    /// Offset from the start of the function of the first instruction of the line.
+    pub offset: u32,
+    /// Source line number.
+    pub line: u32,
+}
+
+/// Backing memory of a [Runtime].
+enum Backing {
+    /// `mmap`ed pages, which are made executable.
+    Mmap,
+    /// Heap memory, which only records the added code, see [`Runtime::recording`].
+    Heap(#[allow(dead_code)] Vec<u8>),
+}
+
+/// Meta data of code added to a [Runtime].
+struct Meta<'a> {
+    /// Function name, empty if anonymous.
+    name: &'a str,
+    /// User data recorded in the descriptor table.
+    data: usize,
+    /// Source file and line mappings announced to the profilers.
+    #[cfg(any(feature = "vtune", feature = "jitdump"))]
+    lines: Option<(&'a str, &'a [LineInfo])>,
+}
+
+impl<'a> Meta<'a> {
+    fn new(name: &'a str, data: usize) -> Meta<'a> {
+        Meta {
+            name,
+            data,
+            #[cfg(any(feature = "vtune", feature = "jitdump"))]
+            lines: None,
+        }
+    }
+}
+
 /// A simple `mmap`ed runtime with executable pages.
 pub struct Runtime {
     buf: *mut u8,
     len: usize,
     idx: usize,
     perf: Option<perf::PerfMap>,
+    backing: Backing,
+    /// Keep the code pages executable while adding code, see [`SharedRuntime`](crate::SharedRuntime).
+    exec_while_writing: bool,
+    /// Synchronization of other threads after writing code, see [`Runtime::set_code_sync`].
+    sync: CodeSync,
+    /// Random state if placement randomization is enabled, see [`Runtime::randomize_placement`].
+    rng: Option<u64>,
+    /// Descriptor table if enabled, see [`Runtime::enable_descriptors`].
+    desc: Option<DescPage>,
+    /// Resolvers of multi-versioned functions, see [`Runtime::add_multiversion`]. Boxed as their
+    /// addresses are embedded in the emitted resolver stubs.
+    #[allow(clippy::vec_box)]
+    resolvers: Vec<Box<Resolver>>,
+    /// Method ids of the functions announced to VTune.
+    #[cfg(feature = "vtune")]
+    vtune: Vec<u32>,
+    /// Jitdump file if enabled, see [`Runtime::with_jitdump`].
+    #[cfg(feature = "jitdump")]
+    jitdump: Option<crate::jitdump::JitDump>,
+    /// Guard regions owned by the runtime, see [`Runtime::add_guard_region`].
+    #[cfg(feature = "guard")]
+    guards: Vec<crate::guard::GuardRegion>,
 }
 
+// SAFETY: The runtime exclusively owns its code pages, the raw pointer is never shared with
+// another runtime.
+unsafe impl Send for Runtime {}
+
 impl Runtime {
     /// Create a new [Runtime].
     ///
@@ -89,6 +164,60 @@ impl Runtime {
             len,
             idx: 0,
             perf: None,
+            backing: Backing::Mmap,
+            exec_while_writing: false,
+            sync: CodeSync::None,
+            rng: None,
+            desc: None,
+            resolvers: Vec::new(),
+            #[cfg(feature = "vtune")]
+            vtune: Vec::new(),
+            #[cfg(feature = "jitdump")]
+            jitdump: None,
+            #[cfg(feature = "guard")]
+            guards: Vec::new(),
+        }
+    }
+
+    /// Create a new [Runtime] which only records the added code in heap memory.
+    ///
+    /// No pages are `mmap`ed or made executable, which allows to test code built on top of the
+    /// [Runtime] under [miri](https://github.com/rust-lang/miri) or on machines where executing
+    /// generated code is not permitted. The added code can be inspected with [`Runtime::code`].
+    ///
+    /// The function pointers returned by [`Runtime::add_code`] must never be called.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut rt = juicebox_asm::Runtime::recording();
+    ///
+    /// let code = [ 0x90 /* nop */, 0xc3 /* ret */ ];
+    /// let _nop = unsafe { rt.add_code::<extern "C" fn()>(&code) };
+    ///
+    /// assert_eq!(rt.code(), &code);
+    /// ```
+    pub fn recording() -> Runtime {
+        let len = 4096;
+        let mut mem = vec![0u8; len];
+
+        Runtime {
+            buf: mem.as_mut_ptr(),
+            len,
+            idx: 0,
+            perf: None,
+            backing: Backing::Heap(mem),
+            exec_while_writing: false,
+            sync: CodeSync::None,
+            rng: None,
+            desc: None,
+            resolvers: Vec::new(),
+            #[cfg(feature = "vtune")]
+            vtune: Vec::new(),
+            #[cfg(feature = "jitdump")]
+            jitdump: None,
+            #[cfg(feature = "guard")]
+            guards: Vec::new(),
         }
     }
 
@@ -107,6 +236,160 @@ impl Runtime {
         rt
     }
 
+    /// Create a new [Runtime] which also records the added code in a perf jitdump file, see
+    /// [`jitdump`](crate::jitdump).
+    ///
+    /// Other than [`Runtime::with_profile`], the dump records the code, the names and the source
+    /// lines of the functions, which allows `perf annotate` to disassemble the jitted code.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `mmap` call fails or the jitdump file can not be created.
+    #[cfg(feature = "jitdump")]
+    pub fn with_jitdump() -> Runtime {
+        let mut rt = Runtime::new();
+        rt.jitdump = Some(crate::jitdump::JitDump::new());
+        rt
+    }
+
+    /// Get the jitdump file, `None` if not enabled, see [`Runtime::with_jitdump`].
+    #[cfg(feature = "jitdump")]
+    pub fn jitdump(&self) -> Option<&crate::jitdump::JitDump> {
+        self.jitdump.as_ref()
+    }
+
+    /// Randomize the placement of code added from now on, as lightweight hardening for long
+    /// running jits.
+    ///
+    /// Each added function is placed after a random gap of up to [`Runtime::MAX_GAP`] bytes,
+    /// which is filled with `int3` instructions such that stray jumps into a gap trap. The gaps
+    /// are part of [`Runtime::code`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the random seed can not be obtained from the system.
+    pub fn randomize_placement(&mut self) {
+        let mut seed = 0u64;
+        let ret = unsafe { libc::getrandom((&mut seed as *mut u64).cast(), 8, 0) };
+        assert_eq!(ret, 8, "Failed to get random seed");
+        // Xorshift state must not be zero.
+        self.rng = Some(seed | 1);
+    }
+
+    /// Maintain a read-only table of [`FunctionDescriptor`](crate::FunctionDescriptor) records
+    /// for the code added from now on, see [`Runtime::descriptors`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `mmap` call for the descriptor page fails.
+    pub fn enable_descriptors(&mut self) {
+        if self.desc.is_none() {
+            self.desc = Some(DescPage::new());
+        }
+    }
+
+    /// Get a lock-free view of the descriptor table, `None` if not enabled.
+    pub fn descriptors(&self) -> Option<Descriptors<'_>> {
+        self.desc.as_ref().map(DescPage::descriptors)
+    }
+
+    /// Add a [`GuardRegion`](crate::guard::GuardRegion) with `size` accessible bytes, which lives
+    /// as long as the runtime. Faults of the code of the runtime in the region can be turned into
+    /// traps.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as
+    /// [`GuardRegion::new`](crate::guard::GuardRegion::new).
+    #[cfg(feature = "guard")]
+    pub fn add_guard_region(&mut self, size: usize) -> &mut crate::guard::GuardRegion {
+        let mut region = crate::guard::GuardRegion::new(size);
+        region.set_code(self.buf, self.len);
+        self.guards.push(region);
+        // UNWRAP: Region was just added.
+        self.guards.last_mut().unwrap()
+    }
+
+    /// Keep the resolver of a multi-versioned function alive as long as the runtime.
+    pub(crate) fn add_resolver(&mut self, res: Box<Resolver>) {
+        self.resolvers.push(res);
+    }
+
+    /// Get the resolvers of the multi-versioned functions.
+    #[cfg(test)]
+    pub(crate) fn resolvers(&self) -> &[Box<Resolver>] {
+        &self.resolvers
+    }
+
+    /// Maximum size in bytes of the random gap placed before each function, see
+    /// [`Runtime::randomize_placement`].
+    pub const MAX_GAP: usize = 64;
+
+    /// Get the size of the random gap to place before the next function, at most `max` bytes.
+    fn next_gap(&mut self, max: usize) -> usize {
+        let Some(x) = &mut self.rng else {
+            return 0;
+        };
+
+        // Xorshift64.
+        *x ^= *x << 13;
+        *x ^= *x >> 7;
+        *x ^= *x << 17;
+
+        // UNWRAP: Modulo MAX_GAP fits into an usize.
+        let gap = usize::try_from(*x % (Self::MAX_GAP as u64 + 1)).unwrap();
+        gap.min(max)
+    }
+
+    /// Keep the code pages executable while adding code, such that other threads can continue to
+    /// execute code of this runtime.
+    pub(crate) fn set_exec_while_writing(&mut self) {
+        self.exec_while_writing = true;
+    }
+
+    /// Set the synchronization of the instruction fetch of other threads, applied each time code
+    /// is added to or patched in the runtime, before the function pointer is returned. Defaults
+    /// to [`CodeSync::None`].
+    ///
+    /// Returns an error and keeps the current synchronization if the kernel does not support
+    /// `sync`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use juicebox_asm::{CodeSync, Runtime};
+    ///
+    /// let mut rt = Runtime::new();
+    /// if rt.set_code_sync(CodeSync::Membarrier).is_err() {
+    ///     // Threads executing the code must serialize themselves.
+    ///     assert_eq!(rt.code_sync(), CodeSync::None);
+    /// }
+    ///
+    /// let code = [ 0x90 /* nop */, 0xc3 /* ret */ ];
+    /// let nop = unsafe { rt.add_code::<extern "C" fn()>(&code) };
+    ///
+    /// let sync = rt.code_sync();
+    /// std::thread::spawn(move || {
+    ///     if sync == CodeSync::None {
+    ///         CodeSync::serialize();
+    ///     }
+    ///     nop();
+    /// })
+    /// .join()
+    /// .unwrap();
+    /// ```
+    pub fn set_code_sync(&mut self, sync: CodeSync) -> std::io::Result<()> {
+        sync.register()?;
+        self.sync = sync;
+        Ok(())
+    }
+
+    /// Get the synchronization of the instruction fetch of other threads, see
+    /// [`Runtime::set_code_sync`].
+    pub fn code_sync(&self) -> CodeSync {
+        self.sync
+    }
+
     /// Add the block of `code` to the runtime and a get function pointer of type `F`.
     ///
     /// # Panics
@@ -129,31 +412,271 @@ impl Runtime {
     /// nop();
     /// ```
     pub unsafe fn add_code<F>(&mut self, code: impl AsRef<[u8]>) -> F {
-        // Get pointer to start of next free byte.
+        let fn_start = self.place(code.as_ref(), &Meta::new("", 0));
+
+        // Return function to newly added code.
+        unsafe { Self::as_fn::<F>(fn_start) }
+    }
+
+    /// Add the block of `code` as function `name` to the runtime and get a function pointer of
+    /// type `F`. The `name` and user `data` are recorded in the descriptor table, if enabled with
+    /// [`Runtime::enable_descriptors`].
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`Runtime::add_code`] or if the descriptor page is
+    /// full.
+    ///
+    /// # Safety
+    ///
+    /// The code added must fulfill the ABI of the specified function `F` and the returned function
+    /// pointer is only valid until the [`Runtime`] is dropped.
+    pub unsafe fn add_function<F>(&mut self, name: &str, data: usize, code: impl AsRef<[u8]>) -> F {
+        let fn_start = self.place(code.as_ref(), &Meta::new(name, data));
+
+        // Return function to newly added code.
+        unsafe { Self::as_fn::<F>(fn_start) }
+    }
+
+    /// Add the block of `code` as function `name` to the runtime like [`Runtime::add_function`]
+    /// and announce it to the profilers together with the `lines` of the `source` file:
+    ///
+    #[cfg_attr(
+        feature = "vtune",
+        doc = "- VTune, see [`vtune::notify_load`](crate::vtune::notify_load)."
+    )]
+    #[cfg_attr(
+        feature = "jitdump",
+        doc = "- perf jitdump, see [`JitDump::code_load`](crate::jitdump::JitDump::code_load)."
+    )]
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`Runtime::add_function`].
+    ///
+    /// # Safety
+    ///
+    /// The code added must fulfill the ABI of the specified function `F` and the returned function
+    /// pointer is only valid until the [`Runtime`] is dropped.
+    #[cfg(any(feature = "vtune", feature = "jitdump"))]
+    pub unsafe fn add_function_with_lines<F>(
+        &mut self,
+        name: &str,
+        data: usize,
+        code: impl AsRef<[u8]>,
+        source: &str,
+        lines: &[LineInfo],
+    ) -> F {
+        let meta = Meta {
+            name,
+            data,
+            lines: Some((source, lines)),
+        };
+        let fn_start = self.place(code.as_ref(), &meta);
+
+        // Return function to newly added code.
+        unsafe { Self::as_fn::<F>(fn_start) }
+    }
+
+    /// Finalize the code emitted by `asm` and add it to the runtime in one step, and get a
+    /// function pointer of type `F`.
+    ///
+    /// Unlike [`Runtime::add_code`] the code is linked against its final address before being
+    /// copied onto the code page, which is required for calls of host functions emitted with
+    /// [`Asm::call_host`] and callbacks registered with [`Asm::on_install`].
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`Runtime::add_code`] and [`Asm::into_code`], except
+    /// for position dependent code, or if a host function is out of range of a `rel32` call from
+    /// the code page.
+    ///
+    /// # Safety
+    ///
+    /// The code added must fulfill the ABI of the specified function `F` and the returned function
+    /// pointer is only valid until the [`Runtime`] is dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use juicebox_asm::{Asm, Runtime};
+    ///
+    /// let mut asm = Asm::new();
+    /// asm.nop();
+    /// asm.ret();
+    ///
+    /// let mut rt = Runtime::new();
+    /// let nop = unsafe { rt.install::<extern "C" fn()>(asm) };
+    ///
+    /// nop();
+    /// ```
+    pub unsafe fn install<F>(&mut self, asm: Asm) -> F {
+        let fn_start = self.install_asm(asm);
+
+        // Return function to newly added code.
+        unsafe { Self::as_fn::<F>(fn_start) }
+    }
+
+    /// Finalize the code emitted by `asm` and add it to the runtime like [`Runtime::install`],
+    /// and get the entry points declared with [`Asm::entry`].
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`Runtime::install`] or if an entry point is declared
+    /// at the end of the code.
+    pub fn install_entries(&mut self, mut asm: Asm) -> EntryPoints {
+        let entries = std::mem::take(asm.entries_mut());
+        for (name, off) in &entries {
+            assert!(*off < asm.len(), "Entry point {} at end of code", name);
+        }
+        let fn_start = self.install_asm(asm);
+        EntryPoints::new(fn_start, entries)
+    }
+
+    /// Finalize the code emitted by `asm`, link it against its final address and copy it into
+    /// the code page, and get a pointer to its start.
+    fn install_asm(&mut self, asm: Asm) -> *mut u8 {
+        let (mut code, fixups) = asm.try_finalize().unwrap_or_else(|e| panic!("{}", e));
+
+        let gap = self.reserve(code.len());
+        fixups.apply(&mut code, self.buf as usize + self.idx + gap);
+        self.place_at(&code, gap, &Meta::new("", 0))
+    }
+
+    /// Copy the `code` into the code page and get a pointer to its start.
+    fn place(&mut self, code: &[u8], meta: &Meta) -> *mut u8 {
+        let gap = self.reserve(code.len());
+        self.place_at(code, gap, meta)
+    }
+
+    /// Check that `len` bytes of code fit on the code page and get the size of the gap placed
+    /// before the code.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the code is empty or does not fit on the code page.
+    fn reserve(&mut self, len: usize) -> usize {
         assert!(self.idx < self.len, "Runtime code page full");
-        let fn_start = self.buf.add(self.idx);
 
-        // Copy over code.
-        let code = code.as_ref();
-        assert!(!code.is_empty(), "Adding empty code not supported");
+        assert!(len != 0, "Adding empty code not supported");
         assert!(
-            code.len() <= (self.len - self.idx),
+            len <= (self.len - self.idx),
             "Code does not fit on the runtime code page"
         );
+
+        // Random gap before the code, zero if randomization is not enabled.
+        self.next_gap(self.len - self.idx - len)
+    }
+
+    /// Copy the `code` after a `gap` obtained from [`Runtime::reserve`] into the code page and get
+    /// a pointer to its start.
+    fn place_at(&mut self, code: &[u8], gap: usize, meta: &Meta) -> *mut u8 {
+        #[cfg(feature = "telemetry")]
+        let now = std::time::Instant::now();
+
+        // Get pointer to start of next free byte after the gap.
+        let gap_start = unsafe { self.buf.add(self.idx) };
+        let fn_start = unsafe { self.buf.add(self.idx + gap) };
+
+        // Fill gap with int3 and copy over code.
         self.unprotect();
+        unsafe { std::ptr::write_bytes(gap_start, 0xcc, gap) };
         unsafe { std::ptr::copy_nonoverlapping(code.as_ptr(), fn_start, code.len()) };
         self.protect();
+        self.sync.sync();
+
+        #[cfg(feature = "valgrind")]
+        crate::valgrind::discard_translations(gap_start, gap + code.len());
 
         // Increment index to next free byte.
-        self.idx += code.len();
+        self.idx += gap + code.len();
 
         // Add perf map entry.
         if let Some(map) = &mut self.perf {
             map.add_entry(fn_start as usize, code.len());
         }
 
-        // Return function to newly added code.
-        unsafe { Self::as_fn::<F>(fn_start) }
+        // Add descriptor table entry.
+        if let Some(desc) = &mut self.desc {
+            desc.append(fn_start as usize, code.len(), meta.name, meta.data);
+        }
+
+        // Name and line mappings announced to the profilers.
+        #[cfg(any(feature = "vtune", feature = "jitdump"))]
+        let (name, source, lines) = {
+            let name = if meta.name.is_empty() {
+                format!("jitfn_{:x}", fn_start as usize)
+            } else {
+                meta.name.to_string()
+            };
+            let (source, lines) = meta.lines.map_or((None, &[][..]), |(s, l)| (Some(s), l));
+            (name, source, lines)
+        };
+
+        // Announce code to VTune.
+        #[cfg(feature = "vtune")]
+        if let Some(id) = crate::vtune::notify_load(&name, fn_start, code.len(), source, lines) {
+            self.vtune.push(id);
+        }
+
+        // Record code in the jitdump file.
+        #[cfg(feature = "jitdump")]
+        if let Some(dump) = &mut self.jitdump {
+            dump.code_load(&name, fn_start, code, source, lines);
+        }
+
+        #[cfg(feature = "telemetry")]
+        crate::telemetry::report(crate::telemetry::Event {
+            phase: crate::telemetry::Phase::Install,
+            time: now.elapsed(),
+            code_size: code.len(),
+            peak_mem: self.idx,
+        });
+
+        fn_start
+    }
+
+    /// Redirect the function at `old` to the function at `new` by patching a `jmp rel32` over the
+    /// first bytes of the code at `old`. Both are function pointers obtained from
+    /// [`Runtime::add_code`] of this runtime.
+    ///
+    /// This allows to hot-patch a function with a recompiled version, all callers of `old` are
+    /// subsequently forwarded to `new`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `old` or `new` do not point into the code added to the runtime or if less than 5
+    /// bytes of code follow `old`.
+    ///
+    /// # Safety
+    ///
+    /// The code at `old` must be at least 5 bytes long and the first 5 bytes must not be executed
+    /// concurrently by any other thread while patching. `new` must fulfill the ABI of `old`.
+    pub unsafe fn redirect(&mut self, old: *const u8, new: *const u8) {
+        let code = self.code().as_ptr_range();
+        assert!(
+            code.contains(&old) && code.contains(&new),
+            "Function not in runtime"
+        );
+
+        // UNWRAP: Both pointers are in the same allocation, checked above.
+        let off = usize::try_from(unsafe { old.offset_from(self.buf) }).unwrap();
+        assert!(off + 5 <= self.idx, "Function too small to redirect");
+
+        // Displacement is relative to the next instruction following the jmp.
+        let disp = unsafe { new.offset_from(old.add(5)) };
+        let disp = i32::try_from(disp).expect("Redirect disp did not fit into i32");
+
+        let mut jmp = [0xe9, 0, 0, 0, 0];
+        jmp[1..].copy_from_slice(&disp.to_ne_bytes());
+
+        self.unprotect();
+        unsafe { std::ptr::copy_nonoverlapping(jmp.as_ptr(), self.buf.add(off), jmp.len()) };
+        self.protect();
+        self.sync.sync();
+
+        #[cfg(feature = "valgrind")]
+        crate::valgrind::discard_translations(unsafe { self.buf.add(off) }, jmp.len());
     }
 
     /// Disassemble the code currently added to the runtime, using
@@ -166,8 +689,32 @@ impl Runtime {
     /// Panics if anything goes wrong with spawning, writing to or reading from
     /// the `ndisasm` child process.
     pub fn disasm(&self) {
+        crate::disasm::disasm(self.code());
+    }
+
+    /// Disassemble the code currently added to the runtime in the given
+    /// [`Syntax`](crate::Syntax) and print it to _stdout_. Intel syntax uses `ndisasm`, AT&T
+    /// syntax uses `objdump`. If the disassembler is not available on the system this prints a
+    /// warning and becomes a nop.
+    ///
+    /// # Panics
+    ///
+    /// Panics if anything goes wrong with spawning, writing to or reading from the disassembler
+    /// child process.
+    pub fn disasm_with(&self, syntax: crate::Syntax) {
+        crate::disasm::disasm_with(self.code(), syntax);
+    }
+
+    /// Get the number of bytes still available for code on the runtime code page, not accounting
+    /// for random gaps, see [`Runtime::randomize_placement`].
+    pub fn available(&self) -> usize {
+        self.len - self.idx
+    }
+
+    /// Get the code currently added to the runtime.
+    pub fn code(&self) -> &[u8] {
         assert!(self.idx <= self.len);
-        crate::disasm::disasm(unsafe { core::slice::from_raw_parts(self.buf, self.idx) });
+        unsafe { core::slice::from_raw_parts(self.buf, self.idx) }
     }
 
     /// Reinterpret the block of code pointed to by `fn_start` as `F`.
@@ -182,6 +729,10 @@ impl Runtime {
     ///
     /// Panics if the `mprotect` call fails.
     fn protect(&mut self) {
+        if let Backing::Heap(_) = self.backing {
+            return;
+        }
+
         unsafe {
             // Remove write permissions from code page and allow to read-execute from it.
             let ret = libc::mprotect(self.buf.cast(), self.len, libc::PROT_READ | libc::PROT_EXEC);
@@ -195,9 +746,18 @@ impl Runtime {
     ///
     /// Panics if the `mprotect` call fails.
     fn unprotect(&mut self) {
+        if let Backing::Heap(_) = self.backing {
+            return;
+        }
+
         unsafe {
             // Add write permissions to code page.
-            let ret = libc::mprotect(self.buf.cast(), self.len, libc::PROT_WRITE);
+            let prot = if self.exec_while_writing {
+                libc::PROT_READ | libc::PROT_WRITE | libc::PROT_EXEC
+            } else {
+                libc::PROT_WRITE
+            };
+            let ret = libc::mprotect(self.buf.cast(), self.len, prot);
             assert_eq!(ret, 0, "Failed to W mprotect runtime code page");
         }
     }
@@ -207,6 +767,17 @@ impl Drop for Runtime {
     /// Unmaps the code page. This invalidates all the function pointer returned by
     /// [`Runtime::add_code`].
     fn drop(&mut self) {
+        #[cfg(feature = "vtune")]
+        self.vtune.drain(..).for_each(crate::vtune::notify_unload);
+
+        if let Backing::Heap(_) = self.backing {
+            // Heap memory is released when the backing is dropped.
+            return;
+        }
+
+        #[cfg(feature = "valgrind")]
+        crate::valgrind::discard_translations(self.buf, self.len);
+
         unsafe {
             let ret = libc::munmap(self.buf.cast(), self.len);
             assert_eq!(ret, 0, "Failed to munmap runtime");
@@ -252,6 +823,58 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_available() {
+        use crate::Asm;
+
+        let mut rt = Runtime::recording();
+        unsafe {
+            rt.add_code::<extern "C" fn()>([0xcc; 4000]);
+        }
+        assert_eq!(rt.available(), 96);
+
+        let mut asm = Asm::new();
+        asm.set_max_len(rt.available());
+        while asm.remaining() > Some(0) {
+            asm.nop();
+        }
+        unsafe {
+            rt.add_code::<extern "C" fn()>(asm.into_code());
+        }
+        assert_eq!(rt.available(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Code size of 2 bytes exceeds the maximum of 1 bytes")]
+    fn test_code_too_large() {
+        let mut asm = crate::Asm::new();
+        asm.set_max_len(1);
+        asm.nop();
+        asm.ret();
+        asm.into_code();
+    }
+
+    #[test]
+    fn test_recording() {
+        let mut rt = Runtime::recording();
+        unsafe {
+            rt.add_code::<extern "C" fn()>([0x90, 0xc3]);
+            rt.add_code::<extern "C" fn()>([0xcc; 4094]);
+        }
+        assert_eq!(rt.code().len(), 4096);
+        assert_eq!(rt.code()[..3], [0x90, 0xc3, 0xcc]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_recording_max_size_plus_1() {
+        let mut rt = Runtime::recording();
+        let code = [0u8; 4097];
+        unsafe {
+            rt.add_code::<extern "C" fn()>(code);
+        }
+    }
+
     #[test]
     #[should_panic]
     fn test_empty_code() {
@@ -261,4 +884,154 @@ mod test {
             rt.add_code::<extern "C" fn()>(code);
         }
     }
+
+    #[test]
+    fn test_randomize_placement() {
+        let mut rt = Runtime::recording();
+        rt.randomize_placement();
+
+        let code = [0x90 /* nop */, 0xc3 /* ret */];
+        let mut fns = Vec::new();
+        for _ in 0..32 {
+            fns.push(unsafe { rt.add_code::<*const u8>(code) });
+        }
+
+        // Each function is preceded by an int3 filled gap.
+        let base = rt.code().as_ptr();
+        let mut end = 0;
+        for f in fns {
+            let off = unsafe { f.offset_from(base) } as usize;
+            assert!(off - end <= Runtime::MAX_GAP);
+            assert!(rt.code()[end..off].iter().all(|&b| b == 0xcc));
+            assert_eq!(&rt.code()[off..off + code.len()], &code);
+            end = off + code.len();
+        }
+        assert_eq!(end, rt.code().len());
+    }
+
+    #[test]
+    fn test_randomize_placement_exec() {
+        let mut rt = Runtime::new();
+        rt.randomize_placement();
+
+        let code = [0x90 /* nop */, 0xc3 /* ret */];
+        for _ in 0..32 {
+            let f = unsafe { rt.add_code::<extern "C" fn()>(code) };
+            f();
+        }
+    }
+
+    #[test]
+    fn test_install_host_call() {
+        use crate::insn::{Add, Mov};
+        use crate::Reg64::*;
+
+        let mut rt = Runtime::new();
+        rt.randomize_placement();
+
+        // lea rax, [rdi + 1]; ret
+        let inc =
+            unsafe { rt.add_code::<extern "C" fn(u64) -> u64>([0x48, 0x8d, 0x47, 0x01, 0xc3]) };
+
+        let mut asm = Asm::new();
+        asm.call_host(inc as usize);
+        asm.mov(rdi, rax);
+        asm.call_host(inc as usize);
+        asm.add(rax, rax);
+        asm.ret();
+
+        let f = unsafe { rt.install::<extern "C" fn(u64) -> u64>(asm) };
+        assert_eq!(f(1), 6);
+    }
+
+    #[test]
+    #[should_panic = "Position dependent code requires installing the code with Runtime::install"]
+    fn test_into_code_host_call() {
+        let mut asm = Asm::new();
+        asm.call_host(0);
+        asm.into_code();
+    }
+
+    #[test]
+    #[should_panic = "Host function out of range for rel32 call"]
+    fn test_install_host_call_out_of_range() {
+        let mut rt = Runtime::new();
+        let far = rt.code().as_ptr() as usize + (1 << 32);
+
+        let mut asm = Asm::new();
+        asm.call_host(far);
+        unsafe { rt.install::<extern "C" fn()>(asm) };
+    }
+
+    #[test]
+    fn test_install_callback() {
+        use std::cell::Cell;
+        use std::rc::Rc;
+
+        let mut rt = Runtime::new();
+        rt.randomize_placement();
+
+        let seen = Rc::new(Cell::new(0));
+        let mut asm = Asm::new();
+        asm.nop();
+        asm.ret();
+        // Callbacks are invoked in order, the nop is patched to a ret by the first one.
+        let s = Rc::clone(&seen);
+        asm.on_install(move |code, base| {
+            assert_eq!(code, [0x90, 0xc3]);
+            code[0] = 0xc3;
+            s.set(base);
+        });
+        asm.on_install(|code, _| assert_eq!(code, [0xc3, 0xc3]));
+
+        let f = unsafe { rt.install::<extern "C" fn()>(asm) };
+        assert_eq!(seen.get(), f as usize);
+        assert_eq!(rt.code()[rt.code().len() - 2..], [0xc3, 0xc3]);
+        f();
+    }
+
+    #[test]
+    #[should_panic = "Position dependent code requires installing the code with Runtime::install"]
+    fn test_into_code_callback() {
+        let mut asm = Asm::new();
+        asm.ret();
+        asm.on_install(|_, _| {});
+        asm.into_code();
+    }
+
+    #[test]
+    #[should_panic = "Entry point end at end of code"]
+    fn test_install_entry_at_end() {
+        let mut asm = Asm::new();
+        asm.ret();
+        asm.entry("end");
+        Runtime::new().install_entries(asm);
+    }
+
+    #[test]
+    fn test_code_sync() {
+        let mut rt = Runtime::new();
+        assert_eq!(rt.code_sync(), CodeSync::None);
+
+        match rt.set_code_sync(CodeSync::Membarrier) {
+            Ok(()) => assert_eq!(rt.code_sync(), CodeSync::Membarrier),
+            Err(_) => assert_eq!(rt.code_sync(), CodeSync::None),
+        }
+
+        let nop = unsafe { rt.add_code::<extern "C" fn()>([0x90, 0xc3]) };
+        let sync = rt.code_sync();
+        std::thread::spawn(move || {
+            if sync == CodeSync::None {
+                CodeSync::serialize();
+            }
+            nop();
+        })
+        .join()
+        .unwrap();
+
+        // Redirecting synchronizes as well.
+        let ret = unsafe { rt.add_code::<*const u8>([0xc3, 0x90, 0x90, 0x90, 0x90]) };
+        let old = unsafe { rt.add_code::<*const u8>([0x90, 0x90, 0x90, 0x90, 0xc3]) };
+        unsafe { rt.redirect(old, ret) };
+    }
 }
@@ -0,0 +1,257 @@
+//! In-process `SIGPROF` based sampling profiler for jitted code.
+//!
+//! The [Sampler] periodically interrupts the process using `ITIMER_PROF` and resolves the
+//! sampled instruction pointer against the descriptor table of a [`Runtime`](crate::Runtime),
+//! see [`Runtime::enable_descriptors`](crate::Runtime::enable_descriptors). When stopped, it
+//! produces a flat [Profile] by jitted function name.
+//!
+//! ```rust
+//! use juicebox_asm::sampler::Sampler;
+//! use juicebox_asm::Runtime;
+//! use std::time::Duration;
+//!
+//! let mut rt = Runtime::new();
+//! rt.enable_descriptors();
+//! let f = unsafe { rt.add_function::<extern "C" fn()>("f", 0, [0xc3 /* ret */]) };
+//!
+//! let sampler = Sampler::start(rt.descriptors().unwrap(), Duration::from_millis(1));
+//! f();
+//! let profile = sampler.stop();
+//! println!("{}", profile);
+//! ```
+
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicPtr, AtomicU64, Ordering};
+use std::time::Duration;
+
+use crate::Descriptors;
+
+/// Maximum number of functions which can be distinguished by the sampler, samples of further
+/// functions are accounted as unknown.
+const MAX_FUNCS: usize = 128;
+
+/// Set while a sampler is active, only a single sampler can be active at a time.
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+/// Descriptor page of the active sampler.
+static DESCS: AtomicPtr<u8> = AtomicPtr::new(std::ptr::null_mut());
+/// Sample counts per descriptor index.
+static COUNTS: [AtomicU64; MAX_FUNCS] = [const { AtomicU64::new(0) }; MAX_FUNCS];
+/// Samples which did not hit jitted code.
+static OTHER: AtomicU64 = AtomicU64::new(0);
+
+/// `SIGPROF` handler, must be async-signal-safe.
+extern "C" fn on_sigprof(_sig: libc::c_int, _info: *mut libc::siginfo_t, ctx: *mut libc::c_void) {
+    let descs = DESCS.load(Ordering::Acquire);
+    if descs.is_null() || ctx.is_null() {
+        return;
+    }
+
+    let ctx = unsafe { &*ctx.cast::<libc::ucontext_t>() };
+    let ip = ctx.uc_mcontext.gregs[libc::REG_RIP as usize] as usize;
+
+    // SAFETY: The descriptor page is valid while the sampler is active.
+    let descs = unsafe { Descriptors::from_ptr(descs) };
+    let idx = descs.lookup(ip).map(|d| unsafe {
+        (d as *const crate::FunctionDescriptor).offset_from(descs.all().as_ptr())
+    } as usize);
+
+    match idx.and_then(|idx| COUNTS.get(idx)) {
+        Some(cnt) => cnt.fetch_add(1, Ordering::Relaxed),
+        None => OTHER.fetch_add(1, Ordering::Relaxed),
+    };
+}
+
+/// An active sampling profiler, see the [module](self) documentation.
+pub struct Sampler<'a> {
+    descs: Descriptors<'a>,
+    old: libc::sigaction,
+}
+
+impl<'a> Sampler<'a> {
+    /// Start sampling every `interval` of consumed cpu time, resolving samples against `descs`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a sampler is already active or installing the signal handler or timer fails.
+    pub fn start(descs: Descriptors<'a>, interval: Duration) -> Sampler<'a> {
+        assert!(
+            !ACTIVE.swap(true, Ordering::AcqRel),
+            "Sampler already active"
+        );
+
+        COUNTS.iter().for_each(|c| c.store(0, Ordering::Relaxed));
+        OTHER.store(0, Ordering::Relaxed);
+        DESCS.store(descs.as_ptr().cast_mut(), Ordering::Release);
+
+        let mut old: libc::sigaction = unsafe { std::mem::zeroed() };
+        unsafe {
+            let mut sa: libc::sigaction = std::mem::zeroed();
+            sa.sa_sigaction = on_sigprof as *const () as libc::sighandler_t;
+            sa.sa_flags = libc::SA_SIGINFO | libc::SA_RESTART;
+            libc::sigemptyset(&mut sa.sa_mask);
+            let ret = libc::sigaction(libc::SIGPROF, &sa, &mut old);
+            assert_eq!(ret, 0, "Failed to install SIGPROF handler");
+        }
+
+        set_timer(interval);
+        Sampler { descs, old }
+    }
+
+    /// Stop sampling and get the collected [Profile].
+    pub fn stop(self) -> Profile {
+        // Stop the timer before collecting, the handler is restored when self is dropped.
+        set_timer(Duration::ZERO);
+        let mut funcs: Vec<_> = self
+            .descs
+            .all()
+            .iter()
+            .zip(COUNTS.iter())
+            .map(|(d, c)| (self.descs.name(d).to_string(), c.load(Ordering::Relaxed)))
+            .filter(|(_, c)| *c > 0)
+            .collect();
+        funcs.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        Profile {
+            funcs,
+            other: OTHER.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl Drop for Sampler<'_> {
+    fn drop(&mut self) {
+        set_timer(Duration::ZERO);
+        unsafe {
+            let ret = libc::sigaction(libc::SIGPROF, &self.old, std::ptr::null_mut());
+            assert_eq!(ret, 0, "Failed to restore SIGPROF handler");
+        }
+        DESCS.store(std::ptr::null_mut(), Ordering::Release);
+        ACTIVE.store(false, Ordering::Release);
+    }
+}
+
+/// Arm the `ITIMER_PROF` timer with `interval`, zero disarms the timer.
+fn set_timer(interval: Duration) {
+    let tv = libc::timeval {
+        tv_sec: interval.as_secs() as libc::time_t,
+        tv_usec: interval.subsec_micros() as libc::suseconds_t,
+    };
+    let timer = libc::itimerval {
+        it_interval: tv,
+        it_value: tv,
+    };
+    let ret = unsafe { libc::setitimer(libc::ITIMER_PROF, &timer, std::ptr::null_mut()) };
+    assert_eq!(ret, 0, "Failed to set ITIMER_PROF");
+}
+
+/// Flat profile by jitted function name, collected by a [Sampler].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Profile {
+    /// Samples per function, sorted by decreasing number of samples.
+    funcs: Vec<(String, u64)>,
+    /// Samples which did not hit jitted code.
+    other: u64,
+}
+
+impl Profile {
+    /// Get the functions with their number of samples, sorted by decreasing number of samples.
+    pub fn funcs(&self) -> &[(String, u64)] {
+        &self.funcs
+    }
+
+    /// Get the number of samples of the function `name`.
+    pub fn samples(&self, name: &str) -> u64 {
+        self.funcs
+            .iter()
+            .filter(|(n, _)| n == name)
+            .map(|(_, c)| c)
+            .sum()
+    }
+
+    /// Get the number of samples which did not hit jitted code.
+    pub fn other(&self) -> u64 {
+        self.other
+    }
+
+    /// Get the total number of samples.
+    pub fn total(&self) -> u64 {
+        self.funcs.iter().map(|(_, c)| c).sum::<u64>() + self.other
+    }
+}
+
+impl fmt::Display for Profile {
+    /// Format as flat profile, one function per line.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let total = self.total().max(1) as f64;
+        writeln!(f, "{:>8} {:>7}  function", "samples", "%")?;
+        for (name, cnt) in &self.funcs {
+            let name = if name.is_empty() { "<anon>" } else { name };
+            writeln!(
+                f,
+                "{:>8} {:>6.2}%  {}",
+                cnt,
+                *cnt as f64 * 100.0 / total,
+                name
+            )?;
+        }
+        write!(
+            f,
+            "{:>8} {:>6.2}%  <other>",
+            self.other,
+            self.other as f64 * 100.0 / total
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::insn::{Dec, Jnz, Mov};
+    use crate::{Asm, Label, Reg64, Runtime};
+
+    #[test]
+    fn test_sample_spin() {
+        // Spin for a number of iterations given as argument.
+        let mut asm = Asm::new();
+        let mut lp = Label::new();
+        asm.mov(Reg64::rax, Reg64::rdi);
+        asm.bind(&mut lp);
+        asm.dec(Reg64::rax);
+        asm.jnz(&mut lp);
+        asm.ret();
+
+        let mut rt = Runtime::new();
+        rt.enable_descriptors();
+        let _ = unsafe {
+            rt.add_function::<*const u8>("idle", 0, [0xc3 /* ret */])
+        };
+        let spin = unsafe { rt.add_function::<extern "C" fn(u64)>("spin", 0, asm.into_code()) };
+
+        let sampler = Sampler::start(rt.descriptors().unwrap(), Duration::from_millis(1));
+        // Spin until samples hit the function, bounded to not hang on failure.
+        for _ in 0..100 {
+            spin(1 << 24);
+            if COUNTS[1].load(Ordering::Relaxed) > 10 {
+                break;
+            }
+        }
+        let profile = sampler.stop();
+
+        assert!(profile.samples("spin") > 10);
+        assert_eq!(profile.samples("idle"), 0);
+        assert_eq!(profile.funcs()[0].0, "spin");
+        assert!(profile.to_string().contains("spin"));
+    }
+
+    #[test]
+    fn test_display() {
+        let profile = Profile {
+            funcs: vec![("foo".into(), 3), (String::new(), 1)],
+            other: 4,
+        };
+        assert_eq!(
+            profile.to_string(),
+            " samples       %  function\n       3  37.50%  foo\n       1  12.50%  <anon>\n       4  50.00%  <other>"
+        );
+    }
+}
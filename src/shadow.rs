@@ -0,0 +1,177 @@
+//! Software shadow stack instrumentation for emitted code.
+//!
+//! When a [ShadowStack] is enabled on the [Asm] with [`Asm::set_shadow_stack`], the return
+//! address is recorded in the shadow stack at each function entry emitted with
+//! [`Asm::shadow_enter`] and each `ret` checks the return address on the machine stack against
+//! the shadow stack. On mismatch, eg due to unbalanced `push`/`pop` instructions, the code traps
+//! with an `ud2` instruction.
+
+use crate::insn::{Add, Cmp, Jnz, Jz, Mov, Pop, Push};
+use crate::{Asm, Imm64, Imm8, Label, Mem64, Reg64};
+
+/// A software shadow stack recording return addresses of instrumented functions.
+///
+/// The shadow stack must outlive any code emitted while it was enabled on an [Asm].
+pub struct ShadowStack {
+    /// Pointer to the next free slot.
+    top: Box<*mut u64>,
+    /// Slots holding the return addresses.
+    slots: Box<[u64]>,
+}
+
+impl ShadowStack {
+    /// Create a new shadow stack with space for `capacity` return addresses.
+    pub fn new(capacity: usize) -> ShadowStack {
+        let mut slots = vec![0u64; capacity].into_boxed_slice();
+        let top = Box::new(slots.as_mut_ptr());
+        ShadowStack { top, slots }
+    }
+
+    /// Get the number of return addresses currently recorded.
+    pub fn depth(&self) -> usize {
+        // UNWRAP: top always points into the slots or one past the end.
+        usize::try_from(unsafe { self.top.offset_from(self.slots.as_ptr()) }).unwrap()
+    }
+
+    /// Get the return addresses currently recorded, the innermost function last.
+    pub fn entries(&self) -> &[u64] {
+        &self.slots[..self.depth()]
+    }
+}
+
+/// Addresses of a [ShadowStack] embedded into the instrumentation code.
+#[derive(Clone, Copy)]
+pub(crate) struct ShadowRef {
+    top: usize,
+    base: usize,
+    limit: usize,
+}
+
+impl Asm {
+    /// Enable shadow stack instrumentation using `ss`, from now on each `ret` checks the return
+    /// address against the shadow stack.
+    ///
+    /// Instrumented code clobbers `r10` and `r11` at function entry and return, which are not
+    /// used for passing arguments or return values according to the SystemV abi.
+    pub fn set_shadow_stack(&mut self, ss: &mut ShadowStack) {
+        let base = ss.slots.as_mut_ptr();
+        self.set_shadow(Some(ShadowRef {
+            top: &mut *ss.top as *mut *mut u64 as usize,
+            base: base as usize,
+            limit: base.wrapping_add(ss.slots.len()) as usize,
+        }));
+    }
+
+    /// Emit the shadow stack function entry, recording the return address of the function. Must
+    /// be emitted as first instruction of the function.
+    ///
+    /// This is a nop if no shadow stack is enabled. Traps if the shadow stack overflows.
+    pub fn shadow_enter(&mut self) {
+        use Reg64::*;
+
+        let Some(ss) = self.shadow() else {
+            return;
+        };
+
+        // r11 = top
+        self.mov(r11, Imm64::from(ss.top));
+        self.mov(r11, Mem64::indirect(r11));
+
+        // if (top == limit) trap
+        self.mov(r10, Imm64::from(ss.limit));
+        self.cmp(r11, r10);
+        let mut ok = Label::new();
+        self.jnz(&mut ok);
+        self.ud2();
+        self.bind(&mut ok);
+
+        // *top = return address
+        self.pop(r10);
+        self.push(r10);
+        self.mov(Mem64::indirect(r11), r10);
+
+        // top += 8
+        self.mov(r10, Imm64::from(ss.top));
+        self.add(Mem64::indirect(r10), Imm8::from(8u8));
+    }
+
+    /// Emit the shadow stack check before the `ret` instruction, traps if the shadow stack is
+    /// empty or the return address does not match.
+    pub(crate) fn shadow_ret(&mut self, ss: ShadowRef) {
+        use Reg64::*;
+
+        // if (top == base) trap
+        self.mov(r10, Imm64::from(ss.top));
+        self.mov(r11, Mem64::indirect(r10));
+        self.mov(r10, Imm64::from(ss.base));
+        self.cmp(r11, r10);
+        let mut ok = Label::new();
+        self.jnz(&mut ok);
+        self.ud2();
+        self.bind(&mut ok);
+
+        // top -= 8
+        self.mov(r10, Imm64::from(ss.top));
+        self.add(Mem64::indirect(r10), Imm8::from(-8i8));
+
+        // if (*top != return address) trap
+        self.mov(r11, Mem64::indirect(r10));
+        self.mov(r11, Mem64::indirect(r11));
+        self.pop(r10);
+        self.push(r10);
+        self.cmp(r11, r10);
+        let mut ok = Label::new();
+        self.jz(&mut ok);
+        self.ud2();
+        self.bind(&mut ok);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::insn::Call;
+    use crate::Runtime;
+
+    #[test]
+    fn test_shadow_stack() {
+        use Reg64::*;
+
+        let mut ss = ShadowStack::new(4);
+        let mut rt = Runtime::new();
+
+        // inner: fn() -> u64 { depth of the shadow stack }
+        let mut asm = Asm::new();
+        asm.set_shadow_stack(&mut ss);
+        asm.shadow_enter();
+        asm.mov(rax, Imm64::from(&*ss.top as *const *mut u64 as usize));
+        asm.mov(rax, Mem64::indirect(rax));
+        asm.ret();
+        let inner = unsafe { rt.add_code::<extern "C" fn() -> usize>(asm.into_code()) };
+
+        // outer: fn() -> u64 { inner() }
+        let mut asm = Asm::new();
+        asm.set_shadow_stack(&mut ss);
+        asm.shadow_enter();
+        asm.push(rbx);
+        asm.mov(rax, Imm64::from(inner as usize));
+        asm.call(rax);
+        asm.pop(rbx);
+        asm.ret();
+        let outer = unsafe { rt.add_code::<extern "C" fn() -> usize>(asm.into_code()) };
+
+        // Top of the shadow stack while executing inner has two entries.
+        let base = ss.slots.as_ptr() as usize;
+        assert_eq!(outer(), base + 2 * 8);
+        assert_eq!(inner(), base + 8);
+        assert_eq!(ss.depth(), 0);
+    }
+
+    #[test]
+    fn test_disabled() {
+        let mut asm = Asm::new();
+        asm.shadow_enter();
+        asm.ret();
+        assert_eq!(asm.into_code(), [0xc3]);
+    }
+}
@@ -0,0 +1,188 @@
+//! A [Runtime] shared between threads.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+
+use crate::{CodeSync, Runtime};
+
+struct Shared {
+    rt: Mutex<Runtime>,
+    /// Installed functions by name, as address to keep the map `Send + Sync`.
+    symbols: RwLock<HashMap<String, usize>>,
+}
+
+/// Cloneable handle to a [Runtime] shared between threads.
+///
+/// Any thread can resolve and call installed functions, while installing code is serialized
+/// internally. The code pages stay executable while new code is installed, hence functions can
+/// be executed concurrently with installation.
+///
+/// Installation synchronizes the instruction fetch of all threads with [`CodeSync::Membarrier`]
+/// if supported by the kernel, see [`SharedRuntime::code_sync`].
+///
+/// ```rust
+/// use juicebox_asm::{Asm, Imm64, Reg64, SharedRuntime};
+/// use juicebox_asm::insn::Mov;
+///
+/// let rt = SharedRuntime::new();
+///
+/// let mut asm = Asm::new();
+/// asm.mov(Reg64::rax, Imm64::from(42));
+/// asm.ret();
+/// unsafe { rt.install::<extern "C" fn() -> u64>("answer", asm.into_code()) };
+///
+/// let view = rt.clone();
+/// std::thread::spawn(move || {
+///     let answer = unsafe { view.get::<extern "C" fn() -> u64>("answer") }.unwrap();
+///     assert_eq!(answer(), 42);
+/// })
+/// .join()
+/// .unwrap();
+/// ```
+#[derive(Clone)]
+pub struct SharedRuntime {
+    inner: Arc<Shared>,
+}
+
+impl SharedRuntime {
+    /// Create a new shared runtime.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `mmap` call fails.
+    pub fn new() -> SharedRuntime {
+        let mut rt = Runtime::new();
+        rt.set_exec_while_writing();
+        // Without kernel support, the threads executing the code must serialize themselves.
+        let _ = rt.set_code_sync(CodeSync::Membarrier);
+        SharedRuntime {
+            inner: Arc::new(Shared {
+                rt: Mutex::new(rt),
+                symbols: RwLock::new(HashMap::new()),
+            }),
+        }
+    }
+
+    /// Install the block of `code` as function `name` and get a function pointer of type `F`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a function `name` is already installed, or under the same conditions as
+    /// [`Runtime::add_code`].
+    ///
+    /// # Safety
+    ///
+    /// The code added must fulfill the ABI of the specified function `F` and the returned function
+    /// pointer is only valid until the last handle to the runtime is dropped.
+    pub unsafe fn install<F>(&self, name: &str, code: impl AsRef<[u8]>) -> F {
+        let mut rt = self.inner.rt.lock().unwrap_or_else(|e| e.into_inner());
+        let mut symbols = self
+            .inner
+            .symbols
+            .write()
+            .unwrap_or_else(|e| e.into_inner());
+        assert!(
+            !symbols.contains_key(name),
+            "Function {} already installed",
+            name
+        );
+
+        let ptr = unsafe { rt.add_code::<*const u8>(code) };
+        symbols.insert(name.to_string(), ptr as usize);
+        unsafe { std::mem::transmute_copy(&ptr) }
+    }
+
+    /// Get the synchronization of the instruction fetch of other threads applied when installing
+    /// code. If this is [`CodeSync::None`], threads must call [`CodeSync::serialize`] before
+    /// executing code installed by another thread.
+    pub fn code_sync(&self) -> CodeSync {
+        self.inner
+            .rt
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .code_sync()
+    }
+
+    /// Get the address of the installed function `name`, `None` if not installed.
+    pub fn resolve(&self, name: &str) -> Option<*const u8> {
+        let symbols = self.inner.symbols.read().unwrap_or_else(|e| e.into_inner());
+        symbols.get(name).map(|&addr| addr as *const u8)
+    }
+
+    /// Get the installed function `name` as function pointer of type `F`, `None` if not
+    /// installed.
+    ///
+    /// # Safety
+    ///
+    /// The function must have been installed with the ABI of `F` and the returned function
+    /// pointer is only valid until the last handle to the runtime is dropped.
+    pub unsafe fn get<F>(&self, name: &str) -> Option<F> {
+        self.resolve(name)
+            .map(|ptr| unsafe { std::mem::transmute_copy(&ptr) })
+    }
+
+    /// Disassemble the code currently installed, see [`Runtime::disasm`].
+    pub fn disasm(&self) {
+        self.inner
+            .rt
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .disasm();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::insn::Mov;
+    use crate::{Asm, Imm64, Reg64};
+
+    fn const_fn(v: u64) -> Vec<u8> {
+        let mut asm = Asm::new();
+        asm.mov(Reg64::rax, Imm64::from(v));
+        asm.ret();
+        asm.into_code()
+    }
+
+    #[test]
+    fn test_resolve() {
+        let rt = SharedRuntime::new();
+        assert!(rt.resolve("f").is_none());
+
+        let f = unsafe { rt.install::<*const u8>("f", const_fn(1)) };
+        assert_eq!(rt.resolve("f"), Some(f));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_install_twice() {
+        let rt = SharedRuntime::new();
+        unsafe { rt.install::<*const u8>("f", const_fn(1)) };
+        unsafe { rt.install::<*const u8>("f", const_fn(2)) };
+    }
+
+    #[test]
+    fn test_concurrent() {
+        let rt = SharedRuntime::new();
+        unsafe { rt.install::<*const u8>("f0", const_fn(0)) };
+
+        // Threads execute installed functions, while new functions are installed.
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                let rt = rt.clone();
+                s.spawn(move || {
+                    let f0 = unsafe { rt.get::<extern "C" fn() -> u64>("f0") }.unwrap();
+                    for _ in 0..10_000 {
+                        assert_eq!(f0(), 0);
+                    }
+                });
+            }
+            for i in 1..100 {
+                let f = unsafe {
+                    rt.install::<extern "C" fn() -> u64>(&format!("f{}", i), const_fn(i))
+                };
+                assert_eq!(f(), i);
+            }
+        });
+    }
+}
@@ -0,0 +1,225 @@
+//! Optional encode-time statistics, to inspect what a code generator emits and where the code
+//! size goes.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::Asm;
+
+/// Number of instructions and bytes accounted to one entry of the [Stats].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Count {
+    /// Number of emitted instructions.
+    pub insns: usize,
+    /// Number of emitted bytes.
+    pub bytes: usize,
+}
+
+impl Count {
+    fn add(&mut self, other: Count) {
+        self.insns += other.insns;
+        self.bytes += other.bytes;
+    }
+}
+
+/// Encode-time statistics collected by an [Asm], see [`Asm::enable_stats`].
+///
+/// Instructions are accounted by their mnemonic and by the category of their operand form, eg
+/// `"reg, reg"`, `"mem, imm"` or `"label"`. Helper sequences emitted as part of a single
+/// instruction, eg the shadow stack check of a `ret`, are accounted to that instruction.
+///
+/// ```rust
+/// use juicebox_asm::{Asm, Imm64, Reg64};
+/// use juicebox_asm::insn::{Add, Mov};
+///
+/// let mut asm = Asm::new();
+/// asm.enable_stats();
+/// asm.mov(Reg64::rax, Imm64::from(1));
+/// asm.add(Reg64::rax, Reg64::rdi);
+/// asm.ret();
+///
+/// let stats = asm.stats().unwrap();
+/// assert_eq!(stats.mnemonic("mov").bytes, 10);
+/// assert_eq!(stats.category("reg, reg").insns, 1);
+/// assert_eq!(stats.total().insns, 3);
+/// println!("{}", stats);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Stats {
+    mnemonics: BTreeMap<&'static str, Count>,
+    categories: BTreeMap<&'static str, Count>,
+}
+
+impl Stats {
+    /// Get the count for the instruction `mnemonic`.
+    pub fn mnemonic(&self, mnemonic: &str) -> Count {
+        self.mnemonics.get(mnemonic).copied().unwrap_or_default()
+    }
+
+    /// Get the count for the operand form `category`.
+    pub fn category(&self, category: &str) -> Count {
+        self.categories.get(category).copied().unwrap_or_default()
+    }
+
+    /// Get an iterator over the counts per mnemonic, sorted by mnemonic.
+    pub fn mnemonics(&self) -> impl Iterator<Item = (&'static str, Count)> + '_ {
+        self.mnemonics.iter().map(|(k, v)| (*k, *v))
+    }
+
+    /// Get an iterator over the counts per operand form category, sorted by category.
+    pub fn categories(&self) -> impl Iterator<Item = (&'static str, Count)> + '_ {
+        self.categories.iter().map(|(k, v)| (*k, *v))
+    }
+
+    /// Get the total count over all instructions.
+    pub fn total(&self) -> Count {
+        let mut total = Count::default();
+        self.mnemonics.values().for_each(|c| total.add(*c));
+        total
+    }
+
+    /// Account an instruction with `bytes` to `mnemonic`.
+    pub(crate) fn add_insn(&mut self, mnemonic: &'static str, bytes: usize) {
+        self.mnemonics
+            .entry(mnemonic)
+            .or_default()
+            .add(Count { insns: 1, bytes });
+    }
+
+    /// Accumulate the counts of `other` into `self`.
+    pub(crate) fn merge(&mut self, other: &Stats) {
+        for (k, v) in &other.mnemonics {
+            self.mnemonics.entry(k).or_default().add(*v);
+        }
+        for (k, v) in &other.categories {
+            self.categories.entry(k).or_default().add(*v);
+        }
+    }
+}
+
+impl fmt::Display for Stats {
+    /// Format the statistics as histogram, with bars scaled by the number of bytes.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const BAR: usize = 40;
+
+        let total = self.total();
+        let max = self
+            .mnemonics
+            .values()
+            .chain(self.categories.values())
+            .map(|c| c.bytes)
+            .max()
+            .unwrap_or(0)
+            .max(1);
+
+        let table = |f: &mut fmt::Formatter<'_>, title: &str, map: &BTreeMap<_, Count>| {
+            writeln!(f, "{:<10} {:>8} {:>8}", title, "insns", "bytes")?;
+            for (name, c) in map {
+                let bar = "#".repeat((c.bytes * BAR).div_ceil(max));
+                writeln!(f, "{:<10} {:>8} {:>8} {}", name, c.insns, c.bytes, bar)?;
+            }
+            writeln!(f)
+        };
+
+        table(f, "mnemonic", &self.mnemonics)?;
+        table(f, "category", &self.categories)?;
+        write!(f, "{:<10} {:>8} {:>8}", "total", total.insns, total.bytes)
+    }
+}
+
+impl Asm {
+    /// Account the bytes emitted since `start` to the operand form `category`.
+    pub(crate) fn insn_category(&mut self, category: &'static str, start: usize) {
+        self.lock_category(category);
+        let bytes = self.len() - start;
+        if let Some(stats) = self.stats_mut() {
+            stats
+                .categories
+                .entry(category)
+                .or_default()
+                .add(Count { insns: 1, bytes });
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::insn::{Add, Jmp, Mov};
+    use crate::{Imm8, Label, Mem64, Reg64, ShadowStack};
+
+    #[test]
+    fn test_disabled() {
+        let mut asm = Asm::new();
+        asm.nop();
+        assert!(asm.stats().is_none());
+    }
+
+    #[test]
+    fn test_count() {
+        let mut asm = Asm::new();
+        asm.nop();
+        asm.enable_stats();
+
+        let mut lp = Label::new();
+        asm.bind(&mut lp);
+        asm.mov(Reg64::rax, Reg64::rdi);
+        asm.mov(Mem64::indirect_disp(Reg64::rdi, 8), Reg64::rax);
+        asm.add(Mem64::indirect(Reg64::rsi), Imm8::from(1u8));
+        asm.jmp(&mut lp);
+        asm.ret();
+
+        let stats = asm.stats().unwrap();
+        assert_eq!(
+            stats.mnemonic("mov"),
+            Count {
+                insns: 2,
+                bytes: 10
+            }
+        );
+        assert_eq!(stats.mnemonic("add"), Count { insns: 1, bytes: 4 });
+        assert_eq!(stats.mnemonic("jmp"), Count { insns: 1, bytes: 2 });
+        assert_eq!(stats.mnemonic("ret"), Count { insns: 1, bytes: 1 });
+        assert_eq!(stats.mnemonic("nop"), Count::default());
+
+        assert_eq!(stats.category("reg, reg"), Count { insns: 1, bytes: 3 });
+        assert_eq!(stats.category("mem, reg"), Count { insns: 1, bytes: 7 });
+        assert_eq!(stats.category("mem, imm"), Count { insns: 1, bytes: 4 });
+        assert_eq!(stats.category("label"), Count { insns: 1, bytes: 2 });
+        assert_eq!(stats.category("none"), Count { insns: 1, bytes: 1 });
+
+        assert_eq!(
+            stats.total(),
+            Count {
+                insns: 5,
+                bytes: 17
+            }
+        );
+        assert_eq!(stats.total().bytes, asm.len() - 1);
+    }
+
+    #[test]
+    fn test_nested() {
+        let mut ss = ShadowStack::new(4);
+        let mut asm = Asm::new();
+        asm.enable_stats();
+        asm.set_shadow_stack(&mut ss);
+        asm.ret();
+
+        // The shadow stack check is accounted to the ret.
+        let stats = asm.stats().unwrap();
+        assert_eq!(stats.mnemonics().count(), 1);
+        assert_eq!(stats.mnemonic("ret").insns, 1);
+        assert_eq!(stats.total().bytes, asm.len());
+    }
+
+    #[test]
+    fn test_display() {
+        let mut asm = Asm::new();
+        asm.enable_stats();
+        asm.nop();
+        let s = asm.stats().unwrap().to_string();
+        assert!(s.contains("nop               1        1 #"));
+        assert!(s.ends_with("total             1        1"));
+    }
+}
@@ -0,0 +1,136 @@
+//! Serialization of the instruction fetch after installing code, such that other threads are
+//! guaranteed to execute the new instructions, see [`Runtime::set_code_sync`].
+//!
+//! The `x64` architecture requires a thread executing code modified by another thread to execute
+//! a serializing instruction before executing the modified code (cross-modifying code). Passing
+//! the function pointer to another thread does not guarantee this, hence code installed while
+//! other threads are running must be synchronized.
+//!
+//! [`Runtime::set_code_sync`]: crate::Runtime::set_code_sync
+
+use std::io;
+use std::sync::OnceLock;
+
+/// Query the supported commands.
+const MEMBARRIER_CMD_QUERY: libc::c_int = 0;
+/// Serialize the cores of all running threads of the process.
+const MEMBARRIER_CMD_PRIVATE_EXPEDITED_SYNC_CORE: libc::c_int = 1 << 5;
+/// Register the process for [`MEMBARRIER_CMD_PRIVATE_EXPEDITED_SYNC_CORE`].
+const MEMBARRIER_CMD_REGISTER_PRIVATE_EXPEDITED_SYNC_CORE: libc::c_int = 1 << 6;
+
+/// Issue the [`membarrier(2)`](https://man7.org/linux/man-pages/man2/membarrier.2.html) system
+/// call with the command `cmd` and get its return value.
+fn membarrier(cmd: libc::c_int) -> io::Result<libc::c_long> {
+    let ret = unsafe {
+        libc::syscall(
+            libc::SYS_membarrier,
+            cmd,
+            0, /* flags */
+            0, /* cpu_id */
+        )
+    };
+    if ret < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(ret)
+    }
+}
+
+/// Mechanism to synchronize the instruction fetch of other threads after installing code, see
+/// [`Runtime::set_code_sync`](crate::Runtime::set_code_sync).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CodeSync {
+    /// No synchronization, the installed code must only be executed by the installing thread, or
+    /// the executing threads serialize themselves with [`CodeSync::serialize`] before executing
+    /// the code.
+    #[default]
+    None,
+    /// Serialize the cores of all running threads of the process with the
+    /// [`membarrier(2)`](https://man7.org/linux/man-pages/man2/membarrier.2.html) `SYNC_CORE`
+    /// command, and the installing core with [`CodeSync::serialize`].
+    Membarrier,
+}
+
+impl CodeSync {
+    /// Prepare the synchronization, ie register the process for the membarrier `SYNC_CORE`
+    /// command. Registering multiple times is fine.
+    ///
+    /// Returns an error if the kernel does not support the synchronization.
+    pub(crate) fn register(self) -> io::Result<()> {
+        match self {
+            CodeSync::None => Ok(()),
+            CodeSync::Membarrier => {
+                let cmds = membarrier(MEMBARRIER_CMD_QUERY)?;
+                if cmds & libc::c_long::from(MEMBARRIER_CMD_PRIVATE_EXPEDITED_SYNC_CORE) == 0 {
+                    return Err(io::Error::from(io::ErrorKind::Unsupported));
+                }
+                membarrier(MEMBARRIER_CMD_REGISTER_PRIVATE_EXPEDITED_SYNC_CORE).map(|_| ())
+            }
+        }
+    }
+
+    /// Synchronize the instruction fetch after code was written.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the membarrier system call fails, which is only possible if the synchronization
+    /// was not registered with [`CodeSync::register`].
+    pub(crate) fn sync(self) {
+        match self {
+            CodeSync::None => {}
+            CodeSync::Membarrier => {
+                membarrier(MEMBARRIER_CMD_PRIVATE_EXPEDITED_SYNC_CORE)
+                    .expect("Failed to serialize cores with membarrier");
+                CodeSync::serialize();
+            }
+        }
+    }
+
+    /// Serialize the instruction fetch of the calling core, with the
+    /// [`serialize`](https://www.felixcloutier.com/x86/serialize) instruction if supported by the
+    /// CPU, else with [`cpuid`](https://www.felixcloutier.com/x86/cpuid).
+    ///
+    /// A thread executing code installed by another thread without [`CodeSync::Membarrier`] must
+    /// call this after obtaining the function pointer and before calling it.
+    pub fn serialize() {
+        use std::arch::x86_64::{__cpuid, __cpuid_count};
+
+        static HAS_SERIALIZE: OnceLock<bool> = OnceLock::new();
+        let has_serialize = *HAS_SERIALIZE.get_or_init(|| {
+            // Structured extended feature flags, edx bit 14.
+            __cpuid(0).eax >= 7 && __cpuid_count(7, 0).edx & (1 << 14) != 0
+        });
+
+        if has_serialize {
+            // serialize
+            unsafe { std::arch::asm!(".byte 0x0f, 0x01, 0xe8", options(nostack, preserves_flags)) };
+        } else {
+            __cpuid(0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_serialize() {
+        CodeSync::serialize();
+        CodeSync::serialize();
+    }
+
+    #[test]
+    fn test_none() {
+        CodeSync::None.register().unwrap();
+        CodeSync::None.sync();
+    }
+
+    #[test]
+    fn test_membarrier() {
+        // The membarrier command may not be supported by the kernel or permitted by a sandbox.
+        if CodeSync::Membarrier.register().is_ok() {
+            CodeSync::Membarrier.sync();
+        }
+    }
+}
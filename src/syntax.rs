@@ -0,0 +1,24 @@
+//! Selection of the assembly syntax used for operand formatting and disassembly listings.
+
+/// Assembly syntax flavor.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum Syntax {
+    /// Intel syntax, eg `mov qword ptr [rax+0x10], rcx`.
+    #[default]
+    Intel,
+    /// AT&T syntax, eg `movq %rcx, 0x10(%rax)`.
+    Att,
+}
+
+/// Wrapper to format an operand in [`Syntax::Att`], while the `Display` implementation of the
+/// operand types uses [`Syntax::Intel`].
+///
+/// ```rust
+/// use juicebox_asm::{Att, Imm8, Mem64, Reg64};
+///
+/// assert_eq!(Att(Reg64::rax).to_string(), "%rax");
+/// assert_eq!(Att(Imm8::from(42u8)).to_string(), "$0x2a");
+/// assert_eq!(Att(Mem64::indirect_disp(Reg64::rax, 16)).to_string(), "0x10(%rax)");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Att<T>(pub T);
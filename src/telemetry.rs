@@ -0,0 +1,125 @@
+//! Lightweight telemetry hooks reporting the time spent in the different jit phases.
+//!
+//! A single process wide [Hook] can be installed with [`set_hook`], which is invoked
+//! synchronously on the thread performing the work. This allows embedders to attribute the jit
+//! overhead to the function currently compiled inside their own profilers.
+//!
+//! - [`Phase::Encode`] and [`Phase::Link`] are reported once per [`Asm`](crate::Asm) when the
+//!   code is taken with [`Asm::into_code`](crate::Asm::into_code).
+//! - [`Phase::Install`] is reported for each [`Runtime::add_code`](crate::Runtime::add_code).
+//!
+//! ```rust
+//! use juicebox_asm::telemetry::{self, Event, Phase};
+//! use juicebox_asm::{Asm, Runtime};
+//!
+//! fn hook(ev: &Event) {
+//!     println!("{:?}: {:?} for {} bytes", ev.phase, ev.time, ev.code_size);
+//! }
+//!
+//! telemetry::set_hook(Some(hook));
+//!
+//! let mut asm = Asm::new();
+//! asm.ret();
+//! let code = asm.into_code(); // Reports Encode and Link.
+//!
+//! let mut rt = Runtime::new();
+//! let f = unsafe { rt.add_code::<extern "C" fn()>(&code) }; // Reports Install.
+//! f();
+//!
+//! telemetry::set_hook(None);
+//! ```
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Phase of the jit reported by an [Event].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Phase {
+    /// Encoding instructions into the code buffer.
+    Encode,
+    /// Binding labels and patching the pending relocations.
+    Link,
+    /// Copying code into the executable memory of the runtime.
+    Install,
+}
+
+/// A telemetry event reported to the installed [Hook].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Event {
+    /// Phase the event reports.
+    pub phase: Phase,
+    /// Time spent in the phase.
+    pub time: Duration,
+    /// Size in bytes of the code the phase operated on.
+    pub code_size: usize,
+    /// Peak memory in bytes occupied during the phase. For [`Phase::Encode`] and [`Phase::Link`]
+    /// this is the capacity of the code buffer, for [`Phase::Install`] the number of bytes in use
+    /// in the runtime after installing the code.
+    pub peak_mem: usize,
+}
+
+/// Telemetry hook, invoked for each reported [Event].
+pub type Hook = fn(&Event);
+
+static HOOK: Mutex<Option<Hook>> = Mutex::new(None);
+
+/// Install the process wide telemetry `hook`, `None` removes the current hook.
+pub fn set_hook(hook: Option<Hook>) {
+    *HOOK.lock().unwrap_or_else(|e| e.into_inner()) = hook;
+}
+
+/// Report an [Event] to the installed hook, if any.
+pub(crate) fn report(ev: Event) {
+    // Copy the hook out to not hold the lock while invoking it.
+    let hook = *HOOK.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(hook) = hook {
+        hook(&ev);
+    }
+}
+
+/// Accumulated time spent in the different phases of an [`Asm`](crate::Asm).
+#[derive(Default)]
+pub(crate) struct Timers {
+    pub(crate) encode: Duration,
+    pub(crate) link: Duration,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::insn::{Jmp, Mov};
+    use crate::{Asm, Label, Reg64, Runtime};
+    use std::cell::RefCell;
+
+    thread_local! {
+        // The hook is process wide, record events per thread as tests run concurrently.
+        static EVENTS: RefCell<Vec<Event>> = const { RefCell::new(Vec::new()) };
+    }
+
+    fn hook(ev: &Event) {
+        EVENTS.with(|evs| evs.borrow_mut().push(*ev));
+    }
+
+    #[test]
+    fn test_report() {
+        set_hook(Some(hook));
+
+        let mut asm = Asm::new();
+        let mut lbl = Label::new();
+        asm.jmp(&mut lbl);
+        asm.mov(Reg64::rax, Reg64::rdi);
+        asm.bind(&mut lbl);
+        asm.ret();
+        let code = asm.into_code();
+
+        let mut rt = Runtime::new();
+        let _ = unsafe { rt.add_code::<extern "C" fn()>(&code) };
+
+        let evs = EVENTS.with(|evs| evs.take());
+        let phases: Vec<_> = evs.iter().map(|e| e.phase).collect();
+        assert_eq!(phases, [Phase::Encode, Phase::Link, Phase::Install]);
+        assert!(evs.iter().all(|e| e.code_size == code.len()));
+        assert!(evs[0].peak_mem >= code.len());
+        assert_eq!(evs[2].peak_mem, code.len());
+    }
+}
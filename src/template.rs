@@ -0,0 +1,346 @@
+//! Definition of code templates which are encoded once and stamped out multiple times with
+//! different immediate values and registers.
+
+use crate::{Asm, Reg64};
+
+/// Location of the register index bits patched by a register [Hole].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct RegBits {
+    /// Shift of the low three bits of the register index in the byte at the hole offset, `0` for
+    /// `modrm.rm` and the opcode, `3` for `modrm.reg`.
+    shift: u8,
+    /// Offset of the `REX` prefix in the code.
+    rex: usize,
+    /// Mask of the `REX.R` or `REX.B` bit holding the high bit of the register index.
+    rex_bit: u8,
+}
+
+/// A hole in a [Template], describing the bytes which are patched when stamping out the template.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Hole {
+    /// Offset of the first byte of the hole in the code.
+    off: usize,
+    /// Length in bytes of the hole.
+    len: usize,
+    /// Register bits of a register hole, `None` for an immediate hole.
+    reg: Option<RegBits>,
+}
+
+impl Hole {
+    /// Get the offset of the hole in the code. For a register hole this is the `ModR/M` or opcode
+    /// byte holding the low bits of the register index.
+    pub fn offset(&self) -> usize {
+        self.off
+    }
+
+    /// Get the size in bytes of the hole.
+    pub fn size(&self) -> usize {
+        self.len
+    }
+
+    /// Check if the hole is a register hole, see [`Asm::reg_hole`].
+    pub fn is_reg(&self) -> bool {
+        self.reg.is_some()
+    }
+
+    /// Patch the hole in `code`, which starts at offset `base`, with `val`.
+    fn patch(&self, code: &mut [u8], base: usize, val: u64) {
+        let off = base + self.off;
+        match self.reg {
+            None => code[off..off + self.len].copy_from_slice(&val.to_le_bytes()[..self.len]),
+            Some(bits) => {
+                assert!(val < 16, "Register hole value must be a register index");
+                let val = val as u8;
+                code[off] = code[off] & !(0b111 << bits.shift) | (val & 0b111) << bits.shift;
+                let rex = base + bits.rex;
+                if val & 0b1000 != 0 {
+                    code[rex] |= bits.rex_bit;
+                } else {
+                    code[rex] &= !bits.rex_bit;
+                }
+            }
+        }
+    }
+}
+
+impl Asm {
+    /// Mark the last `len` emitted bytes as [Hole], to be patched when stamping out a [Template].
+    ///
+    /// Immediate operands are always encoded as the last bytes of an instruction, hence calling
+    /// this right after emitting an instruction with an immediate operand marks the immediate as
+    /// hole. Registers are encoded into the `opcode`, `ModR/M` and `REX` bytes, see
+    /// [`Asm::reg_hole`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` is not one of `1, 2, 4, 8` or exceeds the number of emitted bytes.
+    pub fn hole(&mut self, len: usize) -> Hole {
+        assert!(
+            matches!(len, 1 | 2 | 4 | 8),
+            "Hole len must be 1, 2, 4 or 8"
+        );
+        assert!(len <= self.len(), "Hole exceeds the emitted code");
+        Hole {
+            off: self.len() - len,
+            len,
+            reg: None,
+        }
+    }
+
+    /// Emit the single instruction `f` with the [`Reg64`] operand passed to `f` as register
+    /// [Hole], to be patched with a register index when stamping out a [Template], see
+    /// [`Reg64::index`].
+    ///
+    /// The hole patches the register index bits in the `ModR/M` or opcode byte and in the `REX`
+    /// prefix, which the instruction emits for any register. The instruction is emitted with
+    /// `rax` as placeholder.
+    ///
+    /// ```rust
+    /// use juicebox_asm::{Asm, Reg64, Template};
+    /// use juicebox_asm::insn::Add;
+    ///
+    /// let mut asm = Asm::new();
+    /// let hole = asm.reg_hole(|asm, r| asm.add(r, Reg64::rcx));
+    /// let tmpl = Template::new(asm, vec![hole]);
+    ///
+    /// let mut asm = Asm::new();
+    /// asm.add(Reg64::r9, Reg64::rcx);
+    /// assert_eq!(tmpl.stamp(&[u64::from(Reg64::r9.index())]), asm.into_code());
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `f` does not emit a single instruction which encodes the operand as register in
+    /// the `ModR/M` or opcode byte with a `REX` prefix, eg if the operand is converted to a 32 bit
+    /// register or used as base of a memory operand.
+    pub fn reg_hole(&mut self, f: impl Fn(&mut Asm, Reg64)) -> Hole {
+        let features = self.cpu_features();
+        let probe = |reg: Reg64| {
+            let mut asm = Asm::new();
+            asm.set_cpu_features(features);
+            f(&mut asm, reg);
+            asm.into_code()
+        };
+        const INVALID: &str = "Register hole must be a register operand encoded with REX prefix";
+
+        // Locate the low index bits with `rdi` (0b0111) and the REX bit with `r8` (0b1000).
+        let code = probe(Reg64::rax);
+        let diff = |other: &[u8]| -> Option<(usize, u8)> {
+            assert_eq!(code.len(), other.len(), "{}", INVALID);
+            let mut diffs = code
+                .iter()
+                .zip(other)
+                .enumerate()
+                .filter(|(_, (a, b))| a != b);
+            let (idx, (a, b)) = diffs.next()?;
+            diffs.next().is_none().then_some((idx, a ^ b))
+        };
+        let (off, shift) = match diff(&probe(Reg64::rdi)) {
+            Some((off, 0b111)) => (off, 0),
+            Some((off, 0b111_000)) => (off, 3),
+            _ => panic!("{}", INVALID),
+        };
+        let (rex, rex_bit) = match diff(&probe(Reg64::r8)) {
+            Some((rex, bit @ (0b100 | 0b001))) if code[rex] & 0xf0 == 0x40 => (rex, bit),
+            _ => panic!("{}", INVALID),
+        };
+        let hole = |start: usize| Hole {
+            off: start + off,
+            len: 1,
+            reg: Some(RegBits {
+                shift,
+                rex: start + rex,
+                rex_bit,
+            }),
+        };
+
+        // Registers with special encodings, eg `rsp` as memory base, change other bytes.
+        for reg in Reg64::iter() {
+            let mut patched = code.clone();
+            hole(0).patch(&mut patched, 0, u64::from(reg.index()));
+            assert_eq!(patched, probe(reg), "{}", INVALID);
+        }
+
+        let start = self.len();
+        f(self, Reg64::rax);
+        assert_eq!(self.len() - start, code.len(), "{}", INVALID);
+        hole(start)
+    }
+}
+
+/// A pre-encoded code template with holes for immediate values and registers.
+///
+/// ```rust
+/// use juicebox_asm::{Asm, Imm64, Reg64, Template};
+/// use juicebox_asm::insn::Mov;
+///
+/// let mut asm = Asm::new();
+/// asm.mov(Reg64::rax, Imm64::from(0));
+/// let hole = asm.hole(8);
+/// asm.ret();
+///
+/// let tmpl = Template::new(asm, vec![hole]);
+///
+/// // mov rax, 0x1122 ; ret
+/// let code = tmpl.stamp(&[0x1122]);
+/// assert_eq!(code, [0x48, 0xb8, 0x22, 0x11, 0, 0, 0, 0, 0, 0, 0xc3]);
+/// ```
+pub struct Template {
+    code: Vec<u8>,
+    holes: Vec<Hole>,
+}
+
+impl Template {
+    /// Create a template from the code emitted by `asm` with the given `holes`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any hole indexes out of bound of the emitted code.
+    pub fn new(asm: Asm, holes: Vec<Hole>) -> Template {
+        let code = asm.into_code();
+        for h in &holes {
+            assert!(h.off + h.len <= code.len(), "Hole out of bound of template");
+            if let Some(bits) = h.reg {
+                assert!(bits.rex < code.len(), "Hole out of bound of template");
+            }
+        }
+        Template { code, holes }
+    }
+
+    /// Get the holes of the template.
+    pub fn holes(&self) -> &[Hole] {
+        &self.holes
+    }
+
+    /// Stamp out a copy of the template, see [`Template::stamp_into`].
+    pub fn stamp(&self, values: &[u64]) -> Vec<u8> {
+        let mut code = Vec::with_capacity(self.code.len());
+        self.stamp_into(&mut code, values);
+        code
+    }
+
+    /// Append a copy of the template to `code` and patch the holes with `values`, given in the
+    /// same order as the holes. Each value is truncated to the length of its hole, register holes
+    /// take the register index, see [`Reg64::index`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of `values` does not match the number of holes, or a value of a
+    /// register hole is not a register index.
+    pub fn stamp_into(&self, code: &mut Vec<u8>, values: &[u64]) {
+        assert_eq!(
+            values.len(),
+            self.holes.len(),
+            "Number of values must match the number of holes"
+        );
+
+        let base = code.len();
+        code.extend_from_slice(&self.code);
+
+        for (h, val) in self.holes.iter().zip(values) {
+            h.patch(code, base, *val);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::insn::{Add, Mov, Push};
+    use crate::{Imm16, Imm64, Mem16, Mem64, Reg32};
+
+    #[test]
+    fn test_stamp() {
+        let mut asm = Asm::new();
+        asm.mov(Mem16::indirect_disp(Reg64::rdi, 4), Imm16::from(0u16));
+        let h1 = asm.hole(2);
+        asm.add(Mem16::indirect(Reg64::rsi), Imm16::from(0u16));
+        let h2 = asm.hole(2);
+        asm.ret();
+
+        let tmpl = Template::new(asm, vec![h1, h2]);
+        assert_eq!(tmpl.holes(), &[h1, h2]);
+
+        let mut code = Vec::new();
+        tmpl.stamp_into(&mut code, &[0xaabb, 0x1_ccdd]);
+        tmpl.stamp_into(&mut code, &[0x1122, 0x3344]);
+
+        #[rustfmt::skip]
+        assert_eq!(
+            code,
+            [
+                0x66, 0xc7, 0x87, 0x04, 0x00, 0x00, 0x00, 0xbb, 0xaa,
+                0x66, 0x81, 0x06, 0xdd, 0xcc,
+                0xc3,
+                0x66, 0xc7, 0x87, 0x04, 0x00, 0x00, 0x00, 0x22, 0x11,
+                0x66, 0x81, 0x06, 0x44, 0x33,
+                0xc3,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reg_hole() {
+        let mut asm = Asm::new();
+        let h1 = asm.reg_hole(|asm, r| asm.add(r, Reg64::rcx));
+        let h2 = asm.reg_hole(|asm, r| asm.add(Reg64::rdx, r));
+        let h3 = asm.reg_hole(|asm, r| asm.mov(r, Imm64::from(0)));
+        let h4 = asm.hole(8);
+        let h5 = asm.reg_hole(|asm, r| asm.mov(r, Mem64::indirect_disp(Reg64::rsp, 8)));
+        let h6 = asm.reg_hole(|asm, r| asm.push(r));
+        asm.ret();
+        let tmpl = Template::new(asm, vec![h1, h2, h3, h4, h5, h6]);
+        assert!(h1.is_reg() && !h4.is_reg());
+
+        for r in Reg64::iter() {
+            let idx = u64::from(r.index());
+            let code = tmpl.stamp(&[idx, idx, idx, 0x1122, idx, idx]);
+
+            let mut asm = Asm::new();
+            asm.add(r, Reg64::rcx);
+            asm.add(Reg64::rdx, r);
+            asm.mov(r, Imm64::from(0x1122));
+            asm.mov(r, Mem64::indirect_disp(Reg64::rsp, 8));
+            asm.push(r);
+            asm.ret();
+            assert_eq!(code, asm.into_code(), "{}", r);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Register hole must be a register operand encoded with REX prefix")]
+    fn test_reg_hole_reg32() {
+        Asm::new().reg_hole(|asm, r| asm.add(r.to32(), Reg32::ecx));
+    }
+
+    #[test]
+    #[should_panic(expected = "Register hole must be a register operand encoded with REX prefix")]
+    fn test_reg_hole_mem_base() {
+        Asm::new().reg_hole(|asm, r| asm.mov(Reg64::rax, Mem64::indirect(r)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Register hole value must be a register index")]
+    fn test_reg_hole_invalid_value() {
+        let mut asm = Asm::new();
+        let h = asm.reg_hole(|asm, r| asm.add(r, Reg64::rcx));
+        Template::new(asm, vec![h]).stamp(&[16]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_hole_too_large() {
+        let mut asm = Asm::new();
+        asm.ret();
+        asm.hole(2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_stamp_missing_value() {
+        let mut asm = Asm::new();
+        asm.mov(Reg64::rax, Imm64::from(0));
+        let h = asm.hole(8);
+        Template::new(asm, vec![h]).stamp(&[]);
+    }
+}
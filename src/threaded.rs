@@ -0,0 +1,203 @@
+//! Generator for direct-threaded dispatch of bytecode interpreters, the pattern of a computed
+//! `goto *table[opcode]`.
+//!
+//! Each handler ends with its own copy of the dispatch, fetching the next opcode and jumping
+//! indirectly through a [DispatchTable] to its handler. Compared to a central dispatch loop, the
+//! branch predictor tracks the indirect jump of each handler separately, which predicts the
+//! opcode sequences of the interpreted program much better.
+//!
+//! ```rust
+//! use juicebox_asm::insn::{Add, Dec, Inc, Movzx, Xor};
+//! use juicebox_asm::{Asm, DispatchTable, Mem8, Reg32, Reg64::*, Runtime};
+//!
+//! // Opcodes: 0 = halt, 1 = inc, 2 = double.
+//! let mut tbl = DispatchTable::new(3, rcx, rdx, |asm| {
+//!     asm.movzx(Reg32::ecx, Mem8::indirect(rdi));
+//!     asm.inc(rdi);
+//! });
+//!
+//! // fn(code: *const u8) -> u64
+//! let mut asm = Asm::new();
+//! asm.xor(Reg32::eax, Reg32::eax);
+//! asm.dispatch(&mut tbl);
+//!
+//! asm.bind(tbl.handler(0));
+//! asm.ret();
+//! asm.bind(tbl.handler(1));
+//! asm.inc(rax);
+//! asm.dispatch(&mut tbl);
+//! asm.bind(tbl.handler(2));
+//! asm.add(rax, rax);
+//! asm.dispatch(&mut tbl);
+//!
+//! asm.dispatch_table(&mut tbl);
+//!
+//! let mut rt = Runtime::new();
+//! let run = unsafe { rt.add_code::<extern "C" fn(*const u8) -> u64>(asm.into_code()) };
+//! assert_eq!(run([1, 2, 1, 2, 0].as_ptr()), 6);
+//! ```
+
+use crate::{Asm, Label, Reg64};
+
+/// Table of handler labels indexed by opcode, together with the opcode fetch recipe, see
+/// [`Asm::dispatch`].
+///
+/// The handlers are emitted as jump table, see [`Asm::jump_table`], hence the emitted code is
+/// position independent.
+pub struct DispatchTable {
+    /// Location of the table, see [`Asm::dispatch_table`].
+    table: Label,
+    /// Handlers indexed by opcode.
+    handlers: Vec<Label>,
+    /// Register holding the fetched opcode.
+    opc: Reg64,
+    /// Register clobbered by the dispatch.
+    tmp: Reg64,
+    /// Emits the opcode fetch.
+    fetch: Box<dyn Fn(&mut Asm)>,
+}
+
+impl DispatchTable {
+    /// Create a table with `len` handlers.
+    ///
+    /// `fetch` emits the fetch of the next opcode, which must leave the opcode zero extended in
+    /// `opc`, eg with a `movzx` from the bytecode and an increment of the bytecode pointer.
+    /// Each dispatch clobbers `opc` and `tmp`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `opc` and `tmp` are the same register.
+    pub fn new(len: usize, opc: Reg64, tmp: Reg64, fetch: impl Fn(&mut Asm) + 'static) -> Self {
+        assert_ne!(opc, tmp, "Dispatch registers must differ");
+        DispatchTable {
+            table: Label::new(),
+            handlers: (0..len).map(|_| Label::new()).collect(),
+            opc,
+            tmp,
+            fetch: Box::new(fetch),
+        }
+    }
+
+    /// Get the label of the handler for the opcode `op`, which must be bound to the handler code.
+    ///
+    /// Opcodes sharing a handler bind their labels to the same location.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `op` is out of range of the table.
+    pub fn handler(&mut self, op: usize) -> &mut Label {
+        &mut self.handlers[op]
+    }
+}
+
+impl Asm {
+    /// Emit a dispatch to the handler of the next opcode, at the loop entry and at the end of
+    /// each handler.
+    ///
+    /// The fetched opcode is not range checked, it must be valid for the table.
+    pub fn dispatch(&mut self, tbl: &mut DispatchTable) {
+        let DispatchTable { opc, tmp, .. } = *tbl;
+
+        (tbl.fetch)(self);
+        self.jmp_table(&mut tbl.table, opc, tmp);
+    }
+
+    /// Emit the [DispatchTable] out of the instruction stream, eg after the last handler.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the table was already emitted.
+    pub fn dispatch_table(&mut self, tbl: &mut DispatchTable) {
+        self.jump_table(&mut tbl.table, &mut tbl.handlers);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::insn::{Add, Dec, Inc, Movzx, Xor};
+    use crate::{Mem8, Reg32, Runtime};
+    use Reg64::*;
+
+    fn fetch(asm: &mut Asm) {
+        asm.movzx(Reg32::ecx, Mem8::indirect(rdi));
+        asm.inc(rdi);
+    }
+
+    #[test]
+    fn test_table() {
+        let mut tbl = DispatchTable::new(3, rcx, rdx, |_| {});
+        let mut asm = Asm::new();
+        asm.bind(tbl.handler(0));
+        asm.bind(tbl.handler(2));
+        asm.nop();
+        asm.bind(tbl.handler(1));
+        asm.nop();
+        asm.dispatch_table(&mut tbl);
+        assert_eq!(
+            asm.into_code(),
+            [
+                0x90, 0x90, // handlers
+                0xfe, 0xff, 0xff, 0xff, // 0
+                0xff, 0xff, 0xff, 0xff, // 1
+                0xfe, 0xff, 0xff, 0xff, // 2
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dispatch() {
+        let mut tbl = DispatchTable::new(1, rcx, r9, |_| {});
+        let mut asm = Asm::new();
+        asm.bind(tbl.handler(0));
+        asm.dispatch(&mut tbl);
+        asm.dispatch_table(&mut tbl);
+        assert_eq!(
+            asm.into_code(),
+            [
+                0x4c, 0x8d, 0x0d, 0x0e, 0x00, 0x00, 0x00, // lea r9, [rip+0xe]
+                0x49, 0x63, 0x8c, 0x89, 0x00, 0x00, 0x00, 0x00, // movsxd rcx, [r9+rcx*4+0x0]
+                0x49, 0x01, 0xc9, // add r9, rcx
+                0x49, 0xff, 0xe1, // jmp r9
+                0xeb, 0xff, 0xff, 0xff, // table
+            ]
+        );
+    }
+
+    #[test]
+    fn test_interp() {
+        // Opcodes: 0 = halt, 1 = inc, 2 = dec, 3 = double, 4 = nop.
+        let mut tbl = DispatchTable::new(5, rcx, rdx, fetch);
+
+        // fn(code: *const u8) -> u64
+        let mut asm = Asm::new();
+        asm.xor(Reg32::eax, Reg32::eax);
+        asm.dispatch(&mut tbl);
+
+        asm.bind(tbl.handler(0));
+        asm.ret();
+        asm.bind(tbl.handler(1));
+        asm.inc(rax);
+        asm.dispatch(&mut tbl);
+        asm.bind(tbl.handler(2));
+        asm.dec(rax);
+        asm.dispatch(&mut tbl);
+        asm.bind(tbl.handler(3));
+        asm.add(rax, rax);
+        asm.bind(tbl.handler(4));
+        asm.dispatch(&mut tbl);
+
+        asm.dispatch_table(&mut tbl);
+
+        let mut rt = Runtime::new();
+        let run = unsafe { rt.add_code::<extern "C" fn(*const u8) -> u64>(asm.into_code()) };
+        assert_eq!(run([0].as_ptr()), 0);
+        assert_eq!(run([1, 1, 3, 1, 4, 3, 2, 0].as_ptr()), 9);
+    }
+
+    #[test]
+    #[should_panic(expected = "Dispatch registers must differ")]
+    fn test_same_regs() {
+        DispatchTable::new(1, rcx, rcx, fetch);
+    }
+}
@@ -0,0 +1,56 @@
+//! Support for tiered compilation, detecting hot jitted functions to recompile them.
+//!
+//! A function is instrumented with an invocation counter prologue using [`Asm::hot_counter`],
+//! which invokes a hook once the function becomes hot. The recompiled function can then be
+//! installed and the old entry forwarded to it with [`Runtime::redirect`](crate::Runtime::redirect).
+
+use std::ffi::c_void;
+
+use crate::insn::{Call, Dec, Jnz, Mov, Pop, Push};
+use crate::{Asm, Imm64, Label, Mem64, Reg64};
+
+/// Hook invoked by the invocation counter prologue, see [`Asm::hot_counter`].
+pub type HotHook = extern "C" fn(data: *mut c_void);
+
+impl Asm {
+    /// Emit an invocation counter prologue, which must be emitted at the function entry.
+    ///
+    /// On each invocation the prologue atomically decrements the value at `counter`, the user
+    /// initializes it with the hotness threshold. When the counter reaches zero, `hook` is invoked
+    /// exactly once with `data` as argument before the function body continues to execute. The
+    /// decrement is a `lock dec`, hence this also holds if the function is executed by multiple
+    /// threads concurrently, the hook is invoked by the thread observing the transition to zero.
+    ///
+    /// The prologue preserves the integer argument registers (`rdi`, `rsi`, `rdx`, `rcx`, `r8`,
+    /// `r9`) and `rax` according to the SystemV abi. Vector registers are not preserved, hence the
+    /// `hook` must not clobber them if the function receives floating point arguments.
+    ///
+    /// `counter` and `data` are embedded as absolute addresses in the emitted code and must be
+    /// valid as long as the emitted code can be executed.
+    pub fn hot_counter(&mut self, counter: *mut u64, hook: HotHook, data: *mut c_void) {
+        use Reg64::*;
+
+        let mut skip = Label::new();
+
+        self.push(rax);
+        self.mov(rax, Imm64::from(counter as usize));
+        self.lock(|asm| asm.dec(Mem64::indirect(rax)));
+        self.jnz(&mut skip);
+
+        // Counter reached zero, invoke the hook. Together with rax, seven registers are pushed
+        // which keeps the stack 16 byte aligned at the call, as required by the SystemV abi.
+        let args = [rdi, rsi, rdx, rcx, r8, r9];
+        for r in args {
+            self.push(r);
+        }
+        self.mov(rdi, Imm64::from(data as usize));
+        self.mov(rax, Imm64::from(hook as usize));
+        self.call(rax);
+        for r in args.into_iter().rev() {
+            self.pop(r);
+        }
+
+        self.bind(&mut skip);
+        self.pop(rax);
+    }
+}
@@ -0,0 +1,133 @@
+//! Metadata of emitted instructions which may trap, eg guest memory accesses into a
+//! [`GuardRegion`](crate::guard::GuardRegion).
+//!
+//! Instructions emitted with [`Asm::trapping`] are recorded in the [TrapTable] of the assembler
+//! together with a user trap code. A signal handler looks up the faulting instruction pointer in
+//! the table to turn the fault into a clean guest error.
+
+use crate::Asm;
+
+/// An emitted instruction which may trap.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TrapSite {
+    /// Offset of the first byte of the instruction in the code.
+    pub off: usize,
+    /// Length in bytes of the instruction.
+    pub len: usize,
+    /// User trap code.
+    pub code: u32,
+}
+
+/// Table of [TrapSite]s ordered by offset, see [`Asm::traps`].
+///
+/// Lookups do not allocate, which allows to use the table from signal handlers.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TrapTable {
+    sites: Vec<TrapSite>,
+}
+
+impl TrapTable {
+    /// Add a trap site, keeping the sites ordered by offset.
+    fn add(&mut self, site: TrapSite) {
+        let idx = self.sites.partition_point(|s| s.off <= site.off);
+        self.sites.insert(idx, site);
+    }
+
+    /// Add the trap sites of `other`, with the code of `other` placed at offset `base`.
+    pub(crate) fn merge(&mut self, other: &TrapTable, base: usize) {
+        for site in &other.sites {
+            self.add(TrapSite {
+                off: base + site.off,
+                ..*site
+            });
+        }
+    }
+
+    /// Get all trap sites ordered by offset.
+    pub fn sites(&self) -> &[TrapSite] {
+        &self.sites
+    }
+
+    /// Look up the trap code of the instruction covering the code offset `off`.
+    pub fn lookup(&self, off: usize) -> Option<u32> {
+        let idx = self
+            .sites
+            .partition_point(|s| s.off <= off)
+            .checked_sub(1)?;
+        let site = &self.sites[idx];
+        (off < site.off + site.len).then_some(site.code)
+    }
+
+    /// Look up the trap code of the instruction at `rip`, where the code starts at `entry`, eg the
+    /// function address returned by [`Runtime::add_code`](crate::Runtime::add_code).
+    pub fn lookup_rip(&self, entry: usize, rip: usize) -> Option<u32> {
+        self.lookup(rip.checked_sub(entry)?)
+    }
+}
+
+impl Asm {
+    /// Emit the instructions of `f` and record them as possibly trapping with the user trap
+    /// `code`, see [`Asm::traps`].
+    ///
+    /// ```rust
+    /// use juicebox_asm::insn::Mov;
+    /// use juicebox_asm::{Asm, Mem64, Reg64::*};
+    ///
+    /// const OOB: u32 = 1;
+    ///
+    /// let mut asm = Asm::new();
+    /// asm.mov(rax, rdi);
+    /// asm.trapping(OOB, |asm| asm.mov(rax, Mem64::indirect_base_index(rdi, rsi)));
+    /// asm.ret();
+    ///
+    /// assert_eq!(asm.traps().lookup(0), None);
+    /// assert_eq!(asm.traps().lookup(3), Some(OOB));
+    /// assert_eq!(asm.traps().lookup(7), None);
+    /// ```
+    pub fn trapping(&mut self, code: u32, f: impl FnOnce(&mut Asm)) {
+        let off = self.len();
+        f(self);
+        let len = self.len() - off;
+        if len > 0 {
+            self.traps_mut().add(TrapSite { off, len, code });
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_lookup() {
+        let mut t = TrapTable::default();
+        t.add(TrapSite {
+            off: 10,
+            len: 4,
+            code: 2,
+        });
+        t.add(TrapSite {
+            off: 2,
+            len: 3,
+            code: 1,
+        });
+
+        assert_eq!(t.sites()[0].off, 2);
+        assert_eq!(t.lookup(0), None);
+        assert_eq!(t.lookup(2), Some(1));
+        assert_eq!(t.lookup(4), Some(1));
+        assert_eq!(t.lookup(5), None);
+        assert_eq!(t.lookup(13), Some(2));
+        assert_eq!(t.lookup(14), None);
+
+        assert_eq!(t.lookup_rip(0x1000, 0x100a), Some(2));
+        assert_eq!(t.lookup_rip(0x1000, 0x0fff), None);
+    }
+
+    #[test]
+    fn test_trapping_empty() {
+        let mut asm = Asm::new();
+        asm.trapping(1, |_| {});
+        assert!(asm.traps().sites().is_empty());
+    }
+}
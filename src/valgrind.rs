@@ -0,0 +1,60 @@
+//! [Valgrind client requests](https://valgrind.org/docs/manual/manual-core-adv.html#manual-core-adv.clientreq)
+//! to annotate jitted code.
+//!
+//! Valgrind translates and caches the code it executes. When code is installed into memory
+//! which previously held other code, or code is patched or unmapped, the cached translations
+//! become stale. The [`Runtime`](crate::Runtime) therefore discards the translations of the
+//! affected memory when installing, patching or freeing code.
+//!
+//! When not running under valgrind, the client requests are a nop.
+
+/// Client request codes from `valgrind.h`.
+const RUNNING_ON_VALGRIND: u64 = 0x1001;
+const DISCARD_TRANSLATIONS: u64 = 0x1002;
+
+/// Issue the client request `req` with its `args` and get the result, which is `default` if not
+/// running under valgrind.
+fn client_request(default: u64, req: u64, args: [u64; 5]) -> u64 {
+    let req = [req, args[0], args[1], args[2], args[3], args[4]];
+    let mut res = default;
+    // The special instruction preamble recognized by valgrind on amd64, see `valgrind.h`. The
+    // rotations of rdi sum up to 128 and leave rdi unmodified on a real cpu.
+    unsafe {
+        std::arch::asm!(
+            "rol rdi, 3",
+            "rol rdi, 13",
+            "rol rdi, 61",
+            "rol rdi, 51",
+            "xchg rbx, rbx",
+            in("rax") req.as_ptr(),
+            inout("rdx") res,
+            options(nostack, preserves_flags),
+        );
+    }
+    res
+}
+
+/// Check if the process is running under valgrind, gives the number of nested valgrind
+/// instances or `0` if not running under valgrind.
+pub fn running_on_valgrind() -> u64 {
+    client_request(0, RUNNING_ON_VALGRIND, [0; 5])
+}
+
+/// Discard the cached translations of the code in `[addr, addr+len)`.
+pub(crate) fn discard_translations(addr: *const u8, len: usize) {
+    client_request(0, DISCARD_TRANSLATIONS, [addr as u64, len as u64, 0, 0, 0]);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_native() {
+        // Test suite is not expected to run under valgrind.
+        if running_on_valgrind() == 0 {
+            let code = [0xc3u8];
+            discard_translations(code.as_ptr(), code.len());
+        }
+    }
+}
@@ -0,0 +1,152 @@
+//! Support for the [Intel VTune JIT profiling api][jitapi].
+//!
+//! This mirrors the `jitprofiling` static library shipped with VTune: when profiling with VTune,
+//! the profiler agent library is announced in the `INTEL_JIT_PROFILER64` environment variable.
+//! The agent is loaded on first use and notified about code loaded to and unloaded from a
+//! [`Runtime`](crate::Runtime). If no agent is announced, all notifications are a nop.
+//!
+//! [jitapi]: https://www.intel.com/content/www/us/en/docs/vtune-profiler/user-guide/current/jit-profiling-api.html
+
+use std::ffi::{c_char, c_int, c_uint, c_void, CString};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::OnceLock;
+
+/// `iJVM_EVENT_TYPE_METHOD_LOAD_FINISHED`.
+const EVENT_METHOD_LOAD_FINISHED: c_int = 13;
+/// `iJVM_EVENT_TYPE_METHOD_UNLOAD_START`.
+const EVENT_METHOD_UNLOAD_START: c_int = 14;
+
+pub use crate::LineInfo;
+
+/// `iJIT_Method_Load`.
+#[repr(C)]
+struct MethodLoad {
+    method_id: c_uint,
+    method_name: *const c_char,
+    method_load_address: *const c_void,
+    method_size: c_uint,
+    line_number_size: c_uint,
+    line_number_table: *const LineInfo,
+    class_id: c_uint,
+    class_file_name: *const c_char,
+    source_file_name: *const c_char,
+}
+
+/// `iJIT_Method_Id`.
+#[repr(C)]
+struct MethodId {
+    method_id: c_uint,
+}
+
+type NotifyEvent = unsafe extern "C" fn(event: c_int, data: *mut c_void) -> c_int;
+
+/// Get the `NotifyEvent` entry of the profiler agent, `None` if no agent is announced.
+fn agent() -> Option<NotifyEvent> {
+    static AGENT: OnceLock<Option<NotifyEvent>> = OnceLock::new();
+
+    *AGENT.get_or_init(|| {
+        let path = std::env::var("INTEL_JIT_PROFILER64").ok()?;
+        let path = CString::new(path).ok()?;
+        unsafe {
+            let lib = libc::dlopen(path.as_ptr(), libc::RTLD_LAZY);
+            if lib.is_null() {
+                return None;
+            }
+
+            // The agent optionally provides an initialization function.
+            let init = libc::dlsym(lib, c"Initialize".as_ptr());
+            if !init.is_null() {
+                let init: unsafe extern "C" fn() -> c_int = std::mem::transmute(init);
+                init();
+            }
+
+            let notify = libc::dlsym(lib, c"NotifyEvent".as_ptr());
+            if notify.is_null() {
+                return None;
+            }
+            Some(std::mem::transmute::<*mut c_void, NotifyEvent>(notify))
+        }
+    })
+}
+
+/// Check if a VTune profiler agent is loaded.
+pub fn is_active() -> bool {
+    agent().is_some()
+}
+
+/// Notify the profiler about the function `name` with `size` bytes of code loaded at `addr` and
+/// get the method id to unload it with [`notify_unload`]. Optionally a `source` file with `lines`
+/// mapping code offsets to source lines can be provided.
+///
+/// Returns `None` if no profiler agent is loaded.
+pub fn notify_load(
+    name: &str,
+    addr: *const u8,
+    size: usize,
+    source: Option<&str>,
+    lines: &[LineInfo],
+) -> Option<u32> {
+    static NEXT_ID: AtomicU32 = AtomicU32::new(1);
+
+    let notify = agent()?;
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+
+    // Interior nul bytes can not be represented, drop the strings in that case.
+    let name = CString::new(name).unwrap_or_default();
+    let source = source.and_then(|s| CString::new(s).ok());
+
+    // UNWRAP: Code and line tables on the runtime pages fit into an u32.
+    let mut load = MethodLoad {
+        method_id: id,
+        method_name: name.as_ptr(),
+        method_load_address: addr.cast(),
+        method_size: u32::try_from(size).unwrap(),
+        line_number_size: u32::try_from(lines.len()).unwrap(),
+        line_number_table: if lines.is_empty() {
+            std::ptr::null()
+        } else {
+            lines.as_ptr()
+        },
+        class_id: 0,
+        class_file_name: std::ptr::null(),
+        source_file_name: source.as_ref().map_or(std::ptr::null(), |s| s.as_ptr()),
+    };
+    unsafe {
+        notify(
+            EVENT_METHOD_LOAD_FINISHED,
+            (&mut load as *mut MethodLoad).cast(),
+        )
+    };
+    Some(id)
+}
+
+/// Notify the profiler that the function with the method `id` obtained from [`notify_load`] is
+/// unloaded.
+pub fn notify_unload(id: u32) {
+    if let Some(notify) = agent() {
+        let mut id = MethodId { method_id: id };
+        unsafe { notify(EVENT_METHOD_UNLOAD_START, (&mut id as *mut MethodId).cast()) };
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_layout() {
+        // Must match the layout of iJIT_Method_Load on x64.
+        assert_eq!(std::mem::size_of::<MethodLoad>(), 64);
+        assert_eq!(std::mem::size_of::<LineInfo>(), 8);
+    }
+
+    #[test]
+    fn test_inactive() {
+        // Test suite is not expected to run under VTune.
+        if !is_active() {
+            let code = [0xc3u8];
+            assert_eq!(notify_load("f", code.as_ptr(), code.len(), None, &[]), None);
+            notify_unload(1);
+        }
+    }
+}
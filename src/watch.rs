@@ -0,0 +1,353 @@
+//! Watchpoint instrumentation of memory accesses in emitted code.
+//!
+//! When a [Watch] is enabled on the [Asm] with [`Asm::set_watch`], each instruction emitted
+//! afterwards with an explicit memory operand is preceded by a check of the accessed address. If
+//! the accessed bytes overlap one of the watch ranges, the [WatchHook] is invoked with the
+//! address and size of the access before the instruction executes. This allows to build memory
+//! tracing tools on top of the assembler, without changing the code generator.
+//!
+//! The check preserves all general purpose registers and the flags, and does not touch the red
+//! zone below the stack pointer. Vector registers are not preserved across the hook. Implicit
+//! memory accesses, eg of `push`, `ret` or the string instructions, are not instrumented and
+//! neither is `lea`, which computes the address without accessing memory.
+//!
+//! ```rust
+//! use std::ffi::c_void;
+//! use juicebox_asm::insn::Mov;
+//! use juicebox_asm::{Asm, Mem64, Reg64::*, Runtime, Watch};
+//!
+//! extern "C" fn hook(data: *mut c_void, addr: usize, size: usize) {
+//!     let hits = unsafe { &mut *data.cast::<Vec<(usize, usize)>>() };
+//!     hits.push((addr, size));
+//! }
+//!
+//! let mut hits: Vec<(usize, usize)> = Vec::new();
+//! let mut mem = [0u64; 4];
+//! let base = mem.as_mut_ptr() as usize;
+//!
+//! let mut watch = Watch::new(hook, (&mut hits as *mut Vec<(usize, usize)>).cast());
+//! watch.add_range(base + 16..base + 24);
+//!
+//! let mut asm = Asm::new();
+//! asm.set_watch(Some(watch));
+//! asm.mov(Mem64::indirect_disp(rdi, 8), rsi);
+//! asm.mov(Mem64::indirect_disp(rdi, 16), rsi);
+//! asm.ret();
+//!
+//! let mut rt = Runtime::new();
+//! let f = unsafe { rt.add_code::<extern "C" fn(*mut u64, u64)>(asm.into_code()) };
+//! f(mem.as_mut_ptr(), 42);
+//!
+//! assert_eq!(mem, [0, 42, 42, 0]);
+//! assert_eq!(hits, [(base + 16, 8)]);
+//! ```
+
+use std::ffi::c_void;
+use std::ops::Range;
+
+use crate::insn::{And, Call, Jmp, Lea, Mov, Pop, Push, Sub};
+use crate::mem::{AddrMode, Mem};
+use crate::{Asm, Cond, Imm64, Imm8, Label, Mem64, Reg64};
+
+/// Hook invoked with the user `data` for an access of `size` bytes at `addr`, which overlaps a
+/// watch range, see [Watch].
+pub type WatchHook = extern "C" fn(data: *mut c_void, addr: usize, size: usize);
+
+/// Size of the red zone below the stack pointer, which must not be clobbered.
+const RED_ZONE: i32 = 128;
+
+/// Registers saved around the range check, `rax` holds the accessed address.
+const SAVED: [Reg64; 3] = [Reg64::rax, Reg64::rcx, Reg64::rdx];
+
+/// Caller saved registers additionally saved around the hook invocation.
+const SAVED_CALL: [Reg64; 6] = [
+    Reg64::rsi,
+    Reg64::rdi,
+    Reg64::r8,
+    Reg64::r9,
+    Reg64::r10,
+    Reg64::r11,
+];
+
+/// Watch configuration for instrumenting memory accesses, see [`Asm::set_watch`].
+///
+/// The watch ranges are embedded into the instrumentation code, ranges added after the code was
+/// emitted have no effect on it.
+#[derive(Clone, Debug)]
+pub struct Watch {
+    ranges: Vec<Range<usize>>,
+    hook: WatchHook,
+    data: *mut c_void,
+}
+
+impl Watch {
+    /// Create a watch without any watch ranges, invoking `hook` with the user `data`.
+    pub fn new(hook: WatchHook, data: *mut c_void) -> Watch {
+        Watch {
+            ranges: Vec::new(),
+            hook,
+            data,
+        }
+    }
+
+    /// Add the watch range `range` of byte addresses.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is empty.
+    pub fn add_range(&mut self, range: Range<usize>) {
+        assert!(range.start < range.end, "Watch range must not be empty");
+        self.ranges.push(range);
+    }
+
+    /// Get the watch ranges.
+    pub fn ranges(&self) -> &[Range<usize>] {
+        &self.ranges
+    }
+}
+
+/// Memory access of the instruction currently being emitted, recorded for the instrumentation.
+#[derive(Clone, Copy)]
+pub(crate) struct WatchAccess {
+    mem: Mem64,
+    size: usize,
+}
+
+impl WatchAccess {
+    /// Record the access of the memory operand `op`.
+    pub(crate) fn new<M: Mem>(op: &M) -> WatchAccess {
+        let mem = match op.mode() {
+            AddrMode::Indirect | AddrMode::IndirectDisp => {
+                Mem64::indirect_disp(op.base(), op.disp())
+            }
+            AddrMode::IndirectBaseIndex | AddrMode::IndirectBaseIndexScaleDisp => {
+                Mem64::indirect_base_index_scale_disp(op.base(), op.index(), op.scale(), op.disp())
+            }
+        };
+        WatchAccess {
+            mem,
+            size: M::size(),
+        }
+    }
+
+    /// Get the memory operand addressing the access after `adj` bytes were pushed to the stack.
+    fn adjusted(&self, adj: i32) -> Mem64 {
+        let m = self.mem;
+        if m.base() != Reg64::rsp {
+            return m;
+        }
+        let disp = m.disp().checked_add(adj).expect("Displacement overflow");
+        match m.mode() {
+            AddrMode::IndirectDisp => Mem64::indirect_disp(m.base(), disp),
+            _ => Mem64::indirect_base_index_scale_disp(m.base(), m.index(), m.scale(), disp),
+        }
+    }
+}
+
+impl Asm {
+    /// Enable watch instrumentation with `watch` or disable it with `None`. From now on each
+    /// emitted instruction with a memory operand invokes the hook of the watch, if the access
+    /// overlaps a watch range.
+    pub fn set_watch(&mut self, watch: Option<Watch>) {
+        *self.watch_mut() = watch;
+    }
+
+    /// Emit the instrumentation for the `access` of the instruction `mnemonic`, which is moved in
+    /// front of the instruction by the caller.
+    pub(crate) fn watch_insn(&mut self, mnemonic: &'static str, access: WatchAccess) {
+        if mnemonic == "lea" {
+            return;
+        }
+        // Taking the watch disables the instrumentation of the instrumentation code.
+        let Some(watch) = self.watch_mut().take() else {
+            return;
+        };
+        self.emit_watch(&watch, access);
+        *self.watch_mut() = Some(watch);
+    }
+
+    /// Emit the check of `access` against the ranges of `watch`.
+    fn emit_watch(&mut self, watch: &Watch, access: WatchAccess) {
+        use Reg64::*;
+
+        let mut hit = Label::new();
+        let mut done = Label::new();
+
+        self.lea(rsp, Mem64::indirect_disp(rsp, -RED_ZONE));
+        self.pushfq();
+        for r in SAVED {
+            self.push(r);
+        }
+
+        // CAST: The number of saved registers is small.
+        let pushed = RED_ZONE + 8 * (1 + SAVED.len() as i32);
+        self.lea(rax, access.adjusted(pushed));
+
+        // The access [addr, addr + size) overlaps the range [start, end) iff
+        //   addr - (start - size + 1) < (end - start) + size - 1
+        // when computed with unsigned wrap around arithmetic.
+        for r in &watch.ranges {
+            let lo = r.start.wrapping_sub(access.size - 1);
+            let span = (r.end - r.start).wrapping_add(access.size - 1);
+            self.mov(rcx, rax);
+            self.mov(rdx, Imm64::from(lo));
+            self.sub(rcx, rdx);
+            self.mov(rdx, Imm64::from(span));
+            self.sub(rcx, rdx);
+            self.jcc(Cond::B, &mut hit);
+        }
+        self.jmp(&mut done);
+
+        self.bind(&mut hit);
+        for r in SAVED_CALL {
+            self.push(r);
+        }
+        // Align the stack to 16 byte for the call, rbx is callee saved.
+        self.push(rbx);
+        self.mov(rbx, rsp);
+        self.and(rsp, Imm8::from(-16i8));
+        self.mov(rsi, rax);
+        self.mov(rdx, Imm64::from(access.size));
+        self.mov(rdi, Imm64::from(watch.data as usize));
+        self.mov(rax, Imm64::from(watch.hook as usize));
+        self.call(rax);
+        self.mov(rsp, rbx);
+        self.pop(rbx);
+        for r in SAVED_CALL.into_iter().rev() {
+            self.pop(r);
+        }
+
+        self.bind(&mut done);
+        for r in SAVED.into_iter().rev() {
+            self.pop(r);
+        }
+        self.popfq();
+        self.lea(rsp, Mem64::indirect_disp(rsp, RED_ZONE));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::insn::{Adc, Add};
+    use crate::{Imm16, Mem16, Mem32, Mem8, Reg32, Reg8, Runtime};
+
+    type Hits = Vec<(usize, usize)>;
+
+    extern "C" fn record(data: *mut c_void, addr: usize, size: usize) {
+        let hits = unsafe { &mut *data.cast::<Hits>() };
+        hits.push((addr, size));
+    }
+
+    fn watch(hits: &mut Hits, ranges: &[(usize, usize)]) -> Watch {
+        let mut watch = Watch::new(record, (hits as *mut Hits).cast());
+        for &(start, end) in ranges {
+            watch.add_range(start..end);
+        }
+        watch
+    }
+
+    #[test]
+    fn test_overlap() {
+        use Reg64::*;
+
+        let mut hits = Hits::new();
+        let mut mem = [0u8; 32];
+        let base = mem.as_mut_ptr() as usize;
+
+        // fn(p: *mut u8) storing to p[0..4], p[8..16], p[20] and p[28..30].
+        let mut asm = Asm::new();
+        asm.set_watch(Some(watch(
+            &mut hits,
+            &[(base + 3, base + 9), (base + 20, base + 21)],
+        )));
+        asm.mov(Mem32::indirect(rdi), Reg32::esi);
+        asm.mov(rax, Imm64::from(2u64));
+        asm.mov(Mem64::indirect_disp(rdi, 8), rax);
+        asm.mov(rcx, Imm64::from(20u64));
+        asm.mov(Mem8::indirect_base_index(rdi, rcx), Reg8::dl);
+        asm.mov(Mem16::indirect_disp(rdi, 28), Imm16::from(4u16));
+        asm.ret();
+
+        let mut rt = Runtime::new();
+        let f = unsafe { rt.add_code::<extern "C" fn(*mut u8, u32, u8)>(asm.into_code()) };
+        f(mem.as_mut_ptr(), 1, 3);
+
+        assert_eq!(&mem[..4], &1u32.to_ne_bytes());
+        assert_eq!(&mem[8..16], &2u64.to_ne_bytes());
+        assert_eq!(mem[20], 3);
+        assert_eq!(&mem[28..30], &4u16.to_ne_bytes());
+        assert_eq!(hits, [(base, 4), (base + 8, 8), (base + 20, 1)]);
+    }
+
+    #[test]
+    fn test_preserve() {
+        use Reg64::*;
+
+        let mut hits = Hits::new();
+        let mut val = 0u64;
+        let addr = &mut val as *mut u64 as usize;
+
+        // fn(p: *mut u64, a: u64, b: u64) -> u64, computing a + b with the flags of the add
+        // carried across the instrumented store, and a load relative to rsp.
+        let mut asm = Asm::new();
+        asm.set_watch(Some(watch(&mut hits, &[(addr, addr + 8)])));
+        asm.push(rdx);
+        asm.mov(rax, rsi);
+        asm.add(rax, rdx);
+        asm.mov(Mem64::indirect(rdi), rax);
+        asm.mov(rdx, Imm64::from(0u64));
+        asm.adc(rax, Imm8::from(0u8));
+        asm.mov(rcx, Mem64::indirect(rsp));
+        asm.add(rax, rcx);
+        asm.pop(rdx);
+        asm.ret();
+
+        let mut rt = Runtime::new();
+        let f = unsafe { rt.add_code::<extern "C" fn(*mut u64, u64, u64) -> u64>(asm.into_code()) };
+
+        // (u64::MAX + 2) carries, the load adds b again.
+        assert_eq!(f(&mut val, u64::MAX, 2), 1 + 1 + 2);
+        assert_eq!(val, 1);
+        assert_eq!(hits, [(addr, 8)]);
+    }
+
+    #[test]
+    fn test_lock() {
+        use Reg64::*;
+
+        let mut hits = Hits::new();
+        let mut val = 1u64;
+        let addr = &mut val as *mut u64 as usize;
+
+        let mut asm = Asm::new();
+        asm.set_watch(Some(watch(&mut hits, &[(addr, addr + 8)])));
+        asm.lea(rax, Mem64::indirect(rdi));
+        asm.lock(|asm| asm.add(Mem64::indirect(rdi), rsi));
+        asm.ret();
+
+        let code = asm.into_code();
+        assert_eq!(&code[..3], &[0x48, 0x8d, 0x07], "lea not instrumented");
+        let lock = code.len() - 5;
+        assert_eq!(&code[lock..], &[0xf0, 0x48, 0x01, 0x37, 0xc3]);
+
+        let mut rt = Runtime::new();
+        let f = unsafe { rt.add_code::<extern "C" fn(*mut u64, u64)>(code) };
+        f(&mut val, 41);
+
+        assert_eq!(val, 42);
+        assert_eq!(hits, [(addr, 8)]);
+    }
+
+    #[test]
+    fn test_disable() {
+        use Reg64::*;
+
+        let mut hits = Hits::new();
+        let mut asm = Asm::new();
+        asm.set_watch(Some(watch(&mut hits, &[(0, usize::MAX)])));
+        asm.set_watch(None);
+        asm.mov(rax, Mem64::indirect(rdi));
+        assert_eq!(asm.into_code(), [0x48, 0x8b, 0x07]);
+    }
+}
@@ -0,0 +1,189 @@
+use juicebox_asm::insn::Adc;
+use juicebox_asm::{
+    Asm, Imm16, Imm32, Imm8, Mem16, Mem32, Mem64, Mem8, Reg16::*, Reg32::*, Reg64::*, Reg8::*,
+};
+
+macro_rules! adc {
+    ($op1:expr, $op2:expr) => {{
+        let mut asm = Asm::new();
+        asm.adc($op1, $op2);
+        asm.into_code()
+    }};
+}
+
+#[rustfmt::skip]
+#[test]
+fn adc_rr() {
+    // 64bit.
+    assert_eq!(adc!(rcx, rdx),                                  [0x48, 0x11, 0xd1]);
+    assert_eq!(adc!(r11, r12),                                  [0x4d, 0x11, 0xe3]);
+
+    // 32bit.
+    assert_eq!(adc!(ecx, edx),                                  [0x11, 0xd1]);
+    assert_eq!(adc!(r11d, r12d),                                [0x45, 0x11, 0xe3]);
+
+    // 16bit.
+    assert_eq!(adc!(cx, dx),                                    [0x66, 0x11, 0xd1]);
+    assert_eq!(adc!(r11w, r12w),                                [0x66, 0x45, 0x11, 0xe3]);
+
+    // 8bit.
+    assert_eq!(adc!(cl, dl),                                    [0x10, 0xd1]);
+    assert_eq!(adc!(dil, sil),                                  [0x40, 0x10, 0xf7]);
+    assert_eq!(adc!(r11l, r12l),                                [0x45, 0x10, 0xe3]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn adc_ri() {
+    // 64bit.
+    assert_eq!(adc!(rcx, Imm32::from(0x11223344u32)),           [0x48, 0x81, 0xd1, 0x44, 0x33, 0x22, 0x11]);
+    assert_eq!(adc!(rcx, Imm8::from(0x11u8)),                   [0x48, 0x83, 0xd1, 0x11]);
+    assert_eq!(adc!(r11, Imm32::from(0x11223344u32)),           [0x49, 0x81, 0xd3, 0x44, 0x33, 0x22, 0x11]);
+    assert_eq!(adc!(r11, Imm8::from(0x11u8)),                   [0x49, 0x83, 0xd3, 0x11]);
+
+    // 32bit.
+    assert_eq!(adc!(ecx, Imm32::from(0x11223344u32)),           [0x81, 0xd1, 0x44, 0x33, 0x22, 0x11]);
+    assert_eq!(adc!(ecx, Imm8::from(0x11u8)),                   [0x83, 0xd1, 0x11]);
+    assert_eq!(adc!(r11d, Imm32::from(0x11223344u32)),          [0x41, 0x81, 0xd3, 0x44, 0x33, 0x22, 0x11]);
+    assert_eq!(adc!(r11d, Imm8::from(0x11u8)),                  [0x41, 0x83, 0xd3, 0x11]);
+
+    // 16bit.
+    assert_eq!(adc!(cx, Imm16::from(0x1122u16)),                [0x66, 0x81, 0xd1, 0x22, 0x11]);
+    assert_eq!(adc!(cx, Imm8::from(0x11u8)),                    [0x66, 0x83, 0xd1, 0x11]);
+    assert_eq!(adc!(r11w, Imm16::from(0x1122u16)),              [0x66, 0x41, 0x81, 0xd3, 0x22, 0x11]);
+    assert_eq!(adc!(r11w, Imm8::from(0x11u8)),                  [0x66, 0x41, 0x83, 0xd3, 0x11]);
+
+    // 8bit.
+    assert_eq!(adc!(cl, Imm8::from(0x11u8)),                    [0x80, 0xd1, 0x11]);
+    assert_eq!(adc!(dil, Imm8::from(0x11u8)),                   [0x40, 0x80, 0xd7, 0x11]);
+    assert_eq!(adc!(r11l, Imm8::from(0x11u8)),                  [0x41, 0x80, 0xd3, 0x11]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn adc_rm() {
+    // 64bit.
+    assert_eq!(adc!(rcx, Mem64::indirect(rax)),                 [0x48, 0x13, 0x08]);
+    assert_eq!(adc!(r11, Mem64::indirect_disp(r11, 0x10)),      [0x4d, 0x13, 0x9b, 0x10, 0x00, 0x00, 0x00]);
+
+    // 32bit.
+    assert_eq!(adc!(ecx, Mem32::indirect(rax)),                 [0x13, 0x08]);
+    assert_eq!(adc!(r11d, Mem32::indirect_disp(r11, 0x10)),     [0x45, 0x13, 0x9b, 0x10, 0x00, 0x00, 0x00]);
+
+    // 16bit.
+    assert_eq!(adc!(cx, Mem16::indirect(rax)),                  [0x66, 0x13, 0x08]);
+    assert_eq!(adc!(r11w, Mem16::indirect_disp(r11, 0x10)),     [0x66, 0x45, 0x13, 0x9b, 0x10, 0x00, 0x00, 0x00]);
+
+    // 8bit.
+    assert_eq!(adc!(cl, Mem8::indirect(rax)),                   [0x12, 0x08]);
+    assert_eq!(adc!(dil, Mem8::indirect_disp(r11, 0x10)),       [0x41, 0x12, 0xbb, 0x10, 0x00, 0x00, 0x00]);
+    assert_eq!(adc!(r11l, Mem8::indirect_base_index(rdi, r9)),  [0x46, 0x12, 0x1c, 0x0f]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn adc_mr() {
+    // 64bit.
+    assert_eq!(adc!(Mem64::indirect(rax), rdx),                 [0x48, 0x11, 0x10]);
+    assert_eq!(adc!(Mem64::indirect_disp(r11, 0x10), r12),      [0x4d, 0x11, 0xa3, 0x10, 0x00, 0x00, 0x00]);
+
+    // 32bit.
+    assert_eq!(adc!(Mem32::indirect(rax), edx),                 [0x11, 0x10]);
+    assert_eq!(adc!(Mem32::indirect_disp(r11, 0x10), r12d),     [0x45, 0x11, 0xa3, 0x10, 0x00, 0x00, 0x00]);
+
+    // 16bit.
+    assert_eq!(adc!(Mem16::indirect(rax), dx),                  [0x66, 0x11, 0x10]);
+    assert_eq!(adc!(Mem16::indirect_disp(r11, 0x10), r12w),     [0x66, 0x45, 0x11, 0xa3, 0x10, 0x00, 0x00, 0x00]);
+
+    // 8bit.
+    assert_eq!(adc!(Mem8::indirect(rax), dl),                   [0x10, 0x10]);
+    assert_eq!(adc!(Mem8::indirect_disp(r11, 0x10), sil),       [0x41, 0x10, 0xb3, 0x10, 0x00, 0x00, 0x00]);
+    assert_eq!(adc!(Mem8::indirect_base_index(rdi, r9), r12l),  [0x46, 0x10, 0x24, 0x0f]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn adc_mi() {
+    // 64bit.
+    assert_eq!(adc!(Mem64::indirect(rax), Imm32::from(0x11223344u32)), [0x48, 0x81, 0x10, 0x44, 0x33, 0x22, 0x11]);
+    assert_eq!(adc!(Mem64::indirect(rax), Imm8::from(0x11u8)),  [0x48, 0x83, 0x10, 0x11]);
+    assert_eq!(adc!(Mem64::indirect_disp(r11, 0x10), Imm32::from(0x11223344u32)), [0x49, 0x81, 0x93, 0x10, 0x00, 0x00, 0x00, 0x44, 0x33, 0x22, 0x11]);
+    assert_eq!(adc!(Mem64::indirect_disp(r11, 0x10), Imm8::from(0x11u8)), [0x49, 0x83, 0x93, 0x10, 0x00, 0x00, 0x00, 0x11]);
+    assert_eq!(adc!(Mem64::indirect_base_index(rdi, r9), Imm32::from(0x11223344u32)), [0x4a, 0x81, 0x14, 0x0f, 0x44, 0x33, 0x22, 0x11]);
+    assert_eq!(adc!(Mem64::indirect_base_index(rdi, r9), Imm8::from(0x11u8)), [0x4a, 0x83, 0x14, 0x0f, 0x11]);
+
+    // 32bit.
+    assert_eq!(adc!(Mem32::indirect(rax), Imm32::from(0x11223344u32)), [0x81, 0x10, 0x44, 0x33, 0x22, 0x11]);
+    assert_eq!(adc!(Mem32::indirect(rax), Imm8::from(0x11u8)),  [0x83, 0x10, 0x11]);
+    assert_eq!(adc!(Mem32::indirect_disp(r11, 0x10), Imm32::from(0x11223344u32)), [0x41, 0x81, 0x93, 0x10, 0x00, 0x00, 0x00, 0x44, 0x33, 0x22, 0x11]);
+    assert_eq!(adc!(Mem32::indirect_disp(r11, 0x10), Imm8::from(0x11u8)), [0x41, 0x83, 0x93, 0x10, 0x00, 0x00, 0x00, 0x11]);
+    assert_eq!(adc!(Mem32::indirect_base_index(rdi, r9), Imm32::from(0x11223344u32)), [0x42, 0x81, 0x14, 0x0f, 0x44, 0x33, 0x22, 0x11]);
+    assert_eq!(adc!(Mem32::indirect_base_index(rdi, r9), Imm8::from(0x11u8)), [0x42, 0x83, 0x14, 0x0f, 0x11]);
+
+    // 16bit.
+    assert_eq!(adc!(Mem16::indirect(rax), Imm16::from(0x1122u16)), [0x66, 0x81, 0x10, 0x22, 0x11]);
+    assert_eq!(adc!(Mem16::indirect(rax), Imm8::from(0x11u8)),  [0x66, 0x83, 0x10, 0x11]);
+    assert_eq!(adc!(Mem16::indirect_disp(r11, 0x10), Imm16::from(0x1122u16)), [0x66, 0x41, 0x81, 0x93, 0x10, 0x00, 0x00, 0x00, 0x22, 0x11]);
+    assert_eq!(adc!(Mem16::indirect_disp(r11, 0x10), Imm8::from(0x11u8)), [0x66, 0x41, 0x83, 0x93, 0x10, 0x00, 0x00, 0x00, 0x11]);
+    assert_eq!(adc!(Mem16::indirect_base_index(rdi, r9), Imm16::from(0x1122u16)), [0x66, 0x42, 0x81, 0x14, 0x0f, 0x22, 0x11]);
+    assert_eq!(adc!(Mem16::indirect_base_index(rdi, r9), Imm8::from(0x11u8)), [0x66, 0x42, 0x83, 0x14, 0x0f, 0x11]);
+
+    // 8bit.
+    assert_eq!(adc!(Mem8::indirect(rax), Imm8::from(0x11u8)),   [0x80, 0x10, 0x11]);
+    assert_eq!(adc!(Mem8::indirect_disp(r11, 0x10), Imm8::from(0x11u8)), [0x41, 0x80, 0x93, 0x10, 0x00, 0x00, 0x00, 0x11]);
+    assert_eq!(adc!(Mem8::indirect_base_index(rdi, r9), Imm8::from(0x11u8)), [0x42, 0x80, 0x14, 0x0f, 0x11]);
+}
+
+#[test]
+fn adc_exec() {
+    use juicebox_asm::insn::{Add, Mov};
+    use juicebox_asm::Runtime;
+
+    // fn(lo: u64, hi: u64) -> u64 { ((hi:lo) + (1:u64::MAX)).hi }
+    let mut asm = Asm::new();
+    asm.mov(rax, rsi);
+    asm.mov(rcx, juicebox_asm::Imm64::from(u64::MAX));
+    asm.add(rdi, rcx);
+    asm.adc(rax, Imm8::from(1u8));
+    asm.ret();
+
+    let mut rt = Runtime::new();
+    let f = unsafe { rt.add_code::<extern "C" fn(u64, u64) -> u64>(asm.into_code()) };
+    assert_eq!(f(0, 5), 6);
+    assert_eq!(f(1, 5), 7);
+}
+
+#[rustfmt::skip]
+#[test]
+fn adc_high8() {
+    // Without a REX byte the register codes 4-7 encode the high byte registers.
+    assert_eq!(adc!(ah, cl),                                    [0x10, 0xcc]);
+    assert_eq!(adc!(bl, dh),                                    [0x10, 0xf3]);
+    assert_eq!(adc!(ch, Imm8::from(0x11u8)),                    [0x80, 0xd5, 0x11]);
+    assert_eq!(adc!(ch, Mem8::indirect(rdi)),                   [0x12, 0x2f]);
+    assert_eq!(adc!(Mem8::indirect(rax), bh),                   [0x10, 0x38]);
+}
+
+#[test]
+#[should_panic = "High byte register can not be encoded with a REX prefix"]
+fn adc_high8_rex_rr() {
+    adc!(ah, r9l);
+}
+
+#[test]
+#[should_panic = "High byte register can not be encoded with a REX prefix"]
+fn adc_high8_rex_rr_low() {
+    adc!(bh, sil);
+}
+
+#[test]
+#[should_panic = "High byte register can not be encoded with a REX prefix"]
+fn adc_high8_rex_rm() {
+    adc!(ah, Mem8::indirect(r12));
+}
+
+#[test]
+#[should_panic = "High byte register can not be encoded with a REX prefix"]
+fn adc_high8_rex_mr() {
+    adc!(Mem8::indirect(r12), ah);
+}
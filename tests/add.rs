@@ -0,0 +1,183 @@
+use juicebox_asm::insn::{Add, Mov};
+use juicebox_asm::{
+    Asm, Imm16, Imm32, Imm8, Mem16, Mem32, Mem64, Mem8, Reg16::*, Reg32::*, Reg64::*, Reg8::*,
+    Runtime,
+};
+
+macro_rules! add {
+    ($op1:expr, $op2:expr) => {{
+        let mut asm = Asm::new();
+        asm.add($op1, $op2);
+        asm.into_code()
+    }};
+}
+
+#[rustfmt::skip]
+#[test]
+fn add_rr() {
+    // 64bit.
+    assert_eq!(add!(rcx, rdx),                                   [0x48, 0x01, 0xd1]);
+    assert_eq!(add!(r11, r12),                                   [0x4d, 0x01, 0xe3]);
+
+    // 32bit.
+    assert_eq!(add!(ecx, edx),                                   [0x01, 0xd1]);
+    assert_eq!(add!(r11d, r12d),                                 [0x45, 0x01, 0xe3]);
+
+    // 16bit.
+    assert_eq!(add!(cx, dx),                                     [0x66, 0x01, 0xd1]);
+    assert_eq!(add!(r11w, r12w),                                 [0x66, 0x45, 0x01, 0xe3]);
+
+    // 8bit.
+    assert_eq!(add!(cl, dl),                                     [0x00, 0xd1]);
+    assert_eq!(add!(dil, sil),                                   [0x40, 0x00, 0xf7]);
+    assert_eq!(add!(r11l, r12l),                                 [0x45, 0x00, 0xe3]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn add_ri() {
+    // 64bit.
+    assert_eq!(add!(rcx, Imm8::from(0x11u8)),                    [0x48, 0x83, 0xc1, 0x11]);
+    assert_eq!(add!(r11, Imm8::from(-1i8)),                      [0x49, 0x83, 0xc3, 0xff]);
+    assert_eq!(add!(rcx, Imm32::from(0x11223344u32)),            [0x48, 0x81, 0xc1, 0x44, 0x33, 0x22, 0x11]);
+
+    // 32bit.
+    assert_eq!(add!(ecx, Imm8::from(0x11u8)),                    [0x83, 0xc1, 0x11]);
+    assert_eq!(add!(r11d, Imm32::from(0x11223344u32)),           [0x41, 0x81, 0xc3, 0x44, 0x33, 0x22, 0x11]);
+
+    // 16bit.
+    assert_eq!(add!(cx, Imm8::from(0x11u8)),                     [0x66, 0x83, 0xc1, 0x11]);
+    assert_eq!(add!(r11w, Imm16::from(0x1122u16)),               [0x66, 0x41, 0x81, 0xc3, 0x22, 0x11]);
+
+    // 8bit.
+    assert_eq!(add!(cl, Imm8::from(0x11u8)),                     [0x80, 0xc1, 0x11]);
+    assert_eq!(add!(dil, Imm8::from(0x11u8)),                    [0x40, 0x80, 0xc7, 0x11]);
+    assert_eq!(add!(r11l, Imm8::from(0x11u8)),                   [0x41, 0x80, 0xc3, 0x11]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn add_rm() {
+    // 64bit.
+    assert_eq!(add!(rdx, Mem64::indirect(rax)),                  [0x48, 0x03, 0x10]);
+    assert_eq!(add!(r12, Mem64::indirect_disp(r11, 0x10)),       [0x4d, 0x03, 0xa3, 0x10, 0x00, 0x00, 0x00]);
+
+    // 32bit.
+    assert_eq!(add!(edx, Mem32::indirect(rax)),                  [0x03, 0x10]);
+    assert_eq!(add!(r12d, Mem32::indirect_base_index(rdi, r9)),  [0x46, 0x03, 0x24, 0x0f]);
+
+    // 16bit.
+    assert_eq!(add!(dx, Mem16::indirect(rax)),                   [0x66, 0x03, 0x10]);
+
+    // 8bit.
+    assert_eq!(add!(dl, Mem8::indirect(rax)),                    [0x02, 0x10]);
+    assert_eq!(add!(sil, Mem8::indirect_disp(r11, 0x10)),        [0x41, 0x02, 0xb3, 0x10, 0x00, 0x00, 0x00]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn add_mr() {
+    // 64bit.
+    assert_eq!(add!(Mem64::indirect(rax), rdx),                  [0x48, 0x01, 0x10]);
+    assert_eq!(add!(Mem64::indirect_disp(r11, 0x10), r12),       [0x4d, 0x01, 0xa3, 0x10, 0x00, 0x00, 0x00]);
+
+    // 32bit.
+    assert_eq!(add!(Mem32::indirect(rax), edx),                  [0x01, 0x10]);
+
+    // 16bit.
+    assert_eq!(add!(Mem16::indirect(rax), dx),                   [0x66, 0x01, 0x10]);
+
+    // 8bit.
+    assert_eq!(add!(Mem8::indirect(rax), dl),                    [0x00, 0x10]);
+    assert_eq!(add!(Mem8::indirect_base_index(rdi, r9), r12l),   [0x46, 0x00, 0x24, 0x0f]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn add_mi() {
+    // 64bit.
+    assert_eq!(add!(Mem64::indirect(rax), Imm8::from(0x11u8)),   [0x48, 0x83, 0x00, 0x11]);
+    assert_eq!(add!(Mem64::indirect(rax), Imm32::from(0x11223344u32)), [0x48, 0x81, 0x00, 0x44, 0x33, 0x22, 0x11]);
+
+    // 32bit.
+    assert_eq!(add!(Mem32::indirect_disp(r11, 0x10), Imm8::from(0x11u8)), [0x41, 0x83, 0x83, 0x10, 0x00, 0x00, 0x00, 0x11]);
+    assert_eq!(add!(Mem32::indirect_base_index(rdi, r9), Imm32::from(0x11223344u32)), [0x42, 0x81, 0x04, 0x0f, 0x44, 0x33, 0x22, 0x11]);
+
+    // 16bit.
+    assert_eq!(add!(Mem16::indirect(rax), Imm8::from(0x11u8)),   [0x66, 0x83, 0x00, 0x11]);
+    assert_eq!(add!(Mem16::indirect(rax), Imm16::from(0x1122u16)), [0x66, 0x81, 0x00, 0x22, 0x11]);
+
+    // 8bit.
+    assert_eq!(add!(Mem8::indirect(rax), Imm8::from(0x11u8)),    [0x80, 0x00, 0x11]);
+}
+
+#[test]
+fn add_exec() {
+    let mut rt = Runtime::new();
+
+    // add(a, b) = a + b.
+    let add = {
+        let mut asm = Asm::new();
+        asm.mov(rax, rdi);
+        asm.add(rax, rsi);
+        asm.ret();
+        unsafe { rt.add_code::<extern "C" fn(i64, i64) -> i64>(asm.into_code()) }
+    };
+    assert_eq!(add(40, 2), 42);
+
+    // Sign extended imm8: add_minus_one(a) = a + (-1).
+    let add_minus_one = {
+        let mut asm = Asm::new();
+        asm.mov(rax, rdi);
+        asm.add(rax, Imm8::from(-1i8));
+        asm.ret();
+        unsafe { rt.add_code::<extern "C" fn(i64) -> i64>(asm.into_code()) }
+    };
+    assert_eq!(add_minus_one(43), 42);
+    assert_eq!(add_minus_one(0), -1);
+
+    // Memory operand: add_mem(a, p) = a + *p.
+    let add_mem = {
+        let mut asm = Asm::new();
+        asm.mov(eax, edi);
+        asm.add(eax, Mem32::indirect(rsi));
+        asm.ret();
+        unsafe { rt.add_code::<extern "C" fn(i32, *const i32) -> i32>(asm.into_code()) }
+    };
+    assert_eq!(add_mem(40, &2), 42);
+}
+
+#[rustfmt::skip]
+#[test]
+fn add_high8() {
+    // Without a REX byte the register codes 4-7 encode the high byte registers.
+    assert_eq!(add!(ah, cl),                                     [0x00, 0xcc]);
+    assert_eq!(add!(bl, dh),                                     [0x00, 0xf3]);
+    assert_eq!(add!(ch, Imm8::from(0x11u8)),                     [0x80, 0xc5, 0x11]);
+    assert_eq!(add!(ch, Mem8::indirect(rdi)),                    [0x02, 0x2f]);
+    assert_eq!(add!(Mem8::indirect(rax), bh),                    [0x00, 0x38]);
+}
+
+#[test]
+#[should_panic = "High byte register can not be encoded with a REX prefix"]
+fn add_high8_rex_rr() {
+    add!(ah, r9l);
+}
+
+#[test]
+#[should_panic = "High byte register can not be encoded with a REX prefix"]
+fn add_high8_rex_rr_low() {
+    add!(bh, sil);
+}
+
+#[test]
+#[should_panic = "High byte register can not be encoded with a REX prefix"]
+fn add_high8_rex_rm() {
+    add!(ah, Mem8::indirect(r12));
+}
+
+#[test]
+#[should_panic = "High byte register can not be encoded with a REX prefix"]
+fn add_high8_rex_mr() {
+    add!(Mem8::indirect(r12), ah);
+}
@@ -0,0 +1,170 @@
+use juicebox_asm::insn::And;
+use juicebox_asm::{
+    Asm, Imm16, Imm32, Imm8, Mem16, Mem32, Mem64, Mem8, Reg16::*, Reg32::*, Reg64::*, Reg8::*,
+};
+
+macro_rules! and {
+    ($op1:expr, $op2:expr) => {{
+        let mut asm = Asm::new();
+        asm.and($op1, $op2);
+        asm.into_code()
+    }};
+}
+
+#[rustfmt::skip]
+#[test]
+fn and_rr() {
+    // 64bit.
+    assert_eq!(and!(rcx, rdx),                                  [0x48, 0x21, 0xd1]);
+    assert_eq!(and!(r11, r12),                                  [0x4d, 0x21, 0xe3]);
+
+    // 32bit.
+    assert_eq!(and!(ecx, edx),                                  [0x21, 0xd1]);
+    assert_eq!(and!(r11d, r12d),                                [0x45, 0x21, 0xe3]);
+
+    // 16bit.
+    assert_eq!(and!(cx, dx),                                    [0x66, 0x21, 0xd1]);
+    assert_eq!(and!(r11w, r12w),                                [0x66, 0x45, 0x21, 0xe3]);
+
+    // 8bit.
+    assert_eq!(and!(cl, dl),                                    [0x20, 0xd1]);
+    assert_eq!(and!(dil, sil),                                  [0x40, 0x20, 0xf7]);
+    assert_eq!(and!(r11l, r12l),                                [0x45, 0x20, 0xe3]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn and_ri() {
+    // 64bit.
+    assert_eq!(and!(rcx, Imm32::from(0x11223344u32)),           [0x48, 0x81, 0xe1, 0x44, 0x33, 0x22, 0x11]);
+    assert_eq!(and!(rcx, Imm8::from(0x11u8)),                   [0x48, 0x83, 0xe1, 0x11]);
+    assert_eq!(and!(r11, Imm32::from(0x11223344u32)),           [0x49, 0x81, 0xe3, 0x44, 0x33, 0x22, 0x11]);
+    assert_eq!(and!(r11, Imm8::from(0x11u8)),                   [0x49, 0x83, 0xe3, 0x11]);
+
+    // 32bit.
+    assert_eq!(and!(ecx, Imm32::from(0x11223344u32)),           [0x81, 0xe1, 0x44, 0x33, 0x22, 0x11]);
+    assert_eq!(and!(ecx, Imm8::from(0x11u8)),                   [0x83, 0xe1, 0x11]);
+    assert_eq!(and!(r11d, Imm32::from(0x11223344u32)),          [0x41, 0x81, 0xe3, 0x44, 0x33, 0x22, 0x11]);
+    assert_eq!(and!(r11d, Imm8::from(0x11u8)),                  [0x41, 0x83, 0xe3, 0x11]);
+
+    // 16bit.
+    assert_eq!(and!(cx, Imm16::from(0x1122u16)),                [0x66, 0x81, 0xe1, 0x22, 0x11]);
+    assert_eq!(and!(cx, Imm8::from(0x11u8)),                    [0x66, 0x83, 0xe1, 0x11]);
+    assert_eq!(and!(r11w, Imm16::from(0x1122u16)),              [0x66, 0x41, 0x81, 0xe3, 0x22, 0x11]);
+    assert_eq!(and!(r11w, Imm8::from(0x11u8)),                  [0x66, 0x41, 0x83, 0xe3, 0x11]);
+
+    // 8bit.
+    assert_eq!(and!(cl, Imm8::from(0x11u8)),                    [0x80, 0xe1, 0x11]);
+    assert_eq!(and!(dil, Imm8::from(0x11u8)),                   [0x40, 0x80, 0xe7, 0x11]);
+    assert_eq!(and!(r11l, Imm8::from(0x11u8)),                  [0x41, 0x80, 0xe3, 0x11]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn and_rm() {
+    // 64bit.
+    assert_eq!(and!(rcx, Mem64::indirect(rax)),                 [0x48, 0x23, 0x08]);
+    assert_eq!(and!(r11, Mem64::indirect_disp(r11, 0x10)),      [0x4d, 0x23, 0x9b, 0x10, 0x00, 0x00, 0x00]);
+
+    // 32bit.
+    assert_eq!(and!(ecx, Mem32::indirect(rax)),                 [0x23, 0x08]);
+    assert_eq!(and!(r11d, Mem32::indirect_disp(r11, 0x10)),     [0x45, 0x23, 0x9b, 0x10, 0x00, 0x00, 0x00]);
+
+    // 16bit.
+    assert_eq!(and!(cx, Mem16::indirect(rax)),                  [0x66, 0x23, 0x08]);
+    assert_eq!(and!(r11w, Mem16::indirect_disp(r11, 0x10)),     [0x66, 0x45, 0x23, 0x9b, 0x10, 0x00, 0x00, 0x00]);
+
+    // 8bit.
+    assert_eq!(and!(cl, Mem8::indirect(rax)),                   [0x22, 0x08]);
+    assert_eq!(and!(dil, Mem8::indirect_disp(r11, 0x10)),       [0x41, 0x22, 0xbb, 0x10, 0x00, 0x00, 0x00]);
+    assert_eq!(and!(r11l, Mem8::indirect_base_index(rdi, r9)),  [0x46, 0x22, 0x1c, 0x0f]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn and_mr() {
+    // 64bit.
+    assert_eq!(and!(Mem64::indirect(rax), rdx),                 [0x48, 0x21, 0x10]);
+    assert_eq!(and!(Mem64::indirect_disp(r11, 0x10), r12),      [0x4d, 0x21, 0xa3, 0x10, 0x00, 0x00, 0x00]);
+
+    // 32bit.
+    assert_eq!(and!(Mem32::indirect(rax), edx),                 [0x21, 0x10]);
+    assert_eq!(and!(Mem32::indirect_disp(r11, 0x10), r12d),     [0x45, 0x21, 0xa3, 0x10, 0x00, 0x00, 0x00]);
+
+    // 16bit.
+    assert_eq!(and!(Mem16::indirect(rax), dx),                  [0x66, 0x21, 0x10]);
+    assert_eq!(and!(Mem16::indirect_disp(r11, 0x10), r12w),     [0x66, 0x45, 0x21, 0xa3, 0x10, 0x00, 0x00, 0x00]);
+
+    // 8bit.
+    assert_eq!(and!(Mem8::indirect(rax), dl),                   [0x20, 0x10]);
+    assert_eq!(and!(Mem8::indirect_disp(r11, 0x10), sil),       [0x41, 0x20, 0xb3, 0x10, 0x00, 0x00, 0x00]);
+    assert_eq!(and!(Mem8::indirect_base_index(rdi, r9), r12l),  [0x46, 0x20, 0x24, 0x0f]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn and_mi() {
+    // 64bit.
+    assert_eq!(and!(Mem64::indirect(rax), Imm32::from(0x11223344u32)), [0x48, 0x81, 0x20, 0x44, 0x33, 0x22, 0x11]);
+    assert_eq!(and!(Mem64::indirect(rax), Imm8::from(0x11u8)),  [0x48, 0x83, 0x20, 0x11]);
+    assert_eq!(and!(Mem64::indirect_disp(r11, 0x10), Imm32::from(0x11223344u32)), [0x49, 0x81, 0xa3, 0x10, 0x00, 0x00, 0x00, 0x44, 0x33, 0x22, 0x11]);
+    assert_eq!(and!(Mem64::indirect_disp(r11, 0x10), Imm8::from(0x11u8)), [0x49, 0x83, 0xa3, 0x10, 0x00, 0x00, 0x00, 0x11]);
+    assert_eq!(and!(Mem64::indirect_base_index(rdi, r9), Imm32::from(0x11223344u32)), [0x4a, 0x81, 0x24, 0x0f, 0x44, 0x33, 0x22, 0x11]);
+    assert_eq!(and!(Mem64::indirect_base_index(rdi, r9), Imm8::from(0x11u8)), [0x4a, 0x83, 0x24, 0x0f, 0x11]);
+
+    // 32bit.
+    assert_eq!(and!(Mem32::indirect(rax), Imm32::from(0x11223344u32)), [0x81, 0x20, 0x44, 0x33, 0x22, 0x11]);
+    assert_eq!(and!(Mem32::indirect(rax), Imm8::from(0x11u8)),  [0x83, 0x20, 0x11]);
+    assert_eq!(and!(Mem32::indirect_disp(r11, 0x10), Imm32::from(0x11223344u32)), [0x41, 0x81, 0xa3, 0x10, 0x00, 0x00, 0x00, 0x44, 0x33, 0x22, 0x11]);
+    assert_eq!(and!(Mem32::indirect_disp(r11, 0x10), Imm8::from(0x11u8)), [0x41, 0x83, 0xa3, 0x10, 0x00, 0x00, 0x00, 0x11]);
+    assert_eq!(and!(Mem32::indirect_base_index(rdi, r9), Imm32::from(0x11223344u32)), [0x42, 0x81, 0x24, 0x0f, 0x44, 0x33, 0x22, 0x11]);
+    assert_eq!(and!(Mem32::indirect_base_index(rdi, r9), Imm8::from(0x11u8)), [0x42, 0x83, 0x24, 0x0f, 0x11]);
+
+    // 16bit.
+    assert_eq!(and!(Mem16::indirect(rax), Imm16::from(0x1122u16)), [0x66, 0x81, 0x20, 0x22, 0x11]);
+    assert_eq!(and!(Mem16::indirect(rax), Imm8::from(0x11u8)),  [0x66, 0x83, 0x20, 0x11]);
+    assert_eq!(and!(Mem16::indirect_disp(r11, 0x10), Imm16::from(0x1122u16)), [0x66, 0x41, 0x81, 0xa3, 0x10, 0x00, 0x00, 0x00, 0x22, 0x11]);
+    assert_eq!(and!(Mem16::indirect_disp(r11, 0x10), Imm8::from(0x11u8)), [0x66, 0x41, 0x83, 0xa3, 0x10, 0x00, 0x00, 0x00, 0x11]);
+    assert_eq!(and!(Mem16::indirect_base_index(rdi, r9), Imm16::from(0x1122u16)), [0x66, 0x42, 0x81, 0x24, 0x0f, 0x22, 0x11]);
+    assert_eq!(and!(Mem16::indirect_base_index(rdi, r9), Imm8::from(0x11u8)), [0x66, 0x42, 0x83, 0x24, 0x0f, 0x11]);
+
+    // 8bit.
+    assert_eq!(and!(Mem8::indirect(rax), Imm8::from(0x11u8)),   [0x80, 0x20, 0x11]);
+    assert_eq!(and!(Mem8::indirect_disp(r11, 0x10), Imm8::from(0x11u8)), [0x41, 0x80, 0xa3, 0x10, 0x00, 0x00, 0x00, 0x11]);
+    assert_eq!(and!(Mem8::indirect_base_index(rdi, r9), Imm8::from(0x11u8)), [0x42, 0x80, 0x24, 0x0f, 0x11]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn and_high8() {
+    // Without a REX byte the register codes 4-7 encode the high byte registers.
+    assert_eq!(and!(ah, cl),                                    [0x20, 0xcc]);
+    assert_eq!(and!(bl, dh),                                    [0x20, 0xf3]);
+    assert_eq!(and!(ch, Imm8::from(0x11u8)),                    [0x80, 0xe5, 0x11]);
+    assert_eq!(and!(ch, Mem8::indirect(rdi)),                   [0x22, 0x2f]);
+    assert_eq!(and!(Mem8::indirect(rax), bh),                   [0x20, 0x38]);
+}
+
+#[test]
+#[should_panic = "High byte register can not be encoded with a REX prefix"]
+fn and_high8_rex_rr() {
+    and!(ah, r9l);
+}
+
+#[test]
+#[should_panic = "High byte register can not be encoded with a REX prefix"]
+fn and_high8_rex_rr_low() {
+    and!(bh, sil);
+}
+
+#[test]
+#[should_panic = "High byte register can not be encoded with a REX prefix"]
+fn and_high8_rex_rm() {
+    and!(ah, Mem8::indirect(r12));
+}
+
+#[test]
+#[should_panic = "High byte register can not be encoded with a REX prefix"]
+fn and_high8_rex_mr() {
+    and!(Mem8::indirect(r12), ah);
+}
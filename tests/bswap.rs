@@ -0,0 +1,48 @@
+use juicebox_asm::insn::Bswap;
+use juicebox_asm::{Asm, Reg32::*, Reg64::*};
+
+macro_rules! bswap {
+    ($op1:expr) => {{
+        let mut asm = Asm::new();
+        asm.bswap($op1);
+        asm.into_code()
+    }};
+}
+
+#[rustfmt::skip]
+#[test]
+fn bswap() {
+    assert_eq!(bswap!(rax),         [0x48, 0x0f, 0xc8]);
+    assert_eq!(bswap!(rcx),         [0x48, 0x0f, 0xc9]);
+    assert_eq!(bswap!(r8),          [0x49, 0x0f, 0xc8]);
+    assert_eq!(bswap!(r15),         [0x49, 0x0f, 0xcf]);
+    assert_eq!(bswap!(eax),         [0x0f, 0xc8]);
+    assert_eq!(bswap!(edx),         [0x0f, 0xca]);
+    assert_eq!(bswap!(r9d),         [0x41, 0x0f, 0xc9]);
+    assert_eq!(bswap!(r12d),        [0x41, 0x0f, 0xcc]);
+}
+
+#[test]
+fn bswap_exec() {
+    use juicebox_asm::insn::Mov;
+    use juicebox_asm::Runtime;
+
+    let mut rt = Runtime::new();
+
+    // fn(v: u64) -> u64 { v.swap_bytes() }
+    let mut asm = Asm::new();
+    asm.mov(rax, rdi);
+    asm.bswap(rax);
+    asm.ret();
+    let swap64 = unsafe { rt.add_code::<extern "C" fn(u64) -> u64>(asm.into_code()) };
+
+    // fn(v: u32) -> u32 { v.swap_bytes() }
+    let mut asm = Asm::new();
+    asm.mov(eax, edi);
+    asm.bswap(eax);
+    asm.ret();
+    let swap32 = unsafe { rt.add_code::<extern "C" fn(u32) -> u32>(asm.into_code()) };
+
+    assert_eq!(swap64(0x0123_4567_89ab_cdef), 0xefcd_ab89_6745_2301);
+    assert_eq!(swap32(0x0123_4567), 0x6745_2301);
+}
@@ -0,0 +1,90 @@
+use juicebox_asm::insn::{Add, Call, Mov, Pop, Push};
+use juicebox_asm::{Asm, Label, Mem64, Reg64::*, Runtime};
+
+#[test]
+fn call_label() {
+    {
+        // Bind first.
+        let mut lbl = Label::new();
+        let mut asm = Asm::new();
+        asm.bind(&mut lbl);
+        asm.call(&mut lbl);
+        // 0xfffffffb -> -5
+        assert_eq!(asm.into_code(), [0xe8, 0xfb, 0xff, 0xff, 0xff]);
+    }
+    {
+        // Bind later, no short form.
+        let mut lbl = Label::new();
+        let mut asm = Asm::new();
+        asm.call(&mut lbl);
+        asm.nop();
+        asm.bind(&mut lbl);
+        assert_eq!(asm.into_code(), [0xe8, 0x01, 0x00, 0x00, 0x00, 0x90]);
+    }
+}
+
+#[test]
+fn call_label_exec() {
+    // fn(a: u64) -> u64, calls the local function `rax += a` twice.
+    let mut add = Label::new();
+    let mut asm = Asm::new();
+    asm.mov(rax, rdi);
+    asm.call(&mut add);
+    asm.call(&mut add);
+    asm.ret();
+
+    asm.bind(&mut add);
+    asm.add(rax, rdi);
+    asm.ret();
+
+    let mut rt = Runtime::new();
+    let f = unsafe { rt.add_code::<extern "C" fn(u64) -> u64>(asm.into_code()) };
+    assert_eq!(f(1), 3);
+    assert_eq!(f(7), 21);
+}
+
+#[rustfmt::skip]
+#[test]
+fn call_mem() {
+    let call = |op1: Mem64| {
+        let mut asm = Asm::new();
+        asm.call(op1);
+        asm.into_code()
+    };
+
+    assert_eq!(call(Mem64::indirect(rax)),                   [0x48, 0xff, 0x10]);
+    assert_eq!(call(Mem64::indirect_disp(rdi, 0x10)),        [0x48, 0xff, 0x97, 0x10, 0x00, 0x00, 0x00]);
+    assert_eq!(call(Mem64::indirect_base_index(rdi, rsi)),   [0x48, 0xff, 0x14, 0x37]);
+    assert_eq!(call(Mem64::indirect(r11)),                   [0x49, 0xff, 0x13]);
+}
+
+extern "C" fn double(v: u64) -> u64 {
+    v * 2
+}
+
+extern "C" fn square(v: u64) -> u64 {
+    v * v
+}
+
+#[test]
+fn call_mem_exec() {
+    // fn(vtable: &[extern "C" fn(u64) -> u64; 2], idx: u64, v: u64) -> u64, calls vtable[idx](v).
+    let mut asm = Asm::new();
+    // Keep the stack 16 byte aligned at the call.
+    asm.push(rbx);
+    asm.mov(rax, rdi);
+    asm.mov(rdi, rdx);
+    asm.call(Mem64::indirect_base_index_scale_disp(rax, rsi, 8, 0));
+    asm.pop(rbx);
+    asm.ret();
+
+    let mut rt = Runtime::new();
+    let f = unsafe {
+        rt.add_code::<extern "C" fn(&[extern "C" fn(u64) -> u64; 2], u64, u64) -> u64>(
+            asm.into_code(),
+        )
+    };
+    let vtable = [double as extern "C" fn(u64) -> u64, square];
+    assert_eq!(f(&vtable, 0, 7), 14);
+    assert_eq!(f(&vtable, 1, 7), 49);
+}
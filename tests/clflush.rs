@@ -0,0 +1,106 @@
+use juicebox_asm::insn::{Clflush, Clflushopt, Clwb, Mov};
+use juicebox_asm::{Asm, CpuFeatures, Mem64, Mem8, Reg64::*};
+
+fn flush_features() -> CpuFeatures {
+    let mut features = CpuFeatures::baseline();
+    features.clflushopt = true;
+    features.clwb = true;
+    features
+}
+
+macro_rules! clflush {
+    ($op1:expr) => {{
+        let mut asm = Asm::new();
+        asm.clflush($op1);
+        asm.into_code()
+    }};
+}
+
+macro_rules! clflushopt {
+    ($op1:expr) => {{
+        let mut asm = Asm::new();
+        asm.set_cpu_features(flush_features());
+        asm.clflushopt($op1);
+        asm.into_code()
+    }};
+}
+
+macro_rules! clwb {
+    ($op1:expr) => {{
+        let mut asm = Asm::new();
+        asm.set_cpu_features(flush_features());
+        asm.clwb($op1);
+        asm.into_code()
+    }};
+}
+
+#[rustfmt::skip]
+#[test]
+fn clflush() {
+    assert_eq!(clflush!(Mem8::indirect(rdi)),                       [0x0f, 0xae, 0x3f]);
+    assert_eq!(clflush!(Mem8::indirect_disp(rsp, 0x1000)),          [0x0f, 0xae, 0xbc, 0x24, 0x00, 0x10, 0x00, 0x00]);
+    assert_eq!(clflush!(Mem8::indirect_base_index(r8, r13)),        [0x43, 0x0f, 0xae, 0x3c, 0x28]);
+    assert_eq!(clflush!(Mem8::indirect(r13)),                       [0x41, 0x0f, 0xae, 0x7d, 0x00]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn clflushopt() {
+    assert_eq!(clflushopt!(Mem8::indirect(rdi)),                    [0x66, 0x0f, 0xae, 0x3f]);
+    assert_eq!(clflushopt!(Mem8::indirect_disp(rsp, 0x1000)),       [0x66, 0x0f, 0xae, 0xbc, 0x24, 0x00, 0x10, 0x00, 0x00]);
+    assert_eq!(clflushopt!(Mem8::indirect_base_index(r8, r13)),     [0x66, 0x43, 0x0f, 0xae, 0x3c, 0x28]);
+    assert_eq!(clflushopt!(Mem8::indirect(r13)),                    [0x66, 0x41, 0x0f, 0xae, 0x7d, 0x00]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn clwb() {
+    assert_eq!(clwb!(Mem8::indirect(rdi)),                          [0x66, 0x0f, 0xae, 0x37]);
+    assert_eq!(clwb!(Mem8::indirect_disp(rsp, 0x1000)),             [0x66, 0x0f, 0xae, 0xb4, 0x24, 0x00, 0x10, 0x00, 0x00]);
+    assert_eq!(clwb!(Mem8::indirect_base_index(r8, r13)),           [0x66, 0x43, 0x0f, 0xae, 0x34, 0x28]);
+    assert_eq!(clwb!(Mem8::indirect(r13)),                          [0x66, 0x41, 0x0f, 0xae, 0x75, 0x00]);
+}
+
+#[test]
+#[should_panic(expected = "Instruction clflushopt not enabled in the CPU features")]
+fn clflushopt_disabled() {
+    let mut asm = Asm::new();
+    asm.set_cpu_features(CpuFeatures::baseline());
+    asm.clflushopt(Mem8::indirect(rdi));
+}
+
+#[test]
+#[should_panic(expected = "Instruction clwb not enabled in the CPU features")]
+fn clwb_disabled() {
+    let mut asm = Asm::new();
+    asm.set_cpu_features(CpuFeatures::baseline());
+    asm.clwb(Mem8::indirect(rdi));
+}
+
+#[test]
+fn clflush_exec() {
+    use juicebox_asm::Runtime;
+
+    let host = CpuFeatures::host();
+
+    // fn(p: &mut u64) -> u64, stores to p, flushes the cache line and loads p again.
+    let mut asm = Asm::new();
+    asm.set_cpu_features(host);
+    asm.mov(Mem64::indirect(rdi), rdi);
+    asm.clflush(Mem8::indirect(rdi));
+    if host.clflushopt {
+        asm.clflushopt(Mem8::indirect(rdi));
+    }
+    if host.clwb {
+        asm.clwb(Mem8::indirect(rdi));
+    }
+    asm.mov(rax, Mem64::indirect(rdi));
+    asm.ret();
+
+    let mut rt = Runtime::new();
+    let f = unsafe { rt.add_code::<extern "C" fn(&mut u64) -> u64>(asm.into_code()) };
+    let mut v = 0;
+    let p = &mut v as *mut u64 as u64;
+    assert_eq!(f(&mut v), p);
+    assert_eq!(v, p);
+}
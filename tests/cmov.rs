@@ -0,0 +1,66 @@
+use juicebox_asm::insn::Cmov;
+use juicebox_asm::{Asm, Cond, Mem16, Mem32, Mem64, Reg16::*, Reg32::*, Reg64::*};
+
+macro_rules! cmov {
+    ($cond:expr, $op1:expr, $op2:expr) => {{
+        let mut asm = Asm::new();
+        asm.cmov($cond, $op1, $op2);
+        asm.into_code()
+    }};
+}
+
+#[rustfmt::skip]
+#[test]
+fn cmov_cond() {
+    assert_eq!(cmov!(Cond::O, rax, rcx),                                        [0x48, 0x0f, 0x40, 0xc1]);
+    assert_eq!(cmov!(Cond::No, rax, rcx),                                       [0x48, 0x0f, 0x41, 0xc1]);
+    assert_eq!(cmov!(Cond::B, rax, rcx),                                        [0x48, 0x0f, 0x42, 0xc1]);
+    assert_eq!(cmov!(Cond::Ae, rax, rcx),                                       [0x48, 0x0f, 0x43, 0xc1]);
+    assert_eq!(cmov!(Cond::E, rax, rcx),                                        [0x48, 0x0f, 0x44, 0xc1]);
+    assert_eq!(cmov!(Cond::Ne, rax, rcx),                                       [0x48, 0x0f, 0x45, 0xc1]);
+    assert_eq!(cmov!(Cond::Be, rax, rcx),                                       [0x48, 0x0f, 0x46, 0xc1]);
+    assert_eq!(cmov!(Cond::A, rax, rcx),                                        [0x48, 0x0f, 0x47, 0xc1]);
+    assert_eq!(cmov!(Cond::S, rax, rcx),                                        [0x48, 0x0f, 0x48, 0xc1]);
+    assert_eq!(cmov!(Cond::Ns, rax, rcx),                                       [0x48, 0x0f, 0x49, 0xc1]);
+    assert_eq!(cmov!(Cond::P, rax, rcx),                                        [0x48, 0x0f, 0x4a, 0xc1]);
+    assert_eq!(cmov!(Cond::Np, rax, rcx),                                       [0x48, 0x0f, 0x4b, 0xc1]);
+    assert_eq!(cmov!(Cond::L, rax, rcx),                                        [0x48, 0x0f, 0x4c, 0xc1]);
+    assert_eq!(cmov!(Cond::Ge, rax, rcx),                                       [0x48, 0x0f, 0x4d, 0xc1]);
+    assert_eq!(cmov!(Cond::Le, rax, rcx),                                       [0x48, 0x0f, 0x4e, 0xc1]);
+    assert_eq!(cmov!(Cond::G, rax, rcx),                                        [0x48, 0x0f, 0x4f, 0xc1]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn cmov_forms() {
+    assert_eq!(cmov!(Cond::E, r8, rdi),                                         [0x4c, 0x0f, 0x44, 0xc7]);
+    assert_eq!(cmov!(Cond::Ne, rbx, r15),                                       [0x49, 0x0f, 0x45, 0xdf]);
+    assert_eq!(cmov!(Cond::L, eax, ecx),                                        [0x0f, 0x4c, 0xc1]);
+    assert_eq!(cmov!(Cond::G, r9d, edx),                                        [0x44, 0x0f, 0x4f, 0xca]);
+    assert_eq!(cmov!(Cond::B, ax, cx),                                          [0x66, 0x0f, 0x42, 0xc1]);
+    assert_eq!(cmov!(Cond::A, r10w, r11w),                                      [0x66, 0x45, 0x0f, 0x47, 0xd3]);
+    assert_eq!(cmov!(Cond::E, rax, Mem64::indirect(rdi)),                       [0x48, 0x0f, 0x44, 0x07]);
+    assert_eq!(cmov!(Cond::Ge, r12, Mem64::indirect_disp(rbp, -8)),             [0x4c, 0x0f, 0x4d, 0xa5, 0xf8, 0xff, 0xff, 0xff]);
+    assert_eq!(cmov!(Cond::S, ecx, Mem32::indirect_base_index(r11, rsi)),       [0x41, 0x0f, 0x48, 0x0c, 0x33]);
+    assert_eq!(cmov!(Cond::Be, r8d, Mem32::indirect(rax)),                      [0x44, 0x0f, 0x46, 0x00]);
+    assert_eq!(cmov!(Cond::Ne, dx, Mem16::indirect_disp(rbx, 0x10)),            [0x66, 0x0f, 0x45, 0x93, 0x10, 0x00, 0x00, 0x00]);
+    assert_eq!(cmov!(Cond::O, r14w, Mem16::indirect(r15)),                      [0x66, 0x45, 0x0f, 0x40, 0x37]);
+}
+
+#[test]
+fn cmov_exec() {
+    use juicebox_asm::insn::{Mov, Test};
+    use juicebox_asm::Runtime;
+
+    // fn(a: u64, b: u64) -> u64 { if a == 0 { b } else { a } }
+    let mut asm = Asm::new();
+    asm.mov(rax, rdi);
+    asm.test(rdi, rdi);
+    asm.cmov(Cond::E, rax, rsi);
+    asm.ret();
+
+    let mut rt = Runtime::new();
+    let f = unsafe { rt.add_code::<extern "C" fn(u64, u64) -> u64>(asm.into_code()) };
+    assert_eq!(f(0, 2), 2);
+    assert_eq!(f(1, 2), 1);
+}
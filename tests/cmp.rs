@@ -0,0 +1,190 @@
+use juicebox_asm::insn::{Cmp, Mov, Setcc};
+use juicebox_asm::{
+    Asm, Cond, Imm16, Imm32, Imm64, Imm8, Mem16, Mem32, Mem64, Mem8, Reg16::*, Reg32::*, Reg64::*,
+    Reg8::*, Runtime,
+};
+
+macro_rules! cmp {
+    ($op1:expr, $op2:expr) => {{
+        let mut asm = Asm::new();
+        asm.cmp($op1, $op2);
+        asm.into_code()
+    }};
+}
+
+#[rustfmt::skip]
+#[test]
+fn cmp_rr() {
+    // 64bit.
+    assert_eq!(cmp!(rcx, rdx),                                   [0x48, 0x39, 0xd1]);
+    assert_eq!(cmp!(r11, r12),                                   [0x4d, 0x39, 0xe3]);
+
+    // 32bit.
+    assert_eq!(cmp!(ecx, edx),                                   [0x39, 0xd1]);
+    assert_eq!(cmp!(r11d, r12d),                                 [0x45, 0x39, 0xe3]);
+
+    // 16bit.
+    assert_eq!(cmp!(cx, dx),                                     [0x66, 0x39, 0xd1]);
+    assert_eq!(cmp!(r11w, r12w),                                 [0x66, 0x45, 0x39, 0xe3]);
+
+    // 8bit.
+    assert_eq!(cmp!(cl, dl),                                     [0x38, 0xd1]);
+    assert_eq!(cmp!(dil, sil),                                   [0x40, 0x38, 0xf7]);
+    assert_eq!(cmp!(r11l, r12l),                                 [0x45, 0x38, 0xe3]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn cmp_ri() {
+    // 64bit.
+    assert_eq!(cmp!(rcx, Imm8::from(0x11u8)),                    [0x48, 0x83, 0xf9, 0x11]);
+    assert_eq!(cmp!(r11, Imm8::from(-1i8)),                      [0x49, 0x83, 0xfb, 0xff]);
+    assert_eq!(cmp!(rcx, Imm32::from(0x11223344u32)),            [0x48, 0x81, 0xf9, 0x44, 0x33, 0x22, 0x11]);
+
+    // 32bit.
+    assert_eq!(cmp!(ecx, Imm8::from(0x11u8)),                    [0x83, 0xf9, 0x11]);
+    assert_eq!(cmp!(r11d, Imm32::from(0x11223344u32)),           [0x41, 0x81, 0xfb, 0x44, 0x33, 0x22, 0x11]);
+
+    // 16bit.
+    assert_eq!(cmp!(cx, Imm8::from(0x11u8)),                     [0x66, 0x83, 0xf9, 0x11]);
+    assert_eq!(cmp!(r11w, Imm16::from(0x1122u16)),               [0x66, 0x41, 0x81, 0xfb, 0x22, 0x11]);
+
+    // 8bit.
+    assert_eq!(cmp!(cl, Imm8::from(0x11u8)),                     [0x80, 0xf9, 0x11]);
+    assert_eq!(cmp!(dil, Imm8::from(0x11u8)),                    [0x40, 0x80, 0xff, 0x11]);
+    assert_eq!(cmp!(r11l, Imm8::from(0x11u8)),                   [0x41, 0x80, 0xfb, 0x11]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn cmp_rm() {
+    // 64bit.
+    assert_eq!(cmp!(rdx, Mem64::indirect(rax)),                  [0x48, 0x3b, 0x10]);
+    assert_eq!(cmp!(r12, Mem64::indirect_disp(r11, 0x10)),       [0x4d, 0x3b, 0xa3, 0x10, 0x00, 0x00, 0x00]);
+
+    // 32bit.
+    assert_eq!(cmp!(edx, Mem32::indirect(rax)),                  [0x3b, 0x10]);
+    assert_eq!(cmp!(r12d, Mem32::indirect_base_index(rdi, r9)),  [0x46, 0x3b, 0x24, 0x0f]);
+
+    // 16bit.
+    assert_eq!(cmp!(dx, Mem16::indirect(rax)),                   [0x66, 0x3b, 0x10]);
+
+    // 8bit.
+    assert_eq!(cmp!(dl, Mem8::indirect(rax)),                    [0x3a, 0x10]);
+    assert_eq!(cmp!(sil, Mem8::indirect_disp(r11, 0x10)),        [0x41, 0x3a, 0xb3, 0x10, 0x00, 0x00, 0x00]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn cmp_mr() {
+    // 64bit.
+    assert_eq!(cmp!(Mem64::indirect(rax), rdx),                  [0x48, 0x39, 0x10]);
+    assert_eq!(cmp!(Mem64::indirect_disp(r11, 0x10), r12),       [0x4d, 0x39, 0xa3, 0x10, 0x00, 0x00, 0x00]);
+
+    // 32bit.
+    assert_eq!(cmp!(Mem32::indirect(rax), edx),                  [0x39, 0x10]);
+
+    // 16bit.
+    assert_eq!(cmp!(Mem16::indirect(rax), dx),                   [0x66, 0x39, 0x10]);
+
+    // 8bit.
+    assert_eq!(cmp!(Mem8::indirect(rax), dl),                    [0x38, 0x10]);
+    assert_eq!(cmp!(Mem8::indirect_base_index(rdi, r9), r12l),   [0x46, 0x38, 0x24, 0x0f]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn cmp_mi() {
+    // 64bit.
+    assert_eq!(cmp!(Mem64::indirect(rax), Imm8::from(0x11u8)),   [0x48, 0x83, 0x38, 0x11]);
+    assert_eq!(cmp!(Mem64::indirect(rax), Imm32::from(0x11223344u32)), [0x48, 0x81, 0x38, 0x44, 0x33, 0x22, 0x11]);
+
+    // 32bit.
+    assert_eq!(cmp!(Mem32::indirect_disp(r11, 0x10), Imm8::from(0x11u8)), [0x41, 0x83, 0xbb, 0x10, 0x00, 0x00, 0x00, 0x11]);
+    assert_eq!(cmp!(Mem32::indirect_base_index(rdi, r9), Imm32::from(0x11223344u32)), [0x42, 0x81, 0x3c, 0x0f, 0x44, 0x33, 0x22, 0x11]);
+
+    // 16bit.
+    assert_eq!(cmp!(Mem16::indirect(rax), Imm8::from(0x11u8)),   [0x66, 0x83, 0x38, 0x11]);
+    assert_eq!(cmp!(Mem16::indirect(rax), Imm16::from(0x1122u16)), [0x66, 0x81, 0x38, 0x22, 0x11]);
+
+    // 8bit.
+    assert_eq!(cmp!(Mem8::indirect(rax), Imm8::from(0x11u8)),    [0x80, 0x38, 0x11]);
+}
+
+#[test]
+fn cmp_exec() {
+    let mut rt = Runtime::new();
+
+    // Operand order: less(a, b) = a < b (signed).
+    let less = {
+        let mut asm = Asm::new();
+        asm.mov(rax, Imm64::from(0u64));
+        asm.cmp(rdi, rsi);
+        asm.setcc(Cond::L, al);
+        asm.ret();
+        unsafe { rt.add_code::<extern "C" fn(i64, i64) -> u64>(asm.into_code()) }
+    };
+    assert_eq!(less(1, 2), 1);
+    assert_eq!(less(2, 1), 0);
+    assert_eq!(less(-1, 0), 1);
+
+    // Sign extended imm8: below_minus_one(a) = a < -1 (signed).
+    let below_minus_one = {
+        let mut asm = Asm::new();
+        asm.mov(rax, Imm64::from(0u64));
+        asm.cmp(rdi, Imm8::from(-1i8));
+        asm.setcc(Cond::L, al);
+        asm.ret();
+        unsafe { rt.add_code::<extern "C" fn(i64) -> u64>(asm.into_code()) }
+    };
+    assert_eq!(below_minus_one(-2), 1);
+    assert_eq!(below_minus_one(-1), 0);
+    assert_eq!(below_minus_one(0), 0);
+
+    // Memory operand: mem_less(p, b) = *p < b (signed).
+    let mem_less = {
+        let mut asm = Asm::new();
+        asm.mov(rax, Imm64::from(0u64));
+        asm.cmp(Mem32::indirect(rdi), esi);
+        asm.setcc(Cond::L, al);
+        asm.ret();
+        unsafe { rt.add_code::<extern "C" fn(*const i32, i32) -> u64>(asm.into_code()) }
+    };
+    assert_eq!(mem_less(&-5, 3), 1);
+    assert_eq!(mem_less(&5, 3), 0);
+}
+
+#[rustfmt::skip]
+#[test]
+fn cmp_high8() {
+    // Without a REX byte the register codes 4-7 encode the high byte registers.
+    assert_eq!(cmp!(ah, cl),                                     [0x38, 0xcc]);
+    assert_eq!(cmp!(bl, dh),                                     [0x38, 0xf3]);
+    assert_eq!(cmp!(ch, Imm8::from(0x11u8)),                     [0x80, 0xfd, 0x11]);
+    assert_eq!(cmp!(ch, Mem8::indirect(rdi)),                    [0x3a, 0x2f]);
+    assert_eq!(cmp!(Mem8::indirect(rax), bh),                    [0x38, 0x38]);
+}
+
+#[test]
+#[should_panic = "High byte register can not be encoded with a REX prefix"]
+fn cmp_high8_rex_rr() {
+    cmp!(ah, r9l);
+}
+
+#[test]
+#[should_panic = "High byte register can not be encoded with a REX prefix"]
+fn cmp_high8_rex_rr_low() {
+    cmp!(bh, sil);
+}
+
+#[test]
+#[should_panic = "High byte register can not be encoded with a REX prefix"]
+fn cmp_high8_rex_rm() {
+    cmp!(ah, Mem8::indirect(r12));
+}
+
+#[test]
+#[should_panic = "High byte register can not be encoded with a REX prefix"]
+fn cmp_high8_rex_mr() {
+    cmp!(Mem8::indirect(r12), ah);
+}
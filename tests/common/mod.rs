@@ -0,0 +1,94 @@
+//! Snapshot test utilities.
+//!
+//! [`assert_snapshot`] renders the code of an [`Asm`] into a normalized listing with
+//! [`Asm::export_lst`], eg
+//!
+//! ```text
+//! 0000: 48 89 c8                       mov rax,rcx
+//! 0003: c3                             ret
+//! ```
+//!
+//! The rendered listing is compared against the snapshot checked in at
+//! `tests/snapshots/<name>.lst`. On mismatch a line based diff is reported. Running the tests with
+//! `UPDATE_SNAPSHOTS=1` (re-)writes the snapshot files instead.
+
+use juicebox_asm::Asm;
+use std::path::PathBuf;
+
+/// Compare the listing of the code in `asm` against the snapshot `name`. The code is finalized
+/// first, such that all relocations are resolved. If `objdump` is not available on the system
+/// this prints a warning and becomes a nop.
+///
+/// # Panics
+///
+/// Panics if the rendered listing differs from the snapshot, the snapshot does not exist or `asm`
+/// can not be finalized, see [`Asm::into_code`].
+pub fn assert_snapshot(name: &str, asm: Asm) {
+    let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "tests", "snapshots", name]
+        .iter()
+        .collect::<PathBuf>()
+        .with_extension("lst");
+
+    let Some(actual) = asm.export_lst() else {
+        println!("snapshot: skipping {}, objdump not found", name);
+        return;
+    };
+    // Check that all labels are bound.
+    asm.into_code();
+
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        std::fs::write(&path, &actual)
+            .unwrap_or_else(|_| panic!("Failed to write snapshot {}", path.display()));
+        return;
+    }
+
+    let expected = std::fs::read_to_string(&path).unwrap_or_else(|_| {
+        panic!(
+            "Missing snapshot {}, run with UPDATE_SNAPSHOTS=1 to create it",
+            path.display()
+        )
+    });
+
+    if expected != actual {
+        panic!(
+            "Snapshot {} differs (- expected, + actual), run with UPDATE_SNAPSHOTS=1 to update it\n{}",
+            path.display(),
+            diff(&expected, &actual)
+        );
+    }
+}
+
+/// Compute a line based diff between `old` and `new` using the longest common subsequence.
+fn diff(old: &str, new: &str) -> String {
+    let old: Vec<_> = old.lines().collect();
+    let new: Vec<_> = new.lines().collect();
+
+    // lcs[i][j] holds the length of the lcs of old[i..] and new[j..].
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut out = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            out += &format!("  {}\n", old[i]);
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            out += &format!("- {}\n", old[i]);
+            i += 1;
+        } else {
+            out += &format!("+ {}\n", new[j]);
+            j += 1;
+        }
+    }
+    out
+}
@@ -0,0 +1,115 @@
+use juicebox_asm::insn::{Lzcnt, Tzcnt};
+use juicebox_asm::{Asm, CpuFeatures, Mem16, Mem32, Mem64, Reg16::*, Reg32::*, Reg64::*, Runtime};
+
+fn count_zeros_features() -> CpuFeatures {
+    let mut features = CpuFeatures::baseline();
+    features.lzcnt = true;
+    features.bmi1 = true;
+    features
+}
+
+macro_rules! lzcnt {
+    ($op1:expr, $op2:expr) => {{
+        let mut asm = Asm::new();
+        asm.set_cpu_features(count_zeros_features());
+        asm.lzcnt($op1, $op2);
+        asm.into_code()
+    }};
+}
+
+macro_rules! tzcnt {
+    ($op1:expr, $op2:expr) => {{
+        let mut asm = Asm::new();
+        asm.set_cpu_features(count_zeros_features());
+        asm.tzcnt($op1, $op2);
+        asm.into_code()
+    }};
+}
+
+#[rustfmt::skip]
+#[test]
+fn lzcnt() {
+    assert_eq!(lzcnt!(rax, rcx),                                            [0xf3, 0x48, 0x0f, 0xbd, 0xc1]);
+    assert_eq!(lzcnt!(r9, rdx),                                             [0xf3, 0x4c, 0x0f, 0xbd, 0xca]);
+    assert_eq!(lzcnt!(rbx, r15),                                            [0xf3, 0x49, 0x0f, 0xbd, 0xdf]);
+    assert_eq!(lzcnt!(eax, ecx),                                            [0xf3, 0x0f, 0xbd, 0xc1]);
+    assert_eq!(lzcnt!(r11d, esi),                                           [0xf3, 0x44, 0x0f, 0xbd, 0xde]);
+    assert_eq!(lzcnt!(rax, Mem64::indirect(rdi)),                           [0xf3, 0x48, 0x0f, 0xbd, 0x07]);
+    assert_eq!(lzcnt!(r10, Mem64::indirect_disp(rbx, 0x10)),                [0xf3, 0x4c, 0x0f, 0xbd, 0x93, 0x10, 0x00, 0x00, 0x00]);
+    assert_eq!(lzcnt!(ecx, Mem32::indirect_base_index(rdi, r9)),            [0xf3, 0x42, 0x0f, 0xbd, 0x0c, 0x0f]);
+    // The mandatory prefix is emitted before the operand size prefix.
+    assert_eq!(lzcnt!(ax, cx),                                              [0xf3, 0x66, 0x0f, 0xbd, 0xc1]);
+    assert_eq!(lzcnt!(r8w, r12w),                                           [0xf3, 0x66, 0x45, 0x0f, 0xbd, 0xc4]);
+    assert_eq!(lzcnt!(dx, Mem16::indirect(rsi)),                            [0xf3, 0x66, 0x0f, 0xbd, 0x16]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn tzcnt() {
+    assert_eq!(tzcnt!(rax, rcx),                                            [0xf3, 0x48, 0x0f, 0xbc, 0xc1]);
+    assert_eq!(tzcnt!(r9, rdx),                                             [0xf3, 0x4c, 0x0f, 0xbc, 0xca]);
+    assert_eq!(tzcnt!(rbx, r15),                                            [0xf3, 0x49, 0x0f, 0xbc, 0xdf]);
+    assert_eq!(tzcnt!(eax, ecx),                                            [0xf3, 0x0f, 0xbc, 0xc1]);
+    assert_eq!(tzcnt!(r11d, esi),                                           [0xf3, 0x44, 0x0f, 0xbc, 0xde]);
+    assert_eq!(tzcnt!(rax, Mem64::indirect(rdi)),                           [0xf3, 0x48, 0x0f, 0xbc, 0x07]);
+    assert_eq!(tzcnt!(r10, Mem64::indirect_disp(rbx, 0x10)),                [0xf3, 0x4c, 0x0f, 0xbc, 0x93, 0x10, 0x00, 0x00, 0x00]);
+    assert_eq!(tzcnt!(ecx, Mem32::indirect_base_index(rdi, r9)),            [0xf3, 0x42, 0x0f, 0xbc, 0x0c, 0x0f]);
+    // The mandatory prefix is emitted before the operand size prefix.
+    assert_eq!(tzcnt!(ax, cx),                                              [0xf3, 0x66, 0x0f, 0xbc, 0xc1]);
+    assert_eq!(tzcnt!(r8w, r12w),                                           [0xf3, 0x66, 0x45, 0x0f, 0xbc, 0xc4]);
+    assert_eq!(tzcnt!(dx, Mem16::indirect(rsi)),                            [0xf3, 0x66, 0x0f, 0xbc, 0x16]);
+}
+
+#[test]
+#[should_panic(expected = "Instruction lzcnt not enabled in the CPU features")]
+fn lzcnt_disabled() {
+    let mut asm = Asm::new();
+    asm.set_cpu_features(CpuFeatures::baseline());
+    asm.lzcnt(rax, rcx);
+}
+
+#[test]
+#[should_panic(expected = "Instruction tzcnt not enabled in the CPU features")]
+fn tzcnt_disabled() {
+    let mut asm = Asm::new();
+    asm.set_cpu_features(CpuFeatures::baseline());
+    asm.tzcnt(rax, rcx);
+}
+
+const VALUES: [u64; 5] = [0, 1, 0x100, 0x8000_0000_0000_0000, u64::MAX];
+
+#[test]
+fn lzcnt_exec() {
+    if !std::arch::is_x86_feature_detected!("lzcnt") {
+        return;
+    }
+
+    // fn(v: u64) -> u64 { v.leading_zeros() }
+    let mut asm = Asm::new();
+    asm.lzcnt(rax, rdi);
+    asm.ret();
+
+    let mut rt = Runtime::new();
+    let lzcnt = unsafe { rt.add_code::<extern "C" fn(u64) -> u64>(asm.into_code()) };
+    for v in VALUES {
+        assert_eq!(lzcnt(v), u64::from(v.leading_zeros()));
+    }
+}
+
+#[test]
+fn tzcnt_exec() {
+    if !std::arch::is_x86_feature_detected!("bmi1") {
+        return;
+    }
+
+    // fn(v: u64) -> u64 { v.trailing_zeros() }
+    let mut asm = Asm::new();
+    asm.tzcnt(rax, rdi);
+    asm.ret();
+
+    let mut rt = Runtime::new();
+    let tzcnt = unsafe { rt.add_code::<extern "C" fn(u64) -> u64>(asm.into_code()) };
+    for v in VALUES {
+        assert_eq!(tzcnt(v), u64::from(v.trailing_zeros()));
+    }
+}
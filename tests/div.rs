@@ -0,0 +1,52 @@
+use juicebox_asm::insn::{Div, Idiv};
+use juicebox_asm::{Asm, Mem16, Mem32, Mem64, Mem8, Reg16::*, Reg32::*, Reg64::*, Reg8::*};
+
+macro_rules! div {
+    ($insn:ident, $op1:expr) => {{
+        let mut asm = Asm::new();
+        asm.$insn($op1);
+        asm.into_code()
+    }};
+}
+
+#[rustfmt::skip]
+#[test]
+fn div() {
+    // reg.
+    assert_eq!(div!(div, rcx),                                  [0x48, 0xf7, 0xf1]);
+    assert_eq!(div!(div, r11),                                  [0x49, 0xf7, 0xf3]);
+    assert_eq!(div!(div, ecx),                                  [0xf7, 0xf1]);
+    assert_eq!(div!(div, r11d),                                 [0x41, 0xf7, 0xf3]);
+    assert_eq!(div!(div, cx),                                   [0x66, 0xf7, 0xf1]);
+    assert_eq!(div!(div, r11w),                                 [0x66, 0x41, 0xf7, 0xf3]);
+    assert_eq!(div!(div, cl),                                   [0xf6, 0xf1]);
+    assert_eq!(div!(div, dil),                                  [0x40, 0xf6, 0xf7]);
+    assert_eq!(div!(div, r11l),                                 [0x41, 0xf6, 0xf3]);
+
+    // mem.
+    assert_eq!(div!(div, Mem64::indirect(rax)),                 [0x48, 0xf7, 0x30]);
+    assert_eq!(div!(div, Mem32::indirect_disp(r11, 0x10)),      [0x41, 0xf7, 0xb3, 0x10, 0x00, 0x00, 0x00]);
+    assert_eq!(div!(div, Mem16::indirect_base_index(rdi, r9)),  [0x66, 0x42, 0xf7, 0x34, 0x0f]);
+    assert_eq!(div!(div, Mem8::indirect_disp(rbp, -8)),         [0xf6, 0xb5, 0xf8, 0xff, 0xff, 0xff]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn idiv() {
+    // reg.
+    assert_eq!(div!(idiv, rcx),                                 [0x48, 0xf7, 0xf9]);
+    assert_eq!(div!(idiv, r11),                                 [0x49, 0xf7, 0xfb]);
+    assert_eq!(div!(idiv, ecx),                                 [0xf7, 0xf9]);
+    assert_eq!(div!(idiv, r11d),                                [0x41, 0xf7, 0xfb]);
+    assert_eq!(div!(idiv, cx),                                  [0x66, 0xf7, 0xf9]);
+    assert_eq!(div!(idiv, r11w),                                [0x66, 0x41, 0xf7, 0xfb]);
+    assert_eq!(div!(idiv, cl),                                  [0xf6, 0xf9]);
+    assert_eq!(div!(idiv, dil),                                 [0x40, 0xf6, 0xff]);
+    assert_eq!(div!(idiv, r11l),                                [0x41, 0xf6, 0xfb]);
+
+    // mem.
+    assert_eq!(div!(idiv, Mem64::indirect(rax)),                [0x48, 0xf7, 0x38]);
+    assert_eq!(div!(idiv, Mem32::indirect_disp(r11, 0x10)),     [0x41, 0xf7, 0xbb, 0x10, 0x00, 0x00, 0x00]);
+    assert_eq!(div!(idiv, Mem16::indirect_base_index(rdi, r9)), [0x66, 0x42, 0xf7, 0x3c, 0x0f]);
+    assert_eq!(div!(idiv, Mem8::indirect_disp(rbp, -8)),        [0xf6, 0xbd, 0xf8, 0xff, 0xff, 0xff]);
+}
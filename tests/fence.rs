@@ -0,0 +1,17 @@
+use juicebox_asm::Asm;
+
+macro_rules! fence {
+    ($insn:ident) => {{
+        let mut asm = Asm::new();
+        asm.$insn();
+        asm.into_code()
+    }};
+}
+
+#[rustfmt::skip]
+#[test]
+fn fence() {
+    assert_eq!(fence!(lfence), [0x0f, 0xae, 0xe8]);
+    assert_eq!(fence!(sfence), [0x0f, 0xae, 0xf8]);
+    assert_eq!(fence!(mfence), [0x0f, 0xae, 0xf0]);
+}
@@ -0,0 +1,29 @@
+use juicebox_asm::insn::{Movzx, Setcc, Sub, Xor};
+use juicebox_asm::{Asm, Cond, Reg32::*, Reg64::*, Reg8::*, Runtime};
+
+#[test]
+fn pushfq_popfq() {
+    let mut asm = Asm::new();
+    asm.pushfq();
+    asm.popfq();
+    assert_eq!(asm.into_code(), [0x9c, 0x9d]);
+}
+
+#[test]
+fn pushfq_popfq_exec() {
+    // fn(a: u64, b: u64) -> u64, returns the carry of `a - b`, which is preserved across a `xor`
+    // clobbering the flags.
+    let mut asm = Asm::new();
+    asm.sub(rdi, rsi);
+    asm.pushfq();
+    asm.xor(eax, eax);
+    asm.popfq();
+    asm.setcc(Cond::B, al);
+    asm.movzx(eax, al);
+    asm.ret();
+
+    let mut rt = Runtime::new();
+    let f = unsafe { rt.add_code::<extern "C" fn(u64, u64) -> u64>(asm.into_code()) };
+    assert_eq!(f(1, 2), 1);
+    assert_eq!(f(2, 1), 0);
+}
@@ -0,0 +1,43 @@
+use juicebox_asm::insn::{Mov, Push};
+use juicebox_asm::{Asm, Imm16, Imm8, Mem64, Reg64::*, Runtime};
+
+#[test]
+fn enter_leave() {
+    let mut asm = Asm::new();
+    asm.enter(Imm16::from(0x20u16), Imm8::from(0u8));
+    asm.leave();
+    assert_eq!(asm.into_code(), [0xc8, 0x20, 0x00, 0x00, 0xc9]);
+}
+
+#[test]
+fn enter_leave_exec() {
+    // fn(a: u64) -> u64, stores `a` in a local of the frame, clobbers rsp and returns the local.
+    let mut asm = Asm::new();
+    asm.enter(Imm16::from(0x10u16), Imm8::from(0u8));
+    asm.mov(Mem64::indirect_disp(rbp, -8), rdi);
+    asm.push(rdi);
+    asm.push(rdi);
+    asm.mov(rax, Mem64::indirect_disp(rbp, -8));
+    asm.leave();
+    asm.ret();
+
+    let mut rt = Runtime::new();
+    let f = unsafe { rt.add_code::<extern "C" fn(u64) -> u64>(asm.into_code()) };
+    assert_eq!(f(42), 42);
+}
+
+#[test]
+fn push_rbp_leave_exec() {
+    // fn(a: u64) -> u64, sets up the frame manually and tears it down with leave.
+    let mut asm = Asm::new();
+    asm.push(rbp);
+    asm.mov(rbp, rsp);
+    asm.push(rdi);
+    asm.mov(rax, Mem64::indirect_disp(rbp, -8));
+    asm.leave();
+    asm.ret();
+
+    let mut rt = Runtime::new();
+    let f = unsafe { rt.add_code::<extern "C" fn(u64) -> u64>(asm.into_code()) };
+    assert_eq!(f(7), 7);
+}
@@ -0,0 +1,34 @@
+use juicebox_asm::{Asm, Growth};
+
+#[test]
+fn growth_default() {
+    let mut asm = Asm::new();
+    assert_eq!(asm.capacity(), 1024);
+    for _ in 0..1025 {
+        asm.nop();
+    }
+    assert_eq!(asm.capacity(), 2048);
+}
+
+#[test]
+fn growth_cap() {
+    // The growth is clamped to the cap.
+    let mut asm = Asm::with_growth(Growth::new(4, 3.0).with_cap(10));
+    for _ in 0..5 {
+        asm.nop();
+    }
+    assert_eq!(asm.capacity(), 10);
+    for _ in 0..5 {
+        asm.nop();
+    }
+    assert_eq!(asm.into_boxed_slice().len(), 10);
+}
+
+#[test]
+#[should_panic(expected = "Code buffer capacity of 2 bytes exceeded")]
+fn growth_cap_exceeded() {
+    let mut asm = Asm::with_growth(Growth::new(1, 2.0).with_cap(2));
+    asm.nop();
+    asm.nop();
+    asm.nop();
+}
@@ -0,0 +1,72 @@
+use juicebox_asm::insn::Mov;
+use juicebox_asm::{Asm, Imm64, Mem64, Reg32, Reg64::*, Runtime};
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Number of faults delivered to the host handler.
+static HOST_FAULTS: AtomicUsize = AtomicUsize::new(0);
+
+/// Host `SIGSEGV` handler, which makes the faulting page readable.
+extern "C" fn on_host_fault(
+    _sig: libc::c_int,
+    info: *mut libc::siginfo_t,
+    _ctx: *mut libc::c_void,
+) {
+    let page = unsafe { (*info).si_addr() } as usize & !4095;
+    unsafe { libc::mprotect(page as *mut libc::c_void, 4096, libc::PROT_READ) };
+    HOST_FAULTS.fetch_add(1, Ordering::SeqCst);
+}
+
+#[test]
+fn guard_forward() {
+    // Install the host handler before the guard handler, which forwards non-trap faults to it.
+    unsafe {
+        let mut sa: libc::sigaction = std::mem::zeroed();
+        sa.sa_sigaction = on_host_fault as *const () as libc::sighandler_t;
+        sa.sa_flags = libc::SA_SIGINFO;
+        libc::sigemptyset(&mut sa.sa_mask);
+        assert_eq!(libc::sigaction(libc::SIGSEGV, &sa, std::ptr::null_mut()), 0);
+    }
+
+    // fn load(base: *const u8, addr: u32) -> u64, returns u64::MAX on out of bound accesses.
+    let mut asm = Asm::new();
+    asm.mov(Reg32::esi, Reg32::esi);
+    asm.mov(rax, Mem64::indirect_base_index(rdi, rsi));
+    asm.ret();
+    let trap_off = asm.len();
+    asm.mov(rax, Imm64::from(u64::MAX));
+    asm.ret();
+
+    let mut rt = Runtime::new();
+    let load = unsafe { rt.add_code::<extern "C" fn(*const u8, u32) -> u64>(asm.into_code()) };
+    let region = rt.add_guard_region(4096);
+    region.set_trap(unsafe { (load as *const u8).add(trap_off) });
+    let base = region.base();
+
+    // Host access into the reservation is not resumed at the trap.
+    assert_eq!(unsafe { base.add(0x2000).cast::<u64>().read_volatile() }, 0);
+    assert_eq!(HOST_FAULTS.load(Ordering::SeqCst), 1);
+
+    // Faults outside of any region are forwarded, the host handler stays installed.
+    for n in 2..4 {
+        let page = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                4096,
+                libc::PROT_NONE,
+                libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+                -1,
+                0,
+            )
+        };
+        assert_ne!(page, libc::MAP_FAILED);
+        assert_eq!(unsafe { page.cast::<u64>().read_volatile() }, 0);
+        assert_eq!(HOST_FAULTS.load(Ordering::SeqCst), n);
+        unsafe { libc::munmap(page, 4096) };
+    }
+
+    // Faults of the jitted code are still turned into traps.
+    assert_eq!(load(base, 0), 0);
+    assert_eq!(load(base, 0x4000), u64::MAX);
+    assert_eq!(HOST_FAULTS.load(Ordering::SeqCst), 3);
+}
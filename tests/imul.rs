@@ -0,0 +1,76 @@
+use juicebox_asm::insn::Imul;
+use juicebox_asm::{
+    Asm, Imm16, Imm32, Imm8, Mem16, Mem32, Mem64, Mem8, Reg16::*, Reg32::*, Reg64::*, Reg8::*,
+};
+
+macro_rules! imul {
+    ($op1:expr) => {{
+        let mut asm = Asm::new();
+        asm.imul($op1);
+        asm.into_code()
+    }};
+}
+
+#[rustfmt::skip]
+#[test]
+fn imul_r() {
+    assert_eq!(imul!(rcx),                                          [0x48, 0xf7, 0xe9]);
+    assert_eq!(imul!(r11),                                          [0x49, 0xf7, 0xeb]);
+    assert_eq!(imul!(ecx),                                          [0xf7, 0xe9]);
+    assert_eq!(imul!(r11d),                                         [0x41, 0xf7, 0xeb]);
+    assert_eq!(imul!(cx),                                           [0x66, 0xf7, 0xe9]);
+    assert_eq!(imul!(r11w),                                         [0x66, 0x41, 0xf7, 0xeb]);
+    assert_eq!(imul!(cl),                                           [0xf6, 0xe9]);
+    assert_eq!(imul!(dil),                                          [0x40, 0xf6, 0xef]);
+    assert_eq!(imul!(r11l),                                         [0x41, 0xf6, 0xeb]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn imul_m() {
+    assert_eq!(imul!(Mem64::indirect(rax)),                         [0x48, 0xf7, 0x28]);
+    assert_eq!(imul!(Mem32::indirect_disp(r11, 0x10)),              [0x41, 0xf7, 0xab, 0x10, 0x00, 0x00, 0x00]);
+    assert_eq!(imul!(Mem16::indirect_base_index(rdi, r9)),          [0x66, 0x42, 0xf7, 0x2c, 0x0f]);
+    assert_eq!(imul!(Mem8::indirect_disp(rbp, -8)),                 [0xf6, 0xad, 0xf8, 0xff, 0xff, 0xff]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn imul_rr() {
+    assert_eq!(imul!((rcx, rdx)),                                   [0x48, 0x0f, 0xaf, 0xca]);
+    assert_eq!(imul!((r11, r12)),                                   [0x4d, 0x0f, 0xaf, 0xdc]);
+    assert_eq!(imul!((ecx, edx)),                                   [0x0f, 0xaf, 0xca]);
+    assert_eq!(imul!((r11d, edx)),                                  [0x44, 0x0f, 0xaf, 0xda]);
+    assert_eq!(imul!((cx, dx)),                                     [0x66, 0x0f, 0xaf, 0xca]);
+    assert_eq!(imul!((cx, r12w)),                                   [0x66, 0x41, 0x0f, 0xaf, 0xcc]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn imul_rm() {
+    assert_eq!(imul!((rcx, Mem64::indirect(rax))),                  [0x48, 0x0f, 0xaf, 0x08]);
+    assert_eq!(imul!((r11, Mem64::indirect_base_index(rdi, r9))),   [0x4e, 0x0f, 0xaf, 0x1c, 0x0f]);
+    assert_eq!(imul!((ecx, Mem32::indirect_disp(r11, 0x10))),       [0x41, 0x0f, 0xaf, 0x8b, 0x10, 0x00, 0x00, 0x00]);
+    assert_eq!(imul!((cx, Mem16::indirect(rsi))),                   [0x66, 0x0f, 0xaf, 0x0e]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn imul_rri() {
+    assert_eq!(imul!((rcx, rdx, Imm32::from(0x11223344u32))),       [0x48, 0x69, 0xca, 0x44, 0x33, 0x22, 0x11]);
+    assert_eq!(imul!((r11, rdx, Imm8::from(0x11u8))),               [0x4c, 0x6b, 0xda, 0x11]);
+    assert_eq!(imul!((ecx, r12d, Imm32::from(0x11223344u32))),      [0x41, 0x69, 0xcc, 0x44, 0x33, 0x22, 0x11]);
+    assert_eq!(imul!((ecx, edx, Imm8::from(0x11u8))),               [0x6b, 0xca, 0x11]);
+    assert_eq!(imul!((cx, dx, Imm16::from(0x1122u16))),             [0x66, 0x69, 0xca, 0x22, 0x11]);
+    assert_eq!(imul!((r11w, dx, Imm8::from(0x11u8))),               [0x66, 0x44, 0x6b, 0xda, 0x11]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn imul_rmi() {
+    assert_eq!(imul!((rcx, Mem64::indirect(rax), Imm32::from(0x11223344u32))), [0x48, 0x69, 0x08, 0x44, 0x33, 0x22, 0x11]);
+    assert_eq!(imul!((r11, Mem64::indirect_base_index(rdi, r9), Imm8::from(0x11u8))), [0x4e, 0x6b, 0x1c, 0x0f, 0x11]);
+    assert_eq!(imul!((ecx, Mem32::indirect_disp(r11, 0x10), Imm32::from(0x11223344u32))), [0x41, 0x69, 0x8b, 0x10, 0x00, 0x00, 0x00, 0x44, 0x33, 0x22, 0x11]);
+    assert_eq!(imul!((cx, Mem16::indirect(rsi), Imm16::from(0x1122u16))), [0x66, 0x69, 0x0e, 0x22, 0x11]);
+    assert_eq!(imul!((cx, Mem16::indirect(rsi), Imm8::from(0x11u8))), [0x66, 0x6b, 0x0e, 0x11]);
+}
@@ -1,5 +1,5 @@
 use juicebox_asm::insn::Jmp;
-use juicebox_asm::{Asm, Label};
+use juicebox_asm::{Asm, Cond, Label};
 
 #[test]
 #[should_panic]
@@ -23,8 +23,20 @@ fn jmp_label() {
         let mut asm = Asm::new();
         asm.bind(&mut lbl);
         asm.jmp(&mut lbl);
-        // 0xfffffffb -> -5
-        assert_eq!(asm.into_code(), [0xe9, 0xfb, 0xff, 0xff, 0xff]);
+        // 0xfe -> -2
+        assert_eq!(asm.into_code(), [0xeb, 0xfe]);
+    }
+    {
+        // Bind first, out of rel8 range.
+        let mut lbl = Label::new();
+        let mut asm = Asm::new();
+        asm.bind(&mut lbl);
+        for _ in 0..127 {
+            asm.nop();
+        }
+        asm.jmp(&mut lbl);
+        // 0xffffff7c -> -132
+        assert_eq!(asm.into_code()[127..], [0xe9, 0x7c, 0xff, 0xff, 0xff]);
     }
     {
         // Bind later.
@@ -58,3 +70,159 @@ fn jmp_label2() {
         assert_eq!(asm.into_code()[..5], [0xe9, 0xff, 0x01, 0x00, 0x00]);
     }
 }
+
+#[test]
+fn jcc_label() {
+    for (code, cond) in Cond::ALL.into_iter().enumerate() {
+        let mut lbl = Label::new();
+        let mut asm = Asm::new();
+        asm.bind(&mut lbl);
+        asm.jcc(cond, &mut lbl);
+        for _ in 0..126 {
+            asm.nop();
+        }
+        asm.jcc(cond, &mut lbl);
+        let code = code as u8;
+        let insns = asm.into_code();
+        // 0xfe -> -2
+        assert_eq!(insns[..2], [0x70 + code, 0xfe]);
+        // 0xffffff7a -> -134
+        assert_eq!(insns[128..], [0x0f, 0x80 + code, 0x7a, 0xff, 0xff, 0xff]);
+    }
+}
+
+#[test]
+fn jmp_short_label() {
+    let mut lbl = Label::new();
+    let mut asm = Asm::new();
+    asm.jmp_short(&mut lbl);
+    asm.jcc_short(Cond::Ne, &mut lbl);
+    for _ in 0..0x7d {
+        asm.nop();
+    }
+    asm.bind(&mut lbl);
+    assert_eq!(asm.into_code()[..4], [0xeb, 0x7f, 0x75, 0x7d]);
+}
+
+#[test]
+#[should_panic(expected = "Label out of range for rel8 jump")]
+fn jmp_short_out_of_range() {
+    let mut lbl = Label::new();
+    let mut asm = Asm::new();
+    asm.jmp_short(&mut lbl);
+    for _ in 0..0x80 {
+        asm.nop();
+    }
+    asm.bind(&mut lbl);
+}
+
+#[test]
+fn jcc_exec() {
+    use juicebox_asm::insn::{Mov, Sub};
+    use juicebox_asm::{Imm64, Reg64::*, Runtime};
+
+    let mut rt = Runtime::new();
+    let mut compile = |cond| {
+        // fn(a: u64, b: u64) -> u64 { if cond(a, b) { 1 } else { 0 } }
+        let mut lbl = Label::new();
+        let mut asm = Asm::new();
+        asm.mov(rax, Imm64::from(1));
+        // Sub sets the flags like cmp.
+        asm.sub(rdi, rsi);
+        asm.jcc(cond, &mut lbl);
+        asm.mov(rax, Imm64::from(0));
+        asm.bind(&mut lbl);
+        asm.ret();
+        unsafe { rt.add_code::<extern "C" fn(u64, u64) -> u64>(asm.into_code()) }
+    };
+
+    let (minus1, one) = (-1i64 as u64, 1u64);
+    for (cond, exp) in [
+        (Cond::E, [false, true, false]),
+        (Cond::Ne, [true, false, true]),
+        (Cond::B, [false, false, true]),
+        (Cond::Ae, [true, true, false]),
+        (Cond::A, [true, false, false]),
+        (Cond::Be, [false, true, true]),
+        (Cond::L, [true, false, false]),
+        (Cond::Ge, [false, true, true]),
+        (Cond::G, [false, false, true]),
+        (Cond::Le, [true, true, false]),
+    ] {
+        let f = compile(cond);
+        let res = [f(minus1, one) == 1, f(one, one) == 1, f(one, minus1) == 1];
+        assert_eq!(res, exp, "{:?}", cond);
+    }
+}
+
+#[rustfmt::skip]
+#[test]
+fn jmp_indirect() {
+    use juicebox_asm::{Mem64, Reg64::*};
+
+    macro_rules! jmp {
+        ($op1:expr) => {{
+            let mut asm = Asm::new();
+            asm.jmp($op1);
+            asm.into_code()
+        }};
+    }
+
+    assert_eq!(jmp!(rax),                                        [0x48, 0xff, 0xe0]);
+    assert_eq!(jmp!(r11),                                        [0x49, 0xff, 0xe3]);
+    assert_eq!(jmp!(Mem64::indirect(rax)),                       [0x48, 0xff, 0x20]);
+    assert_eq!(jmp!(Mem64::indirect_disp(rdi, 0x10)),            [0x48, 0xff, 0xa7, 0x10, 0x00, 0x00, 0x00]);
+    assert_eq!(jmp!(Mem64::indirect_base_index(rdi, rsi)),       [0x48, 0xff, 0x24, 0x37]);
+    assert_eq!(jmp!(Mem64::indirect(r11)),                       [0x49, 0xff, 0x23]);
+}
+
+#[test]
+fn jmp_indirect_exec() {
+    use juicebox_asm::insn::Mov;
+    use juicebox_asm::{Imm64, Mem64, Reg64::*, Runtime};
+
+    let mut rt = Runtime::new();
+
+    // fn() -> u64 { 42 }
+    let mut asm = Asm::new();
+    asm.mov(rax, Imm64::from(42));
+    asm.ret();
+    let target = unsafe { rt.add_code::<extern "C" fn() -> u64>(asm.into_code()) };
+
+    // fn(f: extern "C" fn() -> u64) -> u64 { f() }, tail jumps to f.
+    let mut asm = Asm::new();
+    asm.jmp(rdi);
+    let tail_reg =
+        unsafe { rt.add_code::<extern "C" fn(extern "C" fn() -> u64) -> u64>(asm.into_code()) };
+    assert_eq!(tail_reg(target), 42);
+
+    // fn(f: &extern "C" fn() -> u64) -> u64 { (*f)() }, tail jumps through the pointer.
+    let mut asm = Asm::new();
+    asm.jmp(Mem64::indirect(rdi));
+    let tail_mem =
+        unsafe { rt.add_code::<extern "C" fn(&extern "C" fn() -> u64) -> u64>(asm.into_code()) };
+    assert_eq!(tail_mem(&target), 42);
+}
+
+#[test]
+fn jmp_label_id() {
+    let mut asm = Asm::new();
+    let lbl = asm.new_label();
+    asm.jmp(lbl);
+    asm.nop();
+    asm.bind_label(lbl);
+    asm.jmp(lbl);
+    assert_eq!(
+        asm.into_code(),
+        [0xe9, 0x01, 0x00, 0x00, 0x00, 0x90, 0xeb, 0xfe]
+    );
+}
+
+#[test]
+#[should_panic(expected = "Label not bound")]
+fn jmp_label_id_unbound() {
+    let mut asm = Asm::new();
+    let lbl = asm.new_label();
+    asm.jmp(lbl);
+    asm.into_code();
+}
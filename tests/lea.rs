@@ -0,0 +1,30 @@
+use juicebox_asm::insn::Lea;
+use juicebox_asm::{Asm, Mem16, Mem32, Mem64, Reg16::*, Reg32::*, Reg64::*};
+
+macro_rules! lea {
+    ($insn:ident, $op1:expr, $op2:expr) => {{
+        let mut asm = Asm::new();
+        asm.$insn($op1, $op2);
+        asm.into_code()
+    }};
+}
+
+#[rustfmt::skip]
+#[test]
+fn lea() {
+    assert_eq!(lea!(lea, rax, Mem64::indirect(rbx)),                                    [0x48, 0x8d, 0x03]);
+    assert_eq!(lea!(lea, rax, Mem64::indirect_disp(rbx, 8)),                            [0x48, 0x8d, 0x83, 0x08, 0x00, 0x00, 0x00]);
+    assert_eq!(lea!(lea, r11, Mem64::indirect_base_index(rdi, r9)),                     [0x4e, 0x8d, 0x1c, 0x0f]);
+    assert_eq!(lea!(lea, rax, Mem64::indirect_base_index_scale_disp(rbx, rcx, 4, 8)),   [0x48, 0x8d, 0x84, 0x8b, 0x08, 0x00, 0x00, 0x00]);
+    assert_eq!(lea!(lea, rdx, Mem64::indirect_base_index_scale_disp(rsp, rax, 1, 0)),   [0x48, 0x8d, 0x94, 0x04, 0x00, 0x00, 0x00, 0x00]);
+    assert_eq!(lea!(lea, r8, Mem64::indirect_base_index_scale_disp(r13, r12, 8, -16)),  [0x4f, 0x8d, 0x84, 0xe5, 0xf0, 0xff, 0xff, 0xff]);
+    assert_eq!(lea!(lea, r10d, Mem32::indirect_disp(rax, -1)),                          [0x44, 0x8d, 0x90, 0xff, 0xff, 0xff, 0xff]);
+    assert_eq!(lea!(lea, ecx, Mem32::indirect_base_index_scale_disp(rbp, rsi, 2, 0x1000)), [0x8d, 0x8c, 0x75, 0x00, 0x10, 0x00, 0x00]);
+    assert_eq!(lea!(lea, cx, Mem16::indirect_base_index_scale_disp(rax, rbx, 8, 1)),    [0x66, 0x8d, 0x8c, 0xd8, 0x01, 0x00, 0x00, 0x00]);
+}
+
+#[test]
+#[should_panic(expected = "Scale must be 1, 2, 4 or 8")]
+fn lea_invalid_scale() {
+    let _ = Mem64::indirect_base_index_scale_disp(rax, rcx, 3, 0);
+}
@@ -0,0 +1,81 @@
+use juicebox_asm::insn::{Adc, Add, And, Dec, Inc, Mov, Neg, Not, Or, Sbb, Sub};
+use juicebox_asm::{Asm, Imm8, Mem16, Mem32, Mem64, Mem8, Reg16::*, Reg32::*, Reg64::*};
+
+macro_rules! lock {
+    ($f:expr) => {{
+        let mut asm = Asm::new();
+        asm.lock($f);
+        asm.into_code()
+    }};
+}
+
+#[rustfmt::skip]
+#[test]
+fn lock() {
+    assert_eq!(lock!(|a| a.add(Mem64::indirect(rdi), rax)),                           [0xf0, 0x48, 0x01, 0x07]);
+    assert_eq!(lock!(|a| a.add(Mem64::indirect(rdi), Imm8::from(1u8))),               [0xf0, 0x48, 0x83, 0x07, 0x01]);
+    assert_eq!(lock!(|a| a.sub(Mem8::indirect_disp(rsi, 0x10), Imm8::from(0x10u8))),  [0xf0, 0x80, 0xae, 0x10, 0x00, 0x00, 0x00, 0x10]);
+    assert_eq!(lock!(|a| a.or(Mem64::indirect_base_index(rdi, rsi), r9)),             [0xf0, 0x4c, 0x09, 0x0c, 0x37]);
+    assert_eq!(lock!(|a| a.or(Mem8::indirect(r8), Imm8::from(0x80u8))),               [0xf0, 0x41, 0x80, 0x08, 0x80]);
+    assert_eq!(lock!(|a| a.adc(Mem32::indirect(rax), edx)),                           [0xf0, 0x11, 0x10]);
+    assert_eq!(lock!(|a| a.sbb(Mem64::indirect(r15), rcx)),                           [0xf0, 0x49, 0x19, 0x0f]);
+    assert_eq!(lock!(|a| a.inc(Mem64::indirect(rax))),                                [0xf0, 0x48, 0xff, 0x00]);
+    assert_eq!(lock!(|a| a.dec(Mem32::indirect(rdi))),                                [0xf0, 0xff, 0x0f]);
+    assert_eq!(lock!(|a| a.neg(Mem8::indirect(rsi))),                                 [0xf0, 0xf6, 0x1e]);
+    // The lock prefix is emitted before the operand size prefix.
+    assert_eq!(lock!(|a| a.and(Mem16::indirect(rbx), cx)),                            [0xf0, 0x66, 0x21, 0x0b]);
+    assert_eq!(lock!(|a| a.not(Mem16::indirect(rdx))),                                [0xf0, 0x66, 0xf7, 0x12]);
+}
+
+#[test]
+#[should_panic(expected = "Instruction can not be locked: [\"mov\"]")]
+fn lock_not_lockable() {
+    lock!(|a| a.mov(Mem64::indirect(rdi), rax));
+}
+
+#[test]
+#[should_panic(expected = "Instruction can not be locked: [\"add\"]")]
+fn lock_reg_dst() {
+    lock!(|a| a.add(rax, Mem64::indirect(rdi)));
+}
+
+#[test]
+#[should_panic(expected = "Instruction can not be locked: [\"inc\", \"inc\"]")]
+fn lock_two_insns() {
+    lock!(|a| {
+        a.inc(Mem64::indirect(rdi));
+        a.inc(Mem64::indirect(rsi));
+    });
+}
+
+#[test]
+#[should_panic(expected = "Instruction can not be locked: []")]
+fn lock_empty() {
+    lock!(|_| {});
+}
+
+#[test]
+fn lock_exec() {
+    use juicebox_asm::Runtime;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    // fn(cnt: &AtomicU64, val: u64) { cnt.fetch_add(val) }
+    let mut asm = Asm::new();
+    asm.lock(|asm| asm.add(Mem64::indirect(rdi), rsi));
+    asm.ret();
+
+    let mut rt = Runtime::new();
+    let add = unsafe { rt.add_code::<extern "C" fn(&AtomicU64, u64)>(asm.into_code()) };
+
+    let cnt = AtomicU64::new(0);
+    std::thread::scope(|s| {
+        for _ in 0..4 {
+            s.spawn(|| {
+                for _ in 0..1000 {
+                    add(&cnt, 3);
+                }
+            });
+        }
+    });
+    assert_eq!(cnt.load(Ordering::Relaxed), 12000);
+}
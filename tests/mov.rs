@@ -48,6 +48,8 @@ fn mov_ri() {
     // 64bit.
     assert_eq!(mov!(rdi, Imm64::from(0xaabb)), [0x48, 0xbf, 0xbb, 0xaa, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
     assert_eq!(mov!(r12, Imm64::from(0xaabb)), [0x49, 0xbc, 0xbb, 0xaa, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
+    assert_eq!(mov!(rdi, Imm32::from(-2)),     [0x48, 0xc7, 0xc7, 0xfe, 0xff, 0xff, 0xff]);
+    assert_eq!(mov!(r12, Imm32::from(-2)),     [0x49, 0xc7, 0xc4, 0xfe, 0xff, 0xff, 0xff]);
 
     // 32bit.
     assert_eq!(mov!(edi,  Imm32::from(0xaabb)), [0xbf, 0xbb, 0xaa, 0x00, 0x00]);
@@ -88,6 +90,7 @@ fn mov_rm() {
     assert_eq!(mov!(r11l, Mem8::indirect(rsi)), [0x44, 0x8a, 0x1e]);
     assert_eq!(mov!(dil,  Mem8::indirect(r14)), [0x41, 0x8a, 0x3e]);
     assert_eq!(mov!(r15l, Mem8::indirect(r14)), [0x45, 0x8a, 0x3e]);
+    assert_eq!(mov!(rax, Mem64::indirect_base_index_scale_disp(rdi, rsi, 8, 0x10)), [0x48, 0x8b, 0x84, 0xf7, 0x10, 0x00, 0x00, 0x00]);
 }
 
 #[rustfmt::skip]
@@ -115,5 +118,25 @@ fn mov_mr() {
     assert_eq!(mov!(Mem8::indirect(rdx), cl),   [0x88, 0x0a]);
     assert_eq!(mov!(Mem8::indirect(rsi), r11l), [0x44, 0x88, 0x1e]);
     assert_eq!(mov!(Mem8::indirect(r14), dil),  [0x41, 0x88, 0x3e]);
+    assert_eq!(mov!(Mem8::indirect(rax), dil),  [0x40, 0x88, 0x38]);
     assert_eq!(mov!(Mem8::indirect(r14), r15l), [0x45, 0x88, 0x3e]);
+    assert_eq!(mov!(Mem32::indirect_base_index_scale_disp(r12, r15, 2, 0), r9d), [0x47, 0x89, 0x8c, 0x7c, 0x00, 0x00, 0x00, 0x00]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn mov_special_base() {
+    // rsp and r12 as base need a SIB byte, rbp and r13 as base need a displacement.
+    assert_eq!(mov!(rax, Mem64::indirect(rsp)),                         [0x48, 0x8b, 0x04, 0x24]);
+    assert_eq!(mov!(rax, Mem64::indirect(r12)),                         [0x49, 0x8b, 0x04, 0x24]);
+    assert_eq!(mov!(rax, Mem64::indirect(rbp)),                         [0x48, 0x8b, 0x45, 0x00]);
+    assert_eq!(mov!(rax, Mem64::indirect(r13)),                         [0x49, 0x8b, 0x45, 0x00]);
+    assert_eq!(mov!(rax, Mem64::indirect_disp(rsp, 0x10)),              [0x48, 0x8b, 0x84, 0x24, 0x10, 0x00, 0x00, 0x00]);
+    assert_eq!(mov!(rax, Mem64::indirect_disp(r12, -0x8)),              [0x49, 0x8b, 0x84, 0x24, 0xf8, 0xff, 0xff, 0xff]);
+    assert_eq!(mov!(rax, Mem64::indirect_base_index(rbp, rcx)),         [0x48, 0x8b, 0x44, 0x0d, 0x00]);
+    assert_eq!(mov!(rax, Mem64::indirect_base_index(r13, r12)),         [0x4b, 0x8b, 0x44, 0x25, 0x00]);
+    assert_eq!(mov!(rax, Mem64::indirect_base_index(rsp, rcx)),         [0x48, 0x8b, 0x04, 0x0c]);
+    assert_eq!(mov!(Mem32::indirect(r13), ecx),                         [0x41, 0x89, 0x4d, 0x00]);
+    assert_eq!(mov!(Mem8::indirect(rsp), dl),                           [0x88, 0x14, 0x24]);
+    assert_eq!(mov!(Mem16::indirect_disp(r12, 0x20), r9w),              [0x66, 0x45, 0x89, 0x8c, 0x24, 0x20, 0x00, 0x00, 0x00]);
 }
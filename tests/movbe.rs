@@ -0,0 +1,60 @@
+use juicebox_asm::insn::Movbe;
+use juicebox_asm::{Asm, CpuFeatures, Mem16, Mem32, Mem64, Reg16::*, Reg32::*, Reg64::*};
+
+fn movbe_features() -> CpuFeatures {
+    let mut features = CpuFeatures::baseline();
+    features.movbe = true;
+    features
+}
+
+macro_rules! movbe {
+    ($op1:expr, $op2:expr) => {{
+        let mut asm = Asm::new();
+        asm.set_cpu_features(movbe_features());
+        asm.movbe($op1, $op2);
+        asm.into_code()
+    }};
+}
+
+#[rustfmt::skip]
+#[test]
+fn movbe() {
+    assert_eq!(movbe!(rax, Mem64::indirect(rdi)),                           [0x48, 0x0f, 0x38, 0xf0, 0x07]);
+    assert_eq!(movbe!(r9, Mem64::indirect_disp(rsp, 0x10)),                 [0x4c, 0x0f, 0x38, 0xf0, 0x8c, 0x24, 0x10, 0x00, 0x00, 0x00]);
+    assert_eq!(movbe!(ecx, Mem32::indirect_base_index(rdi, r12)),           [0x42, 0x0f, 0x38, 0xf0, 0x0c, 0x27]);
+    assert_eq!(movbe!(r8w, Mem16::indirect(r13)),                           [0x66, 0x45, 0x0f, 0x38, 0xf0, 0x45, 0x00]);
+    assert_eq!(movbe!(Mem64::indirect(rsi), rdx),                           [0x48, 0x0f, 0x38, 0xf1, 0x16]);
+    assert_eq!(movbe!(Mem32::indirect_disp(r11, -0x4), r15d),               [0x45, 0x0f, 0x38, 0xf1, 0xbb, 0xfc, 0xff, 0xff, 0xff]);
+    assert_eq!(movbe!(Mem16::indirect(rax), bx),                            [0x66, 0x0f, 0x38, 0xf1, 0x18]);
+}
+
+#[test]
+#[should_panic(expected = "Instruction movbe not enabled in the CPU features")]
+fn movbe_disabled() {
+    let mut asm = Asm::new();
+    asm.set_cpu_features(CpuFeatures::baseline());
+    asm.movbe(rax, Mem64::indirect(rdi));
+}
+
+#[test]
+fn movbe_exec() {
+    use juicebox_asm::Runtime;
+
+    if !CpuFeatures::host().movbe {
+        return;
+    }
+
+    // fn(src: &u64, dst: &mut u32) -> u64, loads src big-endian and stores the low half
+    // big-endian to dst.
+    let mut asm = Asm::new();
+    asm.set_cpu_features(movbe_features());
+    asm.movbe(rax, Mem64::indirect(rdi));
+    asm.movbe(Mem32::indirect(rsi), eax);
+    asm.ret();
+
+    let mut rt = Runtime::new();
+    let f = unsafe { rt.add_code::<extern "C" fn(&u64, &mut u32) -> u64>(asm.into_code()) };
+    let mut dst = 0;
+    assert_eq!(f(&0x0123_4567_89ab_cdef, &mut dst), 0xefcd_ab89_6745_2301);
+    assert_eq!(dst, 0x0123_4567);
+}
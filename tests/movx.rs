@@ -0,0 +1,80 @@
+use juicebox_asm::insn::{Movsx, Movsxd, Movzx};
+use juicebox_asm::{Asm, Mem16, Mem32, Mem8, Reg16::*, Reg32::*, Reg64::*, Reg8::*};
+
+macro_rules! movx {
+    ($insn:ident, $op1:expr, $op2:expr) => {{
+        let mut asm = Asm::new();
+        asm.$insn($op1, $op2);
+        asm.into_code()
+    }};
+}
+
+#[rustfmt::skip]
+#[test]
+fn movzx() {
+    assert_eq!(movx!(movzx, rax, cl),                                           [0x48, 0x0f, 0xb6, 0xc1]);
+    assert_eq!(movx!(movzx, r11, dil),                                          [0x4c, 0x0f, 0xb6, 0xdf]);
+    assert_eq!(movx!(movzx, rcx, r9w),                                          [0x49, 0x0f, 0xb7, 0xc9]);
+    assert_eq!(movx!(movzx, eax, sil),                                          [0x40, 0x0f, 0xb6, 0xc6]);
+    assert_eq!(movx!(movzx, r8d, bl),                                           [0x44, 0x0f, 0xb6, 0xc3]);
+    assert_eq!(movx!(movzx, edx, cx),                                           [0x0f, 0xb7, 0xd1]);
+    assert_eq!(movx!(movzx, r15d, r12w),                                        [0x45, 0x0f, 0xb7, 0xfc]);
+    assert_eq!(movx!(movzx, ax, r10l),                                          [0x66, 0x41, 0x0f, 0xb6, 0xc2]);
+    assert_eq!(movx!(movzx, cx, dl),                                            [0x66, 0x0f, 0xb6, 0xca]);
+    assert_eq!(movx!(movzx, rax, Mem8::indirect(rdi)),                          [0x48, 0x0f, 0xb6, 0x07]);
+    assert_eq!(movx!(movzx, r9, Mem16::indirect_disp(rax, 0x10)),               [0x4c, 0x0f, 0xb7, 0x88, 0x10, 0x00, 0x00, 0x00]);
+    assert_eq!(movx!(movzx, ecx, Mem8::indirect_base_index(r11, rsi)),          [0x41, 0x0f, 0xb6, 0x0c, 0x33]);
+    assert_eq!(movx!(movzx, eax, Mem16::indirect(r14)),                         [0x41, 0x0f, 0xb7, 0x06]);
+    assert_eq!(movx!(movzx, dx, Mem8::indirect_disp(rbp, -8)),                  [0x66, 0x0f, 0xb6, 0x95, 0xf8, 0xff, 0xff, 0xff]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn movsx() {
+    assert_eq!(movx!(movsx, rax, cl),                                           [0x48, 0x0f, 0xbe, 0xc1]);
+    assert_eq!(movx!(movsx, r11, dil),                                          [0x4c, 0x0f, 0xbe, 0xdf]);
+    assert_eq!(movx!(movsx, rcx, r9w),                                          [0x49, 0x0f, 0xbf, 0xc9]);
+    assert_eq!(movx!(movsx, eax, sil),                                          [0x40, 0x0f, 0xbe, 0xc6]);
+    assert_eq!(movx!(movsx, r8d, bl),                                           [0x44, 0x0f, 0xbe, 0xc3]);
+    assert_eq!(movx!(movsx, edx, cx),                                           [0x0f, 0xbf, 0xd1]);
+    assert_eq!(movx!(movsx, r15d, r12w),                                        [0x45, 0x0f, 0xbf, 0xfc]);
+    assert_eq!(movx!(movsx, ax, r10l),                                          [0x66, 0x41, 0x0f, 0xbe, 0xc2]);
+    assert_eq!(movx!(movsx, cx, dl),                                            [0x66, 0x0f, 0xbe, 0xca]);
+    assert_eq!(movx!(movsx, rax, Mem8::indirect(rdi)),                          [0x48, 0x0f, 0xbe, 0x07]);
+    assert_eq!(movx!(movsx, r9, Mem16::indirect_disp(rax, 0x10)),               [0x4c, 0x0f, 0xbf, 0x88, 0x10, 0x00, 0x00, 0x00]);
+    assert_eq!(movx!(movsx, ecx, Mem8::indirect_base_index(r11, rsi)),          [0x41, 0x0f, 0xbe, 0x0c, 0x33]);
+    assert_eq!(movx!(movsx, eax, Mem16::indirect(r14)),                         [0x41, 0x0f, 0xbf, 0x06]);
+    assert_eq!(movx!(movsx, dx, Mem8::indirect_disp(rbp, -8)),                  [0x66, 0x0f, 0xbe, 0x95, 0xf8, 0xff, 0xff, 0xff]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn movsxd() {
+    assert_eq!(movx!(movsxd, rax, ecx),                                         [0x48, 0x63, 0xc1]);
+    assert_eq!(movx!(movsxd, r11, edi),                                         [0x4c, 0x63, 0xdf]);
+    assert_eq!(movx!(movsxd, rdx, r9d),                                         [0x49, 0x63, 0xd1]);
+    assert_eq!(movx!(movsxd, r15, r15d),                                        [0x4d, 0x63, 0xff]);
+    assert_eq!(movx!(movsxd, rax, Mem32::indirect(rdi)),                        [0x48, 0x63, 0x07]);
+    assert_eq!(movx!(movsxd, r8, Mem32::indirect_disp(rbp, -4)),                [0x4c, 0x63, 0x85, 0xfc, 0xff, 0xff, 0xff]);
+    assert_eq!(movx!(movsxd, rcx, Mem32::indirect_base_index(r10, rax)),        [0x49, 0x63, 0x0c, 0x02]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn movx_high8() {
+    // Without a REX byte the register codes 4-7 encode the high byte registers.
+    assert_eq!(movx!(movzx, eax, ah),                                           [0x0f, 0xb6, 0xc4]);
+    assert_eq!(movx!(movsx, cx, bh),                                            [0x66, 0x0f, 0xbe, 0xcf]);
+}
+
+#[test]
+#[should_panic = "High byte register can not be encoded with a REX prefix"]
+fn movzx_high8_rexw() {
+    movx!(movzx, rax, ah);
+}
+
+#[test]
+#[should_panic = "High byte register can not be encoded with a REX prefix"]
+fn movsx_high8_ext() {
+    movx!(movsx, r8d, ch);
+}
@@ -0,0 +1,31 @@
+use juicebox_asm::insn::Mul;
+use juicebox_asm::{Asm, Mem16, Mem32, Mem64, Mem8, Reg16::*, Reg32::*, Reg64::*, Reg8::*};
+
+macro_rules! mul {
+    ($insn:ident, $op1:expr) => {{
+        let mut asm = Asm::new();
+        asm.$insn($op1);
+        asm.into_code()
+    }};
+}
+
+#[rustfmt::skip]
+#[test]
+fn mul() {
+    // reg.
+    assert_eq!(mul!(mul, rcx),                                  [0x48, 0xf7, 0xe1]);
+    assert_eq!(mul!(mul, r11),                                  [0x49, 0xf7, 0xe3]);
+    assert_eq!(mul!(mul, ecx),                                  [0xf7, 0xe1]);
+    assert_eq!(mul!(mul, r11d),                                 [0x41, 0xf7, 0xe3]);
+    assert_eq!(mul!(mul, cx),                                   [0x66, 0xf7, 0xe1]);
+    assert_eq!(mul!(mul, r11w),                                 [0x66, 0x41, 0xf7, 0xe3]);
+    assert_eq!(mul!(mul, cl),                                   [0xf6, 0xe1]);
+    assert_eq!(mul!(mul, dil),                                  [0x40, 0xf6, 0xe7]);
+    assert_eq!(mul!(mul, r11l),                                 [0x41, 0xf6, 0xe3]);
+
+    // mem.
+    assert_eq!(mul!(mul, Mem64::indirect(rax)),                 [0x48, 0xf7, 0x20]);
+    assert_eq!(mul!(mul, Mem32::indirect_disp(r11, 0x10)),      [0x41, 0xf7, 0xa3, 0x10, 0x00, 0x00, 0x00]);
+    assert_eq!(mul!(mul, Mem16::indirect_base_index(rdi, r9)),  [0x66, 0x42, 0xf7, 0x24, 0x0f]);
+    assert_eq!(mul!(mul, Mem8::indirect_disp(rbp, -8)),         [0xf6, 0xa5, 0xf8, 0xff, 0xff, 0xff]);
+}
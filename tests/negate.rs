@@ -0,0 +1,52 @@
+use juicebox_asm::insn::{Neg, Not};
+use juicebox_asm::{Asm, Mem16, Mem32, Mem64, Mem8, Reg16::*, Reg32::*, Reg64::*, Reg8::*};
+
+macro_rules! negate {
+    ($insn:ident, $op1:expr) => {{
+        let mut asm = Asm::new();
+        asm.$insn($op1);
+        asm.into_code()
+    }};
+}
+
+#[rustfmt::skip]
+#[test]
+fn not() {
+    // reg.
+    assert_eq!(negate!(not, rcx),                               [0x48, 0xf7, 0xd1]);
+    assert_eq!(negate!(not, r11),                               [0x49, 0xf7, 0xd3]);
+    assert_eq!(negate!(not, ecx),                               [0xf7, 0xd1]);
+    assert_eq!(negate!(not, r11d),                              [0x41, 0xf7, 0xd3]);
+    assert_eq!(negate!(not, cx),                                [0x66, 0xf7, 0xd1]);
+    assert_eq!(negate!(not, r11w),                              [0x66, 0x41, 0xf7, 0xd3]);
+    assert_eq!(negate!(not, cl),                                [0xf6, 0xd1]);
+    assert_eq!(negate!(not, dil),                               [0x40, 0xf6, 0xd7]);
+    assert_eq!(negate!(not, r11l),                              [0x41, 0xf6, 0xd3]);
+
+    // mem.
+    assert_eq!(negate!(not, Mem64::indirect(rax)),              [0x48, 0xf7, 0x10]);
+    assert_eq!(negate!(not, Mem32::indirect_disp(r11, 0x10)),   [0x41, 0xf7, 0x93, 0x10, 0x00, 0x00, 0x00]);
+    assert_eq!(negate!(not, Mem16::indirect_base_index(rdi, r9)), [0x66, 0x42, 0xf7, 0x14, 0x0f]);
+    assert_eq!(negate!(not, Mem8::indirect_disp(rbp, -8)),      [0xf6, 0x95, 0xf8, 0xff, 0xff, 0xff]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn neg() {
+    // reg.
+    assert_eq!(negate!(neg, rcx),                               [0x48, 0xf7, 0xd9]);
+    assert_eq!(negate!(neg, r11),                               [0x49, 0xf7, 0xdb]);
+    assert_eq!(negate!(neg, ecx),                               [0xf7, 0xd9]);
+    assert_eq!(negate!(neg, r11d),                              [0x41, 0xf7, 0xdb]);
+    assert_eq!(negate!(neg, cx),                                [0x66, 0xf7, 0xd9]);
+    assert_eq!(negate!(neg, r11w),                              [0x66, 0x41, 0xf7, 0xdb]);
+    assert_eq!(negate!(neg, cl),                                [0xf6, 0xd9]);
+    assert_eq!(negate!(neg, dil),                               [0x40, 0xf6, 0xdf]);
+    assert_eq!(negate!(neg, r11l),                              [0x41, 0xf6, 0xdb]);
+
+    // mem.
+    assert_eq!(negate!(neg, Mem64::indirect(rax)),              [0x48, 0xf7, 0x18]);
+    assert_eq!(negate!(neg, Mem32::indirect_disp(r11, 0x10)),   [0x41, 0xf7, 0x9b, 0x10, 0x00, 0x00, 0x00]);
+    assert_eq!(negate!(neg, Mem16::indirect_base_index(rdi, r9)), [0x66, 0x42, 0xf7, 0x1c, 0x0f]);
+    assert_eq!(negate!(neg, Mem8::indirect_disp(rbp, -8)),      [0xf6, 0x9d, 0xf8, 0xff, 0xff, 0xff]);
+}
@@ -0,0 +1,170 @@
+use juicebox_asm::insn::Or;
+use juicebox_asm::{
+    Asm, Imm16, Imm32, Imm8, Mem16, Mem32, Mem64, Mem8, Reg16::*, Reg32::*, Reg64::*, Reg8::*,
+};
+
+macro_rules! or {
+    ($op1:expr, $op2:expr) => {{
+        let mut asm = Asm::new();
+        asm.or($op1, $op2);
+        asm.into_code()
+    }};
+}
+
+#[rustfmt::skip]
+#[test]
+fn or_rr() {
+    // 64bit.
+    assert_eq!(or!(rcx, rdx),                                   [0x48, 0x09, 0xd1]);
+    assert_eq!(or!(r11, r12),                                   [0x4d, 0x09, 0xe3]);
+
+    // 32bit.
+    assert_eq!(or!(ecx, edx),                                   [0x09, 0xd1]);
+    assert_eq!(or!(r11d, r12d),                                 [0x45, 0x09, 0xe3]);
+
+    // 16bit.
+    assert_eq!(or!(cx, dx),                                     [0x66, 0x09, 0xd1]);
+    assert_eq!(or!(r11w, r12w),                                 [0x66, 0x45, 0x09, 0xe3]);
+
+    // 8bit.
+    assert_eq!(or!(cl, dl),                                     [0x08, 0xd1]);
+    assert_eq!(or!(dil, sil),                                   [0x40, 0x08, 0xf7]);
+    assert_eq!(or!(r11l, r12l),                                 [0x45, 0x08, 0xe3]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn or_ri() {
+    // 64bit.
+    assert_eq!(or!(rcx, Imm32::from(0x11223344u32)),            [0x48, 0x81, 0xc9, 0x44, 0x33, 0x22, 0x11]);
+    assert_eq!(or!(rcx, Imm8::from(0x11u8)),                    [0x48, 0x83, 0xc9, 0x11]);
+    assert_eq!(or!(r11, Imm32::from(0x11223344u32)),            [0x49, 0x81, 0xcb, 0x44, 0x33, 0x22, 0x11]);
+    assert_eq!(or!(r11, Imm8::from(0x11u8)),                    [0x49, 0x83, 0xcb, 0x11]);
+
+    // 32bit.
+    assert_eq!(or!(ecx, Imm32::from(0x11223344u32)),            [0x81, 0xc9, 0x44, 0x33, 0x22, 0x11]);
+    assert_eq!(or!(ecx, Imm8::from(0x11u8)),                    [0x83, 0xc9, 0x11]);
+    assert_eq!(or!(r11d, Imm32::from(0x11223344u32)),           [0x41, 0x81, 0xcb, 0x44, 0x33, 0x22, 0x11]);
+    assert_eq!(or!(r11d, Imm8::from(0x11u8)),                   [0x41, 0x83, 0xcb, 0x11]);
+
+    // 16bit.
+    assert_eq!(or!(cx, Imm16::from(0x1122u16)),                 [0x66, 0x81, 0xc9, 0x22, 0x11]);
+    assert_eq!(or!(cx, Imm8::from(0x11u8)),                     [0x66, 0x83, 0xc9, 0x11]);
+    assert_eq!(or!(r11w, Imm16::from(0x1122u16)),               [0x66, 0x41, 0x81, 0xcb, 0x22, 0x11]);
+    assert_eq!(or!(r11w, Imm8::from(0x11u8)),                   [0x66, 0x41, 0x83, 0xcb, 0x11]);
+
+    // 8bit.
+    assert_eq!(or!(cl, Imm8::from(0x11u8)),                     [0x80, 0xc9, 0x11]);
+    assert_eq!(or!(dil, Imm8::from(0x11u8)),                    [0x40, 0x80, 0xcf, 0x11]);
+    assert_eq!(or!(r11l, Imm8::from(0x11u8)),                   [0x41, 0x80, 0xcb, 0x11]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn or_rm() {
+    // 64bit.
+    assert_eq!(or!(rcx, Mem64::indirect(rax)),                  [0x48, 0x0b, 0x08]);
+    assert_eq!(or!(r11, Mem64::indirect_disp(r11, 0x10)),       [0x4d, 0x0b, 0x9b, 0x10, 0x00, 0x00, 0x00]);
+
+    // 32bit.
+    assert_eq!(or!(ecx, Mem32::indirect(rax)),                  [0x0b, 0x08]);
+    assert_eq!(or!(r11d, Mem32::indirect_disp(r11, 0x10)),      [0x45, 0x0b, 0x9b, 0x10, 0x00, 0x00, 0x00]);
+
+    // 16bit.
+    assert_eq!(or!(cx, Mem16::indirect(rax)),                   [0x66, 0x0b, 0x08]);
+    assert_eq!(or!(r11w, Mem16::indirect_disp(r11, 0x10)),      [0x66, 0x45, 0x0b, 0x9b, 0x10, 0x00, 0x00, 0x00]);
+
+    // 8bit.
+    assert_eq!(or!(cl, Mem8::indirect(rax)),                    [0x0a, 0x08]);
+    assert_eq!(or!(dil, Mem8::indirect_disp(r11, 0x10)),        [0x41, 0x0a, 0xbb, 0x10, 0x00, 0x00, 0x00]);
+    assert_eq!(or!(r11l, Mem8::indirect_base_index(rdi, r9)),   [0x46, 0x0a, 0x1c, 0x0f]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn or_mr() {
+    // 64bit.
+    assert_eq!(or!(Mem64::indirect(rax), rdx),                  [0x48, 0x09, 0x10]);
+    assert_eq!(or!(Mem64::indirect_disp(r11, 0x10), r12),       [0x4d, 0x09, 0xa3, 0x10, 0x00, 0x00, 0x00]);
+
+    // 32bit.
+    assert_eq!(or!(Mem32::indirect(rax), edx),                  [0x09, 0x10]);
+    assert_eq!(or!(Mem32::indirect_disp(r11, 0x10), r12d),      [0x45, 0x09, 0xa3, 0x10, 0x00, 0x00, 0x00]);
+
+    // 16bit.
+    assert_eq!(or!(Mem16::indirect(rax), dx),                   [0x66, 0x09, 0x10]);
+    assert_eq!(or!(Mem16::indirect_disp(r11, 0x10), r12w),      [0x66, 0x45, 0x09, 0xa3, 0x10, 0x00, 0x00, 0x00]);
+
+    // 8bit.
+    assert_eq!(or!(Mem8::indirect(rax), dl),                    [0x08, 0x10]);
+    assert_eq!(or!(Mem8::indirect_disp(r11, 0x10), sil),        [0x41, 0x08, 0xb3, 0x10, 0x00, 0x00, 0x00]);
+    assert_eq!(or!(Mem8::indirect_base_index(rdi, r9), r12l),   [0x46, 0x08, 0x24, 0x0f]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn or_mi() {
+    // 64bit.
+    assert_eq!(or!(Mem64::indirect(rax), Imm32::from(0x11223344u32)), [0x48, 0x81, 0x08, 0x44, 0x33, 0x22, 0x11]);
+    assert_eq!(or!(Mem64::indirect(rax), Imm8::from(0x11u8)),   [0x48, 0x83, 0x08, 0x11]);
+    assert_eq!(or!(Mem64::indirect_disp(r11, 0x10), Imm32::from(0x11223344u32)), [0x49, 0x81, 0x8b, 0x10, 0x00, 0x00, 0x00, 0x44, 0x33, 0x22, 0x11]);
+    assert_eq!(or!(Mem64::indirect_disp(r11, 0x10), Imm8::from(0x11u8)), [0x49, 0x83, 0x8b, 0x10, 0x00, 0x00, 0x00, 0x11]);
+    assert_eq!(or!(Mem64::indirect_base_index(rdi, r9), Imm32::from(0x11223344u32)), [0x4a, 0x81, 0x0c, 0x0f, 0x44, 0x33, 0x22, 0x11]);
+    assert_eq!(or!(Mem64::indirect_base_index(rdi, r9), Imm8::from(0x11u8)), [0x4a, 0x83, 0x0c, 0x0f, 0x11]);
+
+    // 32bit.
+    assert_eq!(or!(Mem32::indirect(rax), Imm32::from(0x11223344u32)), [0x81, 0x08, 0x44, 0x33, 0x22, 0x11]);
+    assert_eq!(or!(Mem32::indirect(rax), Imm8::from(0x11u8)),   [0x83, 0x08, 0x11]);
+    assert_eq!(or!(Mem32::indirect_disp(r11, 0x10), Imm32::from(0x11223344u32)), [0x41, 0x81, 0x8b, 0x10, 0x00, 0x00, 0x00, 0x44, 0x33, 0x22, 0x11]);
+    assert_eq!(or!(Mem32::indirect_disp(r11, 0x10), Imm8::from(0x11u8)), [0x41, 0x83, 0x8b, 0x10, 0x00, 0x00, 0x00, 0x11]);
+    assert_eq!(or!(Mem32::indirect_base_index(rdi, r9), Imm32::from(0x11223344u32)), [0x42, 0x81, 0x0c, 0x0f, 0x44, 0x33, 0x22, 0x11]);
+    assert_eq!(or!(Mem32::indirect_base_index(rdi, r9), Imm8::from(0x11u8)), [0x42, 0x83, 0x0c, 0x0f, 0x11]);
+
+    // 16bit.
+    assert_eq!(or!(Mem16::indirect(rax), Imm16::from(0x1122u16)), [0x66, 0x81, 0x08, 0x22, 0x11]);
+    assert_eq!(or!(Mem16::indirect(rax), Imm8::from(0x11u8)),   [0x66, 0x83, 0x08, 0x11]);
+    assert_eq!(or!(Mem16::indirect_disp(r11, 0x10), Imm16::from(0x1122u16)), [0x66, 0x41, 0x81, 0x8b, 0x10, 0x00, 0x00, 0x00, 0x22, 0x11]);
+    assert_eq!(or!(Mem16::indirect_disp(r11, 0x10), Imm8::from(0x11u8)), [0x66, 0x41, 0x83, 0x8b, 0x10, 0x00, 0x00, 0x00, 0x11]);
+    assert_eq!(or!(Mem16::indirect_base_index(rdi, r9), Imm16::from(0x1122u16)), [0x66, 0x42, 0x81, 0x0c, 0x0f, 0x22, 0x11]);
+    assert_eq!(or!(Mem16::indirect_base_index(rdi, r9), Imm8::from(0x11u8)), [0x66, 0x42, 0x83, 0x0c, 0x0f, 0x11]);
+
+    // 8bit.
+    assert_eq!(or!(Mem8::indirect(rax), Imm8::from(0x11u8)),    [0x80, 0x08, 0x11]);
+    assert_eq!(or!(Mem8::indirect_disp(r11, 0x10), Imm8::from(0x11u8)), [0x41, 0x80, 0x8b, 0x10, 0x00, 0x00, 0x00, 0x11]);
+    assert_eq!(or!(Mem8::indirect_base_index(rdi, r9), Imm8::from(0x11u8)), [0x42, 0x80, 0x0c, 0x0f, 0x11]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn or_high8() {
+    // Without a REX byte the register codes 4-7 encode the high byte registers.
+    assert_eq!(or!(ah, cl),                                     [0x08, 0xcc]);
+    assert_eq!(or!(bl, dh),                                     [0x08, 0xf3]);
+    assert_eq!(or!(ch, Imm8::from(0x11u8)),                     [0x80, 0xcd, 0x11]);
+    assert_eq!(or!(ch, Mem8::indirect(rdi)),                    [0x0a, 0x2f]);
+    assert_eq!(or!(Mem8::indirect(rax), bh),                    [0x08, 0x38]);
+}
+
+#[test]
+#[should_panic = "High byte register can not be encoded with a REX prefix"]
+fn or_high8_rex_rr() {
+    or!(ah, r9l);
+}
+
+#[test]
+#[should_panic = "High byte register can not be encoded with a REX prefix"]
+fn or_high8_rex_rr_low() {
+    or!(bh, sil);
+}
+
+#[test]
+#[should_panic = "High byte register can not be encoded with a REX prefix"]
+fn or_high8_rex_rm() {
+    or!(ah, Mem8::indirect(r12));
+}
+
+#[test]
+#[should_panic = "High byte register can not be encoded with a REX prefix"]
+fn or_high8_rex_mr() {
+    or!(Mem8::indirect(r12), ah);
+}
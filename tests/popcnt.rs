@@ -0,0 +1,62 @@
+use juicebox_asm::insn::Popcnt;
+use juicebox_asm::{Asm, CpuFeatures, Mem16, Mem32, Mem64, Reg16::*, Reg32::*, Reg64::*};
+
+fn popcnt_features() -> CpuFeatures {
+    let mut features = CpuFeatures::baseline();
+    features.popcnt = true;
+    features
+}
+
+macro_rules! popcnt {
+    ($op1:expr, $op2:expr) => {{
+        let mut asm = Asm::new();
+        asm.set_cpu_features(popcnt_features());
+        asm.popcnt($op1, $op2);
+        asm.into_code()
+    }};
+}
+
+#[rustfmt::skip]
+#[test]
+fn popcnt() {
+    assert_eq!(popcnt!(rax, rcx),                                           [0xf3, 0x48, 0x0f, 0xb8, 0xc1]);
+    assert_eq!(popcnt!(r9, rdx),                                            [0xf3, 0x4c, 0x0f, 0xb8, 0xca]);
+    assert_eq!(popcnt!(rbx, r15),                                           [0xf3, 0x49, 0x0f, 0xb8, 0xdf]);
+    assert_eq!(popcnt!(eax, ecx),                                           [0xf3, 0x0f, 0xb8, 0xc1]);
+    assert_eq!(popcnt!(r11d, esi),                                          [0xf3, 0x44, 0x0f, 0xb8, 0xde]);
+    assert_eq!(popcnt!(rax, Mem64::indirect(rdi)),                          [0xf3, 0x48, 0x0f, 0xb8, 0x07]);
+    assert_eq!(popcnt!(r10, Mem64::indirect_disp(rbx, 0x10)),               [0xf3, 0x4c, 0x0f, 0xb8, 0x93, 0x10, 0x00, 0x00, 0x00]);
+    assert_eq!(popcnt!(ecx, Mem32::indirect_base_index(rdi, r9)),           [0xf3, 0x42, 0x0f, 0xb8, 0x0c, 0x0f]);
+    // The mandatory prefix is emitted before the operand size prefix.
+    assert_eq!(popcnt!(ax, cx),                                             [0xf3, 0x66, 0x0f, 0xb8, 0xc1]);
+    assert_eq!(popcnt!(r8w, r12w),                                          [0xf3, 0x66, 0x45, 0x0f, 0xb8, 0xc4]);
+    assert_eq!(popcnt!(dx, Mem16::indirect(rsi)),                           [0xf3, 0x66, 0x0f, 0xb8, 0x16]);
+}
+
+#[test]
+#[should_panic(expected = "Instruction popcnt not enabled in the CPU features")]
+fn popcnt_disabled() {
+    let mut asm = Asm::new();
+    asm.set_cpu_features(CpuFeatures::baseline());
+    asm.popcnt(rax, rcx);
+}
+
+#[test]
+fn popcnt_exec() {
+    use juicebox_asm::Runtime;
+
+    if !std::arch::is_x86_feature_detected!("popcnt") {
+        return;
+    }
+
+    // fn(v: u64) -> u64 { v.count_ones() }
+    let mut asm = Asm::new();
+    asm.popcnt(rax, rdi);
+    asm.ret();
+
+    let mut rt = Runtime::new();
+    let popcnt = unsafe { rt.add_code::<extern "C" fn(u64) -> u64>(asm.into_code()) };
+    for v in [0, 1, 0xff, 0x8000_0000_0000_0001, u64::MAX] {
+        assert_eq!(popcnt(v), u64::from(v.count_ones()));
+    }
+}
@@ -0,0 +1,50 @@
+use juicebox_asm::insn::{Pop, Push};
+use juicebox_asm::{Asm, Mem64, Reg64::*, Runtime};
+
+#[rustfmt::skip]
+#[test]
+fn push_mem() {
+    let push = |op1: Mem64| {
+        let mut asm = Asm::new();
+        asm.push(op1);
+        asm.into_code()
+    };
+
+    assert_eq!(push(Mem64::indirect(rax)),                   [0x48, 0xff, 0x30]);
+    assert_eq!(push(Mem64::indirect_disp(rsp, 0x8)),         [0x48, 0xff, 0xb4, 0x24, 0x08, 0x00, 0x00, 0x00]);
+    assert_eq!(push(Mem64::indirect_base_index(rdi, rsi)),   [0x48, 0xff, 0x34, 0x37]);
+    assert_eq!(push(Mem64::indirect(r12)),                   [0x49, 0xff, 0x34, 0x24]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn pop_mem() {
+    let pop = |op1: Mem64| {
+        let mut asm = Asm::new();
+        asm.pop(op1);
+        asm.into_code()
+    };
+
+    assert_eq!(pop(Mem64::indirect(rax)),                    [0x48, 0x8f, 0x00]);
+    assert_eq!(pop(Mem64::indirect_disp(rdi, -0x8)),         [0x48, 0x8f, 0x87, 0xf8, 0xff, 0xff, 0xff]);
+    assert_eq!(pop(Mem64::indirect_base_index(rdi, rsi)),    [0x48, 0x8f, 0x04, 0x37]);
+    assert_eq!(pop(Mem64::indirect(r13)),                    [0x49, 0x8f, 0x45, 0x00]);
+}
+
+#[test]
+fn push_pop_mem_exec() {
+    // fn(src: &u64, dst: &mut u64) -> u64, copies src to dst through the stack and returns the
+    // value pushed last.
+    let mut asm = Asm::new();
+    asm.push(Mem64::indirect(rdi));
+    asm.push(Mem64::indirect(rsp));
+    asm.pop(Mem64::indirect(rsi));
+    asm.pop(rax);
+    asm.ret();
+
+    let mut rt = Runtime::new();
+    let f = unsafe { rt.add_code::<extern "C" fn(&u64, &mut u64) -> u64>(asm.into_code()) };
+    let mut dst = 0;
+    assert_eq!(f(&0x1122_3344_5566_7788, &mut dst), 0x1122_3344_5566_7788);
+    assert_eq!(dst, 0x1122_3344_5566_7788);
+}
@@ -0,0 +1,47 @@
+use juicebox_asm::insn::{Or, Shl};
+use juicebox_asm::{Asm, Imm8, Reg64::*, Runtime};
+
+#[test]
+fn rdtsc() {
+    let mut asm = Asm::new();
+    asm.rdtsc();
+    asm.rdtscp();
+    assert_eq!(asm.into_code(), [0x0f, 0x31, 0x0f, 0x01, 0xf9]);
+}
+
+/// Compile a `fn() -> u64` reading the time stamp counter with `rdtsc` or `rdtscp`.
+fn read_tsc(rt: &mut Runtime, rdtscp: bool) -> extern "C" fn() -> u64 {
+    let mut asm = Asm::new();
+    if rdtscp {
+        asm.rdtscp();
+    } else {
+        asm.rdtsc();
+    }
+    asm.shl(rdx, Imm8::from(32u8));
+    asm.or(rax, rdx);
+    asm.ret();
+
+    unsafe { rt.add_code::<extern "C" fn() -> u64>(asm.into_code()) }
+}
+
+#[test]
+fn rdtsc_exec() {
+    let mut rt = Runtime::new();
+    let tsc = read_tsc(&mut rt, false);
+    let (t1, t2) = (tsc(), tsc());
+    assert!(t1 > 0 && t2 >= t1);
+}
+
+#[test]
+fn rdtscp_exec() {
+    // CPUID.80000001H:EDX.RDTSCP[bit 27]
+    let rdtscp = std::arch::x86_64::__cpuid(0x8000_0001).edx & (1 << 27) != 0;
+    if !rdtscp {
+        return;
+    }
+
+    let mut rt = Runtime::new();
+    let tsc = read_tsc(&mut rt, true);
+    let (t1, t2) = (tsc(), tsc());
+    assert!(t1 > 0 && t2 >= t1);
+}
@@ -0,0 +1,92 @@
+use juicebox_asm::insn::{Rol, Ror};
+use juicebox_asm::{Asm, Imm8, Mem16, Mem32, Mem64, Mem8, Reg16::*, Reg32::*, Reg64::*, Reg8::*};
+
+macro_rules! rotate {
+    ($insn:ident, $op1:expr, $op2:expr) => {{
+        let mut asm = Asm::new();
+        asm.$insn($op1, $op2);
+        asm.into_code()
+    }};
+}
+
+#[rustfmt::skip]
+#[test]
+fn rol() {
+    // reg imm.
+    assert_eq!(rotate!(rol, rcx, Imm8::from(3u8)),                  [0x48, 0xc1, 0xc1, 0x03]);
+    assert_eq!(rotate!(rol, r11, Imm8::from(3u8)),                  [0x49, 0xc1, 0xc3, 0x03]);
+    assert_eq!(rotate!(rol, ecx, Imm8::from(3u8)),                  [0xc1, 0xc1, 0x03]);
+    assert_eq!(rotate!(rol, r11d, Imm8::from(3u8)),                 [0x41, 0xc1, 0xc3, 0x03]);
+    assert_eq!(rotate!(rol, cx, Imm8::from(3u8)),                   [0x66, 0xc1, 0xc1, 0x03]);
+    assert_eq!(rotate!(rol, r11w, Imm8::from(3u8)),                 [0x66, 0x41, 0xc1, 0xc3, 0x03]);
+    assert_eq!(rotate!(rol, cl, Imm8::from(3u8)),                   [0xc0, 0xc1, 0x03]);
+    assert_eq!(rotate!(rol, dil, Imm8::from(3u8)),                  [0x40, 0xc0, 0xc7, 0x03]);
+    assert_eq!(rotate!(rol, r11l, Imm8::from(3u8)),                 [0x41, 0xc0, 0xc3, 0x03]);
+
+    // mem imm.
+    assert_eq!(rotate!(rol, Mem64::indirect(rax), Imm8::from(3u8)), [0x48, 0xc1, 0x00, 0x03]);
+    assert_eq!(rotate!(rol, Mem32::indirect_disp(r11, 0x10), Imm8::from(3u8)), [0x41, 0xc1, 0x83, 0x10, 0x00, 0x00, 0x00, 0x03]);
+    assert_eq!(rotate!(rol, Mem16::indirect_base_index(rdi, r9), Imm8::from(3u8)), [0x66, 0x42, 0xc1, 0x04, 0x0f, 0x03]);
+    assert_eq!(rotate!(rol, Mem8::indirect_disp(rbp, -8), Imm8::from(3u8)), [0xc0, 0x85, 0xf8, 0xff, 0xff, 0xff, 0x03]);
+
+    // reg cl.
+    assert_eq!(rotate!(rol, rcx, cl),                               [0x48, 0xd3, 0xc1]);
+    assert_eq!(rotate!(rol, r11, cl),                               [0x49, 0xd3, 0xc3]);
+    assert_eq!(rotate!(rol, ecx, cl),                               [0xd3, 0xc1]);
+    assert_eq!(rotate!(rol, r11d, cl),                              [0x41, 0xd3, 0xc3]);
+    assert_eq!(rotate!(rol, cx, cl),                                [0x66, 0xd3, 0xc1]);
+    assert_eq!(rotate!(rol, r11w, cl),                              [0x66, 0x41, 0xd3, 0xc3]);
+    assert_eq!(rotate!(rol, cl, cl),                                [0xd2, 0xc1]);
+    assert_eq!(rotate!(rol, dil, cl),                               [0x40, 0xd2, 0xc7]);
+    assert_eq!(rotate!(rol, r11l, cl),                              [0x41, 0xd2, 0xc3]);
+
+    // mem cl.
+    assert_eq!(rotate!(rol, Mem64::indirect(rax), cl),              [0x48, 0xd3, 0x00]);
+    assert_eq!(rotate!(rol, Mem32::indirect_disp(r11, 0x10), cl),   [0x41, 0xd3, 0x83, 0x10, 0x00, 0x00, 0x00]);
+    assert_eq!(rotate!(rol, Mem16::indirect_base_index(rdi, r9), cl), [0x66, 0x42, 0xd3, 0x04, 0x0f]);
+    assert_eq!(rotate!(rol, Mem8::indirect_disp(rbp, -8), cl),      [0xd2, 0x85, 0xf8, 0xff, 0xff, 0xff]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn ror() {
+    // reg imm.
+    assert_eq!(rotate!(ror, rcx, Imm8::from(3u8)),                  [0x48, 0xc1, 0xc9, 0x03]);
+    assert_eq!(rotate!(ror, r11, Imm8::from(3u8)),                  [0x49, 0xc1, 0xcb, 0x03]);
+    assert_eq!(rotate!(ror, ecx, Imm8::from(3u8)),                  [0xc1, 0xc9, 0x03]);
+    assert_eq!(rotate!(ror, r11d, Imm8::from(3u8)),                 [0x41, 0xc1, 0xcb, 0x03]);
+    assert_eq!(rotate!(ror, cx, Imm8::from(3u8)),                   [0x66, 0xc1, 0xc9, 0x03]);
+    assert_eq!(rotate!(ror, r11w, Imm8::from(3u8)),                 [0x66, 0x41, 0xc1, 0xcb, 0x03]);
+    assert_eq!(rotate!(ror, cl, Imm8::from(3u8)),                   [0xc0, 0xc9, 0x03]);
+    assert_eq!(rotate!(ror, dil, Imm8::from(3u8)),                  [0x40, 0xc0, 0xcf, 0x03]);
+    assert_eq!(rotate!(ror, r11l, Imm8::from(3u8)),                 [0x41, 0xc0, 0xcb, 0x03]);
+
+    // mem imm.
+    assert_eq!(rotate!(ror, Mem64::indirect(rax), Imm8::from(3u8)), [0x48, 0xc1, 0x08, 0x03]);
+    assert_eq!(rotate!(ror, Mem32::indirect_disp(r11, 0x10), Imm8::from(3u8)), [0x41, 0xc1, 0x8b, 0x10, 0x00, 0x00, 0x00, 0x03]);
+    assert_eq!(rotate!(ror, Mem16::indirect_base_index(rdi, r9), Imm8::from(3u8)), [0x66, 0x42, 0xc1, 0x0c, 0x0f, 0x03]);
+    assert_eq!(rotate!(ror, Mem8::indirect_disp(rbp, -8), Imm8::from(3u8)), [0xc0, 0x8d, 0xf8, 0xff, 0xff, 0xff, 0x03]);
+
+    // reg cl.
+    assert_eq!(rotate!(ror, rcx, cl),                               [0x48, 0xd3, 0xc9]);
+    assert_eq!(rotate!(ror, r11, cl),                               [0x49, 0xd3, 0xcb]);
+    assert_eq!(rotate!(ror, ecx, cl),                               [0xd3, 0xc9]);
+    assert_eq!(rotate!(ror, r11d, cl),                              [0x41, 0xd3, 0xcb]);
+    assert_eq!(rotate!(ror, cx, cl),                                [0x66, 0xd3, 0xc9]);
+    assert_eq!(rotate!(ror, r11w, cl),                              [0x66, 0x41, 0xd3, 0xcb]);
+    assert_eq!(rotate!(ror, cl, cl),                                [0xd2, 0xc9]);
+    assert_eq!(rotate!(ror, dil, cl),                               [0x40, 0xd2, 0xcf]);
+    assert_eq!(rotate!(ror, r11l, cl),                              [0x41, 0xd2, 0xcb]);
+
+    // mem cl.
+    assert_eq!(rotate!(ror, Mem64::indirect(rax), cl),              [0x48, 0xd3, 0x08]);
+    assert_eq!(rotate!(ror, Mem32::indirect_disp(r11, 0x10), cl),   [0x41, 0xd3, 0x8b, 0x10, 0x00, 0x00, 0x00]);
+    assert_eq!(rotate!(ror, Mem16::indirect_base_index(rdi, r9), cl), [0x66, 0x42, 0xd3, 0x0c, 0x0f]);
+    assert_eq!(rotate!(ror, Mem8::indirect_disp(rbp, -8), cl),      [0xd2, 0x8d, 0xf8, 0xff, 0xff, 0xff]);
+}
+
+#[test]
+#[should_panic]
+fn rotate_count_not_cl() {
+    rotate!(rol, rax, dl);
+}
@@ -0,0 +1,189 @@
+use juicebox_asm::insn::Sbb;
+use juicebox_asm::{
+    Asm, Imm16, Imm32, Imm8, Mem16, Mem32, Mem64, Mem8, Reg16::*, Reg32::*, Reg64::*, Reg8::*,
+};
+
+macro_rules! sbb {
+    ($op1:expr, $op2:expr) => {{
+        let mut asm = Asm::new();
+        asm.sbb($op1, $op2);
+        asm.into_code()
+    }};
+}
+
+#[rustfmt::skip]
+#[test]
+fn sbb_rr() {
+    // 64bit.
+    assert_eq!(sbb!(rcx, rdx),                                  [0x48, 0x19, 0xd1]);
+    assert_eq!(sbb!(r11, r12),                                  [0x4d, 0x19, 0xe3]);
+
+    // 32bit.
+    assert_eq!(sbb!(ecx, edx),                                  [0x19, 0xd1]);
+    assert_eq!(sbb!(r11d, r12d),                                [0x45, 0x19, 0xe3]);
+
+    // 16bit.
+    assert_eq!(sbb!(cx, dx),                                    [0x66, 0x19, 0xd1]);
+    assert_eq!(sbb!(r11w, r12w),                                [0x66, 0x45, 0x19, 0xe3]);
+
+    // 8bit.
+    assert_eq!(sbb!(cl, dl),                                    [0x18, 0xd1]);
+    assert_eq!(sbb!(dil, sil),                                  [0x40, 0x18, 0xf7]);
+    assert_eq!(sbb!(r11l, r12l),                                [0x45, 0x18, 0xe3]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn sbb_ri() {
+    // 64bit.
+    assert_eq!(sbb!(rcx, Imm32::from(0x11223344u32)),           [0x48, 0x81, 0xd9, 0x44, 0x33, 0x22, 0x11]);
+    assert_eq!(sbb!(rcx, Imm8::from(0x11u8)),                   [0x48, 0x83, 0xd9, 0x11]);
+    assert_eq!(sbb!(r11, Imm32::from(0x11223344u32)),           [0x49, 0x81, 0xdb, 0x44, 0x33, 0x22, 0x11]);
+    assert_eq!(sbb!(r11, Imm8::from(0x11u8)),                   [0x49, 0x83, 0xdb, 0x11]);
+
+    // 32bit.
+    assert_eq!(sbb!(ecx, Imm32::from(0x11223344u32)),           [0x81, 0xd9, 0x44, 0x33, 0x22, 0x11]);
+    assert_eq!(sbb!(ecx, Imm8::from(0x11u8)),                   [0x83, 0xd9, 0x11]);
+    assert_eq!(sbb!(r11d, Imm32::from(0x11223344u32)),          [0x41, 0x81, 0xdb, 0x44, 0x33, 0x22, 0x11]);
+    assert_eq!(sbb!(r11d, Imm8::from(0x11u8)),                  [0x41, 0x83, 0xdb, 0x11]);
+
+    // 16bit.
+    assert_eq!(sbb!(cx, Imm16::from(0x1122u16)),                [0x66, 0x81, 0xd9, 0x22, 0x11]);
+    assert_eq!(sbb!(cx, Imm8::from(0x11u8)),                    [0x66, 0x83, 0xd9, 0x11]);
+    assert_eq!(sbb!(r11w, Imm16::from(0x1122u16)),              [0x66, 0x41, 0x81, 0xdb, 0x22, 0x11]);
+    assert_eq!(sbb!(r11w, Imm8::from(0x11u8)),                  [0x66, 0x41, 0x83, 0xdb, 0x11]);
+
+    // 8bit.
+    assert_eq!(sbb!(cl, Imm8::from(0x11u8)),                    [0x80, 0xd9, 0x11]);
+    assert_eq!(sbb!(dil, Imm8::from(0x11u8)),                   [0x40, 0x80, 0xdf, 0x11]);
+    assert_eq!(sbb!(r11l, Imm8::from(0x11u8)),                  [0x41, 0x80, 0xdb, 0x11]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn sbb_rm() {
+    // 64bit.
+    assert_eq!(sbb!(rcx, Mem64::indirect(rax)),                 [0x48, 0x1b, 0x08]);
+    assert_eq!(sbb!(r11, Mem64::indirect_disp(r11, 0x10)),      [0x4d, 0x1b, 0x9b, 0x10, 0x00, 0x00, 0x00]);
+
+    // 32bit.
+    assert_eq!(sbb!(ecx, Mem32::indirect(rax)),                 [0x1b, 0x08]);
+    assert_eq!(sbb!(r11d, Mem32::indirect_disp(r11, 0x10)),     [0x45, 0x1b, 0x9b, 0x10, 0x00, 0x00, 0x00]);
+
+    // 16bit.
+    assert_eq!(sbb!(cx, Mem16::indirect(rax)),                  [0x66, 0x1b, 0x08]);
+    assert_eq!(sbb!(r11w, Mem16::indirect_disp(r11, 0x10)),     [0x66, 0x45, 0x1b, 0x9b, 0x10, 0x00, 0x00, 0x00]);
+
+    // 8bit.
+    assert_eq!(sbb!(cl, Mem8::indirect(rax)),                   [0x1a, 0x08]);
+    assert_eq!(sbb!(dil, Mem8::indirect_disp(r11, 0x10)),       [0x41, 0x1a, 0xbb, 0x10, 0x00, 0x00, 0x00]);
+    assert_eq!(sbb!(r11l, Mem8::indirect_base_index(rdi, r9)),  [0x46, 0x1a, 0x1c, 0x0f]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn sbb_mr() {
+    // 64bit.
+    assert_eq!(sbb!(Mem64::indirect(rax), rdx),                 [0x48, 0x19, 0x10]);
+    assert_eq!(sbb!(Mem64::indirect_disp(r11, 0x10), r12),      [0x4d, 0x19, 0xa3, 0x10, 0x00, 0x00, 0x00]);
+
+    // 32bit.
+    assert_eq!(sbb!(Mem32::indirect(rax), edx),                 [0x19, 0x10]);
+    assert_eq!(sbb!(Mem32::indirect_disp(r11, 0x10), r12d),     [0x45, 0x19, 0xa3, 0x10, 0x00, 0x00, 0x00]);
+
+    // 16bit.
+    assert_eq!(sbb!(Mem16::indirect(rax), dx),                  [0x66, 0x19, 0x10]);
+    assert_eq!(sbb!(Mem16::indirect_disp(r11, 0x10), r12w),     [0x66, 0x45, 0x19, 0xa3, 0x10, 0x00, 0x00, 0x00]);
+
+    // 8bit.
+    assert_eq!(sbb!(Mem8::indirect(rax), dl),                   [0x18, 0x10]);
+    assert_eq!(sbb!(Mem8::indirect_disp(r11, 0x10), sil),       [0x41, 0x18, 0xb3, 0x10, 0x00, 0x00, 0x00]);
+    assert_eq!(sbb!(Mem8::indirect_base_index(rdi, r9), r12l),  [0x46, 0x18, 0x24, 0x0f]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn sbb_mi() {
+    // 64bit.
+    assert_eq!(sbb!(Mem64::indirect(rax), Imm32::from(0x11223344u32)), [0x48, 0x81, 0x18, 0x44, 0x33, 0x22, 0x11]);
+    assert_eq!(sbb!(Mem64::indirect(rax), Imm8::from(0x11u8)),  [0x48, 0x83, 0x18, 0x11]);
+    assert_eq!(sbb!(Mem64::indirect_disp(r11, 0x10), Imm32::from(0x11223344u32)), [0x49, 0x81, 0x9b, 0x10, 0x00, 0x00, 0x00, 0x44, 0x33, 0x22, 0x11]);
+    assert_eq!(sbb!(Mem64::indirect_disp(r11, 0x10), Imm8::from(0x11u8)), [0x49, 0x83, 0x9b, 0x10, 0x00, 0x00, 0x00, 0x11]);
+    assert_eq!(sbb!(Mem64::indirect_base_index(rdi, r9), Imm32::from(0x11223344u32)), [0x4a, 0x81, 0x1c, 0x0f, 0x44, 0x33, 0x22, 0x11]);
+    assert_eq!(sbb!(Mem64::indirect_base_index(rdi, r9), Imm8::from(0x11u8)), [0x4a, 0x83, 0x1c, 0x0f, 0x11]);
+
+    // 32bit.
+    assert_eq!(sbb!(Mem32::indirect(rax), Imm32::from(0x11223344u32)), [0x81, 0x18, 0x44, 0x33, 0x22, 0x11]);
+    assert_eq!(sbb!(Mem32::indirect(rax), Imm8::from(0x11u8)),  [0x83, 0x18, 0x11]);
+    assert_eq!(sbb!(Mem32::indirect_disp(r11, 0x10), Imm32::from(0x11223344u32)), [0x41, 0x81, 0x9b, 0x10, 0x00, 0x00, 0x00, 0x44, 0x33, 0x22, 0x11]);
+    assert_eq!(sbb!(Mem32::indirect_disp(r11, 0x10), Imm8::from(0x11u8)), [0x41, 0x83, 0x9b, 0x10, 0x00, 0x00, 0x00, 0x11]);
+    assert_eq!(sbb!(Mem32::indirect_base_index(rdi, r9), Imm32::from(0x11223344u32)), [0x42, 0x81, 0x1c, 0x0f, 0x44, 0x33, 0x22, 0x11]);
+    assert_eq!(sbb!(Mem32::indirect_base_index(rdi, r9), Imm8::from(0x11u8)), [0x42, 0x83, 0x1c, 0x0f, 0x11]);
+
+    // 16bit.
+    assert_eq!(sbb!(Mem16::indirect(rax), Imm16::from(0x1122u16)), [0x66, 0x81, 0x18, 0x22, 0x11]);
+    assert_eq!(sbb!(Mem16::indirect(rax), Imm8::from(0x11u8)),  [0x66, 0x83, 0x18, 0x11]);
+    assert_eq!(sbb!(Mem16::indirect_disp(r11, 0x10), Imm16::from(0x1122u16)), [0x66, 0x41, 0x81, 0x9b, 0x10, 0x00, 0x00, 0x00, 0x22, 0x11]);
+    assert_eq!(sbb!(Mem16::indirect_disp(r11, 0x10), Imm8::from(0x11u8)), [0x66, 0x41, 0x83, 0x9b, 0x10, 0x00, 0x00, 0x00, 0x11]);
+    assert_eq!(sbb!(Mem16::indirect_base_index(rdi, r9), Imm16::from(0x1122u16)), [0x66, 0x42, 0x81, 0x1c, 0x0f, 0x22, 0x11]);
+    assert_eq!(sbb!(Mem16::indirect_base_index(rdi, r9), Imm8::from(0x11u8)), [0x66, 0x42, 0x83, 0x1c, 0x0f, 0x11]);
+
+    // 8bit.
+    assert_eq!(sbb!(Mem8::indirect(rax), Imm8::from(0x11u8)),   [0x80, 0x18, 0x11]);
+    assert_eq!(sbb!(Mem8::indirect_disp(r11, 0x10), Imm8::from(0x11u8)), [0x41, 0x80, 0x9b, 0x10, 0x00, 0x00, 0x00, 0x11]);
+    assert_eq!(sbb!(Mem8::indirect_base_index(rdi, r9), Imm8::from(0x11u8)), [0x42, 0x80, 0x1c, 0x0f, 0x11]);
+}
+
+#[test]
+fn sbb_exec() {
+    use juicebox_asm::insn::{Mov, Sub};
+    use juicebox_asm::Runtime;
+
+    // fn(lo: u64, hi: u64) -> u64 { ((hi:lo) - (1:1)).hi }
+    let mut asm = Asm::new();
+    asm.mov(rax, rsi);
+    asm.mov(rcx, juicebox_asm::Imm64::from(1u64));
+    asm.sub(rdi, rcx);
+    asm.sbb(rax, Imm8::from(1u8));
+    asm.ret();
+
+    let mut rt = Runtime::new();
+    let f = unsafe { rt.add_code::<extern "C" fn(u64, u64) -> u64>(asm.into_code()) };
+    assert_eq!(f(1, 5), 4);
+    assert_eq!(f(0, 5), 3);
+}
+
+#[rustfmt::skip]
+#[test]
+fn sbb_high8() {
+    // Without a REX byte the register codes 4-7 encode the high byte registers.
+    assert_eq!(sbb!(ah, cl),                                    [0x18, 0xcc]);
+    assert_eq!(sbb!(bl, dh),                                    [0x18, 0xf3]);
+    assert_eq!(sbb!(ch, Imm8::from(0x11u8)),                    [0x80, 0xdd, 0x11]);
+    assert_eq!(sbb!(ch, Mem8::indirect(rdi)),                   [0x1a, 0x2f]);
+    assert_eq!(sbb!(Mem8::indirect(rax), bh),                   [0x18, 0x38]);
+}
+
+#[test]
+#[should_panic = "High byte register can not be encoded with a REX prefix"]
+fn sbb_high8_rex_rr() {
+    sbb!(ah, r9l);
+}
+
+#[test]
+#[should_panic = "High byte register can not be encoded with a REX prefix"]
+fn sbb_high8_rex_rr_low() {
+    sbb!(bh, sil);
+}
+
+#[test]
+#[should_panic = "High byte register can not be encoded with a REX prefix"]
+fn sbb_high8_rex_rm() {
+    sbb!(ah, Mem8::indirect(r12));
+}
+
+#[test]
+#[should_panic = "High byte register can not be encoded with a REX prefix"]
+fn sbb_high8_rex_mr() {
+    sbb!(Mem8::indirect(r12), ah);
+}
@@ -0,0 +1,42 @@
+use juicebox_asm::insn::Setcc;
+use juicebox_asm::{Asm, Cond, Mem8, Reg64::*, Reg8::*};
+
+macro_rules! setcc {
+    ($cond:expr, $op1:expr) => {{
+        let mut asm = Asm::new();
+        asm.setcc($cond, $op1);
+        asm.into_code()
+    }};
+}
+
+#[rustfmt::skip]
+#[test]
+fn setcc_cond() {
+    assert_eq!(setcc!(Cond::O, al),                                     [0x0f, 0x90, 0xc0]);
+    assert_eq!(setcc!(Cond::No, al),                                    [0x0f, 0x91, 0xc0]);
+    assert_eq!(setcc!(Cond::B, al),                                     [0x0f, 0x92, 0xc0]);
+    assert_eq!(setcc!(Cond::Ae, al),                                    [0x0f, 0x93, 0xc0]);
+    assert_eq!(setcc!(Cond::E, al),                                     [0x0f, 0x94, 0xc0]);
+    assert_eq!(setcc!(Cond::Ne, al),                                    [0x0f, 0x95, 0xc0]);
+    assert_eq!(setcc!(Cond::Be, al),                                    [0x0f, 0x96, 0xc0]);
+    assert_eq!(setcc!(Cond::A, al),                                     [0x0f, 0x97, 0xc0]);
+    assert_eq!(setcc!(Cond::S, al),                                     [0x0f, 0x98, 0xc0]);
+    assert_eq!(setcc!(Cond::Ns, al),                                    [0x0f, 0x99, 0xc0]);
+    assert_eq!(setcc!(Cond::P, al),                                     [0x0f, 0x9a, 0xc0]);
+    assert_eq!(setcc!(Cond::Np, al),                                    [0x0f, 0x9b, 0xc0]);
+    assert_eq!(setcc!(Cond::L, al),                                     [0x0f, 0x9c, 0xc0]);
+    assert_eq!(setcc!(Cond::Ge, al),                                    [0x0f, 0x9d, 0xc0]);
+    assert_eq!(setcc!(Cond::Le, al),                                    [0x0f, 0x9e, 0xc0]);
+    assert_eq!(setcc!(Cond::G, al),                                     [0x0f, 0x9f, 0xc0]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn setcc_forms() {
+    assert_eq!(setcc!(Cond::E, dil),                                    [0x40, 0x0f, 0x94, 0xc7]);
+    assert_eq!(setcc!(Cond::B, r9l),                                    [0x41, 0x0f, 0x92, 0xc1]);
+    assert_eq!(setcc!(Cond::Ne, ah),                                    [0x0f, 0x95, 0xc4]);
+    assert_eq!(setcc!(Cond::L, Mem8::indirect(rdi)),                    [0x0f, 0x9c, 0x07]);
+    assert_eq!(setcc!(Cond::G, Mem8::indirect_disp(r8, 0x10)),          [0x41, 0x0f, 0x9f, 0x80, 0x10, 0x00, 0x00, 0x00]);
+    assert_eq!(setcc!(Cond::O, Mem8::indirect_base_index(rax, r11)),    [0x42, 0x0f, 0x90, 0x04, 0x18]);
+}
@@ -0,0 +1,130 @@
+use juicebox_asm::insn::{Sar, Shl, Shr};
+use juicebox_asm::{Asm, Imm8, Mem16, Mem32, Mem64, Mem8, Reg16::*, Reg32::*, Reg64::*, Reg8::*};
+
+macro_rules! shift {
+    ($insn:ident, $op1:expr, $op2:expr) => {{
+        let mut asm = Asm::new();
+        asm.$insn($op1, $op2);
+        asm.into_code()
+    }};
+}
+
+#[rustfmt::skip]
+#[test]
+fn shl() {
+    // reg imm.
+    assert_eq!(shift!(shl, rcx, Imm8::from(3u8)),                   [0x48, 0xc1, 0xe1, 0x03]);
+    assert_eq!(shift!(shl, r11, Imm8::from(3u8)),                   [0x49, 0xc1, 0xe3, 0x03]);
+    assert_eq!(shift!(shl, ecx, Imm8::from(3u8)),                   [0xc1, 0xe1, 0x03]);
+    assert_eq!(shift!(shl, r11d, Imm8::from(3u8)),                  [0x41, 0xc1, 0xe3, 0x03]);
+    assert_eq!(shift!(shl, cx, Imm8::from(3u8)),                    [0x66, 0xc1, 0xe1, 0x03]);
+    assert_eq!(shift!(shl, r11w, Imm8::from(3u8)),                  [0x66, 0x41, 0xc1, 0xe3, 0x03]);
+    assert_eq!(shift!(shl, cl, Imm8::from(3u8)),                    [0xc0, 0xe1, 0x03]);
+    assert_eq!(shift!(shl, dil, Imm8::from(3u8)),                   [0x40, 0xc0, 0xe7, 0x03]);
+    assert_eq!(shift!(shl, r11l, Imm8::from(3u8)),                  [0x41, 0xc0, 0xe3, 0x03]);
+
+    // mem imm.
+    assert_eq!(shift!(shl, Mem64::indirect(rax), Imm8::from(3u8)),  [0x48, 0xc1, 0x20, 0x03]);
+    assert_eq!(shift!(shl, Mem32::indirect_disp(r11, 0x10), Imm8::from(3u8)), [0x41, 0xc1, 0xa3, 0x10, 0x00, 0x00, 0x00, 0x03]);
+    assert_eq!(shift!(shl, Mem16::indirect_base_index(rdi, r9), Imm8::from(3u8)), [0x66, 0x42, 0xc1, 0x24, 0x0f, 0x03]);
+    assert_eq!(shift!(shl, Mem8::indirect_disp(rbp, -8), Imm8::from(3u8)), [0xc0, 0xa5, 0xf8, 0xff, 0xff, 0xff, 0x03]);
+
+    // reg cl.
+    assert_eq!(shift!(shl, rcx, cl),                                [0x48, 0xd3, 0xe1]);
+    assert_eq!(shift!(shl, r11, cl),                                [0x49, 0xd3, 0xe3]);
+    assert_eq!(shift!(shl, ecx, cl),                                [0xd3, 0xe1]);
+    assert_eq!(shift!(shl, r11d, cl),                               [0x41, 0xd3, 0xe3]);
+    assert_eq!(shift!(shl, cx, cl),                                 [0x66, 0xd3, 0xe1]);
+    assert_eq!(shift!(shl, r11w, cl),                               [0x66, 0x41, 0xd3, 0xe3]);
+    assert_eq!(shift!(shl, cl, cl),                                 [0xd2, 0xe1]);
+    assert_eq!(shift!(shl, dil, cl),                                [0x40, 0xd2, 0xe7]);
+    assert_eq!(shift!(shl, r11l, cl),                               [0x41, 0xd2, 0xe3]);
+
+    // mem cl.
+    assert_eq!(shift!(shl, Mem64::indirect(rax), cl),               [0x48, 0xd3, 0x20]);
+    assert_eq!(shift!(shl, Mem32::indirect_disp(r11, 0x10), cl),    [0x41, 0xd3, 0xa3, 0x10, 0x00, 0x00, 0x00]);
+    assert_eq!(shift!(shl, Mem16::indirect_base_index(rdi, r9), cl), [0x66, 0x42, 0xd3, 0x24, 0x0f]);
+    assert_eq!(shift!(shl, Mem8::indirect_disp(rbp, -8), cl),       [0xd2, 0xa5, 0xf8, 0xff, 0xff, 0xff]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn shr() {
+    // reg imm.
+    assert_eq!(shift!(shr, rcx, Imm8::from(3u8)),                   [0x48, 0xc1, 0xe9, 0x03]);
+    assert_eq!(shift!(shr, r11, Imm8::from(3u8)),                   [0x49, 0xc1, 0xeb, 0x03]);
+    assert_eq!(shift!(shr, ecx, Imm8::from(3u8)),                   [0xc1, 0xe9, 0x03]);
+    assert_eq!(shift!(shr, r11d, Imm8::from(3u8)),                  [0x41, 0xc1, 0xeb, 0x03]);
+    assert_eq!(shift!(shr, cx, Imm8::from(3u8)),                    [0x66, 0xc1, 0xe9, 0x03]);
+    assert_eq!(shift!(shr, r11w, Imm8::from(3u8)),                  [0x66, 0x41, 0xc1, 0xeb, 0x03]);
+    assert_eq!(shift!(shr, cl, Imm8::from(3u8)),                    [0xc0, 0xe9, 0x03]);
+    assert_eq!(shift!(shr, dil, Imm8::from(3u8)),                   [0x40, 0xc0, 0xef, 0x03]);
+    assert_eq!(shift!(shr, r11l, Imm8::from(3u8)),                  [0x41, 0xc0, 0xeb, 0x03]);
+
+    // mem imm.
+    assert_eq!(shift!(shr, Mem64::indirect(rax), Imm8::from(3u8)),  [0x48, 0xc1, 0x28, 0x03]);
+    assert_eq!(shift!(shr, Mem32::indirect_disp(r11, 0x10), Imm8::from(3u8)), [0x41, 0xc1, 0xab, 0x10, 0x00, 0x00, 0x00, 0x03]);
+    assert_eq!(shift!(shr, Mem16::indirect_base_index(rdi, r9), Imm8::from(3u8)), [0x66, 0x42, 0xc1, 0x2c, 0x0f, 0x03]);
+    assert_eq!(shift!(shr, Mem8::indirect_disp(rbp, -8), Imm8::from(3u8)), [0xc0, 0xad, 0xf8, 0xff, 0xff, 0xff, 0x03]);
+
+    // reg cl.
+    assert_eq!(shift!(shr, rcx, cl),                                [0x48, 0xd3, 0xe9]);
+    assert_eq!(shift!(shr, r11, cl),                                [0x49, 0xd3, 0xeb]);
+    assert_eq!(shift!(shr, ecx, cl),                                [0xd3, 0xe9]);
+    assert_eq!(shift!(shr, r11d, cl),                               [0x41, 0xd3, 0xeb]);
+    assert_eq!(shift!(shr, cx, cl),                                 [0x66, 0xd3, 0xe9]);
+    assert_eq!(shift!(shr, r11w, cl),                               [0x66, 0x41, 0xd3, 0xeb]);
+    assert_eq!(shift!(shr, cl, cl),                                 [0xd2, 0xe9]);
+    assert_eq!(shift!(shr, dil, cl),                                [0x40, 0xd2, 0xef]);
+    assert_eq!(shift!(shr, r11l, cl),                               [0x41, 0xd2, 0xeb]);
+
+    // mem cl.
+    assert_eq!(shift!(shr, Mem64::indirect(rax), cl),               [0x48, 0xd3, 0x28]);
+    assert_eq!(shift!(shr, Mem32::indirect_disp(r11, 0x10), cl),    [0x41, 0xd3, 0xab, 0x10, 0x00, 0x00, 0x00]);
+    assert_eq!(shift!(shr, Mem16::indirect_base_index(rdi, r9), cl), [0x66, 0x42, 0xd3, 0x2c, 0x0f]);
+    assert_eq!(shift!(shr, Mem8::indirect_disp(rbp, -8), cl),       [0xd2, 0xad, 0xf8, 0xff, 0xff, 0xff]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn sar() {
+    // reg imm.
+    assert_eq!(shift!(sar, rcx, Imm8::from(3u8)),                   [0x48, 0xc1, 0xf9, 0x03]);
+    assert_eq!(shift!(sar, r11, Imm8::from(3u8)),                   [0x49, 0xc1, 0xfb, 0x03]);
+    assert_eq!(shift!(sar, ecx, Imm8::from(3u8)),                   [0xc1, 0xf9, 0x03]);
+    assert_eq!(shift!(sar, r11d, Imm8::from(3u8)),                  [0x41, 0xc1, 0xfb, 0x03]);
+    assert_eq!(shift!(sar, cx, Imm8::from(3u8)),                    [0x66, 0xc1, 0xf9, 0x03]);
+    assert_eq!(shift!(sar, r11w, Imm8::from(3u8)),                  [0x66, 0x41, 0xc1, 0xfb, 0x03]);
+    assert_eq!(shift!(sar, cl, Imm8::from(3u8)),                    [0xc0, 0xf9, 0x03]);
+    assert_eq!(shift!(sar, dil, Imm8::from(3u8)),                   [0x40, 0xc0, 0xff, 0x03]);
+    assert_eq!(shift!(sar, r11l, Imm8::from(3u8)),                  [0x41, 0xc0, 0xfb, 0x03]);
+
+    // mem imm.
+    assert_eq!(shift!(sar, Mem64::indirect(rax), Imm8::from(3u8)),  [0x48, 0xc1, 0x38, 0x03]);
+    assert_eq!(shift!(sar, Mem32::indirect_disp(r11, 0x10), Imm8::from(3u8)), [0x41, 0xc1, 0xbb, 0x10, 0x00, 0x00, 0x00, 0x03]);
+    assert_eq!(shift!(sar, Mem16::indirect_base_index(rdi, r9), Imm8::from(3u8)), [0x66, 0x42, 0xc1, 0x3c, 0x0f, 0x03]);
+    assert_eq!(shift!(sar, Mem8::indirect_disp(rbp, -8), Imm8::from(3u8)), [0xc0, 0xbd, 0xf8, 0xff, 0xff, 0xff, 0x03]);
+
+    // reg cl.
+    assert_eq!(shift!(sar, rcx, cl),                                [0x48, 0xd3, 0xf9]);
+    assert_eq!(shift!(sar, r11, cl),                                [0x49, 0xd3, 0xfb]);
+    assert_eq!(shift!(sar, ecx, cl),                                [0xd3, 0xf9]);
+    assert_eq!(shift!(sar, r11d, cl),                               [0x41, 0xd3, 0xfb]);
+    assert_eq!(shift!(sar, cx, cl),                                 [0x66, 0xd3, 0xf9]);
+    assert_eq!(shift!(sar, r11w, cl),                               [0x66, 0x41, 0xd3, 0xfb]);
+    assert_eq!(shift!(sar, cl, cl),                                 [0xd2, 0xf9]);
+    assert_eq!(shift!(sar, dil, cl),                                [0x40, 0xd2, 0xff]);
+    assert_eq!(shift!(sar, r11l, cl),                               [0x41, 0xd2, 0xfb]);
+
+    // mem cl.
+    assert_eq!(shift!(sar, Mem64::indirect(rax), cl),               [0x48, 0xd3, 0x38]);
+    assert_eq!(shift!(sar, Mem32::indirect_disp(r11, 0x10), cl),    [0x41, 0xd3, 0xbb, 0x10, 0x00, 0x00, 0x00]);
+    assert_eq!(shift!(sar, Mem16::indirect_base_index(rdi, r9), cl), [0x66, 0x42, 0xd3, 0x3c, 0x0f]);
+    assert_eq!(shift!(sar, Mem8::indirect_disp(rbp, -8), cl),       [0xd2, 0xbd, 0xf8, 0xff, 0xff, 0xff]);
+}
+
+#[test]
+#[should_panic]
+fn shift_count_not_cl() {
+    shift!(shl, rax, dl);
+}
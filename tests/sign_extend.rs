@@ -0,0 +1,36 @@
+use juicebox_asm::insn::{Idiv, Mov};
+use juicebox_asm::{Asm, Reg64::*, Runtime};
+
+macro_rules! sign_extend {
+    ($insn:ident) => {{
+        let mut asm = Asm::new();
+        asm.$insn();
+        asm.into_code()
+    }};
+}
+
+#[rustfmt::skip]
+#[test]
+fn sign_extend() {
+    assert_eq!(sign_extend!(cbw),  [0x66, 0x98]);
+    assert_eq!(sign_extend!(cwde), [0x98]);
+    assert_eq!(sign_extend!(cdqe), [0x48, 0x98]);
+    assert_eq!(sign_extend!(cwd),  [0x66, 0x99]);
+    assert_eq!(sign_extend!(cdq),  [0x99]);
+    assert_eq!(sign_extend!(cqo),  [0x48, 0x99]);
+}
+
+#[test]
+fn cqo_idiv() {
+    // fn(a: i64, b: i64) -> i64 { a / b }
+    let mut asm = Asm::new();
+    asm.mov(rax, rdi);
+    asm.cqo();
+    asm.idiv(rsi);
+    asm.ret();
+
+    let mut rt = Runtime::new();
+    let div = unsafe { rt.add_code::<extern "C" fn(i64, i64) -> i64>(asm.into_code()) };
+    assert_eq!(div(-7, 2), -3);
+    assert_eq!(div(42, -6), -7);
+}
@@ -0,0 +1,50 @@
+mod common;
+
+use common::assert_snapshot;
+use juicebox_asm::insn::*;
+use juicebox_asm::{Asm, Imm64, Label, Reg64::*};
+
+#[test]
+fn fib() {
+    let mut asm = Asm::new();
+    let mut lp = Label::new();
+    let mut end = Label::new();
+
+    asm.mov(rcx, Imm64::from(0));
+    asm.mov(rdx, Imm64::from(1));
+    asm.mov(rax, Imm64::from(0));
+    asm.bind(&mut lp);
+    asm.test(rdi, rdi);
+    asm.jz(&mut end);
+    asm.mov(rcx, rax);
+    asm.add(rax, rdx);
+    asm.mov(rdx, rcx);
+    asm.dec(rdi);
+    asm.jmp(&mut lp);
+    asm.bind(&mut end);
+    asm.ret();
+
+    assert_snapshot("fib", asm);
+}
+
+#[test]
+fn memcpy() {
+    let mut asm = Asm::new();
+    asm.emit_memcpy(rdi, rsi, 15);
+    asm.emit_memcpy(rsi, rdi, 256);
+    asm.emit_memcpy(rax, rcx, rdx);
+    asm.ret();
+
+    assert_snapshot("memcpy", asm);
+}
+
+#[test]
+fn memset() {
+    let mut asm = Asm::new();
+    asm.emit_memset(rdi, 0xaa, 15);
+    asm.emit_memset(rsi, 0, 256);
+    asm.emit_memset(rcx, 0xff, rdx);
+    asm.ret();
+
+    assert_snapshot("memset", asm);
+}
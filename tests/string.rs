@@ -0,0 +1,105 @@
+use juicebox_asm::insn::{Dec, Mov, Not, Setcc, Xor};
+use juicebox_asm::{Asm, Cond, Imm64, Reg64::*, Reg8::*, Runtime};
+
+macro_rules! string {
+    ($insn:ident) => {{
+        let mut asm = Asm::new();
+        asm.$insn();
+        asm.into_code()
+    }};
+}
+
+#[rustfmt::skip]
+#[test]
+fn string() {
+    assert_eq!(string!(rep_movsb),   [0xf3, 0xa4]);
+    assert_eq!(string!(rep_movsq),   [0xf3, 0x48, 0xa5]);
+    assert_eq!(string!(rep_stosb),   [0xf3, 0xaa]);
+    assert_eq!(string!(rep_stosq),   [0xf3, 0x48, 0xab]);
+    assert_eq!(string!(repne_scasb), [0xf2, 0xae]);
+    assert_eq!(string!(cmpsb),       [0xa6]);
+    assert_eq!(string!(repe_cmpsb),  [0xf3, 0xa6]);
+}
+
+#[test]
+fn movs_stos_exec() {
+    // fn(dst: &mut [u64; 4], src: &[u64; 4]), copies the first two words and fills the other two
+    // words with the pattern.
+    let mut asm = Asm::new();
+    asm.mov(rcx, Imm64::from(2));
+    asm.rep_movsq();
+    asm.mov(rax, Imm64::from(0x0101_0101_0101_0101u64 as i64));
+    asm.mov(rcx, Imm64::from(2));
+    asm.rep_stosq();
+    asm.ret();
+
+    let mut rt = Runtime::new();
+    let f = unsafe { rt.add_code::<extern "C" fn(&mut [u64; 4], &[u64; 4])>(asm.into_code()) };
+    let mut dst = [0; 4];
+    f(&mut dst, &[1, 2, 3, 4]);
+    assert_eq!(dst, [1, 2, 0x0101_0101_0101_0101, 0x0101_0101_0101_0101]);
+
+    // fn(dst: *mut u8, src: *const u8, len: u64), copies bytes and fills the byte after the copy.
+    let mut asm = Asm::new();
+    asm.mov(rcx, rdx);
+    asm.rep_movsb();
+    asm.mov(rax, Imm64::from(0xff));
+    asm.mov(rcx, Imm64::from(1));
+    asm.rep_stosb();
+    asm.ret();
+
+    let f = unsafe { rt.add_code::<extern "C" fn(*mut u8, *const u8, u64)>(asm.into_code()) };
+    let mut dst = [0u8; 8];
+    f(dst.as_mut_ptr(), b"hello".as_ptr(), 5);
+    assert_eq!(&dst, b"hello\xff\0\0");
+}
+
+#[test]
+fn scas_exec() {
+    // fn(s: *const u8) -> u64, the length of the nul terminated string s.
+    let mut asm = Asm::new();
+    asm.xor(rax, rax);
+    asm.mov(rcx, Imm64::from(-1i64));
+    asm.repne_scasb();
+    // rcx = -1 - (len + 1)
+    asm.not(rcx);
+    asm.dec(rcx);
+    asm.mov(rax, rcx);
+    asm.ret();
+
+    let mut rt = Runtime::new();
+    let f = unsafe { rt.add_code::<extern "C" fn(*const u8) -> u64>(asm.into_code()) };
+    assert_eq!(f(c"".as_ptr().cast()), 0);
+    assert_eq!(f(c"juicebox".as_ptr().cast()), 8);
+}
+
+#[test]
+fn cmps_exec() {
+    // fn(a: *const u8, b: *const u8, len: u64) -> u64, 1 if the len bytes at a and b are equal.
+    let mut asm = Asm::new();
+    asm.mov(rcx, rdx);
+    // Set ZF for the zero length.
+    asm.xor(rax, rax);
+    asm.repe_cmpsb();
+    asm.setcc(Cond::E, al);
+    asm.ret();
+
+    let mut rt = Runtime::new();
+    let f =
+        unsafe { rt.add_code::<extern "C" fn(*const u8, *const u8, u64) -> u64>(asm.into_code()) };
+    assert_eq!(f(b"abcd".as_ptr(), b"abce".as_ptr(), 3), 1);
+    assert_eq!(f(b"abcd".as_ptr(), b"abce".as_ptr(), 4), 0);
+    assert_eq!(f(b"abcd".as_ptr(), b"xbcd".as_ptr(), 4), 0);
+    assert_eq!(f(b"a".as_ptr(), b"b".as_ptr(), 0), 1);
+
+    // fn(a: *const u8, b: *const u8) -> u64, 1 if the first bytes are equal.
+    let mut asm = Asm::new();
+    asm.xor(rax, rax);
+    asm.cmpsb();
+    asm.setcc(Cond::E, al);
+    asm.ret();
+
+    let f = unsafe { rt.add_code::<extern "C" fn(*const u8, *const u8) -> u64>(asm.into_code()) };
+    assert_eq!(f(b"a".as_ptr(), b"a".as_ptr()), 1);
+    assert_eq!(f(b"a".as_ptr(), b"b".as_ptr()), 0);
+}
@@ -0,0 +1,184 @@
+use juicebox_asm::insn::{Mov, Sub};
+use juicebox_asm::{
+    Asm, Imm16, Imm32, Imm8, Mem16, Mem32, Mem64, Mem8, Reg16::*, Reg32::*, Reg64::*, Reg8::*,
+    Runtime,
+};
+
+macro_rules! sub {
+    ($op1:expr, $op2:expr) => {{
+        let mut asm = Asm::new();
+        asm.sub($op1, $op2);
+        asm.into_code()
+    }};
+}
+
+#[rustfmt::skip]
+#[test]
+fn sub_rr() {
+    // 64bit.
+    assert_eq!(sub!(rcx, rdx),                                   [0x48, 0x29, 0xd1]);
+    assert_eq!(sub!(r11, r12),                                   [0x4d, 0x29, 0xe3]);
+
+    // 32bit.
+    assert_eq!(sub!(ecx, edx),                                   [0x29, 0xd1]);
+    assert_eq!(sub!(r11d, r12d),                                 [0x45, 0x29, 0xe3]);
+
+    // 16bit.
+    assert_eq!(sub!(cx, dx),                                     [0x66, 0x29, 0xd1]);
+    assert_eq!(sub!(r11w, r12w),                                 [0x66, 0x45, 0x29, 0xe3]);
+
+    // 8bit.
+    assert_eq!(sub!(cl, dl),                                     [0x28, 0xd1]);
+    assert_eq!(sub!(dil, sil),                                   [0x40, 0x28, 0xf7]);
+    assert_eq!(sub!(r11l, r12l),                                 [0x45, 0x28, 0xe3]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn sub_ri() {
+    // 64bit.
+    assert_eq!(sub!(rcx, Imm8::from(0x11u8)),                    [0x48, 0x83, 0xe9, 0x11]);
+    assert_eq!(sub!(r11, Imm8::from(-1i8)),                      [0x49, 0x83, 0xeb, 0xff]);
+    assert_eq!(sub!(rcx, Imm32::from(0x11223344u32)),            [0x48, 0x81, 0xe9, 0x44, 0x33, 0x22, 0x11]);
+
+    // 32bit.
+    assert_eq!(sub!(ecx, Imm8::from(0x11u8)),                    [0x83, 0xe9, 0x11]);
+    assert_eq!(sub!(r11d, Imm32::from(0x11223344u32)),           [0x41, 0x81, 0xeb, 0x44, 0x33, 0x22, 0x11]);
+
+    // 16bit.
+    assert_eq!(sub!(cx, Imm8::from(0x11u8)),                     [0x66, 0x83, 0xe9, 0x11]);
+    assert_eq!(sub!(r11w, Imm16::from(0x1122u16)),               [0x66, 0x41, 0x81, 0xeb, 0x22, 0x11]);
+
+    // 8bit.
+    assert_eq!(sub!(cl, Imm8::from(0x11u8)),                     [0x80, 0xe9, 0x11]);
+    assert_eq!(sub!(dil, Imm8::from(0x11u8)),                    [0x40, 0x80, 0xef, 0x11]);
+    assert_eq!(sub!(r11l, Imm8::from(0x11u8)),                   [0x41, 0x80, 0xeb, 0x11]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn sub_rm() {
+    // 64bit.
+    assert_eq!(sub!(rdx, Mem64::indirect(rax)),                  [0x48, 0x2b, 0x10]);
+    assert_eq!(sub!(r12, Mem64::indirect_disp(r11, 0x10)),       [0x4d, 0x2b, 0xa3, 0x10, 0x00, 0x00, 0x00]);
+
+    // 32bit.
+    assert_eq!(sub!(edx, Mem32::indirect(rax)),                  [0x2b, 0x10]);
+    assert_eq!(sub!(r12d, Mem32::indirect_base_index(rdi, r9)),  [0x46, 0x2b, 0x24, 0x0f]);
+
+    // 16bit.
+    assert_eq!(sub!(dx, Mem16::indirect(rax)),                   [0x66, 0x2b, 0x10]);
+
+    // 8bit.
+    assert_eq!(sub!(dl, Mem8::indirect(rax)),                    [0x2a, 0x10]);
+    assert_eq!(sub!(sil, Mem8::indirect_disp(r11, 0x10)),        [0x41, 0x2a, 0xb3, 0x10, 0x00, 0x00, 0x00]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn sub_mr() {
+    // 64bit.
+    assert_eq!(sub!(Mem64::indirect(rax), rdx),                  [0x48, 0x29, 0x10]);
+    assert_eq!(sub!(Mem64::indirect_disp(r11, 0x10), r12),       [0x4d, 0x29, 0xa3, 0x10, 0x00, 0x00, 0x00]);
+
+    // 32bit.
+    assert_eq!(sub!(Mem32::indirect(rax), edx),                  [0x29, 0x10]);
+
+    // 16bit.
+    assert_eq!(sub!(Mem16::indirect(rax), dx),                   [0x66, 0x29, 0x10]);
+
+    // 8bit.
+    assert_eq!(sub!(Mem8::indirect(rax), dl),                    [0x28, 0x10]);
+    assert_eq!(sub!(Mem8::indirect_base_index(rdi, r9), r12l),   [0x46, 0x28, 0x24, 0x0f]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn sub_mi() {
+    // 64bit.
+    assert_eq!(sub!(Mem64::indirect(rax), Imm8::from(0x11u8)),   [0x48, 0x83, 0x28, 0x11]);
+    assert_eq!(sub!(Mem64::indirect(rax), Imm32::from(0x11223344u32)), [0x48, 0x81, 0x28, 0x44, 0x33, 0x22, 0x11]);
+
+    // 32bit.
+    assert_eq!(sub!(Mem32::indirect_disp(r11, 0x10), Imm8::from(0x11u8)), [0x41, 0x83, 0xab, 0x10, 0x00, 0x00, 0x00, 0x11]);
+    assert_eq!(sub!(Mem32::indirect_base_index(rdi, r9), Imm32::from(0x11223344u32)), [0x42, 0x81, 0x2c, 0x0f, 0x44, 0x33, 0x22, 0x11]);
+
+    // 16bit.
+    assert_eq!(sub!(Mem16::indirect(rax), Imm8::from(0x11u8)),   [0x66, 0x83, 0x28, 0x11]);
+    assert_eq!(sub!(Mem16::indirect(rax), Imm16::from(0x1122u16)), [0x66, 0x81, 0x28, 0x22, 0x11]);
+
+    // 8bit.
+    assert_eq!(sub!(Mem8::indirect(rax), Imm8::from(0x11u8)),    [0x80, 0x28, 0x11]);
+}
+
+#[test]
+fn sub_exec() {
+    let mut rt = Runtime::new();
+
+    // Operand order: sub(a, b) = a - b.
+    let sub = {
+        let mut asm = Asm::new();
+        asm.mov(rax, rdi);
+        asm.sub(rax, rsi);
+        asm.ret();
+        unsafe { rt.add_code::<extern "C" fn(i64, i64) -> i64>(asm.into_code()) }
+    };
+    assert_eq!(sub(5, 3), 2);
+    assert_eq!(sub(3, 5), -2);
+
+    // Sign extended imm8: sub_minus_one(a) = a - (-1).
+    let sub_minus_one = {
+        let mut asm = Asm::new();
+        asm.mov(rax, rdi);
+        asm.sub(rax, Imm8::from(-1i8));
+        asm.ret();
+        unsafe { rt.add_code::<extern "C" fn(i64) -> i64>(asm.into_code()) }
+    };
+    assert_eq!(sub_minus_one(41), 42);
+
+    // Memory operand: *p -= b.
+    let sub_mem = {
+        let mut asm = Asm::new();
+        asm.sub(Mem32::indirect(rdi), esi);
+        asm.ret();
+        unsafe { rt.add_code::<extern "C" fn(*mut i32, i32)>(asm.into_code()) }
+    };
+    let mut val = 10;
+    sub_mem(&mut val, 3);
+    assert_eq!(val, 7);
+}
+
+#[rustfmt::skip]
+#[test]
+fn sub_high8() {
+    // Without a REX byte the register codes 4-7 encode the high byte registers.
+    assert_eq!(sub!(ah, cl),                                     [0x28, 0xcc]);
+    assert_eq!(sub!(bl, dh),                                     [0x28, 0xf3]);
+    assert_eq!(sub!(ch, Imm8::from(0x11u8)),                     [0x80, 0xed, 0x11]);
+    assert_eq!(sub!(ch, Mem8::indirect(rdi)),                    [0x2a, 0x2f]);
+    assert_eq!(sub!(Mem8::indirect(rax), bh),                    [0x28, 0x38]);
+}
+
+#[test]
+#[should_panic = "High byte register can not be encoded with a REX prefix"]
+fn sub_high8_rex_rr() {
+    sub!(ah, r9l);
+}
+
+#[test]
+#[should_panic = "High byte register can not be encoded with a REX prefix"]
+fn sub_high8_rex_rr_low() {
+    sub!(bh, sil);
+}
+
+#[test]
+#[should_panic = "High byte register can not be encoded with a REX prefix"]
+fn sub_high8_rex_rm() {
+    sub!(ah, Mem8::indirect(r12));
+}
+
+#[test]
+#[should_panic = "High byte register can not be encoded with a REX prefix"]
+fn sub_high8_rex_mr() {
+    sub!(Mem8::indirect(r12), ah);
+}
@@ -0,0 +1,124 @@
+use juicebox_asm::insn::Test;
+use juicebox_asm::{
+    Asm, Imm16, Imm32, Imm8, Mem16, Mem32, Mem64, Mem8, Reg16::*, Reg32::*, Reg64::*, Reg8::*,
+};
+
+macro_rules! test {
+    ($op1:expr, $op2:expr) => {{
+        let mut asm = Asm::new();
+        asm.test($op1, $op2);
+        asm.into_code()
+    }};
+}
+
+#[rustfmt::skip]
+#[test]
+fn test_rr() {
+    // 64bit.
+    assert_eq!(test!(rcx, rdx),                                  [0x48, 0x85, 0xd1]);
+    assert_eq!(test!(r11, r12),                                  [0x4d, 0x85, 0xe3]);
+
+    // 32bit.
+    assert_eq!(test!(ecx, edx),                                  [0x85, 0xd1]);
+    assert_eq!(test!(r11d, r12d),                                [0x45, 0x85, 0xe3]);
+
+    // 16bit.
+    assert_eq!(test!(cx, dx),                                    [0x66, 0x85, 0xd1]);
+    assert_eq!(test!(r11w, r12w),                                [0x66, 0x45, 0x85, 0xe3]);
+
+    // 8bit.
+    assert_eq!(test!(cl, dl),                                    [0x84, 0xd1]);
+    assert_eq!(test!(dil, sil),                                  [0x40, 0x84, 0xf7]);
+    assert_eq!(test!(r11l, r12l),                                [0x45, 0x84, 0xe3]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn test_ri() {
+    // 64bit.
+    assert_eq!(test!(rcx, Imm32::from(0x11223344u32)),           [0x48, 0xf7, 0xc1, 0x44, 0x33, 0x22, 0x11]);
+    assert_eq!(test!(r11, Imm32::from(0x11223344u32)),           [0x49, 0xf7, 0xc3, 0x44, 0x33, 0x22, 0x11]);
+
+    // 32bit.
+    assert_eq!(test!(ecx, Imm32::from(0x11223344u32)),           [0xf7, 0xc1, 0x44, 0x33, 0x22, 0x11]);
+    assert_eq!(test!(r11d, Imm32::from(0x11223344u32)),          [0x41, 0xf7, 0xc3, 0x44, 0x33, 0x22, 0x11]);
+
+    // 16bit.
+    assert_eq!(test!(cx, Imm16::from(0x1122u16)),                [0x66, 0xf7, 0xc1, 0x22, 0x11]);
+    assert_eq!(test!(r11w, Imm16::from(0x1122u16)),              [0x66, 0x41, 0xf7, 0xc3, 0x22, 0x11]);
+
+    // 8bit.
+    assert_eq!(test!(cl, Imm8::from(0x11u8)),                    [0xf6, 0xc1, 0x11]);
+    assert_eq!(test!(dil, Imm8::from(0x11u8)),                   [0x40, 0xf6, 0xc7, 0x11]);
+    assert_eq!(test!(r11l, Imm8::from(0x11u8)),                  [0x41, 0xf6, 0xc3, 0x11]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn test_mr() {
+    // 64bit.
+    assert_eq!(test!(Mem64::indirect(rax), rdx),                 [0x48, 0x85, 0x10]);
+    assert_eq!(test!(Mem64::indirect_disp(r11, 0x10), r12),      [0x4d, 0x85, 0xa3, 0x10, 0x00, 0x00, 0x00]);
+
+    // 32bit.
+    assert_eq!(test!(Mem32::indirect(rax), edx),                 [0x85, 0x10]);
+    assert_eq!(test!(Mem32::indirect_disp(r11, 0x10), r12d),     [0x45, 0x85, 0xa3, 0x10, 0x00, 0x00, 0x00]);
+
+    // 16bit.
+    assert_eq!(test!(Mem16::indirect(rax), dx),                  [0x66, 0x85, 0x10]);
+    assert_eq!(test!(Mem16::indirect_disp(r11, 0x10), r12w),     [0x66, 0x45, 0x85, 0xa3, 0x10, 0x00, 0x00, 0x00]);
+
+    // 8bit.
+    assert_eq!(test!(Mem8::indirect(rax), dl),                   [0x84, 0x10]);
+    assert_eq!(test!(Mem8::indirect_disp(r11, 0x10), sil),       [0x41, 0x84, 0xb3, 0x10, 0x00, 0x00, 0x00]);
+    assert_eq!(test!(Mem8::indirect_base_index(rdi, r9), r12l),  [0x46, 0x84, 0x24, 0x0f]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn test_mi() {
+    // 64bit.
+    assert_eq!(test!(Mem64::indirect(rax), Imm32::from(0x11223344u32)), [0x48, 0xf7, 0x00, 0x44, 0x33, 0x22, 0x11]);
+    assert_eq!(test!(Mem64::indirect_disp(r11, 0x10), Imm32::from(0x11223344u32)), [0x49, 0xf7, 0x83, 0x10, 0x00, 0x00, 0x00, 0x44, 0x33, 0x22, 0x11]);
+    assert_eq!(test!(Mem64::indirect_base_index(rdi, r9), Imm32::from(0x11223344u32)), [0x4a, 0xf7, 0x04, 0x0f, 0x44, 0x33, 0x22, 0x11]);
+
+    // 32bit.
+    assert_eq!(test!(Mem32::indirect(rax), Imm32::from(0x11223344u32)), [0xf7, 0x00, 0x44, 0x33, 0x22, 0x11]);
+    assert_eq!(test!(Mem32::indirect_disp(r11, 0x10), Imm32::from(0x11223344u32)), [0x41, 0xf7, 0x83, 0x10, 0x00, 0x00, 0x00, 0x44, 0x33, 0x22, 0x11]);
+
+    // 16bit.
+    assert_eq!(test!(Mem16::indirect(rax), Imm16::from(0x1122u16)), [0x66, 0xf7, 0x00, 0x22, 0x11]);
+    assert_eq!(test!(Mem16::indirect_base_index(rdi, r9), Imm16::from(0x1122u16)), [0x66, 0x42, 0xf7, 0x04, 0x0f, 0x22, 0x11]);
+
+    // 8bit.
+    assert_eq!(test!(Mem8::indirect(rax), Imm8::from(0x11u8)),   [0xf6, 0x00, 0x11]);
+    assert_eq!(test!(Mem8::indirect_disp(r11, 0x10), Imm8::from(0x11u8)), [0x41, 0xf6, 0x83, 0x10, 0x00, 0x00, 0x00, 0x11]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn test_high8() {
+    // Without a REX byte the register codes 4-7 encode the high byte registers.
+    assert_eq!(test!(ah, cl),                                    [0x84, 0xcc]);
+    assert_eq!(test!(bl, dh),                                    [0x84, 0xf3]);
+    assert_eq!(test!(ch, Imm8::from(0x11u8)),                    [0xf6, 0xc5, 0x11]);
+    assert_eq!(test!(Mem8::indirect(rax), bh),                   [0x84, 0x38]);
+}
+
+#[test]
+#[should_panic = "High byte register can not be encoded with a REX prefix"]
+fn test_high8_rex_rr() {
+    test!(ah, r9l);
+}
+
+#[test]
+#[should_panic = "High byte register can not be encoded with a REX prefix"]
+fn test_high8_rex_rr_low() {
+    test!(bh, sil);
+}
+
+#[test]
+#[should_panic = "High byte register can not be encoded with a REX prefix"]
+fn test_high8_rex_mr() {
+    test!(Mem8::indirect(r12), ah);
+}
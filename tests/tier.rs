@@ -0,0 +1,75 @@
+use juicebox_asm::insn::*;
+use juicebox_asm::{Asm, Imm64, Reg64::*, Runtime};
+use std::ffi::c_void;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+extern "C" fn hot(data: *mut c_void) {
+    unsafe { *data.cast::<u32>() += 1 };
+}
+
+#[test]
+fn hot_counter_redirect() {
+    let mut hits = 0u32;
+    let mut counter = 3u64;
+
+    let mut rt = Runtime::new();
+
+    // Tier 0: fn(a, b) -> a + b
+    let mut asm = Asm::new();
+    asm.hot_counter(&mut counter, hot, (&mut hits as *mut u32).cast());
+    asm.mov(rax, rdi);
+    asm.add(rax, rsi);
+    asm.ret();
+    let t0 = unsafe { rt.add_code::<extern "C" fn(u64, u64) -> u64>(asm.into_code()) };
+
+    for n in 0..6 {
+        // Arguments must be preserved by the prologue, also when the hook is invoked.
+        assert_eq!(t0(n, 2), n + 2);
+        assert_eq!(hits, if n < 2 { 0 } else { 1 });
+    }
+
+    // Tier 1: fn(a, b) -> 42
+    let mut asm = Asm::new();
+    asm.mov(rax, Imm64::from(42));
+    asm.ret();
+    let t1 = unsafe { rt.add_code::<extern "C" fn(u64, u64) -> u64>(asm.into_code()) };
+
+    unsafe { rt.redirect(t0 as *const u8, t1 as *const u8) };
+    assert_eq!(t0(1, 2), 42);
+}
+
+extern "C" fn hot_atomic(data: *mut c_void) {
+    unsafe { &*data.cast::<AtomicU32>() }.fetch_add(1, Ordering::Relaxed);
+}
+
+#[test]
+fn hot_counter_threads() {
+    static HITS: AtomicU32 = AtomicU32::new(0);
+    // Boxed, as the address is embedded into the code shared between the threads.
+    let counter = Box::into_raw(Box::new(1000u64));
+
+    let mut rt = Runtime::new();
+    let mut asm = Asm::new();
+    asm.hot_counter(
+        counter,
+        hot_atomic,
+        (&HITS as *const AtomicU32).cast_mut().cast(),
+    );
+    asm.ret();
+    let f = unsafe { rt.add_code::<extern "C" fn()>(asm.into_code()) };
+
+    std::thread::scope(|s| {
+        for _ in 0..4 {
+            s.spawn(|| {
+                for _ in 0..500 {
+                    f();
+                }
+            });
+        }
+    });
+
+    // 2000 invocations, the counter crossed zero exactly once and no decrement was lost.
+    assert_eq!(HITS.load(Ordering::Relaxed), 1);
+    assert_eq!(unsafe { *counter }, 1000u64.wrapping_sub(2000));
+    drop(unsafe { Box::from_raw(counter) });
+}
@@ -0,0 +1,91 @@
+use juicebox_asm::insn::Xadd;
+use juicebox_asm::{Asm, Mem16, Mem32, Mem64, Mem8, Reg16::*, Reg32::*, Reg64::*, Reg8::*};
+
+macro_rules! xadd {
+    ($op1:expr, $op2:expr) => {{
+        let mut asm = Asm::new();
+        asm.xadd($op1, $op2);
+        asm.into_code()
+    }};
+}
+
+macro_rules! lock_xadd {
+    ($op1:expr, $op2:expr) => {{
+        let mut asm = Asm::new();
+        asm.lock_xadd($op1, $op2);
+        asm.into_code()
+    }};
+}
+
+#[rustfmt::skip]
+#[test]
+fn xadd() {
+    assert_eq!(xadd!(Mem64::indirect(rdi), rax),                        [0x48, 0x0f, 0xc1, 0x07]);
+    assert_eq!(xadd!(Mem64::indirect_disp(r11, 0x10), r9),              [0x4d, 0x0f, 0xc1, 0x8b, 0x10, 0x00, 0x00, 0x00]);
+    assert_eq!(xadd!(Mem32::indirect_base_index(rdi, r9), ecx),         [0x42, 0x0f, 0xc1, 0x0c, 0x0f]);
+    assert_eq!(xadd!(Mem16::indirect(rax), r12w),                       [0x66, 0x44, 0x0f, 0xc1, 0x20]);
+    assert_eq!(xadd!(Mem8::indirect(rsi), dil),                         [0x40, 0x0f, 0xc0, 0x3e]);
+    assert_eq!(xadd!(Mem8::indirect(rsi), cl),                          [0x0f, 0xc0, 0x0e]);
+}
+
+#[rustfmt::skip]
+#[test]
+fn lock_xadd() {
+    // The lock prefix is emitted before the operand size prefix.
+    assert_eq!(lock_xadd!(Mem64::indirect(rdi), rax),                   [0xf0, 0x48, 0x0f, 0xc1, 0x07]);
+    assert_eq!(lock_xadd!(Mem64::indirect_disp(r11, 0x10), r9),         [0xf0, 0x4d, 0x0f, 0xc1, 0x8b, 0x10, 0x00, 0x00, 0x00]);
+    assert_eq!(lock_xadd!(Mem32::indirect_base_index(rdi, r9), ecx),    [0xf0, 0x42, 0x0f, 0xc1, 0x0c, 0x0f]);
+    assert_eq!(lock_xadd!(Mem16::indirect(rax), r12w),                  [0xf0, 0x66, 0x44, 0x0f, 0xc1, 0x20]);
+    assert_eq!(lock_xadd!(Mem8::indirect(rsi), dil),                    [0xf0, 0x40, 0x0f, 0xc0, 0x3e]);
+    assert_eq!(lock_xadd!(Mem8::indirect(rsi), cl),                     [0xf0, 0x0f, 0xc0, 0x0e]);
+}
+
+#[test]
+fn lock_xadd_exec() {
+    use juicebox_asm::insn::Mov;
+    use juicebox_asm::Runtime;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    // fn(cnt: &AtomicU64, val: u64) -> u64 { cnt.fetch_add(val) }
+    let mut asm = Asm::new();
+    asm.lock_xadd(Mem64::indirect(rdi), rsi);
+    asm.mov(rax, rsi);
+    asm.ret();
+
+    let mut rt = Runtime::new();
+    let fetch_add =
+        unsafe { rt.add_code::<extern "C" fn(&AtomicU64, u64) -> u64>(asm.into_code()) };
+
+    let cnt = AtomicU64::new(0);
+    std::thread::scope(|s| {
+        for _ in 0..4 {
+            s.spawn(|| {
+                for _ in 0..1000 {
+                    fetch_add(&cnt, 2);
+                }
+            });
+        }
+    });
+    assert_eq!(fetch_add(&cnt, 1), 8000);
+    assert_eq!(cnt.load(Ordering::Relaxed), 8001);
+}
+
+#[rustfmt::skip]
+#[test]
+fn xadd_high8() {
+    // Without a REX byte the register codes 4-7 encode the high byte registers.
+    assert_eq!(xadd!(Mem8::indirect(rsi), ah),                          [0x0f, 0xc0, 0x26]);
+    assert_eq!(xadd!(Mem8::indirect(rax), bh),                          [0x0f, 0xc0, 0x38]);
+}
+
+#[test]
+#[should_panic = "High byte register can not be encoded with a REX prefix"]
+fn xadd_high8_rex_mr() {
+    xadd!(Mem8::indirect(r12), ah);
+}
+
+#[test]
+#[should_panic = "High byte register can not be encoded with a REX prefix"]
+fn lock_xadd_high8_rex() {
+    lock_xadd!(Mem8::indirect_base_index(rsi, r9), dh);
+}